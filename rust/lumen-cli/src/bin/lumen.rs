@@ -98,6 +98,10 @@ enum Commands {
         #[arg(long, default_value = "text")]
         output_format: String,
 
+        /// Print compile diagnostics as a JSON array on stdout
+        #[arg(long)]
+        emit_diagnostics_json: bool,
+
         /// Allow unstable features without errors
         #[arg(long)]
         allow_unstable: bool,
@@ -124,6 +128,29 @@ enum Commands {
         /// Default is 0 meaning JIT is always attempted immediately.
         #[arg(long, default_value = "0")]
         jit_threshold: u32,
+
+        /// Calls before a JIT-compiled cell is recompiled with optimisations.
+        /// Defaults to the JIT threshold, meaning cells are optimised at once.
+        #[arg(long)]
+        jit_tier_up_threshold: Option<u32>,
+
+        /// Keep every cell in the interpreter (useful for deterministic benchmarking)
+        #[arg(long)]
+        no_jit: bool,
+
+        /// Disable result caching for `@pure` cells (useful for benchmarking)
+        #[arg(long)]
+        no_memo: bool,
+
+        /// Reproducible run: seed `random`, `random_int`, `uuid` and the
+        /// `crypto.uuid` / `crypto.random_int` tools, schedule futures in
+        /// spawn order, and reject clock reads and nondeterministic tools
+        #[arg(long)]
+        deterministic: bool,
+
+        /// RNG seed for --deterministic
+        #[arg(long, default_value = "0", requires = "deterministic")]
+        seed: u64,
     },
     /// Compile a `.lm`, `.lumen`, `.lm.md`, or `.lumen.md` file to LIR JSON
     Emit {
@@ -149,6 +176,11 @@ enum Commands {
         #[command(subcommand)]
         sub: CacheCommands,
     },
+    /// Inspect durable-execution snapshots
+    Snapshot {
+        #[command(subcommand)]
+        sub: SnapshotCommands,
+    },
     /// Start an interactive REPL
     Repl,
     /// Format Lumen source files
@@ -236,6 +268,24 @@ enum BuildCommands {
         #[arg(long)]
         release: bool,
     },
+    /// Compile ahead of time to a native object file
+    Native {
+        /// Path to the source file
+        #[arg()]
+        file: PathBuf,
+        /// Output object file (default: the source name with `.o`)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Target triple, e.g. `aarch64-unknown-linux-gnu` (default: host)
+        #[arg(long)]
+        target: Option<String>,
+        /// Emit DWARF line tables so debuggers can map back to the source
+        #[arg(short = 'g', long)]
+        debug_info: bool,
+        /// Allow unstable features without errors
+        #[arg(long)]
+        allow_unstable: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -260,6 +310,8 @@ enum TraceCommands {
 enum TraceShowFormat {
     Pretty,
     Replay,
+    /// Chrome Trace Event Format JSON (chrome://tracing, Perfetto)
+    Chrome,
 }
 
 #[derive(Subcommand)]
@@ -272,6 +324,17 @@ enum CacheCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum SnapshotCommands {
+    /// Show state changes between two snapshot files
+    Diff {
+        /// Earlier snapshot (`.snap` file)
+        before: PathBuf,
+        /// Later snapshot (`.snap` file)
+        after: PathBuf,
+    },
+}
+
 /// Register all provider crates into the runtime registry.
 fn register_providers(
     registry: &mut lumen_runtime::tools::ProviderRegistry,
@@ -283,22 +346,46 @@ fn register_providers(
     {
         registry.register("fs.read", Box::new(lumen_provider_fs::FsProvider::read()));
         registry.register("fs.write", Box::new(lumen_provider_fs::FsProvider::write()));
+        registry.register(
+            "fs.append",
+            Box::new(lumen_provider_fs::FsProvider::append()),
+        );
+        registry.register(
+            "fs.read_bytes",
+            Box::new(lumen_provider_fs::FsProvider::read_bytes()),
+        );
+        registry.register(
+            "fs.write_bytes",
+            Box::new(lumen_provider_fs::FsProvider::write_bytes()),
+        );
         registry.register(
             "fs.exists",
             Box::new(lumen_provider_fs::FsProvider::exists()),
         );
+        registry.register("fs.stat", Box::new(lumen_provider_fs::FsProvider::stat()));
         registry.register("fs.list", Box::new(lumen_provider_fs::FsProvider::list()));
+        registry.register("fs.glob", Box::new(lumen_provider_fs::FsProvider::glob()));
         registry.register("fs.mkdir", Box::new(lumen_provider_fs::FsProvider::mkdir()));
         registry.register(
             "fs.remove",
             Box::new(lumen_provider_fs::FsProvider::remove()),
         );
+        registry.register("fs.copy", Box::new(lumen_provider_fs::FsProvider::copy()));
+        registry.register("fs.move", Box::new(lumen_provider_fs::FsProvider::move_()));
     }
 
     #[cfg(feature = "env")]
     {
         registry.register("env.get", Box::new(lumen_provider_env::EnvProvider::get()));
         registry.register("env.set", Box::new(lumen_provider_env::EnvProvider::set()));
+        registry.register(
+            "env.unset",
+            Box::new(lumen_provider_env::EnvProvider::unset()),
+        );
+        registry.register(
+            "env.expand",
+            Box::new(lumen_provider_env::EnvProvider::expand()),
+        );
         registry.register(
             "env.list",
             Box::new(lumen_provider_env::EnvProvider::list()),
@@ -334,6 +421,18 @@ fn register_providers(
             "crypto.sha512",
             Box::new(lumen_provider_crypto::CryptoProvider::sha512()),
         );
+        registry.register(
+            "crypto.sha3_256",
+            Box::new(lumen_provider_crypto::CryptoProvider::sha3_256()),
+        );
+        registry.register(
+            "crypto.sha3_512",
+            Box::new(lumen_provider_crypto::CryptoProvider::sha3_512()),
+        );
+        registry.register(
+            "crypto.blake3",
+            Box::new(lumen_provider_crypto::CryptoProvider::blake3()),
+        );
         registry.register(
             "crypto.md5",
             Box::new(lumen_provider_crypto::CryptoProvider::md5()),
@@ -354,10 +453,34 @@ fn register_providers(
             "crypto.random_int",
             Box::new(lumen_provider_crypto::CryptoProvider::random_int()),
         );
+        registry.register(
+            "crypto.random_bytes",
+            Box::new(lumen_provider_crypto::CryptoProvider::random_bytes()),
+        );
         registry.register(
             "crypto.hmac_sha256",
             Box::new(lumen_provider_crypto::CryptoProvider::hmac_sha256()),
         );
+        registry.register(
+            "crypto.hmac_sha256_verify",
+            Box::new(lumen_provider_crypto::CryptoProvider::hmac_sha256_verify()),
+        );
+        registry.register(
+            "crypto.argon2_hash",
+            Box::new(lumen_provider_crypto::CryptoProvider::argon2_hash()),
+        );
+        registry.register(
+            "crypto.argon2_verify",
+            Box::new(lumen_provider_crypto::CryptoProvider::argon2_verify()),
+        );
+        registry.register(
+            "crypto.aes_gcm_encrypt",
+            Box::new(lumen_provider_crypto::CryptoProvider::aes_gcm_encrypt()),
+        );
+        registry.register(
+            "crypto.aes_gcm_decrypt",
+            Box::new(lumen_provider_crypto::CryptoProvider::aes_gcm_decrypt()),
+        );
         registry.register(
             "crypto.ed25519_keygen",
             Box::new(lumen_provider_crypto::Ed25519Provider::keygen()),
@@ -390,6 +513,22 @@ fn register_providers(
             "http.delete",
             Box::new(lumen_provider_http::HttpProvider::delete()),
         );
+        registry.register(
+            "http.patch",
+            Box::new(lumen_provider_http::HttpProvider::patch()),
+        );
+        registry.register(
+            "http.head",
+            Box::new(lumen_provider_http::HttpProvider::head()),
+        );
+        registry.register(
+            "http.options",
+            Box::new(lumen_provider_http::HttpProvider::options()),
+        );
+        registry.register(
+            "http.download",
+            Box::new(lumen_provider_http::HttpProvider::download()),
+        );
     }
 
     // Register providers from config (these may override defaults or add new ones)
@@ -409,6 +548,12 @@ fn register_providers(
                 "gemini.generate",
                 Box::new(lumen_provider_gemini::GeminiProvider::generate(key.clone())),
             );
+            registry.register(
+                "gemini.generate_stream",
+                Box::new(lumen_provider_gemini::GeminiProvider::generate_stream(
+                    key.clone(),
+                )),
+            );
             registry.register(
                 "gemini.chat",
                 Box::new(lumen_provider_gemini::GeminiProvider::chat(key.clone())),
@@ -419,6 +564,49 @@ fn register_providers(
             );
         }
     }
+
+    #[cfg(feature = "s3")]
+    {
+        // Credentials come from the standard AWS_* environment variables;
+        // `[providers.config.s3]` may override the endpoint and region.
+        if let Ok(mut s3_config) = lumen_provider_s3::S3Config::from_env() {
+            if let Some(cfg) = config.providers.config.get("s3") {
+                if let Some(endpoint) = &cfg.base_url {
+                    s3_config.endpoint = endpoint.clone();
+                }
+                if let Some(region) = cfg.extra.get("region").and_then(|v| v.as_str()) {
+                    s3_config.region = region.to_string();
+                }
+            }
+            registry.register(
+                "s3.get",
+                Box::new(lumen_provider_s3::S3Provider::get(s3_config.clone())),
+            );
+            registry.register(
+                "s3.put",
+                Box::new(lumen_provider_s3::S3Provider::put(s3_config.clone())),
+            );
+            registry.register(
+                "s3.delete",
+                Box::new(lumen_provider_s3::S3Provider::delete(s3_config.clone())),
+            );
+            registry.register(
+                "s3.list",
+                Box::new(lumen_provider_s3::S3Provider::list(s3_config)),
+            );
+        }
+    }
+
+    #[cfg(feature = "email")]
+    {
+        // Default SMTP settings come from the SMTP_* environment variables;
+        // each call may pass its own `smtp` object instead.
+        let provider = match lumen_provider_email::SmtpConfig::from_env() {
+            Ok(smtp_config) => lumen_provider_email::EmailProvider::with_config(smtp_config),
+            Err(_) => lumen_provider_email::EmailProvider::send(),
+        };
+        registry.register("email.send", Box::new(provider));
+    }
 }
 
 fn main() {
@@ -450,15 +638,39 @@ fn dispatch_command(command: Commands) {
         Commands::Check {
             file,
             output_format,
+            emit_diagnostics_json,
             allow_unstable,
-        } => cmd_check(&file, &output_format, allow_unstable),
+        } => {
+            if emit_diagnostics_json {
+                cmd_check_diagnostics_json(&file, allow_unstable)
+            } else {
+                cmd_check(&file, &output_format, allow_unstable)
+            }
+        }
         Commands::Run {
             file,
             cell,
             trace_dir,
             allow_unstable,
             jit_threshold,
-        } => cmd_run(&file, &cell, trace_dir, allow_unstable, jit_threshold),
+            jit_tier_up_threshold,
+            no_jit,
+            no_memo,
+            deterministic,
+            seed,
+        } => cmd_run(
+            &file,
+            &cell,
+            trace_dir,
+            allow_unstable,
+            lumen_vm::jit_tier::JitConfig {
+                hot_threshold: jit_threshold as u64,
+                tier_up_threshold: jit_tier_up_threshold.unwrap_or(jit_threshold) as u64,
+                disable: no_jit,
+            },
+            no_memo,
+            deterministic.then_some(seed),
+        ),
         Commands::Emit {
             file,
             output,
@@ -475,6 +687,9 @@ fn dispatch_command(command: Commands) {
         Commands::Cache { sub } => match sub {
             CacheCommands::Clear { cache_dir } => cmd_cache_clear(&cache_dir),
         },
+        Commands::Snapshot { sub } => match sub {
+            SnapshotCommands::Diff { before, after } => cmd_snapshot_diff(&before, &after),
+        },
         Commands::Repl => repl::run_repl(),
         Commands::Fmt { files, check } => cmd_fmt(files, check),
         Commands::Doc {
@@ -492,6 +707,13 @@ fn dispatch_command(command: Commands) {
         Commands::Ci { path } => cmd_ci(path),
         Commands::Build { sub } => match sub {
             BuildCommands::Wasm { target, release } => cmd_build_wasm(&target, release),
+            BuildCommands::Native {
+                file,
+                output,
+                target,
+                debug_info,
+                allow_unstable,
+            } => cmd_build_native(&file, output, target.as_deref(), debug_info, allow_unstable),
         },
         Commands::Watch { path, interval } => cmd_watch(&path, interval),
         Commands::Migrate { edition, files } => cmd_migrate(&edition, &files),
@@ -863,6 +1085,22 @@ fn compile_source_file(
     source: &str,
     allow_unstable: bool,
 ) -> Result<lumen_compiler::compiler::lir::LirModule, lumen_compiler::CompileError> {
+    compile_source_file_with_warnings(path, source, allow_unstable).map(|out| out.module)
+}
+
+fn compile_source_file_with_warnings(
+    path: &Path,
+    source: &str,
+    allow_unstable: bool,
+) -> Result<lumen_compiler::CompileOutput, lumen_compiler::CompileError> {
+    compile_source_file_with_diagnostics(path, source, allow_unstable).map_err(|f| f.error)
+}
+
+fn compile_source_file_with_diagnostics(
+    path: &Path,
+    source: &str,
+    allow_unstable: bool,
+) -> Result<lumen_compiler::CompileOutput, lumen_compiler::CompileFailure> {
     let source_dir = path
         .parent()
         .unwrap_or_else(|| Path::new("."))
@@ -886,7 +1124,36 @@ fn compile_source_file(
         allow_unstable,
         ..Default::default()
     };
-    lumen_compiler::compile_with_imports_and_options(source, &resolve_import, &opts)
+    lumen_compiler::compile_with_import_sources_and_diagnostics(source, &resolve_import, &opts)
+}
+
+/// `lumen check --emit-diagnostics-json`: print the diagnostics (warnings
+/// included) as one JSON array, empty when the file is clean.
+fn cmd_check_diagnostics_json(file: &PathBuf, allow_unstable: bool) {
+    let source = read_source(file);
+    let filename = file.display().to_string();
+    let (diagnostics, failed) =
+        match compile_source_file_with_diagnostics(file, &source, allow_unstable) {
+            Ok(output) => (
+                serde_json::Value::Array(lumen_compiler::diagnostics::warnings_to_json(
+                    &output.warnings,
+                    &source,
+                    &filename,
+                )),
+                false,
+            ),
+            Err(failure) => (
+                lumen_compiler::diagnostics::failure_to_json(&failure, &source, &filename),
+                true,
+            ),
+        };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&diagnostics).unwrap_or_else(|_| "[]".to_string())
+    );
+    if failed {
+        std::process::exit(EXIT_ERROR);
+    }
 }
 
 fn cmd_check(file: &PathBuf, output_format: &str, allow_unstable: bool) {
@@ -909,8 +1176,14 @@ fn cmd_check(file: &PathBuf, output_format: &str, allow_unstable: bool) {
         ci_output::OutputFormat::Text => {
             // Original human-readable output.
             println!("{} {}", status_label("Checking"), bold(&filename));
-            match compile_source_file(file, &source, allow_unstable) {
-                Ok(_module) => {
+            match compile_source_file_with_warnings(file, &source, allow_unstable) {
+                Ok(output) => {
+                    for warning in &output.warnings {
+                        eprint!(
+                            "{}",
+                            lumen_compiler::format_warning(warning, &source, &filename)
+                        );
+                    }
                     let elapsed = start.elapsed();
                     println!(
                         "{} Finished in {:.2}s — no errors",
@@ -929,12 +1202,16 @@ fn cmd_check(file: &PathBuf, output_format: &str, allow_unstable: bool) {
             let mut report = ci_output::CheckReport::new("lumen-check");
             let file_start = std::time::Instant::now();
 
-            let result = match compile_source_file(file, &source, allow_unstable) {
-                Ok(_module) => ci_output::FileCheckResult {
+            let result = match compile_source_file_with_warnings(file, &source, allow_unstable) {
+                Ok(output) => ci_output::FileCheckResult {
                     file: filename.clone(),
                     passed: true,
                     duration_secs: file_start.elapsed().as_secs_f64(),
-                    diagnostics: vec![],
+                    diagnostics: output
+                        .warnings
+                        .iter()
+                        .map(|w| ci_output::diagnostic_from_warning(w, &source, &filename))
+                        .collect(),
                 },
                 Err(e) => {
                     let diag = ci_output::diagnostic_from_compile_error(&e, &source, &filename);
@@ -1151,7 +1428,9 @@ fn cmd_run(
     cell: &str,
     trace_dir: Option<PathBuf>,
     allow_unstable: bool,
-    jit_threshold: u32,
+    jit: lumen_vm::jit_tier::JitConfig,
+    no_memo: bool,
+    deterministic_seed: Option<u64>,
 ) {
     let source = read_source(file);
     let filename = file.display().to_string();
@@ -1198,11 +1477,16 @@ fn cmd_run(
     }
 
     println!("{} {}", status_label("Running"), cyan(cell));
-    let mut vm = lumen_vm::vm::VM::new();
     // Enable tiered JIT: with --jit-threshold=0 (default), eligible cells are
     // compiled to native code on their very first call. Use a higher value to
-    // defer compilation to only hot cells.
-    vm.enable_jit(jit_threshold as u64);
+    // defer compilation to only hot cells, or --no-jit to stay interpreted.
+    let mut vm = lumen_vm::vm::VM::with_jit_config(jit);
+    if no_memo {
+        vm.set_memoization(false);
+    }
+    if let Some(seed) = deterministic_seed {
+        vm.set_deterministic(seed);
+    }
     if let Some(run_id) = trace_run_id.as_ref() {
         vm.set_trace_id(run_id.clone());
     }
@@ -1247,7 +1531,15 @@ fn cmd_run(
             }
         }));
     }
-    vm.load(module);
+    if let Err(errors) = vm.try_load(module) {
+        let mut chain = error_chain::ErrorChain::new("module failed verification")
+            .caused_by(format!("in file '{}'", filename));
+        for error in &errors {
+            chain = chain.caused_by(error.to_string());
+        }
+        eprintln!("{}", chain.format_with_prefix(&red("✗")));
+        std::process::exit(EXIT_ERROR);
+    }
     match vm.execute(cell, vec![]) {
         Ok(result) => {
             let elapsed = start.elapsed();
@@ -1340,10 +1632,15 @@ fn cmd_trace_show(run_id: &str, trace_dir: &Path, format: TraceShowFormat, verif
     let path = trace_dir.join(format!("{}.jsonl", run_id));
     match read_trace_events(&path) {
         Ok(events) => {
-            println!("{} trace for run {}", status_label("Showing"), cyan(run_id));
+            // Chrome output must stay pure JSON so it can be redirected to a file.
+            let machine_output = format == TraceShowFormat::Chrome;
+            if !machine_output {
+                println!("{} trace for run {}", status_label("Showing"), cyan(run_id));
+            }
 
             if verify_chain {
                 match verify_trace_chain(&events) {
+                    Ok(()) if machine_output => eprintln!("{} trace chain verified", green("✓")),
                     Ok(()) => println!("{} trace chain verified", green("✓")),
                     Err(msg) => {
                         eprintln!("{} {}", red("error:"), msg);
@@ -1365,6 +1662,12 @@ fn cmd_trace_show(run_id: &str, trace_dir: &Path, format: TraceShowFormat, verif
                         println!("{}", replay_line(event));
                     }
                 }
+                TraceShowFormat::Chrome => {
+                    let doc = lumen_runtime::trace::chrome::to_chrome_trace(&events);
+                    if let Ok(json) = serde_json::to_string(&doc) {
+                        println!("{}", json);
+                    }
+                }
             }
         }
         Err(e) => {
@@ -1489,6 +1792,31 @@ fn cmd_cache_clear(cache_dir: &PathBuf) {
     }
 }
 
+fn read_snapshot_file(path: &Path) -> Result<lumen_runtime::snapshot::Snapshot, String> {
+    use lumen_runtime::snapshot::{is_sealed, Snapshot};
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let snapshot = if is_sealed(&bytes) {
+        Snapshot::unseal(&bytes)
+    } else {
+        // Legacy checkpoints carry no codec tag; try both encodings.
+        Snapshot::deserialize(&bytes).or_else(|_| Snapshot::deserialize_compressed(&bytes))
+    };
+    snapshot.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn cmd_snapshot_diff(before: &Path, after: &Path) {
+    let load = |path: &Path| {
+        read_snapshot_file(path).unwrap_or_else(|e| {
+            eprintln!("{} {}", red("error:"), e);
+            std::process::exit(EXIT_ERROR);
+        })
+    };
+    let (before, after) = (load(before), load(after));
+    let diff = lumen_runtime::snapshot::diff(&before, &after);
+    println!("{}", diff);
+}
+
 fn cmd_fmt(files: Vec<PathBuf>, check: bool) {
     if files.is_empty() {
         eprintln!("{} no files specified", red("✗ Error:"));
@@ -1538,6 +1866,84 @@ fn cmd_fmt(files: Vec<PathBuf>, check: bool) {
     }
 }
 
+#[cfg(feature = "jit")]
+fn cmd_build_native(
+    file: &PathBuf,
+    output: Option<PathBuf>,
+    target: Option<&str>,
+    debug_info: bool,
+    allow_unstable: bool,
+) {
+    // Reject an unknown target before spending time on compilation.
+    if let Some(target) = target {
+        if let Err(e) = lumen_codegen::aot::parse_target(target) {
+            eprintln!("{} {}", red("error:"), e);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+
+    let source = read_source(file);
+    let filename = file.display().to_string();
+
+    println!("{} {}", status_label("Compiling"), bold(&filename));
+    let module = match compile_source_file(file, &source, allow_unstable) {
+        Ok(m) => m,
+        Err(e) => {
+            let chain = error_chain::ErrorChain::new("compilation failed")
+                .caused_by(format!("in file '{}'", filename));
+            eprintln!("{}", chain.format_with_prefix(&red("✗")));
+            let formatted = lumen_compiler::format_error(&e, &source, &filename);
+            eprint!("{}", formatted);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+
+    let options = lumen_codegen::aot::AotOptions {
+        target_triple: target.map(str::to_string),
+        debug_info,
+        source_name: Some(filename.clone()),
+    };
+    let bytes =
+        lumen_codegen::aot::compile_object_with_options(&module, &options).unwrap_or_else(|e| {
+            let chain = error_chain::ErrorChain::new("native code generation failed")
+                .caused_by(e.to_string());
+            eprintln!("{}", chain.format_with_prefix(&red("✗")));
+            std::process::exit(EXIT_ERROR);
+        });
+
+    let out_path = output.unwrap_or_else(|| file.with_extension("o"));
+    std::fs::write(&out_path, &bytes).unwrap_or_else(|e| {
+        eprintln!(
+            "{} writing to '{}': {}",
+            red("error:"),
+            out_path.display(),
+            e
+        );
+        std::process::exit(EXIT_ERROR);
+    });
+    println!(
+        "{} {} ({})",
+        green("✓ Wrote"),
+        out_path.display(),
+        target.unwrap_or("host")
+    );
+}
+
+#[cfg(not(feature = "jit"))]
+fn cmd_build_native(
+    _file: &PathBuf,
+    _output: Option<PathBuf>,
+    _target: Option<&str>,
+    _debug_info: bool,
+    _allow_unstable: bool,
+) {
+    eprintln!(
+        "{} native builds need lumen built with the `jit` feature",
+        red("error:")
+    );
+    std::process::exit(EXIT_ERROR);
+}
+
 fn cmd_build_wasm(target: &str, release: bool) {
     // Check if wasm-pack is installed
     let wasm_pack_check = std::process::Command::new("wasm-pack")
@@ -322,6 +322,25 @@ pub fn diagnostic_from_compile_error(
     }
 }
 
+/// Like [`diagnostic_from_compile_error`], for a warning the compiler
+/// reported alongside a successful (or failed) compilation.
+pub fn diagnostic_from_warning(
+    warning: &lumen_compiler::CompileError,
+    source: &str,
+    filename: &str,
+) -> Diagnostic {
+    let (line, column) = extract_location_from_error(warning);
+
+    Diagnostic {
+        file: filename.to_string(),
+        line,
+        column,
+        severity: DiagnosticSeverity::Warning,
+        message: extract_error_summary(warning),
+        details: Some(lumen_compiler::format_warning(warning, source, filename)),
+    }
+}
+
 /// Extract a concise summary message from a compile error.
 fn extract_error_summary(error: &lumen_compiler::CompileError) -> String {
     // CompileError has a Display impl that we can use
@@ -666,6 +685,33 @@ mod tests {
         assert_eq!(diags[0]["message"], "undefined variable 'x'");
     }
 
+    #[test]
+    fn json_output_counts_compiler_warnings() {
+        let warning = lumen_compiler::CompileError::Type(vec![
+            lumen_compiler::compiler::typecheck::TypeError::UnboundedRecursion {
+                name: "spin".to_string(),
+                line: 1,
+            },
+        ]);
+        let diag = diagnostic_from_warning(&warning, "cell spin() -> Int\n", "main.lm");
+        let mut report = CheckReport::new("lumen-check");
+        report.results.push(FileCheckResult {
+            file: "main.lm".to_string(),
+            passed: true,
+            duration_secs: 0.0,
+            diagnostics: vec![diag],
+        });
+
+        let parsed: serde_json::Value =
+            serde_json::from_str(&render_json(&report)).expect("should be valid JSON");
+        assert_eq!(parsed["warnings"], 1);
+        assert_eq!(parsed["errors"], 0);
+        assert_eq!(
+            parsed["results"][0]["diagnostics"][0]["severity"],
+            "warning"
+        );
+    }
+
     #[test]
     fn json_output_empty_report() {
         let report = CheckReport::new("empty");
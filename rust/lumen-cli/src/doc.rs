@@ -346,6 +346,7 @@ fn type_to_string(ty: &TypeExpr) -> String {
             format!("({})", inner)
         }
         TypeExpr::Set(inner, _) => format!("set[{}]", type_to_string(inner)),
+        TypeExpr::Array(inner, len, _) => format!("[{}; {}]", type_to_string(inner), len),
         TypeExpr::Fn(params, ret, effects, _) => {
             let param_str = params
                 .iter()
@@ -20,6 +20,16 @@ const YELLOW: &str = "\x1b[33m";
 const BOLD: &str = "\x1b[1m";
 const RESET: &str = "\x1b[0m";
 
+/// Format a source file, choosing the mode from its path: markdown-first for
+/// `.md` files (including `.lm.md`), code-first for everything else.
+pub fn format_source(content: &str, path: &str) -> String {
+    if path.ends_with(".md") {
+        format_file(content)
+    } else {
+        format_lm_source(content)
+    }
+}
+
 /// Format a complete .lm.md file
 pub fn format_file(content: &str) -> String {
     let mut output = String::new();
@@ -67,11 +77,25 @@ pub fn format_file(content: &str) -> String {
 /// - Formats code sections using the AST-based pretty printer
 /// - Maintains blank lines around markdown blocks
 /// - Keeps docstrings attached to their declarations (no added blank line)
+/// - Preserves the directive header (`@lumen 1`, `@package "name"`, ...) verbatim
 pub fn format_lm_source(content: &str) -> String {
     let lines: Vec<&str> = content.lines().collect();
     let mut output = String::new();
     let mut i = 0;
 
+    // Directive header: leading `@` lines and the blank lines among them
+    let header = lines
+        .iter()
+        .take_while(|l| l.trim().is_empty() || l.trim().starts_with('@'))
+        .count();
+    if lines[..header].iter().any(|l| !l.trim().is_empty()) {
+        while i < header {
+            output.push_str(lines[i]);
+            output.push('\n');
+            i += 1;
+        }
+    }
+
     while i < lines.len() {
         if lines[i].trim().starts_with("```") {
             // Markdown block — preserve verbatim through closing ```
@@ -535,6 +559,10 @@ impl Formatter {
                 line.push_str(&import.path.join("::"));
             }
         }
+        if let Some(alias) = &import.alias {
+            line.push_str(" as ");
+            line.push_str(alias);
+        }
 
         self.writeln(&line);
     }
@@ -1330,6 +1358,7 @@ impl Formatter {
                 result
             }
             TypeExpr::Set(inner, _) => format!("set[{}]", self.fmt_type(inner)),
+            TypeExpr::Array(inner, len, _) => format!("[{}; {}]", self.fmt_type(inner), len),
             TypeExpr::Fn(params, ret, effects, _) => {
                 let mut result = String::from("fn(");
                 for (i, param) in params.iter().enumerate() {
@@ -1489,16 +1518,7 @@ pub fn format_files(files: &[PathBuf], check_mode: bool) -> Result<(bool, usize)
         let content = std::fs::read_to_string(file)
             .map_err(|e| format!("error reading '{}': {}", file.display(), e))?;
 
-        let is_lm_md = file
-            .to_str()
-            .map(|s| s.ends_with(".lm.md"))
-            .unwrap_or(false);
-
-        let formatted = if is_lm_md {
-            format_file(&content)
-        } else {
-            format_lm_source(&content)
-        };
+        let formatted = format_source(&content, &file.to_string_lossy());
 
         if content != formatted {
             needs_formatting = true;
@@ -2004,4 +2024,23 @@ end
         assert!(output.contains("```markdown"), "info string preserved");
         assert!(output.contains("# Title"), "content preserved");
     }
+
+    #[test]
+    fn test_lm_directive_header_preserved() {
+        let input = "@lumen 1\n@package \"demo\"\n\ncell main() -> Int\n    return 0\nend\n";
+        assert_eq!(
+            format_lm_source(input),
+            "@lumen 1\n@package \"demo\"\n\ncell main() -> Int\n  return 0\nend\n"
+        );
+    }
+
+    #[test]
+    fn test_format_source_dispatches_on_extension() {
+        let md = "# Doc\n\n```lumen\ncell main() -> Int\nreturn 0\nend\n```\n";
+        assert_eq!(format_source(md, "doc.lm.md"), format_file(md));
+        assert_eq!(format_source(md, "README.md"), format_file(md));
+        let code = "cell main() -> Int\nreturn 0\nend\n";
+        assert_eq!(format_source(code, "main.lm"), format_lm_source(code));
+        assert_eq!(format_source(code, "main.lumen"), format_lm_source(code));
+    }
 }
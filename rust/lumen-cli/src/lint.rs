@@ -1,10 +1,13 @@
 //! Lumen linter — style and correctness checks beyond type checking
 //!
-//! Implements 10 lint rules:
+//! Implements 12 lint rules:
 //! - Style: unused-variable, naming-convention, empty-block, redundant-return, long-cell, missing-type-annotation
-//! - Correctness: unreachable-code, infinite-loop, unused-import, shadowed-builtin
+//! - Correctness: unreachable-code, infinite-loop, unbounded-recursion, unused-import, shadowed-builtin,
+//!   float-precision-loss
 
 use lumen_compiler::compiler::ast::*;
+use lumen_compiler::compiler::resolve;
+use lumen_compiler::compiler::typecheck::{self, recurses_on_every_path, TypeError};
 use lumen_compiler::markdown::extract::extract_blocks;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
@@ -149,6 +152,9 @@ impl Linter {
         // Check unused imports
         self.check_unused_imports(program);
 
+        // Check implicit Int -> Float widening that loses precision
+        self.check_float_widening(program);
+
         // Check items
         for item in &program.items {
             match item {
@@ -168,6 +174,9 @@ impl Linter {
         // Check for long cells
         self.check_long_cell(cell);
 
+        // Check for recursion without a base case
+        self.check_unbounded_recursion(cell);
+
         // Build variable usage map
         let mut defined_vars = HashMap::new();
         let mut used_vars = HashSet::new();
@@ -392,6 +401,32 @@ impl Linter {
         }
     }
 
+    /// Integers the type checker reports as losing precision when widened
+    /// to `Float`.
+    fn check_float_widening(&mut self, program: &Program) {
+        let (symbols, _) = resolve::resolve_partial(program);
+        let (_, warnings) = typecheck::typecheck_with_warnings(program, &symbols);
+        for warning in warnings {
+            if let TypeError::PrecisionLoss { value, line } = warning {
+                let literal = value.trim_start_matches('-').to_string();
+                self.warn(LintWarning::new(
+                    "float-precision-loss",
+                    Severity::Warning,
+                    format!(
+                        "integer {} exceeds 2^53 and loses precision when implicitly widened to Float",
+                        value
+                    ),
+                    &self.filename,
+                    line,
+                    Some(format!(
+                        "write it as a Float literal ({}.0) if rounding is intended, or keep it an Int",
+                        literal
+                    )),
+                ));
+            }
+        }
+    }
+
     fn check_empty_block(&mut self, block: &[Stmt], line: usize, kind: &str) {
         if block.is_empty() {
             self.warn(LintWarning::new(
@@ -465,6 +500,22 @@ impl Linter {
         false
     }
 
+    fn check_unbounded_recursion(&mut self, cell: &CellDef) {
+        if recurses_on_every_path(cell) {
+            self.warn(LintWarning::new(
+                "unbounded-recursion",
+                Severity::Warning,
+                format!(
+                    "cell '{}' recurses on every path (no reachable base case)",
+                    cell.name
+                ),
+                &self.filename,
+                cell.span.line,
+                Some("add a branch that returns without calling the cell".to_string()),
+            ));
+        }
+    }
+
     fn needs_type_annotation(&self, expr: &Expr) -> bool {
         // Only warn for complex expressions where type might be ambiguous
         matches!(
@@ -536,6 +587,10 @@ impl Linter {
         // Collect imports
         for item in &program.items {
             if let Item::Import(import) = item {
+                if import.alias.is_some() {
+                    // Names are only reachable qualified through the alias
+                    continue;
+                }
                 match &import.names {
                     ImportList::Names(names) => {
                         for name in names {
@@ -874,4 +929,84 @@ end
         let warnings = lint_file(source, "test.lm.md");
         assert!(warnings.iter().any(|w| w.rule == "infinite-loop"));
     }
+
+    #[test]
+    fn test_unbounded_recursion() {
+        let source = r#"
+```lumen
+cell countdown(n: Int) -> Int
+  print(n)
+  return countdown(n - 1)
+end
+```
+"#;
+        let warnings = lint_file(source, "test.lm.md");
+        assert!(warnings
+            .iter()
+            .any(|w| w.rule == "unbounded-recursion" && w.message.contains("'countdown'")));
+    }
+
+    #[test]
+    fn test_recursion_with_base_case() {
+        let source = r#"
+```lumen
+cell factorial(n: Int) -> Int
+  if n <= 1
+    return 1
+  end
+  n * factorial(n - 1)
+end
+```
+"#;
+        let warnings = lint_file(source, "test.lm.md");
+        assert!(!warnings.iter().any(|w| w.rule == "unbounded-recursion"));
+    }
+
+    #[test]
+    fn test_small_int_widened_to_float_is_silent() {
+        let source = r#"
+```lumen
+cell scale(x: Float) -> Float
+  x * 2.0
+end
+
+cell main() -> Float
+  let a: Float = 42
+  scale(1024) + a + 3
+end
+```
+"#;
+        let warnings = lint_file(source, "test.lm.md");
+        assert!(!warnings.iter().any(|w| w.rule == "float-precision-loss"));
+    }
+
+    #[test]
+    fn test_large_int_widened_to_float_warns() {
+        let source = r#"
+```lumen
+const BIG = 9007199254740993
+
+cell scale(x: Float) -> Float
+  x * 2.0
+end
+
+cell main() -> Float
+  let a: Float = 9007199254740993
+  let b = scale(BIG)
+  let c = 0.5 + -9007199254740993
+  a + b + c
+end
+```
+"#;
+        let warnings = lint_file(source, "test.lm.md");
+        let lines: Vec<usize> = warnings
+            .iter()
+            .filter(|w| w.rule == "float-precision-loss")
+            .map(|w| w.line)
+            .collect();
+        assert_eq!(lines, vec![8, 9, 10], "{:?}", warnings);
+        assert!(warnings
+            .iter()
+            .any(|w| w.message.contains("9007199254740993") && w.message.contains("2^53")));
+    }
 }
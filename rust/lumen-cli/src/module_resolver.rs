@@ -1,34 +1,27 @@
 //! Module resolution for Lumen imports.
 
+use lumen_compiler::ImportSource;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// File name suffixes of Lumen module sources.
+const MODULE_EXTENSIONS: [&str; 4] = [".lm", ".lumen", ".lm.md", ".lumen.md"];
+
 /// Compile a source file with import resolution.
 pub fn compile_source_file(
     path: &Path,
     source: &str,
 ) -> Result<lumen_compiler::compiler::lir::LirModule, lumen_compiler::CompileError> {
-    let source_dir = path
-        .parent()
-        .unwrap_or_else(|| Path::new("."))
-        .to_path_buf();
-    let mut resolver = ModuleResolver::new(source_dir.clone());
-
-    if let Some(project_root) = find_project_root(&source_dir) {
-        let src_dir = project_root.join("src");
-        if src_dir.is_dir() && src_dir != source_dir {
-            resolver.add_root(src_dir);
-        }
-        if project_root != source_dir {
-            resolver.add_root(project_root);
-        }
-    }
-
-    let resolver = RefCell::new(resolver);
+    let resolver = RefCell::new(ModuleResolver::for_source_file(path));
     let resolve_import = |module_path: &str| resolver.borrow_mut().resolve(module_path);
 
-    lumen_compiler::compile_with_imports(source, &resolve_import)
+    lumen_compiler::compile_with_import_sources(
+        source,
+        &resolve_import,
+        &lumen_compiler::CompileOptions::default(),
+    )
+    .map(|out| out.module)
 }
 
 /// Find project root by looking for lumen.toml.
@@ -52,11 +45,23 @@ fn find_project_root(start: &Path) -> Option<PathBuf> {
 /// - utils/math.lumen
 /// - utils/math.lm.md
 /// - utils/math.lumen.md
+///
+/// then `mod`/`main` files inside a `utils/math/` directory. A directory
+/// without either resolves to all of the module files directly inside it.
 pub struct ModuleResolver {
     /// Search roots for resolving relative imports.
     search_roots: Vec<PathBuf>,
     /// Cache of resolved module paths to source content
-    cache: HashMap<String, String>,
+    cache: HashMap<String, ImportSource>,
+}
+
+/// Where a module path resolves on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleLocation {
+    /// A single module file.
+    File(PathBuf),
+    /// A directory module: its module files, sorted by file name.
+    Directory(Vec<PathBuf>),
 }
 
 impl ModuleResolver {
@@ -67,6 +72,28 @@ impl ModuleResolver {
         }
     }
 
+    /// A resolver for imports in the source file at `path`: searches the
+    /// file's directory, then the enclosing project's `src/` and root (found
+    /// via `lumen.toml`).
+    pub fn for_source_file(path: &Path) -> Self {
+        let source_dir = path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf();
+        let mut resolver = Self::new(source_dir.clone());
+
+        if let Some(project_root) = find_project_root(&source_dir) {
+            let src_dir = project_root.join("src");
+            if src_dir.is_dir() && src_dir != source_dir {
+                resolver.add_root(src_dir);
+            }
+            if project_root != source_dir {
+                resolver.add_root(project_root);
+            }
+        }
+        resolver
+    }
+
     pub fn add_root(&mut self, root: PathBuf) {
         if !self.search_roots.contains(&root) {
             self.search_roots.push(root);
@@ -76,12 +103,29 @@ impl ModuleResolver {
     /// Resolve a module path to its source content.
     ///
     /// Module path format: "utils.math" resolves to a supported Lumen source path.
-    pub fn resolve(&mut self, module_path: &str) -> Option<String> {
+    pub fn resolve(&mut self, module_path: &str) -> Option<ImportSource> {
         // Check cache first
         if let Some(cached) = self.cache.get(module_path) {
             return Some(cached.clone());
         }
 
+        let source = match self.locate(module_path)? {
+            ModuleLocation::File(path) => ImportSource::File(std::fs::read_to_string(path).ok()?),
+            ModuleLocation::Directory(paths) => {
+                let mut files = Vec::with_capacity(paths.len());
+                for path in paths {
+                    let name = path.file_name()?.to_str()?.to_string();
+                    files.push((name, std::fs::read_to_string(&path).ok()?));
+                }
+                ImportSource::Directory(files)
+            }
+        };
+        self.cache.insert(module_path.to_string(), source.clone());
+        Some(source)
+    }
+
+    /// Find the file or directory a module path refers to, without reading it.
+    pub fn locate(&self, module_path: &str) -> Option<ModuleLocation> {
         // Convert module.path.notation to filesystem path
         let fs_path = module_path.replace('.', "/");
 
@@ -101,16 +145,36 @@ impl ModuleResolver {
                 root.join(fs_path.clone()).join("main.lumen.md"),
             ];
 
-            for path in &candidates {
-                if path.exists() {
-                    if let Ok(source) = std::fs::read_to_string(path) {
-                        self.cache.insert(module_path.to_string(), source.clone());
-                        return Some(source);
-                    }
-                }
+            if let Some(path) = candidates.into_iter().find(|path| path.is_file()) {
+                return Some(ModuleLocation::File(path));
+            }
+
+            if let Some(files) = module_dir_files(&root.join(&fs_path)) {
+                return Some(ModuleLocation::Directory(files));
             }
         }
 
         None
     }
 }
+
+/// The module files directly inside `dir`, sorted by file name. Returns
+/// `None` if `dir` is not a directory or has no module files.
+fn module_dir_files(dir: &Path) -> Option<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            if !path.is_file() || !MODULE_EXTENSIONS.iter().any(|ext| name.ends_with(ext)) {
+                return None;
+            }
+            Some(path)
+        })
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+    files.sort();
+    Some(files)
+}
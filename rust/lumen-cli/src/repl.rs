@@ -6,6 +6,7 @@ use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+use lumen_compiler::markdown::extract::extract_blocks;
 use lumen_vm::values::Value;
 use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
@@ -265,7 +266,8 @@ const TYPES: &[&str] = &[
 
 /// REPL commands for tab completion.
 const COMMANDS: &[&str] = &[
-    ":help", ":quit", ":reset", ":type", ":clear", ":history", ":load", ":env", ":time", ":doc",
+    ":help", ":quit", ":reset", ":type", ":clear", ":history", ":load", ":reload", ":env", ":time",
+    ":doc",
 ];
 
 /// Environment variable used to override REPL history location.
@@ -358,6 +360,45 @@ struct SessionState {
     definitions: Vec<String>,
     /// Map of symbol names to their definition index
     symbols: HashMap<String, usize>,
+    /// File loaded with `:load`; its items precede the session definitions.
+    loaded: Option<LoadedModule>,
+}
+
+/// A source file loaded into the session with `:load`.
+struct LoadedModule {
+    path: PathBuf,
+    /// Lumen code extracted from the file.
+    code: String,
+    /// Names of the items the file defines.
+    symbols: Vec<String>,
+}
+
+impl LoadedModule {
+    fn read(path: &Path) -> Result<Self, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let code = if path.to_string_lossy().ends_with(".md") {
+            let extracted = extract_blocks(&source);
+            let blocks: Vec<&str> = extracted
+                .code_blocks
+                .iter()
+                .map(|block| block.code.as_str())
+                .collect();
+            blocks.join("\n")
+        } else {
+            source
+        };
+        let symbols = code
+            .lines()
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .filter_map(|line| extract_symbol_name(line.strip_prefix("pub ").unwrap_or(line)))
+            .collect();
+        Ok(Self {
+            path: path.to_path_buf(),
+            code,
+            symbols,
+        })
+    }
 }
 
 impl SessionState {
@@ -373,11 +414,41 @@ impl SessionState {
     fn clear(&mut self) {
         self.definitions.clear();
         self.symbols.clear();
+        self.loaded = None;
+    }
+
+    /// Sorted names of session definitions and loaded items.
+    fn symbol_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.symbols.keys().cloned().collect();
+        if let Some(loaded) = &self.loaded {
+            names.extend(loaded.symbols.iter().cloned());
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Load `path` into the session, replacing any previously loaded file.
+    ///
+    /// The file is compiled together with the current session definitions;
+    /// if that fails the previous state is kept and the error is returned.
+    fn load(&mut self, path: &Path) -> Result<&LoadedModule, String> {
+        let module = LoadedModule::read(path)?;
+        let previous = self.loaded.replace(module);
+        if let Err(e) = lumen_compiler::compile(&self.build_source("")) {
+            self.loaded = previous;
+            return Err(e.to_string());
+        }
+        Ok(self.loaded.as_ref().expect("module was just loaded"))
     }
 
     /// Build a source file with all definitions plus the current input.
     fn build_source(&self, input: &str) -> String {
         let mut src = String::from("# repl\n\n```lumen\n");
+        if let Some(loaded) = &self.loaded {
+            src.push_str(&loaded.code);
+            src.push('\n');
+        }
         for def in &self.definitions {
             src.push_str(def);
             src.push('\n');
@@ -426,6 +497,7 @@ enum ReplCommand<'a> {
     Env,
     Type(&'a str),
     Load(&'a str),
+    Reload,
     Time(&'a str),
     Doc(&'a str),
     DocIndex,
@@ -467,6 +539,7 @@ fn parse_repl_command(line: &str) -> ParsedCommand<'_> {
             Some(path) => ParsedCommand::Command(ReplCommand::Load(path)),
             None => ParsedCommand::InvalidUsage("Usage: :load <file>"),
         },
+        ":reload" => ParsedCommand::Command(ReplCommand::Reload),
         ":time" => match arg {
             Some(expr) => ParsedCommand::Command(ReplCommand::Time(expr)),
             None => ParsedCommand::InvalidUsage("Usage: :time <expr>"),
@@ -687,7 +760,14 @@ fn handle_command<H: Helper>(
             Some(true)
         }
         ParsedCommand::Command(ReplCommand::Load(path)) => {
-            cmd_load(path);
+            cmd_load(Path::new(path), session_state);
+            Some(true)
+        }
+        ParsedCommand::Command(ReplCommand::Reload) => {
+            match session_state.loaded.as_ref().map(|m| m.path.clone()) {
+                Some(path) => cmd_load(&path, session_state),
+                None => eprintln!("{} no file loaded. Use :load <file>.", red("Error:")),
+            }
             Some(true)
         }
         ParsedCommand::Command(ReplCommand::Time(expr)) => {
@@ -772,17 +852,42 @@ fn wrap_as_source(input: &str, session_state: &SessionState) -> String {
     }
 }
 
+/// Result of evaluating one REPL input.
+#[derive(Debug)]
+enum EvalOutcome {
+    /// The input defined items; nothing was executed.
+    Defined,
+    /// The input ran and produced a value.
+    Value(Value),
+}
+
+#[derive(Debug)]
+enum EvalError {
+    Compile(String),
+    Runtime(String),
+}
+
 /// Evaluate input: compile and run, printing the result.
 fn eval_input(input: &str, session_state: &mut SessionState) {
+    match evaluate(input, session_state) {
+        Ok(EvalOutcome::Defined) => println!("{}", gray("(defined)")),
+        Ok(EvalOutcome::Value(result)) => {
+            // Don't print Null for side-effect-only statements
+            if !matches!(result, Value::Null) {
+                let type_name = value_type_name(&result);
+                println!("{} {}", result, gray(&format!(": {}", type_name)));
+            }
+        }
+        Err(EvalError::Compile(e)) => eprintln!("{} {}", red("Error:"), e),
+        Err(EvalError::Runtime(e)) => eprintln!("{} {}", red("Runtime error:"), e),
+    }
+}
+
+/// Compile and run input against the session, recording new definitions.
+fn evaluate(input: &str, session_state: &mut SessionState) -> Result<EvalOutcome, EvalError> {
     let source = wrap_as_source(input, session_state);
 
-    let module = match lumen_compiler::compile(&source) {
-        Ok(m) => m,
-        Err(e) => {
-            eprintln!("{} {}", red("Error:"), e);
-            return;
-        }
-    };
+    let module = lumen_compiler::compile(&source).map_err(|e| EvalError::Compile(e.to_string()))?;
 
     // If this is a definition or a persistent statement (like let), add to session state
     let is_definition = is_item_definition(input);
@@ -793,21 +898,20 @@ fn eval_input(input: &str, session_state: &mut SessionState) {
     }
 
     if is_definition {
-        println!("{}", gray("(defined)"));
-        return;
+        return Ok(EvalOutcome::Defined);
     }
 
-    // Find the entry cell — prefer "main" (synthesized from top-level stmts)
-    let entry = if module.cells.iter().any(|c| c.name == "main") {
-        "main".to_string()
-    } else if module.cells.iter().any(|c| c.name == "__script_main") {
+    // Find the entry cell synthesized from top-level stmts. It is named
+    // "__script_main" when a loaded file or definition already has a "main".
+    let entry = if module.cells.iter().any(|c| c.name == "__script_main") {
         "__script_main".to_string()
+    } else if module.cells.iter().any(|c| c.name == "main") {
+        "main".to_string()
     } else if module.cells.len() == 1 {
         module.cells[0].name.clone()
     } else {
         // Definition-only input (records, enums, etc.) — nothing to execute
-        println!("{}", gray("(defined)"));
-        return;
+        return Ok(EvalOutcome::Defined);
     };
 
     let registry = lumen_runtime::tools::ProviderRegistry::new();
@@ -815,18 +919,9 @@ fn eval_input(input: &str, session_state: &mut SessionState) {
     vm.set_provider_registry(registry);
     vm.load(module);
 
-    match vm.execute(&entry, vec![]) {
-        Ok(result) => {
-            // Don't print Null for side-effect-only statements
-            if !matches!(result, Value::Null) {
-                let type_name = value_type_name(&result);
-                println!("{} {}", result, gray(&format!(": {}", type_name)));
-            }
-        }
-        Err(e) => {
-            eprintln!("{} {}", red("Runtime error:"), e);
-        }
-    }
+    vm.execute(&entry, vec![])
+        .map(EvalOutcome::Value)
+        .map_err(|e| EvalError::Runtime(e.to_string()))
 }
 
 /// Handle the :type command — evaluate and report the runtime type.
@@ -853,49 +948,23 @@ fn cmd_type(expr: &str, session_state: &SessionState) {
     }
 }
 
-/// Handle the :load command — load and evaluate a .lm.md file.
-fn cmd_load(path: &str) {
-    let source = match fs::read_to_string(path) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("{} Failed to read {}: {}", red("Error:"), path, e);
-            return;
-        }
-    };
-
-    let module = match lumen_compiler::compile(&source) {
-        Ok(m) => m,
-        Err(e) => {
-            eprintln!("{} {}", red("Compile error:"), e);
-            return;
-        }
-    };
-
-    // Find main or first cell
-    let entry = if module.cells.iter().any(|c| c.name == "main") {
-        "main".to_string()
-    } else if !module.cells.is_empty() {
-        module.cells[0].name.clone()
-    } else {
-        println!("{}", gray("No executable cells found."));
-        return;
-    };
-
-    let registry = lumen_runtime::tools::ProviderRegistry::new();
-    let mut vm = lumen_vm::vm::VM::new();
-    vm.set_provider_registry(registry);
-    vm.load(module);
-
-    match vm.execute(&entry, vec![]) {
-        Ok(result) => {
-            if !matches!(result, Value::Null) {
-                let type_name = value_type_name(&result);
-                println!("{} {}", result, gray(&format!(": {}", type_name)));
-            }
-        }
-        Err(e) => {
-            eprintln!("{} {}", red("Runtime error:"), e);
+/// Handle the :load and :reload commands — compile a file and make its
+/// items available at the prompt.
+fn cmd_load(path: &Path, session_state: &mut SessionState) {
+    match session_state.load(path) {
+        Ok(loaded) => {
+            let summary = if loaded.symbols.is_empty() {
+                "no items".to_string()
+            } else {
+                loaded.symbols.join(", ")
+            };
+            println!(
+                "{} {}",
+                green(&format!("Loaded {}:", path.display())),
+                gray(&summary)
+            );
         }
+        Err(e) => eprintln!("{} {}", red("Compile error:"), e),
     }
 }
 
@@ -1088,11 +1157,10 @@ fn cmd_doc_index(session_state: &SessionState) {
         gray("Use :doc <symbol> to inspect a session definition or intrinsic.")
     );
 
-    if session_state.symbols.is_empty() {
+    let names = session_state.symbol_names();
+    if names.is_empty() {
         println!("  {}", gray("Session symbols: none"));
     } else {
-        let mut names: Vec<_> = session_state.symbols.keys().cloned().collect();
-        names.sort();
         println!(
             "  {}",
             gray(&format!("Session symbols: {}", names.join(", ")))
@@ -1129,15 +1197,14 @@ fn cmd_doc_index(session_state: &SessionState) {
 
 /// Handle the :env command — show all defined symbols.
 fn cmd_env(session_state: &SessionState) {
-    if session_state.symbols.is_empty() {
+    let names = session_state.symbol_names();
+    if names.is_empty() {
         println!("{}", gray("No symbols defined."));
         return;
     }
 
     println!("{}", bold("Defined symbols:"));
-    let mut names: Vec<_> = session_state.symbols.keys().collect();
-    names.sort();
-    for name in names {
+    for name in &names {
         println!("  {}", cyan(name));
     }
 }
@@ -1225,7 +1292,12 @@ fn print_help() {
     println!(
         "  {}  {}",
         cyan(":load <file>"),
-        gray("Load and execute a .lm.md file")
+        gray("Load a file and make its cells callable")
+    );
+    println!(
+        "  {}  {}",
+        cyan(":reload"),
+        gray("Reload the last loaded file")
     );
     println!("  {}  {}", cyan(":env"), gray("Show all defined symbols"));
     println!(
@@ -1397,4 +1469,109 @@ mod tests {
         assert!(rendered.contains("Intrinsic `len`"));
         assert!(rendered.contains("Alias: `length` resolves to `len`"));
     }
+
+    struct TempDir {
+        path: PathBuf,
+    }
+
+    impl TempDir {
+        fn new(prefix: &str) -> Self {
+            let stamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let path =
+                std::env::temp_dir().join(format!("{}_{}_{}", prefix, std::process::id(), stamp));
+            fs::create_dir_all(&path).expect("should create temp test directory");
+            Self { path }
+        }
+
+        fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+
+    fn test_editor() -> Editor<LumenCompleter, rustyline::history::DefaultHistory> {
+        Editor::new().expect("editor")
+    }
+
+    fn eval_value(input: &str, state: &mut SessionState) -> Value {
+        match evaluate(input, state) {
+            Ok(EvalOutcome::Value(value)) => value,
+            other => panic!("expected a value for `{}`, got {:?}", input, other),
+        }
+    }
+
+    #[test]
+    fn test_load_makes_cells_callable() {
+        let dir = TempDir::new("lumen_repl_load");
+        let path = dir.path().join("math.lm.md");
+        fs::write(
+            &path,
+            "# Math\n\n```lumen\ncell double(x: Int) -> Int\n  return x * 2\nend\n```\n",
+        )
+        .unwrap();
+
+        let mut rl = test_editor();
+        let mut state = SessionState::default();
+        let line = format!(":load {}", path.display());
+        assert_eq!(handle_command(&line, &mut rl, &mut state), Some(true));
+        assert_eq!(state.symbol_names(), vec!["double".to_string()]);
+        assert_eq!(eval_value("double(21)", &mut state), Value::Int(42));
+    }
+
+    #[test]
+    fn test_reload_picks_up_changes() {
+        let dir = TempDir::new("lumen_repl_load");
+        let path = dir.path().join("greet.lm");
+        fs::write(&path, "cell answer() -> Int\n  return 1\nend\n").unwrap();
+
+        let mut rl = test_editor();
+        let mut state = SessionState::default();
+        handle_command(&format!(":load {}", path.display()), &mut rl, &mut state);
+        assert_eq!(eval_value("answer()", &mut state), Value::Int(1));
+
+        fs::write(&path, "cell answer() -> Int\n  return 42\nend\n").unwrap();
+        assert_eq!(handle_command(":reload", &mut rl, &mut state), Some(true));
+        assert_eq!(eval_value("answer()", &mut state), Value::Int(42));
+    }
+
+    #[test]
+    fn test_reload_compile_error_keeps_previous_state() {
+        let dir = TempDir::new("lumen_repl_load");
+        let path = dir.path().join("lib.lm");
+        fs::write(&path, "cell answer() -> Int\n  return 7\nend\n").unwrap();
+
+        let mut rl = test_editor();
+        let mut state = SessionState::default();
+        handle_command(&format!(":load {}", path.display()), &mut rl, &mut state);
+
+        fs::write(
+            &path,
+            "cell answer() -> Int\n  return undefined_name\nend\n",
+        )
+        .unwrap();
+        assert_eq!(handle_command(":reload", &mut rl, &mut state), Some(true));
+        assert_eq!(eval_value("answer()", &mut state), Value::Int(7));
+
+        // A file that fails to compile is not loaded in the first place.
+        let broken = dir.path().join("broken.lm");
+        fs::write(&broken, "cell broken() -> Int\n  return nope\nend\n").unwrap();
+        handle_command(&format!(":load {}", broken.display()), &mut rl, &mut state);
+        assert_eq!(state.loaded.as_ref().unwrap().path, path);
+    }
+
+    #[test]
+    fn test_parse_reload_command() {
+        assert_eq!(
+            parse_repl_command(":reload"),
+            ParsedCommand::Command(ReplCommand::Reload)
+        );
+    }
 }
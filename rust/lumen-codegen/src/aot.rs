@@ -0,0 +1,250 @@
+//! Ahead-of-time compilation to native object files.
+//!
+//! Ties together [`CodegenContext`], [`lower_module`] and [`emit_object`] for
+//! a chosen target triple, so a module can be compiled for another machine
+//! (e.g. `aarch64-unknown-linux-gnu` from an x86-64 host). With
+//! [`AotOptions::debug_info`] the object also carries DWARF line tables
+//! built from the LIR source map (see [`crate::debug_info`]).
+
+use lumen_compiler::compiler::lir::LirModule;
+use target_lexicon::{Architecture, Triple};
+
+use crate::context::CodegenContext;
+use crate::debug_info::{add_debug_info, DebugTarget};
+use crate::emit::{emit_product, CodegenError};
+use crate::lower::lower_module;
+
+/// Architectures the AOT backend can generate code for.
+pub const SUPPORTED_ARCHITECTURES: &[&str] = &["x86_64", "aarch64", "riscv64", "s390x"];
+
+/// Parse `target_triple` and check that the AOT backend supports it.
+pub fn parse_target(target_triple: &str) -> Result<Triple, CodegenError> {
+    let triple: Triple = target_triple.parse().map_err(|e| {
+        CodegenError::TargetError(format!("invalid target triple `{target_triple}`: {e}"))
+    })?;
+    let supported = matches!(
+        triple.architecture,
+        Architecture::X86_64
+            | Architecture::Aarch64(_)
+            | Architecture::Riscv64(_)
+            | Architecture::S390x
+    );
+    if !supported {
+        return Err(CodegenError::TargetError(format!(
+            "unsupported target `{target_triple}`: AOT compilation supports {}",
+            SUPPORTED_ARCHITECTURES.join(", ")
+        )));
+    }
+    Ok(triple)
+}
+
+/// Options for [`compile_object_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct AotOptions {
+    /// Target triple to compile for; the host when `None`.
+    pub target_triple: Option<String>,
+    /// Emit DWARF debug info mapping machine code to source lines.
+    pub debug_info: bool,
+    /// Source file name recorded in the debug info.
+    pub source_name: Option<String>,
+}
+
+/// Compile `lir` to a relocatable object file for `target_triple`, or for
+/// the host when `None`. The object's header records the target machine.
+pub fn compile_object(
+    lir: &LirModule,
+    target_triple: Option<&str>,
+) -> Result<Vec<u8>, CodegenError> {
+    let options = AotOptions {
+        target_triple: target_triple.map(str::to_string),
+        ..Default::default()
+    };
+    compile_object_with_options(lir, &options)
+}
+
+/// [`compile_object`] with explicit [`AotOptions`].
+pub fn compile_object_with_options(
+    lir: &LirModule,
+    options: &AotOptions,
+) -> Result<Vec<u8>, CodegenError> {
+    let mut ctx = match options.target_triple.as_deref() {
+        Some(target) => CodegenContext::new_with_target(&parse_target(target)?.to_string())?,
+        None => CodegenContext::new()?,
+    };
+    let pointer_type = ctx.pointer_type();
+    let lowered = lower_module(&mut ctx.module, lir, pointer_type)?;
+    let target = DebugTarget {
+        endianness: ctx.isa.endianness(),
+        address_size: ctx.isa.pointer_bytes(),
+    };
+    let mut product = ctx.module.finish();
+    if options.debug_info {
+        let source_name = options.source_name.as_deref().unwrap_or("main.lm");
+        add_debug_info(&mut product, lir, &lowered, source_name, target)?;
+    }
+    emit_product(product)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lumen_compiler::compiler::lir::{Constant, Instruction, LirCell, OpCode};
+
+    const EM_X86_64: u16 = 62;
+    const EM_AARCH64: u16 = 183;
+    const EM_RISCV: u16 = 243;
+
+    fn answer_module() -> LirModule {
+        LirModule {
+            version: "1.0.0".to_string(),
+            doc_hash: "test".to_string(),
+            strings: Vec::new(),
+            types: Vec::new(),
+            cells: vec![LirCell {
+                name: "answer".to_string(),
+                params: Vec::new(),
+                returns: Some("Int".to_string()),
+                registers: 2,
+                constants: vec![Constant::Int(42)],
+                instructions: vec![
+                    Instruction::abx(OpCode::LoadK, 0, 0),
+                    Instruction::abc(OpCode::Return, 0, 1, 0),
+                ],
+                effect_handler_metas: Vec::new(),
+                memoizable: false,
+            }],
+            tools: Vec::new(),
+            policies: Vec::new(),
+            agents: Vec::new(),
+            addons: Vec::new(),
+            effects: Vec::new(),
+            effect_binds: Vec::new(),
+            handlers: Vec::new(),
+            source_map: Vec::new(),
+        }
+    }
+
+    /// `e_machine` of a little-endian ELF object.
+    fn elf_machine(bytes: &[u8]) -> u16 {
+        assert_eq!(&bytes[..4], b"\x7fELF", "expected an ELF object");
+        u16::from_le_bytes([bytes[18], bytes[19]])
+    }
+
+    #[test]
+    fn cross_compiles_for_aarch64_linux() {
+        let bytes = compile_object(&answer_module(), Some("aarch64-unknown-linux-gnu"))
+            .expect("aarch64 object");
+        assert_eq!(elf_machine(&bytes), EM_AARCH64);
+    }
+
+    #[test]
+    fn cross_compiles_for_each_linux_architecture() {
+        for (triple, machine) in [
+            ("x86_64-unknown-linux-gnu", EM_X86_64),
+            ("riscv64gc-unknown-linux-gnu", EM_RISCV),
+        ] {
+            let bytes = compile_object(&answer_module(), Some(triple)).expect(triple);
+            assert_eq!(elf_machine(&bytes), machine, "{triple}");
+        }
+    }
+
+    #[test]
+    fn macos_target_emits_arm64_mach_o() {
+        let bytes =
+            compile_object(&answer_module(), Some("aarch64-apple-darwin")).expect("darwin object");
+        // MH_MAGIC_64 followed by CPU_TYPE_ARM64, both little-endian.
+        assert_eq!(&bytes[..4], &0xfeed_facf_u32.to_le_bytes());
+        assert_eq!(&bytes[4..8], &0x0100_000c_u32.to_le_bytes());
+    }
+
+    #[test]
+    fn unsupported_architecture_is_rejected() {
+        let err = compile_object(&answer_module(), Some("i686-unknown-linux-gnu")).unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("unsupported target `i686-unknown-linux-gnu`"),
+            "{message}"
+        );
+        assert!(message.contains("aarch64"), "{message}");
+    }
+
+    #[test]
+    fn malformed_triple_is_rejected() {
+        let err = parse_target("not-a-real-triple").unwrap_err();
+        assert!(err.to_string().contains("invalid target triple"), "{err}");
+    }
+
+    // -- Debug info --------------------------------------------------------
+
+    use gimli::{EndianSlice, LittleEndian};
+    use object::{Object, ObjectSection};
+
+    const SOURCE: &str = "cell helper(x: Int) -> Int\n  let y = x * 2\n  return y + 1\nend\n\ncell main() -> Int\n  return helper(20)\nend\n";
+
+    fn compile_with_debug_info(debug_info: bool) -> Vec<u8> {
+        let lir = lumen_compiler::compile_raw(SOURCE).expect("compilation should succeed");
+        let options = AotOptions {
+            target_triple: Some("x86_64-unknown-linux-gnu".to_string()),
+            debug_info,
+            source_name: Some("answer.lm".to_string()),
+        };
+        compile_object_with_options(&lir, &options).expect("object")
+    }
+
+    /// Every (file, line) row in the object's line programs.
+    fn line_rows(bytes: &[u8]) -> Vec<(String, u64)> {
+        let file = object::File::parse(bytes).expect("parse object");
+        let load = |id: gimli::SectionId| -> Result<Vec<u8>, gimli::Error> {
+            Ok(file
+                .section_by_name(id.name())
+                .and_then(|s| s.uncompressed_data().ok())
+                .map(|d| d.into_owned())
+                .unwrap_or_default())
+        };
+        let owned = gimli::DwarfSections::load(load).unwrap();
+        let dwarf = owned.borrow(|section| EndianSlice::new(section, LittleEndian));
+
+        let mut rows = Vec::new();
+        let mut units = dwarf.units();
+        while let Some(header) = units.next().unwrap() {
+            let unit = dwarf.unit(header).unwrap();
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+            let mut program_rows = program.rows();
+            while let Some((header, row)) = program_rows.next_row().unwrap() {
+                if row.end_sequence() {
+                    continue;
+                }
+                let entry = row.file(header).unwrap();
+                let name = dwarf.attr_string(&unit, entry.path_name()).unwrap();
+                let line = row.line().map(|l| l.get()).unwrap_or(0);
+                rows.push((name.to_string_lossy().into_owned(), line));
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn debug_info_adds_line_table_for_source_lines() {
+        let bytes = compile_with_debug_info(true);
+        let file = object::File::parse(&*bytes).expect("parse object");
+        assert!(file.section_by_name(".debug_line").is_some());
+        assert!(file.section_by_name(".debug_info").is_some());
+
+        let rows = line_rows(&bytes);
+        // `return y + 1` is line 3 of the source.
+        assert!(
+            rows.contains(&("answer.lm".to_string(), 3)),
+            "rows: {rows:?}"
+        );
+        assert!(rows.iter().all(|(name, _)| name == "answer.lm"));
+    }
+
+    #[test]
+    fn debug_info_is_off_by_default() {
+        let bytes = compile_with_debug_info(false);
+        let file = object::File::parse(&*bytes).expect("parse object");
+        assert!(file.section_by_name(".debug_line").is_none());
+    }
+}
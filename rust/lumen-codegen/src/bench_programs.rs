@@ -20,6 +20,7 @@ fn empty_module(cells: Vec<LirCell>) -> LirModule {
         effects: Vec::new(),
         effect_binds: Vec::new(),
         handlers: Vec::new(),
+        source_map: Vec::new(),
     }
 }
 
@@ -84,6 +85,7 @@ pub fn fibonacci_lir() -> LirModule {
             Instruction::abc(OpCode::Return, 7, 1, 0),  // 14: return r7
         ],
         effect_handler_metas: Vec::new(),
+        memoizable: false,
     };
 
     empty_module(vec![cell])
@@ -144,6 +146,7 @@ pub fn arithmetic_lir() -> LirModule {
             Instruction::abc(OpCode::Return, 9, 1, 0),
         ],
         effect_handler_metas: Vec::new(),
+        memoizable: false,
     };
 
     empty_module(vec![cell])
@@ -189,6 +192,7 @@ pub fn simple_loop_lir() -> LirModule {
             Instruction::abc(OpCode::Return, 1, 1, 0), // 7: return r1
         ],
         effect_handler_metas: Vec::new(),
+        memoizable: false,
     };
 
     empty_module(vec![cell])
@@ -233,6 +237,7 @@ pub fn multi_cell_lir(count: usize) -> LirModule {
                     Instruction::abc(OpCode::Return, 5, 1, 0),
                 ],
                 effect_handler_metas: Vec::new(),
+                memoizable: false,
             }
         })
         .collect();
@@ -273,6 +278,7 @@ pub fn tail_recursive_countdown_lir() -> LirModule {
             Instruction::abc(OpCode::TailCall, 3, 1, 1), // 9: tail-call countdown(r4)
         ],
         effect_handler_metas: Vec::new(),
+        memoizable: false,
     };
 
     empty_module(vec![cell])
@@ -0,0 +1,347 @@
+//! DWARF debug information for AOT object files.
+//!
+//! Builds a single compilation unit whose line program maps each function's
+//! machine code back to Lumen source lines, using the code ranges recorded by
+//! [`lower_module`](crate::lower::lower_module) and the module's LIR source
+//! map. Each cell also gets a `DW_TAG_subprogram` so debuggers can name the
+//! function they stopped in.
+//!
+//! Addresses are emitted as relocations against the function symbols, so the
+//! output is correct wherever the linker places the code.
+
+use std::collections::HashMap;
+
+use cranelift_codegen::ir::Endianness;
+use cranelift_object::ObjectProduct;
+use gimli::write::{
+    Address, AttributeValue, DwarfUnit, EndianVec, LineProgram, LineString, Range, RangeList,
+    Sections, Writer,
+};
+use gimli::{Encoding, Format, LineEncoding, RunTimeEndian, SectionId};
+use object::write::{Relocation, SectionId as ObjectSectionId, StandardSegment};
+use object::{BinaryFormat, RelocationEncoding, RelocationFlags, RelocationKind, SectionKind};
+
+use lumen_compiler::compiler::lir::LirModule;
+use lumen_compiler::compiler::tokens::Span;
+
+use crate::emit::CodegenError;
+use crate::lower::LoweredModule;
+
+/// Target properties the DWARF encoding depends on.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugTarget {
+    pub endianness: Endianness,
+    /// Size of a code address in bytes.
+    pub address_size: u8,
+}
+
+/// Add `.debug_info`, `.debug_abbrev`, `.debug_line` and friends describing
+/// `lowered` to `product`. `source_name` is recorded as the file the line
+/// numbers refer to.
+pub fn add_debug_info(
+    product: &mut ObjectProduct,
+    lir: &LirModule,
+    lowered: &LoweredModule,
+    source_name: &str,
+    target: DebugTarget,
+) -> Result<(), CodegenError> {
+    let encoding = Encoding {
+        format: Format::Dwarf32,
+        version: 4,
+        address_size: target.address_size,
+    };
+    let mut dwarf = DwarfUnit::new(encoding);
+
+    let mut program = LineProgram::new(
+        encoding,
+        LineEncoding::default(),
+        LineString::String(b".".to_vec()),
+        LineString::String(source_name.as_bytes().to_vec()),
+        None,
+    );
+    let directory = program.default_directory();
+    let file = program.add_file(
+        LineString::String(source_name.as_bytes().to_vec()),
+        directory,
+        None,
+    );
+
+    let spans: HashMap<(&str, usize), Span> = lir
+        .source_map()
+        .iter()
+        .map(|entry| ((entry.cell.as_str(), entry.instruction), entry.span))
+        .collect();
+
+    let mut ranges = Vec::with_capacity(lowered.functions.len());
+    let root = dwarf.unit.root();
+    for (symbol, function) in lowered.functions.iter().enumerate() {
+        let begin = Address::Symbol { symbol, addend: 0 };
+        ranges.push(Range::StartLength {
+            begin,
+            length: function.code_size as u64,
+        });
+
+        program.begin_sequence(Some(begin));
+        let mut last_line = None;
+        for range in &function.code_ranges {
+            let Some(span) = spans.get(&(function.name.as_str(), range.instruction)) else {
+                continue;
+            };
+            if last_line == Some(span.line) {
+                continue;
+            }
+            last_line = Some(span.line);
+            let row = program.row();
+            row.address_offset = range.start as u64;
+            row.file = file;
+            row.line = span.line as u64;
+            row.column = span.col as u64;
+            program.generate_row();
+        }
+        program.end_sequence(function.code_size as u64);
+
+        let subprogram = dwarf.unit.add(root, gimli::DW_TAG_subprogram);
+        let entry = dwarf.unit.get_mut(subprogram);
+        entry.set(
+            gimli::DW_AT_name,
+            AttributeValue::String(function.name.as_bytes().to_vec()),
+        );
+        entry.set(gimli::DW_AT_external, AttributeValue::Flag(true));
+        entry.set(gimli::DW_AT_low_pc, AttributeValue::Address(begin));
+        entry.set(
+            gimli::DW_AT_high_pc,
+            AttributeValue::Udata(function.code_size as u64),
+        );
+        let first_line = function
+            .code_ranges
+            .iter()
+            .find_map(|range| spans.get(&(function.name.as_str(), range.instruction)));
+        if let Some(span) = first_line {
+            entry.set(
+                gimli::DW_AT_decl_file,
+                AttributeValue::FileIndex(Some(file)),
+            );
+            entry.set(
+                gimli::DW_AT_decl_line,
+                AttributeValue::Udata(span.line as u64),
+            );
+        }
+    }
+
+    let range_list = dwarf.unit.ranges.add(RangeList(ranges));
+    let unit = dwarf.unit.get_mut(root);
+    unit.set(
+        gimli::DW_AT_name,
+        AttributeValue::String(source_name.as_bytes().to_vec()),
+    );
+    unit.set(
+        gimli::DW_AT_producer,
+        AttributeValue::String(format!("lumen {}", env!("CARGO_PKG_VERSION")).into_bytes()),
+    );
+    unit.set(gimli::DW_AT_comp_dir, AttributeValue::String(b".".to_vec()));
+    unit.set(
+        gimli::DW_AT_low_pc,
+        AttributeValue::Address(Address::Constant(0)),
+    );
+    unit.set(
+        gimli::DW_AT_ranges,
+        AttributeValue::RangeListRef(range_list),
+    );
+    dwarf.unit.line_program = program;
+
+    let endian = match target.endianness {
+        Endianness::Little => RunTimeEndian::Little,
+        Endianness::Big => RunTimeEndian::Big,
+    };
+    // ELF objects are combined section by section at link time, so offsets
+    // between debug sections must be relocated too.
+    let relocate_offsets = product.object.format() == BinaryFormat::Elf;
+    let mut sections = Sections::new(DebugWriter::new(endian, relocate_offsets));
+    dwarf
+        .write(&mut sections)
+        .map_err(|e| CodegenError::EmissionError(format!("failed to write DWARF: {e}")))?;
+
+    write_sections(product, &sections, lowered)
+}
+
+/// Copy the DWARF sections into the object and apply their relocations.
+fn write_sections(
+    product: &mut ObjectProduct,
+    sections: &Sections<DebugWriter>,
+    lowered: &LoweredModule,
+) -> Result<(), CodegenError> {
+    let macho = product.object.format() == BinaryFormat::MachO;
+    let mut section_ids: HashMap<SectionId, ObjectSectionId> = HashMap::new();
+    sections.for_each(|id, writer| {
+        if writer.data.len() == 0 {
+            return Ok(());
+        }
+        let name = if macho {
+            id.name().replacen('.', "__", 1)
+        } else {
+            id.name().to_string()
+        };
+        let segment = product.object.segment_name(StandardSegment::Debug).to_vec();
+        let section = product
+            .object
+            .add_section(segment, name.into_bytes(), SectionKind::Debug);
+        product
+            .object
+            .append_section_data(section, writer.data.slice(), 1);
+        section_ids.insert(id, section);
+        Ok::<(), CodegenError>(())
+    })?;
+
+    sections.for_each(|id, writer| {
+        let Some(&section) = section_ids.get(&id) else {
+            return Ok(());
+        };
+        for reloc in &writer.relocs {
+            let (symbol, addend) = match reloc.target {
+                // Relocate against the text section rather than the function
+                // symbol: Mach-O tools don't resolve debug relocations that
+                // name external symbols.
+                RelocTarget::Function(index) => {
+                    let function = product.function_symbol(lowered.functions[index].func_id);
+                    match product.object.symbol_section_and_offset(function) {
+                        Some((symbol, offset)) => (symbol, reloc.addend + offset as i64),
+                        None => (function, reloc.addend),
+                    }
+                }
+                RelocTarget::Section(target) => match section_ids.get(&target) {
+                    Some(&target) => (product.object.section_symbol(target), reloc.addend),
+                    None => continue,
+                },
+            };
+            product
+                .object
+                .add_relocation(
+                    section,
+                    Relocation {
+                        offset: reloc.offset,
+                        symbol,
+                        addend,
+                        flags: RelocationFlags::Generic {
+                            kind: RelocationKind::Absolute,
+                            encoding: RelocationEncoding::Generic,
+                            size: reloc.size * 8,
+                        },
+                    },
+                )
+                .map_err(|e| {
+                    CodegenError::EmissionError(format!("failed to relocate {}: {e}", id.name()))
+                })?;
+        }
+        Ok(())
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Relocation-recording writer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy)]
+enum RelocTarget {
+    /// Index into `LoweredModule::functions`.
+    Function(usize),
+    Section(SectionId),
+}
+
+#[derive(Debug, Clone)]
+struct DebugReloc {
+    offset: u64,
+    size: u8,
+    target: RelocTarget,
+    addend: i64,
+}
+
+/// A gimli section writer that leaves symbol addresses (and, for ELF,
+/// cross-section offsets) as zero and records a relocation for each.
+#[derive(Debug, Clone)]
+struct DebugWriter {
+    data: EndianVec<RunTimeEndian>,
+    relocs: Vec<DebugReloc>,
+    relocate_offsets: bool,
+}
+
+impl DebugWriter {
+    fn new(endian: RunTimeEndian, relocate_offsets: bool) -> Self {
+        Self {
+            data: EndianVec::new(endian),
+            relocs: Vec::new(),
+            relocate_offsets,
+        }
+    }
+}
+
+impl Writer for DebugWriter {
+    type Endian = RunTimeEndian;
+
+    fn endian(&self) -> Self::Endian {
+        self.data.endian()
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.data.write(bytes)
+    }
+
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.data.write_at(offset, bytes)
+    }
+
+    fn write_address(&mut self, address: Address, size: u8) -> gimli::write::Result<()> {
+        match address {
+            Address::Constant(value) => self.write_udata(value, size),
+            Address::Symbol { symbol, addend } => {
+                self.relocs.push(DebugReloc {
+                    offset: self.len() as u64,
+                    size,
+                    target: RelocTarget::Function(symbol),
+                    addend,
+                });
+                self.write_udata(0, size)
+            }
+        }
+    }
+
+    fn write_offset(
+        &mut self,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        if !self.relocate_offsets {
+            return self.write_udata(val as u64, size);
+        }
+        self.relocs.push(DebugReloc {
+            offset: self.len() as u64,
+            size,
+            target: RelocTarget::Section(section),
+            addend: val as i64,
+        });
+        self.write_udata(0, size)
+    }
+
+    fn write_offset_at(
+        &mut self,
+        offset: usize,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        if !self.relocate_offsets {
+            return self.write_udata_at(offset, val as u64, size);
+        }
+        self.relocs.push(DebugReloc {
+            offset: offset as u64,
+            size,
+            target: RelocTarget::Section(section),
+            addend: val as i64,
+        });
+        self.write_udata_at(offset, 0, size)
+    }
+}
@@ -4,7 +4,7 @@
 
 use std::path::Path;
 
-use cranelift_object::ObjectModule;
+use cranelift_object::{ObjectModule, ObjectProduct};
 use thiserror::Error;
 
 /// Errors that can occur during code generation.
@@ -25,7 +25,12 @@ pub enum CodegenError {
 
 /// Finish the module and return the raw object file bytes.
 pub fn emit_object(module: ObjectModule) -> Result<Vec<u8>, CodegenError> {
-    let product = module.finish();
+    emit_product(module.finish())
+}
+
+/// Return the raw object file bytes for an already finished module, e.g. one
+/// that had extra sections added to it.
+pub fn emit_product(product: ObjectProduct) -> Result<Vec<u8>, CodegenError> {
     let bytes = product
         .emit()
         .map_err(|e| CodegenError::EmissionError(format!("failed to emit object file: {e}")))?;
@@ -64,6 +69,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: Vec::new(),
+                memoizable: false,
             }],
             tools: Vec::new(),
             policies: Vec::new(),
@@ -72,6 +78,7 @@ mod tests {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         };
 
         let mut ctx = CodegenContext::new().expect("host context");
@@ -23,11 +23,33 @@
 //! | `addr[T]`     | pointer (`i64`)    |
 //! | `String`      | pointer (`i64`)    |
 //! | everything else | pointer (`i64`)  |
+//!
+//! ## Callbacks
+//!
+//! The opposite direction — handing a Lumen cell to a C API that expects a
+//! function pointer, such as `qsort`'s comparator — goes through
+//! [`define_callback_trampoline`]. It emits a small shim with the C
+//! signature described by a [`CallbackSignature`] that converts each argument
+//! to the cell's parameter type, calls the compiled cell, and converts the
+//! result back. The JIT exposes this as
+//! [`JitEngine::register_callback`](crate::jit::JitEngine::register_callback),
+//! which also keeps the shim's code alive until the callback is released.
+//!
+//! Supported callback signatures are scalars and pointers:
+//!
+//! | C type    | Lumen parameter / result      |
+//! |-----------|-------------------------------|
+//! | `int8_t` / `_Bool` | `Bool` or `Int` (zero-extended) |
+//! | `int32_t` | `Int` (sign-extended; results are truncated) |
+//! | `int64_t` | `Int`                         |
+//! | `double`  | `Float`                       |
+//! | `void *`  | `Int` address (zero-extended) |
+//! | `void`    | result only; the cell's value is discarded |
 
 use cranelift_codegen::ir::types;
-use cranelift_codegen::ir::{AbiParam, InstBuilder, Type as ClifType, Value};
+use cranelift_codegen::ir::{AbiParam, InstBuilder, Signature, Type as ClifType, Value};
 use cranelift_codegen::isa::CallConv;
-use cranelift_frontend::FunctionBuilder;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
 use cranelift_module::{FuncId, Linkage, Module};
 use cranelift_object::ObjectModule;
 use target_lexicon::Triple;
@@ -73,6 +95,9 @@ impl CallingConvention {
 pub enum CType {
     /// 64-bit signed integer (`int64_t` / Lumen `Int`).
     I64,
+    /// 32-bit signed integer (`int`). Only produced for callback signatures;
+    /// Lumen values are always 64 bits wide.
+    I32,
     /// 64-bit IEEE 754 float (`double` / Lumen `Float`).
     F64,
     /// 8-bit integer used for booleans (`_Bool` / Lumen `Bool`).
@@ -92,6 +117,7 @@ impl CType {
     pub fn to_clif_type(self, pointer_type: ClifType) -> ClifType {
         match self {
             CType::I64 => types::I64,
+            CType::I32 => types::I32,
             CType::F64 => types::F64,
             CType::I8 => types::I8,
             CType::Pointer => pointer_type,
@@ -254,6 +280,159 @@ pub fn declare_externs(
     Ok(results)
 }
 
+// ---------------------------------------------------------------------------
+// Callbacks
+// ---------------------------------------------------------------------------
+
+/// The C signature a Lumen cell is exposed under when passed to native code
+/// as a function pointer. See the [module docs](self#callbacks) for the
+/// supported parameter and return types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackSignature {
+    /// Parameter types in declaration order.
+    pub param_types: Vec<CType>,
+    /// Return type. `CType::Void` discards the cell's result.
+    pub return_type: CType,
+}
+
+impl CallbackSignature {
+    /// Create a new callback signature.
+    pub fn new(param_types: Vec<CType>, return_type: CType) -> Self {
+        Self {
+            param_types,
+            return_type,
+        }
+    }
+
+    /// Build the Cranelift [`Signature`] native callers use.
+    pub fn build_signature(&self, call_conv: CallConv, pointer_type: ClifType) -> Signature {
+        let mut sig = Signature::new(call_conv);
+        for param in &self.param_types {
+            sig.params
+                .push(AbiParam::new(param.to_clif_type(pointer_type)));
+        }
+        if self.return_type != CType::Void {
+            sig.returns
+                .push(AbiParam::new(self.return_type.to_clif_type(pointer_type)));
+        }
+        sig
+    }
+}
+
+/// Define a function named `name` with the C signature `callback` that
+/// forwards to the compiled cell at `target`, whose ABI signature is
+/// `target_sig`.
+///
+/// Fails if the two signatures can't be bridged: a parameter count mismatch,
+/// a `Void` parameter, or a float passed where the cell expects an integer
+/// (or the reverse).
+pub fn define_callback_trampoline<M: Module>(
+    module: &mut M,
+    name: &str,
+    callback: &CallbackSignature,
+    target: *const u8,
+    target_sig: &Signature,
+) -> Result<FuncId, CodegenError> {
+    if callback.param_types.len() != target_sig.params.len() {
+        return Err(CodegenError::LoweringError(format!(
+            "callback '{name}' has {} parameters but the cell takes {}",
+            callback.param_types.len(),
+            target_sig.params.len()
+        )));
+    }
+    let mismatch = |what: String| {
+        CodegenError::LoweringError(format!("callback '{name}': cannot convert {what}"))
+    };
+
+    let pointer_type = module.isa().pointer_type();
+    let sig = callback.build_signature(module.isa().default_call_conv(), pointer_type);
+    let func_id = module
+        .declare_function(name, Linkage::Local, &sig)
+        .map_err(|e| CodegenError::LoweringError(format!("declare_function({name}): {e}")))?;
+
+    let mut ctx = module.make_context();
+    ctx.func.signature = sig;
+    let mut fb_ctx = FunctionBuilderContext::new();
+    let mut builder = FunctionBuilder::new(&mut ctx.func, &mut fb_ctx);
+    let entry = builder.create_block();
+    builder.append_block_params_for_function_params(entry);
+    builder.switch_to_block(entry);
+    builder.seal_block(entry);
+
+    let params = builder.block_params(entry).to_vec();
+    let mut args = Vec::with_capacity(params.len());
+    for (i, (value, ctype)) in params.into_iter().zip(&callback.param_types).enumerate() {
+        let to = target_sig.params[i].value_type;
+        if *ctype == CType::Void {
+            return Err(mismatch(format!("void parameter {i}")));
+        }
+        let arg = coerce(
+            &mut builder,
+            value,
+            ctype.to_clif_type(pointer_type),
+            to,
+            *ctype,
+        )
+        .ok_or_else(|| mismatch(format!("parameter {i} from {ctype:?} to {to}")))?;
+        args.push(arg);
+    }
+
+    let sig_ref = builder.import_signature(target_sig.clone());
+    let callee = builder.ins().iconst(pointer_type, target as i64);
+    let call = builder.ins().call_indirect(sig_ref, callee, &args);
+    let result = builder.inst_results(call).first().copied();
+
+    match (callback.return_type, result) {
+        (CType::Void, _) => {
+            builder.ins().return_(&[]);
+        }
+        (ctype, Some(value)) => {
+            let from = target_sig.returns[0].value_type;
+            let ret = coerce(
+                &mut builder,
+                value,
+                from,
+                ctype.to_clif_type(pointer_type),
+                ctype,
+            )
+            .ok_or_else(|| mismatch(format!("result from {from} to {ctype:?}")))?;
+            builder.ins().return_(&[ret]);
+        }
+        (ctype, None) => return Err(mismatch(format!("missing result to {ctype:?}"))),
+    }
+    builder.finalize();
+
+    module
+        .define_function(func_id, &mut ctx)
+        .map_err(|e| CodegenError::LoweringError(format!("define_function({name}): {e}")))?;
+    Ok(func_id)
+}
+
+/// Convert `value` between two scalar types for a callback shim. Integers are
+/// widened (sign-extended only for `CType::I32`) or truncated; floats must
+/// stay floats.
+fn coerce(
+    builder: &mut FunctionBuilder,
+    value: Value,
+    from: ClifType,
+    to: ClifType,
+    ctype: CType,
+) -> Option<Value> {
+    if from == to {
+        return Some(value);
+    }
+    if !from.is_int() || !to.is_int() {
+        return None;
+    }
+    Some(if from.bits() > to.bits() {
+        builder.ins().ireduce(to, value)
+    } else if ctype == CType::I32 {
+        builder.ins().sextend(to, value)
+    } else {
+        builder.ins().uextend(to, value)
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -13,7 +13,7 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
 
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
-use cranelift_codegen::ir::{types, AbiParam, InstBuilder, Type as ClifType};
+use cranelift_codegen::ir::{types, AbiParam, InstBuilder, Signature, Type as ClifType};
 use cranelift_codegen::Context;
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
 use cranelift_jit::{JITBuilder, JITModule};
@@ -22,6 +22,8 @@ use cranelift_module::{FuncId, Linkage, Module};
 use lumen_compiler::compiler::lir::{Constant, Instruction, LirCell, LirModule, OpCode};
 
 use crate::emit::CodegenError;
+use crate::ffi::{define_callback_trampoline, CallbackSignature};
+use crate::opcodes::{OpContext, OpcodeRegistry};
 use crate::types::lir_type_str_to_cl_type;
 
 /// Maximum number of virtual registers we support per cell.
@@ -166,6 +168,41 @@ fn register_string_helpers(builder: &mut JITBuilder) {
     builder.symbol("jit_rt_string_eq", jit_rt_string_eq as *const u8);
     builder.symbol("jit_rt_string_cmp", jit_rt_string_cmp as *const u8);
     builder.symbol("jit_rt_string_drop", jit_rt_string_drop as *const u8);
+    builder.symbol("jit_rt_call_enter", jit_rt_call_enter as *const u8);
+    builder.symbol("jit_rt_call_exit", jit_rt_call_exit as *const u8);
+}
+
+// ---------------------------------------------------------------------------
+// Call depth accounting (extern "C" functions callable from JIT code)
+// ---------------------------------------------------------------------------
+
+thread_local! {
+    /// Calls between JIT-compiled cells that may still nest on this thread.
+    static CALL_DEPTH_BUDGET: std::cell::Cell<usize> = const { std::cell::Cell::new(usize::MAX) };
+    /// Set when a call found the budget exhausted; native frames then unwind.
+    static CALL_DEPTH_EXCEEDED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Claim one level of call depth before a native call. Returns 0, and marks
+/// the execution as unwinding, when the budget is exhausted.
+extern "C" fn jit_rt_call_enter() -> i64 {
+    CALL_DEPTH_BUDGET.with(|budget| match budget.get() {
+        0 => {
+            CALL_DEPTH_EXCEEDED.with(|exceeded| exceeded.set(true));
+            0
+        }
+        n => {
+            budget.set(n - 1);
+            1
+        }
+    })
+}
+
+/// Release the level claimed by [`jit_rt_call_enter`]. Returns 1 when the
+/// execution is unwinding, so the caller returns immediately too.
+extern "C" fn jit_rt_call_exit() -> i64 {
+    CALL_DEPTH_BUDGET.with(|budget| budget.set(budget.get().saturating_add(1)));
+    CALL_DEPTH_EXCEEDED.with(|exceeded| exceeded.get()) as i64
 }
 
 // ---------------------------------------------------------------------------
@@ -290,6 +327,9 @@ pub enum JitError {
     CellNotFound(String),
     /// JIT module creation failed.
     ModuleError(String),
+    /// Calls between compiled cells nested deeper than the depth budget
+    /// given to [`JitEngine::execute_jit_within_depth`].
+    CallDepthExceeded,
 }
 
 impl std::fmt::Display for JitError {
@@ -298,6 +338,7 @@ impl std::fmt::Display for JitError {
             JitError::CompileError(e) => write!(f, "JIT compile error: {e}"),
             JitError::CellNotFound(name) => write!(f, "cell not found: {name}"),
             JitError::ModuleError(msg) => write!(f, "JIT module error: {msg}"),
+            JitError::CallDepthExceeded => write!(f, "call depth exceeded in JIT code"),
         }
     }
 }
@@ -322,12 +363,41 @@ struct CompiledFunction {
     param_count: usize,
     /// True if the function returns a heap-allocated string pointer.
     returns_string: bool,
+    /// True if any parameter is a heap-allocated string pointer.
+    takes_string: bool,
+    /// The ABI signature the function was compiled with.
+    signature: Signature,
 }
 
 // Safety: The function pointers are valid for the lifetime of the JITModule
 // that produced them. We ensure the JITModule lives as long as the JitEngine.
 unsafe impl Send for CompiledFunction {}
 
+// ---------------------------------------------------------------------------
+// Native callbacks
+// ---------------------------------------------------------------------------
+
+/// A compiled cell exposed to native code as a C function pointer, created
+/// by [`JitEngine::register_callback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NativeCallback {
+    id: u64,
+    fn_ptr: *const u8,
+}
+
+impl NativeCallback {
+    /// The registration id, unique within the engine that created it.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The C function pointer. Transmute it to an `extern "C" fn` type that
+    /// matches the [`CallbackSignature`] it was registered with.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.fn_ptr
+    }
+}
+
 // ---------------------------------------------------------------------------
 // JIT Engine
 // ---------------------------------------------------------------------------
@@ -352,6 +422,13 @@ pub struct JitEngine {
     codegen_settings: CodegenSettings,
     /// Compilation statistics.
     stats: JitStats,
+    /// Modules holding the trampolines of live native callbacks, keyed by
+    /// callback id. Keeping them here keeps the shim code mapped for as long
+    /// as native code may call it.
+    callbacks: HashMap<u64, JITModule>,
+    next_callback_id: u64,
+    /// Lowerings for `OpCode::Extension` instructions.
+    opcodes: OpcodeRegistry,
 }
 
 impl JitEngine {
@@ -364,9 +441,23 @@ impl JitEngine {
             cache: HashMap::new(),
             codegen_settings: settings,
             stats: JitStats::default(),
+            callbacks: HashMap::new(),
+            next_callback_id: 0,
+            opcodes: OpcodeRegistry::new(),
         }
     }
 
+    /// The extension opcodes this engine can compile.
+    pub fn opcodes(&self) -> &OpcodeRegistry {
+        &self.opcodes
+    }
+
+    /// Mutable access to the extension opcode registry. Register opcodes
+    /// before compiling the modules that use them.
+    pub fn opcodes_mut(&mut self) -> &mut OpcodeRegistry {
+        &mut self.opcodes
+    }
+
     /// Record a call to `cell_name` and return `true` if the cell *just*
     /// crossed the hot threshold (i.e., it was not hot before this call
     /// but now is). This is the trigger for the runtime to schedule JIT
@@ -401,7 +492,7 @@ impl JitEngine {
         let pointer_type = jit_module.isa().pointer_type();
 
         // Lower all cells into the JIT module.
-        let lowered = lower_module_jit(&mut jit_module, module, pointer_type)?;
+        let lowered = lower_module_jit(&mut jit_module, module, pointer_type, &self.opcodes)?;
 
         // Finalize all definitions so we can retrieve function pointers.
         jit_module
@@ -417,6 +508,12 @@ impl JitEngine {
                     fn_ptr,
                     param_count: func.param_count,
                     returns_string: func.returns_string,
+                    takes_string: func.takes_string,
+                    signature: jit_module
+                        .declarations()
+                        .get_function_decl(func.func_id)
+                        .signature
+                        .clone(),
                 },
             );
             self.stats.cells_compiled += 1;
@@ -559,6 +656,26 @@ impl JitEngine {
         }
     }
 
+    /// [`execute_jit`](Self::execute_jit), allowing calls between compiled
+    /// cells to nest at most `depth_budget` deep. Deeper recursion unwinds
+    /// the native frames and fails with [`JitError::CallDepthExceeded`]
+    /// instead of overflowing the native stack.
+    pub fn execute_jit_within_depth(
+        &mut self,
+        cell_name: &str,
+        args: &[i64],
+        depth_budget: usize,
+    ) -> Result<i64, JitError> {
+        let saved = CALL_DEPTH_BUDGET.with(|budget| budget.replace(depth_budget));
+        CALL_DEPTH_EXCEEDED.with(|exceeded| exceeded.set(false));
+        let result = self.execute_jit(cell_name, args);
+        CALL_DEPTH_BUDGET.with(|budget| budget.set(saved));
+        if CALL_DEPTH_EXCEEDED.with(|exceeded| exceeded.replace(false)) {
+            return Err(JitError::CallDepthExceeded);
+        }
+        result
+    }
+
     /// Compile a cell if not already compiled, then execute it.
     /// Convenience method that combines `compile_hot` and `execute_jit`.
     pub fn compile_and_execute(
@@ -571,6 +688,72 @@ impl JitEngine {
         self.execute_jit(cell_name, args)
     }
 
+    /// Wrap the compiled cell `cell_name` in a C-ABI trampoline with the given
+    /// signature, so it can be handed to native code that expects a function
+    /// pointer. The cell must already be compiled.
+    ///
+    /// The trampoline stays valid until [`release_callback`] is called or the
+    /// engine is dropped, even if the cell is later invalidated. Cells that
+    /// take or return `String` are rejected: the JIT's string pointers are
+    /// owned Rust strings, not C strings.
+    ///
+    /// [`release_callback`]: JitEngine::release_callback
+    pub fn register_callback(
+        &mut self,
+        cell_name: &str,
+        signature: &CallbackSignature,
+    ) -> Result<NativeCallback, JitError> {
+        let compiled = self
+            .cache
+            .get(cell_name)
+            .ok_or_else(|| JitError::CellNotFound(cell_name.to_string()))?;
+        if compiled.takes_string || compiled.returns_string {
+            return Err(JitError::ModuleError(format!(
+                "cell '{cell_name}' passes strings and cannot be used as a native callback"
+            )));
+        }
+
+        let builder = JITBuilder::new(cranelift_module::default_libcall_names())
+            .map_err(|e| JitError::ModuleError(format!("JITBuilder creation failed: {e}")))?;
+        let mut module = JITModule::new(builder);
+        let id = self.next_callback_id;
+        let func_id = define_callback_trampoline(
+            &mut module,
+            &format!("__lumen_callback_{id}_{cell_name}"),
+            signature,
+            compiled.fn_ptr,
+            &compiled.signature,
+        )?;
+        module
+            .finalize_definitions()
+            .map_err(|e| JitError::ModuleError(format!("finalize_definitions failed: {e}")))?;
+        let fn_ptr = module.get_finalized_function(func_id);
+
+        self.next_callback_id += 1;
+        self.callbacks.insert(id, module);
+        Ok(NativeCallback { id, fn_ptr })
+    }
+
+    /// Unregister a callback and free its trampoline. Returns `false` if it
+    /// was already released.
+    ///
+    /// # Safety
+    /// Native code must not call `callback`'s function pointer afterwards.
+    pub unsafe fn release_callback(&mut self, callback: NativeCallback) -> bool {
+        match self.callbacks.remove(&callback.id) {
+            Some(module) => {
+                module.free_memory();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of callbacks currently registered.
+    pub fn callback_count(&self) -> usize {
+        self.callbacks.len()
+    }
+
     /// Remove a cached cell (e.g. when source code changes).
     pub fn invalidate(&mut self, cell_name: &str) {
         self.cache.remove(cell_name);
@@ -614,8 +797,11 @@ impl JitEngine {
 /// compile. Cells containing unsupported opcodes (e.g. Intrinsic, ToolCall,
 /// NewList, etc.) are filtered out before compilation so we never emit traps
 /// for unsupported operations.
-fn is_cell_jit_compilable(cell: &LirCell) -> bool {
+fn is_cell_jit_compilable(cell: &LirCell, opcodes: &OpcodeRegistry) -> bool {
     cell.instructions.iter().all(|instr| {
+        if instr.op == OpCode::Extension {
+            return opcodes.contains(instr.b);
+        }
         matches!(
             instr.op,
             OpCode::LoadK
@@ -661,6 +847,83 @@ fn is_cell_jit_compilable(cell: &LirCell) -> bool {
     })
 }
 
+/// Pick the cells to compile.
+///
+/// Starts from the cells whose opcodes are all supported, then drops every
+/// cell on a cycle of tail calls through other cells: native code only turns
+/// *self* tail calls into loops, so mutual tail recursion would grow the
+/// native stack without bound, while the interpreter reuses the frame.
+/// Finally drops, until nothing changes, every cell that calls a cell that
+/// won't be compiled, since JIT code can only call other JIT code.
+fn select_jit_cells<'a>(lir: &'a LirModule, opcodes: &OpcodeRegistry) -> Vec<&'a LirCell> {
+    let cell_names: HashSet<&str> = lir.cells.iter().map(|c| c.name.as_str()).collect();
+    // Module cells each cell calls, flagged with whether the call is a tail call.
+    let callees: HashMap<&str, Vec<(String, bool)>> = lir
+        .cells
+        .iter()
+        .map(|cell| {
+            let calls = cell
+                .instructions
+                .iter()
+                .enumerate()
+                .filter(|(_, inst)| matches!(inst.op, OpCode::Call | OpCode::TailCall))
+                .filter_map(|(pc, inst)| {
+                    let name = find_callee_name(cell, &cell.instructions, pc, inst.a)?;
+                    cell_names
+                        .contains(name.as_str())
+                        .then_some((name, inst.op == OpCode::TailCall))
+                })
+                .collect();
+            (cell.name.as_str(), calls)
+        })
+        .collect();
+
+    let mut selected: HashSet<&str> = lir
+        .cells
+        .iter()
+        .filter(|c| is_cell_jit_compilable(c, opcodes))
+        .map(|c| c.name.as_str())
+        .collect();
+    selected.retain(|name| !in_tail_call_cycle(name, &callees));
+    loop {
+        let before = selected.len();
+        let snapshot = selected.clone();
+        selected.retain(|name| {
+            callees[name]
+                .iter()
+                .all(|(callee, _)| snapshot.contains(callee.as_str()))
+        });
+        if selected.len() == before {
+            break;
+        }
+    }
+
+    lir.cells
+        .iter()
+        .filter(|c| selected.contains(c.name.as_str()))
+        .collect()
+}
+
+/// Whether `start` can reach itself through tail calls to other cells.
+fn in_tail_call_cycle(start: &str, callees: &HashMap<&str, Vec<(String, bool)>>) -> bool {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut stack = vec![start];
+    while let Some(name) = stack.pop() {
+        for (callee, is_tail) in callees.get(name).into_iter().flatten() {
+            if !is_tail || callee == name {
+                continue;
+            }
+            if callee == start {
+                return true;
+            }
+            if seen.insert(callee.as_str()) {
+                stack.push(callee.as_str());
+            }
+        }
+    }
+    false
+}
+
 // ---------------------------------------------------------------------------
 // JIT-specific lowering (mirrors lower.rs but targets JITModule)
 // ---------------------------------------------------------------------------
@@ -675,6 +938,7 @@ struct JitLoweredFunction {
     func_id: FuncId,
     param_count: usize,
     returns_string: bool,
+    takes_string: bool,
 }
 
 /// Lower an entire LIR module into Cranelift IR inside the given `JITModule`.
@@ -684,15 +948,11 @@ fn lower_module_jit(
     module: &mut JITModule,
     lir: &LirModule,
     pointer_type: ClifType,
+    opcodes: &OpcodeRegistry,
 ) -> Result<JitLoweredModule, CodegenError> {
     let mut fb_ctx = FunctionBuilderContext::new();
 
-    // Filter to only JIT-compilable cells.
-    let compilable_cells: Vec<&LirCell> = lir
-        .cells
-        .iter()
-        .filter(|c| is_cell_jit_compilable(c))
-        .collect();
+    let compilable_cells = select_jit_cells(lir, opcodes);
 
     if compilable_cells.is_empty() {
         return Ok(JitLoweredModule {
@@ -741,7 +1001,15 @@ fn lower_module_jit(
 
     for cell in &compilable_cells {
         let func_id = func_ids[&cell.name];
-        lower_cell_jit(module, cell, &mut fb_ctx, pointer_type, func_id, &func_ids)?;
+        lower_cell_jit(
+            module,
+            cell,
+            &mut fb_ctx,
+            pointer_type,
+            func_id,
+            &func_ids,
+            opcodes,
+        )?;
         let ret_is_string = cell
             .returns
             .as_deref()
@@ -752,6 +1020,7 @@ fn lower_module_jit(
             func_id,
             param_count: cell.params.len(),
             returns_string: ret_is_string,
+            takes_string: cell.params.iter().any(|p| p.ty == "String"),
         });
     }
 
@@ -839,6 +1108,7 @@ fn lower_cell_jit(
     pointer_type: ClifType,
     func_id: FuncId,
     func_ids: &HashMap<String, FuncId>,
+    opcodes: &OpcodeRegistry,
 ) -> Result<(), CodegenError> {
     let mut sig = module.make_signature();
     for param in &cell.params {
@@ -912,6 +1182,11 @@ fn lower_cell_jit(
     )?;
     let str_drop_ref =
         declare_helper_func(module, &mut func, "jit_rt_string_drop", &[types::I64], &[])?;
+    let depth = CallDepthHelpers {
+        enter: declare_helper_func(module, &mut func, "jit_rt_call_enter", &[], &[types::I64])?,
+        exit: declare_helper_func(module, &mut func, "jit_rt_call_exit", &[], &[types::I64])?,
+        ret_ty: abi_ret,
+    };
 
     // Suppress unused-variable warnings for helpers not yet used in all paths.
     let _ = str_clone_ref;
@@ -1699,8 +1974,7 @@ fn lower_cell_jit(
                                 let arg_reg = base + 1 + i as u8;
                                 args.push(use_var(&mut builder, &vars, arg_reg));
                             }
-                            let call = builder.ins().call(func_ref, &args);
-                            let result = builder.inst_results(call)[0];
+                            let result = emit_counted_call(&mut builder, &depth, func_ref, &args);
                             def_var(&mut builder, &vars, base, result);
                         } else {
                             let zero = builder.ins().iconst(types::I64, 0);
@@ -1784,8 +2058,7 @@ fn lower_cell_jit(
                                 let arg_reg = base + 1 + i as u8;
                                 args.push(use_var(&mut builder, &vars, arg_reg));
                             }
-                            let call = builder.ins().call(func_ref, &args);
-                            let result = builder.inst_results(call)[0];
+                            let result = emit_counted_call(&mut builder, &depth, func_ref, &args);
                             builder.ins().return_(&[result]);
                             terminated = true;
                         } else {
@@ -1810,6 +2083,31 @@ fn lower_cell_jit(
 
             OpCode::Nop => {}
 
+            OpCode::Extension => {
+                let def = opcodes.get(inst.b).ok_or_else(|| {
+                    CodegenError::LoweringError(format!(
+                        "unregistered extension opcode {} in cell '{}'",
+                        inst.b, cell.name
+                    ))
+                })?;
+                let mut ctx = OpContext::new(&mut builder, &vars, *inst);
+                def.lower(&mut ctx).map_err(|e| {
+                    CodegenError::LoweringError(format!(
+                        "extension opcode '{}' in cell '{}': {e}",
+                        def.name(),
+                        cell.name
+                    ))
+                })?;
+                for &(reg, ty) in ctx.written() {
+                    let written_ty = if ty == types::F64 {
+                        JitVarType::Float
+                    } else {
+                        JitVarType::Int
+                    };
+                    var_types.insert(reg as u32, written_ty);
+                }
+            }
+
             // Everything else -> error (should be unreachable due to pre-scan).
             _ => {
                 return Err(CodegenError::LoweringError(format!(
@@ -1840,6 +2138,55 @@ fn lower_cell_jit(
 // Variable helpers
 // ---------------------------------------------------------------------------
 
+/// The call depth helpers declared in the function being lowered, and its
+/// return type (for the value returned while unwinding).
+struct CallDepthHelpers {
+    enter: cranelift_codegen::ir::FuncRef,
+    exit: cranelift_codegen::ir::FuncRef,
+    ret_ty: ClifType,
+}
+
+/// Emit a call to another compiled cell, bracketed by the call depth helpers.
+/// When the depth budget is exhausted, at this call or anywhere beneath it,
+/// the current function returns a zero value at once so the native frames
+/// unwind back to the VM.
+fn emit_counted_call(
+    builder: &mut FunctionBuilder,
+    depth: &CallDepthHelpers,
+    func_ref: cranelift_codegen::ir::FuncRef,
+    args: &[cranelift_codegen::ir::Value],
+) -> cranelift_codegen::ir::Value {
+    let call_block = builder.create_block();
+    let unwind_block = builder.create_block();
+    let cont_block = builder.create_block();
+
+    let enter = builder.ins().call(depth.enter, &[]);
+    let entered = builder.inst_results(enter)[0];
+    builder
+        .ins()
+        .brif(entered, call_block, &[], unwind_block, &[]);
+
+    builder.switch_to_block(call_block);
+    let call = builder.ins().call(func_ref, args);
+    let result = builder.inst_results(call)[0];
+    let exit = builder.ins().call(depth.exit, &[]);
+    let unwinding = builder.inst_results(exit)[0];
+    builder
+        .ins()
+        .brif(unwinding, unwind_block, &[], cont_block, &[]);
+
+    builder.switch_to_block(unwind_block);
+    let zero = if depth.ret_ty == types::F64 {
+        builder.ins().f64const(0.0)
+    } else {
+        builder.ins().iconst(depth.ret_ty, 0)
+    };
+    builder.ins().return_(&[zero]);
+
+    builder.switch_to_block(cont_block);
+    result
+}
+
 /// Declare an external helper function in both the JIT module and the current
 /// Cranelift function, returning a `FuncRef` that can be used with `builder.ins().call()`.
 fn declare_helper_func(
@@ -1939,6 +2286,8 @@ fn lower_constant(
 #[cfg(all(test, target_arch = "x86_64"))]
 mod tests {
     use super::*;
+    use crate::ffi::CType;
+    use crate::opcodes::OpcodeDef;
     use lumen_compiler::compiler::lir::{
         Constant, Instruction, LirCell, LirModule, LirParam, OpCode,
     };
@@ -1960,6 +2309,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: Vec::new(),
+                memoizable: false,
             }],
             tools: Vec::new(),
             policies: Vec::new(),
@@ -1968,6 +2318,7 @@ mod tests {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
@@ -1985,6 +2336,7 @@ mod tests {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
@@ -2095,6 +2447,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2125,6 +2478,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 1, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2166,6 +2520,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2223,6 +2578,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 1, 1, 0),  // 9: return r1
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2296,6 +2652,7 @@ mod tests {
                 Instruction::abc(OpCode::TailCall, 5, 3, 1), // 10: tail-call
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2335,6 +2692,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 1, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let main_cell = LirCell {
@@ -2350,6 +2708,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let lir = make_module_with_cells(vec![double_cell, main_cell]);
@@ -2468,6 +2827,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 3, 1, 0),    // 7: return r3
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2506,6 +2866,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let answer_cell = LirCell {
@@ -2519,6 +2880,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let lir = make_module_with_cells(vec![add_cell, answer_cell]);
@@ -2566,6 +2928,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2613,6 +2976,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2646,6 +3010,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2685,6 +3050,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2718,6 +3084,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2751,6 +3118,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2780,6 +3148,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2809,6 +3178,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2841,6 +3211,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 1, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2881,6 +3252,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2948,6 +3320,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),  // 11: return r0
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -2997,6 +3370,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 3, 1, 0),  // 7: return r3
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -3032,6 +3406,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -3071,6 +3446,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 4, 1, 0), // return "abc"
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -3120,6 +3496,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 3, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -3146,6 +3523,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: Vec::new(),
+                memoizable: false,
             },
             LirCell {
                 name: "int_cell".to_string(),
@@ -3158,6 +3536,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: Vec::new(),
+                memoizable: false,
             },
         ]);
 
@@ -3187,6 +3566,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 1, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -3227,6 +3607,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0), // return r0
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }]);
 
         let settings = CodegenSettings::default();
@@ -3239,4 +3620,174 @@ mod tests {
         let s = unsafe { jit_take_string(raw) };
         assert_eq!(s, "hello world");
     }
+
+    /// `cell descending(a: Int, b: Int) -> Int = b - a`, a comparator that
+    /// orders larger values first.
+    fn descending_comparator_module() -> LirModule {
+        let param = |name: &str, register| LirParam {
+            name: name.to_string(),
+            ty: "Int".to_string(),
+            register,
+            variadic: false,
+        };
+        make_module_with_cells(vec![LirCell {
+            name: "descending".to_string(),
+            params: vec![param("a", 0), param("b", 1)],
+            returns: Some("Int".to_string()),
+            registers: 3,
+            constants: vec![],
+            instructions: vec![
+                Instruction::abc(OpCode::Sub, 2, 1, 0),
+                Instruction::abc(OpCode::Return, 2, 1, 0),
+            ],
+            effect_handler_metas: Vec::new(),
+            memoizable: false,
+        }])
+    }
+
+    /// A native sort taking a C comparator, standing in for APIs like `qsort`.
+    extern "C" fn native_insertion_sort(
+        values: *mut i64,
+        len: usize,
+        compare: extern "C" fn(i64, i64) -> i32,
+    ) {
+        let values = unsafe { std::slice::from_raw_parts_mut(values, len) };
+        for i in 1..len {
+            let mut j = i;
+            while j > 0 && compare(values[j - 1], values[j]) > 0 {
+                values.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+
+    #[test]
+    fn callback_round_trips_through_native_code() {
+        let mut engine = JitEngine::new(CodegenSettings::default(), 0);
+        engine
+            .compile_module(&descending_comparator_module())
+            .expect("compile");
+
+        let signature = CallbackSignature::new(vec![CType::I64, CType::I64], CType::I32);
+        let callback = engine
+            .register_callback("descending", &signature)
+            .expect("register callback");
+        assert_eq!(engine.callback_count(), 1);
+
+        // Invalidating the cell must not invalidate the callback.
+        engine.invalidate("descending");
+
+        let compare: extern "C" fn(i64, i64) -> i32 =
+            unsafe { std::mem::transmute(callback.as_ptr()) };
+        let mut values = vec![3, -7, 42, 0, 5];
+        native_insertion_sort(values.as_mut_ptr(), values.len(), compare);
+        assert_eq!(values, vec![42, 5, 3, 0, -7]);
+
+        assert!(unsafe { engine.release_callback(callback) });
+        assert!(!unsafe { engine.release_callback(callback) });
+        assert_eq!(engine.callback_count(), 0);
+    }
+
+    #[test]
+    fn callback_widens_narrow_integer_arguments() {
+        let mut engine = JitEngine::new(CodegenSettings::default(), 0);
+        engine
+            .compile_module(&descending_comparator_module())
+            .expect("compile");
+
+        let signature = CallbackSignature::new(vec![CType::I32, CType::I32], CType::I64);
+        let callback = engine
+            .register_callback("descending", &signature)
+            .expect("register callback");
+        let compare: extern "C" fn(i32, i32) -> i64 =
+            unsafe { std::mem::transmute(callback.as_ptr()) };
+        assert_eq!(compare(-2, 3), 5);
+        assert_eq!(compare(3, -2), -5);
+    }
+
+    #[test]
+    fn callback_signature_must_match_cell() {
+        let mut engine = JitEngine::new(CodegenSettings::default(), 0);
+        engine
+            .compile_module(&descending_comparator_module())
+            .expect("compile");
+
+        let arity = CallbackSignature::new(vec![CType::I64], CType::I64);
+        let err = engine.register_callback("descending", &arity).unwrap_err();
+        assert!(err.to_string().contains("has 1 parameters"), "{err}");
+
+        let float = CallbackSignature::new(vec![CType::F64, CType::I64], CType::I64);
+        let err = engine.register_callback("descending", &float).unwrap_err();
+        assert!(err.to_string().contains("parameter 0"), "{err}");
+
+        let missing = CallbackSignature::new(vec![], CType::Void);
+        assert!(matches!(
+            engine.register_callback("nope", &missing),
+            Err(JitError::CellNotFound(_))
+        ));
+        assert_eq!(engine.callback_count(), 0);
+    }
+
+    /// Extension opcode 7: double register A in place.
+    fn double_opcode() -> OpcodeDef {
+        OpcodeDef::new(7, "double", |ctx| {
+            let reg = ctx.instruction().a;
+            let value = ctx.read(reg)?;
+            let doubled = ctx.builder().ins().iadd(value, value);
+            ctx.write(reg, doubled)
+        })
+    }
+
+    /// `cell twice(x: Int) -> Int` built from extension opcode 7.
+    fn extension_module() -> LirModule {
+        make_module_with_cells(vec![LirCell {
+            name: "twice".to_string(),
+            params: vec![LirParam {
+                name: "x".to_string(),
+                ty: "Int".to_string(),
+                register: 0,
+                variadic: false,
+            }],
+            returns: Some("Int".to_string()),
+            registers: 2,
+            constants: vec![],
+            instructions: vec![
+                Instruction::abc(OpCode::Move, 1, 0, 0),
+                Instruction::abc(OpCode::Extension, 1, 7, 0),
+                Instruction::abc(OpCode::Return, 1, 1, 0),
+            ],
+            effect_handler_metas: Vec::new(),
+            memoizable: false,
+        }])
+    }
+
+    #[test]
+    fn registered_extension_opcode_is_jit_compiled() {
+        let mut engine = JitEngine::new(CodegenSettings::default(), 0);
+        assert!(engine.opcodes_mut().register(double_opcode()).is_none());
+        engine.compile_module(&extension_module()).expect("compile");
+
+        assert_eq!(engine.execute_jit_unary("twice", 21).unwrap(), 42);
+        assert_eq!(engine.execute_jit_unary("twice", -4).unwrap(), -8);
+    }
+
+    #[test]
+    fn unregistered_extension_opcode_is_left_to_the_interpreter() {
+        let mut engine = JitEngine::new(CodegenSettings::default(), 0);
+        engine.compile_module(&extension_module()).expect("compile");
+        assert!(!engine.is_compiled("twice"));
+    }
+
+    #[test]
+    fn extension_lowering_errors_name_the_opcode() {
+        let mut engine = JitEngine::new(CodegenSettings::default(), 0);
+        engine
+            .opcodes_mut()
+            .register(OpcodeDef::new(7, "broken", |ctx| {
+                let value = ctx.builder().ins().f64const(1.5);
+                ctx.write(1, value)
+            }));
+        let err = engine.compile_module(&extension_module()).unwrap_err();
+        assert!(err.to_string().contains("'broken'"), "{err}");
+    }
 }
@@ -2,12 +2,17 @@
 //!
 //! Lowers LIR bytecode modules to native machine code.
 
+pub mod aot;
 pub mod bench_programs;
 pub mod context;
+pub mod debug_info;
 pub mod emit;
 pub mod ffi;
 pub mod jit;
 pub mod lower;
+pub mod opcodes;
 pub mod types;
 pub mod wasm;
 pub mod wit;
+
+pub use opcodes::{OpContext, OpcodeDef, OpcodeRegistry};
@@ -28,7 +28,7 @@ use std::collections::{BTreeSet, HashMap};
 
 use cranelift_codegen::ir::condcodes::IntCC;
 use cranelift_codegen::ir::types;
-use cranelift_codegen::ir::{AbiParam, InstBuilder, Type as ClifType, Value};
+use cranelift_codegen::ir::{AbiParam, InstBuilder, SourceLoc, Type as ClifType, Value};
 use cranelift_codegen::Context;
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
 use cranelift_module::{FuncId, Linkage, Module};
@@ -51,6 +51,21 @@ pub struct LoweredModule {
 pub struct LoweredFunction {
     pub name: String,
     pub func_id: FuncId,
+    /// Size of the function's machine code in bytes.
+    pub code_size: u32,
+    /// Machine code generated for each LIR instruction, ordered by offset.
+    pub code_ranges: Vec<CodeRange>,
+}
+
+/// A span of a function's machine code lowered from one LIR instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeRange {
+    /// Offset of the first byte, relative to the start of the function.
+    pub start: u32,
+    /// Offset one past the last byte.
+    pub end: u32,
+    /// Index into the cell's `instructions`.
+    pub instruction: usize,
 }
 
 /// Lower an entire LIR module into Cranelift IR inside the given `ObjectModule`.
@@ -92,10 +107,13 @@ pub fn lower_module(
 
     for cell in &lir.cells {
         let func_id = func_ids[&cell.name];
-        lower_cell(module, cell, &mut fb_ctx, pointer_type, func_id, &func_ids)?;
+        let (code_size, code_ranges) =
+            lower_cell(module, cell, &mut fb_ctx, pointer_type, func_id, &func_ids)?;
         lowered.functions.push(LoweredFunction {
             name: cell.name.clone(),
             func_id,
+            code_size,
+            code_ranges,
         });
     }
 
@@ -162,7 +180,7 @@ fn lower_cell(
     pointer_type: ClifType,
     func_id: FuncId,
     func_ids: &HashMap<String, FuncId>,
-) -> Result<(), CodegenError> {
+) -> Result<(u32, Vec<CodeRange>), CodegenError> {
     // Re-build the signature.
     let mut sig = module.make_signature();
     for _param in &cell.params {
@@ -270,6 +288,9 @@ fn lower_cell(
             builder.switch_to_block(target_block);
             terminated = false;
         }
+        // Tag the IR with the instruction index so debug info can map
+        // machine code back to the LIR source map.
+        builder.set_srcloc(SourceLoc::new(pc as u32));
 
         if terminated {
             continue;
@@ -613,7 +634,22 @@ fn lower_cell(
         .define_function(func_id, &mut ctx)
         .map_err(|e| CodegenError::LoweringError(format!("define_function({}): {e}", cell.name)))?;
 
-    Ok(())
+    let compiled = ctx.compiled_code().ok_or_else(|| {
+        CodegenError::LoweringError(format!("no compiled code for {}", cell.name))
+    })?;
+    let code_ranges = compiled
+        .buffer
+        .get_srclocs_sorted()
+        .iter()
+        .filter(|loc| !loc.loc.is_default())
+        .map(|loc| CodeRange {
+            start: loc.start,
+            end: loc.end,
+            instruction: loc.loc.bits() as usize,
+        })
+        .collect();
+
+    Ok((compiled.code_buffer().len() as u32, code_ranges))
 }
 
 // ---------------------------------------------------------------------------
@@ -744,6 +780,7 @@ mod tests {
                 constants,
                 instructions,
                 effect_handler_metas: Vec::new(),
+                memoizable: false,
             }],
             tools: Vec::new(),
             policies: Vec::new(),
@@ -752,6 +789,7 @@ mod tests {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
@@ -769,6 +807,7 @@ mod tests {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
@@ -1091,6 +1130,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 1, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let main_cell = LirCell {
@@ -1106,6 +1146,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let lir = make_multi_cell_module(vec![double_cell, main_cell]);
@@ -1143,6 +1184,7 @@ mod tests {
             constants: vec![],
             instructions: vec![Instruction::abc(OpCode::Return, 0, 1, 0)],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let main_cell = LirCell {
@@ -1157,6 +1199,7 @@ mod tests {
                 Instruction::abc(OpCode::TailCall, 0, 1, 1),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let lir = make_multi_cell_module(vec![identity_cell, main_cell]);
@@ -1351,6 +1394,7 @@ end
                 Instruction::abc(OpCode::TailCall, 3, 1, 1), // 9: tail-call countdown(r4)
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         // Verify TCO detection
@@ -1391,6 +1435,7 @@ end
             constants: vec![],
             instructions: vec![Instruction::abc(OpCode::Return, 0, 1, 0)],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         let caller = LirCell {
@@ -1410,6 +1455,7 @@ end
                 Instruction::abc(OpCode::TailCall, 1, 1, 1), // tail-call helper(x)
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         // Verify: caller does NOT have self-tail-calls
@@ -1452,6 +1498,7 @@ end
                 Instruction::abc(OpCode::TailCall, 1, 1, 1),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
         assert!(has_self_tail_call(&self_call));
 
@@ -1473,6 +1520,7 @@ end
                 Instruction::abc(OpCode::TailCall, 1, 1, 1),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
         assert!(!has_self_tail_call(&other_call));
 
@@ -1488,6 +1536,7 @@ end
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
         assert!(!has_self_tail_call(&no_tc));
     }
@@ -1550,6 +1599,7 @@ end
                 Instruction::abc(OpCode::TailCall, 5, 3, 1), // 10: tail-call fib_acc(r6, r7, r8)
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
 
         assert!(has_self_tail_call(&cell));
@@ -0,0 +1,230 @@
+//! Embedder-defined opcodes.
+//!
+//! LIR reserves [`OpCode::Extension`](lumen_compiler::compiler::lir::OpCode::Extension)
+//! for operations the compiler itself doesn't know about. Its `B` operand
+//! selects an extension id, and `A` and `C` are free for the extension to use
+//! as register operands.
+//!
+//! An [`OpcodeRegistry`] maps extension ids to [`OpcodeDef`]s. Each definition
+//! carries a lowering closure that emits Cranelift IR for one instruction
+//! through an [`OpContext`]. The JIT consults its registry (see
+//! [`JitEngine::opcodes_mut`](crate::jit::JitEngine::opcodes_mut)) whenever it
+//! meets an extension opcode; cells that use an unregistered id are left to
+//! the interpreter, which rejects them at runtime.
+//!
+//! ```ignore
+//! let mut engine = JitEngine::new(CodegenSettings::default(), 0);
+//! engine.opcodes_mut().register(OpcodeDef::new(1, "double", |ctx| {
+//!     let reg = ctx.instruction().a;
+//!     let value = ctx.read(reg)?;
+//!     let doubled = ctx.builder().ins().iadd(value, value);
+//!     ctx.write(reg, doubled)
+//! }));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use cranelift_codegen::ir::{Type as ClifType, Value};
+use cranelift_frontend::{FunctionBuilder, Variable};
+
+use lumen_compiler::compiler::lir::Instruction;
+
+use crate::emit::CodegenError;
+
+/// Lowering hook for an extension opcode.
+pub type LowerFn = dyn Fn(&mut OpContext<'_, '_>) -> Result<(), CodegenError> + Send + Sync;
+
+/// Definition of one extension opcode.
+#[derive(Clone)]
+pub struct OpcodeDef {
+    id: u8,
+    name: String,
+    lower: Arc<LowerFn>,
+}
+
+impl OpcodeDef {
+    /// Define extension `id` (the `B` operand of `OpCode::Extension`), lowered
+    /// by `lower`.
+    pub fn new(
+        id: u8,
+        name: impl Into<String>,
+        lower: impl Fn(&mut OpContext<'_, '_>) -> Result<(), CodegenError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            lower: Arc::new(lower),
+        }
+    }
+
+    /// The extension id.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Human-readable name, used in diagnostics.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Run the lowering hook for one instruction.
+    pub fn lower(&self, ctx: &mut OpContext<'_, '_>) -> Result<(), CodegenError> {
+        (self.lower)(ctx)
+    }
+}
+
+impl fmt::Debug for OpcodeDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpcodeDef")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extension opcodes known to a code generator, keyed by id.
+#[derive(Debug, Clone, Default)]
+pub struct OpcodeRegistry {
+    defs: HashMap<u8, OpcodeDef>,
+}
+
+impl OpcodeRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `def`, returning the definition it replaces, if any.
+    pub fn register(&mut self, def: OpcodeDef) -> Option<OpcodeDef> {
+        self.defs.insert(def.id, def)
+    }
+
+    /// Look up the definition for extension `id`.
+    pub fn get(&self, id: u8) -> Option<&OpcodeDef> {
+        self.defs.get(&id)
+    }
+
+    /// Whether extension `id` has a definition.
+    pub fn contains(&self, id: u8) -> bool {
+        self.defs.contains_key(&id)
+    }
+
+    /// Number of registered extensions.
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    /// Whether no extensions are registered.
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+}
+
+/// What a lowering hook sees: the instruction being lowered, the function
+/// builder positioned at it, and the cell's registers.
+///
+/// Registers hold `i64` values, except registers the cell uses for floats,
+/// which hold `f64`. Values written back must have the register's type.
+pub struct OpContext<'a, 'f> {
+    builder: &'a mut FunctionBuilder<'f>,
+    vars: &'a [Variable],
+    instruction: Instruction,
+    written: Vec<(u8, ClifType)>,
+}
+
+impl<'a, 'f> OpContext<'a, 'f> {
+    pub(crate) fn new(
+        builder: &'a mut FunctionBuilder<'f>,
+        vars: &'a [Variable],
+        instruction: Instruction,
+    ) -> Self {
+        Self {
+            builder,
+            vars,
+            instruction,
+            written: Vec::new(),
+        }
+    }
+
+    /// The instruction being lowered.
+    pub fn instruction(&self) -> Instruction {
+        self.instruction
+    }
+
+    /// Number of registers in the cell.
+    pub fn register_count(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// The builder, for emitting instructions.
+    pub fn builder(&mut self) -> &mut FunctionBuilder<'f> {
+        self.builder
+    }
+
+    /// Read register `reg`.
+    pub fn read(&mut self, reg: u8) -> Result<Value, CodegenError> {
+        let var = self.var(reg)?;
+        Ok(self.builder.use_var(var))
+    }
+
+    /// Write `value` to register `reg`.
+    pub fn write(&mut self, reg: u8, value: Value) -> Result<(), CodegenError> {
+        let var = self.var(reg)?;
+        let ty = self.builder.func.dfg.value_type(value);
+        self.builder.try_def_var(var, value).map_err(|e| {
+            CodegenError::LoweringError(format!(
+                "extension opcode {} wrote r{reg}: {e}",
+                self.instruction.b
+            ))
+        })?;
+        self.written.push((reg, ty));
+        Ok(())
+    }
+
+    /// Registers written by the hook, with the type of the value written.
+    pub(crate) fn written(&self) -> &[(u8, ClifType)] {
+        &self.written
+    }
+
+    fn var(&self, reg: u8) -> Result<Variable, CodegenError> {
+        self.vars.get(reg as usize).copied().ok_or_else(|| {
+            CodegenError::LoweringError(format!(
+                "extension opcode {} used register r{reg}, but the cell has {} registers",
+                self.instruction.b,
+                self.vars.len()
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop(id: u8, name: &str) -> OpcodeDef {
+        OpcodeDef::new(id, name, |_| Ok(()))
+    }
+
+    #[test]
+    fn register_and_look_up() {
+        let mut registry = OpcodeRegistry::new();
+        assert!(registry.is_empty());
+        assert!(registry.register(noop(3, "three")).is_none());
+        assert!(registry.contains(3));
+        assert!(!registry.contains(4));
+        assert_eq!(registry.get(3).map(OpcodeDef::name), Some("three"));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn registering_an_id_again_replaces_it() {
+        let mut registry = OpcodeRegistry::new();
+        registry.register(noop(3, "old"));
+        let replaced = registry.register(noop(3, "new"));
+        assert_eq!(replaced.as_ref().map(OpcodeDef::name), Some("old"));
+        assert_eq!(registry.get(3).map(OpcodeDef::name), Some("new"));
+        assert_eq!(registry.len(), 1);
+    }
+}
@@ -33,6 +33,7 @@ pub fn lumen_type_to_cl_type(ty: &LumenType, pointer_type: ClifType) -> ClifType
         | LumenType::Union(_)
         | LumenType::Tuple(_)
         | LumenType::Set(_)
+        | LumenType::Array(_, _)
         | LumenType::Fn(_, _)
         | LumenType::Generic(_)
         | LumenType::TypeRef(_, _)
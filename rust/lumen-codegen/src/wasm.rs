@@ -18,8 +18,20 @@
 //!   opcodes.
 //!
 //! The public entry point is [`compile_to_wasm`].
+//!
+//! ## SIMD
+//!
+//! With [`WasmOptions::simd`] set, independent element-wise integer operations
+//! in straight-line code (such as `[a[0] + b[0], a[1] + b[1], ...]` over a
+//! fixed-size array) are paired into `i64x2` lanes and emitted as `v128`
+//! instructions. Pairing happens within a basic block, including loop bodies;
+//! loops are not unrolled, so a loop that handles one element per iteration
+//! stays scalar. Anything that doesn't fit the pattern stays scalar. The
+//! output then needs a runtime with the WebAssembly SIMD proposal.
+
+use std::collections::{BTreeMap, BTreeSet};
 
-use lumen_compiler::compiler::lir::{Constant, LirCell, LirModule, OpCode};
+use lumen_compiler::compiler::lir::{Constant, Instruction, LirCell, LirModule, OpCode};
 
 use crate::emit::CodegenError;
 
@@ -58,18 +70,35 @@ impl std::fmt::Display for WasmTarget {
 // WasmCodegen — the encoder
 // ---------------------------------------------------------------------------
 
+/// Options controlling wasm code generation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WasmOptions {
+    /// Lower element-wise integer operations to `v128` SIMD instructions.
+    pub simd: bool,
+}
+
 /// Compiles an [`LirModule`] to a WebAssembly binary.
 ///
 /// This struct accumulates section data and produces a valid `.wasm` file
 /// conforming to the WebAssembly 1.0 binary format.
 pub struct WasmCodegen {
     target: WasmTarget,
+    options: WasmOptions,
 }
 
 impl WasmCodegen {
     /// Create a new WASM codegen instance for the given target.
     pub fn new(target: WasmTarget) -> Self {
-        Self { target }
+        Self {
+            target,
+            options: WasmOptions::default(),
+        }
+    }
+
+    /// Enable or disable SIMD lowering.
+    pub fn with_simd(mut self, simd: bool) -> Self {
+        self.options.simd = simd;
+        self
     }
 
     /// Return the target this codegen is configured for.
@@ -79,7 +108,7 @@ impl WasmCodegen {
 
     /// Compile an LIR module to WASM binary bytes.
     pub fn compile(&self, lir: &LirModule) -> Result<Vec<u8>, CodegenError> {
-        compile_to_wasm(lir, self.target)
+        compile_to_wasm_with_options(lir, self.target, self.options)
     }
 }
 
@@ -91,7 +120,16 @@ impl WasmCodegen {
 ///
 /// Returns the raw `.wasm` bytes. The `target` selects whether WASI imports
 /// are assumed (currently informational — no WASI imports are emitted yet).
-pub fn compile_to_wasm(lir: &LirModule, _target: WasmTarget) -> Result<Vec<u8>, CodegenError> {
+pub fn compile_to_wasm(lir: &LirModule, target: WasmTarget) -> Result<Vec<u8>, CodegenError> {
+    compile_to_wasm_with_options(lir, target, WasmOptions::default())
+}
+
+/// [`compile_to_wasm`] with explicit [`WasmOptions`].
+pub fn compile_to_wasm_with_options(
+    lir: &LirModule,
+    _target: WasmTarget,
+    options: WasmOptions,
+) -> Result<Vec<u8>, CodegenError> {
     if lir.cells.is_empty() {
         return Err(CodegenError::LoweringError(
             "cannot compile empty module to wasm".to_string(),
@@ -124,7 +162,7 @@ pub fn compile_to_wasm(lir: &LirModule, _target: WasmTarget) -> Result<Vec<u8>,
     emit_section(&mut wasm, 7, &export_section);
 
     // ---- 10. Code section (id=10) ----------------------------------------
-    let code_section = encode_code_section(lir)?;
+    let code_section = encode_code_section(lir, options)?;
     emit_section(&mut wasm, 10, &code_section);
 
     Ok(wasm)
@@ -233,12 +271,12 @@ fn encode_export_section(cells: &[LirCell]) -> Vec<u8> {
 }
 
 /// Encode the code section: function bodies.
-fn encode_code_section(lir: &LirModule) -> Result<Vec<u8>, CodegenError> {
+fn encode_code_section(lir: &LirModule, options: WasmOptions) -> Result<Vec<u8>, CodegenError> {
     let mut section_buf = Vec::new();
     encode_u32_leb128(&mut section_buf, lir.cells.len() as u32);
 
     for cell in &lir.cells {
-        let body = encode_function_body(cell, lir)?;
+        let body = encode_function_body(cell, lir, options)?;
         encode_u32_leb128(&mut section_buf, body.len() as u32);
         section_buf.extend_from_slice(&body);
     }
@@ -252,7 +290,11 @@ fn encode_code_section(lir: &LirModule) -> Result<Vec<u8>, CodegenError> {
 ///   local declarations (registers beyond params)
 ///   instruction bytecodes
 ///   0x0B (end)
-fn encode_function_body(cell: &LirCell, _lir: &LirModule) -> Result<Vec<u8>, CodegenError> {
+fn encode_function_body(
+    cell: &LirCell,
+    _lir: &LirModule,
+    options: WasmOptions,
+) -> Result<Vec<u8>, CodegenError> {
     let mut buf = Vec::new();
 
     // Local declarations: we need (registers - params) additional locals, all i64.
@@ -260,17 +302,36 @@ fn encode_function_body(cell: &LirCell, _lir: &LirModule) -> Result<Vec<u8>, Cod
     let num_regs = (cell.registers as usize).max(num_params);
     let extra_locals = num_regs.saturating_sub(num_params);
 
+    let simd_pairs = if options.simd {
+        plan_simd_pairs(&cell.instructions)
+    } else {
+        BTreeMap::new()
+    };
+    let deferred: BTreeSet<usize> = simd_pairs.values().copied().collect();
+    // A single v128 scratch local, placed after the i64 registers.
+    let vector_local = num_regs as u32;
+
+    let groups = (extra_locals > 0) as u32 + !simd_pairs.is_empty() as u32;
+    encode_u32_leb128(&mut buf, groups);
     if extra_locals > 0 {
-        // One local declaration: count of i64 locals.
-        encode_u32_leb128(&mut buf, 1); // 1 group of locals
         encode_u32_leb128(&mut buf, extra_locals as u32);
         buf.push(0x7E); // i64
-    } else {
-        encode_u32_leb128(&mut buf, 0); // 0 local declaration groups
+    }
+    if !simd_pairs.is_empty() {
+        encode_u32_leb128(&mut buf, 1);
+        buf.push(0x7B); // v128
     }
 
     // Translate LIR instructions to wasm opcodes.
-    for inst in &cell.instructions {
+    for (pc, inst) in cell.instructions.iter().enumerate() {
+        if deferred.contains(&pc) {
+            // Computed in lane 0 when its partner is reached.
+            continue;
+        }
+        if let Some(&first) = simd_pairs.get(&pc) {
+            emit_simd_pair(&mut buf, &cell.instructions[first], inst, vector_local);
+            continue;
+        }
         match inst.op {
             OpCode::LoadK => {
                 let a = inst.a;
@@ -459,6 +520,137 @@ fn encode_function_body(cell: &LirCell, _lir: &LirModule) -> Result<Vec<u8>, Cod
     Ok(buf)
 }
 
+// ---------------------------------------------------------------------------
+// SIMD lowering
+// ---------------------------------------------------------------------------
+
+/// `i64x2` instruction (after the 0xFD prefix) for a binary LIR opcode that
+/// can run lane-wise with the same wrapping semantics as the scalar form.
+fn simd_binop(op: OpCode) -> Option<u32> {
+    match op {
+        OpCode::Add => Some(0xCE),    // i64x2.add
+        OpCode::Sub => Some(0xD1),    // i64x2.sub
+        OpCode::Mul => Some(0xD5),    // i64x2.mul
+        OpCode::BitAnd => Some(0x4E), // v128.and
+        OpCode::BitOr => Some(0x50),  // v128.or
+        OpCode::BitXor => Some(0x51), // v128.xor
+        _ => None,
+    }
+}
+
+/// Registers read and written by an instruction with no side effects beyond
+/// its destination register, or `None` for anything else.
+fn straight_line_effects(inst: &Instruction) -> Option<(Vec<u8>, u8)> {
+    match inst.op {
+        OpCode::LoadK | OpCode::LoadInt | OpCode::LoadBool => Some((vec![], inst.a)),
+        OpCode::Move | OpCode::Neg | OpCode::Not => Some((vec![inst.b], inst.a)),
+        OpCode::GetIndex
+        | OpCode::Add
+        | OpCode::Sub
+        | OpCode::Mul
+        | OpCode::Div
+        | OpCode::Mod
+        | OpCode::FloorDiv
+        | OpCode::BitAnd
+        | OpCode::BitOr
+        | OpCode::BitXor
+        | OpCode::Shl
+        | OpCode::Shr
+        | OpCode::Eq
+        | OpCode::Lt
+        | OpCode::Le
+        | OpCode::And
+        | OpCode::Or => Some((vec![inst.b, inst.c], inst.a)),
+        _ => None,
+    }
+}
+
+/// Instructions that control can reach other than by falling through.
+fn jump_targets(instructions: &[Instruction]) -> BTreeSet<usize> {
+    let mut targets = BTreeSet::new();
+    for (pc, inst) in instructions.iter().enumerate() {
+        let next = pc as i64 + 1;
+        let target = match inst.op {
+            OpCode::Jmp | OpCode::Break | OpCode::Continue => next + inst.sax_val() as i64,
+            OpCode::Loop => next + inst.sbx() as i64,
+            OpCode::ForPrep => next + inst.bx() as i64,
+            OpCode::ForLoop => next - inst.bx() as i64,
+            OpCode::Test => next + 1,
+            _ => continue,
+        };
+        targets.insert(target as usize);
+    }
+    targets
+}
+
+/// Pair up independent instances of the same element-wise operation so each
+/// pair can run as one `i64x2` instruction. Returns second index → first.
+///
+/// The first instruction is sunk to its partner's position, so nothing in
+/// between may read its result or overwrite its operands or destination, and
+/// the partner must not depend on it. Pairs never span a jump target, so
+/// inside a loop only operations from the same iteration are paired.
+fn plan_simd_pairs(instructions: &[Instruction]) -> BTreeMap<usize, usize> {
+    let mut pairs = BTreeMap::new();
+    let targets = jump_targets(instructions);
+    let mut taken = BTreeSet::new();
+
+    for (i, first) in instructions.iter().enumerate() {
+        if taken.contains(&i) || simd_binop(first.op).is_none() {
+            continue;
+        }
+        for (j, next) in instructions.iter().enumerate().skip(i + 1) {
+            if targets.contains(&j) {
+                break;
+            }
+            let Some((reads, write)) = straight_line_effects(next) else {
+                break;
+            };
+            let uses_result = reads.contains(&first.a) || write == first.a;
+            if next.op == first.op && !taken.contains(&j) && !uses_result {
+                pairs.insert(j, i);
+                taken.insert(i);
+                taken.insert(j);
+                break;
+            }
+            if uses_result || write == first.b || write == first.c {
+                break;
+            }
+        }
+    }
+    pairs
+}
+
+/// Emit two independent binary operations as a single `i64x2` operation,
+/// packing their operands into lanes 0 and 1 and unpacking the results.
+fn emit_simd_pair(buf: &mut Vec<u8>, first: &Instruction, second: &Instruction, vector: u32) {
+    emit_i64x2_pack(buf, first.b, second.b);
+    emit_i64x2_pack(buf, first.c, second.c);
+    let op = simd_binop(first.op).expect("paired instructions have a SIMD form");
+    emit_simd_op(buf, op);
+    emit_local_set(buf, vector);
+    for (lane, dest) in [(0u8, first.a), (1u8, second.a)] {
+        emit_local_get(buf, vector);
+        emit_simd_op(buf, 0x1D); // i64x2.extract_lane
+        buf.push(lane);
+        emit_local_set(buf, dest as u32);
+    }
+}
+
+/// Push a v128 holding the i64 registers `lo` and `hi` as lanes 0 and 1.
+fn emit_i64x2_pack(buf: &mut Vec<u8>, lo: u8, hi: u8) {
+    emit_local_get(buf, lo as u32);
+    emit_simd_op(buf, 0x12); // i64x2.splat
+    emit_local_get(buf, hi as u32);
+    emit_simd_op(buf, 0x1E); // i64x2.replace_lane
+    buf.push(1);
+}
+
+fn emit_simd_op(buf: &mut Vec<u8>, op: u32) {
+    buf.push(0xFD); // SIMD prefix
+    encode_u32_leb128(buf, op);
+}
+
 // ---------------------------------------------------------------------------
 // Wasm instruction helpers
 // ---------------------------------------------------------------------------
@@ -592,6 +784,7 @@ mod tests {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
@@ -620,6 +813,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }
     }
 
@@ -635,6 +829,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }
     }
 
@@ -731,6 +926,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 7, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
         let lir = empty_lir_module(vec![cell]);
         let bytes =
@@ -768,6 +964,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
         let lir = empty_lir_module(vec![cell]);
         let bytes =
@@ -804,6 +1001,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 2, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
         let lir = empty_lir_module(vec![cell]);
         let bytes =
@@ -871,10 +1069,175 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         };
         let lir = empty_lir_module(vec![cell]);
         let bytes = compile_to_wasm(&lir, WasmTarget::Wasm32Unknown)
             .expect("float constant should compile");
         assert_eq!(&bytes[0..4], b"\0asm");
     }
+
+    // -- SIMD lowering -----------------------------------------------------
+
+    const I64X2_ADD: [u8; 3] = [0xFD, 0xCE, 0x01];
+
+    fn contains_seq(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    fn elementwise_add_module() -> LirModule {
+        let source = "# simd\n\n```lumen\ncell add4(a: list[Int], b: list[Int]) -> list[Int]\n  return [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]]\nend\n```\n";
+        lumen_compiler::compile(source).expect("compilation should succeed")
+    }
+
+    #[test]
+    fn simd_lowers_elementwise_array_add_to_v128() {
+        let lir = elementwise_add_module();
+        let options = WasmOptions { simd: true };
+        let bytes = compile_to_wasm_with_options(&lir, WasmTarget::Wasm32Unknown, options)
+            .expect("simd add should compile");
+        assert!(contains_seq(&bytes, &I64X2_ADD), "expected i64x2.add");
+        // Four lanes of additions become two vector adds.
+        let count = bytes.windows(3).filter(|w| *w == I64X2_ADD).count();
+        assert_eq!(count, 2);
+        // The body declares a v128 scratch local.
+        assert!(bytes.contains(&0x7B));
+    }
+
+    #[test]
+    fn scalar_lowering_is_the_default() {
+        let lir = elementwise_add_module();
+        let bytes = compile_to_wasm(&lir, WasmTarget::Wasm32Unknown).expect("should compile");
+        assert!(!contains_seq(&bytes, &I64X2_ADD));
+        let via_struct = WasmCodegen::new(WasmTarget::Wasm32Unknown)
+            .with_simd(true)
+            .compile(&lir)
+            .expect("should compile");
+        assert!(contains_seq(&via_struct, &I64X2_ADD));
+    }
+
+    #[test]
+    fn dependent_operations_stay_scalar() {
+        // r3 = r0 + r1; r4 = r3 + r2 — the second add needs the first's result.
+        let mut cell = simple_add_cell();
+        cell.registers = 5;
+        cell.instructions = vec![
+            Instruction::abc(OpCode::Add, 3, 0, 1),
+            Instruction::abc(OpCode::Add, 4, 3, 1),
+            Instruction::abc(OpCode::Return, 4, 1, 0),
+        ];
+        assert!(plan_simd_pairs(&cell.instructions).is_empty());
+        let lir = empty_lir_module(vec![cell]);
+        let bytes = compile_to_wasm_with_options(
+            &lir,
+            WasmTarget::Wasm32Unknown,
+            WasmOptions { simd: true },
+        )
+        .expect("should compile");
+        assert!(!contains_seq(&bytes, &I64X2_ADD));
+    }
+
+    #[test]
+    fn pairs_do_not_cross_intervening_writes_or_jumps() {
+        let independent = vec![
+            Instruction::abc(OpCode::Add, 2, 0, 1),
+            Instruction::abc(OpCode::Move, 5, 0, 0),
+            Instruction::abc(OpCode::Add, 3, 5, 1),
+        ];
+        assert_eq!(plan_simd_pairs(&independent), BTreeMap::from([(2, 0)]));
+
+        // Sinking the first add past `r1 = r0` would change its operand.
+        let clobbered = vec![
+            Instruction::abc(OpCode::Add, 2, 0, 1),
+            Instruction::abc(OpCode::Move, 1, 0, 0),
+            Instruction::abc(OpCode::Add, 3, 0, 1),
+        ];
+        assert!(plan_simd_pairs(&clobbered).is_empty());
+
+        let across_call = vec![
+            Instruction::abc(OpCode::Add, 2, 0, 1),
+            Instruction::abc(OpCode::Call, 4, 0, 0),
+            Instruction::abc(OpCode::Add, 3, 0, 1),
+        ];
+        assert!(plan_simd_pairs(&across_call).is_empty());
+
+        let across_target = vec![
+            Instruction::abc(OpCode::Add, 2, 0, 1),
+            Instruction::abc(OpCode::Add, 3, 0, 1),
+            Instruction::sax(OpCode::Jmp, -2),
+        ];
+        assert!(plan_simd_pairs(&across_target).is_empty());
+    }
+
+    fn compile_simd(source: &str) -> Vec<u8> {
+        let source = format!("# simd\n\n```lumen\n{}\n```\n", source);
+        let lir = lumen_compiler::compile(&source).expect("compilation should succeed");
+        compile_to_wasm_with_options(&lir, WasmTarget::Wasm32Unknown, WasmOptions { simd: true })
+            .expect("simd lowering should compile")
+    }
+
+    #[test]
+    fn simd_pairs_operations_inside_a_for_loop_body() {
+        // Each iteration scales one element of each list; the two
+        // multiplications are independent and share a lane pair.
+        let bytes = compile_simd(
+            "cell scaled_sum(a: list[Int], b: list[Int], k: Int) -> list[Int]
+  let mut out: list[Int] = []
+  for i in 0..len(a)
+    out = append(out, a[i] * k + b[i] * k)
+  end
+  return out
+end",
+        );
+        assert!(
+            contains_seq(&bytes, &[0xFD, 0xD5, 0x01]),
+            "expected i64x2.mul"
+        );
+    }
+
+    #[test]
+    fn simd_leaves_one_element_per_iteration_loops_scalar() {
+        // Iterations are not unrolled, so a loop body with a single add has
+        // nothing to pair with.
+        let bytes = compile_simd(
+            "cell add(a: list[Int], b: list[Int]) -> list[Int]
+  let mut out: list[Int] = []
+  for i in 0..len(a)
+    out = append(out, a[i] + b[i])
+  end
+  return out
+end",
+        );
+        assert!(!contains_seq(&bytes, &I64X2_ADD));
+        assert!(!bytes.contains(&0x7B), "no v128 local without pairs");
+    }
+
+    #[test]
+    fn loop_opcodes_bound_simd_pairs() {
+        // 0: ForPrep -> 4; 1-2: body; 3: ForLoop -> 1; 4-5: after the loop.
+        let body = |a| Instruction::abc(OpCode::Add, a, 0, 1);
+        let instructions = vec![
+            Instruction::abx(OpCode::ForPrep, 6, 3),
+            body(2),
+            body(3),
+            Instruction::abx(OpCode::ForLoop, 6, 3),
+            body(4),
+            body(5),
+        ];
+        assert_eq!(jump_targets(&instructions), BTreeSet::from([1, 4]));
+        assert_eq!(
+            plan_simd_pairs(&instructions),
+            BTreeMap::from([(2, 1), (5, 4)])
+        );
+
+        // `Loop` jumps back to its first body instruction, which a pair from
+        // before the loop must not cross.
+        let instructions = vec![
+            body(2),
+            body(3),
+            Instruction::abx(OpCode::Loop, 6, (-2i16) as u16),
+        ];
+        assert_eq!(jump_targets(&instructions), BTreeSet::from([1]));
+        assert!(plan_simd_pairs(&instructions).is_empty());
+    }
 }
@@ -320,6 +320,7 @@ mod tests {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
@@ -335,6 +336,7 @@ mod tests {
                 Instruction::abc(OpCode::Return, 0, 1, 0),
             ],
             effect_handler_metas: Vec::new(),
+            memoizable: false,
         }
     }
 
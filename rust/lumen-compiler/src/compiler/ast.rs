@@ -1,7 +1,7 @@
 use crate::compiler::tokens::Span;
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 /// A complete Lumen program (one `.lm.md` file)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +41,29 @@ pub enum Item {
     MacroDecl(MacroDeclDef),
 }
 
+impl Program {
+    /// Top-level definitions hidden from importers.
+    ///
+    /// A module that marks nothing `pub` exports everything. Once any cell,
+    /// record, enum, type alias, or trait is marked `pub`, the unmarked ones
+    /// are private.
+    pub fn private_names(&self) -> HashSet<String> {
+        let visibility = self.items.iter().filter_map(|item| match item {
+            Item::Cell(c) => Some((&c.name, c.is_pub)),
+            Item::Record(r) => Some((&r.name, r.is_pub)),
+            Item::Enum(e) => Some((&e.name, e.is_pub)),
+            Item::TypeAlias(t) => Some((&t.name, t.is_pub)),
+            Item::Trait(t) => Some((&t.name, t.is_pub)),
+            _ => None,
+        });
+        let (public, private): (Vec<_>, Vec<_>) = visibility.partition(|(_, is_pub)| *is_pub);
+        if public.is_empty() {
+            return HashSet::new();
+        }
+        private.into_iter().map(|(name, _)| name.clone()).collect()
+    }
+}
+
 impl Item {
     pub fn span(&self) -> Span {
         match self {
@@ -90,6 +113,8 @@ pub enum TypeExpr {
     Fn(Vec<TypeExpr>, Box<TypeExpr>, Vec<String>, Span),
     /// Generic type: Name[T, U]
     Generic(String, Vec<TypeExpr>, Span),
+    /// Fixed-size array: [T; N]
+    Array(Box<TypeExpr>, usize, Span),
 }
 
 impl TypeExpr {
@@ -105,6 +130,7 @@ impl TypeExpr {
             TypeExpr::Set(_, s) => *s,
             TypeExpr::Fn(_, _, _, s) => *s,
             TypeExpr::Generic(_, _, s) => *s,
+            TypeExpr::Array(_, _, s) => *s,
         }
     }
 }
@@ -175,6 +201,10 @@ pub struct CellDef {
     pub is_async: bool,
     pub is_extern: bool,
     pub must_use: bool,
+    /// Declared with `@pure`: the cell may perform no effects and may only call
+    /// other `@pure` cells. Verified during resolution.
+    #[serde(default)]
+    pub is_pure: bool,
     pub where_clauses: Vec<Expr>,
     pub span: Span,
     pub doc: Option<String>,
@@ -197,6 +227,9 @@ pub struct ProcessDecl {
     pub cells: Vec<CellDef>,
     pub grants: Vec<GrantDecl>,
     pub pipeline_stages: Vec<String>,
+    /// Source span of each entry in `pipeline_stages`.
+    #[serde(default)]
+    pub pipeline_stage_spans: Vec<Span>,
     pub machine_initial: Option<String>,
     pub machine_states: Vec<MachineStateDecl>,
     pub span: Span,
@@ -308,6 +341,9 @@ pub struct ImportName {
 pub struct ImportDecl {
     pub path: Vec<String>,
     pub names: ImportList,
+    /// Module alias from `import path as alias`; symbols are then only
+    /// visible qualified, as `alias.Name`.
+    pub alias: Option<String>,
     pub is_pub: bool,
     pub span: Span,
 }
@@ -76,6 +76,10 @@ fn resolve_error_code(e: &ResolveError) -> &'static str {
         ResolveError::TraitMethodSignatureMismatch { .. } => "E0125",
         ResolveError::UnstableFeature { .. } => "E0126",
         ResolveError::DeprecatedUsage { .. } => "E0127",
+        ResolveError::ImpureCell { .. } => "E0128",
+        ResolveError::EffectfulConstInit { .. } => "E0129",
+        ResolveError::VersionMismatch { .. } => "E0130",
+        ResolveError::ImportedSymbolPrivate { .. } => "E0131",
     }
 }
 
@@ -93,6 +97,14 @@ fn type_error_code(e: &TypeError) -> &'static str {
         TypeError::ImmutableAssign { .. } => "E0207",
         TypeError::IncompleteMatch { .. } => "E0208",
         TypeError::MustUseIgnored { .. } => "E0209",
+        TypeError::ArrayLengthMismatch { .. } => "E0210",
+        TypeError::IndexOutOfBounds { .. } => "E0211",
+        TypeError::UnknownMethod { .. } => "E0212",
+        TypeError::AmbiguousMethod { .. } => "E0213",
+        TypeError::DuplicateField { .. } => "E0214",
+        TypeError::MissingField { .. } => "E0215",
+        TypeError::UnboundedRecursion { .. } => "E0216",
+        TypeError::PrecisionLoss { .. } => "E0217",
     }
 }
 
@@ -217,6 +229,10 @@ pub fn error_doc(code: &str) -> &'static str {
         "E0125" => "A trait implementation method has an incompatible signature. The parameter types and return type must match the trait declaration.",
         "E0126" => "An unstable feature was used without opting in. Pass `--allow-unstable` or set `allow_unstable = true` in the compile options.",
         "E0127" => "A deprecated cell, record, or enum was used. The declaration is marked `@deprecated` and may be removed in a future edition.",
+        "E0128" => "A cell marked `@pure` performs an effect or calls a cell that is not `@pure`. Remove the effectful operation or drop the @pure attribute.",
+        "E0129" => "A `const` initializer performs an effect, such as printing or calling a tool. Constants are evaluated at compile time, so compute the value in a cell instead.",
+        "E0130" => "The version in an imported module's `@package` directive does not satisfy the version requirement declared with `@requires`. Update the module or relax the requirement.",
+        "E0131" => "A named import refers to a symbol the module does not export. Once a module marks any definition `pub`, only `pub` definitions can be imported.",
 
         // Type
         "E0200" => "An expression's type does not match the expected type. For example, a cell returning String where Int is declared.",
@@ -229,6 +245,14 @@ pub fn error_doc(code: &str) -> &'static str {
         "E0207" => "An assignment was made to an immutable variable. Declare the variable with 'let mut' to allow reassignment.",
         "E0208" => "A match expression does not cover all variants of the matched enum. Add the missing arms or use a wildcard '_' pattern.",
         "E0209" => "The return value of a @must_use cell was discarded. Assign the result to a variable or use it in an expression.",
+        "E0210" => "An array literal does not have the number of elements required by its fixed-size array type. Add or remove elements, or change the declared length.",
+        "E0211" => "A constant index is outside the bounds of a fixed-size array. Valid indices for [T; N] are -N through N-1.",
+        "E0212" => "A method call `value.method(args)` did not resolve. Method calls desugar to `method(value, args)`, so a cell or builtin named `method` must take the receiver's type as its first parameter.",
+        "E0213" => "A method call matches both a field of the receiver record and a cell taking the record as its first parameter. Call the field as `(value.method)(args)` or the cell as `method(value, args)`.",
+        "E0214" => "A record construction supplied the same field label more than once. Each field may be given exactly once.",
+        "E0215" => "A record construction omitted a field that has no default value and is not optional. Supply the field, give it a default in the record definition, or make its type optional.",
+        "E0216" => "A cell calls itself on every path before it can return, so any call to it recurses until the stack overflows. Reported as a warning; add a base case that returns without recursing.",
+        "E0217" => "An integer beyond 2^53 is implicitly widened to Float, which cannot represent it exactly, so the value is rounded. Reported as a warning; write a Float literal if rounding is intended, or keep the value an Int.",
 
         // Constraint
         "E0300" => "A field constraint (where clause) is invalid. Ensure the constraint expression is well-formed and uses supported operations.",
@@ -253,8 +277,9 @@ pub fn all_error_codes() -> Vec<(&'static str, &'static str)> {
         "E0013", "E0014", "E0015", "E0016", "E0100", "E0101", "E0102", "E0103", "E0104", "E0105",
         "E0106", "E0107", "E0108", "E0109", "E0110", "E0111", "E0112", "E0113", "E0114", "E0115",
         "E0116", "E0117", "E0118", "E0119", "E0120", "E0121", "E0122", "E0123", "E0124", "E0125",
-        "E0126", "E0127", "E0200", "E0201", "E0202", "E0203", "E0204", "E0205", "E0206", "E0207",
-        "E0208", "E0209", "E0300", "E0400", "E0401", "E0402", "E0403", "E0500",
+        "E0126", "E0127", "E0128", "E0129", "E0130", "E0131", "E0200", "E0201", "E0202", "E0203",
+        "E0204", "E0205", "E0206", "E0207", "E0208", "E0209", "E0210", "E0211", "E0212", "E0213",
+        "E0214", "E0215", "E0216", "E0217", "E0300", "E0400", "E0401", "E0402", "E0403", "E0500",
     ];
     codes.iter().map(|&c| (c, error_doc(c))).collect()
 }
@@ -139,6 +139,13 @@ impl<'a> GbnfContext<'a> {
                 let inner = parts.join(" ',' ws ");
                 format!("'[' ws {} ws ']'", inner)
             }
+            TypeExpr::Array(inner, len, _) => {
+                // A JSON array with exactly `len` elements
+                self.ensure_ws();
+                let elem = self.lower_type_expr(inner);
+                let parts = vec![elem; *len];
+                format!("'[' ws {} ws ']'", parts.join(" ',' ws "))
+            }
             TypeExpr::Set(inner, _) => {
                 // Same JSON representation as list
                 self.ensure_ws();
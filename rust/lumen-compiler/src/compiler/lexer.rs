@@ -1083,9 +1083,9 @@ impl Lexer {
     }
 
     fn single(&mut self, kind: TokenKind) -> Token {
-        let span = self.span_here();
+        let (so, sl, sc) = (self.byte_offset, self.line, self.col);
         self.advance();
-        Token::new(kind, span)
+        Token::new(kind, self.span_from(so, sl, sc))
     }
 
     pub fn tokenize(&mut self) -> Result<Vec<Token>, LexError> {
@@ -1,6 +1,7 @@
 //! LIR (Lumen Intermediate Representation) data types.
 //! 32-bit fixed-width instructions, Lua-style register VM.
 
+use crate::compiler::tokens::Span;
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 
@@ -106,7 +107,7 @@ pub enum OpCode {
     Spawn = 0x65,      // A, Bx: R[A] = spawn async(proto=Bx)
     Perform = 0x66,    // A, B, C: perform effect B, operation C, result to A
     HandlePush = 0x67, // Ax: push effect handler scope at offset Ax
-    HandlePop = 0x68,  // pop current effect handler scope
+    HandlePop = 0x68,  // Ax=0: pop current effect handler scope; Ax=HANDLER_EXIT: see below
     Resume = 0x69,     // A: resume suspended computation with value in A
 
     // List ops
@@ -115,6 +116,9 @@ pub enum OpCode {
     // Type checks
     IsVariant = 0x71, // A, Bx: if A is variant w/ tag Bx, skip next
     Unbox = 0x72,     // A, B: A = B.payload (for unions)
+
+    // Extensions
+    Extension = 0x7F, // A, B, C: embedder-defined opcode B over registers A and C
 }
 
 /// Intrinsic function IDs
@@ -276,6 +280,11 @@ pub enum IntrinsicId {
     EnvVars = 137,
 }
 
+/// `HandlePop` operand marking the end of a handle block's handler clauses,
+/// reached when a handler finishes without resuming. It discards the
+/// continuation the handler was given instead of popping a scope.
+pub const HANDLER_EXIT: u32 = 1;
+
 /// A 32-bit instruction
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Instruction {
@@ -513,6 +522,10 @@ pub struct LirCell {
     /// that the handler scope matches against.
     #[serde(default)]
     pub effect_handler_metas: Vec<LirEffectHandlerMeta>,
+    /// Set for cells declared `@pure` whose purity was verified during resolution.
+    /// Results of such cells depend only on their arguments and may be memoized.
+    #[serde(default)]
+    pub memoizable: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -594,6 +607,17 @@ pub struct LirEffectHandlerMeta {
     pub handler_ip: usize,
 }
 
+/// Source location of one lowered instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LirSourceMapEntry {
+    /// Name of the cell containing the instruction
+    pub cell: String,
+    /// Index into the cell's `instructions`
+    pub instruction: usize,
+    /// Span of the statement the instruction was lowered from
+    pub span: Span,
+}
+
 /// Complete LIR module
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LirModule {
@@ -609,6 +633,9 @@ pub struct LirModule {
     pub effects: Vec<LirEffect>,
     pub effect_binds: Vec<LirEffectBind>,
     pub handlers: Vec<LirHandler>,
+    /// Side table mapping instructions back to source spans
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_map: Vec<LirSourceMapEntry>,
 }
 
 impl LirModule {
@@ -626,14 +653,30 @@ impl LirModule {
             effects: Vec::new(),
             effect_binds: Vec::new(),
             handlers: Vec::new(),
+            source_map: Vec::new(),
         }
     }
 
+    /// Source spans for lowered instructions, ordered by cell then instruction.
+    pub fn source_map(&self) -> &[LirSourceMapEntry] {
+        &self.source_map
+    }
+
+    /// Span of the statement that instruction `ip` of `cell` was lowered from.
+    pub fn source_span(&self, cell: &str, ip: usize) -> Option<Span> {
+        self.source_map
+            .iter()
+            .find(|e| e.cell == cell && e.instruction == ip)
+            .map(|e| e.span)
+    }
+
     /// Merge another module's definitions into this module.
     ///
     /// This is used during import resolution to link imported modules into the main module.
-    /// String table entries are deduplicated. Other items (cells, types, etc.) are appended,
-    /// assuming no name conflicts (the resolver should have already checked this).
+    /// String table entries are deduplicated. Other items (cells, types, etc.) are appended
+    /// unless one of the same name is already present. Imported cells are compiled under
+    /// module-qualified names, so a cell is only skipped when the same module is merged
+    /// twice (a diamond import).
     pub fn merge(&mut self, other: &LirModule) {
         use std::collections::HashMap;
 
@@ -655,10 +698,18 @@ impl LirModule {
             }
         }
 
-        // Merge cells (no string remapping needed for simple names)
+        // Merge cells (no string remapping needed for simple names), carrying
+        // over the source map entries of the cells that were taken
         for cell in &other.cells {
             if !self.cells.iter().any(|c| c.name == cell.name) {
                 self.cells.push(cell.clone());
+                self.source_map.extend(
+                    other
+                        .source_map
+                        .iter()
+                        .filter(|e| e.cell == cell.name)
+                        .cloned(),
+                );
             }
         }
 
@@ -2,12 +2,13 @@
 
 use crate::compiler::ast::*;
 use crate::compiler::lir::*;
+use crate::compiler::optimize;
 use crate::compiler::regalloc::RegAlloc;
 use crate::compiler::resolve::SymbolTable;
 use crate::compiler::tokens::Span;
 use num_bigint::BigInt;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Map a string name to an IntrinsicId, if it corresponds to a built-in function.
 fn get_intrinsic_id(name: &str) -> Option<IntrinsicId> {
@@ -619,8 +620,9 @@ fn instr_reads_reg(instr: &Instruction, reg: u8) -> bool {
 /// Invariant instructions are **moved** — they are inserted immediately before
 /// the loop header and the original slot is replaced with `Nop`.  All jump
 /// offsets referencing instructions at or after the insertion point are adjusted
-/// to account for the newly inserted instructions.
-fn hoist_loop_invariants(instrs: &mut Vec<Instruction>) {
+/// to account for the newly inserted instructions. `spans` is kept aligned with
+/// `instrs`; hoisted copies keep the span of the instruction they replace.
+fn hoist_loop_invariants(instrs: &mut Vec<Instruction>, spans: &mut Vec<Option<Span>>) {
     if instrs.len() < 3 {
         return;
     }
@@ -756,8 +758,10 @@ fn hoist_loop_invariants(instrs: &mut Vec<Instruction>) {
         }
 
         // Now insert the hoisted instructions.
-        for (idx, inst) in to_insert.into_iter().enumerate() {
+        let hoisted_spans: Vec<Option<Span>> = hoistable.iter().map(|&(pc, _)| spans[pc]).collect();
+        for (idx, (inst, span)) in to_insert.into_iter().zip(hoisted_spans).enumerate() {
             instrs.insert(insert_point + idx, inst);
+            spans.insert(insert_point + idx, span);
         }
     }
 }
@@ -827,6 +831,57 @@ fn eliminate_redundant_bool_eq(instrs: &mut [Instruction]) {
     }
 }
 
+/// Post-lowering pass: turn calls in tail position into `TailCall`.
+///
+/// `Stmt::Return` already emits `TailCall` for `return f(x)`, but calls whose
+/// result reaches a `Return` some other way — the implicit result of a cell
+/// body, the branches of a trailing `if`/`match` — are lowered as a plain
+/// `Call`. This pass looks at what follows each `Call`: if the only thing
+/// that happens to its result is being copied by `Move`/`MoveOwn` and carried
+/// through forward `Jmp`s to a `Return` of that value, the call is rewritten
+/// to `TailCall` so the VM reuses the frame.
+///
+/// The instructions after the call are left in place; `TailCall` to a builtin
+/// writes its result to the call register and falls through to them.
+///
+/// Cells that install effect handlers are skipped, since a reused frame would
+/// keep the caller's handlers in scope for the callee.
+fn promote_tail_calls(instrs: &mut [Instruction]) {
+    // Bound the walk so pathological jump chains can't make this quadratic.
+    const MAX_STEPS: usize = 32;
+
+    if instrs.iter().any(|i| i.op == OpCode::HandlePush) {
+        return;
+    }
+    for pc in 0..instrs.len() {
+        let call = instrs[pc];
+        if call.op != OpCode::Call {
+            continue;
+        }
+        let mut value = call.a;
+        let mut ip = pc + 1;
+        for _ in 0..MAX_STEPS {
+            let Some(&next) = instrs.get(ip) else {
+                break;
+            };
+            match next.op {
+                OpCode::Move | OpCode::MoveOwn if next.b == value => {
+                    value = next.a;
+                    ip += 1;
+                }
+                OpCode::Jmp if next.sax_val() >= 0 => {
+                    ip = ip + 1 + next.sax_val() as usize;
+                }
+                OpCode::Return if next.a == value && next.b == 1 => {
+                    instrs[pc] = Instruction::abc(OpCode::TailCall, call.a, call.b, 0);
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+}
+
 /// Post-lowering pass: remove all `Nop` instructions from the instruction
 /// stream and adjust jump offsets to maintain correct control flow.
 ///
@@ -841,8 +896,8 @@ fn eliminate_redundant_bool_eq(instrs: &mut [Instruction]) {
 /// 2. For each jump instruction (`Jmp`, `Break`, `Continue`), recalculate the
 ///    offset using the mapping.
 /// 3. For each `HandlePush` instruction, recalculate the `bx` offset.
-/// 4. Remove all Nop instructions.
-fn strip_nops(instrs: &mut Vec<Instruction>) {
+/// 4. Remove all Nop instructions, and their entries in `spans`.
+fn strip_nops(instrs: &mut Vec<Instruction>, spans: &mut Vec<Option<Span>>) {
     if instrs.is_empty() {
         return;
     }
@@ -921,6 +976,8 @@ fn strip_nops(instrs: &mut Vec<Instruction>) {
     }
 
     // Remove all Nop instructions by retaining only non-Nops.
+    let mut kept = instrs.iter().map(|i| i.op != OpCode::Nop);
+    spans.retain(|_| kept.next().unwrap_or(false));
     instrs.retain(|i| i.op != OpCode::Nop);
 }
 
@@ -962,13 +1019,73 @@ fn lift_local_defs(body: &[Stmt], module: &mut LirModule, lowerer: &mut Lowerer)
 
 /// Lower an entire program to a LIR module.
 pub fn lower(program: &Program, symbols: &SymbolTable, source: &str) -> LirModule {
+    lower_with_method_calls(program, symbols, source, &HashSet::new())
+}
+
+/// Optional transformations applied while lowering.
+#[derive(Debug, Clone, Default)]
+pub struct LowerOptions {
+    /// Replace operator expressions over constants with their value
+    /// (see [`optimize::fold_constant`]).
+    pub fold_constants: bool,
+    /// Path of the module being lowered when it is an import. Its cells are
+    /// then compiled under [`SymbolTable::module_cell_name`].
+    pub module_path: Option<String>,
+}
+
+/// Lower `program`, emitting the method calls in `method_calls` (as returned
+/// by `typecheck_with_method_calls`) as `method(receiver, args)`.
+pub fn lower_with_method_calls(
+    program: &Program,
+    symbols: &SymbolTable,
+    source: &str,
+    method_calls: &HashSet<Span>,
+) -> LirModule {
+    lower_with_options(
+        program,
+        symbols,
+        source,
+        method_calls,
+        LowerOptions::default(),
+    )
+}
+
+/// [`lower_with_method_calls`] with optional transformations enabled.
+pub fn lower_with_options(
+    program: &Program,
+    symbols: &SymbolTable,
+    source: &str,
+    method_calls: &HashSet<Span>,
+    options: LowerOptions,
+) -> LirModule {
     let doc_hash = format!("sha256:{:x}", Sha256::digest(source.as_bytes()));
     let mut module = LirModule::new(doc_hash);
     let mut lowerer = Lowerer::new(
         symbols,
+        method_calls,
         collect_effect_tool_bindings(program),
         collect_effect_handler_cells(program),
     );
+    lowerer.fold_constants = options.fold_constants;
+    lowerer.inferred_return_cells = program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Cell(c) if c.return_type.is_none() => Some(c.name.clone()),
+            _ => None,
+        })
+        .collect();
+    if let Some(module_path) = options.module_path {
+        lowerer.own_cells = program
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Cell(c) => Some(c.name.clone()),
+                _ => None,
+            })
+            .collect();
+        lowerer.module_path = Some(module_path);
+    }
 
     for d in &program.directives {
         let name = match &d.value {
@@ -1168,6 +1285,7 @@ pub fn lower(program: &Program, symbols: &SymbolTable, source: &str) -> LirModul
                         is_async: false,
                         is_extern: false,
                         must_use: false,
+                        is_pure: false,
                         where_clauses: vec![],
                         span,
                         doc: None,
@@ -1287,6 +1405,7 @@ pub fn lower(program: &Program, symbols: &SymbolTable, source: &str) -> LirModul
 
     // Collect string table
     module.strings = lowerer.strings;
+    module.source_map = lowerer.source_map;
     module
 }
 
@@ -1508,6 +1627,8 @@ struct LoopContext {
 
 struct Lowerer<'a> {
     symbols: &'a SymbolTable,
+    /// Call spans that typecheck resolved UFCS-style (`x.f(a)` → `f(x, a)`).
+    method_calls: &'a HashSet<Span>,
     tool_indices: HashMap<String, u16>,
     effect_tool_bindings: HashMap<String, String>,
     effect_handler_cells: HashMap<String, String>,
@@ -1519,11 +1640,26 @@ struct Lowerer<'a> {
     /// Accumulated effect handler metadata for the current cell being lowered.
     /// Each entry corresponds to one HandlePush instruction emitted.
     effect_handler_metas: Vec<LirEffectHandlerMeta>,
+    /// Statement spans for the instructions of the cell being lowered,
+    /// indexed like its instruction vector.
+    instr_spans: Vec<Option<Span>>,
+    /// Source map entries for every cell lowered so far.
+    source_map: Vec<LirSourceMapEntry>,
+    /// Fold constant operator expressions instead of emitting the operators.
+    fold_constants: bool,
+    /// Set when lowering an imported module; see [`LowerOptions::module_path`].
+    module_path: Option<String>,
+    /// The module's top-level cells, which are qualified by `module_path`.
+    own_cells: HashSet<String>,
+    /// Top-level cells whose return type is inferred; their tail expression
+    /// is returned just like an annotated cell's.
+    inferred_return_cells: HashSet<String>,
 }
 
 impl<'a> Lowerer<'a> {
     fn new(
         symbols: &'a SymbolTable,
+        method_calls: &'a HashSet<Span>,
         effect_tool_bindings: HashMap<String, String>,
         effect_handler_cells: HashMap<String, String>,
     ) -> Self {
@@ -1536,6 +1672,7 @@ impl<'a> Lowerer<'a> {
             .collect();
         Self {
             symbols,
+            method_calls,
             tool_indices,
             effect_tool_bindings,
             effect_handler_cells,
@@ -1544,6 +1681,22 @@ impl<'a> Lowerer<'a> {
             lambda_cells: Vec::new(),
             defer_stack: Vec::new(),
             effect_handler_metas: Vec::new(),
+            instr_spans: Vec::new(),
+            source_map: Vec::new(),
+            fold_constants: false,
+            module_path: None,
+            own_cells: HashSet::new(),
+            inferred_return_cells: HashSet::new(),
+        }
+    }
+
+    /// The name the top-level cell `name` is compiled under.
+    fn compiled_cell_name(&self, name: &str) -> String {
+        match &self.module_path {
+            Some(path) if self.own_cells.contains(name) => {
+                SymbolTable::module_cell_name(path, name)
+            }
+            _ => name.to_string(),
         }
     }
 
@@ -1689,6 +1842,25 @@ impl<'a> Lowerer<'a> {
         self.emit_call_with_regs(callee_reg, &arg_regs, ra, instrs)
     }
 
+    /// For a reference to an imported cell under a local name — `alias.name`
+    /// through a module alias, or a renamed import — return the local name
+    /// and the name the cell was compiled under. Local variables shadow
+    /// imports.
+    fn imported_cell_target(&self, expr: &Expr, ra: &RegAlloc) -> Option<(String, String)> {
+        let local = match expr {
+            Expr::Ident(name, _) if ra.lookup(name).is_none() => name.clone(),
+            Expr::DotAccess(obj, member, _) => match obj.as_ref() {
+                Expr::Ident(alias, _) if ra.lookup(alias).is_none() => {
+                    format!("{}.{}", alias, member)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let target = self.symbols.imported_cells.get(&local)?.clone();
+        Some((local, target))
+    }
+
     /// If `callee_name` refers to a cell with a variadic last parameter,
     /// pack the extra arguments beyond the fixed params into a list.
     fn pack_variadic_args(
@@ -1919,6 +2091,7 @@ impl<'a> Lowerer<'a> {
             constants,
             instructions,
             effect_handler_metas: vec![],
+            memoizable: false,
         }
     }
 
@@ -1957,11 +2130,13 @@ impl<'a> Lowerer<'a> {
             constants,
             instructions,
             effect_handler_metas: vec![],
+            memoizable: false,
         }
     }
 
     fn lower_cell(&mut self, cell: &CellDef) -> LirCell {
-        self.intern_string(&cell.name);
+        let name = self.compiled_cell_name(&cell.name);
+        self.intern_string(&name);
         let mut ra = RegAlloc::new(&cell.name);
         let mut constants: Vec<Constant> = Vec::new();
         let mut instructions: Vec<Instruction> = Vec::new();
@@ -1970,6 +2145,8 @@ impl<'a> Lowerer<'a> {
         let saved_defers = std::mem::take(&mut self.defer_stack);
         // Save and reset effect handler metas for this cell scope
         let saved_metas = std::mem::take(&mut self.effect_handler_metas);
+        // Save and reset instruction spans for this cell scope
+        let saved_spans = std::mem::take(&mut self.instr_spans);
 
         // Allocate param registers
         let params: Vec<LirParam> = cell
@@ -1988,19 +2165,25 @@ impl<'a> Lowerer<'a> {
 
         // Lower body with implicit return support
         let has_return_type = cell.return_type.is_some();
+        let inferred_return = self.inferred_return_cells.contains(&cell.name);
         let body_len = cell.body.len();
         for (idx, stmt) in cell.body.iter().enumerate() {
             let is_last = idx == body_len - 1;
-            // Implicit return: if last statement is an expression and cell has a return type
-            if is_last && has_return_type {
+            let start = instructions.len();
+            // Implicit return: if last statement is an expression and cell has
+            // a declared or inferred return type
+            if is_last && (has_return_type || inferred_return) {
                 if let Stmt::Expr(es) = stmt {
                     let val_reg =
                         self.lower_expr(&es.expr, &mut ra, &mut constants, &mut instructions);
                     // Emit accumulated defer blocks in LIFO order before return
                     self.emit_defers(&mut ra, &mut constants, &mut instructions);
                     instructions.push(Instruction::abc(OpCode::Return, val_reg, 1, 0));
+                    self.record_spans(start, instructions.len(), stmt.span());
                     continue;
                 }
+            }
+            if is_last && has_return_type {
                 // Support match as implicit return value
                 if let Stmt::Match(ms) = stmt {
                     let match_expr = Expr::MatchExpr {
@@ -2012,6 +2195,7 @@ impl<'a> Lowerer<'a> {
                         self.lower_expr(&match_expr, &mut ra, &mut constants, &mut instructions);
                     self.emit_defers(&mut ra, &mut constants, &mut instructions);
                     instructions.push(Instruction::abc(OpCode::Return, val_reg, 1, 0));
+                    self.record_spans(start, instructions.len(), stmt.span());
                     continue;
                 }
                 // Support for-loop as implicit return value (collects into list)
@@ -2027,6 +2211,7 @@ impl<'a> Lowerer<'a> {
                     );
                     self.emit_defers(&mut ra, &mut constants, &mut instructions);
                     instructions.push(Instruction::abc(OpCode::Return, result_reg, 1, 0));
+                    self.record_spans(start, instructions.len(), stmt.span());
                     continue;
                 }
                 // Support if/else as implicit return value
@@ -2041,6 +2226,7 @@ impl<'a> Lowerer<'a> {
                     );
                     self.emit_defers(&mut ra, &mut constants, &mut instructions);
                     instructions.push(Instruction::abc(OpCode::Return, result_reg, 1, 0));
+                    self.record_spans(start, instructions.len(), stmt.span());
                     continue;
                 }
             }
@@ -2061,25 +2247,53 @@ impl<'a> Lowerer<'a> {
             instructions.push(Instruction::abc(OpCode::Return, r, 1, 0));
         }
 
+        // Anything emitted outside a statement (e.g. the implicit return)
+        // maps to the cell itself
+        self.record_spans(0, instructions.len(), cell.span);
+
         // Restore defer stack and collect effect handler metas
         self.defer_stack = saved_defers;
         let effect_handler_metas = std::mem::replace(&mut self.effect_handler_metas, saved_metas);
+        let mut spans = std::mem::replace(&mut self.instr_spans, saved_spans);
 
         // Peephole optimizations
-        hoist_loop_invariants(&mut instructions);
+        hoist_loop_invariants(&mut instructions, &mut spans);
         eliminate_redundant_moves(&mut instructions);
         optimize_move_own(&mut instructions);
         eliminate_redundant_bool_eq(&mut instructions);
-        strip_nops(&mut instructions);
+        strip_nops(&mut instructions, &mut spans);
+        promote_tail_calls(&mut instructions);
+
+        for (ip, span) in spans.into_iter().enumerate() {
+            if let Some(span) = span {
+                self.source_map.push(LirSourceMapEntry {
+                    cell: name.clone(),
+                    instruction: ip,
+                    span,
+                });
+            }
+        }
 
         LirCell {
-            name: cell.name.clone(),
+            name,
             params,
             returns: cell.return_type.as_ref().map(format_type_expr),
             registers: ra.max_regs(),
             constants,
             instructions,
             effect_handler_metas,
+            memoizable: cell.is_pure,
+        }
+    }
+
+    /// Attribute instructions `start..end` that don't have a span yet to `span`.
+    /// Nested statements are lowered first, so they keep their own spans.
+    fn record_spans(&mut self, start: usize, end: usize, span: Span) {
+        if self.instr_spans.len() < end {
+            self.instr_spans.resize(end, None);
+        }
+        for slot in &mut self.instr_spans[start..end] {
+            slot.get_or_insert(span);
         }
     }
 
@@ -2089,6 +2303,18 @@ impl<'a> Lowerer<'a> {
         ra: &mut RegAlloc,
         consts: &mut Vec<Constant>,
         instrs: &mut Vec<Instruction>,
+    ) {
+        let start = instrs.len();
+        self.lower_stmt_kind(stmt, ra, consts, instrs);
+        self.record_spans(start, instrs.len(), stmt.span());
+    }
+
+    fn lower_stmt_kind(
+        &mut self,
+        stmt: &Stmt,
+        ra: &mut RegAlloc,
+        consts: &mut Vec<Constant>,
+        instrs: &mut Vec<Instruction>,
     ) {
         match stmt {
             Stmt::Let(ls) => {
@@ -2295,6 +2521,15 @@ impl<'a> Lowerer<'a> {
                 // because they lower to different opcodes.
                 if self.defer_stack.is_empty() {
                     if let Expr::Call(ref callee, ref args, _) = rs.value {
+                        if let Some((_, target)) = self.imported_cell_target(callee, ra) {
+                            let callee_reg = ra.alloc_temp();
+                            let callee_idx = consts.len() as u16;
+                            consts.push(Constant::String(target));
+                            instrs.push(Instruction::abx(OpCode::LoadK, callee_reg, callee_idx));
+                            let arg_regs = self.lower_call_arg_regs(args, None, ra, consts, instrs);
+                            self.emit_tail_call_with_regs(callee_reg, &arg_regs, ra, instrs);
+                            return;
+                        }
                         if let Expr::Ident(ref name, _) = **callee {
                             let is_user_cell = self.symbols.cells.contains_key(name);
                             let is_tool = self.tool_indices.contains_key(name);
@@ -2313,7 +2548,7 @@ impl<'a> Lowerer<'a> {
                             {
                                 let callee_reg = ra.alloc_temp();
                                 let callee_idx = consts.len() as u16;
-                                consts.push(Constant::String(name.to_string()));
+                                consts.push(Constant::String(self.compiled_cell_name(name)));
                                 instrs.push(Instruction::abx(
                                     OpCode::LoadK,
                                     callee_reg,
@@ -3023,8 +3258,17 @@ impl<'a> Lowerer<'a> {
                     instrs.push(Instruction::abc(OpCode::Move, dest, value_reg, 0));
                 }
             }
+            Pattern::Variant(name, payload, span) => {
+                // `let Point(x, y) = p` — positional record destructuring.
+                if let Some(Ok(record_pattern)) =
+                    self.symbols
+                        .positional_record_pattern(name, payload.as_deref(), *span)
+                {
+                    self.lower_let_pattern(&record_pattern, value_reg, ra, consts, instrs);
+                }
+            }
             _ => {
-                // Unsupported patterns in let position (Guard, Or, Variant, etc.)
+                // Unsupported patterns in let position (Guard, Or, etc.)
                 // are not valid irrefutable patterns — silently ignored.
             }
         }
@@ -3046,7 +3290,21 @@ impl<'a> Lowerer<'a> {
                 instrs.push(Instruction::abc(OpCode::Eq, cmp_reg, value_reg, lit_reg));
                 fail_jumps.push(self.emit_jump_if_false(cmp_reg, instrs));
             }
-            Pattern::Variant(tag, binding, _) => {
+            Pattern::Variant(tag, binding, span) => {
+                if let Some(Ok(record_pattern)) =
+                    self.symbols
+                        .positional_record_pattern(tag, binding.as_deref(), *span)
+                {
+                    self.lower_match_pattern(
+                        &record_pattern,
+                        value_reg,
+                        ra,
+                        consts,
+                        instrs,
+                        fail_jumps,
+                    );
+                    return;
+                }
                 let tag_idx = self.intern_string(tag);
                 instrs.push(Instruction::abx(OpCode::IsVariant, value_reg, tag_idx));
                 let fail_jmp = instrs.len();
@@ -3289,6 +3547,11 @@ impl<'a> Lowerer<'a> {
         consts: &mut Vec<Constant>,
         instrs: &mut Vec<Instruction>,
     ) -> u8 {
+        if self.fold_constants {
+            if let Some(folded) = optimize::fold_constant(expr) {
+                return self.lower_expr(&folded, ra, consts, instrs);
+            }
+        }
         match expr {
             Expr::IntLit(n, _) => {
                 let dest = ra.alloc_temp();
@@ -3419,6 +3682,12 @@ impl<'a> Lowerer<'a> {
             Expr::Ident(name, _) => {
                 if let Some(reg) = ra.lookup(name) {
                     reg
+                } else if let Some(target) = self.symbols.imported_cells.get(name) {
+                    let dest = ra.alloc_temp();
+                    let kidx = consts.len() as u16;
+                    consts.push(Constant::String(target.clone()));
+                    instrs.push(Instruction::abx(OpCode::LoadK, dest, kidx));
+                    dest
                 } else if let Some(const_info) = self.symbols.consts.get(name) {
                     if let Some(ref value_expr) = const_info.value {
                         self.lower_expr(value_expr, ra, consts, instrs)
@@ -3449,7 +3718,7 @@ impl<'a> Lowerer<'a> {
                 } else {
                     let dest = ra.alloc_temp();
                     let kidx = consts.len() as u16;
-                    consts.push(Constant::String(name.clone()));
+                    consts.push(Constant::String(self.compiled_cell_name(name)));
                     instrs.push(Instruction::abx(OpCode::LoadK, dest, kidx));
                     dest
                 }
@@ -3722,6 +3991,7 @@ impl<'a> Lowerer<'a> {
                         constants: vec![],
                         instructions: linstrs,
                         effect_handler_metas: vec![],
+                        memoizable: false,
                     });
 
                     // Create closure and capture f and g
@@ -3783,7 +4053,24 @@ impl<'a> Lowerer<'a> {
                 dest
             }
 
-            Expr::Call(callee, args, _) => {
+            Expr::Call(callee, args, span) => {
+                if let Expr::DotAccess(receiver, method, _) = callee.as_ref() {
+                    if self.method_calls.contains(span) {
+                        let call = crate::compiler::typecheck::desugar_method_call(
+                            receiver, method, args, *span,
+                        );
+                        return self.lower_expr(&call, ra, consts, instrs);
+                    }
+                }
+                if let Some((local, target)) = self.imported_cell_target(callee, ra) {
+                    let callee_reg = ra.alloc_temp();
+                    let callee_idx = consts.len() as u16;
+                    consts.push(Constant::String(target));
+                    instrs.push(Instruction::abx(OpCode::LoadK, callee_reg, callee_idx));
+                    let arg_regs = self.lower_call_arg_regs(args, None, ra, consts, instrs);
+                    let arg_regs = self.pack_variadic_args(&local, arg_regs, ra, consts, instrs);
+                    return self.emit_call_with_regs(callee_reg, &arg_regs, ra, instrs);
+                }
                 if let Some(effect_path) = effect_operation_name(callee.as_ref()) {
                     if let Some(handler_cell) = self.effect_handler_cells.get(&effect_path).cloned()
                     {
@@ -4283,6 +4570,14 @@ impl<'a> Lowerer<'a> {
                 self.lower_tool_call(alias, args, ra, consts, instrs)
             }
             Expr::DotAccess(obj, field, _) => {
+                // `alias.cell` through a module alias names the imported cell
+                if let Some((_, target)) = self.imported_cell_target(expr, ra) {
+                    let dest = ra.alloc_temp();
+                    let kidx = consts.len() as u16;
+                    consts.push(Constant::String(target));
+                    instrs.push(Instruction::abx(OpCode::LoadK, dest, kidx));
+                    return dest;
+                }
                 // Check for Enum.Variant access (bare, no call)
                 if let Expr::Ident(enum_name, _) = obj.as_ref() {
                     let is_enum_dot_variant = self.symbols.types.values().any(|t| {
@@ -4430,6 +4725,9 @@ impl<'a> Lowerer<'a> {
 
                 // Save and reset defer stack for lambda scope
                 let saved_defers = std::mem::take(&mut self.defer_stack);
+                // Lambda instructions live in their own vector; don't let their
+                // statements write spans for the enclosing cell
+                let saved_spans = std::mem::take(&mut self.instr_spans);
 
                 match body {
                     LambdaBody::Expr(e) => {
@@ -4455,8 +4753,9 @@ impl<'a> Lowerer<'a> {
                     }
                 }
 
-                // Restore defer stack
+                // Restore defer stack and the enclosing cell's spans
                 self.defer_stack = saved_defers;
+                self.instr_spans = saved_spans;
 
                 let proto_idx = self.lambda_cells.len() as u16;
                 self.lambda_cells.push(LirCell {
@@ -4467,6 +4766,7 @@ impl<'a> Lowerer<'a> {
                     constants: lconsts,
                     instructions: linstrs,
                     effect_handler_metas: vec![],
+                    memoizable: false,
                 });
 
                 let dest = ra.alloc_temp();
@@ -5377,6 +5677,8 @@ impl<'a> Lowerer<'a> {
                         self.lower_stmt(stmt, ra, consts, instrs);
                     }
                 }
+                // A handler that didn't resume ends here; drop its continuation.
+                instrs.push(Instruction::ax(OpCode::HandlePop, HANDLER_EXIT));
 
                 // Patch jump past handlers
                 let after_handlers = instrs.len();
@@ -5768,7 +6070,11 @@ fn format_spec_to_string(spec: &FormatSpec) -> String {
 fn format_type_expr(ty: &TypeExpr) -> String {
     match ty {
         TypeExpr::Named(n, _) => n.clone(),
-        TypeExpr::List(inner, _) => format!("list[{}]", format_type_expr(inner)),
+        // Fixed-size arrays share the list representation at runtime; their
+        // length is enforced by the typechecker.
+        TypeExpr::List(inner, _) | TypeExpr::Array(inner, _, _) => {
+            format!("list[{}]", format_type_expr(inner))
+        }
         TypeExpr::Map(k, v, _) => format!("map[{}, {}]", format_type_expr(k), format_type_expr(v)),
         TypeExpr::Result(ok, err, _) => format!(
             "result[{}, {}]",
@@ -6663,4 +6969,25 @@ end"#;
         assert!(has_inf, "constant pool should contain INFINITY");
         assert!(has_nan, "constant pool should contain NAN");
     }
+
+    #[test]
+    fn test_source_map_maps_return_to_its_line() {
+        let src = "cell main() -> Int\n  let x = 1\n  let y = x + 2\n  return y\nend";
+        let module = lower_src(src);
+        let cell = &module.cells[0];
+        let ret_ip = cell
+            .instructions
+            .iter()
+            .position(|i| i.op == OpCode::Return)
+            .expect("cell should return");
+        let span = module
+            .source_span("main", ret_ip)
+            .expect("return should be mapped");
+        assert_eq!(span.line, 4);
+        assert_eq!(module.source_span("main", 0).map(|s| s.line), Some(2));
+        assert!(module
+            .source_map()
+            .iter()
+            .all(|e| e.cell == "main" && e.instruction < cell.instructions.len()));
+    }
 }
@@ -11,6 +11,7 @@ pub mod lexer;
 pub mod lir;
 pub mod lower;
 pub mod macros;
+pub mod optimize;
 pub mod ownership;
 pub mod parity_memory;
 pub mod parser;
@@ -0,0 +1,324 @@
+//! Compile-time optimizations.
+//!
+//! Constant folding (applied while lowering): a binary or unary expression
+//! whose operands are all literals is replaced by the literal it evaluates to.
+//! Folding follows the VM's runtime semantics exactly; anything the VM would
+//! reject (integer overflow, division by zero) or that would not produce a
+//! finite float is left alone so the program still fails the same way at
+//! runtime.
+//!
+//! Dead-code elimination (applied to the linked module): imported cells and
+//! types that the importing module cannot reach are dropped.
+
+use crate::compiler::ast::{BinOp, Expr, UnaryOp};
+use crate::compiler::lir::{Constant, Instruction, LirModule, OpCode};
+use std::collections::{HashMap, HashSet};
+
+/// A literal value produced by folding.
+#[derive(Debug, Clone, PartialEq)]
+enum Lit {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+}
+
+/// Fold `expr` if it is an operator expression over constants.
+///
+/// Returns the replacement literal expression, or `None` when `expr` is not
+/// an operator expression or cannot be folded. Subexpressions of an
+/// expression that doesn't fold as a whole are folded as they are lowered.
+pub fn fold_constant(expr: &Expr) -> Option<Expr> {
+    let span = match expr {
+        Expr::BinOp(_, _, _, span) | Expr::UnaryOp(_, _, span) => *span,
+        _ => return None,
+    };
+    Some(match eval(expr)? {
+        Lit::Int(n) => Expr::IntLit(n, span),
+        Lit::Float(f) => Expr::FloatLit(f, span),
+        Lit::Str(s) => Expr::StringLit(s, span),
+        Lit::Bool(b) => Expr::BoolLit(b, span),
+    })
+}
+
+fn eval(expr: &Expr) -> Option<Lit> {
+    match expr {
+        Expr::IntLit(n, _) => Some(Lit::Int(*n)),
+        Expr::FloatLit(f, _) => Some(Lit::Float(*f)),
+        Expr::StringLit(s, _) => Some(Lit::Str(s.clone())),
+        Expr::BoolLit(b, _) => Some(Lit::Bool(*b)),
+        Expr::UnaryOp(op, inner, _) => match (op, eval(inner)?) {
+            (UnaryOp::Neg, Lit::Int(n)) => n.checked_neg().map(Lit::Int),
+            (UnaryOp::Neg, Lit::Float(f)) => Some(Lit::Float(-f)),
+            (UnaryOp::Not, Lit::Bool(b)) => Some(Lit::Bool(!b)),
+            _ => None,
+        },
+        Expr::BinOp(lhs, op, rhs, _) => eval_binop(eval(lhs)?, *op, eval(rhs)?),
+        _ => None,
+    }
+}
+
+fn eval_binop(lhs: Lit, op: BinOp, rhs: Lit) -> Option<Lit> {
+    match (lhs, rhs) {
+        (Lit::Int(a), Lit::Int(b)) => match op {
+            BinOp::Add => a.checked_add(b).map(Lit::Int),
+            BinOp::Sub => a.checked_sub(b).map(Lit::Int),
+            BinOp::Mul => a.checked_mul(b).map(Lit::Int),
+            BinOp::Div => a.checked_div(b).map(Lit::Int),
+            BinOp::FloorDiv => a.checked_div_euclid(b).map(Lit::Int),
+            BinOp::Mod => a.checked_rem_euclid(b).map(Lit::Int),
+            BinOp::Pow => u32::try_from(b)
+                .ok()
+                .and_then(|b| a.checked_pow(b))
+                .map(Lit::Int),
+            BinOp::BitAnd => Some(Lit::Int(a & b)),
+            BinOp::BitOr => Some(Lit::Int(a | b)),
+            BinOp::BitXor => Some(Lit::Int(a ^ b)),
+            BinOp::Eq => Some(Lit::Bool(a == b)),
+            BinOp::NotEq => Some(Lit::Bool(a != b)),
+            BinOp::Lt => Some(Lit::Bool(a < b)),
+            BinOp::LtEq => Some(Lit::Bool(a <= b)),
+            BinOp::Gt => Some(Lit::Bool(a > b)),
+            BinOp::GtEq => Some(Lit::Bool(a >= b)),
+            _ => None,
+        },
+        (Lit::Float(a), Lit::Float(b)) => {
+            let folded = match op {
+                BinOp::Add => a + b,
+                BinOp::Sub => a - b,
+                BinOp::Mul => a * b,
+                BinOp::Div => a / b,
+                _ => return None,
+            };
+            folded.is_finite().then_some(Lit::Float(folded))
+        }
+        (Lit::Str(a), Lit::Str(b)) => match op {
+            BinOp::Add | BinOp::Concat => Some(Lit::Str(a + &b)),
+            BinOp::Eq => Some(Lit::Bool(a == b)),
+            BinOp::NotEq => Some(Lit::Bool(a != b)),
+            _ => None,
+        },
+        (Lit::Bool(a), Lit::Bool(b)) => match op {
+            BinOp::And => Some(Lit::Bool(a && b)),
+            BinOp::Or => Some(Lit::Bool(a || b)),
+            BinOp::Eq => Some(Lit::Bool(a == b)),
+            BinOp::NotEq => Some(Lit::Bool(a != b)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Drop the cells and types merged in from imports that nothing reachable
+/// from `own_cells` and `own_types` (the importing module's definitions, which
+/// are always kept) refers to.
+///
+/// References are found conservatively: a cell is reachable from another when
+/// its name appears among that cell's string constants (direct calls, cells
+/// passed by name, tool dispatch) or it is the target of a `Closure`. Handler
+/// and agent cells and lambdas are always kept. A type is kept when its name,
+/// or one of its variants' names, appears in a kept cell's constants,
+/// signature, or the type names its instructions refer to, or in a kept type.
+pub fn eliminate_dead_code(
+    module: &mut LirModule,
+    own_cells: &HashSet<String>,
+    own_types: &HashSet<String>,
+) {
+    let index: HashMap<&str, usize> = module
+        .cells
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.name.as_str(), i))
+        .collect();
+    let pinned: HashSet<&str> = module
+        .handlers
+        .iter()
+        .flat_map(|h| h.handles.iter().map(|handle| handle.cell.as_str()))
+        .chain(
+            module
+                .agents
+                .iter()
+                .flat_map(|a| a.methods.iter().map(String::as_str)),
+        )
+        .collect();
+
+    let mut keep = vec![false; module.cells.len()];
+    let mut worklist: Vec<usize> = Vec::new();
+    for (i, cell) in module.cells.iter().enumerate() {
+        if own_cells.contains(&cell.name)
+            || pinned.contains(cell.name.as_str())
+            || cell.name.starts_with('<')
+        {
+            keep[i] = true;
+            worklist.push(i);
+        }
+    }
+    while let Some(i) = worklist.pop() {
+        let cell = &module.cells[i];
+        let by_name = cell.constants.iter().filter_map(|k| match k {
+            Constant::String(s) => index.get(s.as_str()).copied(),
+            _ => None,
+        });
+        let by_closure = cell
+            .instructions
+            .iter()
+            .filter(|instr| instr.op == OpCode::Closure)
+            .map(|instr| instr.bx() as usize)
+            .filter(|&j| j < keep.len());
+        let targets: Vec<usize> = by_name.chain(by_closure).collect();
+        for j in targets {
+            if !keep[j] {
+                keep[j] = true;
+                worklist.push(j);
+            }
+        }
+    }
+
+    // Names mentioned by the kept cells, then by the types they keep.
+    let mut mentioned: HashSet<String> = HashSet::new();
+    for (cell, _) in module.cells.iter().zip(&keep).filter(|(_, &k)| k) {
+        for constant in &cell.constants {
+            if let Constant::String(s) = constant {
+                mentioned.extend(name_tokens(s));
+            }
+        }
+        for param in &cell.params {
+            mentioned.extend(name_tokens(&param.ty));
+        }
+        if let Some(returns) = &cell.returns {
+            mentioned.extend(name_tokens(returns));
+        }
+        let named = cell.instructions.iter().filter(|instr| {
+            matches!(
+                instr.op,
+                OpCode::NewRecord | OpCode::IsVariant | OpCode::Schema
+            )
+        });
+        for instr in named {
+            if let Some(s) = module.strings.get(instr.bx() as usize) {
+                mentioned.extend(name_tokens(s));
+            }
+        }
+    }
+    let mut keep_types: Vec<bool> = module
+        .types
+        .iter()
+        .map(|t| own_types.contains(&t.name))
+        .collect();
+    loop {
+        let mut changed = false;
+        for (i, ty) in module.types.iter().enumerate() {
+            if !keep_types[i]
+                && (mentioned.contains(&ty.name)
+                    || ty.variants.iter().any(|v| mentioned.contains(&v.name)))
+            {
+                keep_types[i] = true;
+                changed = true;
+            }
+            if keep_types[i] {
+                for field in &ty.fields {
+                    changed |= extend_new(&mut mentioned, name_tokens(&field.ty));
+                }
+                for payload in ty.variants.iter().filter_map(|v| v.payload.as_ref()) {
+                    changed |= extend_new(&mut mentioned, name_tokens(payload));
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // Drop the unreachable definitions and renumber closure targets.
+    let mut new_index = vec![None; keep.len()];
+    let mut next = 0u16;
+    for (i, &k) in keep.iter().enumerate() {
+        if k {
+            new_index[i] = Some(next);
+            next += 1;
+        }
+    }
+    let mut kept_iter = keep.iter();
+    module.cells.retain(|_| *kept_iter.next().unwrap_or(&true));
+    for cell in &mut module.cells {
+        for instr in &mut cell.instructions {
+            if instr.op == OpCode::Closure {
+                if let Some(Some(idx)) = new_index.get(instr.bx() as usize) {
+                    *instr = Instruction::abx(OpCode::Closure, instr.a, *idx);
+                }
+            }
+        }
+    }
+    let mut kept_types_iter = keep_types.iter();
+    module
+        .types
+        .retain(|_| *kept_types_iter.next().unwrap_or(&true));
+    let kept_names: HashSet<String> = module.cells.iter().map(|c| c.name.clone()).collect();
+    module.source_map.retain(|e| kept_names.contains(&e.cell));
+}
+
+/// Identifier-like words in a type string or name, e.g. `list[Point]` yields
+/// `list` and `Point`.
+fn name_tokens(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+/// Add `items` to `set`, reporting whether anything was new.
+fn extend_new(set: &mut HashSet<String>, items: impl Iterator<Item = String>) -> bool {
+    let mut added = false;
+    for item in items {
+        added |= set.insert(item);
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::tokens::Span;
+
+    fn int(n: i64) -> Expr {
+        Expr::IntLit(n, Span::dummy())
+    }
+
+    fn bin(lhs: Expr, op: BinOp, rhs: Expr) -> Expr {
+        Expr::BinOp(Box::new(lhs), op, Box::new(rhs), Span::dummy())
+    }
+
+    #[test]
+    fn folds_nested_int_arithmetic() {
+        let expr = bin(int(2), BinOp::Add, bin(int(3), BinOp::Mul, int(4)));
+        assert!(matches!(fold_constant(&expr), Some(Expr::IntLit(14, _))));
+    }
+
+    #[test]
+    fn leaves_runtime_errors_unfolded() {
+        assert!(fold_constant(&bin(int(i64::MAX), BinOp::Add, int(1))).is_none());
+        assert!(fold_constant(&bin(int(1), BinOp::Div, int(0))).is_none());
+        assert!(fold_constant(&bin(int(i64::MIN), BinOp::FloorDiv, int(-1))).is_none());
+        assert!(fold_constant(&bin(int(2), BinOp::Pow, int(-1))).is_none());
+    }
+
+    #[test]
+    fn folds_strings_and_bools() {
+        let s = |v: &str| Expr::StringLit(v.to_string(), Span::dummy());
+        assert!(matches!(
+            fold_constant(&bin(s("a"), BinOp::Add, s("b"))),
+            Some(Expr::StringLit(ref v, _)) if v == "ab"
+        ));
+        let b = |v| Expr::BoolLit(v, Span::dummy());
+        assert!(matches!(
+            fold_constant(&bin(b(true), BinOp::And, b(false))),
+            Some(Expr::BoolLit(false, _))
+        ));
+    }
+
+    #[test]
+    fn leaves_non_constant_operands_alone() {
+        let x = Expr::Ident("x".to_string(), Span::dummy());
+        assert!(fold_constant(&bin(x, BinOp::Add, int(1))).is_none());
+        assert!(fold_constant(&int(1)).is_none());
+    }
+}
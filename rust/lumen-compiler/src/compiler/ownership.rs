@@ -177,6 +177,7 @@ pub fn ownership_mode_for_type(ty: &Type) -> OwnershipMode {
         Type::Enum(_) => OwnershipMode::Owned,
         // Compound / heap types — must be moved
         Type::List(_)
+        | Type::Array(_, _)
         | Type::Map(_, _)
         | Type::Set(_)
         | Type::Tuple(_)
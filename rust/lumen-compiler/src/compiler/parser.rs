@@ -552,6 +552,7 @@ impl Parser {
                 is_async: false,
                 is_extern: false,
                 must_use: false,
+                is_pure: false,
                 where_clauses: vec![],
                 span: span_start.merge(end_span),
                 doc: None,
@@ -570,14 +571,15 @@ impl Parser {
         })
     }
 
-    /// Check if current position is `@must_use` (@ followed by identifier "must_use")
-    fn is_must_use_attribute(&self) -> bool {
+    /// Check if current position is a cell attribute: `@must_use` or `@pure`
+    /// (@ followed by one of those identifiers)
+    fn is_cell_attribute(&self) -> bool {
         if !matches!(self.peek_kind(), TokenKind::At) {
             return false;
         }
-        // Look ahead: position after @ should be ident "must_use"
+        // Look ahead: position after @ should be ident "must_use" or "pure"
         if let Some(tok) = self.tokens.get(self.pos + 1) {
-            matches!(&tok.kind, TokenKind::Ident(name) if name == "must_use")
+            matches!(&tok.kind, TokenKind::Ident(name) if name == "must_use" || name == "pure")
         } else {
             false
         }
@@ -666,35 +668,46 @@ impl Parser {
                 Ok(Item::Cell(c))
             }
             TokenKind::At => {
-                // Check for @must_use before a cell definition
-                if self.is_must_use_attribute() {
-                    self.advance(); // consume '@'
-                    self.advance(); // consume 'must_use'
-                    self.skip_newlines();
+                // Check for @must_use / @pure before a cell definition.
+                // Both may be stacked in any order: `@pure @must_use cell ...`
+                if self.is_cell_attribute() {
+                    let mut must_use = false;
+                    let mut is_pure = false;
+                    let mut last_attr = String::new();
+                    while self.is_cell_attribute() {
+                        self.advance(); // consume '@'
+                        last_attr = self.expect_ident()?;
+                        match last_attr.as_str() {
+                            "must_use" => must_use = true,
+                            _ => is_pure = true,
+                        }
+                        self.skip_newlines();
+                    }
                     if matches!(self.peek_kind(), TokenKind::Pub) {
                         // @must_use pub cell ...
                         self.advance();
                         self.skip_newlines();
                         let mut c = self.parse_cell(true)?;
                         c.is_pub = true;
-                        c.must_use = true;
+                        c.must_use = must_use;
+                        c.is_pure = is_pure;
                         Ok(Item::Cell(c))
                     } else if matches!(self.peek_kind(), TokenKind::Cell) {
                         let mut c = self.parse_cell(true)?;
                         c.is_pub = is_pub;
-                        c.must_use = true;
+                        c.must_use = must_use;
+                        c.is_pure = is_pure;
                         Ok(Item::Cell(c))
                     } else {
-                        // @must_use not followed by cell — treat as regular attribute
-                        // Back up and re-parse as attribute
-                        // Since we already consumed @ and must_use, just make an AddonDecl
+                        // Attribute not followed by cell — treat as regular attribute
+                        // Since we already consumed the attribute, just make an AddonDecl
                         let end = self.current().span;
                         if matches!(self.peek_kind(), TokenKind::Newline) {
                             self.skip_newlines();
                         }
                         Ok(Item::Addon(AddonDecl {
                             kind: "attribute".into(),
-                            name: Some("must_use".to_string()),
+                            name: Some(last_attr),
                             span: end,
                         }))
                     }
@@ -1086,6 +1099,7 @@ impl Parser {
                 is_async: false,
                 is_extern: false,
                 must_use: false,
+                is_pure: false,
                 where_clauses: vec![],
                 span,
                 doc: None,
@@ -1124,6 +1138,7 @@ impl Parser {
                     is_async: false,
                     is_extern: false,
                     must_use: false,
+                    is_pure: false,
                     where_clauses: vec![],
                     span: start.merge(end_span),
                     doc: None,
@@ -1148,6 +1163,7 @@ impl Parser {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![],
             span: start.merge(end_span),
             doc: None,
@@ -1628,19 +1644,20 @@ impl Parser {
                 start
             };
 
-            // Build match arms: pattern => then_body, _ => else_body
-            let mut arms = vec![MatchArm {
-                pattern,
-                body: then_body,
-                span: start,
-            }];
-            if let Some(eb) = else_body {
-                arms.push(MatchArm {
+            // Build match arms: pattern => then_body, _ => else_body (or
+            // nothing), so a missing else never makes the match non-exhaustive
+            let arms = vec![
+                MatchArm {
+                    pattern,
+                    body: then_body,
+                    span: start,
+                },
+                MatchArm {
                     pattern: Pattern::Wildcard(start),
-                    body: eb,
+                    body: else_body.unwrap_or_default(),
                     span: start,
-                });
-            }
+                },
+            ];
 
             return Ok(Stmt::Match(MatchStmt {
                 subject,
@@ -1795,7 +1812,11 @@ impl Parser {
             } else {
                 first_pattern
             };
-            if matches!(self.peek_kind(), TokenKind::If) {
+            // Arm guard: `pattern if cond`, `pattern when cond`, or `pattern where cond`
+            if matches!(
+                self.peek_kind(),
+                TokenKind::If | TokenKind::When | TokenKind::Where
+            ) {
                 self.advance();
                 let guard = self.parse_expr(0)?;
                 pattern = Pattern::Guard {
@@ -2494,6 +2515,24 @@ impl Parser {
             self.advance();
             path.push(self.expect_ident()?);
         }
+        // `import path as alias` imports everything under `alias.`; a name
+        // list may still follow to restrict it.
+        let alias = if matches!(self.peek_kind(), TokenKind::As) {
+            self.advance();
+            Some(self.expect_ident()?)
+        } else {
+            None
+        };
+        if alias.is_some() && !matches!(self.peek_kind(), TokenKind::Colon) {
+            let span = start.merge(self.current().span);
+            return Ok(ImportDecl {
+                path,
+                names: ImportList::Wildcard,
+                alias,
+                is_pub,
+                span,
+            });
+        }
         self.expect(&TokenKind::Colon)?;
         let names = if matches!(self.peek_kind(), TokenKind::Star) {
             self.advance();
@@ -2525,6 +2564,7 @@ impl Parser {
         Ok(ImportDecl {
             path,
             names,
+            alias,
             is_pub,
             span,
         })
@@ -2932,6 +2972,7 @@ impl Parser {
         let mut cells = Vec::new();
         let mut grants = Vec::new();
         let mut pipeline_stages = Vec::new();
+        let mut pipeline_stage_spans = Vec::new();
         let mut machine_initial = None;
         let mut machine_states = Vec::new();
         let mut configs = BTreeMap::new();
@@ -2980,7 +3021,8 @@ impl Parser {
                 }
                 TokenKind::Grant => grants.push(self.parse_grant()?),
                 TokenKind::Ident(name) if kind == "pipeline" && name == "stages" => {
-                    pipeline_stages = self.parse_pipeline_stages_decl()?;
+                    (pipeline_stages, pipeline_stage_spans) =
+                        self.parse_pipeline_stages_decl()?.into_iter().unzip();
                 }
                 TokenKind::Ident(name) if kind == "machine" && name == "initial" => {
                     machine_initial = Some(self.parse_machine_initial_decl()?);
@@ -3055,13 +3097,14 @@ impl Parser {
             cells,
             grants,
             pipeline_stages,
+            pipeline_stage_spans,
             machine_initial,
             machine_states,
             span: start.merge(end_span),
         })
     }
 
-    fn parse_pipeline_stages_decl(&mut self) -> Result<Vec<String>, ParseError> {
+    fn parse_pipeline_stages_decl(&mut self) -> Result<Vec<(String, Span)>, ParseError> {
         let kw = self.expect_ident()?;
         if kw != "stages" {
             let tok = self.current().clone();
@@ -3099,7 +3142,8 @@ impl Parser {
             if matches!(self.peek_kind(), TokenKind::End | TokenKind::Eof) {
                 break;
             }
-            stages.push(self.parse_dotted_ident()?);
+            let span = self.current().span;
+            stages.push((self.parse_dotted_ident()?, span));
             self.consume_rest_of_line();
             self.skip_newlines();
         }
@@ -3503,6 +3547,7 @@ impl Parser {
                 is_async: false,
                 is_extern: false,
                 must_use: false,
+                is_pure: false,
                 where_clauses: vec![],
                 span,
                 doc: None,
@@ -3537,6 +3582,7 @@ impl Parser {
                     is_async: false,
                     is_extern: false,
                     must_use: false,
+                    is_pure: false,
                     where_clauses: vec![],
                     span: start.merge(end_span),
                     doc: None,
@@ -3559,6 +3605,7 @@ impl Parser {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![],
             span: start.merge(end_span),
             doc: None,
@@ -4376,12 +4423,43 @@ impl Parser {
                 self.expect(&TokenKind::RParen)?;
                 Ok(TypeExpr::Tuple(types, s))
             }
+            TokenKind::LBracket => {
+                // Fixed-size array type: [T; N]
+                let s = self.advance().span;
+                let inner = self.parse_type()?;
+                self.expect(&TokenKind::Semicolon)?;
+                let tok = self.current().clone();
+                let len = match tok.kind {
+                    TokenKind::IntLit(n) if n >= 0 => {
+                        self.advance();
+                        n as usize
+                    }
+                    _ => {
+                        return Err(ParseError::Unexpected {
+                            found: format!("{}", tok.kind),
+                            expected: "array length".into(),
+                            line: tok.span.line,
+                            col: tok.span.col,
+                        })
+                    }
+                };
+                self.expect(&TokenKind::RBracket)?;
+                Ok(TypeExpr::Array(Box::new(inner), len, s))
+            }
             TokenKind::Yield => {
                 self.advance();
                 self.parse_base_type()
             }
             TokenKind::Ident(_) => {
-                let name = self.expect_ident()?;
+                let mut name = self.expect_ident()?;
+                // Module-qualified name: alias.Type
+                while matches!(self.peek_kind(), TokenKind::Dot)
+                    && matches!(self.peek_n_kind(1), Some(TokenKind::Ident(_)))
+                {
+                    self.advance();
+                    name.push('.');
+                    name.push_str(&self.expect_ident()?);
+                }
                 let span = self.current().span;
                 // Check for generic: Name[T, U]
                 if matches!(self.peek_kind(), TokenKind::LBracket) {
@@ -5505,8 +5583,7 @@ impl Parser {
                         span,
                     ));
                 }
-                let span = self.current().span;
-                Ok(Expr::Ident(name, span))
+                Ok(Expr::Ident(name, start))
             }
             // Type keywords used as function names in expression position
             TokenKind::String_ => {
@@ -5731,6 +5808,10 @@ impl Parser {
             if !args.is_empty() {
                 self.expect(&TokenKind::Comma)?;
                 self.skip_whitespace_tokens();
+                // Trailing comma: `f(a, b,)`
+                if matches!(self.peek_kind(), TokenKind::RParen) {
+                    break;
+                }
             }
             if matches!(self.peek_kind(), TokenKind::Schema) {
                 let ks = self.advance().span;
@@ -5782,7 +5863,7 @@ impl Parser {
                     self.advance();
                     self.skip_whitespace_tokens();
                     let val = self.parse_expr(0)?;
-                    let span = val.span();
+                    let span = self.tokens[save].span.merge(val.span());
                     args.push(CallArg::Named(name_clone, val, span));
                 } else {
                     // Property shorthand: Point(x, y) => Point(x: x, y: y)
@@ -7006,10 +7087,35 @@ end"#;
         let Stmt::Match(ms) = &c.body[0] else {
             panic!("expected match from if-let desugar");
         };
-        // No else branch means only one match arm
-        assert_eq!(ms.arms.len(), 1);
+        // No else branch means an empty wildcard arm
+        assert_eq!(ms.arms.len(), 2);
         assert!(matches!(&ms.arms[0].pattern, Pattern::Variant(name, _, _)
             if name == "ok"));
+        assert!(matches!(&ms.arms[1].pattern, Pattern::Wildcard(_)));
+        assert!(ms.arms[1].body.is_empty());
+    }
+
+    #[test]
+    fn test_match_arm_guard_keywords() {
+        for keyword in ["if", "when", "where"] {
+            let src = format!(
+                "cell test(x: Int) -> Int\n  match x\n    n {} n > 0 -> return n\n    _ -> return 0\n  end\nend",
+                keyword
+            );
+            let prog = parse_src(&src).unwrap();
+            let Item::Cell(c) = &prog.items[0] else {
+                panic!("expected cell");
+            };
+            let Stmt::Match(ms) = &c.body[0] else {
+                panic!("expected match");
+            };
+            assert!(
+                matches!(&ms.arms[0].pattern, Pattern::Guard { inner, .. }
+                    if matches!(inner.as_ref(), Pattern::Ident(n, _) if n == "n")),
+                "guard keyword '{}' not parsed",
+                keyword
+            );
+        }
     }
 
     #[test]
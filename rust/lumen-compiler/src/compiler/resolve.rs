@@ -179,6 +179,19 @@ pub enum ResolveError {
         operation: String,
         line: usize,
     },
+    #[error("cell '{cell}' is declared @pure but {reason} (line {line})")]
+    ImpureCell {
+        cell: String,
+        reason: String,
+        line: usize,
+    },
+    #[error("const '{name}' initializer performs effect '{effect}'; cause: {cause} (line {line})")]
+    EffectfulConstInit {
+        name: String,
+        effect: String,
+        cause: String,
+        line: usize,
+    },
     #[error("machine '{machine}' initial state '{state}' is undefined (line {line})")]
     MachineUnknownInitial {
         machine: String,
@@ -245,10 +258,11 @@ pub enum ResolveError {
         stage: String,
         line: usize,
     },
-    #[error("pipeline '{pipeline}' stage type mismatch from '{from_stage}' to '{to_stage}' at line {line}: expected {expected}, got {actual}")]
+    #[error("pipeline '{pipeline}' stage type mismatch: '{from_stage}' (line {from_line}) outputs {actual}, but '{to_stage}' (line {line}) expects {expected}")]
     PipelineStageTypeMismatch {
         pipeline: String,
         from_stage: String,
+        from_line: usize,
         to_stage: String,
         expected: String,
         actual: String,
@@ -260,12 +274,25 @@ pub enum ResolveError {
     CircularImport { module: String, chain: String },
     #[error("module '{module}' not found at line {line}")]
     ModuleNotFound { module: String, line: usize },
+    #[error("module '{module}' has version {found}, which does not satisfy the required version {required} (line {line})")]
+    VersionMismatch {
+        module: String,
+        required: String,
+        found: String,
+        line: usize,
+    },
     #[error("imported symbol '{symbol}' not found in module '{module}' at line {line}")]
     ImportedSymbolNotFound {
         symbol: String,
         module: String,
         line: usize,
     },
+    #[error("'{symbol}' is private to module '{module}' and cannot be imported at line {line}; mark it `pub` to export it")]
+    ImportedSymbolPrivate {
+        symbol: String,
+        module: String,
+        line: usize,
+    },
     #[error(
         "impl for trait '{trait_name}' on '{target_type}' is missing required methods {missing:?} at line {line}"
     )]
@@ -321,6 +348,9 @@ pub struct SymbolTable {
     pub traits: HashMap<String, TraitInfo>,
     pub impls: Vec<ImplInfo>,
     pub consts: HashMap<String, ConstInfo>,
+    /// Imported cells known locally under another name (`alias.name` or an
+    /// `as` rename), mapped to the name the defining module compiled them as.
+    pub imported_cells: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -345,6 +375,8 @@ pub struct CellInfo {
     /// Generic type parameter names (e.g. ["T", "U"])
     pub generic_params: Vec<String>,
     pub must_use: bool,
+    /// Declared `@pure`; enforced by the purity check after effect inference.
+    pub is_pure: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -466,14 +498,35 @@ impl SymbolTable {
             traits: HashMap::new(),
             impls: Vec::new(),
             consts: HashMap::new(),
+            imported_cells: HashMap::new(),
         }
     }
 
-    /// Import a cell from an external module
-    pub fn import_cell(&mut self, name: String, info: CellInfo) {
+    /// Import cell `source_name` from an external module as `name`
+    pub fn import_cell(&mut self, name: String, source_name: &str, info: CellInfo) {
+        if name != source_name {
+            self.imported_cells
+                .insert(name.clone(), source_name.to_string());
+        }
         self.cells.insert(name, info);
     }
 
+    /// The name an imported symbol is registered under: `alias.name` when
+    /// its module was imported with `import path as alias`, otherwise `name`.
+    pub fn import_name(module_alias: Option<&str>, name: &str) -> String {
+        match module_alias {
+            Some(alias) => format!("{}.{}", alias, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// The name a cell of the imported module at `module_path` is compiled
+    /// under. Qualifying by module keeps same-named cells of different
+    /// modules apart once their LIR is merged.
+    pub fn module_cell_name(module_path: &str, name: &str) -> String {
+        format!("{}::{}", module_path, name)
+    }
+
     /// Import a type from an external module
     pub fn import_type(&mut self, name: String, info: TypeInfo) {
         self.types.insert(name, info);
@@ -483,6 +536,54 @@ impl SymbolTable {
     pub fn import_type_alias(&mut self, name: String, type_expr: TypeExpr) {
         self.type_aliases.insert(name, type_expr);
     }
+
+    /// Interpret a constructor-style pattern `Point(x, y)` as positional
+    /// destructuring of the record `name`.
+    ///
+    /// The parser cannot tell records from enum variants, so these patterns
+    /// arrive as `Pattern::Variant`. When `name` is a record type (and not
+    /// also a variant name) this returns the equivalent
+    /// `Pattern::RecordDestructure`, binding fields in declaration order.
+    /// An arity mismatch is reported as `Err((field_count, pattern_count))`.
+    pub fn positional_record_pattern(
+        &self,
+        name: &str,
+        payload: Option<&Pattern>,
+        span: crate::compiler::tokens::Span,
+    ) -> Option<Result<Pattern, (usize, usize)>> {
+        let TypeInfoKind::Record(def) = &self.types.get(name)?.kind else {
+            return None;
+        };
+        let is_variant = self.types.values().any(|t| {
+            matches!(&t.kind, TypeInfoKind::Enum(e) if e.variants.iter().any(|v| v.name == name))
+        });
+        if is_variant {
+            return None;
+        }
+
+        let elements: Vec<Pattern> = match payload {
+            None => Vec::new(),
+            Some(Pattern::TupleDestructure { elements, .. }) if def.fields.len() != 1 => {
+                elements.clone()
+            }
+            Some(p) => vec![p.clone()],
+        };
+        if elements.len() != def.fields.len() {
+            return Some(Err((def.fields.len(), elements.len())));
+        }
+        let fields = def
+            .fields
+            .iter()
+            .zip(elements)
+            .map(|(field, pat)| (field.name.clone(), Some(pat)))
+            .collect();
+        Some(Ok(Pattern::RecordDestructure {
+            type_name: name.to_string(),
+            fields,
+            open: false,
+            span,
+        }))
+    }
 }
 
 /// Resolve all names in a program, building the symbol table.
@@ -607,6 +708,7 @@ fn register_local_defs_in_body(
                                 .map(|gp| gp.name.clone())
                                 .collect(),
                             must_use: c.must_use,
+                            is_pure: c.is_pure,
                         });
                     }
                 }
@@ -721,6 +823,7 @@ fn resolve_with_base_inner(
                         effects: c.effects.clone(),
                         generic_params: c.generic_params.iter().map(|gp| gp.name.clone()).collect(),
                         must_use: c.must_use,
+                        is_pure: c.is_pure,
                     });
                 }
             },
@@ -789,6 +892,7 @@ fn resolve_with_base_inner(
                             effects: vec![],
                             generic_params: vec![],
                             must_use: false,
+                            is_pure: false,
                         },
                     );
                 }
@@ -817,6 +921,7 @@ fn resolve_with_base_inner(
                                     .map(|gp| gp.name.clone())
                                     .collect(),
                                 must_use: cell.must_use,
+                                is_pure: cell.is_pure,
                             });
                         }
                     }
@@ -918,6 +1023,7 @@ fn resolve_with_base_inner(
                             effects: vec![],
                             generic_params: vec![],
                             must_use: false,
+                            is_pure: false,
                         },
                     );
                 }
@@ -937,6 +1043,7 @@ fn resolve_with_base_inner(
                             .map(|gp| gp.name.clone())
                             .collect(),
                         must_use: cell.must_use,
+                        is_pure: cell.is_pure,
                     });
                 }
                 for g in &p.grants {
@@ -977,6 +1084,7 @@ fn resolve_with_base_inner(
                             .map(|gp| gp.name.clone())
                             .collect(),
                         must_use: false,
+                        is_pure: false,
                     });
                 }
             }
@@ -1021,6 +1129,7 @@ fn resolve_with_base_inner(
                             .map(|gp| gp.name.clone())
                             .collect(),
                         must_use: false,
+                        is_pure: false,
                     });
                 }
             }
@@ -1152,6 +1261,7 @@ fn resolve_with_base_inner(
                             effects: method.effects.clone(),
                             generic_params: method_generic_params,
                             must_use: method.must_use,
+                            is_pure: method.is_pure,
                         });
                     }
                 }
@@ -2000,16 +2110,20 @@ fn validate_pipeline_stages(
     }
 
     let mut previous_output: Option<TypeExpr> = None;
-    let mut previous_stage: Option<String> = None;
-    for stage in &process.pipeline_stages {
+    let mut previous_stage: Option<(String, usize)> = None;
+    for (idx, stage) in process.pipeline_stages.iter().enumerate() {
+        let line = process
+            .pipeline_stage_spans
+            .get(idx)
+            .map_or(process.span.line, |span| span.line);
         let Some(cell) = table.cells.get(stage) else {
             errors.push(ResolveError::PipelineUnknownStage {
                 pipeline: process.name.clone(),
                 stage: stage.clone(),
-                line: process.span.line,
+                line,
             });
             previous_output = None;
-            previous_stage = Some(stage.clone());
+            previous_stage = Some((stage.clone(), line));
             continue;
         };
 
@@ -2022,20 +2136,22 @@ fn validate_pipeline_stages(
             errors.push(ResolveError::PipelineStageArity {
                 pipeline: process.name.clone(),
                 stage: stage.clone(),
-                line: process.span.line,
+                line,
             });
         } else if let Some(prev_out) = previous_output.as_ref() {
             let expected = &non_self_params[0].1;
             if !pipeline_type_compatible(expected, prev_out) {
+                let (from_stage, from_line) = previous_stage
+                    .clone()
+                    .unwrap_or_else(|| ("<entry>".to_string(), process.span.line));
                 errors.push(ResolveError::PipelineStageTypeMismatch {
                     pipeline: process.name.clone(),
-                    from_stage: previous_stage
-                        .clone()
-                        .unwrap_or_else(|| "<entry>".to_string()),
+                    from_stage,
+                    from_line,
                     to_stage: stage.clone(),
                     expected: machine_type_key(expected),
                     actual: machine_type_key(prev_out),
-                    line: process.span.line,
+                    line,
                 });
             }
         }
@@ -2045,7 +2161,7 @@ fn validate_pipeline_stages(
                 .clone()
                 .unwrap_or(TypeExpr::Named("Any".to_string(), process.span)),
         );
-        previous_stage = Some(stage.clone());
+        previous_stage = Some((stage.clone(), line));
     }
 }
 
@@ -2089,6 +2205,7 @@ fn machine_type_key(ty: &TypeExpr) -> String {
             format!("({})", inner)
         }
         TypeExpr::Set(inner, _) => format!("set[{}]", machine_type_key(inner)),
+        TypeExpr::Array(inner, len, _) => format!("[{};{}]", machine_type_key(inner), len),
         TypeExpr::Fn(_, _, _, _) => "fn".to_string(),
         TypeExpr::Generic(name, _, _) => name.clone(),
     }
@@ -2407,6 +2524,36 @@ fn desugar_pipe_application(
     }
 }
 
+/// Method-call syntax `recv.method(args)` whose method names a user cell,
+/// rewritten to `method(recv, args)` as the typechecker and lowering do, so
+/// effect and purity checks see the cell call. Namespaced references
+/// (`module.cell`, agent, process and type members) are left alone.
+fn desugar_cell_method_call(
+    callee: &Expr,
+    args: &[CallArg],
+    span: crate::compiler::tokens::Span,
+    table: &SymbolTable,
+) -> Option<Expr> {
+    let Expr::DotAccess(receiver, method, _) = callee else {
+        return None;
+    };
+    if !table.cells.contains_key(method) {
+        return None;
+    }
+    if let Expr::Ident(owner, _) = receiver.as_ref() {
+        let namespaced = table.cells.contains_key(&format!("{}.{}", owner, method))
+            || table.agents.contains_key(owner)
+            || table.types.contains_key(owner)
+            || table.processes.values().any(|p| p.name == *owner);
+        if namespaced {
+            return None;
+        }
+    }
+    Some(crate::compiler::typecheck::desugar_method_call(
+        receiver, method, args, span,
+    ))
+}
+
 fn collect_pattern_call_requirements(
     pat: &Pattern,
     table: &SymbolTable,
@@ -2540,6 +2687,9 @@ fn collect_expr_call_requirements(
             collect_expr_call_requirements(handler, table, out);
         }
         Expr::Call(callee, args, span) => {
+            if let Some(call_expr) = desugar_cell_method_call(callee, args, *span, table) {
+                return collect_expr_call_requirements(&call_expr, table, out);
+            }
             collect_expr_call_requirements(callee, table, out);
             for a in args {
                 match a {
@@ -2548,14 +2698,14 @@ fn collect_expr_call_requirements(
                     }
                 }
             }
+            // Effect-free cell calls are recorded too so that the purity
+            // check can see every callee; they impose no effect requirement.
             if let Some((target, effects)) = resolve_call_target_effects(callee, table) {
-                if !effects.is_empty() {
-                    out.push(CallRequirement {
-                        callee: target,
-                        effects,
-                        line: span.line,
-                    });
-                }
+                out.push(CallRequirement {
+                    callee: target,
+                    effects,
+                    line: span.line,
+                });
             }
         }
         Expr::ToolCall(callee, args, span) => {
@@ -2851,6 +3001,9 @@ fn collect_expr_effect_evidence(
             push_effect_evidence(out, "async", span.line, "await expression".to_string());
         }
         Expr::Call(callee, args, span) => {
+            if let Some(call_expr) = desugar_cell_method_call(callee, args, *span, table) {
+                return collect_expr_effect_evidence(&call_expr, table, current, out);
+            }
             collect_expr_effect_evidence(callee, table, current, out);
             for a in args {
                 match a {
@@ -3260,7 +3413,10 @@ fn infer_expr_effects(
             infer_expr_effects(inner, table, current, out);
             infer_expr_effects(handler, table, current, out);
         }
-        Expr::Call(callee, args, _) => {
+        Expr::Call(callee, args, span) => {
+            if let Some(call_expr) = desugar_cell_method_call(callee, args, *span, table) {
+                return infer_expr_effects(&call_expr, table, current, out);
+            }
             infer_expr_effects(callee, table, current, out);
             for a in args {
                 match a {
@@ -3501,6 +3657,7 @@ fn apply_effect_inference(
     let enforce_declared_effect_rows = strict && !doc_mode;
     let cells = collect_effect_cells(program);
     if cells.is_empty() {
+        enforce_effect_free_consts(program, table, &HashMap::new(), errors);
         return;
     }
 
@@ -3574,6 +3731,8 @@ fn apply_effect_inference(
 
     enforce_effect_call_compatibility(program, table, &cells, errors);
     enforce_deterministic_profile(program, table, &cells, errors);
+    enforce_pure_cells(table, &cells, &effective, errors);
+    enforce_effect_free_consts(program, table, &effective, errors);
 }
 
 fn enforce_effect_call_compatibility(
@@ -3656,6 +3815,89 @@ fn enforce_deterministic_profile(
     }
 }
 
+/// Verify that every `@pure` cell performs no effects and only calls other
+/// `@pure` cells. Tool calls surface here as effects (`external` or the bound
+/// effect), so they are reported through the effect check.
+fn enforce_pure_cells(
+    table: &SymbolTable,
+    cells: &[EffectCell],
+    effective: &HashMap<String, BTreeSet<String>>,
+    errors: &mut Vec<ResolveError>,
+) {
+    for cell in cells {
+        let Some(info) = table.cells.get(&cell.name) else {
+            continue;
+        };
+        if !info.is_pure {
+            continue;
+        }
+
+        let effects = normalized_non_pure_effects(&info.effects);
+        if !effects.is_empty() {
+            let evidence = collect_cell_effect_evidence(cell, table, effective);
+            for effect in effects {
+                let (line, cause) = match evidence.get(&effect) {
+                    Some(ev) => (ev.line, format!("; cause: {}", ev.cause)),
+                    None => (cell.line, String::new()),
+                };
+                errors.push(ResolveError::ImpureCell {
+                    cell: cell.name.clone(),
+                    reason: format!("performs effect '{}'{}", effect, cause),
+                    line,
+                });
+            }
+        }
+
+        let mut reqs = Vec::new();
+        for stmt in &cell.body {
+            collect_stmt_call_requirements(stmt, table, &mut reqs);
+        }
+        let mut seen = BTreeSet::new();
+        for req in reqs {
+            let callee_pure = table.cells.get(&req.callee).is_some_and(|c| c.is_pure);
+            if req.callee.starts_with("tool ") || callee_pure {
+                continue;
+            }
+            if seen.insert(req.callee.clone()) {
+                errors.push(ResolveError::ImpureCell {
+                    cell: cell.name.clone(),
+                    reason: format!("calls '{}' which is not declared @pure", req.callee),
+                    line: req.line,
+                });
+            }
+        }
+    }
+}
+
+/// Verify that `const` initializers perform no effects. A const must be
+/// evaluable at compile time, so each effect is reported at the first call
+/// that introduces it.
+fn enforce_effect_free_consts(
+    program: &Program,
+    table: &SymbolTable,
+    effective: &HashMap<String, BTreeSet<String>>,
+    errors: &mut Vec<ResolveError>,
+) {
+    for item in &program.items {
+        let Item::ConstDecl(c) = item else {
+            continue;
+        };
+        let mut evidence = Vec::new();
+        collect_expr_effect_evidence(&c.value, table, effective, &mut evidence);
+        let mut seen = BTreeSet::new();
+        for ev in evidence {
+            if seen.insert(ev.effect.clone()) {
+                errors.push(ResolveError::EffectfulConstInit {
+                    name: c.name.clone(),
+                    effect: ev.effect,
+                    cause: ev.cause,
+                    line: ev.line,
+                });
+            }
+        }
+    }
+}
+
 /// Compute Levenshtein edit distance between two strings
 fn edit_distance(a: &str, b: &str) -> usize {
     let a_chars: Vec<char> = a.chars().collect();
@@ -3921,6 +4163,18 @@ fn type_expr_compatible(
                 actual_generics,
             )
         }
+        (
+            TypeExpr::Array(expected_inner, expected_len, _),
+            TypeExpr::Array(actual_inner, actual_len, _),
+        ) => {
+            expected_len == actual_len
+                && type_expr_compatible(
+                    expected_inner,
+                    actual_inner,
+                    expected_generics,
+                    actual_generics,
+                )
+        }
         (TypeExpr::Map(expected_k, expected_v, _), TypeExpr::Map(actual_k, actual_v, _))
         | (TypeExpr::Result(expected_k, expected_v, _), TypeExpr::Result(actual_k, actual_v, _)) => {
             type_expr_compatible(expected_k, actual_k, expected_generics, actual_generics)
@@ -4078,6 +4332,7 @@ fn format_type_expr(ty: &TypeExpr) -> String {
             format!("({})", rendered.join(", "))
         }
         TypeExpr::Set(inner, _) => format!("set[{}]", format_type_expr(inner)),
+        TypeExpr::Array(inner, len, _) => format!("[{}; {}]", format_type_expr(inner), len),
         TypeExpr::Fn(params, ret, effects, _) => {
             let rendered_params = params.iter().map(format_type_expr).collect::<Vec<_>>();
             if effects.is_empty() {
@@ -4155,7 +4410,7 @@ fn check_type_refs_with_generics(
                 check_type_refs_with_generics(t, table, type_alias_arities, errors, generics);
             }
         }
-        TypeExpr::Set(inner, _) => {
+        TypeExpr::Set(inner, _) | TypeExpr::Array(inner, _, _) => {
             check_type_refs_with_generics(inner, table, type_alias_arities, errors, generics)
         }
         TypeExpr::Fn(params, ret, _, _) => {
@@ -4256,6 +4511,16 @@ mod tests {
         assert!(effects.contains(&"emit".to_string()));
     }
 
+    #[test]
+    fn test_effect_inference_through_method_call() {
+        let table = resolve_src(
+            "cell shout(s: String) -> String / {emit}\n  emit(s)\n  return s\nend\n\ncell loud(s: String) -> String\n  return s.shout()\nend",
+        )
+        .unwrap();
+        let effects = &table.cells.get("loud").unwrap().effects;
+        assert!(effects.contains(&"emit".to_string()));
+    }
+
     #[test]
     fn test_undeclared_effect_error_in_strict_mode() {
         let sp = s();
@@ -4280,6 +4545,7 @@ mod tests {
                 is_extern: false,
                 where_clauses: vec![],
                 must_use: false,
+                is_pure: false,
                 span: sp,
                 doc: None,
                 deprecated: None,
@@ -4320,6 +4586,7 @@ mod tests {
                 is_async: false,
                 is_extern: false,
                 must_use: false,
+                is_pure: false,
                 where_clauses: vec![],
                 span: sp,
                 doc: None,
@@ -4544,6 +4811,37 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn test_pipeline_stage_type_mismatch_reports_both_stages() {
+        let err = resolve_src(
+            "cell produce(x: Int) -> Int\n  return x\nend\n\ncell consume(s: String) -> String\n  return s\nend\n\npipeline P\n  stages:\n    produce\n      -> consume\n  end\nend",
+        )
+        .unwrap_err();
+        let mismatch = err
+            .iter()
+            .find(|e| matches!(e, ResolveError::PipelineStageTypeMismatch { .. }))
+            .expect("expected a stage type mismatch");
+        let ResolveError::PipelineStageTypeMismatch {
+            from_stage,
+            from_line,
+            to_stage,
+            expected,
+            actual,
+            line,
+            ..
+        } = mismatch
+        else {
+            unreachable!()
+        };
+        assert_eq!((from_stage.as_str(), *from_line), ("produce", 11));
+        assert_eq!((to_stage.as_str(), *line), ("consume", 12));
+        assert_eq!((actual.as_str(), expected.as_str()), ("Int", "String"));
+        assert_eq!(
+            mismatch.to_string(),
+            "pipeline 'P' stage type mismatch: 'produce' (line 11) outputs Int, but 'consume' (line 12) expects String"
+        );
+    }
+
     #[test]
     fn test_duplicate_record_detection() {
         let err =
@@ -3,9 +3,13 @@
 use crate::compiler::ast::*;
 use crate::compiler::resolve::SymbolTable;
 
-use std::collections::HashMap;
+use crate::compiler::tokens::Span;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
+/// Largest integer magnitude a `Float` (f64) represents exactly: 2^53.
+const MAX_EXACT_FLOAT_INT: i128 = 1 << 53;
+
 /// Check if a name is a built-in function
 fn is_builtin_function(name: &str) -> bool {
     matches!(
@@ -292,10 +296,27 @@ fn desugar_pipe_application(
     }
 }
 
+/// Rewrite `receiver.method(args)` as `method(receiver, args)`.
+pub(crate) fn desugar_method_call(
+    receiver: &Expr,
+    method: &str,
+    args: &[CallArg],
+    span: Span,
+) -> Expr {
+    let mut call_args = Vec::with_capacity(args.len() + 1);
+    call_args.push(CallArg::Positional(receiver.clone()));
+    call_args.extend(args.iter().cloned());
+    Expr::Call(
+        Box::new(Expr::Ident(method.to_string(), span)),
+        call_args,
+        span,
+    )
+}
+
 fn type_contains_any(ty: &Type) -> bool {
     match ty {
         Type::Any => true,
-        Type::List(inner) | Type::Set(inner) => type_contains_any(inner),
+        Type::List(inner) | Type::Set(inner) | Type::Array(inner, _) => type_contains_any(inner),
         Type::Map(k, v) | Type::Result(k, v) => type_contains_any(k) || type_contains_any(v),
         Type::Fn(params, ret) => params.iter().any(type_contains_any) || type_contains_any(ret),
         Type::Union(types) | Type::Tuple(types) => types.iter().any(type_contains_any),
@@ -344,6 +365,23 @@ fn edit_distance(a: &str, b: &str) -> usize {
     matrix[a_len][b_len]
 }
 
+/// The label of a named argument, which starts its `label: value` span.
+fn label_span(label: &str, arg_span: Span) -> Span {
+    Span {
+        end: (arg_span.start + label.len()).min(arg_span.end),
+        ..arg_span
+    }
+}
+
+/// Types whose shape is not known statically; destructuring them is checked
+/// at runtime instead.
+fn is_opaque_subject(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Any | Type::Json | Type::Generic(_) | Type::TypeRef(..) | Type::Union(_)
+    )
+}
+
 /// Find similar names for "did you mean?" suggestions
 fn suggest_similar(name: &str, candidates: &[&str], max_distance: usize) -> Vec<String> {
     let mut matches: Vec<(usize, String)> = candidates
@@ -387,6 +425,20 @@ pub enum TypeError {
         line: usize,
         suggestions: Vec<String>,
     },
+    /// `span` covers the repeated field label.
+    #[error("duplicate field '{field}' in construction of '{ty}' at line {}", .span.line)]
+    DuplicateField {
+        field: String,
+        ty: String,
+        span: Span,
+    },
+    /// `span` covers the whole construction.
+    #[error("missing field '{field}' in construction of '{ty}' at line {}", .span.line)]
+    MissingField {
+        field: String,
+        ty: String,
+        span: Span,
+    },
     #[error("undefined type '{name}' at line {line}")]
     UndefinedType { name: String, line: usize },
     #[error("missing return in cell '{name}' at line {line}")]
@@ -401,6 +453,31 @@ pub enum TypeError {
     },
     #[error("unused result of @must_use cell '{name}' at line {line}")]
     MustUseIgnored { name: String, line: usize },
+    #[error("array literal has {actual} elements but type [_; {expected}] requires {expected} at line {line}")]
+    ArrayLengthMismatch {
+        expected: usize,
+        actual: usize,
+        line: usize,
+    },
+    #[error("index {index} is out of bounds for array of length {len} at line {line}")]
+    IndexOutOfBounds { index: i64, len: usize, line: usize },
+    #[error("no method '{method}' for receiver of type {receiver} at line {line}: no cell or builtin named '{method}' takes {receiver} as its first argument")]
+    UnknownMethod {
+        method: String,
+        receiver: String,
+        line: usize,
+        suggestions: Vec<String>,
+    },
+    #[error("cell '{name}' recurses on every path at line {line}: no reachable base case")]
+    UnboundedRecursion { name: String, line: usize },
+    #[error("integer {value} exceeds 2^53 and loses precision when implicitly widened to Float at line {line}")]
+    PrecisionLoss { value: String, line: usize },
+    #[error("ambiguous method call '.{method}(...)' on {receiver} at line {line}: matches both field '{method}' and cell '{method}'")]
+    AmbiguousMethod {
+        method: String,
+        receiver: String,
+        line: usize,
+    },
 }
 
 /// Resolved type representation
@@ -421,6 +498,8 @@ pub enum Type {
     Union(Vec<Type>),
     Tuple(Vec<Type>),
     Set(Box<Type>),
+    /// Fixed-size array `[T; N]`; represented as a list at runtime.
+    Array(Box<Type>, usize),
     Fn(Vec<Type>, Box<Type>),
     Generic(String),
     TypeRef(String, Vec<Type>),
@@ -452,6 +531,7 @@ impl std::fmt::Display for Type {
                 write!(f, "({})", parts.join(", "))
             }
             Type::Set(t) => write!(f, "set[{}]", t),
+            Type::Array(t, n) => write!(f, "[{}; {}]", t, n),
             Type::Fn(params, ret) => {
                 let ps: Vec<_> = params.iter().map(|t| format!("{}", t)).collect();
                 write!(f, "fn({}) -> {}", ps.join(", "), ret)
@@ -544,7 +624,8 @@ fn unify_for_inference_inner(
                 inferred.entry(name.clone()).or_insert_with(|| ty.clone());
             }
         }
-        (TypeExpr::List(inner, _), Type::List(inner_ty)) => {
+        (TypeExpr::List(inner, _), Type::List(inner_ty) | Type::Array(inner_ty, _))
+        | (TypeExpr::Array(inner, _, _), Type::List(inner_ty) | Type::Array(inner_ty, _)) => {
             unify_for_inference_inner(inner, inner_ty, _symbols, inferred, generic_param_names);
         }
         (TypeExpr::Map(k, v, _), Type::Map(kt, vt)) => {
@@ -641,6 +722,10 @@ fn resolve_type_expr_with_subst(ty: &TypeExpr, symbols: &SymbolTable, subst: &Ty
         TypeExpr::Set(inner, _) => Type::Set(Box::new(resolve_type_expr_with_subst(
             inner, symbols, subst,
         ))),
+        TypeExpr::Array(inner, len, _) => Type::Array(
+            Box::new(resolve_type_expr_with_subst(inner, symbols, subst)),
+            *len,
+        ),
         TypeExpr::Fn(params, ret, _, _) => {
             let param_types = params
                 .iter()
@@ -685,12 +770,31 @@ struct TypeChecker<'a> {
     locals: HashMap<String, Type>,
     mutables: HashMap<String, bool>,
     errors: Vec<TypeError>,
+    /// Findings that don't fail the check, reported as compile warnings.
+    warnings: Vec<TypeError>,
+    /// Spans of `receiver.method(args)` calls resolved to `method(receiver, args)`.
+    method_calls: HashSet<Span>,
+    /// `return` types (and lines) seen while checking a cell declared
+    /// without a return type; `None` when the cell is annotated.
+    returns: Option<Vec<(Type, usize)>>,
+    /// Return type inferred for the most recently checked unannotated cell.
+    inferred_return: Option<Type>,
+    /// Top-level cells declared without a return type, by name.
+    unannotated: HashMap<String, &'a CellDef>,
+    /// Return types inferred for `unannotated` cells so far; `None` while the
+    /// cell's own inference is in progress.
+    inferred_returns: HashMap<String, Option<Type>>,
+    /// Type bound by each `let` and each `for` loop variable, keyed by the
+    /// statement's span.
+    let_types: HashMap<Span, Type>,
 }
 
+/// A call argument's type and line, plus the text of the argument if it is an
+/// integer too large to widen to `Float` exactly (see [`TypeChecker::large_int`]).
 #[derive(Debug)]
 enum CheckedCallArg {
-    Positional(Type, usize),
-    Named(String, Type, usize),
+    Positional(Type, usize, Option<String>),
+    Named(String, Type, usize, Option<String>),
 }
 
 impl<'a> TypeChecker<'a> {
@@ -701,6 +805,13 @@ impl<'a> TypeChecker<'a> {
             locals: HashMap::new(),
             mutables: HashMap::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
+            method_calls: HashSet::new(),
+            returns: None,
+            inferred_return: None,
+            unannotated: HashMap::new(),
+            inferred_returns: HashMap::new(),
+            let_types: HashMap::new(),
         }
     }
 
@@ -723,12 +834,83 @@ impl<'a> TypeChecker<'a> {
         } else {
             None
         };
+        self.returns = return_type.is_none().then(Vec::new);
 
         let body_len = cell.body.len();
         for (i, stmt) in cell.body.iter().enumerate() {
             let is_tail = body_len > 0 && i == body_len - 1;
             self.check_stmt(stmt, return_type.as_ref(), is_tail);
         }
+        if recurses_on_every_path(cell) {
+            self.warnings.push(TypeError::UnboundedRecursion {
+                name: cell.name.clone(),
+                line: cell.span.line,
+            });
+        }
+
+        self.inferred_return = self
+            .returns
+            .take()
+            .map(|returns| self.unify_returns(&returns));
+    }
+
+    /// Return type of the unannotated top-level cell `name`, inferred from
+    /// its body the first time it is needed. A call reached while the cell's
+    /// own inference is in progress (recursion) is typed `Any`.
+    fn inferred_return_type(&mut self, name: &str) -> Option<Type> {
+        let cell = *self.unannotated.get(name)?;
+        if let Some(known) = self.inferred_returns.get(name) {
+            return Some(known.clone().unwrap_or(Type::Any));
+        }
+        self.inferred_returns.insert(name.to_string(), None);
+        // Check the callee in a scope of its own; its errors are reported
+        // when the cell itself is checked.
+        let locals = std::mem::take(&mut self.locals);
+        let mutables = std::mem::take(&mut self.mutables);
+        let returns = self.returns.take();
+        let errors = std::mem::take(&mut self.errors);
+        let warnings = std::mem::take(&mut self.warnings);
+        self.check_cell(cell);
+        let ty = self.inferred_return.take().unwrap_or(Type::Any);
+        self.locals = locals;
+        self.mutables = mutables;
+        self.returns = returns;
+        self.errors = errors;
+        self.warnings = warnings;
+        self.inferred_returns
+            .insert(name.to_string(), Some(ty.clone()));
+        Some(ty)
+    }
+
+    /// Combine the `return` types of an unannotated cell into its return
+    /// type. All returns must agree, except that returning `null` alongside a
+    /// `T` infers `T | Null`; a cell without returns returns `Null`.
+    fn unify_returns(&mut self, returns: &[(Type, usize)]) -> Type {
+        let mut inferred: Option<Type> = None;
+        let mut nullable = false;
+        for (ty, line) in returns {
+            match (ty, &inferred) {
+                (Type::Null, _) => nullable = true,
+                (Type::Any, _) => {}
+                (_, None) => inferred = Some(ty.clone()),
+                (_, Some(Type::Any)) => inferred = Some(ty.clone()),
+                (_, Some(first)) if first == ty => {}
+                (_, Some(first)) => {
+                    self.errors.push(TypeError::Mismatch {
+                        expected: first.to_string(),
+                        actual: ty.to_string(),
+                        line: *line,
+                    });
+                }
+            }
+        }
+        let any_return = returns.iter().any(|(ty, _)| matches!(ty, Type::Any));
+        match inferred {
+            Some(ty) if nullable => Type::Union(vec![ty, Type::Null]),
+            Some(ty) => ty,
+            None if any_return => Type::Any,
+            None => Type::Null,
+        }
     }
 
     fn check_agent_cell(&mut self, cell: &CellDef) {
@@ -784,23 +966,26 @@ impl<'a> TypeChecker<'a> {
         let mut positional_idx = 0usize;
         for arg in args {
             match arg {
-                CheckedCallArg::Positional(actual_ty, arg_line) => {
+                CheckedCallArg::Positional(actual_ty, arg_line, large) => {
                     if positional_idx < fixed_count {
                         if let Some((_, expected_expr, _)) = params.get(positional_idx) {
                             let expected_ty = resolve_type_expr(expected_expr, self.symbols);
                             self.check_compat(&expected_ty, actual_ty, *arg_line);
+                            self.warn_precision_loss(&expected_ty, large, *arg_line);
                         }
                     } else if has_variadic {
                         let (_, variadic_expr, _) = &params[params.len() - 1];
                         let elem_ty = resolve_type_expr(variadic_expr, self.symbols);
                         self.check_compat(&elem_ty, actual_ty, *arg_line);
+                        self.warn_precision_loss(&elem_ty, large, *arg_line);
                     }
                     positional_idx += 1;
                 }
-                CheckedCallArg::Named(name, actual_ty, arg_line) => {
+                CheckedCallArg::Named(name, actual_ty, arg_line, large) => {
                     if let Some((_, expected_expr, _)) = params.iter().find(|(p, _, _)| p == name) {
                         let expected_ty = resolve_type_expr(expected_expr, self.symbols);
                         self.check_compat(&expected_ty, actual_ty, *arg_line);
+                        self.warn_precision_loss(&expected_ty, large, *arg_line);
                     } else {
                         self.errors.push(TypeError::Mismatch {
                             expected: format!("parameter '{}'", name),
@@ -840,26 +1025,29 @@ impl<'a> TypeChecker<'a> {
         let mut positional_idx = 0usize;
         for arg in args {
             match arg {
-                CheckedCallArg::Positional(actual_ty, arg_line) => {
+                CheckedCallArg::Positional(actual_ty, arg_line, large) => {
                     if positional_idx < fixed_count {
                         if let Some((_, expected_expr, _)) = params.get(positional_idx) {
                             let expected_ty =
                                 resolve_type_expr_with_subst(expected_expr, self.symbols, subst);
                             self.check_compat(&expected_ty, actual_ty, *arg_line);
+                            self.warn_precision_loss(&expected_ty, large, *arg_line);
                         }
                     } else if has_variadic {
                         let (_, variadic_expr, _) = &params[params.len() - 1];
                         let elem_ty =
                             resolve_type_expr_with_subst(variadic_expr, self.symbols, subst);
                         self.check_compat(&elem_ty, actual_ty, *arg_line);
+                        self.warn_precision_loss(&elem_ty, large, *arg_line);
                     }
                     positional_idx += 1;
                 }
-                CheckedCallArg::Named(name, actual_ty, arg_line) => {
+                CheckedCallArg::Named(name, actual_ty, arg_line, large) => {
                     if let Some((_, expected_expr, _)) = params.iter().find(|(p, _, _)| p == name) {
                         let expected_ty =
                             resolve_type_expr_with_subst(expected_expr, self.symbols, subst);
                         self.check_compat(&expected_ty, actual_ty, *arg_line);
+                        self.warn_precision_loss(&expected_ty, large, *arg_line);
                     } else {
                         self.errors.push(TypeError::Mismatch {
                             expected: format!("parameter '{}'", name),
@@ -875,11 +1063,18 @@ impl<'a> TypeChecker<'a> {
     fn check_stmt(&mut self, stmt: &Stmt, expected_return: Option<&Type>, is_tail: bool) {
         match stmt {
             Stmt::Let(ls) => {
-                let val_type = self.infer_expr(&ls.value);
+                let mut val_type = self.infer_expr(&ls.value);
                 if let Some(ref ann) = ls.ty {
                     let expected = resolve_type_expr(ann, self.symbols);
                     self.check_compat(&expected, &val_type, ls.span.line);
+                    self.check_widening(&expected, &ls.value, ls.span.line);
+                    self.check_array_literal(&expected, &ls.value, ls.span.line);
+                    // Keep the declared length so constant indices can be checked.
+                    if matches!(expected, Type::Array(..)) {
+                        val_type = expected;
+                    }
                 }
+                self.let_types.insert(ls.span, val_type.clone());
                 if let Some(ref pattern) = ls.pattern {
                     // Destructuring let — register all bound names from the pattern
                     self.bind_let_pattern(pattern, &val_type, ls.span.line);
@@ -939,7 +1134,7 @@ impl<'a> TypeChecker<'a> {
             Stmt::For(fs) => {
                 let iter_type = self.infer_expr(&fs.iter);
                 let elem_type = match &iter_type {
-                    Type::List(inner) => *inner.clone(),
+                    Type::List(inner) | Type::Array(inner, _) => *inner.clone(),
                     Type::Set(inner) => *inner.clone(),
                     Type::Map(k, _) => *k.clone(),
                     Type::Any => Type::Any,
@@ -952,6 +1147,7 @@ impl<'a> TypeChecker<'a> {
                         Type::Any
                     }
                 };
+                self.let_types.insert(fs.span, elem_type.clone());
                 self.locals.insert(fs.var.clone(), elem_type);
                 if let Some(filter) = &fs.filter {
                     self.infer_expr(filter);
@@ -1010,6 +1206,10 @@ impl<'a> TypeChecker<'a> {
                 let val_type = self.infer_expr(&rs.value);
                 if let Some(expected) = expected_return {
                     self.check_compat(expected, &val_type, rs.span.line);
+                    self.check_widening(expected, &rs.value, rs.span.line);
+                    self.check_array_literal(expected, &rs.value, rs.span.line);
+                } else if let Some(returns) = self.returns.as_mut() {
+                    returns.push((val_type, rs.span.line));
                 }
             }
             Stmt::Halt(hs) => {
@@ -1062,7 +1262,15 @@ impl<'a> TypeChecker<'a> {
                 }
             }
             Stmt::Expr(es) => {
-                self.infer_expr(&es.expr);
+                let ty = self.infer_expr(&es.expr);
+                // The tail expression is the cell's implicit return value.
+                if let (true, Some(expected)) = (is_tail, expected_return) {
+                    self.check_widening(expected, &es.expr, es.span.line);
+                } else if is_tail {
+                    if let Some(returns) = self.returns.as_mut() {
+                        returns.push((ty, es.span.line));
+                    }
+                }
                 // Check if this is a call to a @must_use cell whose result is discarded.
                 // Skip the check if this is the tail expression (implicit return).
                 if !is_tail {
@@ -1181,6 +1389,140 @@ impl<'a> TypeChecker<'a> {
         }
     }
 
+    /// Element types for an `arity`-element tuple pattern, reporting a shape mismatch.
+    fn destructure_tuple_types(
+        &mut self,
+        arity: usize,
+        subject_type: &Type,
+        line: usize,
+    ) -> Vec<Type> {
+        match subject_type {
+            Type::Tuple(types) if types.len() == arity => types.clone(),
+            Type::Tuple(types) => {
+                self.errors.push(TypeError::Mismatch {
+                    expected: format!("tuple of {} elements", types.len()),
+                    actual: format!("pattern with {} elements", arity),
+                    line,
+                });
+                vec![Type::Any; arity]
+            }
+            other if is_opaque_subject(other) => vec![Type::Any; arity],
+            other => {
+                self.errors.push(TypeError::Mismatch {
+                    expected: "Tuple".into(),
+                    actual: format!("{}", other),
+                    line,
+                });
+                vec![Type::Any; arity]
+            }
+        }
+    }
+
+    /// The record a record pattern destructures: its own type name, or the
+    /// subject's record type for anonymous `{ a, b }` patterns. Reports a
+    /// mismatch when the subject is a different record or not a record.
+    fn destructure_record_name(
+        &mut self,
+        type_name: &str,
+        subject_type: &Type,
+        line: usize,
+    ) -> String {
+        let actual = match subject_type {
+            Type::Record(name) | Type::TypeRef(name, _) => Some(name.as_str()),
+            other if is_opaque_subject(other) => None,
+            other => {
+                self.errors.push(TypeError::Mismatch {
+                    expected: if type_name.is_empty() {
+                        "record".into()
+                    } else {
+                        type_name.to_string()
+                    },
+                    actual: format!("{}", other),
+                    line,
+                });
+                None
+            }
+        };
+        match actual {
+            Some(actual) if type_name.is_empty() => actual.to_string(),
+            Some(actual) if actual != type_name => {
+                self.errors.push(TypeError::Mismatch {
+                    expected: type_name.to_string(),
+                    actual: actual.to_string(),
+                    line,
+                });
+                type_name.to_string()
+            }
+            _ => type_name.to_string(),
+        }
+    }
+
+    /// Type of `field_name` in record `record_name`, reporting unknown fields
+    /// of known records.
+    fn destructure_field_type(&mut self, record_name: &str, field_name: &str, line: usize) -> Type {
+        let Some(crate::compiler::resolve::TypeInfoKind::Record(def)) =
+            self.symbols.types.get(record_name).map(|ti| &ti.kind)
+        else {
+            return Type::Any;
+        };
+        if let Some(field) = def.fields.iter().find(|f| f.name == field_name) {
+            return resolve_type_expr(&field.ty, self.symbols);
+        }
+        let field_names: Vec<&str> = def.fields.iter().map(|f| f.name.as_str()).collect();
+        let suggestions = suggest_similar(field_name, &field_names, 2);
+        self.errors.push(TypeError::UnknownField {
+            field: field_name.to_string(),
+            ty: record_name.to_string(),
+            line,
+            suggestions,
+        });
+        Type::Any
+    }
+
+    /// Check the field labels of a record construction: each label may
+    /// appear once, and every field without a default or optional type must
+    /// be supplied. Unknown labels are reported by the caller, so a field that
+    /// one of them looks like a misspelling of is not also reported missing.
+    fn check_record_labels(
+        &mut self,
+        record_name: &str,
+        def: &RecordDef,
+        labels: &[(&str, Span)],
+        span: Span,
+    ) {
+        let mut seen = HashSet::new();
+        for (label, label_span) in labels {
+            if !seen.insert(*label) {
+                self.errors.push(TypeError::DuplicateField {
+                    field: label.to_string(),
+                    ty: record_name.to_string(),
+                    span: *label_span,
+                });
+            }
+        }
+        for field in &def.fields {
+            if field.default_value.is_some() || seen.contains(field.name.as_str()) {
+                continue;
+            }
+            let accepts_null = match resolve_type_expr(&field.ty, self.symbols) {
+                Type::Null | Type::Any => true,
+                Type::Union(members) => members.iter().any(|m| matches!(m, Type::Null)),
+                _ => false,
+            };
+            let misspelled = labels.iter().any(|(label, _)| {
+                !def.fields.iter().any(|f| f.name == *label)
+                    && !suggest_similar(label, &[field.name.as_str()], 2).is_empty()
+            });
+            if !accepts_null && !misspelled {
+                self.errors.push(TypeError::MissingField {
+                    field: field.name.clone(),
+                    ty: record_name.to_string(),
+                    span,
+                });
+            }
+        }
+    }
+
     /// Register variable bindings from an irrefutable destructuring pattern
     /// used in `let` position.  Walks the pattern tree and inserts each
     /// bound name into `self.locals` with the appropriate type.
@@ -1193,10 +1535,7 @@ impl<'a> TypeChecker<'a> {
             }
             Pattern::Wildcard(_) => {}
             Pattern::TupleDestructure { elements, .. } => {
-                let elem_types: Vec<Type> = match subject_type {
-                    Type::Tuple(types) => types.clone(),
-                    _ => vec![Type::Any; elements.len()],
-                };
+                let elem_types = self.destructure_tuple_types(elements.len(), subject_type, line);
                 for (idx, p) in elements.iter().enumerate() {
                     let ty = elem_types.get(idx).cloned().unwrap_or(Type::Any);
                     self.bind_let_pattern(p, &ty, line);
@@ -1205,20 +1544,9 @@ impl<'a> TypeChecker<'a> {
             Pattern::RecordDestructure {
                 type_name, fields, ..
             } => {
+                let record_name = self.destructure_record_name(type_name, subject_type, line);
                 for (field_name, field_pat) in fields {
-                    let field_ty = if let Some(ti) = self.symbols.types.get(type_name) {
-                        if let crate::compiler::resolve::TypeInfoKind::Record(def) = &ti.kind {
-                            if let Some(field) = def.fields.iter().find(|f| f.name == *field_name) {
-                                resolve_type_expr(&field.ty, self.symbols)
-                            } else {
-                                Type::Any
-                            }
-                        } else {
-                            Type::Any
-                        }
-                    } else {
-                        Type::Any
-                    };
+                    let field_ty = self.destructure_field_type(&record_name, field_name, line);
                     if let Some(p) = field_pat {
                         self.bind_let_pattern(p, &field_ty, line);
                     } else {
@@ -1228,9 +1556,31 @@ impl<'a> TypeChecker<'a> {
                     }
                 }
             }
+            Pattern::Variant(name, payload, span) => {
+                // `let Point(x, y) = p` — positional record destructuring.
+                match self
+                    .symbols
+                    .positional_record_pattern(name, payload.as_deref(), *span)
+                {
+                    Some(Ok(record_pattern)) => {
+                        self.bind_let_pattern(&record_pattern, subject_type, line)
+                    }
+                    Some(Err((field_count, pattern_count))) => {
+                        self.errors.push(TypeError::Mismatch {
+                            expected: format!("{} with {} fields", name, field_count),
+                            actual: format!("pattern with {} fields", pattern_count),
+                            line,
+                        });
+                        if let Some(p) = payload {
+                            self.bind_let_pattern(p, &Type::Any, line);
+                        }
+                    }
+                    None => {}
+                }
+            }
             Pattern::ListDestructure { elements, rest, .. } => {
                 let elem_type = match subject_type {
-                    Type::List(inner) => *inner.clone(),
+                    Type::List(inner) | Type::Array(inner, _) => *inner.clone(),
                     _ => Type::Any,
                 };
                 for p in elements {
@@ -1265,7 +1615,43 @@ impl<'a> TypeChecker<'a> {
         line: usize,
     ) {
         match pattern {
-            Pattern::Variant(tag, binding, _) => {
+            Pattern::Variant(tag, binding, span) => {
+                // `Point(x, y)` naming a record is positional destructuring.
+                match self
+                    .symbols
+                    .positional_record_pattern(tag, binding.as_deref(), *span)
+                {
+                    Some(Ok(record_pattern)) => {
+                        self.bind_match_pattern(
+                            &record_pattern,
+                            subject_type,
+                            covered_variants,
+                            has_catchall,
+                            line,
+                        );
+                        return;
+                    }
+                    Some(Err((field_count, pattern_count))) => {
+                        self.errors.push(TypeError::Mismatch {
+                            expected: format!("{} with {} fields", tag, field_count),
+                            actual: format!("pattern with {} fields", pattern_count),
+                            line,
+                        });
+                        if let Some(p) = binding {
+                            let mut payload_variants = Vec::new();
+                            let mut payload_catchall = false;
+                            self.bind_match_pattern(
+                                p,
+                                &Type::Any,
+                                &mut payload_variants,
+                                &mut payload_catchall,
+                                line,
+                            );
+                        }
+                        return;
+                    }
+                    None => {}
+                }
                 let mut valid_variant = false;
                 let mut payload_type = Type::Any;
                 let mut expects_payload = false;
@@ -1319,11 +1705,15 @@ impl<'a> TypeChecker<'a> {
                             line,
                         });
                     }
+                    // A binding inside the payload covers the payload, not
+                    // the other variants of the subject.
+                    let mut payload_variants = Vec::new();
+                    let mut payload_catchall = false;
                     self.bind_match_pattern(
                         inner_pattern,
                         &payload_type,
-                        covered_variants,
-                        has_catchall,
+                        &mut payload_variants,
+                        &mut payload_catchall,
                         line,
                     );
                 }
@@ -1377,7 +1767,7 @@ impl<'a> TypeChecker<'a> {
             }
             Pattern::ListDestructure { elements, rest, .. } => {
                 let elem_type = match subject_type {
-                    Type::List(inner) => *inner.clone(),
+                    Type::List(inner) | Type::Array(inner, _) => *inner.clone(),
                     Type::Any => Type::Any,
                     other => {
                         self.errors.push(TypeError::Mismatch {
@@ -1396,61 +1786,22 @@ impl<'a> TypeChecker<'a> {
                         .insert(rest_name.clone(), Type::List(Box::new(elem_type)));
                 }
             }
-            Pattern::TupleDestructure { elements, .. } => match subject_type {
-                Type::Tuple(types) => {
-                    for (idx, p) in elements.iter().enumerate() {
-                        let ty = types.get(idx).cloned().unwrap_or(Type::Any);
-                        self.bind_match_pattern(p, &ty, covered_variants, has_catchall, line);
-                    }
-                }
-                Type::Any => {
-                    for p in elements {
-                        self.bind_match_pattern(
-                            p,
-                            &Type::Any,
-                            covered_variants,
-                            has_catchall,
-                            line,
-                        );
-                    }
-                }
-                other => {
-                    self.errors.push(TypeError::Mismatch {
-                        expected: "Tuple".into(),
-                        actual: format!("{}", other),
-                        line,
-                    });
+            Pattern::TupleDestructure { elements, .. } => {
+                let elem_types = self.destructure_tuple_types(elements.len(), subject_type, line);
+                for (idx, p) in elements.iter().enumerate() {
+                    let ty = elem_types.get(idx).cloned().unwrap_or(Type::Any);
+                    self.bind_match_pattern(p, &ty, covered_variants, has_catchall, line);
                 }
-            },
+            }
             Pattern::RecordDestructure {
                 type_name,
                 fields,
                 open: _,
                 ..
             } => {
-                if let Type::Record(actual_name) = subject_type {
-                    if actual_name != type_name {
-                        self.errors.push(TypeError::Mismatch {
-                            expected: type_name.clone(),
-                            actual: actual_name.clone(),
-                            line,
-                        });
-                    }
-                }
+                let record_name = self.destructure_record_name(type_name, subject_type, line);
                 for (field_name, field_pat) in fields {
-                    let field_ty = if let Some(ti) = self.symbols.types.get(type_name) {
-                        if let crate::compiler::resolve::TypeInfoKind::Record(def) = &ti.kind {
-                            if let Some(field) = def.fields.iter().find(|f| f.name == *field_name) {
-                                resolve_type_expr(&field.ty, self.symbols)
-                            } else {
-                                Type::Any
-                            }
-                        } else {
-                            Type::Any
-                        }
-                    } else {
-                        Type::Any
-                    };
+                    let field_ty = self.destructure_field_type(&record_name, field_name, line);
                     if let Some(p) = field_pat {
                         self.bind_match_pattern(p, &field_ty, covered_variants, has_catchall, line);
                     } else {
@@ -1664,6 +2015,7 @@ impl<'a> TypeChecker<'a> {
                                     &subst,
                                 );
                                 self.check_compat(&expected, &val_type, span.line);
+                                self.check_widening(&expected, fval, span.line);
                             } else {
                                 let field_names: Vec<&str> =
                                     def.fields.iter().map(|f| f.name.as_str()).collect();
@@ -1676,18 +2028,13 @@ impl<'a> TypeChecker<'a> {
                                 });
                             }
                         }
-                        // 2. Check for missing fields (fields with defaults are optional)
-                        for field_def in &def.fields {
-                            if field_def.default_value.is_none()
-                                && !fields.iter().any(|(fname, _)| fname == &field_def.name)
-                            {
-                                self.errors.push(TypeError::Mismatch {
-                                    expected: format!("field '{}'", field_def.name),
-                                    actual: "missing".into(),
-                                    line: span.line,
-                                });
-                            }
-                        }
+                        // 2. Check for duplicate and missing fields
+                        // Record literal fields carry no label spans of their own.
+                        let labels: Vec<(&str, Span)> = fields
+                            .iter()
+                            .map(|(fname, _)| (fname.as_str(), *span))
+                            .collect();
+                        self.check_record_labels(name, def, &labels, *span);
 
                         // Return the instantiated generic type if applicable
                         if !generic_args.is_empty() {
@@ -1709,6 +2056,20 @@ impl<'a> TypeChecker<'a> {
             Expr::BinOp(lhs, op, rhs, _span) => {
                 let lt = self.infer_expr(lhs);
                 let rt = self.infer_expr(rhs);
+                if matches!(
+                    op,
+                    BinOp::Add
+                        | BinOp::Sub
+                        | BinOp::Mul
+                        | BinOp::Div
+                        | BinOp::FloorDiv
+                        | BinOp::Mod
+                        | BinOp::Pow
+                ) {
+                    // Mixed Int/Float arithmetic widens the Int operand.
+                    self.check_widening(&lt, rhs, _span.line);
+                    self.check_widening(&rt, lhs, _span.line);
+                }
                 match op {
                     BinOp::Add
                     | BinOp::Sub
@@ -1788,12 +2149,21 @@ impl<'a> TypeChecker<'a> {
                 }
             }
             Expr::Call(callee, args, span) => {
+                if let Expr::DotAccess(receiver, method, _) = callee.as_ref() {
+                    if let Some(ty) = self.infer_method_call(receiver, method, args, *span) {
+                        return ty;
+                    }
+                }
                 let mut checked_args = Vec::new();
                 for arg in args {
                     match arg {
                         CallArg::Positional(e) => {
                             let ty = self.infer_expr(e);
-                            checked_args.push(CheckedCallArg::Positional(ty, e.span().line));
+                            checked_args.push(CheckedCallArg::Positional(
+                                ty,
+                                e.span().line,
+                                self.large_int(e),
+                            ));
                         }
                         CallArg::Named(name, e, _) => {
                             let ty = self.infer_expr(e);
@@ -1801,13 +2171,14 @@ impl<'a> TypeChecker<'a> {
                                 name.clone(),
                                 ty,
                                 e.span().line,
+                                self.large_int(e),
                             ));
                         }
                         CallArg::Role(_, _, _) => {}
                     }
                 }
                 // Try to resolve the return type
-                if let Expr::Ident(name, _) = callee.as_ref() {
+                if let Some(name) = &self.callee_name(callee) {
                     // Check if it's a cell/function call
                     if let Some(ci) = self.symbols.cells.get(name).cloned() {
                         if !ci.generic_params.is_empty() {
@@ -1820,7 +2191,7 @@ impl<'a> TypeChecker<'a> {
                             let mut positional_idx = 0usize;
                             for checked_arg in &checked_args {
                                 match checked_arg {
-                                    CheckedCallArg::Positional(arg_ty, _) => {
+                                    CheckedCallArg::Positional(arg_ty, ..) => {
                                         if let Some((_, param_ty_expr, _)) =
                                             ci.params.get(positional_idx)
                                         {
@@ -1834,7 +2205,7 @@ impl<'a> TypeChecker<'a> {
                                         }
                                         positional_idx += 1;
                                     }
-                                    CheckedCallArg::Named(pname, arg_ty, _) => {
+                                    CheckedCallArg::Named(pname, arg_ty, ..) => {
                                         if let Some((_, param_ty_expr, _)) =
                                             ci.params.iter().find(|(n, _, _)| n == pname)
                                         {
@@ -1870,12 +2241,18 @@ impl<'a> TypeChecker<'a> {
                             if let Some(ref rt) = ci.return_type {
                                 return resolve_type_expr_with_subst(rt, self.symbols, &subst);
                             }
+                            if let Some(ty) = self.inferred_return_type(name) {
+                                return ty;
+                            }
                         } else {
                             // Non-generic cell: use standard checking
                             self.check_call_against_signature(&ci.params, &checked_args, span.line);
                             if let Some(ref rt) = ci.return_type {
                                 return resolve_type_expr(rt, self.symbols);
                             }
+                            if let Some(ty) = self.inferred_return_type(name) {
+                                return ty;
+                            }
                         }
                     }
                     // Check if it's a record construction
@@ -1886,7 +2263,7 @@ impl<'a> TypeChecker<'a> {
                                 // Build inference map from named arguments
                                 let mut inferred: HashMap<String, Type> = HashMap::new();
                                 for checked_arg in &checked_args {
-                                    if let CheckedCallArg::Named(fname, arg_ty, _) = checked_arg {
+                                    if let CheckedCallArg::Named(fname, arg_ty, ..) = checked_arg {
                                         if let Some(field_def) =
                                             def.fields.iter().find(|f| f.name == *fname)
                                         {
@@ -1913,7 +2290,9 @@ impl<'a> TypeChecker<'a> {
 
                             // Check constructor arguments match record fields
                             for checked_arg in &checked_args {
-                                if let CheckedCallArg::Named(fname, arg_ty, line) = checked_arg {
+                                if let CheckedCallArg::Named(fname, arg_ty, line, large) =
+                                    checked_arg
+                                {
                                     if let Some(field_def) =
                                         def.fields.iter().find(|f| f.name == *fname)
                                     {
@@ -1923,6 +2302,7 @@ impl<'a> TypeChecker<'a> {
                                             &subst,
                                         );
                                         self.check_compat(&expected, arg_ty, *line);
+                                        self.warn_precision_loss(&expected, large, *line);
                                     } else {
                                         let field_names: Vec<&str> =
                                             def.fields.iter().map(|f| f.name.as_str()).collect();
@@ -1937,6 +2317,22 @@ impl<'a> TypeChecker<'a> {
                                 }
                             }
 
+                            // Positional arguments are not matched to fields, so
+                            // only fully labeled constructions are checked.
+                            let labels: Option<Vec<(&str, Span)>> = args
+                                .iter()
+                                .filter(|arg| !matches!(arg, CallArg::Role(..)))
+                                .map(|arg| match arg {
+                                    CallArg::Named(fname, _, arg_span) => {
+                                        Some((fname.as_str(), label_span(fname, *arg_span)))
+                                    }
+                                    _ => None,
+                                })
+                                .collect();
+                            if let Some(labels) = labels {
+                                self.check_record_labels(name, def, &labels, *span);
+                            }
+
                             // Return the instantiated generic type if applicable
                             return if !generic_args.is_empty() {
                                 Type::TypeRef(name.clone(), generic_args)
@@ -1951,8 +2347,8 @@ impl<'a> TypeChecker<'a> {
                         let arg_types: Vec<Type> = checked_args
                             .iter()
                             .map(|a| match a {
-                                CheckedCallArg::Positional(ty, _) => ty.clone(),
-                                CheckedCallArg::Named(_, ty, _) => ty.clone(),
+                                CheckedCallArg::Positional(ty, ..) => ty.clone(),
+                                CheckedCallArg::Named(_, ty, ..) => ty.clone(),
                             })
                             .collect();
                         if let Some(ret_ty) = builtin_return_type(name, &arg_types) {
@@ -1974,6 +2370,9 @@ impl<'a> TypeChecker<'a> {
                 Type::Any
             }
             Expr::DotAccess(obj, field, _span) => {
+                if self.module_alias_cell(obj, field).is_some() {
+                    return Type::Any;
+                }
                 let ot = self.infer_expr(obj);
                 match &ot {
                     Type::Record(ref name) => {
@@ -2006,11 +2405,15 @@ impl<'a> TypeChecker<'a> {
                 }
                 Type::Any
             }
-            Expr::IndexAccess(obj, idx, _) => {
+            Expr::IndexAccess(obj, idx, span) => {
                 let ot = self.infer_expr(obj);
                 self.infer_expr(idx);
                 match ot {
                     Type::List(inner) => *inner,
+                    Type::Array(inner, len) => {
+                        self.check_const_index(idx, len, span.line);
+                        *inner
+                    }
                     Type::Map(_, v) => *v,
                     _ => Type::Any,
                 }
@@ -2037,6 +2440,8 @@ impl<'a> TypeChecker<'a> {
             } => {
                 let saved_locals = self.locals.clone();
                 let saved_mutables = self.mutables.clone();
+                // A lambda's returns belong to the lambda, not the enclosing cell.
+                let saved_returns = self.returns.take();
                 let mut param_types = Vec::new();
                 for p in params {
                     let pt = resolve_type_expr(&p.ty, self.symbols);
@@ -2062,6 +2467,7 @@ impl<'a> TypeChecker<'a> {
                 };
                 self.locals = saved_locals;
                 self.mutables = saved_mutables;
+                self.returns = saved_returns;
                 Type::Fn(param_types, Box::new(ret))
             }
             Expr::TupleLit(elems, _) => {
@@ -2191,7 +2597,7 @@ impl<'a> TypeChecker<'a> {
                 let ot = self.infer_expr(obj);
                 self.infer_expr(idx);
                 let elem_type = match ot {
-                    Type::List(inner) => *inner,
+                    Type::List(inner) | Type::Array(inner, _) => *inner,
                     _ => Type::Any,
                 };
                 Type::Union(vec![elem_type, Type::Null])
@@ -2300,7 +2706,7 @@ impl<'a> TypeChecker<'a> {
 
                 let iter_type = self.infer_expr(iter);
                 let elem_type = match &iter_type {
-                    Type::List(inner) => *inner.clone(),
+                    Type::List(inner) | Type::Array(inner, _) => *inner.clone(),
                     Type::Set(inner) => *inner.clone(),
                     _ => Type::Any,
                 };
@@ -2309,7 +2715,7 @@ impl<'a> TypeChecker<'a> {
                 for clause in extra_clauses {
                     let clause_iter_type = self.infer_expr(&clause.iter);
                     let clause_elem_type = match &clause_iter_type {
-                        Type::List(inner) => *inner.clone(),
+                        Type::List(inner) | Type::Array(inner, _) => *inner.clone(),
                         Type::Set(inner) => *inner.clone(),
                         _ => Type::Any,
                     };
@@ -2438,6 +2844,224 @@ impl<'a> TypeChecker<'a> {
         }
     }
 
+    /// Check that a list literal initializing a fixed-size array has exactly
+    /// the declared number of elements. Literals with spreads are skipped.
+    fn check_array_literal(&mut self, expected: &Type, value: &Expr, line: usize) {
+        let (Type::Array(_, len), Expr::ListLit(elems, _)) = (expected, value) else {
+            return;
+        };
+        if elems.iter().any(|e| matches!(e, Expr::SpreadExpr(..))) {
+            return;
+        }
+        if elems.len() != *len {
+            self.errors.push(TypeError::ArrayLengthMismatch {
+                expected: *len,
+                actual: elems.len(),
+                line,
+            });
+        }
+    }
+
+    /// Reject constant indices outside `-len..len` on a fixed-size array.
+    fn check_const_index(&mut self, idx: &Expr, len: usize, line: usize) {
+        let index = match idx {
+            Expr::IntLit(n, _) => *n,
+            Expr::UnaryOp(UnaryOp::Neg, inner, _) => match inner.as_ref() {
+                Expr::IntLit(n, _) => -*n,
+                _ => return,
+            },
+            _ => return,
+        };
+        let len_i = len as i64;
+        if index >= len_i || index < -len_i {
+            self.errors
+                .push(TypeError::IndexOutOfBounds { index, len, line });
+        }
+    }
+
+    /// Resolve `receiver.method(args)` UFCS-style as `method(receiver, args)`
+    /// against user cells whose first parameter accepts the receiver type,
+    /// falling back to builtins. Returns `None` when the call is left to the
+    /// regular path (record field calls, `Enum.Variant(..)`, agent/process
+    /// methods, effect operations, or receivers of unknown type).
+    /// The cell name a call goes through: a plain identifier, or
+    /// `alias.cell` for a module imported with `import path as alias`.
+    fn callee_name(&self, callee: &Expr) -> Option<String> {
+        match callee {
+            Expr::Ident(name, _) => Some(name.clone()),
+            Expr::DotAccess(obj, member, _) => self.module_alias_cell(obj, member),
+            _ => None,
+        }
+    }
+
+    /// `alias.member` if `obj` is a module alias (not a local) and the module
+    /// exports a cell `member`.
+    fn module_alias_cell(&self, obj: &Expr, member: &str) -> Option<String> {
+        match obj {
+            Expr::Ident(alias, _) if !self.locals.contains_key(alias) => {
+                let qualified = format!("{}.{}", alias, member);
+                self.symbols
+                    .cells
+                    .contains_key(&qualified)
+                    .then_some(qualified)
+            }
+            _ => None,
+        }
+    }
+
+    fn infer_method_call(
+        &mut self,
+        receiver: &Expr,
+        method: &str,
+        args: &[CallArg],
+        span: Span,
+    ) -> Option<Type> {
+        // Non-local identifiers are namespaces (types, agents, modules, effects).
+        if let Expr::Ident(name, _) = receiver {
+            if !self.locals.contains_key(name) {
+                return None;
+            }
+        }
+
+        // Receiver errors are reported when the call itself is checked.
+        let mark = self.errors.len();
+        let recv_ty = self.infer_expr(receiver);
+        self.errors.truncate(mark);
+
+        let record_fields = match &recv_ty {
+            Type::Any => return None,
+            // Agent and process instances dispatch their own methods.
+            Type::Record(name) | Type::TypeRef(name, _)
+                if self.symbols.agents.contains_key(name)
+                    || self.symbols.processes.values().any(|p| p.name == *name) =>
+            {
+                return None
+            }
+            Type::Record(name) | Type::TypeRef(name, _) => match self.symbols.types.get(name) {
+                Some(ti) => match &ti.kind {
+                    crate::compiler::resolve::TypeInfoKind::Record(rd) => {
+                        rd.fields.iter().map(|f| f.name.as_str()).collect()
+                    }
+                    _ => Vec::new(),
+                },
+                None => return None,
+            },
+            _ => Vec::new(),
+        };
+        let has_field = record_fields.contains(&method);
+        let user_cell = self.symbols.cells.get(method);
+        let cell_accepts = user_cell.is_some_and(|ci| self.first_param_accepts(ci, &recv_ty));
+
+        if has_field {
+            if cell_accepts {
+                self.errors.push(TypeError::AmbiguousMethod {
+                    method: method.to_string(),
+                    receiver: recv_ty.to_string(),
+                    line: span.line,
+                });
+                return Some(Type::Any);
+            }
+            return None;
+        }
+
+        if cell_accepts {
+            return Some(self.infer_desugared_method_call(receiver, method, args, span));
+        }
+        // Records may carry host-provided methods (e.g. typestate transitions),
+        // so only plain values fall back to builtins or report unknown methods.
+        if matches!(recv_ty, Type::Record(_) | Type::TypeRef(_, _)) {
+            return None;
+        }
+        let builtin = user_cell.is_none()
+            && (is_builtin_function(method) || builtin_return_type(method, &[]).is_some());
+        if !builtin {
+            let mut candidates: Vec<&str> = self
+                .symbols
+                .cells
+                .iter()
+                .filter(|(_, ci)| self.first_param_accepts(ci, &recv_ty))
+                .map(|(name, _)| name.as_str())
+                .collect();
+            candidates.extend(record_fields);
+            candidates.sort_unstable();
+            self.errors.push(TypeError::UnknownMethod {
+                method: method.to_string(),
+                receiver: recv_ty.to_string(),
+                line: span.line,
+                suggestions: suggest_similar(method, &candidates, 2),
+            });
+            for arg in args {
+                if let CallArg::Positional(e) | CallArg::Named(_, e, _) = arg {
+                    self.infer_expr(e);
+                }
+            }
+            return Some(Type::Any);
+        }
+
+        Some(self.infer_desugared_method_call(receiver, method, args, span))
+    }
+
+    fn infer_desugared_method_call(
+        &mut self,
+        receiver: &Expr,
+        method: &str,
+        args: &[CallArg],
+        span: Span,
+    ) -> Type {
+        self.method_calls.insert(span);
+        self.infer_expr(&desugar_method_call(receiver, method, args, span))
+    }
+
+    /// Whether `cell`'s first parameter accepts a value of type `actual`.
+    /// Generic parameters accept anything.
+    fn first_param_accepts(
+        &self,
+        cell: &crate::compiler::resolve::CellInfo,
+        actual: &Type,
+    ) -> bool {
+        let Some((_, param_ty, _)) = cell.params.first() else {
+            return false;
+        };
+        let subst = build_subst(
+            &cell.generic_params,
+            &vec![Type::Any; cell.generic_params.len()],
+        );
+        let expected = resolve_type_expr_with_subst(param_ty, self.symbols, &subst);
+        let mut probe = TypeChecker::new(self.symbols, self.allow_placeholders);
+        probe.check_compat(&expected, actual, 0);
+        probe.errors.is_empty()
+    }
+
+    /// Warn if `expected` is `Float` and `expr` is an integer too large to
+    /// widen to it exactly.
+    fn check_widening(&mut self, expected: &Type, expr: &Expr, line: usize) {
+        let large = self.large_int(expr);
+        self.warn_precision_loss(expected, &large, line);
+    }
+
+    fn warn_precision_loss(&mut self, expected: &Type, large: &Option<String>, line: usize) {
+        if let (Type::Float, Some(value)) = (expected, large) {
+            self.warnings.push(TypeError::PrecisionLoss {
+                value: value.clone(),
+                line,
+            });
+        }
+    }
+
+    /// The source text of `expr` if it is provably an integer beyond 2^53:
+    /// a literal, a negated literal, or a constant bound to one.
+    fn large_int(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::UnaryOp(UnaryOp::Neg, inner, _) => {
+                self.large_int(inner).map(|text| format!("-{}", text))
+            }
+            Expr::Ident(name, _) if !self.locals.contains_key(name) => {
+                large_int_literal(self.symbols.consts.get(name)?.value.as_ref()?)
+            }
+            _ => large_int_literal(expr),
+        }
+    }
+
     fn check_compat(&mut self, expected: &Type, actual: &Type, line: usize) {
         if *expected == Type::Any || *actual == Type::Any {
             return;
@@ -2475,6 +3099,21 @@ impl<'a> TypeChecker<'a> {
             return;
         }
 
+        // Fixed-size arrays: lengths must agree between arrays, while lists of
+        // statically unknown length convert in either direction (literal
+        // initializers are length-checked by `check_array_literal`).
+        match (expected, actual) {
+            (Type::Array(e, n), Type::Array(a, m)) if n == m => {
+                self.check_compat(e, a, line);
+                return;
+            }
+            (Type::Array(e, _), Type::List(a)) | (Type::List(e), Type::Array(a, _)) => {
+                self.check_compat(e, a, line);
+                return;
+            }
+            _ => {}
+        }
+
         // Result compatibility: Result[A, B] is compatible with Result[C, D] if A compat C, B compat D
         // Allow implicit wrapping into `ok(...)` when a plain value is returned for a Result type.
         if let Type::Result(ok, _) = expected {
@@ -2699,6 +3338,18 @@ fn collect_int_patterns(
     }
 }
 
+/// The source text of an integer literal (possibly negated) beyond 2^53.
+fn large_int_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::IntLit(n, _) if (*n as i128).abs() > MAX_EXACT_FLOAT_INT => Some(n.to_string()),
+        Expr::BigIntLit(n, _) => Some(n.to_string()),
+        Expr::UnaryOp(UnaryOp::Neg, inner, _) => {
+            large_int_literal(inner).map(|text| format!("-{}", text))
+        }
+        _ => None,
+    }
+}
+
 /// Extract an integer literal value from an expression (handles negation).
 fn extract_int_lit(expr: &Expr) -> Option<i64> {
     match expr {
@@ -2714,15 +3365,194 @@ fn extract_int_lit(expr: &Expr) -> Option<i64> {
     }
 }
 
+/// Whether every path through `cell` calls the cell again before it can
+/// return, so that any call to it recurses forever.
+pub fn recurses_on_every_path(cell: &CellDef) -> bool {
+    always_recurses(&cell.body, &cell.name)
+}
+
+/// Conservatively decide whether every path through `stmts` calls `name`
+/// before the cell can return. Only calls that are evaluated
+/// unconditionally count; anything less obvious is assumed to be a base case.
+fn always_recurses(stmts: &[Stmt], name: &str) -> bool {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Return(ret) => return calls_unconditionally(&ret.value, name),
+            Stmt::Halt(_) => return false,
+            Stmt::Let(let_stmt) => {
+                if calls_unconditionally(&let_stmt.value, name) {
+                    return true;
+                }
+            }
+            Stmt::Assign(assign) => {
+                if calls_unconditionally(&assign.value, name) {
+                    return true;
+                }
+            }
+            Stmt::Expr(expr_stmt) => {
+                if calls_unconditionally(&expr_stmt.expr, name) {
+                    return true;
+                }
+            }
+            Stmt::If(if_stmt) => {
+                if calls_unconditionally(&if_stmt.condition, name) {
+                    return true;
+                }
+                if let Some(else_body) = &if_stmt.else_body {
+                    if always_recurses(&if_stmt.then_body, name) && always_recurses(else_body, name)
+                    {
+                        return true;
+                    }
+                }
+                if may_return(std::slice::from_ref(stmt)) {
+                    return false;
+                }
+            }
+            Stmt::Match(match_stmt) => {
+                if calls_unconditionally(&match_stmt.subject, name) {
+                    return true;
+                }
+                if !match_stmt.arms.is_empty()
+                    && match_stmt
+                        .arms
+                        .iter()
+                        .all(|arm| always_recurses(&arm.body, name))
+                {
+                    return true;
+                }
+                if may_return(std::slice::from_ref(stmt)) {
+                    return false;
+                }
+            }
+            _ => {
+                if may_return(std::slice::from_ref(stmt)) {
+                    return false;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Whether any nested statement can leave the cell early.
+fn may_return(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(|stmt| match stmt {
+        Stmt::Return(_) | Stmt::Halt(_) => true,
+        Stmt::If(if_stmt) => {
+            may_return(&if_stmt.then_body)
+                || if_stmt
+                    .else_body
+                    .as_ref()
+                    .is_some_and(|body| may_return(body))
+        }
+        Stmt::Match(match_stmt) => match_stmt.arms.iter().any(|arm| may_return(&arm.body)),
+        Stmt::For(for_stmt) => may_return(&for_stmt.body),
+        Stmt::While(while_stmt) => may_return(&while_stmt.body),
+        Stmt::Loop(loop_stmt) => may_return(&loop_stmt.body),
+        _ => false,
+    })
+}
+
+/// Whether evaluating `expr` always calls `name`. Short-circuiting
+/// operands, conditional branches and lambda bodies are not followed.
+fn calls_unconditionally(expr: &Expr, name: &str) -> bool {
+    match expr {
+        Expr::Call(callee, args, _) => {
+            matches!(callee.as_ref(), Expr::Ident(n, _) if n == name)
+                || calls_unconditionally(callee, name)
+                || args.iter().any(|arg| match arg {
+                    CallArg::Positional(e) | CallArg::Named(_, e, _) | CallArg::Role(_, e, _) => {
+                        calls_unconditionally(e, name)
+                    }
+                })
+        }
+        Expr::BinOp(lhs, op, rhs, _) => {
+            calls_unconditionally(lhs, name)
+                || (!matches!(op, BinOp::And | BinOp::Or) && calls_unconditionally(rhs, name))
+        }
+        Expr::UnaryOp(_, inner, _)
+        | Expr::DotAccess(inner, _, _)
+        | Expr::TryExpr(inner, _)
+        | Expr::NullAssert(inner, _)
+        | Expr::AwaitExpr(inner, _)
+        | Expr::NullCoalesce(inner, _, _)
+        | Expr::IsType { expr: inner, .. }
+        | Expr::TypeCast { expr: inner, .. }
+        | Expr::IfExpr { cond: inner, .. } => calls_unconditionally(inner, name),
+        Expr::IndexAccess(base, index, _) => {
+            calls_unconditionally(base, name) || calls_unconditionally(index, name)
+        }
+        Expr::Pipe { left, right, .. } => {
+            calls_unconditionally(left, name) || calls_unconditionally(right, name)
+        }
+        Expr::ListLit(items, _) | Expr::TupleLit(items, _) | Expr::SetLit(items, _) => {
+            items.iter().any(|e| calls_unconditionally(e, name))
+        }
+        Expr::RecordLit(_, fields, _) => fields.iter().any(|(_, e)| calls_unconditionally(e, name)),
+        _ => false,
+    }
+}
+
 /// Typecheck a program.
 pub fn typecheck(program: &Program, symbols: &SymbolTable) -> Result<(), Vec<TypeError>> {
+    typecheck_with_method_calls(program, symbols).map(|_| ())
+}
+
+/// Typecheck `program`, returning the spans of method-call expressions that
+/// resolved UFCS-style so lowering can emit them as plain calls.
+pub fn typecheck_with_method_calls(
+    program: &Program,
+    symbols: &SymbolTable,
+) -> Result<HashSet<Span>, Vec<TypeError>> {
+    typecheck_with_warnings(program, symbols).0
+}
+
+/// Like [`typecheck_with_method_calls`], but also returns the warnings found
+/// along the way, such as cells that recurse on every path. Warnings are
+/// returned whether or not the program has type errors.
+pub fn typecheck_with_warnings(
+    program: &Program,
+    symbols: &SymbolTable,
+) -> (Result<HashSet<Span>, Vec<TypeError>>, Vec<TypeError>) {
+    let checker = check_program(program, symbols);
+    let result = if checker.errors.is_empty() {
+        Ok(checker.method_calls)
+    } else {
+        Err(checker.errors)
+    };
+    (result, checker.warnings)
+}
+
+/// The type inferred for every `let` binding and `for` loop variable, keyed
+/// by the statement's span. Type errors elsewhere don't stop inference; a binding whose
+/// initializer can't be typed maps to [`Type::Any`].
+pub fn infer_let_types(program: &Program, symbols: &SymbolTable) -> HashMap<Span, Type> {
+    check_program(program, symbols).let_types
+}
+
+fn check_program<'a>(program: &'a Program, symbols: &'a SymbolTable) -> TypeChecker<'a> {
     let strict = parse_directive_bool(program, "strict").unwrap_or(true);
     let doc_mode = parse_directive_bool(program, "doc_mode").unwrap_or(false);
     let allow_placeholders = doc_mode || !strict;
     let mut checker = TypeChecker::new(symbols, allow_placeholders);
+    checker.unannotated = program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Cell(c) if c.return_type.is_none() && !c.body.is_empty() => {
+                Some((c.name.clone(), c))
+            }
+            _ => None,
+        })
+        .collect();
     for item in &program.items {
         match item {
-            Item::Cell(c) => checker.check_cell(c),
+            Item::Cell(c) => {
+                checker.check_cell(c);
+                if let Some(ty) = checker.inferred_return.take() {
+                    checker.inferred_returns.insert(c.name.clone(), Some(ty));
+                }
+            }
             Item::Agent(a) => {
                 for cell in &a.cells {
                     checker.check_agent_cell(cell);
@@ -2749,13 +3579,110 @@ pub fn typecheck(program: &Program, symbols: &SymbolTable) -> Result<(), Vec<Typ
                     checker.check_cell(method);
                 }
             }
+            Item::ConstDecl(c) => {
+                if let Some(ann) = &c.type_ann {
+                    checker.locals.clear();
+                    let expected = resolve_type_expr(ann, symbols);
+                    checker.check_widening(&expected, &c.value, c.span.line);
+                }
+            }
             _ => {}
         }
     }
-    if checker.errors.is_empty() {
-        Ok(())
-    } else {
-        Err(checker.errors)
+    checker
+}
+
+/// Infer the return types of top-level cells declared without one and record
+/// them in `symbols`, for tools that read signatures from the symbol table.
+/// Typechecking infers these itself; a cell whose returns cannot be typed
+/// keeps no return type.
+pub fn infer_return_types(program: &Program, symbols: &mut SymbolTable) {
+    let inferred: Vec<(String, Option<TypeExpr>)> = {
+        let checker = check_program(program, symbols);
+        checker
+            .inferred_returns
+            .iter()
+            .filter_map(|(name, ty)| {
+                let cell = checker.unannotated.get(name)?;
+                let ty = ty.as_ref().and_then(|ty| type_to_type_expr(ty, cell.span));
+                Some((name.clone(), ty))
+            })
+            .collect()
+    };
+    for (name, ty) in inferred {
+        if let Some(info) = symbols.cells.get_mut(&name) {
+            info.return_type = ty;
+        }
+    }
+}
+
+/// Convert an inferred type back to a type expression for the symbol table.
+/// `Any` has no source form and yields `None`.
+fn type_to_type_expr(ty: &Type, span: Span) -> Option<TypeExpr> {
+    let named = |name: &str| Some(TypeExpr::Named(name.to_string(), span));
+    match ty {
+        Type::String => named("String"),
+        Type::Int => named("Int"),
+        Type::Float => named("Float"),
+        Type::Bool => named("Bool"),
+        Type::Bytes => named("Bytes"),
+        Type::Json => named("Json"),
+        Type::Null => Some(TypeExpr::Null(span)),
+        Type::Record(name) | Type::Enum(name) | Type::Generic(name) => named(name),
+        Type::List(inner) => Some(TypeExpr::List(
+            Box::new(type_to_type_expr(inner, span)?),
+            span,
+        )),
+        Type::Set(inner) => Some(TypeExpr::Set(
+            Box::new(type_to_type_expr(inner, span)?),
+            span,
+        )),
+        Type::Map(k, v) => Some(TypeExpr::Map(
+            Box::new(type_to_type_expr(k, span)?),
+            Box::new(type_to_type_expr(v, span)?),
+            span,
+        )),
+        Type::Result(ok, err) => Some(TypeExpr::Result(
+            Box::new(type_to_type_expr(ok, span)?),
+            Box::new(type_to_type_expr(err, span)?),
+            span,
+        )),
+        Type::Array(inner, len) => Some(TypeExpr::Array(
+            Box::new(type_to_type_expr(inner, span)?),
+            *len,
+            span,
+        )),
+        Type::Union(members) => Some(TypeExpr::Union(
+            members
+                .iter()
+                .map(|m| type_to_type_expr(m, span))
+                .collect::<Option<_>>()?,
+            span,
+        )),
+        Type::Tuple(members) => Some(TypeExpr::Tuple(
+            members
+                .iter()
+                .map(|m| type_to_type_expr(m, span))
+                .collect::<Option<_>>()?,
+            span,
+        )),
+        Type::Fn(params, ret) => Some(TypeExpr::Fn(
+            params
+                .iter()
+                .map(|p| type_to_type_expr(p, span))
+                .collect::<Option<_>>()?,
+            Box::new(type_to_type_expr(ret, span)?),
+            vec![],
+            span,
+        )),
+        Type::TypeRef(name, args) => Some(TypeExpr::Generic(
+            name.clone(),
+            args.iter()
+                .map(|a| type_to_type_expr(a, span))
+                .collect::<Option<_>>()?,
+            span,
+        )),
+        Type::Any => None,
     }
 }
 
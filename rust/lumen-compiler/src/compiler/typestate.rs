@@ -1135,6 +1135,7 @@ mod tests {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![],
             span: span(1),
             doc: None,
@@ -788,6 +788,7 @@ mod tests {
             traits: Default::default(),
             impls: Default::default(),
             consts: Default::default(),
+            imported_cells: Default::default(),
         }
     }
 
@@ -818,6 +819,7 @@ mod tests {
                 is_async: false,
                 is_extern: false,
                 must_use: false,
+                is_pure: false,
                 where_clauses: vec![wc],
                 span: span(),
                 doc: None,
@@ -937,6 +939,7 @@ mod tests {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses,
             span: span(),
             doc: None,
@@ -1477,6 +1480,7 @@ mod tests {
                     cells: vec![process_cell],
                     grants: vec![],
                     pipeline_stages: vec![],
+                    pipeline_stage_spans: vec![],
                     machine_initial: None,
                     machine_states: vec![],
                     span: span(),
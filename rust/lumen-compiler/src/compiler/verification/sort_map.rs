@@ -78,7 +78,7 @@ pub fn type_to_smt_sort(ty: &Type, symbols: Option<&SymbolTable>) -> Result<SmtS
         Type::Null => Ok(SmtSort::BoolSort),
 
         // Collections
-        Type::List(elem) => {
+        Type::List(elem) | Type::Array(elem, _) => {
             let elem_sort = type_to_smt_sort(elem, symbols)?;
             Ok(SmtSort::ArraySort {
                 index: Box::new(SmtSort::IntSort),
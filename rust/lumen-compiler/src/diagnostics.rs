@@ -7,8 +7,9 @@ use crate::compiler::lexer::LexError;
 use crate::compiler::ownership::OwnershipError;
 use crate::compiler::parser::ParseError;
 use crate::compiler::resolve::ResolveError;
+use crate::compiler::tokens::Span;
 use crate::compiler::typecheck::TypeError;
-use crate::CompileError;
+use crate::{CompileError, CompileFailure};
 
 /// Severity level for diagnostics
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,6 +19,15 @@ pub enum Severity {
     Note,
 }
 
+/// The source region a diagnostic points at: 1-based, `end_col` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticSpan {
+    pub line: usize,
+    pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
 /// A rendered diagnostic with source context
 #[derive(Debug, Clone)]
 pub struct Diagnostic {
@@ -28,7 +38,7 @@ pub struct Diagnostic {
     pub line: Option<usize>,
     pub col: Option<usize>,
     pub source_line: Option<String>,
-    pub underline: Option<String>,
+    pub span: Option<DiagnosticSpan>,
     pub suggestions: Vec<String>,
 }
 
@@ -48,6 +58,7 @@ impl Diagnostic {
                     "RESOLVE ERROR"
                 }
                 Some("E0107") | Some("E0108") | Some("E0109") => "UNDECLARED EFFECT",
+                Some("E0128") => "IMPURE CELL",
                 Some(c) if c.starts_with("E011") => "MACHINE ERROR",
                 Some(c) if c.starts_with("E012") => "IMPORT ERROR",
                 Some("E0200") => "TYPE MISMATCH",
@@ -60,6 +71,8 @@ impl Diagnostic {
                 Some("E0207") => "IMMUTABLE ASSIGN",
                 Some("E0208") => "INCOMPLETE MATCH",
                 Some("E0209") => "MUST USE",
+                Some("E0210") => "ARRAY LENGTH",
+                Some("E0211") => "INDEX OUT OF BOUNDS",
                 Some("E0300") => "CONSTRAINT ERROR",
                 Some(c) if c.starts_with("E04") => "OWNERSHIP ERROR",
                 Some("E0500") => "LOWERING ERROR",
@@ -99,8 +112,8 @@ impl Diagnostic {
         out.push('\n');
 
         // Source snippet with context (show 1-3 lines)
-        if let (Some(line_num), Some(ref line_text), Some(ref underline)) =
-            (self.line, &self.source_line, &self.underline)
+        if let (Some(line_num), Some(ref line_text), Some(underline)) =
+            (self.line, &self.source_line, self.underline())
         {
             // Show line number slightly dimmed
             let line_str = format!("{}", line_num);
@@ -108,7 +121,7 @@ impl Diagnostic {
 
             // Point to the error with red carets
             let spaces = " ".repeat(line_str.len());
-            out.push_str(&format!("  {} │ {}\n", spaces, red(underline)));
+            out.push_str(&format!("  {} │ {}\n", spaces, red(&underline)));
         }
 
         out.push('\n');
@@ -225,8 +238,8 @@ impl Diagnostic {
         }
 
         // Source line with underline
-        if let (Some(line_num), Some(ref line_text), Some(ref underline)) =
-            (self.line, &self.source_line, &self.underline)
+        if let (Some(line_num), Some(ref line_text), Some(underline)) =
+            (self.line, &self.source_line, self.underline())
         {
             out.push_str("   |\n");
             out.push_str(&format!("{:>3} | {}\n", line_num, line_text));
@@ -243,6 +256,98 @@ impl Diagnostic {
 
         out
     }
+
+    /// Carets under the spanned columns of `source_line`.
+    fn underline(&self) -> Option<String> {
+        self.span
+            .map(|s| make_underline(s.col, s.end_col.saturating_sub(s.col)))
+    }
+
+    /// Machine-readable form for CI systems and editors.
+    ///
+    /// `span` is `{line, start_col, end_line, end_col}` (1-based, `end_col`
+    /// exclusive); without a structured span it falls back to one column at
+    /// `col`, and it is `null` when the diagnostic has no line.
+    pub fn to_json(&self) -> serde_json::Value {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        };
+        let col = self.col.or(self.span.map(|s| s.col));
+        let span = self.span.or_else(|| {
+            self.line.map(|line| {
+                let col = col.unwrap_or(1);
+                DiagnosticSpan {
+                    line,
+                    col,
+                    end_line: line,
+                    end_col: col + 1,
+                }
+            })
+        });
+        let span = span.map(|s| {
+            serde_json::json!({
+                "line": s.line,
+                "start_col": s.col,
+                "end_line": s.end_line,
+                "end_col": s.end_col,
+            })
+        });
+        serde_json::json!({
+            "severity": severity,
+            "code": self.code,
+            "message": self.message,
+            "file": self.file,
+            "line": self.line,
+            "col": col,
+            "span": span,
+            "suggestions": self.suggestions,
+        })
+    }
+}
+
+/// Convert a CompileError into a JSON array of diagnostics, one object per
+/// error (`Multiple` is flattened). See [`Diagnostic::to_json`] for the shape.
+pub fn to_json(error: &CompileError, source: &str, filename: &str) -> serde_json::Value {
+    serde_json::Value::Array(
+        format_compile_error(error, source, filename)
+            .iter()
+            .map(Diagnostic::to_json)
+            .collect(),
+    )
+}
+
+/// Render warnings as JSON diagnostics, each with `"severity": "warning"`.
+pub fn warnings_to_json(
+    warnings: &[CompileError],
+    source: &str,
+    filename: &str,
+) -> Vec<serde_json::Value> {
+    warnings
+        .iter()
+        .flat_map(|w| format_compile_error(w, source, filename))
+        .map(|mut d| {
+            d.severity = Severity::Warning;
+            d.to_json()
+        })
+        .collect()
+}
+
+/// Render a failed compilation as a JSON array: its errors followed by the
+/// warnings found before it stopped.
+pub fn failure_to_json(
+    failure: &CompileFailure,
+    source: &str,
+    filename: &str,
+) -> serde_json::Value {
+    let mut diagnostics: Vec<serde_json::Value> =
+        format_compile_error(&failure.error, source, filename)
+            .iter()
+            .map(Diagnostic::to_json)
+            .collect();
+    diagnostics.extend(warnings_to_json(&failure.warnings, source, filename));
+    serde_json::Value::Array(diagnostics)
 }
 
 // ANSI color helpers
@@ -270,6 +375,23 @@ fn get_source_line(source: &str, line: usize) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// A single-line span from the `(col, len)` columns a diagnostic underlines.
+fn span_on(line: Option<usize>, columns: Option<(usize, usize)>) -> Option<DiagnosticSpan> {
+    let (line, (col, len)) = line.zip(columns)?;
+    Some(DiagnosticSpan {
+        line,
+        col,
+        end_line: line,
+        end_col: col + len.max(1),
+    })
+}
+
+/// The `(col, len)` columns of `span` on its first line, `source_line`.
+fn span_columns(span: Span, source_line: &str) -> (usize, usize) {
+    let rest = (source_line.len() + 1).saturating_sub(span.col);
+    (span.col, (span.end - span.start).min(rest).max(1))
+}
+
 fn make_underline(col: usize, len: usize) -> String {
     format!(
         "{}{}",
@@ -391,6 +513,11 @@ const BUILTINS: &[&str] = &[
     "resume",
 ];
 
+/// Whether `name` is a builtin function.
+pub fn is_builtin_function(name: &str) -> bool {
+    BUILTINS.contains(&name)
+}
+
 /// Convert a CompileError + source text into a list of Diagnostics
 pub fn format_compile_error(error: &CompileError, source: &str, filename: &str) -> Vec<Diagnostic> {
     // Generate fix-it hints for the whole error up front.
@@ -427,7 +554,7 @@ pub fn format_compile_error(error: &CompileError, source: &str, filename: &str)
             line: None,
             col: None,
             source_line: None,
-            underline: None,
+            span: None,
             suggestions: vec![
                 "Consider breaking large cells into smaller helper cells.".to_string()
             ],
@@ -446,7 +573,7 @@ pub fn format_compile_error(error: &CompileError, source: &str, filename: &str)
                 line: None,
                 col: None,
                 source_line: None,
-                underline: None,
+                span: None,
                 suggestions: vec![],
             })
             .collect(),
@@ -460,7 +587,7 @@ pub fn format_compile_error(error: &CompileError, source: &str, filename: &str)
                 line: None,
                 col: None,
                 source_line: None,
-                underline: None,
+                span: None,
                 suggestions: vec![],
             })
             .collect(),
@@ -487,7 +614,7 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
     match error {
         LexError::UnexpectedChar { ch, line, col } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 1));
+            let columns = source_line.as_ref().map(|_| (*col, 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -497,15 +624,13 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
         LexError::UnterminatedString { line, col } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line
-                .as_ref()
-                .map(|l| make_underline(*col, l.len() - col + 1));
+            let columns = source_line.as_ref().map(|l| (*col, l.len() - col + 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -515,15 +640,15 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec!["add a closing quote".to_string()],
             }
         }
         LexError::InconsistentIndent { line } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|l| {
+            let columns = source_line.as_ref().map(|l| {
                 let indent = l.chars().take_while(|c| c.is_whitespace()).count();
-                make_underline(1, indent.max(1))
+                (1, indent.max(1))
             });
 
             Diagnostic {
@@ -534,7 +659,7 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
                 line: Some(*line),
                 col: Some(1),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![
                     "ensure all indentation uses the same number of spaces".to_string()
                 ],
@@ -542,7 +667,7 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
         }
         LexError::InvalidNumber { line, col } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 1));
+            let columns = source_line.as_ref().map(|_| (*col, 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -552,13 +677,13 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
         LexError::InvalidBytesLiteral { line, col } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 1));
+            let columns = source_line.as_ref().map(|_| (*col, 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -568,13 +693,13 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec!["bytes literals must be hex: b\"48656c6c6f\"".to_string()],
             }
         }
         LexError::InvalidUnicodeEscape { line, col } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 1));
+            let columns = source_line.as_ref().map(|_| (*col, 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -584,13 +709,13 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec!["use \\u{XXXX} format for unicode escapes".to_string()],
             }
         }
         LexError::UnterminatedMarkdownBlock { line, col } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 3));
+            let columns = source_line.as_ref().map(|_| (*col, 3));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -600,7 +725,7 @@ fn format_lex_error(error: &LexError, source: &str, filename: &str) -> Diagnosti
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec!["add a closing ``` fence".to_string()],
             }
         }
@@ -617,16 +742,16 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
             col,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|s| {
+            let columns = source_line.as_ref().map(|s| {
                 // Try to underline the whole token
                 let col_idx = col.saturating_sub(1);
                 if let Some(token_end) = s[col_idx..]
                     .chars()
                     .position(|c| c.is_whitespace() || c == '(' || c == ')' || c == '{' || c == '}')
                 {
-                    make_underline(*col, token_end.max(1))
+                    (*col, token_end.max(1))
                 } else {
-                    make_underline(*col, s[col_idx..].len().max(1))
+                    (*col, s[col_idx..].len().max(1))
                 }
             });
 
@@ -668,7 +793,7 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions,
             }
         }
@@ -680,7 +805,7 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
             line: None,
             col: None,
             source_line: None,
-            underline: None,
+            span: None,
             suggestions: vec!["check for missing 'end' keywords".to_string()],
         },
         ParseError::UnclosedBracket {
@@ -691,7 +816,7 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
             current_col,
         } => {
             let source_line = get_source_line(source, *open_line);
-            let underline = source_line.as_ref().map(|_| make_underline(*open_col, 1));
+            let columns = source_line.as_ref().map(|_| (*open_col, 1));
             Diagnostic {
                 severity: Severity::Error,
                 code: Some(code.clone()),
@@ -703,7 +828,7 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
                 line: Some(*current_line),
                 col: Some(*current_col),
                 source_line,
-                underline,
+                span: span_on(Some(*current_line), columns),
                 suggestions: vec![format!(
                     "add closing '{}'",
                     match *bracket {
@@ -723,7 +848,7 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
             current_col,
         } => {
             let source_line = get_source_line(source, *open_line);
-            let underline = source_line.as_ref().map(|_| make_underline(*open_col, 1));
+            let columns = source_line.as_ref().map(|_| (*open_col, 1));
             Diagnostic {
                 severity: Severity::Error,
                 code: Some(code.clone()),
@@ -735,13 +860,13 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
                 line: Some(*current_line),
                 col: Some(*current_col),
                 source_line,
-                underline,
+                span: span_on(Some(*current_line), columns),
                 suggestions: vec!["add 'end' to close the block".to_string()],
             }
         }
         ParseError::MissingType { line, col, .. } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 1));
+            let columns = source_line.as_ref().map(|_| (*col, 1));
             Diagnostic {
                 severity: Severity::Error,
                 code: Some(code),
@@ -750,13 +875,13 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
         ParseError::IncompleteExpression { line, col, .. } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 1));
+            let columns = source_line.as_ref().map(|_| (*col, 1));
             Diagnostic {
                 severity: Severity::Error,
                 code: Some(code),
@@ -765,13 +890,13 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
         ParseError::MalformedConstruct { line, col, .. } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(*col, 1));
+            let columns = source_line.as_ref().map(|_| (*col, 1));
             Diagnostic {
                 severity: Severity::Error,
                 code: Some(code),
@@ -780,7 +905,7 @@ fn format_parse_error(error: &ParseError, source: &str, filename: &str) -> Diagn
                 line: Some(*line),
                 col: Some(*col),
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
@@ -796,11 +921,11 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
             suggestions: error_suggestions,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|l| {
+            let columns = source_line.as_ref().map(|l| {
                 if let Some(pos) = l.find(name) {
-                    make_underline(pos + 1, name.len())
+                    (pos + 1, name.len())
                 } else {
-                    make_underline(1, 1)
+                    (1, 1)
                 }
             });
 
@@ -818,7 +943,7 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: help,
             }
         }
@@ -828,11 +953,11 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
             suggestions: error_suggestions,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|l| {
+            let columns = source_line.as_ref().map(|l| {
                 if let Some(pos) = l.find(name) {
-                    make_underline(pos + 1, name.len())
+                    (pos + 1, name.len())
                 } else {
-                    make_underline(1, 1)
+                    (1, 1)
                 }
             });
 
@@ -850,17 +975,17 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: help,
             }
         }
         ResolveError::UndefinedTool { name, line } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|l| {
+            let columns = source_line.as_ref().map(|l| {
                 if let Some(pos) = l.find(name) {
-                    make_underline(pos + 1, name.len())
+                    (pos + 1, name.len())
                 } else {
-                    make_underline(1, 1)
+                    (1, 1)
                 }
             });
 
@@ -872,17 +997,17 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec!["ensure the tool is declared with 'use tool'".to_string()],
             }
         }
         ResolveError::Duplicate { name, line } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|l| {
+            let columns = source_line.as_ref().map(|l| {
                 if let Some(pos) = l.find(name) {
-                    make_underline(pos + 1, name.len())
+                    (pos + 1, name.len())
                 } else {
-                    make_underline(1, 1)
+                    (1, 1)
                 }
             });
 
@@ -894,7 +1019,7 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
@@ -905,7 +1030,7 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
             cause,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(1, 1));
+            let columns = source_line.as_ref().map(|_| (1, 1));
 
             let mut suggestions = vec![format!(
                 "add '{}' to the effect row of cell '{}'",
@@ -926,10 +1051,57 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions,
             }
         }
+        ResolveError::PipelineStageTypeMismatch {
+            pipeline,
+            from_stage,
+            from_line,
+            to_stage,
+            expected,
+            actual,
+            line,
+        } => {
+            let source_line = get_source_line(source, *line);
+            let columns = source_line.as_ref().map(|l| {
+                if let Some(pos) = l.find(to_stage.as_str()) {
+                    (pos + 1, to_stage.len())
+                } else {
+                    (1, 1)
+                }
+            });
+
+            let width = from_stage.len().max(to_stage.len()) + 2;
+            Diagnostic {
+                severity: Severity::Error,
+                code: Some(code),
+                message: format!(
+                    "pipeline '{}' passes {} from '{}' to '{}', which expects {}",
+                    pipeline, actual, from_stage, to_stage, expected
+                ),
+                file: Some(filename.to_string()),
+                line: Some(*line),
+                col: None,
+                source_line,
+                span: span_on(Some(*line), columns),
+                suggestions: vec![
+                    format!(
+                        "{:<width$} (line {}) outputs {}",
+                        format!("'{}'", from_stage),
+                        from_line,
+                        actual
+                    ),
+                    format!(
+                        "{:<width$} (line {}) expects {}",
+                        format!("'{}'", to_stage),
+                        line,
+                        expected
+                    ),
+                ],
+            }
+        }
         _ => {
             // Fallback for other resolve errors
             Diagnostic {
@@ -940,7 +1112,7 @@ fn format_resolve_error(error: &ResolveError, source: &str, filename: &str) -> D
                 line: None,
                 col: None,
                 source_line: None,
-                underline: None,
+                span: None,
                 suggestions: vec![],
             }
         }
@@ -956,7 +1128,7 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
             line,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(1, 1));
+            let columns = source_line.as_ref().map(|_| (1, 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -966,17 +1138,17 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
         TypeError::UndefinedVar { name, line } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|l| {
+            let columns = source_line.as_ref().map(|l| {
                 if let Some(pos) = l.find(name) {
-                    make_underline(pos + 1, name.len())
+                    (pos + 1, name.len())
                 } else {
-                    make_underline(1, 1)
+                    (1, 1)
                 }
             });
 
@@ -997,7 +1169,7 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: help,
             }
         }
@@ -1008,11 +1180,11 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
             suggestions: error_suggestions,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|l| {
+            let columns = source_line.as_ref().map(|l| {
                 if let Some(pos) = l.find(field) {
-                    make_underline(pos + 1, field.len())
+                    (pos + 1, field.len())
                 } else {
-                    make_underline(1, 1)
+                    (1, 1)
                 }
             });
 
@@ -1030,7 +1202,75 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
+                suggestions: help,
+            }
+        }
+        TypeError::DuplicateField { field, ty, span } => {
+            let source_line = get_source_line(source, span.line);
+            let columns = source_line.as_ref().map(|l| span_columns(*span, l));
+
+            Diagnostic {
+                severity: Severity::Error,
+                code: Some(code),
+                message: format!("duplicate field '{}' in construction of '{}'", field, ty),
+                file: Some(filename.to_string()),
+                line: Some(span.line),
+                col: None,
+                source_line,
+                span: span_on(Some(span.line), columns),
+                suggestions: vec![format!("remove one of the '{}' arguments", field)],
+            }
+        }
+        TypeError::MissingField { field, ty, span } => {
+            let source_line = get_source_line(source, span.line);
+            let columns = source_line.as_ref().map(|l| span_columns(*span, l));
+
+            Diagnostic {
+                severity: Severity::Error,
+                code: Some(code),
+                message: format!("missing field '{}' in construction of '{}'", field, ty),
+                file: Some(filename.to_string()),
+                line: Some(span.line),
+                col: None,
+                source_line,
+                span: span_on(Some(span.line), columns),
+                suggestions: vec![format!("add '{}: ...' to the construction", field)],
+            }
+        }
+        TypeError::UnknownMethod {
+            method,
+            receiver,
+            line,
+            suggestions: error_suggestions,
+        } => {
+            let source_line = get_source_line(source, *line);
+            let needle = format!(".{}", method);
+            let columns = source_line.as_ref().map(|l| {
+                if let Some(pos) = l.find(&needle) {
+                    (pos + 2, method.len())
+                } else {
+                    (1, 1)
+                }
+            });
+
+            let mut help = vec![format!(
+                "`value.{}(args)` calls `{}(value, args)`; define a cell whose first parameter accepts {}",
+                method, method, receiver
+            )];
+            if let Some(first) = error_suggestions.first() {
+                help.insert(0, format!("Did you mean `{}`?", first));
+            }
+
+            Diagnostic {
+                severity: Severity::Error,
+                code: Some(code),
+                message: format!("no method '{}' for receiver of type {}", method, receiver),
+                file: Some(filename.to_string()),
+                line: Some(*line),
+                col: None,
+                source_line,
+                span: span_on(Some(*line), columns),
                 suggestions: help,
             }
         }
@@ -1040,7 +1280,7 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
             line,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(1, 1));
+            let columns = source_line.as_ref().map(|_| (1, 1));
 
             let missing_list = missing.join(", ");
             let suggestions = vec![format!(
@@ -1059,10 +1299,58 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions,
             }
         }
+        TypeError::UnboundedRecursion { name, line } => {
+            let source_line = get_source_line(source, *line);
+            let columns = source_line.as_ref().map(|l| match l.find(name.as_str()) {
+                Some(pos) => (pos + 1, name.len()),
+                None => (1, 1),
+            });
+
+            Diagnostic {
+                severity: Severity::Warning,
+                code: Some(code),
+                message: format!(
+                    "cell '{}' recurses on every path (no reachable base case)",
+                    name
+                ),
+                file: Some(filename.to_string()),
+                line: Some(*line),
+                col: None,
+                source_line,
+                span: span_on(Some(*line), columns),
+                suggestions: vec!["add a branch that returns without calling the cell".to_string()],
+            }
+        }
+        TypeError::PrecisionLoss { value, line } => {
+            let source_line = get_source_line(source, *line);
+            let literal = value.trim_start_matches('-');
+            let columns = source_line.as_ref().map(|l| match l.find(literal) {
+                Some(pos) => (pos + 1, literal.len()),
+                None => (1, 1),
+            });
+
+            Diagnostic {
+                severity: Severity::Warning,
+                code: Some(code),
+                message: format!(
+                    "integer {} exceeds 2^53 and loses precision when implicitly widened to Float",
+                    value
+                ),
+                file: Some(filename.to_string()),
+                line: Some(*line),
+                col: None,
+                source_line,
+                span: span_on(Some(*line), columns),
+                suggestions: vec![format!(
+                    "write it as a Float literal ({}.0) if rounding is intended, or keep it an Int",
+                    literal
+                )],
+            }
+        }
         _ => {
             // Fallback for other type errors
             let line = match error {
@@ -1071,12 +1359,15 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
                 | TypeError::Mismatch { line, .. }
                 | TypeError::UndefinedVar { line, .. }
                 | TypeError::UnknownField { line, .. }
-                | TypeError::IncompleteMatch { line, .. } => Some(*line),
+                | TypeError::IncompleteMatch { line, .. }
+                | TypeError::ArrayLengthMismatch { line, .. }
+                | TypeError::IndexOutOfBounds { line, .. }
+                | TypeError::AmbiguousMethod { line, .. } => Some(*line),
                 _ => None,
             };
 
             let source_line = line.and_then(|l| get_source_line(source, l));
-            let underline = source_line.as_ref().map(|_| make_underline(1, 1));
+            let columns = source_line.as_ref().map(|_| (1, 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -1086,7 +1377,7 @@ fn format_type_error(error: &TypeError, source: &str, filename: &str) -> Diagnos
                 line,
                 col: None,
                 source_line,
-                underline,
+                span: span_on(line, columns),
                 suggestions: vec![],
             }
         }
@@ -1102,7 +1393,7 @@ fn format_constraint_error(error: &ConstraintError, source: &str, filename: &str
             message,
         } => {
             let source_line = get_source_line(source, *line);
-            let underline = source_line.as_ref().map(|_| make_underline(1, 1));
+            let columns = source_line.as_ref().map(|_| (1, 1));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -1112,7 +1403,7 @@ fn format_constraint_error(error: &ConstraintError, source: &str, filename: &str
                 line: Some(*line),
                 col: None,
                 source_line,
-                underline,
+                span: span_on(Some(*line), columns),
                 suggestions: vec![],
             }
         }
@@ -1128,9 +1419,9 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
             used_at,
         } => {
             let source_line = get_source_line(source, used_at.line);
-            let underline = source_line
+            let columns = source_line
                 .as_ref()
-                .map(|_| make_underline(used_at.col.max(1), variable.len().max(1)));
+                .map(|_| (used_at.col.max(1), variable.len().max(1)));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -1143,7 +1434,7 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
                 line: Some(used_at.line),
                 col: Some(used_at.col),
                 source_line,
-                underline,
+                span: span_on(Some(used_at.line), columns),
                 suggestions: vec![format!(
                     "'{}' was moved at line {}. Consider cloning it or restructuring to avoid reuse after move.",
                     variable, moved_at.line
@@ -1155,9 +1446,9 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
             declared_at,
         } => {
             let source_line = get_source_line(source, declared_at.line);
-            let underline = source_line
+            let columns = source_line
                 .as_ref()
-                .map(|_| make_underline(declared_at.col.max(1), variable.len().max(1)));
+                .map(|_| (declared_at.col.max(1), variable.len().max(1)));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -1170,7 +1461,7 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
                 line: Some(declared_at.line),
                 col: Some(declared_at.col),
                 source_line,
-                underline,
+                span: span_on(Some(declared_at.line), columns),
                 suggestions: vec![format!(
                     "owned variable '{}' must be used or explicitly dropped before going out of scope",
                     variable
@@ -1183,9 +1474,9 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
             second_borrow,
         } => {
             let source_line = get_source_line(source, second_borrow.line);
-            let underline = source_line
+            let columns = source_line
                 .as_ref()
-                .map(|_| make_underline(second_borrow.col.max(1), variable.len().max(1)));
+                .map(|_| (second_borrow.col.max(1), variable.len().max(1)));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -1198,7 +1489,7 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
                 line: Some(second_borrow.line),
                 col: Some(second_borrow.col),
                 source_line,
-                underline,
+                span: span_on(Some(second_borrow.line), columns),
                 suggestions: vec![format!(
                     "cannot create a second borrow of '{}' while the first borrow (line {}) is active",
                     variable, first_borrow.line
@@ -1211,9 +1502,9 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
             move_at,
         } => {
             let source_line = get_source_line(source, move_at.line);
-            let underline = source_line
+            let columns = source_line
                 .as_ref()
-                .map(|_| make_underline(move_at.col.max(1), variable.len().max(1)));
+                .map(|_| (move_at.col.max(1), variable.len().max(1)));
 
             Diagnostic {
                 severity: Severity::Error,
@@ -1226,7 +1517,7 @@ fn format_ownership_error(error: &OwnershipError, source: &str, filename: &str)
                 line: Some(move_at.line),
                 col: Some(move_at.col),
                 source_line,
-                underline,
+                span: span_on(Some(move_at.line), columns),
                 suggestions: vec![format!(
                     "the borrow of '{}' at line {} must end before the value can be moved",
                     variable, borrow_at.line
@@ -1616,6 +1907,48 @@ mod tests {
             .any(|s| s.contains("for") || s.contains("to")));
     }
 
+    #[test]
+    fn test_format_unbounded_recursion_is_a_warning() {
+        let error = TypeError::UnboundedRecursion {
+            name: "spin".to_string(),
+            line: 1,
+        };
+        let diag = format_type_error(&error, "cell spin(n: Int) -> Int\n", "test.lm");
+
+        assert_eq!(diag.severity, Severity::Warning);
+        assert_eq!(diag.code, Some("E0216".to_string()));
+    }
+
+    #[test]
+    fn test_format_precision_loss_is_a_warning() {
+        let error = TypeError::PrecisionLoss {
+            value: "9007199254740993".to_string(),
+            line: 1,
+        };
+        let diag = format_type_error(&error, "let x: Float = 9007199254740993\n", "test.lm");
+
+        assert_eq!(diag.severity, Severity::Warning);
+        assert_eq!(diag.code, Some("E0217".to_string()));
+    }
+
+    #[test]
+    fn test_failure_json_keeps_warnings() {
+        let source =
+            "cell main() -> Float\n  let x: Float = 9007199254740993\n  return missing\nend\n";
+        let failure =
+            crate::compile_raw_with_diagnostics(source, &crate::CompileOptions::default())
+                .expect_err("undefined name should fail");
+        let json = failure_to_json(&failure, source, "test.lm");
+        let severities: Vec<&str> = json
+            .as_array()
+            .expect("array")
+            .iter()
+            .map(|d| d["severity"].as_str().expect("severity"))
+            .collect();
+        assert!(severities.contains(&"error"), "{:?}", json);
+        assert!(severities.contains(&"warning"), "{:?}", json);
+    }
+
     #[test]
     fn test_render_plain() {
         let diag = Diagnostic {
@@ -1626,7 +1959,12 @@ mod tests {
             line: Some(10),
             col: Some(5),
             source_line: Some("  let x = foo".to_string()),
-            underline: Some("         ^^^".to_string()),
+            span: Some(DiagnosticSpan {
+                line: 10,
+                col: 10,
+                end_line: 10,
+                end_col: 13,
+            }),
             suggestions: vec!["did you mean 'for'?".to_string()],
         };
 
@@ -1649,7 +1987,12 @@ mod tests {
             line: Some(10),
             col: Some(5),
             source_line: Some("  let x = foo".to_string()),
-            underline: Some("         ^^^".to_string()),
+            span: Some(DiagnosticSpan {
+                line: 10,
+                col: 10,
+                end_line: 10,
+                end_col: 13,
+            }),
             suggestions: vec!["did you mean 'for'?".to_string()],
         };
 
@@ -274,7 +274,11 @@ fn opcode_info(op: &OpCode) -> (&str, &str, &str) {
             "Effects",
         ),
         HandlePush => ("Ax", "Push effect handler scope at offset Ax", "Effects"),
-        HandlePop => ("–", "Pop current effect handler scope", "Effects"),
+        HandlePop => (
+            "Ax",
+            "Pop current effect handler scope (Ax=1: end of handler clauses)",
+            "Effects",
+        ),
         Resume => (
             "A",
             "Resume suspended continuation with value in A",
@@ -291,6 +295,13 @@ fn opcode_info(op: &OpCode) -> (&str, &str, &str) {
             "Type Checks",
         ),
         Unbox => ("A, B", "A = B.payload (unbox union value)", "Type Checks"),
+
+        // Extensions
+        Extension => (
+            "A, B, C",
+            "Embedder-defined opcode B over registers A and C",
+            "Extensions",
+        ),
     }
 }
 
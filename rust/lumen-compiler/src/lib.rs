@@ -10,7 +10,8 @@ pub mod markdown;
 use compiler::ast::{Directive, ImportDecl, ImportList, Item};
 use compiler::lir::LirModule;
 use compiler::resolve::SymbolTable;
-use std::collections::HashSet;
+use compiler::tokens::Span;
+use std::collections::{HashMap, HashSet};
 
 use thiserror::Error;
 
@@ -52,6 +53,15 @@ pub struct CompileOptions {
     pub allow_unstable: bool,
     /// Language edition for forward-compatibility. Default: `"2026"`.
     pub edition: String,
+    /// Compile independent imported modules on multiple threads. The result
+    /// is the same either way. Default: `true`.
+    pub parallel_imports: bool,
+    /// Apply optimizations (currently constant folding) while lowering.
+    /// Default: `true`.
+    pub optimize: bool,
+    /// Drop imported cells and types the root module never reaches.
+    /// Default: `false`.
+    pub eliminate_dead_code: bool,
 }
 
 impl Default for CompileOptions {
@@ -63,6 +73,9 @@ impl Default for CompileOptions {
             session_actions: std::collections::HashMap::new(),
             allow_unstable: false,
             edition: "2026".to_string(),
+            parallel_imports: true,
+            optimize: true,
+            eliminate_dead_code: false,
         }
     }
 }
@@ -117,24 +130,112 @@ impl From<compiler::parser::ParseError> for CompileError {
     }
 }
 
+/// A compiled module together with the non-fatal findings produced while
+/// compiling it.
+#[derive(Debug)]
+pub struct CompileOutput {
+    pub module: LirModule,
+    /// Type-checker warnings (as `CompileError::Type`) and findings from
+    /// analyses running in `Warn` mode, in the same shape as the error they
+    /// would be in `Error` mode (e.g. `CompileError::Ownership`).
+    pub warnings: Vec<CompileError>,
+}
+
+impl CompileOutput {
+    fn new(module: LirModule) -> Self {
+        Self {
+            module,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// A failed compilation together with the warnings found before it stopped,
+/// so tools can show both.
+#[derive(Debug)]
+pub struct CompileFailure {
+    pub error: CompileError,
+    /// Warnings in the same shape as [`CompileOutput::warnings`].
+    pub warnings: Vec<CompileError>,
+}
+
+impl From<CompileError> for CompileFailure {
+    fn from(error: CompileError) -> Self {
+        Self {
+            error,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Collect `@requires <module> <version-req>` directives into a map from
+/// module path to version requirement.
+fn version_requirements(directives: &[Directive]) -> HashMap<String, String> {
+    directives
+        .iter()
+        .filter(|d| d.name == "requires")
+        .filter_map(|d| {
+            let value = d.value.as_deref()?.trim();
+            let (module, req) = value.split_once(char::is_whitespace)?;
+            Some((module.to_string(), req.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Check the version in an imported module's `@package <name> <version>`
+/// directive against the importer's requirement. A module without a
+/// parseable version never satisfies a requirement.
+fn check_module_version(
+    module: &str,
+    required: &str,
+    imported_directives: &[markdown::extract::DirectiveLine],
+    line: usize,
+) -> Option<compiler::resolve::ResolveError> {
+    let found = imported_directives
+        .iter()
+        .find(|d| d.name == "package")
+        .and_then(|d| d.value.as_deref())
+        .and_then(|value| value.split_whitespace().nth(1))
+        .map(|version| version.trim_matches('"'));
+    let satisfied = match (semver::VersionReq::parse(required), found) {
+        (Ok(req), Some(found)) => semver::Version::parse(found)
+            .map(|v| req.matches(&v))
+            .unwrap_or(false),
+        _ => false,
+    };
+    if satisfied {
+        return None;
+    }
+    Some(compiler::resolve::ResolveError::VersionMismatch {
+        module: module.to_string(),
+        required: required.to_string(),
+        found: found.unwrap_or("<none>").to_string(),
+        line,
+    })
+}
+
 /// Run the three optional analysis passes (ownership, typestate, session types).
 ///
 /// Returns any hard errors produced by analyses running in `Error` mode.
-/// Analyses in `Warn` mode or `Off` mode do not contribute errors.
+/// Findings from analyses in `Warn` mode are pushed onto `warnings` instead.
 fn run_optional_analyses(
     program: &compiler::ast::Program,
     symbols: &SymbolTable,
     options: &CompileOptions,
+    warnings: &mut Vec<CompileError>,
 ) -> Vec<CompileError> {
     let mut errors = Vec::new();
 
     // 1. Ownership analysis
     if options.ownership_mode != OwnershipCheckMode::Off {
         let ownership_errors = compiler::ownership::check_program(program, symbols);
-        if !ownership_errors.is_empty() && options.ownership_mode == OwnershipCheckMode::Error {
-            errors.push(CompileError::Ownership(ownership_errors));
+        if !ownership_errors.is_empty() {
+            if options.ownership_mode == OwnershipCheckMode::Error {
+                errors.push(CompileError::Ownership(ownership_errors));
+            } else {
+                warnings.push(CompileError::Ownership(ownership_errors));
+            }
         }
-        // In Warn mode, errors are detected but silently discarded.
     }
 
     // 2. Typestate checking (opt-in: only if declarations are provided)
@@ -188,9 +289,27 @@ fn lower_safe(
     program: &compiler::ast::Program,
     symbols: &SymbolTable,
     source: &str,
+    method_calls: &HashSet<Span>,
+    options: &CompileOptions,
+) -> Result<LirModule, CompileError> {
+    lower_module_safe(program, symbols, source, method_calls, options, None)
+}
+
+/// [`lower_safe`] for the module imported as `module_path`, if any.
+fn lower_module_safe(
+    program: &compiler::ast::Program,
+    symbols: &SymbolTable,
+    source: &str,
+    method_calls: &HashSet<Span>,
+    options: &CompileOptions,
+    module_path: Option<&str>,
 ) -> Result<LirModule, CompileError> {
+    let lower_options = compiler::lower::LowerOptions {
+        fold_constants: options.optimize,
+        module_path: module_path.map(str::to_string),
+    };
     std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        compiler::lower::lower(program, symbols, source)
+        compiler::lower::lower_with_options(program, symbols, source, method_calls, lower_options)
     }))
     .map_err(|panic_val| {
         let msg = if let Some(s) = panic_val.downcast_ref::<String>() {
@@ -221,24 +340,131 @@ pub fn compile_with_imports_and_options(
     resolve_import: &dyn Fn(&str) -> Option<String>,
     options: &CompileOptions,
 ) -> Result<LirModule, CompileError> {
-    let mut compilation_stack = HashSet::new();
-    compile_with_imports_internal(
-        source,
-        resolve_import,
-        &mut compilation_stack,
-        None,
-        options,
-    )
+    compile_with_imports_and_warnings(source, resolve_import, options).map(|out| out.module)
 }
 
-/// Internal implementation that tracks the compilation stack for circular import detection
-fn compile_with_imports_internal(
+/// Like [`compile_with_imports_and_options`], but also returns the warnings
+/// found in `source`. Warnings in imported modules are not included.
+pub fn compile_with_imports_and_warnings(
     source: &str,
     resolve_import: &dyn Fn(&str) -> Option<String>,
-    compilation_stack: &mut HashSet<String>,
-    _current_module: Option<&str>,
     options: &CompileOptions,
-) -> Result<LirModule, CompileError> {
+) -> Result<CompileOutput, CompileError> {
+    compile_with_imports_and_diagnostics(source, resolve_import, options).map_err(|f| f.error)
+}
+
+/// Like [`compile_with_imports_and_warnings`], but a failed compilation
+/// keeps the warnings of the module that failed alongside its error.
+pub fn compile_with_imports_and_diagnostics(
+    source: &str,
+    resolve_import: &dyn Fn(&str) -> Option<String>,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileFailure> {
+    ImportGraph::discover(source, resolve_import, options).compile(options)
+}
+
+/// What an import path resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportSource {
+    /// A single module file.
+    File(String),
+    /// A directory module: the `(file name, source)` of every module file in
+    /// it. The files are compiled as one module, ordered by file name.
+    Directory(Vec<(String, String)>),
+}
+
+impl ImportSource {
+    /// The source of the whole module. Directory files are concatenated in
+    /// file name order; raw (non-Markdown) files are fenced so that they mix
+    /// with `.lm.md` files.
+    pub fn into_source(self) -> String {
+        match self {
+            ImportSource::File(source) => source,
+            ImportSource::Directory(mut files) => {
+                files.sort_by(|a, b| a.0.cmp(&b.0));
+                let mut combined = String::new();
+                for (name, source) in files {
+                    if !combined.is_empty() && !combined.ends_with('\n') {
+                        combined.push('\n');
+                    }
+                    if name.ends_with(".md") {
+                        combined.push_str(&source);
+                    } else {
+                        combined.push_str("```lumen\n");
+                        combined.push_str(&source);
+                        if !source.ends_with('\n') {
+                            combined.push('\n');
+                        }
+                        combined.push_str("```\n");
+                    }
+                }
+                combined
+            }
+        }
+    }
+}
+
+/// Like [`compile_with_imports_and_warnings`], for a resolver that can
+/// resolve a module path to a directory of files as well as a single file.
+pub fn compile_with_import_sources(
+    source: &str,
+    resolve_import: &dyn Fn(&str) -> Option<ImportSource>,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileError> {
+    compile_with_import_sources_and_diagnostics(source, resolve_import, options)
+        .map_err(|f| f.error)
+}
+
+/// Like [`compile_with_import_sources`], but a failed compilation keeps the
+/// warnings of the module that failed alongside its error.
+pub fn compile_with_import_sources_and_diagnostics(
+    source: &str,
+    resolve_import: &dyn Fn(&str) -> Option<ImportSource>,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileFailure> {
+    let resolve = |module: &str| resolve_import(module).map(ImportSource::into_source);
+    compile_with_imports_and_diagnostics(source, &resolve, options)
+}
+
+/// The modules currently being compiled, innermost last.
+#[derive(Debug, Default)]
+struct ImportStack {
+    order: Vec<String>,
+    members: HashSet<String>,
+}
+
+impl ImportStack {
+    fn push(&mut self, module: String) {
+        self.members.insert(module.clone());
+        self.order.push(module);
+    }
+
+    fn pop(&mut self) {
+        if let Some(module) = self.order.pop() {
+            self.members.remove(&module);
+        }
+    }
+
+    /// If importing `module` would close a cycle, describe it in import order,
+    /// e.g. `a -> b -> c -> a`.
+    fn cycle_to(&self, module: &str) -> Option<String> {
+        if !self.members.contains(module) {
+            return None;
+        }
+        let start = self.order.iter().position(|m| m == module)?;
+        let mut chain = self.order[start..].to_vec();
+        chain.push(module.to_string());
+        Some(chain.join(" -> "))
+    }
+}
+
+/// Extract the Markdown code blocks and directives of a module and parse it.
+///
+/// Returns `Ok(None)` for a module without any code.
+fn parse_module(
+    source: &str,
+    options: &CompileOptions,
+) -> Result<Option<compiler::ast::Program>, CompileError> {
     // 1. Extract Markdown blocks
     let extracted = markdown::extract::extract_blocks(source);
 
@@ -253,7 +479,6 @@ fn compile_with_imports_internal(
         })
         .collect();
 
-    // 3. Concatenate all code blocks
     // 3. Concatenate all code blocks preserving line numbers
     let mut full_code = String::new();
     let mut current_line = 1;
@@ -272,7 +497,7 @@ fn compile_with_imports_internal(
     }
 
     if full_code.trim().is_empty() {
-        return Ok(LirModule::new("sha256:empty".to_string()));
+        return Ok(None);
     }
 
     // 4. Lex
@@ -286,206 +511,568 @@ fn compile_with_imports_internal(
     if !parse_errors.is_empty() {
         return Err(CompileError::Parse(parse_errors));
     }
+    Ok(Some(program))
+}
 
-    // 6. Process imports before resolution
-    let mut base_symbols = SymbolTable::new();
-    let mut import_errors = Vec::new();
-    let mut imported_modules: Vec<LirModule> = Vec::new();
-
-    // Collect all imports
-    let imports: Vec<&ImportDecl> = program
-        .items
-        .iter()
-        .filter_map(|item| {
-            if let Item::Import(imp) = item {
-                Some(imp)
-            } else {
-                None
-            }
-        })
-        .collect();
+/// What an import declaration resolved to while walking the import graph.
+enum ImportOutcome {
+    /// The import is reported as an error in the importing module.
+    Error(compiler::resolve::ResolveError),
+    /// The import names the module at this index in [`ImportGraph::nodes`].
+    Module(usize),
+}
 
-    // Process each import
-    for import in imports {
-        let module_path = import.path.join(".");
+/// The outcome of compiling one module of the import graph.
+enum NodeResult {
+    Compiled(Box<CompileOutput>),
+    Failed(CompileFailure),
+    /// An imported module failed; its error is this module's error.
+    ImportFailed(usize),
+}
 
-        // Check for circular imports
-        if compilation_stack.contains(&module_path) {
-            let chain: Vec<String> = compilation_stack.iter().cloned().collect();
-            let chain_str = format!("{} -> {}", chain.join(" -> "), module_path);
-            import_errors.push(compiler::resolve::ResolveError::CircularImport {
-                module: module_path.clone(),
-                chain: chain_str,
-            });
-            continue;
-        }
+/// A module reached while walking the import graph.
+struct ModuleNode {
+    source: String,
+    /// Import path of the module; `None` for the source being compiled.
+    path: Option<String>,
+    /// `None` when the module has nothing to compile or failed to parse; its
+    /// `result` is then already set.
+    program: Option<compiler::ast::Program>,
+    /// One outcome per import declaration, in source order.
+    imports: Vec<ImportOutcome>,
+    result: Option<NodeResult>,
+}
 
-        // Resolve the module source
-        let imported_source = match resolve_import(&module_path) {
-            Some(src) => src,
-            None => {
-                import_errors.push(compiler::resolve::ResolveError::ModuleNotFound {
-                    module: module_path.clone(),
-                    line: import.span.line,
-                });
-                continue;
-            }
-        };
+/// All modules reachable from the root source, each compiled at most once.
+///
+/// The graph is walked sequentially, in the same depth-first order imports
+/// are written, so `resolve_import` is only ever called from one thread and
+/// circular imports are detected against the exact import chain. Imports that
+/// would close a cycle become errors rather than edges, which leaves a DAG
+/// that [`ImportGraph::compile`] can build in parallel: every module whose
+/// imports are finished is compiled in the same wave. Results are looked up
+/// by node rather than gathered by completion, so the merged module does not
+/// depend on thread scheduling.
+struct ImportGraph {
+    /// Node 0 is the root source.
+    nodes: Vec<ModuleNode>,
+    by_path: HashMap<String, usize>,
+}
 
-        // Track this module in the compilation stack
-        compilation_stack.insert(module_path.clone());
+impl ImportGraph {
+    fn discover(
+        source: &str,
+        resolve_import: &dyn Fn(&str) -> Option<String>,
+        options: &CompileOptions,
+    ) -> Self {
+        Self::discover_within(
+            source,
+            None,
+            resolve_import,
+            &mut ImportStack::default(),
+            options,
+        )
+    }
 
-        // Recursively compile the imported module. The markdown pipeline now
-        // supports fenced and unfenced source forms.
-        let imported_module = compile_with_imports_internal(
-            &imported_source,
+    /// Like [`Self::discover`], for the module at `module_path` that is being
+    /// imported by the modules on `compilation_stack`.
+    fn discover_within(
+        source: &str,
+        module_path: Option<String>,
+        resolve_import: &dyn Fn(&str) -> Option<String>,
+        compilation_stack: &mut ImportStack,
+        options: &CompileOptions,
+    ) -> Self {
+        let mut graph = Self {
+            nodes: Vec::new(),
+            by_path: HashMap::new(),
+        };
+        graph.add_module(
+            source.to_string(),
+            module_path,
             resolve_import,
             compilation_stack,
-            Some(&module_path),
             options,
-        )?;
-
-        // Remove from stack after compilation
-        compilation_stack.remove(&module_path);
+        );
+        graph
+    }
 
-        // Keep the compiled module for later merging
-        imported_modules.push(imported_module);
+    fn add_module(
+        &mut self,
+        source: String,
+        path: Option<String>,
+        resolve_import: &dyn Fn(&str) -> Option<String>,
+        compilation_stack: &mut ImportStack,
+        options: &CompileOptions,
+    ) -> usize {
+        let idx = self.nodes.len();
+        let (program, result) = match parse_module(&source, options) {
+            Ok(Some(program)) => (Some(program), None),
+            Ok(None) => {
+                let empty = CompileOutput::new(LirModule::new("sha256:empty".to_string()));
+                (None, Some(NodeResult::Compiled(Box::new(empty))))
+            }
+            Err(err) => (None, Some(NodeResult::Failed(err.into()))),
+        };
+        let (imports, requirements) = match &program {
+            Some(program) => (
+                program
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        Item::Import(imp) => Some((imp.path.join("."), imp.span.line)),
+                        _ => None,
+                    })
+                    .collect(),
+                version_requirements(&program.directives),
+            ),
+            None => (Vec::new(), HashMap::new()),
+        };
+        self.nodes.push(ModuleNode {
+            source,
+            path,
+            program,
+            imports: Vec::new(),
+            result,
+        });
+
+        let mut outcomes = Vec::new();
+        for (module_path, line) in imports {
+            // Check for circular imports
+            if let Some(chain) = compilation_stack.cycle_to(&module_path) {
+                outcomes.push(ImportOutcome::Error(
+                    compiler::resolve::ResolveError::CircularImport {
+                        module: module_path,
+                        chain,
+                    },
+                ));
+                continue;
+            }
 
-        // Extract symbols from the imported module by parsing it as markdown if it has
-        // fenced lumen blocks, otherwise as raw source.
-        let imported_extracted = markdown::extract::extract_blocks(&imported_source);
-        let (imported_code, imported_directives, imported_line, imported_offset) =
-            if imported_extracted.code_blocks.is_empty() {
-                (imported_source.clone(), vec![], 1, 0)
-            } else {
-                let mut code = String::new();
-                let mut first_line = 1;
-                let mut first_offset = 0;
-                for (i, block) in imported_extracted.code_blocks.iter().enumerate() {
-                    if i == 0 {
-                        first_line = block.code_start_line;
-                        first_offset = block.code_offset;
+            // Resolve the module source, unless another import already did
+            let existing = self.by_path.get(&module_path).copied();
+            let imported_source = match existing {
+                Some(dep) => self.nodes[dep].source.clone(),
+                None => match resolve_import(&module_path) {
+                    Some(src) => src,
+                    None => {
+                        outcomes.push(ImportOutcome::Error(
+                            compiler::resolve::ResolveError::ModuleNotFound {
+                                module: module_path,
+                                line,
+                            },
+                        ));
+                        continue;
                     }
-                    if !code.is_empty() {
-                        code.push('\n');
-                    }
-                    code.push_str(&block.code);
+                },
+            };
+
+            // Check the module's declared version against any requirement
+            if let Some(required) = requirements.get(&module_path) {
+                let imported_directives =
+                    markdown::extract::extract_blocks(&imported_source).directives;
+                if let Some(err) =
+                    check_module_version(&module_path, required, &imported_directives, line)
+                {
+                    outcomes.push(ImportOutcome::Error(err));
+                    continue;
                 }
-                let directives: Vec<Directive> = imported_extracted
-                    .directives
+            }
+
+            let dep = match existing {
+                Some(dep) => dep,
+                None => {
+                    // Walk the imported module with it on the compilation stack
+                    compilation_stack.push(module_path.clone());
+                    self.by_path.insert(module_path.clone(), self.nodes.len());
+                    let dep = self.add_module(
+                        imported_source,
+                        Some(module_path.clone()),
+                        resolve_import,
+                        compilation_stack,
+                        options,
+                    );
+                    compilation_stack.pop();
+                    dep
+                }
+            };
+            outcomes.push(ImportOutcome::Module(dep));
+        }
+        self.nodes[idx].imports = outcomes;
+        idx
+    }
+
+    /// Compile every module, imports before importers, and return the root.
+    fn compile(mut self, options: &CompileOptions) -> Result<CompileOutput, CompileFailure> {
+        while self.nodes[0].result.is_none() {
+            // Modules whose imports are all finished. A failed import fails the
+            // importer without compiling it, as the first failure in source
+            // order would when compiling sequentially.
+            let mut ready = Vec::new();
+            for idx in 0..self.nodes.len() {
+                let node = &self.nodes[idx];
+                if node.result.is_some() {
+                    continue;
+                }
+                let deps: Vec<usize> = node
+                    .imports
                     .iter()
-                    .map(|d| Directive {
-                        name: d.name.clone(),
-                        value: d.value.clone(),
-                        span: d.span,
+                    .filter_map(|outcome| match outcome {
+                        ImportOutcome::Module(dep) => Some(*dep),
+                        ImportOutcome::Error(_) => None,
                     })
                     .collect();
-                (code, directives, first_line, first_offset)
-            };
-
-        let mut imported_lexer =
-            compiler::lexer::Lexer::new(&imported_code, imported_line, imported_offset);
-        if let Ok(imported_tokens) = imported_lexer.tokenize() {
-            let mut imported_parser =
-                compiler::parser::Parser::with_edition(imported_tokens, options.edition.clone());
-            if let Ok(imported_program) = imported_parser.parse_program(imported_directives) {
-                if let Ok(imported_symbols) = compiler::resolve::resolve(&imported_program) {
-                    // Import the requested symbols
-                    match &import.names {
-                        ImportList::Wildcard => {
-                            // Import all top-level definitions
-                            for (name, info) in imported_symbols.cells {
-                                base_symbols.import_cell(name, info);
-                            }
-                            for (name, info) in imported_symbols.types {
-                                base_symbols.import_type(name, info);
-                            }
-                            for (name, type_expr) in imported_symbols.type_aliases {
-                                base_symbols.import_type_alias(name, type_expr);
-                            }
-                        }
-                        ImportList::Names(names) => {
-                            for import_name in names {
-                                let symbol_name = &import_name.name;
-                                let local_name = import_name.alias.as_ref().unwrap_or(symbol_name);
-
-                                // Try to find the symbol in cells, types, or type aliases
-                                let mut found = false;
-
-                                if let Some(cell_info) = imported_symbols.cells.get(symbol_name) {
-                                    base_symbols.import_cell(local_name.clone(), cell_info.clone());
-                                    found = true;
-                                }
-
-                                if let Some(type_info) = imported_symbols.types.get(symbol_name) {
-                                    base_symbols.import_type(local_name.clone(), type_info.clone());
-                                    found = true;
-                                }
-
-                                if let Some(type_expr) =
-                                    imported_symbols.type_aliases.get(symbol_name)
-                                {
-                                    base_symbols
-                                        .import_type_alias(local_name.clone(), type_expr.clone());
-                                    found = true;
-                                }
-
-                                if !found {
-                                    import_errors.push(
-                                        compiler::resolve::ResolveError::ImportedSymbolNotFound {
-                                            symbol: symbol_name.clone(),
-                                            module: module_path.clone(),
-                                            line: import_name.span.line,
-                                        },
-                                    );
-                                }
-                            }
-                        }
-                    }
+                if deps.iter().any(|&dep| self.nodes[dep].result.is_none()) {
+                    continue;
                 }
+                let failed = deps
+                    .into_iter()
+                    .find(|&dep| !matches!(self.nodes[dep].result, Some(NodeResult::Compiled(_))));
+                match failed {
+                    Some(dep) => self.nodes[idx].result = Some(NodeResult::ImportFailed(dep)),
+                    None => ready.push(idx),
+                }
+            }
+
+            let tasks: Vec<(usize, Vec<ImportOutcome>)> = ready
+                .into_iter()
+                .map(|idx| (idx, std::mem::take(&mut self.nodes[idx].imports)))
+                .collect();
+            for (idx, result) in self.compile_wave(tasks, options) {
+                self.nodes[idx].result = Some(result);
             }
         }
-    }
 
-    // 7. Resolve with imported symbols pre-populated (collect errors, continue with partial table)
-    // Use resolve_with_base_partial so imported symbols are available during resolution
-    let (symbols, resolve_errors) =
-        compiler::resolve::resolve_with_base_partial(&program, base_symbols);
-    let mut all_errors: Vec<CompileError> = Vec::new();
-    if !import_errors.is_empty() {
-        all_errors.push(CompileError::Resolve(import_errors));
-    }
-    if !resolve_errors.is_empty() {
-        all_errors.push(CompileError::Resolve(resolve_errors));
+        let mut idx = 0;
+        loop {
+            match self.nodes[idx].result.take() {
+                Some(NodeResult::Compiled(output)) => return Ok(*output),
+                Some(NodeResult::Failed(failure)) => return Err(failure),
+                Some(NodeResult::ImportFailed(dep)) => idx = dep,
+                None => unreachable!("import graph node left uncompiled"),
+            }
+        }
     }
 
-    // 8. Typecheck (run even if resolve had errors, using partial symbol table)
-    if let Err(type_errors) = compiler::typecheck::typecheck(&program, &symbols) {
-        all_errors.push(CompileError::Type(type_errors));
-    }
+    /// Compile independent modules, splitting them across threads when
+    /// `options.parallel_imports` is set.
+    fn compile_wave(
+        &self,
+        tasks: Vec<(usize, Vec<ImportOutcome>)>,
+        options: &CompileOptions,
+    ) -> Vec<(usize, NodeResult)> {
+        let threads = if options.parallel_imports {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+                .min(tasks.len())
+        } else {
+            1
+        };
+        if threads <= 1 {
+            return tasks
+                .into_iter()
+                .map(|(idx, imports)| (idx, self.compile_node(idx, imports, options)))
+                .collect();
+        }
 
-    // 9. Validate constraints
-    if let Err(constraint_errors) = compiler::constraints::validate_constraints(&program) {
-        all_errors.push(CompileError::Constraint(constraint_errors));
+        let per_thread = tasks.len().div_ceil(threads);
+        let mut chunks: Vec<Vec<(usize, Vec<ImportOutcome>)>> = Vec::new();
+        let mut tasks = tasks.into_iter().peekable();
+        while tasks.peek().is_some() {
+            chunks.push(tasks.by_ref().take(per_thread).collect());
+        }
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .into_iter()
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .into_iter()
+                            .map(|(idx, imports)| (idx, self.compile_node(idx, imports, options)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
     }
 
-    // 10. Run optional analysis passes (ownership, typestate, session types)
-    all_errors.extend(run_optional_analyses(&program, &symbols, options));
+    /// Resolve, check, and lower one module whose imports all compiled.
+    fn compile_node(
+        &self,
+        idx: usize,
+        imports: Vec<ImportOutcome>,
+        options: &CompileOptions,
+    ) -> NodeResult {
+        let node = &self.nodes[idx];
+        let program = match &node.program {
+            Some(program) => program,
+            None => unreachable!("only parsed modules are compiled"),
+        };
+
+        // 6. Process imports before resolution
+        let mut base_symbols = SymbolTable::new();
+        let mut import_errors = Vec::new();
+        let mut imported_modules: Vec<&LirModule> = Vec::new();
+
+        let decls = program.items.iter().filter_map(|item| match item {
+            Item::Import(imp) => Some(imp),
+            _ => None,
+        });
+        for (import, outcome) in decls.zip(imports) {
+            let dep = match outcome {
+                ImportOutcome::Error(err) => {
+                    import_errors.push(err);
+                    continue;
+                }
+                ImportOutcome::Module(dep) => dep,
+            };
+            match &self.nodes[dep].result {
+                Some(NodeResult::Compiled(output)) => imported_modules.push(&output.module),
+                _ => unreachable!("importers are only compiled after their imports"),
+            }
+            import_module_symbols(
+                import,
+                &import.path.join("."),
+                &self.nodes[dep].source,
+                options,
+                &mut base_symbols,
+                &mut import_errors,
+            );
+        }
 
-    // If there were any errors, report them all
-    if let Some(combined) = CompileError::from_multiple(all_errors) {
-        return Err(combined);
+        // 7. Resolve with imported symbols pre-populated (collect errors, continue with partial table)
+        // Use resolve_with_base_partial so imported symbols are available during resolution
+        let (symbols, resolve_errors) =
+            compiler::resolve::resolve_with_base_partial(program, base_symbols);
+        let mut all_errors: Vec<CompileError> = Vec::new();
+        if !import_errors.is_empty() {
+            all_errors.push(CompileError::Resolve(import_errors));
+        }
+        if !resolve_errors.is_empty() {
+            all_errors.push(CompileError::Resolve(resolve_errors));
+        }
+
+        // 8. Typecheck (run even if resolve had errors, using partial symbol table)
+        let mut warnings = Vec::new();
+        let (typecheck_result, type_warnings) =
+            compiler::typecheck::typecheck_with_warnings(program, &symbols);
+        if !type_warnings.is_empty() {
+            warnings.push(CompileError::Type(type_warnings));
+        }
+        let method_calls = typecheck_result.unwrap_or_else(|type_errors| {
+            all_errors.push(CompileError::Type(type_errors));
+            HashSet::new()
+        });
+
+        // 9. Validate constraints
+        if let Err(constraint_errors) = compiler::constraints::validate_constraints(program) {
+            all_errors.push(CompileError::Constraint(constraint_errors));
+        }
+
+        // 10. Run optional analysis passes (ownership, typestate, session types)
+        all_errors.extend(run_optional_analyses(
+            program,
+            &symbols,
+            options,
+            &mut warnings,
+        ));
+
+        // If there were any errors, report them all
+        if let Some(combined) = CompileError::from_multiple(all_errors) {
+            return NodeResult::Failed(CompileFailure {
+                error: combined,
+                warnings,
+            });
+        }
+
+        // 11. Lower to LIR
+        let mut module = match lower_module_safe(
+            program,
+            &symbols,
+            &node.source,
+            &method_calls,
+            options,
+            node.path.as_deref(),
+        ) {
+            Ok(module) => module,
+            Err(error) => return NodeResult::Failed(CompileFailure { error, warnings }),
+        };
+
+        // 12. Merge imported modules
+        let own_cells: HashSet<String> = module.cells.iter().map(|c| c.name.clone()).collect();
+        let own_types: HashSet<String> = module.types.iter().map(|t| t.name.clone()).collect();
+        for imported_module in imported_modules {
+            module.merge(imported_module);
+        }
+
+        // 13. Prune what the program can't reach (the root module only, so
+        // intermediate modules keep everything their importers might use)
+        if node.path.is_none() && options.eliminate_dead_code {
+            compiler::optimize::eliminate_dead_code(&mut module, &own_cells, &own_types);
+        }
+
+        NodeResult::Compiled(Box::new(CompileOutput { module, warnings }))
     }
+}
 
-    // 11. Lower to LIR
-    let mut module = lower_safe(&program, &symbols, source)?;
+/// Add the names `import` requests from the module at `module_path` to
+/// `base_symbols`.
+fn import_module_symbols(
+    import: &ImportDecl,
+    module_path: &str,
+    imported_source: &str,
+    options: &CompileOptions,
+    base_symbols: &mut SymbolTable,
+    import_errors: &mut Vec<compiler::resolve::ResolveError>,
+) {
+    // Extract symbols from the imported module by parsing it as markdown if it has
+    // fenced lumen blocks, otherwise as raw source.
+    let imported_extracted = markdown::extract::extract_blocks(imported_source);
+    let (imported_code, imported_directives, imported_line, imported_offset) =
+        if imported_extracted.code_blocks.is_empty() {
+            (imported_source.to_string(), vec![], 1, 0)
+        } else {
+            let mut code = String::new();
+            let mut first_line = 1;
+            let mut first_offset = 0;
+            for (i, block) in imported_extracted.code_blocks.iter().enumerate() {
+                if i == 0 {
+                    first_line = block.code_start_line;
+                    first_offset = block.code_offset;
+                }
+                if !code.is_empty() {
+                    code.push('\n');
+                }
+                code.push_str(&block.code);
+            }
+            let directives: Vec<Directive> = imported_extracted
+                .directives
+                .iter()
+                .map(|d| Directive {
+                    name: d.name.clone(),
+                    value: d.value.clone(),
+                    span: d.span,
+                })
+                .collect();
+            (code, directives, first_line, first_offset)
+        };
 
-    // 11. Merge imported modules
-    for imported_module in imported_modules {
-        module.merge(&imported_module);
+    let mut imported_lexer =
+        compiler::lexer::Lexer::new(&imported_code, imported_line, imported_offset);
+    if let Ok(imported_tokens) = imported_lexer.tokenize() {
+        let mut imported_parser =
+            compiler::parser::Parser::with_edition(imported_tokens, options.edition.clone());
+        if let Ok(imported_program) = imported_parser.parse_program(imported_directives) {
+            if let Ok(mut imported_symbols) = compiler::resolve::resolve(&imported_program) {
+                compiler::typecheck::infer_return_types(&imported_program, &mut imported_symbols);
+                import_requested_names(
+                    import,
+                    module_path,
+                    &imported_program,
+                    imported_symbols,
+                    base_symbols,
+                    import_errors,
+                );
+            }
+        }
     }
+}
 
-    Ok(module)
+/// Add the symbols `import` names (or every exported symbol, for a wildcard
+/// import) from the resolved module to `base_symbols`.
+fn import_requested_names(
+    import: &ImportDecl,
+    module_path: &str,
+    imported_program: &compiler::ast::Program,
+    imported_symbols: SymbolTable,
+    base_symbols: &mut SymbolTable,
+    import_errors: &mut Vec<compiler::resolve::ResolveError>,
+) {
+    let private = imported_program.private_names();
+    let module_alias = import.alias.as_deref();
+    match &import.names {
+        ImportList::Wildcard => {
+            // Import all exported top-level definitions
+            for (name, info) in imported_symbols.cells {
+                if !private.contains(&name) {
+                    base_symbols.import_cell(
+                        SymbolTable::import_name(module_alias, &name),
+                        &SymbolTable::module_cell_name(module_path, &name),
+                        info,
+                    );
+                }
+            }
+            for (name, info) in imported_symbols.types {
+                if !private.contains(&name) {
+                    base_symbols.import_type(SymbolTable::import_name(module_alias, &name), info);
+                }
+            }
+            for (name, type_expr) in imported_symbols.type_aliases {
+                if !private.contains(&name) {
+                    base_symbols.import_type_alias(
+                        SymbolTable::import_name(module_alias, &name),
+                        type_expr,
+                    );
+                }
+            }
+        }
+        ImportList::Names(names) => {
+            for import_name in names {
+                let symbol_name = &import_name.name;
+                if private.contains(symbol_name) {
+                    import_errors.push(compiler::resolve::ResolveError::ImportedSymbolPrivate {
+                        symbol: symbol_name.clone(),
+                        module: module_path.to_string(),
+                        line: import_name.span.line,
+                    });
+                    continue;
+                }
+                let local_name = &SymbolTable::import_name(
+                    module_alias,
+                    import_name.alias.as_ref().unwrap_or(symbol_name),
+                );
+
+                // Try to find the symbol in cells, types, or type aliases
+                let mut found = false;
+
+                if let Some(cell_info) = imported_symbols.cells.get(symbol_name) {
+                    base_symbols.import_cell(
+                        local_name.clone(),
+                        &SymbolTable::module_cell_name(module_path, symbol_name),
+                        cell_info.clone(),
+                    );
+                    found = true;
+                }
+
+                if let Some(type_info) = imported_symbols.types.get(symbol_name) {
+                    base_symbols.import_type(local_name.clone(), type_info.clone());
+                    found = true;
+                }
+
+                if let Some(type_expr) = imported_symbols.type_aliases.get(symbol_name) {
+                    base_symbols.import_type_alias(local_name.clone(), type_expr.clone());
+                    found = true;
+                }
+
+                if !found {
+                    import_errors.push(compiler::resolve::ResolveError::ImportedSymbolNotFound {
+                        symbol: symbol_name.clone(),
+                        module: module_path.to_string(),
+                        line: import_name.span.line,
+                    });
+                }
+            }
+        }
+    }
 }
 
 /// Compile raw .lm source with access to external modules for import resolution.
@@ -496,7 +1083,7 @@ pub fn compile_raw_with_imports(
     source: &str,
     resolve_import: &dyn Fn(&str) -> Option<String>,
 ) -> Result<LirModule, CompileError> {
-    let mut compilation_stack = HashSet::new();
+    let mut compilation_stack = ImportStack::default();
     compile_raw_with_imports_internal(source, resolve_import, &mut compilation_stack, None)
 }
 
@@ -504,7 +1091,7 @@ pub fn compile_raw_with_imports(
 fn compile_raw_with_imports_internal(
     source: &str,
     resolve_import: &dyn Fn(&str) -> Option<String>,
-    compilation_stack: &mut HashSet<String>,
+    compilation_stack: &mut ImportStack,
     _current_module: Option<&str>,
 ) -> Result<LirModule, CompileError> {
     if source.trim().is_empty() {
@@ -545,12 +1132,10 @@ fn compile_raw_with_imports_internal(
         let module_path = import.path.join(".");
 
         // Check for circular imports
-        if compilation_stack.contains(&module_path) {
-            let chain: Vec<String> = compilation_stack.iter().cloned().collect();
-            let chain_str = format!("{} -> {}", chain.join(" -> "), module_path);
+        if let Some(chain) = compilation_stack.cycle_to(&module_path) {
             import_errors.push(compiler::resolve::ResolveError::CircularImport {
                 module: module_path.clone(),
-                chain: chain_str,
+                chain,
             });
             continue;
         }
@@ -568,20 +1153,24 @@ fn compile_raw_with_imports_internal(
         };
 
         // Track this module in the compilation stack
-        compilation_stack.insert(module_path.clone());
+        compilation_stack.push(module_path.clone());
 
         // Recursively compile the imported module through the markdown pipeline,
         // which also handles unfenced source.
-        let imported_module = compile_with_imports_internal(
+        let options = CompileOptions::default();
+        let imported_module = ImportGraph::discover_within(
             &imported_source,
+            Some(module_path.clone()),
             resolve_import,
             compilation_stack,
-            Some(&module_path),
-            &CompileOptions::default(),
-        )?;
+            &options,
+        )
+        .compile(&options)
+        .map_err(|f| f.error)?
+        .module;
 
         // Remove from stack after compilation
-        compilation_stack.remove(&module_path);
+        compilation_stack.pop();
 
         // Keep the compiled module for later merging
         imported_modules.push(imported_module);
@@ -623,59 +1212,19 @@ fn compile_raw_with_imports_internal(
         if let Ok(imported_tokens) = imported_lexer.tokenize() {
             let mut imported_parser = compiler::parser::Parser::new(imported_tokens);
             if let Ok(imported_program) = imported_parser.parse_program(imported_directives) {
-                if let Ok(imported_symbols) = compiler::resolve::resolve(&imported_program) {
-                    // Import the requested symbols
-                    match &import.names {
-                        ImportList::Wildcard => {
-                            // Import all top-level definitions
-                            for (name, info) in imported_symbols.cells {
-                                base_symbols.import_cell(name, info);
-                            }
-                            for (name, info) in imported_symbols.types {
-                                base_symbols.import_type(name, info);
-                            }
-                            for (name, type_expr) in imported_symbols.type_aliases {
-                                base_symbols.import_type_alias(name, type_expr);
-                            }
-                        }
-                        ImportList::Names(names) => {
-                            for import_name in names {
-                                let symbol_name = &import_name.name;
-                                let local_name = import_name.alias.as_ref().unwrap_or(symbol_name);
-
-                                // Try to find the symbol in cells, types, or type aliases
-                                let mut found = false;
-
-                                if let Some(cell_info) = imported_symbols.cells.get(symbol_name) {
-                                    base_symbols.import_cell(local_name.clone(), cell_info.clone());
-                                    found = true;
-                                }
-
-                                if let Some(type_info) = imported_symbols.types.get(symbol_name) {
-                                    base_symbols.import_type(local_name.clone(), type_info.clone());
-                                    found = true;
-                                }
-
-                                if let Some(type_expr) =
-                                    imported_symbols.type_aliases.get(symbol_name)
-                                {
-                                    base_symbols
-                                        .import_type_alias(local_name.clone(), type_expr.clone());
-                                    found = true;
-                                }
-
-                                if !found {
-                                    import_errors.push(
-                                        compiler::resolve::ResolveError::ImportedSymbolNotFound {
-                                            symbol: symbol_name.clone(),
-                                            module: module_path.clone(),
-                                            line: import_name.span.line,
-                                        },
-                                    );
-                                }
-                            }
-                        }
-                    }
+                if let Ok(mut imported_symbols) = compiler::resolve::resolve(&imported_program) {
+                    compiler::typecheck::infer_return_types(
+                        &imported_program,
+                        &mut imported_symbols,
+                    );
+                    import_requested_names(
+                        import,
+                        &module_path,
+                        &imported_program,
+                        imported_symbols,
+                        &mut base_symbols,
+                        &mut import_errors,
+                    );
                 }
             }
         }
@@ -693,9 +1242,11 @@ fn compile_raw_with_imports_internal(
     }
 
     // 5. Typecheck (run even if resolve had errors, using partial symbol table)
-    if let Err(type_errors) = compiler::typecheck::typecheck(&program, &symbols) {
-        all_errors.push(CompileError::Type(type_errors));
-    }
+    let method_calls = compiler::typecheck::typecheck_with_method_calls(&program, &symbols)
+        .unwrap_or_else(|type_errors| {
+            all_errors.push(CompileError::Type(type_errors));
+            HashSet::new()
+        });
 
     // 6. Validate constraints
     if let Err(constraint_errors) = compiler::constraints::validate_constraints(&program) {
@@ -708,7 +1259,13 @@ fn compile_raw_with_imports_internal(
     }
 
     // 7. Lower to LIR
-    let mut module = lower_safe(&program, &symbols, source)?;
+    let mut module = lower_safe(
+        &program,
+        &symbols,
+        source,
+        &method_calls,
+        &CompileOptions::default(),
+    )?;
 
     // 8. Merge imported modules
     for imported_module in imported_modules {
@@ -729,19 +1286,38 @@ pub fn compile_raw_with_options(
     source: &str,
     options: &CompileOptions,
 ) -> Result<LirModule, CompileError> {
+    compile_raw_with_warnings(source, options).map(|out| out.module)
+}
+
+/// Like [`compile_raw_with_options`], but also returns warnings.
+pub fn compile_raw_with_warnings(
+    source: &str,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileError> {
+    compile_raw_with_diagnostics(source, options).map_err(|f| f.error)
+}
+
+/// Like [`compile_raw_with_warnings`], but a failed compilation keeps the
+/// warnings found so far alongside the error.
+pub fn compile_raw_with_diagnostics(
+    source: &str,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileFailure> {
     if source.trim().is_empty() {
-        return Ok(LirModule::new("sha256:empty".to_string()));
+        return Ok(CompileOutput::new(LirModule::new(
+            "sha256:empty".to_string(),
+        )));
     }
 
     // 1. Lex (start at line 1, offset 0)
     let mut lexer = compiler::lexer::Lexer::new(source, 1, 0);
-    let tokens = lexer.tokenize()?;
+    let tokens = lexer.tokenize().map_err(CompileError::from)?;
 
     // 2. Parse (no directives for raw source)
     let mut parser = compiler::parser::Parser::with_edition(tokens, options.edition.clone());
     let (program, parse_errors) = parser.parse_program_with_recovery(vec![]);
     if !parse_errors.is_empty() {
-        return Err(CompileError::Parse(parse_errors));
+        return Err(CompileError::Parse(parse_errors).into());
     }
 
     // 3. Resolve (collect errors but continue with partial symbol table)
@@ -752,9 +1328,16 @@ pub fn compile_raw_with_options(
     }
 
     // 4. Typecheck (run even if resolve had errors, using partial symbol table)
-    if let Err(type_errors) = compiler::typecheck::typecheck(&program, &symbols) {
-        all_errors.push(CompileError::Type(type_errors));
+    let mut warnings = Vec::new();
+    let (typecheck_result, type_warnings) =
+        compiler::typecheck::typecheck_with_warnings(&program, &symbols);
+    if !type_warnings.is_empty() {
+        warnings.push(CompileError::Type(type_warnings));
     }
+    let method_calls = typecheck_result.unwrap_or_else(|type_errors| {
+        all_errors.push(CompileError::Type(type_errors));
+        HashSet::new()
+    });
 
     // 5. Validate constraints
     if let Err(constraint_errors) = compiler::constraints::validate_constraints(&program) {
@@ -762,17 +1345,26 @@ pub fn compile_raw_with_options(
     }
 
     // 6. Run optional analysis passes (ownership, typestate, session types)
-    all_errors.extend(run_optional_analyses(&program, &symbols, options));
+    all_errors.extend(run_optional_analyses(
+        &program,
+        &symbols,
+        options,
+        &mut warnings,
+    ));
 
     // If there were any errors, report them all
     if let Some(combined) = CompileError::from_multiple(all_errors) {
-        return Err(combined);
+        return Err(CompileFailure {
+            error: combined,
+            warnings,
+        });
     }
 
     // 7. Lower to LIR
-    let module = lower_safe(&program, &symbols, source)?;
-
-    Ok(module)
+    match lower_safe(&program, &symbols, source, &method_calls, options) {
+        Ok(module) => Ok(CompileOutput { module, warnings }),
+        Err(error) => Err(CompileFailure { error, warnings }),
+    }
 }
 
 pub fn compile(source: &str) -> Result<LirModule, CompileError> {
@@ -784,6 +1376,24 @@ pub fn compile_with_options(
     source: &str,
     options: &CompileOptions,
 ) -> Result<LirModule, CompileError> {
+    compile_with_warnings(source, options).map(|out| out.module)
+}
+
+/// Like [`compile_with_options`], but also returns warnings, such as
+/// ownership violations found in `OwnershipCheckMode::Warn`.
+pub fn compile_with_warnings(
+    source: &str,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileError> {
+    compile_with_diagnostics(source, options).map_err(|f| f.error)
+}
+
+/// Like [`compile_with_warnings`], but a failed compilation keeps the
+/// warnings found so far alongside the error.
+pub fn compile_with_diagnostics(
+    source: &str,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileFailure> {
     // 1. Extract Markdown blocks
     let extracted = markdown::extract::extract_blocks(source);
 
@@ -813,18 +1423,20 @@ pub fn compile_with_options(
     }
 
     if full_code.trim().is_empty() {
-        return Ok(LirModule::new("sha256:empty".to_string()));
+        return Ok(CompileOutput::new(LirModule::new(
+            "sha256:empty".to_string(),
+        )));
     }
 
     // 4. Lex
     let mut lexer = compiler::lexer::Lexer::new(&full_code, 1, 0);
-    let tokens = lexer.tokenize()?;
+    let tokens = lexer.tokenize().map_err(CompileError::from)?;
 
     // 5. Parse
     let mut parser = compiler::parser::Parser::with_edition(tokens, options.edition.clone());
     let (program, parse_errors) = parser.parse_program_with_recovery(directives);
     if !parse_errors.is_empty() {
-        return Err(CompileError::Parse(parse_errors));
+        return Err(CompileError::Parse(parse_errors).into());
     }
 
     // 6. Resolve (collect errors but continue with partial symbol table)
@@ -835,9 +1447,16 @@ pub fn compile_with_options(
     }
 
     // 7. Typecheck (run even if resolve had errors, using partial symbol table)
-    if let Err(type_errors) = compiler::typecheck::typecheck(&program, &symbols) {
-        all_errors.push(CompileError::Type(type_errors));
+    let mut warnings = Vec::new();
+    let (typecheck_result, type_warnings) =
+        compiler::typecheck::typecheck_with_warnings(&program, &symbols);
+    if !type_warnings.is_empty() {
+        warnings.push(CompileError::Type(type_warnings));
     }
+    let method_calls = typecheck_result.unwrap_or_else(|type_errors| {
+        all_errors.push(CompileError::Type(type_errors));
+        HashSet::new()
+    });
 
     // 8. Validate constraints
     if let Err(constraint_errors) = compiler::constraints::validate_constraints(&program) {
@@ -845,17 +1464,26 @@ pub fn compile_with_options(
     }
 
     // 9. Run optional analysis passes (ownership, typestate, session types)
-    all_errors.extend(run_optional_analyses(&program, &symbols, options));
+    all_errors.extend(run_optional_analyses(
+        &program,
+        &symbols,
+        options,
+        &mut warnings,
+    ));
 
     // If there were any errors, report them all
     if let Some(combined) = CompileError::from_multiple(all_errors) {
-        return Err(combined);
+        return Err(CompileFailure {
+            error: combined,
+            warnings,
+        });
     }
 
     // 10. Lower to LIR
-    let module = lower_safe(&program, &symbols, source)?;
-
-    Ok(module)
+    match lower_safe(&program, &symbols, source, &method_calls, options) {
+        Ok(module) => Ok(CompileOutput { module, warnings }),
+        Err(error) => Err(CompileFailure { error, warnings }),
+    }
 }
 
 /// Format a compile error with rich diagnostics (colors, source snippets, suggestions).
@@ -870,6 +1498,18 @@ pub fn format_error(error: &CompileError, source: &str, filename: &str) -> Strin
         .join("\n")
 }
 
+/// Format a warning from [`CompileOutput::warnings`] for terminal display.
+pub fn format_warning(warning: &CompileError, source: &str, filename: &str) -> String {
+    diagnostics::format_compile_error(warning, source, filename)
+        .into_iter()
+        .map(|mut d| {
+            d.severity = diagnostics::Severity::Warning;
+            d.render_ansi()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +122,7 @@ pub fn extract_blocks(source: &str) -> ExtractResult {
                     });
                     has_fenced_blocks = true;
                     fence_code.clear();
+                    byte_offset += line.len() + 1;
                     continue;
                 }
             }
@@ -476,4 +477,16 @@ This is documentation only.
         assert!(result.code_blocks.is_empty());
         assert!(!result.has_fenced_blocks);
     }
+
+    #[test]
+    fn test_code_offsets_point_at_block_contents() {
+        let src =
+            "# A\n\n```lumen\ncell a() -> Int\nend\n```\n\n```lumen\ncell b() -> Int\nend\n```\n";
+        let result = extract_blocks(src);
+        assert_eq!(result.code_blocks.len(), 2);
+        for block in &result.code_blocks {
+            assert!(src[block.code_offset..].starts_with(&block.code));
+        }
+        assert_eq!(result.code_blocks[1].code_start_line, 9);
+    }
 }
@@ -0,0 +1,83 @@
+//! Constant folding behind `CompileOptions::optimize`.
+
+use lumen_compiler::compiler::lir::{Constant, LirModule, OpCode};
+use lumen_compiler::{compile_with_options, CompileOptions};
+
+fn md(source: &str) -> String {
+    format!("# test\n\n```lumen\n{}\n```\n", source.trim())
+}
+
+fn compile_src(source: &str, optimize: bool) -> LirModule {
+    let options = CompileOptions {
+        optimize,
+        ..Default::default()
+    };
+    match compile_with_options(&md(source), &options) {
+        Ok(module) => module,
+        Err(err) => panic!("failed to compile:\n{}\n--- error ---\n{}", source, err),
+    }
+}
+
+fn ops(module: &LirModule, cell: &str) -> Vec<OpCode> {
+    module
+        .cells
+        .iter()
+        .find(|c| c.name == cell)
+        .unwrap_or_else(|| panic!("no cell '{}'", cell))
+        .instructions
+        .iter()
+        .map(|i| i.op)
+        .collect()
+}
+
+fn is_arith(op: &OpCode) -> bool {
+    matches!(op, OpCode::Add | OpCode::Sub | OpCode::Mul | OpCode::Div)
+}
+
+#[test]
+fn arithmetic_on_literals_folds_to_one_constant() {
+    let module = compile_src("cell main() -> Int\n  return 2 + 3 * 4\nend", true);
+    let main_ops = ops(&module, "main");
+    assert!(!main_ops.iter().any(is_arith), "got: {:?}", main_ops);
+    assert_eq!(main_ops, vec![OpCode::LoadK, OpCode::Return]);
+    let main = &module.cells[0];
+    assert!(
+        matches!(main.constants.as_slice(), [Constant::Int(14)]),
+        "got: {:?}",
+        main.constants
+    );
+}
+
+#[test]
+fn string_concatenation_folds() {
+    let module = compile_src("cell main() -> String\n  return \"a\" + \"b\"\nend", true);
+    assert!(!ops(&module, "main").contains(&OpCode::Concat));
+    assert!(!ops(&module, "main").contains(&OpCode::Add));
+    assert!(module.cells[0]
+        .constants
+        .iter()
+        .any(|c| matches!(c, Constant::String(s) if s == "ab")));
+}
+
+#[test]
+fn operations_on_variables_are_untouched() {
+    let module = compile_src("cell inc(x: Int) -> Int\n  return x + 1\nend", true);
+    assert!(ops(&module, "inc").contains(&OpCode::Add));
+}
+
+#[test]
+fn overflowing_arithmetic_is_left_for_runtime() {
+    let module = compile_src(
+        "cell main() -> Int\n  return 9223372036854775807 + 1\nend",
+        true,
+    );
+    assert!(ops(&module, "main").contains(&OpCode::Add));
+}
+
+#[test]
+fn optimize_off_keeps_operators() {
+    let module = compile_src("cell main() -> Int\n  return 2 + 3 * 4\nend", false);
+    let main_ops = ops(&module, "main");
+    assert!(main_ops.contains(&OpCode::Add), "got: {:?}", main_ops);
+    assert!(main_ops.contains(&OpCode::Mul), "got: {:?}", main_ops);
+}
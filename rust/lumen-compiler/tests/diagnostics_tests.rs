@@ -4,9 +4,12 @@
 //! - `type_diff()`: concise expected-vs-actual formatting for type errors
 //! - `suggest_similar_names()`: Levenshtein-based name suggestions
 //! - End-to-end wiring in `format_compile_error`
+//! - `to_json()`: machine-readable diagnostics
 
 use lumen_compiler::compile;
-use lumen_compiler::diagnostics::{format_compile_error, suggest_similar_names, type_diff};
+use lumen_compiler::diagnostics::{
+    format_compile_error, suggest_similar_names, to_json, type_diff,
+};
 
 fn markdown(code: &str) -> String {
     format!("# test\n\n```lumen\n{}\n```\n", code.trim())
@@ -331,3 +334,85 @@ fn type_diff_deeply_nested() {
     let d = type_diff("result[list[Int], String]", "result[list[String], String]");
     assert!(d.contains("ok type"), "got: {}", d);
 }
+
+// ============================================================================
+// to_json — machine-readable diagnostics
+// ============================================================================
+
+#[test]
+fn json_type_mismatch_has_line_and_message() {
+    let src = markdown(
+        r#"
+cell main() -> Int
+  let s: String = 42
+  return 1
+end
+"#,
+    );
+    let err = compile(&src).expect_err("should fail with type mismatch");
+    let json = to_json(&err, &src, "test.lm.md");
+    let diags = json.as_array().expect("diagnostics array");
+    let mismatch = diags
+        .iter()
+        .find(|d| {
+            d["message"]
+                .as_str()
+                .unwrap_or("")
+                .contains("type mismatch")
+        })
+        .unwrap_or_else(|| panic!("no type mismatch in {}", json));
+    // `let s: String = 42` is line 5 of the markdown file.
+    assert_eq!(mismatch["line"], 5);
+    assert_eq!(mismatch["span"]["line"], 5);
+    assert_eq!(mismatch["severity"], "error");
+    assert_eq!(mismatch["file"], "test.lm.md");
+    assert!(mismatch["code"].as_str().unwrap().starts_with('E'));
+    assert!(!mismatch["message"].as_str().unwrap().is_empty());
+    assert!(mismatch["suggestions"].is_array());
+}
+
+#[test]
+fn json_flattens_multiple_errors() {
+    let src = markdown(
+        r#"
+cell main() -> Int
+  let s: String = 42
+  return missing_name
+end
+"#,
+    );
+    let err = compile(&src).expect_err("should fail");
+    let json = to_json(&err, &src, "test.lm.md");
+    let diags = json.as_array().unwrap();
+    assert_eq!(
+        diags.len(),
+        format_compile_error(&err, &src, "test.lm.md").len()
+    );
+    assert!(diags.len() >= 2, "got: {}", json);
+    assert!(diags.iter().all(|d| d["message"].as_str().is_some()));
+}
+
+#[test]
+fn json_span_covers_the_undefined_name() {
+    let src = markdown(
+        r#"
+cell main() -> Int
+  return missing_name
+end
+"#,
+    );
+    let err = compile(&src).expect_err("should fail");
+    let json = to_json(&err, &src, "test.lm.md");
+    let undefined = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["message"].as_str().unwrap_or("").contains("missing_name"))
+        .unwrap_or_else(|| panic!("no undefined-name error in {}", json));
+    // `  return missing_name` is line 5; the name spans columns 10..22.
+    assert_eq!(undefined["span"]["line"], 5);
+    assert_eq!(undefined["span"]["start_col"], 10);
+    assert_eq!(undefined["span"]["end_line"], 5);
+    assert_eq!(undefined["span"]["end_col"], 22);
+    assert_eq!(undefined["col"], 10);
+}
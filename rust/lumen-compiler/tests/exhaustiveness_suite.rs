@@ -206,14 +206,14 @@ end
     );
 }
 
-// ── Guards — currently treated as covering the variant ──
-// Note: guard predicates aren't tracked for exhaustiveness (would need SMT solver).
-// A guarded arm for a variant still counts as covering that variant.
+// ── Guards — a guarded arm never counts toward exhaustiveness ──
+// Guard predicates aren't analyzed (would need an SMT solver), so the guard
+// may fail at runtime and the variant still needs an unguarded arm.
 
 #[test]
-fn guard_still_covers_variant() {
-    assert_ok(
-        "guard_still_covers_variant",
+fn guarded_arm_does_not_cover_variant() {
+    assert_err(
+        "guarded_arm_does_not_cover_variant",
         r#"
 enum Value
   Small(Int)
@@ -227,6 +227,65 @@ cell classify(v: Value) -> String
   end
 end
 "#,
+        "IncompleteMatch",
+    );
+}
+
+#[test]
+fn all_guarded_arms_still_require_catchall() {
+    assert_err(
+        "all_guarded_arms_still_require_catchall",
+        r#"
+enum Value
+  Small(Int)
+  Large(Int)
+end
+
+cell classify(v: Value) -> String
+  match v
+    Small(n) when n > 0 -> return "positive small"
+    Large(n) where n > 100 -> return "huge"
+  end
+end
+"#,
+        "IncompleteMatch",
+    );
+}
+
+#[test]
+fn guarded_arms_with_unguarded_fallbacks_are_exhaustive() {
+    assert_ok(
+        "guarded_arms_with_unguarded_fallbacks_are_exhaustive",
+        r#"
+enum Value
+  Small(Int)
+  Large(Int)
+end
+
+cell classify(v: Value) -> String
+  match v
+    Small(n) when n > 0 -> return "positive small"
+    Small(_) -> return "small"
+    Large(n) -> return "large"
+  end
+end
+"#,
+    );
+}
+
+#[test]
+fn guard_must_be_bool() {
+    assert_err(
+        "guard_must_be_bool",
+        r#"
+cell classify(x: Int) -> String
+  match x
+    n when n + 1 -> return "odd"
+    _ -> return "other"
+  end
+end
+"#,
+        "Mismatch",
     );
 }
 
@@ -285,6 +285,7 @@ fn fuzz_verification_no_panic() {
         traits: Default::default(),
         impls: Default::default(),
         consts: Default::default(),
+        imported_cells: Default::default(),
     };
 
     let ops = [
@@ -596,6 +597,7 @@ fn fuzz_cell_contracts_no_panic() {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![where_clause],
             span,
             doc: None,
@@ -620,6 +622,7 @@ fn fuzz_cell_contracts_no_panic() {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![],
             span,
             doc: None,
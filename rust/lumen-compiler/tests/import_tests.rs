@@ -1,4 +1,9 @@
-use lumen_compiler::compile_with_imports;
+use lumen_compiler::compiler::resolve::ResolveError;
+use lumen_compiler::{
+    compile_with_import_sources, compile_with_imports, compile_with_imports_and_options,
+};
+use lumen_compiler::{CompileError, CompileOptions, ImportSource};
+use std::cell::RefCell;
 
 #[test]
 fn test_import_cell() {
@@ -37,7 +42,7 @@ end
         "Expected 'main' cell in output"
     );
     assert!(
-        module.cells.iter().any(|c| c.name == "square"),
+        module.cells.iter().any(|c| c.name == "mathlib::square"),
         "Expected imported 'square' cell in output"
     );
 }
@@ -116,6 +121,36 @@ end
     }
 }
 
+#[test]
+fn test_circular_import_chain_is_in_import_order() {
+    let module = |import: &str| {
+        format!(
+            "```lumen\nimport {}: f\n\ncell f() -> Int\n  return 1\nend\n```\n",
+            import
+        )
+    };
+    let main_source = "```lumen\nimport a: f\n\ncell main() -> Int\n  return f()\nend\n```\n";
+    for _ in 0..5 {
+        let err = compile_with_imports(main_source, &|name| match name {
+            "a" => Some(module("b")),
+            "b" => Some(module("c")),
+            "c" => Some(module("a")),
+            _ => None,
+        })
+        .expect_err("Expected circular import error");
+        match err {
+            CompileError::Resolve(errors) => match errors.as_slice() {
+                [ResolveError::CircularImport { module, chain }] => {
+                    assert_eq!(module, "a");
+                    assert_eq!(chain, "a -> b -> c -> a");
+                }
+                other => panic!("Expected one CircularImport error, got: {:?}", other),
+            },
+            other => panic!("Expected resolve error, got: {:?}", other),
+        }
+    }
+}
+
 #[test]
 fn test_module_not_found() {
     let main_source = r#"
@@ -176,7 +211,7 @@ end
         "Expected 'main' cell in output"
     );
     assert!(
-        module.cells.iter().any(|c| c.name == "compute"),
+        module.cells.iter().any(|c| c.name == "mathlib::compute"),
         "Expected imported 'compute' cell in output"
     );
 }
@@ -290,3 +325,418 @@ end
         );
     }
 }
+
+const VERSIONED_LIB: &str = r#"@lumen 1
+@package "mathlib" 1.4.2
+
+```lumen
+cell square(x: Int) -> Int
+  return x * x
+end
+```
+"#;
+
+fn compile_requiring(requirement: &str) -> Result<(), String> {
+    let main_source = format!(
+        r#"@requires mathlib {}
+
+```lumen
+import mathlib: square
+
+cell main() -> Int
+  return square(5)
+end
+```
+"#,
+        requirement
+    );
+    compile_with_imports(&main_source, &|module| {
+        if module == "mathlib" {
+            Some(VERSIONED_LIB.to_string())
+        } else {
+            None
+        }
+    })
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_import_version_requirement_satisfied() {
+    let result = compile_requiring("^1.2");
+    assert!(
+        result.is_ok(),
+        "Expected successful compilation, got: {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_import_version_requirement_not_satisfied() {
+    let err = compile_requiring("^2.0").expect_err("Expected version mismatch error");
+    assert!(
+        err.contains("VersionMismatch") && err.contains("1.4.2") && err.contains("^2.0"),
+        "Expected VersionMismatch naming both versions, got: {}",
+        err
+    );
+}
+
+#[test]
+fn test_module_alias_separates_same_named_types() {
+    let geo_source = r#"
+```lumen
+record Config
+  x: Int
+end
+```
+"#;
+    let net_source = r#"
+```lumen
+record Config
+  host: String
+end
+```
+"#;
+    let main_source = r#"
+```lumen
+import geo as g
+import net as n: Config
+
+cell x_of(c: g.Config) -> Int
+  return c.x
+end
+
+cell host_of(c: n.Config) -> String
+  return c.host
+end
+```
+"#;
+    let resolve = |module: &str| match module {
+        "geo" => Some(geo_source.to_string()),
+        "net" => Some(net_source.to_string()),
+        _ => None,
+    };
+    let result = compile_with_imports(main_source, &resolve);
+    assert!(
+        result.is_ok(),
+        "Expected aliased imports to resolve, got: {:?}",
+        result.err()
+    );
+
+    // Each alias refers to its own module's `Config`: `n.Config.host` is a String.
+    let wrong_type = main_source.replace("n.Config) -> String", "n.Config) -> Int");
+    let err = compile_with_imports(&wrong_type, &resolve)
+        .expect_err("n.Config.host is not an Int")
+        .to_string();
+    assert!(
+        err.contains("Mismatch { expected: \"Int\", actual: \"String\""),
+        "got: {}",
+        err
+    );
+
+    // Without the alias prefix the name is not in scope.
+    let unqualified = main_source.replace("c: g.Config", "c: Config");
+    let err = compile_with_imports(&unqualified, &resolve)
+        .expect_err("unqualified Config should not resolve")
+        .to_string();
+    assert!(err.contains("Config"), "got: {}", err);
+}
+
+#[test]
+fn test_parallel_imports_match_sequential_result() {
+    // Four independent libraries that all import a shared `base` (a diamond).
+    let lib = |name: &str| {
+        format!(
+            r#"
+```lumen
+import base: double
+
+record {name}Point
+  x: Int
+end
+
+cell {name}_value(n: Int) -> Int
+  return double(n) + 1
+end
+```
+"#
+        )
+    };
+    let base = r#"
+```lumen
+cell double(n: Int) -> Int
+  return n * 2
+end
+```
+"#;
+    let main_source = r#"
+```lumen
+import alpha: alpha_value
+import beta: beta_value
+import gamma: gamma_value
+import delta: delta_value
+
+cell main() -> Int
+  return alpha_value(1) + beta_value(2) + gamma_value(3) + delta_value(4)
+end
+```
+"#;
+    let requests = RefCell::new(Vec::new());
+    let resolve = |module: &str| {
+        requests.borrow_mut().push(module.to_string());
+        match module {
+            "base" => Some(base.to_string()),
+            "alpha" | "beta" | "gamma" | "delta" => Some(lib(module)),
+            _ => None,
+        }
+    };
+
+    let compile = |parallel_imports: bool| {
+        let options = CompileOptions {
+            parallel_imports,
+            ..Default::default()
+        };
+        let module = compile_with_imports_and_options(main_source, &resolve, &options)
+            .expect("imports should compile");
+        serde_json::to_string(&module).unwrap()
+    };
+    let sequential = compile(false);
+    // The shared import is only resolved (and compiled) once per build.
+    assert_eq!(requests.borrow().iter().filter(|m| *m == "base").count(), 1);
+    for _ in 0..5 {
+        assert_eq!(compile(true), sequential);
+    }
+}
+
+#[test]
+fn test_directory_import_merges_all_files() {
+    // `utils/` holds a raw `.lm` file and a Markdown `.lm.md` file.
+    let files = vec![
+        (
+            "strings.lm.md".to_string(),
+            "# Strings\n\n```lumen\ncell shout(s: String) -> String\n  return s + \"!\"\nend\n```\n"
+                .to_string(),
+        ),
+        (
+            "math.lm".to_string(),
+            "cell double(n: Int) -> Int\n  return n * 2\nend\n".to_string(),
+        ),
+    ];
+    let main_source = r#"
+```lumen
+import utils: double, shout
+
+cell main() -> String
+  return shout("x") + string(double(2))
+end
+```
+"#;
+    let resolve = |module: &str| match module {
+        "utils" => Some(ImportSource::Directory(files.clone())),
+        _ => None,
+    };
+    let result = compile_with_import_sources(main_source, &resolve, &CompileOptions::default());
+    let module = match result {
+        Ok(out) => out.module,
+        Err(e) => panic!("Expected directory import to compile, got: {}", e),
+    };
+    for name in ["main", "utils::double", "utils::shout"] {
+        assert!(
+            module.cells.iter().any(|c| c.name == name),
+            "Expected '{}' cell in output",
+            name
+        );
+    }
+
+    // Files are combined in file name order regardless of listing order.
+    let mut reversed = files.clone();
+    reversed.reverse();
+    assert_eq!(
+        ImportSource::Directory(files).into_source(),
+        ImportSource::Directory(reversed).into_source()
+    );
+}
+
+#[test]
+fn test_dead_code_elimination_prunes_uncalled_imports() {
+    let lib_source = r#"
+```lumen
+record Unused
+  value: Int
+end
+
+cell used(x: Int) -> Int
+  return x + 1
+end
+
+cell unused_a(x: Int) -> Unused
+  return Unused(value: x)
+end
+
+cell unused_b() -> String
+  return "never"
+end
+```
+"#;
+    let main_source = r#"
+```lumen
+import lib: used, unused_a, unused_b
+
+cell main() -> Int
+  return used(41)
+end
+```
+"#;
+    let resolve = |module: &str| match module {
+        "lib" => Some(lib_source.to_string()),
+        _ => None,
+    };
+    let compile = |eliminate_dead_code: bool| {
+        let options = CompileOptions {
+            eliminate_dead_code,
+            ..Default::default()
+        };
+        compile_with_imports_and_options(main_source, &resolve, &options)
+            .expect("imports should compile")
+    };
+    let names = |module: &lumen_compiler::compiler::lir::LirModule| {
+        let mut names: Vec<String> = module.cells.iter().map(|c| c.name.clone()).collect();
+        names.sort();
+        names
+    };
+
+    let full = compile(false);
+    assert_eq!(
+        names(&full),
+        vec!["lib::unused_a", "lib::unused_b", "lib::used", "main"]
+    );
+    assert!(full.types.iter().any(|t| t.name == "Unused"));
+
+    let pruned = compile(true);
+    assert_eq!(names(&pruned), vec!["lib::used", "main"]);
+    assert!(pruned.types.iter().all(|t| t.name != "Unused"));
+    assert!(pruned
+        .source_map
+        .iter()
+        .all(|e| e.cell == "main" || e.cell == "lib::used"));
+}
+
+const EXPORTING_LIB: &str = r#"
+```lumen
+record Secret
+  value: Int
+end
+
+cell helper(x: Int) -> Int
+  return x * 2
+end
+
+pub cell api(x: Int) -> Int
+  return helper(x) + 1
+end
+```
+"#;
+
+fn resolve_exporting_lib(module: &str) -> Option<String> {
+    match module {
+        "lib" => Some(EXPORTING_LIB.to_string()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_wildcard_import_skips_private_helper() {
+    let uses_api = r#"
+```lumen
+import lib: *
+
+cell main() -> Int
+  return api(20)
+end
+```
+"#;
+    let module = compile_with_imports(uses_api, &resolve_exporting_lib)
+        .unwrap_or_else(|e| panic!("Expected exported cell to import, got: {}", e));
+    // The helper is still compiled in, since `api` calls it.
+    assert!(module.cells.iter().any(|c| c.name == "lib::helper"));
+
+    let uses_secret = r#"
+```lumen
+import lib: *
+
+cell main(s: Secret) -> Int
+  return api(s.value)
+end
+```
+"#;
+    let err = compile_with_imports(uses_secret, &resolve_exporting_lib)
+        .expect_err("private record should not be visible through a wildcard import");
+    match err {
+        CompileError::Resolve(errors) => assert!(
+            errors.iter().any(|e| matches!(
+                e,
+                ResolveError::UndefinedType { name, .. } if name == "Secret"
+            )),
+            "Expected undefined type 'Secret', got: {:?}",
+            errors
+        ),
+        other => panic!("Expected resolve error, got: {}", other),
+    }
+}
+
+#[test]
+fn test_named_import_of_private_symbol_fails() {
+    let main_source = r#"
+```lumen
+import lib: api, helper
+
+cell main() -> Int
+  return api(helper(1))
+end
+```
+"#;
+    let err = compile_with_imports(main_source, &resolve_exporting_lib)
+        .expect_err("importing a private symbol by name should fail");
+    let errors = match err {
+        CompileError::Resolve(errors) => errors,
+        other => panic!("Expected resolve error, got: {}", other),
+    };
+    assert_eq!(errors.len(), 1, "only 'helper' is private: {:?}", errors);
+    assert_eq!(
+        errors[0].to_string(),
+        "'helper' is private to module 'lib' and cannot be imported at line 3; \
+         mark it `pub` to export it"
+    );
+}
+
+#[test]
+fn test_module_alias_call_is_typechecked() {
+    let util_source = r#"
+```lumen
+cell double(x: Int) -> Int
+  return x * 2
+end
+```
+"#;
+    let resolve = |module: &str| match module {
+        "util" => Some(util_source.to_string()),
+        _ => None,
+    };
+    let main_source = r#"
+```lumen
+import util as u
+
+cell main() -> String
+  return u.double(21)
+end
+```
+"#;
+    let err = compile_with_imports(main_source, &resolve)
+        .expect_err("u.double returns Int, not String")
+        .to_string();
+    assert!(err.contains("Mismatch"), "got: {}", err);
+
+    let fixed = main_source.replace("-> String", "-> Int");
+    let module = compile_with_imports(&fixed, &resolve).expect("aliased call should compile");
+    assert!(module.cells.iter().any(|c| c.name == "util::double"));
+}
@@ -52,7 +52,7 @@ end
     .expect("unfenced imports should compile");
 
     assert!(module.cells.iter().any(|c| c.name == "main"));
-    assert!(module.cells.iter().any(|c| c.name == "square"));
+    assert!(module.cells.iter().any(|c| c.name == "math::square"));
 }
 
 #[test]
@@ -1,5 +1,7 @@
 //! Tests for new syntax features: spaceship operator (T113), membership `in` (T115),
-//! error propagation `?` (T121), and `@must_use` attribute (T164).
+//! error propagation `?` (T121), `@must_use` attribute (T164), `@pure` cells,
+//! fixed-size arrays, effect-free `const` initializers, and method-call
+//! (UFCS) desugaring.
 
 use lumen_compiler::compile;
 
@@ -318,3 +320,316 @@ end
 "#,
     );
 }
+
+// ═══════════════════════════════════════════════════════════════════
+// @pure cells
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn pure_cell_compiles_and_is_memoizable() {
+    let md = markdown_from_code(
+        r#"
+@pure
+cell square(x: Int) -> Int
+  x * x
+end
+
+@pure
+cell sum_of_squares(a: Int, b: Int) -> Int
+  square(a) + square(b)
+end
+
+cell main() -> Int
+  sum_of_squares(3, 4)
+end
+"#,
+    );
+    let module = compile(&md).expect("pure cells should compile");
+    let memoizable = |name: &str| {
+        module
+            .cells
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| panic!("cell '{}' missing from LIR", name))
+            .memoizable
+    };
+    assert!(memoizable("square"));
+    assert!(memoizable("sum_of_squares"));
+    assert!(!memoizable("main"));
+}
+
+#[test]
+fn pure_cell_calling_tool_errors() {
+    assert_compile_error(
+        r#"
+use tool http.get as HttpGet
+grant HttpGet
+
+@pure
+cell fetch(url: String) -> String
+  string(HttpGet(url: url))
+end
+"#,
+        "performs effect 'external'; cause: tool call 'HttpGet'",
+    );
+}
+
+#[test]
+fn pure_cell_calling_impure_cell_errors() {
+    assert_compile_error(
+        r#"
+cell double(x: Int) -> Int
+  x * 2
+end
+
+@pure
+cell quadruple(x: Int) -> Int
+  double(double(x))
+end
+"#,
+        "calls 'double' which is not declared @pure",
+    );
+}
+
+#[test]
+fn pure_cell_calling_impure_cell_by_method_syntax_errors() {
+    assert_compile_error(
+        r#"
+cell shout(s: String) -> String / {emit}
+  emit(s)
+  s
+end
+
+@pure
+cell loud(s: String) -> String
+  s.shout()
+end
+"#,
+        "calls 'shout' which is not declared @pure",
+    );
+}
+
+#[test]
+fn pure_stacks_with_must_use() {
+    let md = markdown_from_code(
+        r#"
+@pure
+@must_use
+cell inc(x: Int) -> Int
+  x + 1
+end
+
+cell main() -> Int
+  inc(1)
+end
+"#,
+    );
+    let module = compile(&md).expect("stacked attributes should compile");
+    assert!(module.cells.iter().any(|c| c.name == "inc" && c.memoizable));
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Fixed-size arrays [T; N]
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn array_literal_matching_length_compiles() {
+    assert_compiles(
+        r#"
+cell main() -> Int
+  let a: [Int; 4] = [1, 2, 3, 4]
+  a[3] + a[-4]
+end
+"#,
+    );
+}
+
+#[test]
+fn array_literal_wrong_length_is_error() {
+    assert_compile_error(
+        r#"
+cell main() -> Int
+  let a: [Int; 4] = [1, 2, 3]
+  a[0]
+end
+"#,
+        "ArrayLengthMismatch",
+    );
+}
+
+#[test]
+fn array_constant_index_out_of_bounds_is_error() {
+    assert_compile_error(
+        r#"
+cell main() -> Int
+  let a: [Int; 4] = [1, 2, 3, 4]
+  a[4]
+end
+"#,
+        "IndexOutOfBounds",
+    );
+}
+
+#[test]
+fn array_return_type_checks_length() {
+    assert_compile_error(
+        r#"
+cell origin() -> [Float; 3]
+  return [0.0, 0.0]
+end
+"#,
+        "ArrayLengthMismatch",
+    );
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Effect-free const initializers
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn const_with_pure_arithmetic_compiles() {
+    assert_compiles(
+        r#"
+const SECONDS_PER_DAY: Int = 60 * 60 * 24
+
+cell main() -> Int
+  SECONDS_PER_DAY
+end
+"#,
+    );
+}
+
+#[test]
+fn const_initialized_with_print_errors() {
+    assert_compile_error(
+        r#"
+const GREETING = print("hello")
+
+cell main() -> Int
+  1
+end
+"#,
+        r#"EffectfulConstInit { name: "GREETING", effect: "emit", cause: "call to 'print'""#,
+    );
+}
+
+#[test]
+fn const_calling_effectful_tool_errors() {
+    assert_compile_error(
+        r#"
+use tool http.get as HttpGet
+bind effect http to HttpGet
+grant HttpGet
+
+const HOMEPAGE = HttpGet(url: "https://example.com")
+
+cell main() -> Int
+  1
+end
+"#,
+        r#"EffectfulConstInit { name: "HOMEPAGE", effect: "http", cause: "tool call 'HttpGet'""#,
+    );
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Method-call desugaring: value.method(args) → method(value, args)
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn method_call_desugars_to_builtin_map() {
+    assert_compiles(
+        r#"
+cell double(x: Int) -> Int
+  x * 2
+end
+
+cell main() -> list[Int]
+  [1, 2, 3].map(double)
+end
+"#,
+    );
+}
+
+#[test]
+fn method_call_typechecks_like_free_call() {
+    // `[1, 2, 3].map(double)` has the type of `map([1, 2, 3], double)`.
+    assert_compile_error(
+        r#"
+cell main() -> String
+  let s: String = [1, 2, 3].map(fn(x: Int) -> Int => x * 2)
+  s
+end
+"#,
+        r#"Mismatch { expected: "String", actual: "list[Int]""#,
+    );
+}
+
+#[test]
+fn method_call_resolves_user_cell_by_first_param() {
+    assert_compiles(
+        r#"
+cell total(xs: list[Int], start: Int) -> Int
+  let mut sum = start
+  for x in xs
+    sum = sum + x
+  end
+  sum
+end
+
+cell main() -> Int
+  let xs = [1, 2, 3]
+  xs.total(10)
+end
+"#,
+    );
+}
+
+#[test]
+fn method_call_unknown_method_is_error() {
+    assert_compile_error(
+        r#"
+cell main() -> Int
+  [1, 2, 3].frobnicate()
+end
+"#,
+        r#"UnknownMethod { method: "frobnicate", receiver: "list[Int]""#,
+    );
+}
+
+#[test]
+fn method_call_first_param_mismatch_is_error() {
+    assert_compile_error(
+        r#"
+cell shout(s: String) -> String
+  upper(s)
+end
+
+cell main() -> String
+  let n = 42
+  n.shout()
+end
+"#,
+        r#"UnknownMethod { method: "shout", receiver: "Int""#,
+    );
+}
+
+#[test]
+fn method_call_ambiguous_field_and_cell_is_error() {
+    assert_compile_error(
+        r#"
+record Handler
+  run: fn(Int) -> Int
+end
+
+cell run(h: Handler, x: Int) -> Int
+  x
+end
+
+cell main() -> Int
+  let h = Handler(run: fn(x: Int) -> Int => x + 1)
+  h.run(1)
+end
+"#,
+        r#"AmbiguousMethod { method: "run", receiver: "Handler""#,
+    );
+}
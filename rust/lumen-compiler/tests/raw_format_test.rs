@@ -67,7 +67,7 @@ end
         "Should contain 'main' cell"
     );
     assert!(
-        module.cells.iter().any(|c| c.name == "square"),
+        module.cells.iter().any(|c| c.name == "math::square"),
         "Should contain imported 'square' cell"
     );
 }
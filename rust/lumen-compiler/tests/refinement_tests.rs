@@ -61,6 +61,7 @@ fn make_cell(name: &str, params: Vec<Param>, where_clauses: Vec<Expr>, body: Vec
         is_async: false,
         is_extern: false,
         must_use: false,
+        is_pure: false,
         where_clauses,
         span: span(),
         doc: None,
@@ -96,6 +97,7 @@ fn empty_symbol_table() -> SymbolTable {
         traits: Default::default(),
         impls: Default::default(),
         consts: Default::default(),
+        imported_cells: Default::default(),
     }
 }
 
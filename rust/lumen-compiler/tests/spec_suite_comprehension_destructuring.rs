@@ -3,6 +3,7 @@
 //! Tests for:
 //! - Nested list comprehension (multiple for-clauses)
 //! - Let destructuring with type patterns
+//! - Positional record destructuring and pattern shape checks
 
 use lumen_compiler::compile;
 
@@ -17,7 +18,6 @@ fn assert_ok(id: &str, code: &str) {
     }
 }
 
-#[allow(dead_code)]
 fn assert_err(id: &str, code: &str, expect: &str) {
     let md = markdown(code);
     match compile(&md) {
@@ -310,6 +310,163 @@ end
     );
 }
 
+// ============================================================================
+// T206: Positional record destructuring and shape checks
+// ============================================================================
+
+#[test]
+fn t206_let_positional_record_destructure() {
+    assert_ok(
+        "let_positional_record",
+        r#"
+record Point
+  x: Int
+  y: Float
+end
+
+cell main() -> Float
+  let Point(x, y) = Point(x: 1, y: 2.5)
+  let total: Float = y + 1.0
+  return total
+end
+"#,
+    );
+}
+
+#[test]
+fn t206_positional_record_fields_are_typed() {
+    assert_err(
+        "positional_record_field_types",
+        r#"
+record Point
+  x: Int
+  y: Float
+end
+
+cell main() -> String
+  let Point(x, y) = Point(x: 1, y: 2.5)
+  return x
+end
+"#,
+        "mismatch",
+    );
+}
+
+#[test]
+fn t206_nested_record_in_match_arm() {
+    assert_ok(
+        "nested_record_in_match",
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+enum Shape
+  Dot(Point)
+  Segment((Point, Point))
+end
+
+cell length_hint(s: Shape) -> Int
+  match s
+    Dot(Point(x, y)) -> return x + y
+    Segment((Point(x: x1, y: y1), Point(x2, _))) -> return x2 - x1 + y1
+  end
+end
+"#,
+    );
+}
+
+#[test]
+fn t206_tuple_arity_mismatch() {
+    assert_err(
+        "tuple_arity_mismatch",
+        r#"
+cell main() -> Int
+  let (a, b, c) = (1, 2)
+  return a
+end
+"#,
+        "tuple of 2 elements",
+    );
+}
+
+#[test]
+fn t206_tuple_pattern_on_non_tuple() {
+    assert_err(
+        "tuple_pattern_on_int",
+        r#"
+cell main() -> Int
+  let n = 5
+  let (a, b) = n
+  return a
+end
+"#,
+        "expected: \"Tuple\"",
+    );
+}
+
+#[test]
+fn t206_record_pattern_field_count_mismatch() {
+    assert_err(
+        "record_field_count_mismatch",
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+cell main() -> Int
+  let Point(x, y, z) = Point(x: 1, y: 2)
+  return x
+end
+"#,
+        "Point with 2 fields",
+    );
+}
+
+#[test]
+fn t206_record_pattern_wrong_type() {
+    assert_err(
+        "record_pattern_wrong_type",
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+record Size
+  w: Int
+end
+
+cell main() -> Int
+  let Size(w) = Point(x: 1, y: 2)
+  return w
+end
+"#,
+        "expected: \"Size\", actual: \"Point\"",
+    );
+}
+
+#[test]
+fn t206_record_pattern_unknown_field() {
+    assert_err(
+        "record_pattern_unknown_field",
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+cell main() -> Int
+  let Point(x:, z:) = Point(x: 1, y: 2)
+  return x
+end
+"#,
+        "UnknownField",
+    );
+}
+
 // ============================================================================
 // Combined / regression tests
 // ============================================================================
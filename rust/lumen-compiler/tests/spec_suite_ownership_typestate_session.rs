@@ -10,8 +10,8 @@ use lumen_compiler::compiler::session::{Action, SessionType};
 use lumen_compiler::compiler::tokens::Span;
 use lumen_compiler::compiler::typestate::{Transition, TypestateDecl};
 use lumen_compiler::{
-    compile_raw_with_options, compile_with_options, CompileError, CompileOptions,
-    OwnershipCheckMode,
+    compile_raw_with_options, compile_raw_with_warnings, compile_with_options,
+    compile_with_warnings, CompileError, CompileOptions, OwnershipCheckMode,
 };
 use std::collections::HashMap;
 
@@ -98,6 +98,42 @@ fn ownership_warn_mode_allows_use_after_move() {
     );
 }
 
+#[test]
+fn ownership_warn_mode_reports_warnings() {
+    let opts = CompileOptions {
+        ownership_mode: OwnershipCheckMode::Warn,
+        ..Default::default()
+    };
+    let output = compile_raw_with_warnings(USE_AFTER_MOVE_CODE, &opts)
+        .expect("Warn mode should not block compilation");
+    assert!(output.module.cells.iter().any(|c| c.name == "main"));
+    assert_eq!(output.warnings.len(), 1, "warnings: {:?}", output.warnings);
+    match &output.warnings[0] {
+        CompileError::Ownership(errors) => assert!(!errors.is_empty()),
+        other => panic!("expected ownership warning, got {:?}", other),
+    }
+
+    let md = markdown(USE_AFTER_MOVE_CODE);
+    let output = compile_with_warnings(&md, &opts).expect("markdown compile should succeed");
+    assert!(matches!(
+        output.warnings.as_slice(),
+        [CompileError::Ownership(_)]
+    ));
+}
+
+#[test]
+fn ownership_warnings_empty_for_clean_code_and_off_mode() {
+    let output = compile_raw_with_warnings(CLEAN_CODE, &CompileOptions::default()).unwrap();
+    assert!(output.warnings.is_empty());
+
+    let opts = CompileOptions {
+        ownership_mode: OwnershipCheckMode::Off,
+        ..Default::default()
+    };
+    let output = compile_raw_with_warnings(USE_AFTER_MOVE_CODE, &opts).unwrap();
+    assert!(output.warnings.is_empty());
+}
+
 #[test]
 fn ownership_off_mode_skips_analysis() {
     let opts = CompileOptions {
@@ -48,7 +48,10 @@ end
         "expected main cell in compiled module"
     );
     assert!(
-        module.cells.iter().any(|c| c.name == "assert_not_contains"),
+        module
+            .cells
+            .iter()
+            .any(|c| c.name == "std.testing::assert_not_contains"),
         "expected imported std.testing helper cells in compiled module"
     );
 }
@@ -77,7 +80,10 @@ end
         "expected main cell in compiled module"
     );
     assert!(
-        module.cells.iter().any(|c| c.name == "summarize_tests"),
+        module
+            .cells
+            .iter()
+            .any(|c| c.name == "std.testing::summarize_tests"),
         "expected imported summary helpers in compiled module"
     );
 }
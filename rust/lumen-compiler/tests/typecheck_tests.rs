@@ -4,6 +4,13 @@
 //! to ensure the typechecker correctly enforces Lumen's type system.
 
 use lumen_compiler::compile;
+use lumen_compiler::compiler::ast::TypeExpr;
+use lumen_compiler::compiler::lexer::Lexer;
+use lumen_compiler::compiler::parser::Parser;
+use lumen_compiler::compiler::resolve::resolve;
+use lumen_compiler::compiler::typecheck::infer_return_types;
+use lumen_compiler::compiler::typecheck::TypeError;
+use lumen_compiler::{compile_with_warnings, CompileError, CompileOptions};
 
 fn markdown_from_code(source: &str) -> String {
     format!("# typecheck-test\n\n```lumen\n{}\n```\n", source.trim())
@@ -39,6 +46,21 @@ fn assert_compiles(source: &str) {
     }
 }
 
+/// The type-checker warnings from compiling `source`, which must succeed.
+fn type_warnings(source: &str) -> Vec<TypeError> {
+    let md = markdown_from_code(source);
+    let output = compile_with_warnings(&md, &CompileOptions::default())
+        .unwrap_or_else(|err| panic!("expected source to compile, got:\n{}", err));
+    output
+        .warnings
+        .into_iter()
+        .flat_map(|warning| match warning {
+            CompileError::Type(warnings) => warnings,
+            _ => vec![],
+        })
+        .collect()
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // Type mismatch detection
 // ═══════════════════════════════════════════════════════════════════
@@ -545,6 +567,114 @@ end
 //     );
 // }
 
+/// Return type recorded in the symbol table for `cell`.
+fn inferred_return_type(source: &str, cell: &str) -> Option<TypeExpr> {
+    let mut lexer = Lexer::new(source.trim(), 1, 0);
+    let tokens = lexer.tokenize().expect("lex failed");
+    let program = Parser::new(tokens)
+        .parse_program(vec![])
+        .expect("parse failed");
+    let mut symbols = resolve(&program).expect("resolve failed");
+    infer_return_types(&program, &mut symbols);
+    symbols.cells[cell].return_type.clone()
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Return type inference
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn typecheck_infers_int_return_type() {
+    let source = r#"
+cell double(x: Int)
+  if x < 0
+    return 0
+  end
+  return x * 2
+end
+
+cell main() -> Int
+  return double(21)
+end
+"#;
+    assert_compiles(source);
+    assert!(matches!(
+        inferred_return_type(source, "double"),
+        Some(TypeExpr::Named(ref name, _)) if name == "Int"
+    ));
+
+    // Callers see the inferred type.
+    assert_type_error(
+        r#"
+cell double(x: Int)
+  return x * 2
+end
+
+cell main() -> String
+  return double(21)
+end
+"#,
+        "Mismatch { expected: \"String\", actual: \"Int\"",
+    );
+}
+
+#[test]
+fn typecheck_conflicting_inferred_returns_error() {
+    assert_type_error(
+        r#"
+cell describe(x: Int)
+  if x > 0
+    return 1
+  end
+  return "none"
+end
+"#,
+        "Mismatch { expected: \"Int\", actual: \"String\", line: 8 }",
+    );
+}
+
+#[test]
+fn typecheck_infers_return_type_from_tail_expression() {
+    // `quadruple` is checked before the cell it calls.
+    let source = r#"
+cell quadruple(x: Int)
+  double(double(x))
+end
+
+cell double(x: Int)
+  x + x
+end
+
+cell main() -> Int
+  let y: Int = quadruple(4)
+  return y
+end
+"#;
+    assert_compiles(source);
+    assert!(matches!(
+        inferred_return_type(source, "double"),
+        Some(TypeExpr::Named(ref name, _)) if name == "Int"
+    ));
+}
+
+#[test]
+fn typecheck_cell_without_return_infers_null() {
+    let source = r#"
+cell log_value(x: Int)
+  print(x)
+end
+
+cell main() -> Null
+  return log_value(1)
+end
+"#;
+    assert_compiles(source);
+    assert!(matches!(
+        inferred_return_type(source, "log_value"),
+        Some(TypeExpr::Null(_))
+    ));
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // Nested record access types
 // ═══════════════════════════════════════════════════════════════════
@@ -576,6 +706,136 @@ end
     );
 }
 
+// ═══════════════════════════════════════════════════════════════════
+// Record construction labels and trailing commas
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn typecheck_trailing_commas_accepted() {
+    assert_compiles(
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+cell add(a: Int, b: Int) -> Int
+  return a + b
+end
+
+cell main() -> Int
+  let p = Point(x: 1, y: 2,)
+  let xs = [p.x, p.y,]
+  let m = {"a": 1, "b": 2,}
+  return add(xs[0], m["b"],)
+end
+"#,
+    );
+}
+
+#[test]
+fn typecheck_record_duplicate_field_label() {
+    assert_type_error(
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+cell main() -> Point
+  return Point(x: 1, x: 2, y: 3)
+end
+"#,
+        "DuplicateField { field: \"x\", ty: \"Point\"",
+    );
+}
+
+#[test]
+fn typecheck_record_missing_required_field() {
+    assert_type_error(
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+cell main() -> Point
+  return Point(x: 1)
+end
+"#,
+        "MissingField { field: \"y\", ty: \"Point\"",
+    );
+}
+
+#[test]
+fn typecheck_record_label_errors_carry_spans() {
+    let source = "record Point\n  x: Int\n  y: Int\nend\n\ncell main() -> Point\n  return Point(x: 1, x: 2)\nend";
+    let errors = match compile(&markdown_from_code(source)) {
+        Err(CompileError::Type(errors)) => errors,
+        Err(CompileError::Multiple(all)) => all
+            .into_iter()
+            .flat_map(|e| match e {
+                CompileError::Type(errors) => errors,
+                _ => vec![],
+            })
+            .collect(),
+        other => panic!("expected type errors, got {:?}", other),
+    };
+    // Line 10 of the markdown; `return Point(x: 1, x: 2)` starts at column 3.
+    let duplicate = errors
+        .iter()
+        .find_map(|e| match e {
+            TypeError::DuplicateField { span, .. } => Some(*span),
+            _ => None,
+        })
+        .expect("duplicate field");
+    assert_eq!((duplicate.line, duplicate.col), (10, 22));
+    assert_eq!(duplicate.end - duplicate.start, 1);
+    let missing = errors
+        .iter()
+        .find_map(|e| match e {
+            TypeError::MissingField { span, .. } => Some(*span),
+            _ => None,
+        })
+        .expect("missing field");
+    assert_eq!((missing.line, missing.col), (10, 10));
+    assert_eq!(missing.end - missing.start, "Point(x: 1, x: 2)".len());
+}
+
+#[test]
+fn typecheck_record_defaulted_and_optional_fields_may_be_omitted() {
+    assert_compiles(
+        r#"
+record Config
+  name: String
+  retries: Int = 3
+  label: String?
+end
+
+cell main() -> Config
+  return Config(name: "svc")
+end
+"#,
+    );
+}
+
+#[test]
+fn typecheck_record_misspelled_field_suggests_correct_one() {
+    assert_type_error(
+        r#"
+record Point
+  x: Int
+  yy: Int
+end
+
+cell main() -> Point
+  return Point(x: 1, yz: 2)
+end
+"#,
+        "UnknownField { field: \"yz\", ty: \"Point\", line: 10, suggestions: [\"yy\"] }]",
+    );
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // Generics and trait conformance baseline
 // ═══════════════════════════════════════════════════════════════════
@@ -738,3 +998,102 @@ end
 //         "mismatch",
 //     );
 // }
+
+// ═══════════════════════════════════════════════════════════════════
+// Warnings
+// ═══════════════════════════════════════════════════════════════════
+
+#[test]
+fn typecheck_warns_on_recursion_without_base_case() {
+    let warnings = type_warnings(
+        r#"
+cell countdown(n: Int) -> Int
+  print(n)
+  return countdown(n - 1)
+end
+"#,
+    );
+    assert!(
+        matches!(
+            warnings.as_slice(),
+            [TypeError::UnboundedRecursion { name, line: 4 }] if name == "countdown"
+        ),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn typecheck_recursion_with_base_case_is_silent() {
+    let warnings = type_warnings(
+        r#"
+cell factorial(n: Int) -> Int
+  if n <= 1
+    return 1
+  end
+  n * factorial(n - 1)
+end
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn typecheck_small_int_widened_to_float_is_silent() {
+    let warnings = type_warnings(
+        r#"
+cell scale(x: Float) -> Float
+  x * 2.0
+end
+
+cell main() -> Float
+  let a: Float = 42
+  scale(1024) + a + 3
+end
+"#,
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn typecheck_warns_on_large_int_widened_to_float() {
+    let warnings = type_warnings(
+        r#"
+const BIG = 9007199254740993
+
+record Point
+  x: Float
+end
+
+cell scale(x: Float) -> Float
+  x * 2.0
+end
+
+cell main() -> Float
+  let a: Float = 9007199254740993
+  let b = scale(BIG)
+  let c = 0.5 + -9007199254740993
+  let p = Point(x: BIG)
+  return -BIG
+end
+"#,
+    );
+    let widened: Vec<(String, usize)> = warnings
+        .iter()
+        .filter_map(|w| match w {
+            TypeError::PrecisionLoss { value, line } => Some((value.clone(), *line)),
+            _ => None,
+        })
+        .collect();
+    let big = "9007199254740993".to_string();
+    assert_eq!(
+        widened,
+        vec![
+            (big.clone(), 15),
+            (big.clone(), 16),
+            (format!("-{}", big), 17),
+            (big.clone(), 18),
+            (format!("-{}", big), 19),
+        ]
+    );
+}
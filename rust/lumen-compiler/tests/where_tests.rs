@@ -151,6 +151,7 @@ fn cell_where_clause_nonzero_divisor_via_ast() {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![Expr::BinOp(
                 Box::new(Expr::Ident("b".to_string(), span)),
                 BinOp::NotEq,
@@ -201,6 +202,7 @@ fn cell_where_clause_positive_param_via_ast() {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![Expr::BinOp(
                 Box::new(Expr::Ident("n".to_string(), span)),
                 BinOp::GtEq,
@@ -267,6 +269,7 @@ fn cell_where_clause_multiple_via_ast() {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![
                 Expr::BinOp(
                     Box::new(Expr::Ident("lo".to_string(), span)),
@@ -406,6 +409,7 @@ fn collect_cell_where_clause_via_ast() {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![Expr::BinOp(
                 Box::new(Expr::Ident("b".to_string(), span)),
                 BinOp::NotEq,
@@ -484,6 +488,7 @@ fn verify_record_field_constraint_always_true() {
         traits: Default::default(),
         impls: Default::default(),
         consts: Default::default(),
+        imported_cells: Default::default(),
     };
 
     let results = verify(&program, &symbols);
@@ -651,6 +656,7 @@ fn cell_where_clause_lowers_to_constraint() {
             is_async: false,
             is_extern: false,
             must_use: false,
+            is_pure: false,
             where_clauses: vec![Expr::BinOp(
                 Box::new(Expr::Ident("b".to_string(), span)),
                 BinOp::NotEq,
@@ -1,13 +1,50 @@
 //! Context-aware code completion
 
-use lsp_types::{CompletionItem, CompletionItemKind, CompletionList, CompletionParams};
-use lumen_compiler::compiler::ast::{Item, Program};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionList, CompletionParams, Position};
+use lumen_compiler::compiler::ast::{CellDef, Item, Program, Stmt, TypeExpr};
+use lumen_compiler::compiler::tokens::Span;
+use lumen_compiler::compiler::typecheck::Type;
+use std::collections::HashMap;
 
 pub fn build_completion(
-    _params: CompletionParams,
-    _text: &str,
+    params: CompletionParams,
+    text: &str,
     program: Option<&Program>,
 ) -> CompletionList {
+    let position = params.text_document_position.position;
+    let prefix = line_prefix(text, position);
+
+    if let Some(prog) = program {
+        // `receiver.` — members of whatever the receiver names or holds
+        if let Some(chain) = member_receiver(&prefix) {
+            return CompletionList {
+                is_incomplete: false,
+                items: member_completions(prog, &chain, position.line),
+            };
+        }
+
+        // `cell f() -> T / {` — declared effects
+        if let Some(listed) = effect_row_context(&prefix) {
+            return CompletionList {
+                is_incomplete: false,
+                items: effect_completions(prog, &listed),
+            };
+        }
+    }
+
+    // A `/` outside an effect row is division; don't pop up the full list
+    let triggered_by_slash = params
+        .context
+        .as_ref()
+        .and_then(|context| context.trigger_character.as_deref())
+        == Some("/");
+    if triggered_by_slash {
+        return CompletionList {
+            is_incomplete: false,
+            items: Vec::new(),
+        };
+    }
+
     let mut items = Vec::new();
 
     // Always add keywords
@@ -30,6 +67,285 @@ pub fn build_completion(
     }
 }
 
+/// The text of the cursor's line up to the cursor.
+fn line_prefix(text: &str, position: Position) -> String {
+    let line = text.lines().nth(position.line as usize).unwrap_or("");
+    let mut utf16 = 0;
+    line.chars()
+        .take_while(|c| {
+            utf16 += c.len_utf16() as u32;
+            utf16 <= position.character
+        })
+        .collect()
+}
+
+fn is_identifier(segment: &str) -> bool {
+    segment
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// If the cursor follows `a.b.` (plus any partially typed member), the
+/// receiver path `["a", "b"]`.
+fn member_receiver(prefix: &str) -> Option<Vec<String>> {
+    let before_partial = prefix.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+    let receiver = before_partial.strip_suffix('.')?;
+    let start = receiver
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let chain: Vec<String> = receiver[start..].split('.').map(str::to_string).collect();
+    chain
+        .iter()
+        .all(|segment| is_identifier(segment))
+        .then_some(chain)
+}
+
+/// If the cursor is inside the effect row of a cell signature or function
+/// type (after `/`), the effects already listed there.
+fn effect_row_context(prefix: &str) -> Option<Vec<String>> {
+    let (signature, row) = prefix.rsplit_once('/')?;
+    let trimmed = signature.trim_start();
+    let is_signature = trimmed.contains("fn(")
+        || trimmed
+            .split_whitespace()
+            .take_while(|word| matches!(*word, "pub" | "async" | "extern" | "cell"))
+            .any(|word| word == "cell");
+    if !is_signature {
+        return None;
+    }
+    let row = row.trim_start();
+    let listed = row.strip_prefix('{').unwrap_or(row);
+    if !listed
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == ',' || c.is_whitespace())
+    {
+        return None;
+    }
+    Some(
+        listed
+            .split(',')
+            .map(|effect| effect.trim().to_string())
+            .filter(|effect| !effect.is_empty())
+            .collect(),
+    )
+}
+
+fn effect_completions(program: &Program, listed: &[String]) -> Vec<CompletionItem> {
+    program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Effect(effect) if !listed.contains(&effect.name) => Some(CompletionItem {
+                label: effect.name.clone(),
+                kind: Some(CompletionItemKind::INTERFACE),
+                detail: Some(format!("effect {}", effect.name)),
+                ..Default::default()
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn member_completions(program: &Program, chain: &[String], line: u32) -> Vec<CompletionItem> {
+    if let [name] = chain {
+        if let Some(items) = named_members(program, name) {
+            return items;
+        }
+    }
+
+    let Some(cell) = enclosing_cell(program, line) else {
+        return Vec::new();
+    };
+    let Some(mut type_name) = binding_type(program, cell, &chain[0], line) else {
+        return Vec::new();
+    };
+    for field in &chain[1..] {
+        let Some(next) = record_fields(program, &type_name)
+            .and_then(|fields| fields.iter().find(|f| &f.name == field))
+            .and_then(|f| type_name_of(&f.ty))
+        else {
+            return Vec::new();
+        };
+        type_name = next;
+    }
+
+    record_fields(program, &type_name)
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|field| CompletionItem {
+                    label: field.name.clone(),
+                    kind: Some(CompletionItemKind::FIELD),
+                    detail: Some(type_expr_to_string(&field.ty)),
+                    ..Default::default()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Members of a name that is itself a declaration: an enum's variants and
+/// methods, an effect's operations, or the operations of effects bound to a
+/// tool alias.
+fn named_members(program: &Program, name: &str) -> Option<Vec<CompletionItem>> {
+    let operation = |op: &CellDef| CompletionItem {
+        label: op.name.clone(),
+        kind: Some(CompletionItemKind::METHOD),
+        detail: Some(cell_signature(op)),
+        ..Default::default()
+    };
+    for item in &program.items {
+        match item {
+            Item::Enum(enum_def) if enum_def.name == name => {
+                let variants = enum_def.variants.iter().map(|variant| CompletionItem {
+                    label: variant.name.clone(),
+                    kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    detail: Some(format!("variant of {}", enum_def.name)),
+                    ..Default::default()
+                });
+                return Some(
+                    variants
+                        .chain(enum_def.methods.iter().map(operation))
+                        .collect(),
+                );
+            }
+            Item::Effect(effect) if effect.name == name => {
+                return Some(effect.operations.iter().map(operation).collect());
+            }
+            Item::UseTool(tool) if tool.alias == name => {
+                let bound: Vec<&str> = program
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        Item::EffectBind(bind) if bind.tool_alias == name => {
+                            Some(bind.effect_path.as_str())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let methods = program.items.iter().filter_map(|item| match item {
+                    Item::Effect(effect) if bound.contains(&effect.name.as_str()) => {
+                        Some(effect.operations.iter().map(operation))
+                    }
+                    _ => None,
+                });
+                return Some(methods.flatten().collect());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The last top-level cell starting at or before the zero-based `line`.
+fn enclosing_cell(program: &Program, line: u32) -> Option<&CellDef> {
+    program
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Cell(cell) if cell.span.line.saturating_sub(1) as u32 <= line => Some(cell),
+            _ => None,
+        })
+        .max_by_key(|cell| cell.span.line)
+}
+
+/// The record type of a parameter, or of the latest `let`/`for` binding of
+/// `name` before the zero-based `line` as the type checker infers it.
+fn binding_type(program: &Program, cell: &CellDef, name: &str, line: u32) -> Option<String> {
+    let let_types = crate::inlay_hints::infer_let_types(program);
+    let mut found = cell
+        .params
+        .iter()
+        .find(|p| p.name == name)
+        .and_then(|p| type_name_of(&p.ty));
+    collect_binding_type(&let_types, &cell.body, name, line, &mut found);
+    found
+}
+
+fn collect_binding_type(
+    let_types: &HashMap<Span, Type>,
+    body: &[Stmt],
+    name: &str,
+    line: u32,
+    found: &mut Option<String>,
+) {
+    let record_name = |span: &Span| match let_types.get(span) {
+        Some(Type::Record(name) | Type::TypeRef(name, _)) => Some(name.clone()),
+        _ => None,
+    };
+    for stmt in body {
+        if stmt.span().line.saturating_sub(1) as u32 > line {
+            return;
+        }
+        match stmt {
+            Stmt::Let(let_stmt) if let_stmt.name == name => {
+                *found = record_name(&let_stmt.span);
+            }
+            Stmt::If(if_stmt) => {
+                collect_binding_type(let_types, &if_stmt.then_body, name, line, found);
+                if let Some(else_body) = &if_stmt.else_body {
+                    collect_binding_type(let_types, else_body, name, line, found);
+                }
+            }
+            Stmt::For(for_stmt) => {
+                if for_stmt.var == name {
+                    *found = record_name(&for_stmt.span);
+                }
+                collect_binding_type(let_types, &for_stmt.body, name, line, found);
+            }
+            Stmt::While(while_stmt) => {
+                collect_binding_type(let_types, &while_stmt.body, name, line, found);
+            }
+            Stmt::Loop(loop_stmt) => {
+                collect_binding_type(let_types, &loop_stmt.body, name, line, found);
+            }
+            Stmt::Match(match_stmt) => {
+                for arm in &match_stmt.arms {
+                    collect_binding_type(let_types, &arm.body, name, line, found);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn type_name_of(ty: &TypeExpr) -> Option<String> {
+    match ty {
+        TypeExpr::Named(name, _) | TypeExpr::Generic(name, _, _) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+fn record_fields<'a>(
+    program: &'a Program,
+    type_name: &str,
+) -> Option<&'a [lumen_compiler::compiler::ast::FieldDef]> {
+    program.items.iter().find_map(|item| match item {
+        Item::Record(record) if record.name == type_name => Some(record.fields.as_slice()),
+        _ => None,
+    })
+}
+
+fn cell_signature(cell: &CellDef) -> String {
+    let params_str = cell
+        .params
+        .iter()
+        .map(|p| format!("{}: {}", p.name, type_expr_to_string(&p.ty)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let return_str = cell
+        .return_type
+        .as_ref()
+        .map(|t| format!(" -> {}", type_expr_to_string(t)))
+        .unwrap_or_default();
+
+    format!("cell {}({}){}", cell.name, params_str, return_str)
+}
+
 fn add_keywords(items: &mut Vec<CompletionItem>) {
     let keywords = vec![
         "cell",
@@ -243,25 +559,10 @@ fn add_program_symbols(program: &Program, items: &mut Vec<CompletionItem>) {
     for item in &program.items {
         match item {
             Item::Cell(cell) => {
-                let params_str = cell
-                    .params
-                    .iter()
-                    .map(|p| format!("{}: {}", p.name, type_expr_to_string(&p.ty)))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-
-                let return_str = cell
-                    .return_type
-                    .as_ref()
-                    .map(|t| format!(" -> {}", type_expr_to_string(t)))
-                    .unwrap_or_default();
-
-                let signature = format!("cell {}({}){}", cell.name, params_str, return_str);
-
                 items.push(CompletionItem {
                     label: cell.name.clone(),
                     kind: Some(CompletionItemKind::FUNCTION),
-                    detail: Some(signature),
+                    detail: Some(cell_signature(cell)),
                     ..Default::default()
                 });
             }
@@ -320,9 +621,7 @@ fn add_program_symbols(program: &Program, items: &mut Vec<CompletionItem>) {
     }
 }
 
-fn type_expr_to_string(ty: &lumen_compiler::compiler::ast::TypeExpr) -> String {
-    use lumen_compiler::compiler::ast::TypeExpr;
-
+fn type_expr_to_string(ty: &TypeExpr) -> String {
     match ty {
         TypeExpr::Named(name, _) => name.clone(),
         TypeExpr::List(inner, _) => format!("list[{}]", type_expr_to_string(inner)),
@@ -351,6 +650,7 @@ fn type_expr_to_string(ty: &lumen_compiler::compiler::ast::TypeExpr) -> String {
             format!("({})", inner)
         }
         TypeExpr::Set(inner, _) => format!("set[{}]", type_expr_to_string(inner)),
+        TypeExpr::Array(inner, len, _) => format!("[{}; {}]", type_expr_to_string(inner), len),
         TypeExpr::Fn(params, ret, effects, _) => {
             let params_str = params
                 .iter()
@@ -375,3 +675,136 @@ fn type_expr_to_string(ty: &lumen_compiler::compiler::ast::TypeExpr) -> String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{TextDocumentIdentifier, TextDocumentPositionParams};
+
+    const PROGRAM: &str = r#"record Point
+  x: Int
+  y: Int
+end
+
+record Segment
+  start: Point
+  stop: Point
+end
+
+enum Shape
+  Circle
+  Square
+end
+
+effect Console
+  cell log(message: String) -> Null
+end
+
+effect Clock
+  cell now() -> Int
+end
+
+cell length(seg: Segment) -> Int
+  let p = Point(x: 1, y: 2)
+  return p.x
+end
+
+cell first_start(segs: list[Segment]) -> Int
+  let first = segs[0]
+  let a = first.start
+  for s in segs
+    return s.stop.x
+  end
+  return a.x
+end
+"#;
+
+    fn complete(text: &str, line: u32, character: u32) -> Vec<String> {
+        // Completion runs against the last successful parse while the
+        // current line is still being typed.
+        let (program, _) = crate::parse_for_features(PROGRAM, false);
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: "file:///test.lm".parse().unwrap(),
+                },
+                position: Position::new(line, character),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        };
+        build_completion(params, text, program.as_ref())
+            .items
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+    }
+
+    fn with_line(line: u32, replacement: &str) -> String {
+        PROGRAM
+            .lines()
+            .enumerate()
+            .map(|(i, l)| if i as u32 == line { replacement } else { l })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn record_fields_after_dot_on_local() {
+        let text = with_line(24, "  return p.");
+        assert_eq!(complete(&text, 24, 11), vec!["x", "y"]);
+    }
+
+    #[test]
+    fn record_fields_after_dot_on_parameter_chain() {
+        let text = with_line(24, "  return seg.start.");
+        assert_eq!(complete(&text, 24, 19), vec!["x", "y"]);
+        let text = with_line(24, "  return seg.st");
+        assert_eq!(complete(&text, 24, 15), vec!["start", "stop"]);
+    }
+
+    #[test]
+    fn record_fields_after_dot_use_inferred_binding_types() {
+        // `let a = first.start` where `first = segs[0]`
+        let text = with_line(33, "  return a.");
+        assert_eq!(complete(&text, 33, 11), vec!["x", "y"]);
+        // `for s in segs`
+        let text = with_line(31, "    return s.");
+        assert_eq!(complete(&text, 31, 13), vec!["start", "stop"]);
+    }
+
+    #[test]
+    fn enum_variants_and_effect_operations_after_dot() {
+        let text = with_line(24, "  return Shape.");
+        assert_eq!(complete(&text, 24, 15), vec!["Circle", "Square"]);
+        let text = with_line(24, "  Console.");
+        assert_eq!(complete(&text, 24, 10), vec!["log"]);
+    }
+
+    #[test]
+    fn unknown_receiver_offers_no_keywords() {
+        let text = with_line(24, "  return missing.");
+        assert!(complete(&text, 24, 17).is_empty());
+    }
+
+    #[test]
+    fn effects_in_cell_signature_effect_row() {
+        let text = with_line(22, "cell length(seg: Segment) -> Int / {");
+        assert_eq!(complete(&text, 22, 36), vec!["Console", "Clock"]);
+        let text = with_line(22, "cell length(seg: Segment) -> Int / {Console, C");
+        assert_eq!(complete(&text, 22, 46), vec!["Clock"]);
+    }
+
+    #[test]
+    fn other_positions_fall_back_to_the_full_list() {
+        let text = with_line(24, "  return ");
+        let labels = complete(&text, 24, 9);
+        assert!(labels.contains(&"cell".to_string()));
+        assert!(labels.contains(&"print".to_string()));
+        assert!(labels.contains(&"length".to_string()));
+        // Division is not an effect row
+        let text = with_line(24, "  return 4 / ");
+        assert!(complete(&text, 24, 13).contains(&"let".to_string()));
+    }
+}
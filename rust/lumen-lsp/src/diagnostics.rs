@@ -2,20 +2,27 @@
 
 use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 use lumen_compiler::compiler::constraints::ConstraintError;
+use lumen_compiler::compiler::error_codes;
 use lumen_compiler::compiler::lexer::LexError;
 use lumen_compiler::compiler::ownership::OwnershipError;
 use lumen_compiler::compiler::parser::ParseError;
 use lumen_compiler::compiler::resolve::ResolveError;
+use lumen_compiler::compiler::session::SessionError;
+use lumen_compiler::compiler::tokens::Span;
 use lumen_compiler::compiler::typecheck::TypeError;
-use lumen_compiler::CompileError;
+use lumen_compiler::compiler::typestate::TypestateError;
+use lumen_compiler::{CompileError, CompileOptions, OwnershipCheckMode};
 
 /// Convert a compile error into LSP diagnostics
-pub fn compile_error_to_diagnostics(error: &CompileError, _source: &str) -> Vec<Diagnostic> {
+pub fn compile_error_to_diagnostics(error: &CompileError, source: &str) -> Vec<Diagnostic> {
     match error {
         CompileError::Lex(e) => vec![lex_error_to_diagnostic(e)],
         CompileError::Parse(errors) => errors.iter().map(parse_error_to_diagnostic).collect(),
         CompileError::Resolve(errors) => errors.iter().map(resolve_error_to_diagnostic).collect(),
-        CompileError::Type(errors) => errors.iter().map(type_error_to_diagnostic).collect(),
+        CompileError::Type(errors) => errors
+            .iter()
+            .map(|e| type_error_to_diagnostic(e, source))
+            .collect(),
         CompileError::Constraint(errors) => {
             errors.iter().map(constraint_error_to_diagnostic).collect()
         }
@@ -24,35 +31,140 @@ pub fn compile_error_to_diagnostics(error: &CompileError, _source: &str) -> Vec<
         }
         CompileError::Multiple(errors) => errors
             .iter()
-            .flat_map(|e| compile_error_to_diagnostics(e, _source))
+            .flat_map(|e| compile_error_to_diagnostics(e, source))
             .collect(),
         CompileError::Lower(msg) => vec![Diagnostic {
-            range: lsp_types::Range::default(),
-            severity: Some(lsp_types::DiagnosticSeverity::ERROR),
+            range: Range::default(),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(lsp_types::NumberOrString::String("E0500".to_string())),
+            source: Some("lumen".to_string()),
             message: msg.clone(),
             ..Default::default()
         }],
         CompileError::Typestate(errors) => errors
             .iter()
-            .map(|e| Diagnostic {
-                range: lsp_types::Range::default(),
-                severity: Some(lsp_types::DiagnosticSeverity::WARNING),
-                message: format!("Typestate: {:?}", e),
-                ..Default::default()
-            })
+            .map(|e| typestate_error_to_diagnostic(e, source))
             .collect(),
         CompileError::Session(errors) => errors
             .iter()
-            .map(|e| Diagnostic {
-                range: lsp_types::Range::default(),
-                severity: Some(lsp_types::DiagnosticSeverity::WARNING),
-                message: format!("Session type: {:?}", e),
-                ..Default::default()
-            })
+            .map(|e| session_error_to_diagnostic(e, source))
             .collect(),
     }
 }
 
+/// Compile a document and convert the outcome to diagnostics: hard errors
+/// plus the findings of analyses running in `Warn` mode (ownership by
+/// default) as warnings, which are reported whether or not compilation
+/// succeeds.
+pub fn diagnose(text: &str, is_markdown: bool) -> Vec<Diagnostic> {
+    let options = CompileOptions {
+        ownership_mode: OwnershipCheckMode::Warn,
+        ..Default::default()
+    };
+    let result = if is_markdown {
+        lumen_compiler::compile_with_diagnostics(text, &options)
+    } else {
+        lumen_compiler::compile_raw_with_diagnostics(text, &options)
+    };
+    let (mut diagnostics, warnings) = match result {
+        Ok(output) => (Vec::new(), output.warnings),
+        Err(failure) => (
+            compile_error_to_diagnostics(&failure.error, text),
+            failure.warnings,
+        ),
+    };
+    diagnostics.extend(
+        warnings
+            .iter()
+            .flat_map(|warning| compile_error_to_diagnostics(warning, text))
+            .map(|diagnostic| Diagnostic {
+                severity: Some(DiagnosticSeverity::WARNING),
+                ..diagnostic
+            }),
+    );
+    diagnostics
+}
+
+/// Range starting at a span and covering its source text on that line, or
+/// the rest of the line when the span is empty. Spans count characters and
+/// bytes; LSP positions count UTF-16 code units, so both ends are measured
+/// against the line in `source` (falling back to the raw counts when the
+/// line isn't there).
+fn span_range(span: &Span, source: &str) -> Range {
+    let line = span.line.saturating_sub(1) as u32;
+    let len = span.end.saturating_sub(span.start);
+    let (start, end) = match source.lines().nth(line as usize) {
+        Some(text) => {
+            let mut chars = text.chars().skip(span.col.saturating_sub(1));
+            let start: usize = text
+                .chars()
+                .take(span.col.saturating_sub(1))
+                .map(char::len_utf16)
+                .sum();
+            let mut covered = 0;
+            let mut width = 0;
+            while covered < len {
+                let Some(ch) = chars.next() else { break };
+                covered += ch.len_utf8();
+                width += ch.len_utf16();
+            }
+            (start as u32, start as u32 + width as u32)
+        }
+        None => {
+            let start = span.col.saturating_sub(1) as u32;
+            (start, start.saturating_add(len as u32))
+        }
+    };
+    let end = if len > 0 { end } else { u32::MAX };
+    Range {
+        start: Position {
+            line,
+            character: start,
+        },
+        end: Position {
+            line,
+            character: end,
+        },
+    }
+}
+
+fn typestate_error_to_diagnostic(error: &TypestateError, source: &str) -> Diagnostic {
+    let span = match error {
+        TypestateError::InvalidTransition { span, .. }
+        | TypestateError::UseInWrongState { span, .. }
+        | TypestateError::UninitializedTypestate { span, .. }
+        | TypestateError::UndeclaredTypestate { span, .. }
+        | TypestateError::BranchStateMismatch { span, .. } => span,
+    };
+    Diagnostic {
+        range: span_range(span, source),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(lsp_types::NumberOrString::String("E0600".to_string())),
+        source: Some("lumen".to_string()),
+        message: error.to_string(),
+        ..Default::default()
+    }
+}
+
+fn session_error_to_diagnostic(error: &SessionError, source: &str) -> Diagnostic {
+    let span = match error {
+        SessionError::UnexpectedMessage { span, .. }
+        | SessionError::SessionNotComplete { span, .. }
+        | SessionError::ProtocolViolation { span, .. }
+        | SessionError::WrongActionKind { span, .. }
+        | SessionError::UnknownBranch { span, .. }
+        | SessionError::DualityViolation { span, .. } => span,
+    };
+    Diagnostic {
+        range: span_range(span, source),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(lsp_types::NumberOrString::String("E0700".to_string())),
+        source: Some("lumen".to_string()),
+        message: error.to_string(),
+        ..Default::default()
+    }
+}
+
 fn lex_error_to_diagnostic(error: &LexError) -> Diagnostic {
     match error {
         LexError::UnexpectedChar { ch, line, col } => {
@@ -573,7 +685,7 @@ fn resolve_error_to_diagnostic(error: &ResolveError) -> Diagnostic {
     }
 }
 
-fn type_error_to_diagnostic(error: &TypeError) -> Diagnostic {
+fn type_error_to_diagnostic(error: &TypeError, source: &str) -> Diagnostic {
     match error {
         TypeError::Mismatch {
             expected,
@@ -699,6 +811,44 @@ fn type_error_to_diagnostic(error: &TypeError) -> Diagnostic {
                 data: None,
             }
         }
+        TypeError::DuplicateField { span, .. } | TypeError::MissingField { span, .. } => {
+            Diagnostic {
+                range: span_range(span, source),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(lsp_types::NumberOrString::String(
+                    error_codes::type_code(error).to_string(),
+                )),
+                source: Some("lumen".to_string()),
+                message: error.to_string(),
+                ..Default::default()
+            }
+        }
+        TypeError::UnboundedRecursion { line, .. } | TypeError::PrecisionLoss { line, .. } => {
+            let line_zero = line.saturating_sub(1) as u32;
+
+            Diagnostic {
+                range: Range {
+                    start: Position {
+                        line: line_zero,
+                        character: 0,
+                    },
+                    end: Position {
+                        line: line_zero,
+                        character: u32::MAX,
+                    },
+                },
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(lsp_types::NumberOrString::String(
+                    error_codes::type_code(error).to_string(),
+                )),
+                source: Some("lumen".to_string()),
+                message: error.to_string(),
+                related_information: None,
+                tags: None,
+                code_description: None,
+                data: None,
+            }
+        }
         _ => {
             // Fallback for other type errors
             let line = match error {
@@ -724,7 +874,9 @@ fn type_error_to_diagnostic(error: &TypeError) -> Diagnostic {
                     },
                 },
                 severity: Some(DiagnosticSeverity::ERROR),
-                code: Some(lsp_types::NumberOrString::String("E049".to_string())),
+                code: Some(lsp_types::NumberOrString::String(
+                    error_codes::type_code(error).to_string(),
+                )),
                 source: Some("lumen".to_string()),
                 message: error.to_string(),
                 related_information: None,
@@ -897,3 +1049,160 @@ fn ownership_error_to_diagnostic(error: &OwnershipError) -> Diagnostic {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USE_AFTER_MOVE: &str = "cell main() -> list[Int]\n  let xs = [1, 2, 3]\n  let a = xs\n  let b = xs\n  return b\nend\n";
+
+    fn use_after_move(diagnostics: &[Diagnostic]) -> &Diagnostic {
+        diagnostics
+            .iter()
+            .find(|d| d.message.starts_with("use of moved variable"))
+            .unwrap_or_else(|| panic!("no use-after-move in {:?}", diagnostics))
+    }
+
+    #[test]
+    fn ownership_violation_is_a_warning_on_the_use_line() {
+        let diagnostics = diagnose(USE_AFTER_MOVE, false);
+        let diagnostic = use_after_move(&diagnostics);
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.range.start, Position::new(3, 10));
+        assert!(
+            diagnostic.message.contains("moved variable 'xs'"),
+            "{}",
+            diagnostic.message
+        );
+    }
+
+    #[test]
+    fn ownership_warning_lines_follow_markdown_blocks() {
+        let md = format!("# Demo\n\nProse.\n\n```lumen\n{}```\n", USE_AFTER_MOVE);
+        let diagnostics = diagnose(&md, true);
+        let diagnostic = use_after_move(&diagnostics);
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.range.start.line, 8);
+    }
+
+    #[test]
+    fn clean_source_has_no_diagnostics() {
+        let source = "cell main() -> Int\n  let x = 1\n  return x + x\nend\n";
+        assert!(diagnose(source, false).is_empty());
+    }
+
+    #[test]
+    fn unbounded_recursion_is_a_warning_with_its_stable_code() {
+        let error = TypeError::UnboundedRecursion {
+            name: "spin".to_string(),
+            line: 2,
+        };
+        let diagnostic = type_error_to_diagnostic(&error, "");
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(
+            diagnostic.code,
+            Some(lsp_types::NumberOrString::String("E0216".to_string()))
+        );
+        assert_eq!(diagnostic.range.start.line, 1);
+    }
+
+    #[test]
+    fn precision_loss_is_a_warning_with_its_stable_code() {
+        let error = TypeError::PrecisionLoss {
+            value: "9007199254740993".to_string(),
+            line: 3,
+        };
+        let diagnostic = type_error_to_diagnostic(&error, "");
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(
+            diagnostic.code,
+            Some(lsp_types::NumberOrString::String("E0217".to_string()))
+        );
+    }
+
+    #[test]
+    fn fallback_type_errors_carry_their_stable_code() {
+        let error = TypeError::NotCallable { line: 1 };
+        let diagnostic = type_error_to_diagnostic(&error, "");
+        assert_eq!(
+            diagnostic.code,
+            Some(lsp_types::NumberOrString::String("E0202".to_string()))
+        );
+    }
+
+    #[test]
+    fn duplicate_field_points_at_the_repeated_label() {
+        let source = "record Point\n  x: Int\nend\n\ncell main() -> Point\n  return Point(x: 1, x: 2)\nend\n";
+        let diagnostics = diagnose(source, false);
+        let duplicate = diagnostics
+            .iter()
+            .find(|d| d.message.starts_with("duplicate field 'x'"))
+            .unwrap_or_else(|| panic!("no duplicate field in {:?}", diagnostics));
+        assert_eq!(duplicate.range.start, Position::new(5, 21));
+        assert_eq!(duplicate.range.end, Position::new(5, 22));
+    }
+
+    #[test]
+    fn ownership_warnings_are_published_alongside_hard_errors() {
+        let source = format!(
+            "{}\ncell broken() -> Int\n  return missing\nend\n",
+            USE_AFTER_MOVE
+        );
+        let diagnostics = diagnose(&source, false);
+        let warning = use_after_move(&diagnostics);
+        assert_eq!(warning.severity, Some(DiagnosticSeverity::WARNING));
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d.severity == Some(DiagnosticSeverity::ERROR)),
+            "{:?}",
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn span_ranges_count_utf16_code_units() {
+        // "🙂" is one character, four bytes and two UTF-16 code units.
+        let source = "let s = \"🙂\" + ok\n";
+        let span = Span::new(17, 19, 1, 15);
+        assert_eq!(&source[span.start..span.end], "ok");
+        let range = span_range(&span, source);
+        assert_eq!(range.start, Position::new(0, 15));
+        assert_eq!(range.end, Position::new(0, 17));
+
+        let emoji = Span::new(9, 13, 1, 10);
+        let range = span_range(&emoji, source);
+        assert_eq!(range.start, Position::new(0, 9));
+        assert_eq!(range.end, Position::new(0, 11));
+    }
+
+    #[test]
+    fn hard_errors_stay_errors() {
+        let diagnostics = diagnose("cell main() -> Missing\n  return 1\nend\n", false);
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.severity == Some(DiagnosticSeverity::ERROR)));
+    }
+
+    #[test]
+    fn typestate_and_session_errors_carry_their_spans() {
+        let span = Span::new(10, 14, 3, 5);
+        let typestate = CompileError::Typestate(vec![TypestateError::UndeclaredTypestate {
+            type_name: "File".to_string(),
+            span,
+        }]);
+        let session = CompileError::Session(vec![SessionError::DualityViolation {
+            detail: "send/send".to_string(),
+            span,
+        }]);
+        for error in [typestate, session] {
+            let diagnostics = compile_error_to_diagnostics(&error, "");
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].range, span_range(&span, ""));
+            assert_eq!(diagnostics[0].range.start, Position::new(2, 4));
+            assert_eq!(diagnostics[0].range.end, Position::new(2, 8));
+            assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        }
+    }
+}
@@ -1,96 +1,215 @@
-//! Folding range provider — returns foldable regions for cells, records, enums, etc.
+//! Folding range provider — returns foldable regions for declarations, nested
+//! blocks (`if`, `for`, `while`, `loop`, `match`) and markdown code fences.
 
 use lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
-use lumen_compiler::compiler::ast::{Item, Program};
+use lumen_compiler::compiler::ast::{CellDef, Item, Program, Stmt};
+use lumen_compiler::compiler::tokens::Span;
+use lumen_compiler::markdown::extract::extract_blocks;
 
 use crate::document_symbols::byte_offset_to_line;
 
 pub fn build_folding_ranges(
-    _params: FoldingRangeParams,
+    params: FoldingRangeParams,
     text: &str,
     program: Option<&Program>,
 ) -> Vec<FoldingRange> {
-    let prog = match program {
-        Some(p) => p,
-        None => return vec![],
-    };
-
     let mut ranges = Vec::new();
 
-    for item in &prog.items {
-        match item {
-            Item::Cell(cell) => {
-                if let Some(range) = make_folding_range(&cell.span, text, FoldingRangeKind::Region)
-                {
-                    ranges.push(range);
-                }
+    if params.text_document.uri.path().as_str().ends_with(".md") {
+        add_fence_ranges(text, &mut ranges);
+    }
+
+    if let Some(prog) = program {
+        for item in &prog.items {
+            add_item_ranges(item, text, &mut ranges);
+        }
+    }
+
+    ranges
+}
+
+fn add_item_ranges(item: &Item, text: &str, ranges: &mut Vec<FoldingRange>) {
+    match item {
+        Item::Cell(cell) => add_cell_ranges(cell, text, ranges),
+        Item::Record(record) => push_range(&record.span, text, ranges),
+        Item::Enum(enum_def) => {
+            push_range(&enum_def.span, text, ranges);
+            for method in &enum_def.methods {
+                add_cell_ranges(method, text, ranges);
             }
-            Item::Record(record) => {
-                if let Some(range) =
-                    make_folding_range(&record.span, text, FoldingRangeKind::Region)
-                {
-                    ranges.push(range);
-                }
+        }
+        Item::Agent(agent) => {
+            push_range(&agent.span, text, ranges);
+            for cell in &agent.cells {
+                add_cell_ranges(cell, text, ranges);
             }
-            Item::Enum(enum_def) => {
-                if let Some(range) =
-                    make_folding_range(&enum_def.span, text, FoldingRangeKind::Region)
-                {
-                    ranges.push(range);
-                }
+        }
+        Item::Process(process) => {
+            push_range(&process.span, text, ranges);
+            for cell in &process.cells {
+                add_cell_ranges(cell, text, ranges);
             }
-            Item::Process(process) => {
-                if let Some(range) =
-                    make_folding_range(&process.span, text, FoldingRangeKind::Region)
-                {
-                    ranges.push(range);
-                }
+        }
+        Item::Effect(effect) => push_range(&effect.span, text, ranges),
+        Item::Handler(handler) => {
+            push_range(&handler.span, text, ranges);
+            for handle in &handler.handles {
+                add_cell_ranges(handle, text, ranges);
             }
-            Item::Effect(effect) => {
-                if let Some(range) =
-                    make_folding_range(&effect.span, text, FoldingRangeKind::Region)
-                {
-                    ranges.push(range);
+        }
+        Item::TypeAlias(alias) => push_range(&alias.span, text, ranges),
+        _ => {}
+    }
+}
+
+fn add_cell_ranges(cell: &CellDef, text: &str, ranges: &mut Vec<FoldingRange>) {
+    push_range(&cell.span, text, ranges);
+    add_block_ranges(&cell.body, text, ranges);
+}
+
+/// Folds for the multi-line blocks in a statement list, recursively.
+fn add_block_ranges(body: &[Stmt], text: &str, ranges: &mut Vec<FoldingRange>) {
+    for stmt in body {
+        match stmt {
+            Stmt::If(if_stmt) => {
+                push_range(&if_stmt.span, text, ranges);
+                add_block_ranges(&if_stmt.then_body, text, ranges);
+                if let Some(else_body) = &if_stmt.else_body {
+                    add_block_ranges(else_body, text, ranges);
                 }
             }
-            Item::Handler(handler) => {
-                if let Some(range) =
-                    make_folding_range(&handler.span, text, FoldingRangeKind::Region)
-                {
-                    ranges.push(range);
-                }
+            Stmt::For(for_stmt) => {
+                push_range(&for_stmt.span, text, ranges);
+                add_block_ranges(&for_stmt.body, text, ranges);
+            }
+            Stmt::While(while_stmt) => {
+                push_range(&while_stmt.span, text, ranges);
+                add_block_ranges(&while_stmt.body, text, ranges);
+            }
+            Stmt::Loop(loop_stmt) => {
+                push_range(&loop_stmt.span, text, ranges);
+                add_block_ranges(&loop_stmt.body, text, ranges);
             }
-            Item::TypeAlias(alias) => {
-                if let Some(range) = make_folding_range(&alias.span, text, FoldingRangeKind::Region)
-                {
-                    ranges.push(range);
+            Stmt::Match(match_stmt) => {
+                push_range(&match_stmt.span, text, ranges);
+                for arm in &match_stmt.arms {
+                    add_block_ranges(&arm.body, text, ranges);
                 }
             }
+            Stmt::LocalCell(cell) => add_cell_ranges(cell, text, ranges),
             _ => {}
         }
     }
+}
 
-    ranges
+/// Fold each fenced Lumen block from its opening to its closing fence.
+fn add_fence_ranges(text: &str, ranges: &mut Vec<FoldingRange>) {
+    for block in extract_blocks(text).code_blocks {
+        let start_line = block.span.line.saturating_sub(1) as u32;
+        let end_line =
+            (block.code_start_line.saturating_sub(1) + block.code.lines().count()) as u32;
+        if end_line > start_line {
+            ranges.push(FoldingRange {
+                start_line,
+                start_character: None,
+                end_line,
+                end_character: None,
+                kind: Some(FoldingRangeKind::Region),
+                collapsed_text: None,
+            });
+        }
+    }
 }
 
-fn make_folding_range(
-    span: &lumen_compiler::compiler::tokens::Span,
-    text: &str,
-    kind: FoldingRangeKind,
-) -> Option<FoldingRange> {
+/// Fold a span from its first line to the line holding its last byte (the
+/// closing `end` for block constructs).
+fn push_range(span: &Span, text: &str, ranges: &mut Vec<FoldingRange>) {
     let start_line = span.line.saturating_sub(1) as u32;
-    let end_line = byte_offset_to_line(text, span.end)?;
+    let Some(end_line) = byte_offset_to_line(text, span.end) else {
+        return;
+    };
 
     if end_line <= start_line {
-        return None;
+        return;
     }
 
-    Some(FoldingRange {
+    ranges.push(FoldingRange {
         start_line,
         start_character: None,
         end_line,
         end_character: None,
-        kind: Some(kind),
+        kind: Some(FoldingRangeKind::Region),
         collapsed_text: None,
-    })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::TextDocumentIdentifier;
+
+    fn folds(text: &str, path: &str) -> Vec<(u32, u32)> {
+        let (program, _) = crate::parse_for_features(text, path.ends_with(".md"));
+        let params = FoldingRangeParams {
+            text_document: TextDocumentIdentifier {
+                uri: format!("file://{}", path).parse().unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let mut ranges: Vec<(u32, u32)> = build_folding_ranges(params, text, program.as_ref())
+            .into_iter()
+            .map(|r| (r.start_line, r.end_line))
+            .collect();
+        ranges.sort();
+        ranges
+    }
+
+    const TWELVE_LINE_CELL: &str = "cell main() -> Int
+  let total = 0
+  for i in [1, 2, 3]
+    total = total + i
+  end
+  if total > 5
+    print(\"big\")
+  else
+    print(\"small\")
+  end
+  return total
+end
+
+record Point
+  x: Int
+  y: Int
+end
+";
+
+    #[test]
+    fn cell_folds_from_header_to_end() {
+        let ranges = folds(TWELVE_LINE_CELL, "/test.lm");
+        assert!(ranges.contains(&(0, 11)), "got {:?}", ranges);
+        assert!(ranges.contains(&(13, 16)), "got {:?}", ranges);
+    }
+
+    #[test]
+    fn nested_blocks_fold_to_their_own_end() {
+        let ranges = folds(TWELVE_LINE_CELL, "/test.lm");
+        assert_eq!(ranges, vec![(0, 11), (2, 4), (5, 9), (13, 16)]);
+    }
+
+    #[test]
+    fn match_folds_to_its_end() {
+        let source =
+            "cell f(x: Int) -> Int\n  match x\n    1 -> return 10\n    _ -> return 0\n  end\nend\n";
+        assert_eq!(folds(source, "/test.lm"), vec![(0, 5), (1, 4)]);
+    }
+
+    #[test]
+    fn markdown_fences_and_later_blocks_fold_at_document_lines() {
+        let source = "# Doc\n\n```lumen\nrecord P\n  x: Int\nend\n```\n\nProse.\n\n```lumen\ncell main() -> Int\n  return 1\nend\n```\n";
+        assert_eq!(
+            folds(source, "/doc.lm.md"),
+            vec![(2, 6), (3, 5), (10, 14), (11, 13)]
+        );
+    }
 }
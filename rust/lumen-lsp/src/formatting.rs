@@ -1,7 +1,8 @@
 //! textDocument/formatting handler
 //!
-//! Delegates to the formatter in `lumen_cli::fmt` and returns a single
-//! whole-document `TextEdit` when the source changes.
+//! Delegates to `lumen_cli::fmt::format_source` — the same entry point
+//! `lumen fmt` uses — and returns a single whole-document `TextEdit` when the
+//! source changes.
 
 use lsp_types::{DocumentFormattingParams, Position, Range, TextEdit};
 
@@ -16,19 +17,7 @@ pub fn build_formatting(
     text: &str,
     uri_path: &str,
 ) -> Vec<TextEdit> {
-    let is_lm_md = uri_path.ends_with(".lm.md");
-    let is_lumen = uri_path.ends_with(".lumen");
-    let is_lm = uri_path.ends_with(".lm");
-    let is_markdown = uri_path.ends_with(".md") && !is_lm_md;
-
-    let formatted = if is_lm_md || is_markdown {
-        lumen_cli::fmt::format_file(text)
-    } else if is_lm || is_lumen {
-        lumen_cli::fmt::format_lm_source(text)
-    } else {
-        // Unknown extension — try code-first formatting
-        lumen_cli::fmt::format_lm_source(text)
-    };
+    let formatted = lumen_cli::fmt::format_source(text, uri_path);
 
     // If the formatted output is the same as the input, return empty edits
     if formatted == text {
@@ -37,7 +26,11 @@ pub fn build_formatting(
 
     // Return a single TextEdit that replaces the entire document
     let line_count = text.lines().count() as u32;
-    let last_line_len = text.lines().last().map(|l| l.len() as u32).unwrap_or(0);
+    let last_line_len = text
+        .lines()
+        .last()
+        .map(|l| l.encode_utf16().count() as u32)
+        .unwrap_or(0);
 
     // Handle trailing newline: if text ends with \n, the last "line" from
     // lines() is the one before the newline, but the position is actually
@@ -173,4 +166,54 @@ mod tests {
             assert!(edits[0].new_text.contains("  return 42"));
         }
     }
+
+    fn format(source: &str, path: &str) -> String {
+        let edits = build_formatting(make_params(), source, path);
+        match edits.as_slice() {
+            [] => source.to_string(),
+            [edit] => edit.new_text.clone(),
+            _ => panic!("expected a single whole-document edit, got {:?}", edits),
+        }
+    }
+
+    #[test]
+    fn misindented_cell_is_rewritten_to_canonical_form() {
+        let source = "cell add(a: Int, b: Int) -> Int\n    let sum = a + b\n    return sum\nend\n";
+        let edits = build_formatting(make_params(), source, "/test.lm");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            edits[0].new_text,
+            "cell add(a: Int, b: Int) -> Int\n  let sum = a + b\n  return sum\nend\n"
+        );
+        assert_eq!(edits[0].range.end, Position::new(4, 0));
+        // Formatting is idempotent
+        assert!(build_formatting(make_params(), &edits[0].new_text, "/test.lm").is_empty());
+    }
+
+    #[test]
+    fn raw_source_keeps_directive_header() {
+        let source = "@lumen 1\n@package \"demo\"\n\ncell main() -> Int\nreturn 0\nend\n";
+        assert_eq!(
+            format(source, "/main.lm"),
+            "@lumen 1\n@package \"demo\"\n\ncell main() -> Int\n  return 0\nend\n"
+        );
+    }
+
+    #[test]
+    fn markdown_source_keeps_directives_and_formats_fences() {
+        let source = "@lumen 1\n@package \"demo\"\n\n# Demo\n\n```lumen\ncell main() -> Int\n    return 0\nend\n```\n";
+        assert_eq!(
+            format(source, "/demo.lm.md"),
+            "@lumen 1\n@package \"demo\"\n\n# Demo\n\n```lumen\ncell main() -> Int\n  return 0\nend\n```\n"
+        );
+    }
+
+    #[test]
+    fn matches_cli_formatter() {
+        let source = "cell foo() -> Int\nreturn 42\nend\n";
+        assert_eq!(
+            format(source, "/test.lm"),
+            lumen_cli::fmt::format_source(source, "test.lm")
+        );
+    }
 }
@@ -1,152 +1,79 @@
-//! Go-to-definition support
+//! Go-to-definition support, following imports into other files
 
+use crate::cache::CompilationCache;
+use crate::workspace_index::{path_to_uri, uri_to_path, WorkspaceIndex};
 use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, Position, Range, Uri};
-use lumen_compiler::compiler::ast::{Item, Program};
+use lumen_cli::module_resolver::{ModuleLocation, ModuleResolver};
+use lumen_compiler::compiler::ast::{ImportList, Item, Program};
 
 pub fn build_goto_definition(
     params: GotoDefinitionParams,
     text: &str,
     program: Option<&Program>,
     uri: &Uri,
+    cache: &CompilationCache,
+    index: &mut WorkspaceIndex,
 ) -> Option<GotoDefinitionResponse> {
     let position = params.text_document_position_params.position;
     let word = extract_word_at_position(text, position)?;
+    let prog = program?;
 
-    if let Some(prog) = program {
-        for item in &prog.items {
-            match item {
-                Item::Cell(cell) if cell.name == word => {
-                    let line = if cell.span.line > 0 {
-                        (cell.span.line - 1) as u32
-                    } else {
-                        0
-                    };
-
-                    return Some(GotoDefinitionResponse::Scalar(Location {
-                        uri: uri.clone(),
-                        range: Range {
-                            start: Position { line, character: 0 },
-                            end: Position {
-                                line,
-                                character: u32::MAX,
-                            },
-                        },
-                    }));
-                }
-                Item::Record(record) if record.name == word => {
-                    let line = if record.span.line > 0 {
-                        (record.span.line - 1) as u32
-                    } else {
-                        0
-                    };
-
-                    return Some(GotoDefinitionResponse::Scalar(Location {
-                        uri: uri.clone(),
-                        range: Range {
-                            start: Position { line, character: 0 },
-                            end: Position {
-                                line,
-                                character: u32::MAX,
-                            },
-                        },
-                    }));
-                }
-                Item::Enum(enum_def) if enum_def.name == word => {
-                    let line = if enum_def.span.line > 0 {
-                        (enum_def.span.line - 1) as u32
-                    } else {
-                        0
-                    };
-
-                    return Some(GotoDefinitionResponse::Scalar(Location {
-                        uri: uri.clone(),
-                        range: Range {
-                            start: Position { line, character: 0 },
-                            end: Position {
-                                line,
-                                character: u32::MAX,
-                            },
-                        },
-                    }));
-                }
-                Item::TypeAlias(alias) if alias.name == word => {
-                    let line = if alias.span.line > 0 {
-                        (alias.span.line - 1) as u32
-                    } else {
-                        0
-                    };
-
-                    return Some(GotoDefinitionResponse::Scalar(Location {
-                        uri: uri.clone(),
-                        range: Range {
-                            start: Position { line, character: 0 },
-                            end: Position {
-                                line,
-                                character: u32::MAX,
-                            },
-                        },
-                    }));
-                }
-                Item::Process(process) if process.name == word => {
-                    let line = if process.span.line > 0 {
-                        (process.span.line - 1) as u32
-                    } else {
-                        0
-                    };
-
-                    return Some(GotoDefinitionResponse::Scalar(Location {
-                        uri: uri.clone(),
-                        range: Range {
-                            start: Position { line, character: 0 },
-                            end: Position {
-                                line,
-                                character: u32::MAX,
-                            },
-                        },
-                    }));
-                }
-                Item::Effect(effect) if effect.name == word => {
-                    let line = if effect.span.line > 0 {
-                        (effect.span.line - 1) as u32
-                    } else {
-                        0
-                    };
-
-                    return Some(GotoDefinitionResponse::Scalar(Location {
-                        uri: uri.clone(),
-                        range: Range {
-                            start: Position { line, character: 0 },
-                            end: Position {
-                                line,
-                                character: u32::MAX,
-                            },
-                        },
-                    }));
-                }
-                // Check enum variants
-                Item::Enum(enum_def) => {
-                    for variant in &enum_def.variants {
-                        if variant.name == word {
-                            let line = if enum_def.span.line > 0 {
-                                (enum_def.span.line - 1) as u32
-                            } else {
-                                0
-                            };
-
-                            return Some(GotoDefinitionResponse::Scalar(Location {
-                                uri: uri.clone(),
-                                range: Range {
-                                    start: Position { line, character: 0 },
-                                    end: Position {
-                                        line,
-                                        character: u32::MAX,
-                                    },
-                                },
-                            }));
-                        }
-                    }
-                }
-                _ => {}
+    if let Some(line) = definition_line(prog, &word) {
+        return Some(GotoDefinitionResponse::Scalar(line_location(uri, line)));
+    }
+
+    imported_definition(prog, &word, uri, cache, index).map(GotoDefinitionResponse::Scalar)
+}
+
+/// Resolve `word` through the document's imports and find its definition in
+/// the imported module's file. Open documents are read from the cache, other
+/// files from disk via the workspace index.
+fn imported_definition(
+    prog: &Program,
+    word: &str,
+    uri: &Uri,
+    cache: &CompilationCache,
+    index: &mut WorkspaceIndex,
+) -> Option<Location> {
+    let resolver = ModuleResolver::for_source_file(&uri_to_path(uri)?);
+
+    for item in &prog.items {
+        let Item::Import(import) = item else {
+            continue;
+        };
+        if import.alias.is_some() {
+            continue;
+        }
+        let name = match &import.names {
+            ImportList::Names(names) => match names
+                .iter()
+                .find(|n| n.alias.as_deref().unwrap_or(&n.name) == word || n.name == word)
+            {
+                Some(imported) => imported.name.as_str(),
+                None => continue,
+            },
+            ImportList::Wildcard => word,
+        };
+
+        let Some(location) = resolver.locate(&import.path.join(".")) else {
+            continue;
+        };
+        let files = match location {
+            ModuleLocation::File(path) => vec![path],
+            ModuleLocation::Directory(paths) => paths,
+        };
+        for path in files {
+            let Some(file_uri) = path_to_uri(&path) else {
+                continue;
+            };
+            let line = match cache.get_program(&file_uri) {
+                Some(open) => definition_line(open, name),
+                None => index
+                    .program(&path)
+                    .and_then(|program| definition_line(program, name)),
+            };
+            if let Some(line) = line {
+                return Some(line_location(&file_uri, line));
             }
         }
     }
@@ -154,6 +81,37 @@ pub fn build_goto_definition(
     None
 }
 
+/// The zero-based line of the top-level declaration of `word`, or of the enum
+/// declaring a variant named `word`.
+fn definition_line(prog: &Program, word: &str) -> Option<u32> {
+    let span = prog.items.iter().find_map(|item| match item {
+        Item::Cell(cell) if cell.name == word => Some(cell.span),
+        Item::Record(record) if record.name == word => Some(record.span),
+        Item::Enum(enum_def) if enum_def.name == word => Some(enum_def.span),
+        Item::TypeAlias(alias) if alias.name == word => Some(alias.span),
+        Item::Process(process) if process.name == word => Some(process.span),
+        Item::Effect(effect) if effect.name == word => Some(effect.span),
+        Item::Enum(enum_def) if enum_def.variants.iter().any(|v| v.name == word) => {
+            Some(enum_def.span)
+        }
+        _ => None,
+    })?;
+    Some(span.line.saturating_sub(1) as u32)
+}
+
+fn line_location(uri: &Uri, line: u32) -> Location {
+    Location {
+        uri: uri.clone(),
+        range: Range {
+            start: Position { line, character: 0 },
+            end: Position {
+                line,
+                character: u32::MAX,
+            },
+        },
+    }
+}
+
 fn extract_word_at_position(text: &str, position: Position) -> Option<String> {
     let lines: Vec<&str> = text.lines().collect();
     let line = lines.get(position.line as usize)?;
@@ -179,3 +137,91 @@ fn extract_word_at_position(text: &str, position: Position) -> Option<String> {
 
     Some(line[start..end].to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{TextDocumentIdentifier, TextDocumentPositionParams};
+    use std::path::{Path, PathBuf};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/goto")
+            .join(name)
+    }
+
+    fn goto(path: &Path, position: Position, cache: &CompilationCache) -> Option<Location> {
+        let uri = path_to_uri(path).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        let (program, _) = crate::parse_for_features(&text, false);
+        let params = GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let mut index = WorkspaceIndex::new();
+        match build_goto_definition(params, &text, program.as_ref(), &uri, cache, &mut index)? {
+            GotoDefinitionResponse::Scalar(location) => Some(location),
+            other => panic!("expected a single location, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn imported_cell_resolves_to_the_defining_file() {
+        let main = fixture("main.lm");
+        // `  return double(21)` — cursor on `double`
+        let location = goto(&main, Position::new(3, 10), &CompilationCache::new()).unwrap();
+        assert_eq!(location.uri, path_to_uri(&fixture("math.lm")).unwrap());
+        assert_eq!(location.range.start.line, 2);
+    }
+
+    #[test]
+    fn imported_cell_prefers_the_open_buffer() {
+        let math = fixture("math.lm");
+        let mut cache = CompilationCache::new();
+        let edited = "# moved down\n\n\n\ncell double(x: Int) -> Int\n  return x * 2\nend\n";
+        let (program, symbols) = crate::parse_for_features(edited, false);
+        cache.update(
+            path_to_uri(&math).unwrap(),
+            edited.to_string(),
+            program,
+            symbols,
+            Vec::new(),
+            Default::default(),
+        );
+        let location = goto(&fixture("main.lm"), Position::new(3, 10), &cache).unwrap();
+        assert_eq!(location.range.start.line, 4);
+    }
+
+    #[test]
+    fn unresolvable_imports_do_not_hide_later_ones() {
+        // `import missing: *` precedes `import math: double`
+        let main = fixture("after_missing.lm");
+        let location = goto(&main, Position::new(4, 10), &CompilationCache::new()).unwrap();
+        assert_eq!(location.uri, path_to_uri(&fixture("math.lm")).unwrap());
+        assert_eq!(location.range.start.line, 2);
+    }
+
+    #[test]
+    fn local_definitions_stay_in_the_same_file() {
+        let main = fixture("main.lm");
+        // `cell main() -> Int` — cursor on `main`
+        let location = goto(&main, Position::new(2, 6), &CompilationCache::new()).unwrap();
+        assert_eq!(location.uri, path_to_uri(&main).unwrap());
+        assert_eq!(location.range.start.line, 2);
+    }
+
+    #[test]
+    fn unknown_names_have_no_definition() {
+        // `  return double(21)` — cursor on `return`
+        assert!(goto(
+            &fixture("main.lm"),
+            Position::new(3, 3),
+            &CompilationCache::new()
+        )
+        .is_none());
+    }
+}
@@ -18,11 +18,7 @@ pub fn build_hover(params: HoverParams, text: &str, program: Option<&Program>) -
                         .collect::<Vec<_>>()
                         .join(", ");
 
-                    let return_str = cell
-                        .return_type
-                        .as_ref()
-                        .map(|t| format!(" -> {}", type_expr_to_string(t)))
-                        .unwrap_or_default();
+                    let return_str = cell_return_str(cell, prog);
 
                     let effects_str = if !cell.effects.is_empty() {
                         format!(" / {{{}}}", cell.effects.join(", "))
@@ -221,6 +217,25 @@ fn extract_word_at_position(text: &str, position: Position) -> Option<String> {
     Some(line[start..end].to_string())
 }
 
+/// Render a cell's ` -> Type` suffix, using the return type the compiler
+/// inferred when the cell omits its annotation.
+pub fn cell_return_str(cell: &lumen_compiler::compiler::ast::CellDef, program: &Program) -> String {
+    let inferred = match &cell.return_type {
+        Some(ty) => Some(ty.clone()),
+        None => {
+            let (mut symbols, _) = lumen_compiler::compiler::resolve::resolve_partial(program);
+            lumen_compiler::compiler::typecheck::infer_return_types(program, &mut symbols);
+            symbols
+                .cells
+                .get(&cell.name)
+                .and_then(|info| info.return_type.clone())
+        }
+    };
+    inferred
+        .map(|t| format!(" -> {}", type_expr_to_string(&t)))
+        .unwrap_or_default()
+}
+
 pub fn type_expr_to_string(ty: &lumen_compiler::compiler::ast::TypeExpr) -> String {
     use lumen_compiler::compiler::ast::TypeExpr;
 
@@ -252,6 +267,7 @@ pub fn type_expr_to_string(ty: &lumen_compiler::compiler::ast::TypeExpr) -> Stri
             format!("({})", inner)
         }
         TypeExpr::Set(inner, _) => format!("set[{}]", type_expr_to_string(inner)),
+        TypeExpr::Array(inner, len, _) => format!("[{}; {}]", type_expr_to_string(inner), len),
         TypeExpr::Fn(params, ret, effects, _) => {
             let params_str = params
                 .iter()
@@ -4,14 +4,47 @@
 //! - **Type hints**: Show inferred types for `let` bindings without explicit annotation
 //! - **Parameter hints**: Show parameter names at call sites
 
+use crate::rename::{declaration_at, locate_name};
 use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, Position};
-use lumen_compiler::compiler::ast::{CallArg, CellDef, Expr, Item, Stmt};
-use lumen_compiler::compiler::resolve::SymbolTable;
+use lumen_compiler::compiler::ast::{CallArg, CellDef, Expr, Item, Program, Stmt};
+use lumen_compiler::compiler::resolve::{self, SymbolTable};
+use lumen_compiler::compiler::tokens::Span;
+use lumen_compiler::compiler::typecheck::{self, Type};
+use std::collections::HashMap;
+
+/// Client-configurable inlay hint behaviour, read from the `inlayHints`
+/// section of the initialization options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InlayHintOptions {
+    /// Skip type hints for bindings initialized with a literal, whose type is
+    /// already obvious from the source.
+    pub exclude_obvious: bool,
+}
+
+impl InlayHintOptions {
+    pub fn from_initialization_options(options: Option<&serde_json::Value>) -> Self {
+        let exclude_obvious = options
+            .and_then(|o| o.pointer("/inlayHints/excludeObvious"))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        Self { exclude_obvious }
+    }
+}
+
+/// What type hints need beyond the AST: the source lines, to place each hint
+/// right after the binding's name, and the types inferred by the checker.
+struct TypeHintContext<'a> {
+    lines: Vec<&'a str>,
+    let_types: HashMap<Span, Type>,
+    options: InlayHintOptions,
+}
 
 pub fn build_inlay_hints(
     _params: InlayHintParams,
-    program: Option<&lumen_compiler::compiler::ast::Program>,
+    text: &str,
+    program: Option<&Program>,
     symbols: Option<&SymbolTable>,
+    options: InlayHintOptions,
 ) -> Vec<InlayHint> {
     let mut hints = Vec::new();
 
@@ -29,10 +62,16 @@ pub fn build_inlay_hints(
             })
             .collect();
 
+        let ctx = TypeHintContext {
+            lines: text.lines().collect(),
+            let_types: infer_let_types(prog),
+            options,
+        };
+
         for item in &prog.items {
             if let Item::Cell(cell) = item {
                 for stmt in &cell.body {
-                    extract_hints_from_stmt(stmt, &mut hints, symbols, &cell_defs);
+                    extract_hints_from_stmt(stmt, &mut hints, symbols, &cell_defs, &ctx);
                 }
             }
         }
@@ -41,40 +80,50 @@ pub fn build_inlay_hints(
     hints
 }
 
+/// Run type inference over the whole program. Resolution errors are ignored
+/// so that a half-written document still gets hints for what does check.
+pub(crate) fn infer_let_types(program: &Program) -> HashMap<Span, Type> {
+    let (mut symbols, _) = resolve::resolve_partial(program);
+    typecheck::infer_return_types(program, &mut symbols);
+    typecheck::infer_let_types(program, &symbols)
+}
+
 fn extract_hints_from_stmt(
     stmt: &Stmt,
     hints: &mut Vec<InlayHint>,
     symbols: Option<&SymbolTable>,
     cell_defs: &[&CellDef],
+    ctx: &TypeHintContext,
 ) {
     match stmt {
         Stmt::Let(let_stmt) => {
             // Only show hints for bindings without explicit type annotation
-            if let_stmt.ty.is_none() {
-                // Infer the type from the initializer
-                let inferred_type = infer_type_from_expr(&let_stmt.value);
-                let line = if let_stmt.span.line > 0 {
-                    (let_stmt.span.line - 1) as u32
-                } else {
-                    0
-                };
-
-                // Position after the variable name
-                let position = Position {
-                    line,
-                    character: (let_stmt.span.start + let_stmt.name.len()) as u32,
-                };
-
-                hints.push(InlayHint {
-                    position,
-                    label: InlayHintLabel::String(format!(": {}", inferred_type)),
-                    kind: Some(InlayHintKind::TYPE),
-                    text_edits: None,
-                    tooltip: None,
-                    padding_left: None,
-                    padding_right: Some(true),
-                    data: None,
-                });
+            if let_stmt.ty.is_none() && let_stmt.pattern.is_none() {
+                let inferred = ctx
+                    .let_types
+                    .get(&let_stmt.span)
+                    .filter(|ty| **ty != Type::Any);
+                let obvious = ctx.options.exclude_obvious && is_literal(&let_stmt.value);
+                let name_end = locate_name(
+                    &ctx.lines,
+                    declaration_at(&let_stmt.span, &let_stmt.name),
+                    &let_stmt.name,
+                );
+                if let (Some(ty), Some(name), false) = (inferred, name_end, obvious) {
+                    hints.push(InlayHint {
+                        position: Position {
+                            line: name.line,
+                            character: name.end_char,
+                        },
+                        label: InlayHintLabel::String(format!(": {}", ty)),
+                        kind: Some(InlayHintKind::TYPE),
+                        text_edits: None,
+                        tooltip: None,
+                        padding_left: None,
+                        padding_right: Some(true),
+                        data: None,
+                    });
+                }
             }
 
             // Also check the initializer expression for call-site param hints
@@ -83,36 +132,36 @@ fn extract_hints_from_stmt(
         Stmt::If(if_stmt) => {
             extract_param_hints_from_expr(&if_stmt.condition, hints, symbols, cell_defs);
             for s in &if_stmt.then_body {
-                extract_hints_from_stmt(s, hints, symbols, cell_defs);
+                extract_hints_from_stmt(s, hints, symbols, cell_defs, ctx);
             }
             if let Some(else_stmts) = &if_stmt.else_body {
                 for s in else_stmts {
-                    extract_hints_from_stmt(s, hints, symbols, cell_defs);
+                    extract_hints_from_stmt(s, hints, symbols, cell_defs, ctx);
                 }
             }
         }
         Stmt::While(while_stmt) => {
             extract_param_hints_from_expr(&while_stmt.condition, hints, symbols, cell_defs);
             for s in &while_stmt.body {
-                extract_hints_from_stmt(s, hints, symbols, cell_defs);
+                extract_hints_from_stmt(s, hints, symbols, cell_defs, ctx);
             }
         }
         Stmt::Loop(loop_stmt) => {
             for s in &loop_stmt.body {
-                extract_hints_from_stmt(s, hints, symbols, cell_defs);
+                extract_hints_from_stmt(s, hints, symbols, cell_defs, ctx);
             }
         }
         Stmt::For(for_stmt) => {
             extract_param_hints_from_expr(&for_stmt.iter, hints, symbols, cell_defs);
             for s in &for_stmt.body {
-                extract_hints_from_stmt(s, hints, symbols, cell_defs);
+                extract_hints_from_stmt(s, hints, symbols, cell_defs, ctx);
             }
         }
         Stmt::Match(match_stmt) => {
             extract_param_hints_from_expr(&match_stmt.subject, hints, symbols, cell_defs);
             for arm in &match_stmt.arms {
                 for s in &arm.body {
-                    extract_hints_from_stmt(s, hints, symbols, cell_defs);
+                    extract_hints_from_stmt(s, hints, symbols, cell_defs, ctx);
                 }
             }
         }
@@ -301,81 +350,19 @@ fn is_trivial_arg(expr: &Expr, param_name: &str) -> bool {
     }
 }
 
-fn infer_type_from_expr(expr: &Expr) -> String {
-    match expr {
-        Expr::IntLit(_, _) => "Int".to_string(),
-        Expr::FloatLit(_, _) => "Float".to_string(),
-        Expr::StringLit(_, _) => "String".to_string(),
-        Expr::BoolLit(_, _) => "Bool".to_string(),
-        Expr::ListLit(items, _) => {
-            if items.is_empty() {
-                "list[_]".to_string()
-            } else {
-                let elem_type = infer_type_from_expr(&items[0]);
-                format!("list[{}]", elem_type)
-            }
-        }
-        Expr::MapLit(entries, _) => {
-            if entries.is_empty() {
-                "map[_, _]".to_string()
-            } else {
-                let key_type = infer_type_from_expr(&entries[0].0);
-                let val_type = infer_type_from_expr(&entries[0].1);
-                format!("map[{}, {}]", key_type, val_type)
-            }
-        }
-        Expr::RecordLit(type_name, _, _) => type_name.clone(),
-        Expr::Call(func_expr, _, _) => {
-            // For builtins, we can infer some types
-            if let Expr::Ident(name, _) = &**func_expr {
-                match name.as_str() {
-                    "print" => "Void".to_string(),
-                    "len" => "Int".to_string(),
-                    "to_string" => "String".to_string(),
-                    "parse_int" => "result[Int, String]".to_string(),
-                    "parse_float" => "result[Float, String]".to_string(),
-                    _ => "<unknown>".to_string(),
-                }
-            } else {
-                "<unknown>".to_string()
-            }
-        }
-        Expr::BinOp(left, op, right, _) => {
-            use lumen_compiler::compiler::ast::BinOp;
-            match op {
-                BinOp::Add
-                | BinOp::Sub
-                | BinOp::Mul
-                | BinOp::Div
-                | BinOp::FloorDiv
-                | BinOp::Mod => {
-                    // Numeric operations
-                    let left_type = infer_type_from_expr(left);
-                    if left_type == "Float" || infer_type_from_expr(right) == "Float" {
-                        "Float".to_string()
-                    } else {
-                        "Int".to_string()
-                    }
-                }
-                BinOp::Eq
-                | BinOp::NotEq
-                | BinOp::Lt
-                | BinOp::Gt
-                | BinOp::LtEq
-                | BinOp::GtEq
-                | BinOp::And
-                | BinOp::Or => "Bool".to_string(),
-                _ => "<unknown>".to_string(),
-            }
-        }
-        Expr::TupleLit(items, _) => {
-            let types: Vec<String> = items.iter().map(infer_type_from_expr).collect();
-            format!("({})", types.join(", "))
-        }
-        Expr::NullLit(_) => "Null".to_string(),
-        Expr::StringInterp(_, _) => "String".to_string(),
-        _ => "<unknown>".to_string(),
-    }
+/// Whether `expr` is a literal whose type is evident at a glance.
+fn is_literal(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::IntLit(..)
+            | Expr::BigIntLit(..)
+            | Expr::FloatLit(..)
+            | Expr::StringLit(..)
+            | Expr::RawStringLit(..)
+            | Expr::BytesLit(..)
+            | Expr::BoolLit(..)
+            | Expr::NullLit(..)
+    )
 }
 
 #[cfg(test)]
@@ -416,7 +403,13 @@ mod tests {
             },
         };
 
-        let hints = build_inlay_hints(params, program.as_ref(), symbols.as_ref());
+        let hints = build_inlay_hints(
+            params,
+            source,
+            program.as_ref(),
+            symbols.as_ref(),
+            InlayHintOptions::default(),
+        );
 
         // Should have at least one type hint for `let x = 42`
         let type_hints: Vec<_> = hints
@@ -459,7 +452,13 @@ mod tests {
             },
         };
 
-        let hints = build_inlay_hints(params, program.as_ref(), symbols.as_ref());
+        let hints = build_inlay_hints(
+            params,
+            source,
+            program.as_ref(),
+            symbols.as_ref(),
+            InlayHintOptions::default(),
+        );
 
         let type_hints: Vec<_> = hints
             .iter()
@@ -499,7 +498,13 @@ mod tests {
             },
         };
 
-        let hints = build_inlay_hints(params, program.as_ref(), symbols.as_ref());
+        let hints = build_inlay_hints(
+            params,
+            source,
+            program.as_ref(),
+            symbols.as_ref(),
+            InlayHintOptions::default(),
+        );
 
         let type_hints: Vec<_> = hints
             .iter()
@@ -537,7 +542,13 @@ mod tests {
             },
         };
 
-        let hints = build_inlay_hints(params, program.as_ref(), symbols.as_ref());
+        let hints = build_inlay_hints(
+            params,
+            source,
+            program.as_ref(),
+            symbols.as_ref(),
+            InlayHintOptions::default(),
+        );
 
         let param_hints: Vec<_> = hints
             .iter()
@@ -596,7 +607,13 @@ mod tests {
             },
         };
 
-        let hints = build_inlay_hints(params, program.as_ref(), symbols.as_ref());
+        let hints = build_inlay_hints(
+            params,
+            source,
+            program.as_ref(),
+            symbols.as_ref(),
+            InlayHintOptions::default(),
+        );
 
         let param_hints: Vec<_> = hints
             .iter()
@@ -641,7 +658,13 @@ mod tests {
             },
         };
 
-        let hints = build_inlay_hints(params, program.as_ref(), symbols.as_ref());
+        let hints = build_inlay_hints(
+            params,
+            source,
+            program.as_ref(),
+            symbols.as_ref(),
+            InlayHintOptions::default(),
+        );
 
         let type_hints: Vec<_> = hints
             .iter()
@@ -657,4 +680,73 @@ mod tests {
         });
         assert!(has_tuple, "Should infer tuple type (Int, String)");
     }
+
+    fn type_hints(source: &str, options: InlayHintOptions) -> Vec<(Position, String)> {
+        let program = parse_program(source);
+        let params = InlayHintParams {
+            work_done_progress_params: Default::default(),
+            text_document: lsp_types::TextDocumentIdentifier {
+                uri: "file:///test.lm".parse().unwrap(),
+            },
+            range: lsp_types::Range::default(),
+        };
+        build_inlay_hints(params, source, program.as_ref(), None, options)
+            .into_iter()
+            .filter(|h| h.kind == Some(InlayHintKind::TYPE))
+            .map(|h| match h.label {
+                InlayHintLabel::String(s) => (h.position, s),
+                InlayHintLabel::LabelParts(_) => panic!("expected a string label"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_type_hints_use_inferred_types() {
+        let source = "cell main() -> Int\n  let x = 1 + 2\n  let s = \"hi\"\n  return x\nend";
+        let hints = type_hints(source, InlayHintOptions::default());
+        let labels: Vec<&str> = hints.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec![": Int", ": String"]);
+    }
+
+    #[test]
+    fn test_type_hint_follows_the_binding_name() {
+        let source = "cell main() -> Int\n  let total = 1 + 2\n  return total\nend";
+        let hints = type_hints(source, InlayHintOptions::default());
+        assert_eq!(
+            hints[0].0,
+            Position {
+                line: 1,
+                character: 11
+            }
+        );
+    }
+
+    #[test]
+    fn test_type_hint_for_call_uses_declared_return_type() {
+        let source = "cell double(n: Int) -> Float\n  return 2.0\nend\n\ncell main() -> Int\n  let d = double(3)\n  return 0\nend";
+        let hints = type_hints(source, InlayHintOptions::default());
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].1, ": Float");
+    }
+
+    #[test]
+    fn test_exclude_obvious_skips_literal_initializers() {
+        let source = "cell main() -> Int\n  let x = 1 + 2\n  let s = \"hi\"\n  return x\nend";
+        let options = InlayHintOptions {
+            exclude_obvious: true,
+        };
+        let hints = type_hints(source, options);
+        let labels: Vec<&str> = hints.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec![": Int"]);
+    }
+
+    #[test]
+    fn test_options_read_from_initialization_options() {
+        let init = serde_json::json!({ "inlayHints": { "excludeObvious": true } });
+        assert!(InlayHintOptions::from_initialization_options(Some(&init)).exclude_obvious);
+        assert_eq!(
+            InlayHintOptions::from_initialization_options(None),
+            InlayHintOptions::default()
+        );
+    }
 }
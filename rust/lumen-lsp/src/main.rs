@@ -15,9 +15,11 @@ mod goto_definition;
 mod hover;
 mod implementations;
 mod inlay_hints;
+mod references;
 mod rename;
 mod semantic_tokens;
 mod signature_help;
+mod workspace_index;
 
 use cache::CompilationCache;
 use lsp_server::{Connection, Message, Notification, Request, Response};
@@ -26,10 +28,12 @@ use lsp_types::request::Request as _;
 use lsp_types::*;
 use lumen_compiler::compiler::lexer::Lexer;
 use lumen_compiler::compiler::parser::Parser;
+use lumen_compiler::compiler::tokens::TokenKind;
 use lumen_compiler::markdown::extract::extract_blocks;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
+use workspace_index::WorkspaceIndex;
 
 fn main() {
     let (connection, io_threads) = Connection::stdio();
@@ -41,7 +45,7 @@ fn main() {
         definition_provider: Some(OneOf::Left(true)),
         hover_provider: Some(HoverProviderCapability::Simple(true)),
         completion_provider: Some(CompletionOptions {
-            trigger_characters: Some(vec![".".into(), ":".into()]),
+            trigger_characters: Some(vec![".".into(), ":".into(), "/".into()]),
             ..Default::default()
         }),
         document_symbol_provider: Some(OneOf::Left(true)),
@@ -95,21 +99,74 @@ fn main() {
     };
 
     let caps_json = serde_json::to_value(capabilities).unwrap();
-    let _init_params = connection.initialize(caps_json).unwrap();
+    let init_params = connection.initialize(caps_json).unwrap();
+    let hint_options = inlay_hints::InlayHintOptions::from_initialization_options(
+        init_params.get("initializationOptions"),
+    );
 
     let mut cache = CompilationCache::new();
     let mut diagnostics_latency = DiagnosticsLatency::default();
+    let mut pending = PendingReindex::default();
+    let mut index = WorkspaceIndex::new();
+
+    loop {
+        // Wait for the next message, waking up early to re-index documents
+        // whose debounce window has elapsed.
+        let msg = match pending.next_due() {
+            Some(due) => {
+                match connection
+                    .receiver
+                    .recv_timeout(due.saturating_duration_since(Instant::now()))
+                {
+                    Ok(msg) => msg,
+                    Err(err) if err.is_timeout() => {
+                        for (uri, change) in pending.take_due(Instant::now()) {
+                            reindex_document(
+                                &uri,
+                                change,
+                                &mut cache,
+                                &connection,
+                                &mut diagnostics_latency,
+                            );
+                        }
+                        continue;
+                    }
+                    Err(_) => break,
+                }
+            }
+            None => match connection.receiver.recv() {
+                Ok(msg) => msg,
+                Err(_) => break,
+            },
+        };
 
-    for msg in &connection.receiver {
         match msg {
             Message::Notification(not) => {
-                handle_notification(&not, &connection, &mut cache, &mut diagnostics_latency);
+                handle_notification(
+                    &not,
+                    &connection,
+                    &mut cache,
+                    &mut pending,
+                    &mut diagnostics_latency,
+                );
             }
             Message::Request(req) => {
                 if connection.handle_shutdown(&req).unwrap() {
                     break;
                 }
-                handle_request(&req, &connection, &cache);
+                // A request must see the latest text of the document it
+                // targets; other documents keep waiting out their debounce.
+                let target = request_document(&req);
+                for (uri, change) in pending.take_for_request(target.as_ref(), Instant::now()) {
+                    reindex_document(
+                        &uri,
+                        change,
+                        &mut cache,
+                        &connection,
+                        &mut diagnostics_latency,
+                    );
+                }
+                handle_request(&req, &connection, &cache, &mut index, hint_options);
             }
             _ => {}
         }
@@ -119,6 +176,9 @@ fn main() {
 }
 
 const DIAGNOSTIC_LATENCY_WINDOW: usize = 200;
+/// Quiet period after the last edit before a document is re-compiled and
+/// re-indexed.
+const REINDEX_DEBOUNCE: Duration = Duration::from_millis(150);
 const DIAGNOSTIC_LATENCY_REPORT_EVERY: u64 = 20;
 
 #[derive(Debug, Clone, Copy)]
@@ -166,6 +226,81 @@ struct ChangeContext {
     inserted_key_path_markers: bool,
 }
 
+impl ChangeContext {
+    /// Fold a later change into this one, so a debounced re-index sees every
+    /// edit made since the last one.
+    fn merge(&mut self, later: ChangeContext) {
+        self.ranged_line_spans.extend(later.ranged_line_spans);
+        self.saw_full_content_replace |= later.saw_full_content_replace;
+        self.inserted_key_path_markers |= later.inserted_key_path_markers;
+    }
+}
+
+/// The document a request is about, from its `textDocument.uri` parameter.
+/// Workspace-wide requests have none.
+fn request_document(req: &Request) -> Option<Uri> {
+    let uri = req.params.get("textDocument")?.get("uri")?;
+    serde_json::from_value(uri.clone()).ok()
+}
+
+/// Documents whose text has changed but which have not been re-indexed yet.
+#[derive(Default)]
+struct PendingReindex {
+    entries: HashMap<Uri, (ChangeContext, Instant)>,
+}
+
+impl PendingReindex {
+    /// Record a change, pushing the document's deadline back by the debounce.
+    fn schedule(&mut self, uri: Uri, change: ChangeContext, now: Instant) {
+        let due = now + REINDEX_DEBOUNCE;
+        match self.entries.get_mut(&uri) {
+            Some((pending, pending_due)) => {
+                pending.merge(change);
+                *pending_due = due;
+            }
+            None => {
+                self.entries.insert(uri, (change, due));
+            }
+        }
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.entries.values().map(|(_, due)| *due).min()
+    }
+
+    fn take_due(&mut self, now: Instant) -> Vec<(Uri, ChangeContext)> {
+        let due: Vec<Uri> = self
+            .entries
+            .iter()
+            .filter(|(_, (_, due))| *due <= now)
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        due.into_iter()
+            .filter_map(|uri| self.remove(&uri).map(|change| (uri, change)))
+            .collect()
+    }
+
+    /// The changes to settle before answering a request on `target`: that
+    /// document's, whether or not it is due, plus any that are due anyway.
+    fn take_for_request(
+        &mut self,
+        target: Option<&Uri>,
+        now: Instant,
+    ) -> Vec<(Uri, ChangeContext)> {
+        let mut changes = self.take_due(now);
+        if let Some(uri) = target {
+            if let Some(change) = self.remove(uri) {
+                changes.push((uri.clone(), change));
+            }
+        }
+        changes
+    }
+
+    fn remove(&mut self, uri: &Uri) -> Option<ChangeContext> {
+        self.entries.remove(uri).map(|(change, _)| change)
+    }
+}
+
 #[derive(Default)]
 struct DiagnosticsLatency {
     compile_samples_ms: VecDeque<f64>,
@@ -242,6 +377,7 @@ fn handle_notification(
     not: &Notification,
     connection: &Connection,
     cache: &mut CompilationCache,
+    pending: &mut PendingReindex,
     diagnostics_latency: &mut DiagnosticsLatency,
 ) {
     if not.method == notification::DidOpenTextDocument::METHOD {
@@ -249,6 +385,7 @@ fn handle_notification(
         {
             let uri = params.text_document.uri.clone();
             let text = params.text_document.text.clone();
+            pending.remove(&uri);
 
             process_document(
                 &uri,
@@ -268,17 +405,12 @@ fn handle_notification(
             let previous_text = cache.get_text(&uri).cloned().unwrap_or_default();
             let content_changes = params.content_changes;
 
+            // Keep the stored text current on every edit; compiling and
+            // re-indexing waits until the edits settle.
             match apply_text_document_changes(&previous_text, &content_changes) {
                 Some((text, change_context)) => {
-                    process_document(
-                        &uri,
-                        &text,
-                        cache,
-                        connection,
-                        DocumentEvent::Change,
-                        Some(change_context),
-                        diagnostics_latency,
-                    );
+                    cache.update_text_only(&uri, text);
+                    pending.schedule(uri, change_context, Instant::now());
                 }
                 None => {
                     eprintln!(
@@ -298,15 +430,8 @@ fn handle_notification(
                             ),
                         };
 
-                        process_document(
-                            &uri,
-                            &full_change.text,
-                            cache,
-                            connection,
-                            DocumentEvent::Change,
-                            Some(fallback_context),
-                            diagnostics_latency,
-                        );
+                        cache.update_text_only(&uri, full_change.text.clone());
+                        pending.schedule(uri, fallback_context, Instant::now());
                     }
                 }
             }
@@ -315,6 +440,7 @@ fn handle_notification(
         if let Ok(params) = serde_json::from_value::<DidSaveTextDocumentParams>(not.params.clone())
         {
             let uri = params.text_document.uri.clone();
+            pending.remove(&uri);
 
             // Re-run full compilation on save to ensure fresh diagnostics
             if let Some(text) = cache.get_text(&uri) {
@@ -333,6 +459,27 @@ fn handle_notification(
     }
 }
 
+/// Re-index a document after its debounce window, using the stored text.
+fn reindex_document(
+    uri: &Uri,
+    change: ChangeContext,
+    cache: &mut CompilationCache,
+    connection: &Connection,
+    diagnostics_latency: &mut DiagnosticsLatency,
+) {
+    if let Some(text) = cache.get_text(uri).cloned() {
+        process_document(
+            uri,
+            &text,
+            cache,
+            connection,
+            DocumentEvent::Change,
+            Some(change),
+            diagnostics_latency,
+        );
+    }
+}
+
 /// Process a document: compile it and publish diagnostics
 fn process_document(
     uri: &Uri,
@@ -362,25 +509,22 @@ fn process_document(
         return;
     }
 
-    // Run full compilation
-    let compile_result = if is_markdown {
-        lumen_compiler::compile(text)
-    } else {
-        lumen_compiler::compile_raw(text)
-    };
-
-    let diagnostics = match &compile_result {
-        Ok(_) => vec![],
-        Err(err) => diagnostics::compile_error_to_diagnostics(err, text),
-    };
+    // Run full compilation, including warnings from ownership analysis
+    let diagnostics = diagnostics::diagnose(text, is_markdown);
     let diagnostics_for_cache = diagnostics.clone();
 
     // Publish diagnostics
     publish_diagnostics(connection, uri.clone(), diagnostics);
     diagnostics_latency.record(DiagnosticAction::Recompiled, event, started.elapsed());
 
-    // Try to parse for completion/hover even if full compilation failed
-    let (program, symbols) = parse_for_features(text, is_markdown);
+    // Try to parse for completion/hover even if full compilation failed.
+    // While the user is mid-edit (say, just typed `p.`) the text won't
+    // parse; keep the last good AST so completion can still resolve `p`.
+    let (mut program, mut symbols) = parse_for_features(text, is_markdown);
+    if program.is_none() {
+        program = cache.get_program(uri).cloned();
+        symbols = cache.get_symbols(uri).cloned();
+    }
 
     // Update cache
     cache.update(
@@ -484,10 +628,13 @@ fn apply_text_document_changes(
     Some((updated, context))
 }
 
+/// Map an LSP position (UTF-16 column) to a byte offset. `\n`, `\r\n` and
+/// `\r` all end a line; a column past the end of the line clamps to it, as
+/// the protocol specifies.
 fn lsp_position_to_byte_offset(text: &str, position: Position) -> Option<usize> {
     let line_start = line_start_offset(text, position.line)?;
     let line_end = text[line_start..]
-        .find('\n')
+        .find(['\r', '\n'])
         .map(|idx| line_start + idx)
         .unwrap_or(text.len());
     let line_slice = &text[line_start..line_end];
@@ -504,11 +651,7 @@ fn lsp_position_to_byte_offset(text: &str, position: Position) -> Option<usize>
         }
     }
 
-    if utf16_col == position.character {
-        Some(line_end)
-    } else {
-        None
-    }
+    Some(line_end)
 }
 
 fn line_start_offset(text: &str, line: u32) -> Option<usize> {
@@ -516,13 +659,23 @@ fn line_start_offset(text: &str, line: u32) -> Option<usize> {
         return Some(0);
     }
 
+    let bytes = text.as_bytes();
     let mut current_line = 0;
-    for (idx, ch) in text.char_indices() {
-        if ch == '\n' {
-            current_line += 1;
-            if current_line == line {
-                return Some(idx + 1);
-            }
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let break_len = match bytes[idx] {
+            b'\r' if bytes.get(idx + 1) == Some(&b'\n') => 2,
+            b'\r' | b'\n' => 1,
+            _ => 0,
+        };
+        if break_len == 0 {
+            idx += 1;
+            continue;
+        }
+        idx += break_len;
+        current_line += 1;
+        if current_line == line {
+            return Some(idx);
         }
     }
 
@@ -537,49 +690,37 @@ fn parse_for_features(
     Option<lumen_compiler::compiler::ast::Program>,
     Option<lumen_compiler::compiler::resolve::SymbolTable>,
 ) {
-    let (code, first_line, first_offset) = if is_markdown {
+    // Lex. Markdown blocks are lexed one at a time at their own line and
+    // byte offset, so spans in later blocks still point into the document.
+    let tokens = if is_markdown {
         let extracted = extract_blocks(text);
-        let mut full_code = String::new();
-        let mut first_block_line = 1;
-        let mut first_block_offset = 0;
+        if extracted
+            .code_blocks
+            .iter()
+            .all(|block| block.code.is_empty())
+        {
+            return (None, None);
+        }
 
+        let mut tokens = Vec::new();
+        let last = extracted.code_blocks.len() - 1;
         for (i, block) in extracted.code_blocks.iter().enumerate() {
-            if i == 0 {
-                first_block_line = block.code_start_line;
-                first_block_offset = block.code_offset;
-            }
-            if !full_code.is_empty() {
-                full_code.push('\n');
+            let mut lexer = Lexer::new(&block.code, block.code_start_line, block.code_offset);
+            let Ok(mut block_tokens) = lexer.tokenize() else {
+                return (None, None);
+            };
+            if i != last && matches!(block_tokens.last().map(|t| &t.kind), Some(TokenKind::Eof)) {
+                block_tokens.pop();
             }
-            full_code.push_str(&block.code);
+            tokens.extend(block_tokens);
         }
-
-        if full_code.is_empty() {
-            return (None, None);
-        }
-
-        // Extract directives
-        let _directives: Vec<lumen_compiler::compiler::ast::Directive> = extracted
-            .directives
-            .iter()
-            .map(|d| lumen_compiler::compiler::ast::Directive {
-                name: d.name.clone(),
-                value: d.value.clone(),
-                span: d.span,
-            })
-            .collect();
-
-        // Return code with directives
-        (full_code, first_block_line, first_block_offset)
+        tokens
     } else {
-        (text.to_string(), 1, 0)
-    };
-
-    // Lex
-    let mut lexer = Lexer::new(&code, first_line, first_offset);
-    let tokens = match lexer.tokenize() {
-        Ok(t) => t,
-        Err(_) => return (None, None),
+        let mut lexer = Lexer::new(text, 1, 0);
+        match lexer.tokenize() {
+            Ok(t) => t,
+            Err(_) => return (None, None),
+        }
     };
 
     // Parse (use empty directives for raw, we already extracted them for markdown)
@@ -621,7 +762,13 @@ fn publish_diagnostics(connection: &Connection, uri: Uri, diagnostics: Vec<Diagn
     let _ = connection.sender.send(Message::Notification(not));
 }
 
-fn handle_request(req: &Request, connection: &Connection, cache: &CompilationCache) {
+fn handle_request(
+    req: &Request,
+    connection: &Connection,
+    cache: &CompilationCache,
+    index: &mut WorkspaceIndex,
+    hint_options: inlay_hints::InlayHintOptions,
+) {
     match req.method.as_str() {
         request::GotoDefinition::METHOD => {
             if let Ok(params) = serde_json::from_value::<GotoDefinitionParams>(req.params.clone()) {
@@ -633,7 +780,9 @@ fn handle_request(req: &Request, connection: &Connection, cache: &CompilationCac
                 let text = cache.get_text(&uri).map(|s| s.as_str()).unwrap_or("");
                 let program = cache.get_program(&uri);
 
-                let result = goto_definition::build_goto_definition(params, text, program, &uri);
+                let result = goto_definition::build_goto_definition(
+                    params, text, program, &uri, cache, index,
+                );
 
                 let response = Response {
                     id: req.id.clone(),
@@ -715,10 +864,12 @@ fn handle_request(req: &Request, connection: &Connection, cache: &CompilationCac
         request::InlayHintRequest::METHOD => {
             if let Ok(params) = serde_json::from_value::<InlayHintParams>(req.params.clone()) {
                 let uri = &params.text_document.uri;
+                let text = cache.get_text(uri).map(|s| s.as_str()).unwrap_or("");
                 let program = cache.get_program(uri);
                 let symbols = cache.get_symbols(uri);
 
-                let result = inlay_hints::build_inlay_hints(params, program, symbols);
+                let result =
+                    inlay_hints::build_inlay_hints(params, text, program, symbols, hint_options);
 
                 let response = Response {
                     id: req.id.clone(),
@@ -814,9 +965,25 @@ fn handle_request(req: &Request, connection: &Connection, cache: &CompilationCac
             }
         }
         request::References::METHOD => {
+            let result =
+                if let Ok(params) = serde_json::from_value::<ReferenceParams>(req.params.clone()) {
+                    let uri = &params.text_document_position.text_document.uri;
+                    let text = cache.get_text(uri).map(|s| s.as_str()).unwrap_or("");
+                    let program = cache.get_program(uri);
+
+                    references::build_references(
+                        uri,
+                        text,
+                        params.text_document_position.position,
+                        program,
+                        params.context.include_declaration,
+                    )
+                } else {
+                    Vec::new()
+                };
             let response = Response {
                 id: req.id.clone(),
-                result: Some(serde_json::to_value(Vec::<Location>::new()).unwrap()),
+                result: Some(serde_json::to_value(result).unwrap()),
                 error: None,
             };
             let _ = connection.sender.send(Message::Response(response));
@@ -912,6 +1079,148 @@ mod tests {
         );
     }
 
+    #[test]
+    fn incremental_edit_sequence_matches_full_replacement() {
+        let original = "cell main() -> Int\n  let x = 1\n  return x\nend\n";
+        let expected = "# Adds one\ncell main() -> Int\n  let y = 41\n  return y + 1\nend\n";
+        let changes = vec![
+            ranged_change(1, 6, 1, 7, "y"),
+            ranged_change(1, 10, 1, 11, "41"),
+            ranged_change(2, 9, 2, 10, "y + 1"),
+            ranged_change(0, 0, 0, 0, "# Adds one\n"),
+        ];
+
+        let (incremental, context) = apply_text_document_changes(original, &changes).unwrap();
+        let full = vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: expected.to_string(),
+        }];
+        let (replaced, _) = apply_text_document_changes(original, &full).unwrap();
+
+        assert_eq!(incremental, expected);
+        assert_eq!(incremental, replaced);
+        assert_eq!(context.ranged_line_spans.len(), 4);
+        assert!(!context.saw_full_content_replace);
+
+        // The same edits one notification at a time give the same buffer
+        let stepwise = changes.iter().fold(original.to_string(), |text, change| {
+            apply_text_document_changes(&text, std::slice::from_ref(change))
+                .unwrap()
+                .0
+        });
+        assert_eq!(stepwise, expected);
+    }
+
+    #[test]
+    fn multi_line_ranges_replace_across_lines() {
+        let previous = "one\ntwo\nthree\n";
+        let changes = vec![ranged_change(0, 1, 2, 2, "X")];
+        let (updated, context) = apply_text_document_changes(previous, &changes).unwrap();
+        assert_eq!(updated, "oXree\n");
+        assert_eq!(
+            context.ranged_line_spans,
+            vec![EditLineSpan { start: 0, end: 2 }]
+        );
+    }
+
+    #[test]
+    fn crlf_line_breaks_are_single_line_ends() {
+        let previous = "cell main()\r\n  return 1\r\nend\r\n";
+        let changes = vec![
+            ranged_change(1, 9, 1, 10, "2"),
+            // Column past the end of a line clamps to before the `\r\n`
+            ranged_change(0, 40, 0, 40, " -> Int"),
+        ];
+        let (updated, _) = apply_text_document_changes(previous, &changes).unwrap();
+        assert_eq!(updated, "cell main() -> Int\r\n  return 2\r\nend\r\n");
+
+        assert_eq!(
+            lsp_position_to_byte_offset("a\rb\r\nc", Position::new(1, 0)),
+            Some(2)
+        );
+        assert_eq!(
+            lsp_position_to_byte_offset("a\rb\r\nc", Position::new(2, 0)),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn pending_reindex_debounces_and_merges_changes() {
+        let uri: Uri = "file:///doc.lm.md".parse().unwrap();
+        let start = Instant::now();
+        let mut pending = PendingReindex::default();
+
+        pending.schedule(
+            uri.clone(),
+            ChangeContext {
+                ranged_line_spans: vec![EditLineSpan { start: 3, end: 3 }],
+                ..ChangeContext::default()
+            },
+            start,
+        );
+        let later = start + REINDEX_DEBOUNCE / 2;
+        pending.schedule(
+            uri.clone(),
+            ChangeContext {
+                ranged_line_spans: vec![EditLineSpan { start: 9, end: 9 }],
+                inserted_key_path_markers: true,
+                ..ChangeContext::default()
+            },
+            later,
+        );
+
+        // The second edit pushed the deadline back
+        assert!(pending.take_due(start + REINDEX_DEBOUNCE).is_empty());
+        assert_eq!(pending.next_due(), Some(later + REINDEX_DEBOUNCE));
+
+        let due = pending.take_due(later + REINDEX_DEBOUNCE);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, uri);
+        assert_eq!(due[0].1.ranged_line_spans.len(), 2);
+        assert!(due[0].1.inserted_key_path_markers);
+        assert_eq!(pending.next_due(), None);
+    }
+
+    #[test]
+    fn requests_settle_only_their_own_document_before_the_deadline() {
+        let edited: Uri = "file:///edited.lm.md".parse().unwrap();
+        let other: Uri = "file:///other.lm.md".parse().unwrap();
+        let start = Instant::now();
+        let mut pending = PendingReindex::default();
+        pending.schedule(edited.clone(), ChangeContext::default(), start);
+        pending.schedule(other.clone(), ChangeContext::default(), start);
+
+        let completion = Request::new(
+            1.into(),
+            "textDocument/completion".to_string(),
+            serde_json::json!({
+                "textDocument": {"uri": "file:///edited.lm.md"},
+                "position": {"line": 0, "character": 0},
+            }),
+        );
+        let target = request_document(&completion);
+        assert_eq!(target.as_ref(), Some(&edited));
+
+        let settled = pending.take_for_request(target.as_ref(), start);
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].0, edited);
+
+        // A workspace-wide request leaves undue documents alone ...
+        let symbols = Request::new(
+            2.into(),
+            "workspace/symbol".to_string(),
+            serde_json::json!({"query": "main"}),
+        );
+        assert_eq!(request_document(&symbols), None);
+        assert!(pending.take_for_request(None, start).is_empty());
+
+        // ... until their deadline passes.
+        let settled = pending.take_for_request(None, start + REINDEX_DEBOUNCE);
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].0, other);
+    }
+
     #[test]
     fn utf16_positions_resolve_to_byte_offsets() {
         let text = "a🙂b\n";
@@ -0,0 +1,118 @@
+//! Find-references support for LSP
+//!
+//! Handles `textDocument/references`. The identifier under the cursor is
+//! resolved the same way rename resolves it, and its occurrences are collected
+//! from the parsed AST, so substrings, comments, and unrelated bindings with
+//! the same name are not reported.
+
+use crate::rename::{find_all_occurrences, identifier_at};
+use lsp_types::{Location, Position, Uri};
+use lumen_compiler::compiler::ast::Program;
+
+/// Locations of every reference to the symbol at `position`, including its
+/// declarations when `include_declaration` is set.
+pub fn build_references(
+    uri: &Uri,
+    text: &str,
+    position: Position,
+    program: Option<&Program>,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let (Some(word), Some(program)) = (identifier_at(text, position), program) else {
+        return Vec::new();
+    };
+
+    find_all_occurrences(text, position, &word, program)
+        .into_iter()
+        .filter(|occ| include_declaration || !occ.declaration)
+        .map(|occ| Location {
+            uri: uri.clone(),
+            range: occ.range(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lumen_compiler::compiler::lexer::Lexer;
+    use lumen_compiler::compiler::parser::Parser;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source, 1, 0).tokenize().unwrap();
+        Parser::new(tokens).parse_program(vec![]).unwrap()
+    }
+
+    fn references_at(source: &str, line: u32, character: u32, decl: bool) -> Vec<(u32, u32, u32)> {
+        let uri: Uri = "file:///test.lm".parse().unwrap();
+        let program = parse(source);
+        build_references(
+            &uri,
+            source,
+            Position { line, character },
+            Some(&program),
+            decl,
+        )
+        .into_iter()
+        .map(|loc| {
+            (
+                loc.range.start.line,
+                loc.range.start.character,
+                loc.range.end.character,
+            )
+        })
+        .collect()
+    }
+
+    #[test]
+    fn value_does_not_match_inside_values() {
+        let source = "cell main() -> Int\n  let value = 1\n  let values = [value, 2]\n  return value + len(values)\nend";
+        assert_eq!(
+            references_at(source, 1, 7, true),
+            vec![(1, 6, 11), (2, 16, 21), (3, 9, 14)]
+        );
+    }
+
+    #[test]
+    fn reference_in_nested_block_is_found() {
+        let source = "cell main(limit: Int) -> Int\n  let total = 0\n  for i in range(0, limit)\n    if i > 2\n      total = total + i\n    end\n  end\n  return total\nend";
+        let refs = references_at(source, 7, 10, true);
+        assert_eq!(refs, vec![(1, 6, 11), (4, 6, 11), (4, 14, 19), (7, 9, 14)]);
+
+        let without_decl = references_at(source, 7, 10, false);
+        assert!(
+            !without_decl.contains(&(1, 6, 11)),
+            "got {:?}",
+            without_decl
+        );
+        assert!(without_decl.contains(&(4, 14, 19)));
+    }
+
+    #[test]
+    fn binding_in_another_cell_is_not_a_reference() {
+        let source = "cell helper(value: Int) -> Int\n  return value\nend\n\ncell main() -> Int\n  let value = 2\n  return helper(value)\nend";
+        assert_eq!(
+            references_at(source, 6, 17, true),
+            vec![(5, 6, 11), (6, 16, 21)]
+        );
+    }
+
+    #[test]
+    fn cell_references_cover_definition_and_calls() {
+        let source = "cell twice(n: Int) -> Int\n  return n * 2\nend\n\ncell main() -> Int\n  return twice(twice(1))\nend";
+        assert_eq!(
+            references_at(source, 5, 10, true),
+            vec![(0, 5, 10), (5, 9, 14), (5, 15, 20)]
+        );
+        assert_eq!(
+            references_at(source, 5, 10, false),
+            vec![(5, 9, 14), (5, 15, 20)]
+        );
+    }
+
+    #[test]
+    fn keyword_has_no_references() {
+        let source = "cell main() -> Int\n  return 0\nend";
+        assert!(references_at(source, 0, 1, true).is_empty());
+    }
+}
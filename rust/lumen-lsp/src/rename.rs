@@ -2,7 +2,9 @@
 //!
 //! Handles `textDocument/rename` and `textDocument/prepareRename` requests.
 //! Finds all occurrences of an identifier within a single document and returns
-//! a `WorkspaceEdit` that replaces them all with the new name.
+//! a `WorkspaceEdit` that replaces them all with the new name. Keywords and
+//! builtins the document doesn't redefine can't be renamed, and the new name
+//! must be a valid identifier.
 
 use lsp_types::{Position, PrepareRenameResponse, Range, TextEdit, Uri, WorkspaceEdit};
 use lumen_compiler::compiler::ast::{
@@ -12,13 +14,42 @@ use lumen_compiler::compiler::tokens::Span;
 
 /// Information about a single occurrence of a symbol in the source text.
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct SymbolOccurrence {
+pub(crate) struct SymbolOccurrence {
     /// 0-based line
-    line: u32,
+    pub line: u32,
     /// 0-based start column (UTF-16)
-    start_char: u32,
+    pub start_char: u32,
     /// 0-based end column (UTF-16)
-    end_char: u32,
+    pub end_char: u32,
+    /// Whether this occurrence declares the symbol rather than using it
+    pub declaration: bool,
+}
+
+impl SymbolOccurrence {
+    pub fn range(&self) -> Range {
+        Range {
+            start: Position {
+                line: self.line,
+                character: self.start_char,
+            },
+            end: Position {
+                line: self.line,
+                character: self.end_char,
+            },
+        }
+    }
+
+    fn contains(&self, position: Position) -> bool {
+        self.line == position.line
+            && (self.start_char..=self.end_char).contains(&position.character)
+    }
+}
+
+/// Occurrences of a name inside one cell body, where a parameter or local
+/// binding may shadow the top-level symbol.
+struct CellScope {
+    occurrences: Vec<SymbolOccurrence>,
+    binds_name: bool,
 }
 
 /// Prepare rename: validates that the cursor is on a renameable symbol and
@@ -30,8 +61,7 @@ pub fn prepare_rename(
 ) -> Option<PrepareRenameResponse> {
     let word = extract_word_at_position(text, position)?;
 
-    // Don't rename keywords
-    if is_keyword(&word) {
+    if !is_renameable(&word, program) {
         return None;
     }
 
@@ -70,11 +100,11 @@ pub fn rename_symbol(
 ) -> Option<WorkspaceEdit> {
     let word = extract_word_at_position(text, position)?;
 
-    if is_keyword(&word) || new_name.is_empty() {
+    if !is_renameable(&word, program) || !is_identifier(new_name) || is_keyword(new_name) {
         return None;
     }
 
-    let occurrences = find_all_occurrences(text, &word, program);
+    let occurrences = find_all_occurrences(text, position, &word, program?);
     if occurrences.is_empty() {
         return None;
     }
@@ -82,16 +112,7 @@ pub fn rename_symbol(
     let edits: Vec<TextEdit> = occurrences
         .into_iter()
         .map(|occ| TextEdit {
-            range: Range {
-                start: Position {
-                    line: occ.line,
-                    character: occ.start_char,
-                },
-                end: Position {
-                    line: occ.line,
-                    character: occ.end_char,
-                },
-            },
+            range: occ.range(),
             new_text: new_name.to_string(),
         })
         .collect();
@@ -103,50 +124,92 @@ pub fn rename_symbol(
     })
 }
 
-/// Find all occurrences of the given identifier in the document.
-/// Uses the AST to locate semantically meaningful occurrences rather than
-/// blindly doing text search.
-fn find_all_occurrences(
+/// Find the occurrences of the identifier at `position` that refer to the
+/// same symbol: the binding it resolves to and every use of it.
+///
+/// A name bound by a parameter or local in the cell under the cursor resolves
+/// to that binding, so only that cell is searched. Otherwise the name refers
+/// to a top-level symbol and every cell that doesn't shadow it is searched.
+pub(crate) fn find_all_occurrences(
     text: &str,
+    position: Position,
     name: &str,
-    program: Option<&Program>,
+    program: &Program,
 ) -> Vec<SymbolOccurrence> {
-    let mut occurrences = Vec::new();
-
-    if let Some(prog) = program {
-        collect_occurrences_in_program(prog, name, &mut occurrences);
-    }
+    let lines: Vec<&str> = text.lines().collect();
+    let locate = |occurrences: Vec<SymbolOccurrence>| -> Vec<SymbolOccurrence> {
+        occurrences
+            .into_iter()
+            .filter_map(|occ| locate_name(&lines, occ, name))
+            .collect()
+    };
 
-    // Deduplicate by (line, start_char)
-    occurrences.sort_by(|a, b| a.line.cmp(&b.line).then(a.start_char.cmp(&b.start_char)));
-    occurrences.dedup();
+    let mut top_level = Vec::new();
+    let mut scopes = Vec::new();
+    collect_occurrences_in_program(program, name, &mut top_level, &mut scopes);
 
-    // If AST-based search finds nothing, fall back to text-based search
-    // for identifiers (whole-word matching)
-    if occurrences.is_empty() {
-        occurrences = find_text_occurrences(text, name);
+    let mut occurrences = Vec::new();
+    let mut local = None;
+    for scope in scopes {
+        let scope_occurrences = locate(scope.occurrences);
+        if !scope.binds_name {
+            occurrences.extend(scope_occurrences);
+        } else if scope_occurrences.iter().any(|occ| occ.contains(position)) {
+            local = Some(scope_occurrences);
+        }
     }
+    let mut occurrences = match local {
+        Some(local) => local,
+        None => {
+            occurrences.extend(locate(top_level));
+            occurrences
+        }
+    };
 
+    occurrences.sort_by(|a, b| a.line.cmp(&b.line).then(a.start_char.cmp(&b.start_char)));
+    occurrences.dedup_by(|later, earlier| {
+        let same = later.line == earlier.line && later.start_char == earlier.start_char;
+        if same {
+            earlier.declaration |= later.declaration;
+        }
+        same
+    });
     occurrences
 }
 
-/// Collect all AST-based occurrences of `name` in the program.
-fn collect_occurrences_in_program(prog: &Program, name: &str, out: &mut Vec<SymbolOccurrence>) {
+/// Collect all AST-based occurrences of `name` in the program: declarations
+/// of top-level symbols into `top_level`, and each cell body into its own
+/// scope.
+fn collect_occurrences_in_program(
+    prog: &Program,
+    name: &str,
+    top_level: &mut Vec<SymbolOccurrence>,
+    scopes: &mut Vec<CellScope>,
+) {
+    let mut cell_scope = |cell: &CellDef, top_level: &mut Vec<SymbolOccurrence>| {
+        if cell.name == name {
+            top_level.push(declaration_at(&cell.span, name));
+        }
+        let mut occurrences = Vec::new();
+        collect_occurrences_in_cell(cell, name, &mut occurrences);
+        scopes.push(CellScope {
+            occurrences,
+            binds_name: cell.params.iter().any(|p| p.name == name)
+                || stmts_declare_name(&cell.body, name),
+        });
+    };
+
     for item in &prog.items {
+        let out = &mut *top_level;
         match item {
-            Item::Cell(cell) => {
-                if cell.name == name {
-                    push_span_occurrence(&cell.span, name, &cell.name, out);
-                }
-                collect_occurrences_in_cell(cell, name, out);
-            }
+            Item::Cell(cell) => cell_scope(cell, out),
             Item::Record(record) => {
                 if record.name == name {
-                    push_span_occurrence(&record.span, name, &record.name, out);
+                    out.push(declaration_at(&record.span, name));
                 }
                 for field in &record.fields {
                     if field.name == name {
-                        push_span_occurrence(&field.span, name, &field.name, out);
+                        out.push(declaration_at(&field.span, name));
                     }
                     if let Some(default) = &field.default_value {
                         collect_occurrences_in_expr(default, name, out);
@@ -155,57 +218,51 @@ fn collect_occurrences_in_program(prog: &Program, name: &str, out: &mut Vec<Symb
             }
             Item::Enum(enum_def) => {
                 if enum_def.name == name {
-                    push_span_occurrence(&enum_def.span, name, &enum_def.name, out);
+                    out.push(declaration_at(&enum_def.span, name));
                 }
                 for variant in &enum_def.variants {
                     if variant.name == name {
-                        push_span_occurrence(&variant.span, name, &variant.name, out);
+                        out.push(declaration_at(&variant.span, name));
                     }
                 }
                 for method in &enum_def.methods {
-                    if method.name == name {
-                        push_span_occurrence(&method.span, name, &method.name, out);
-                    }
-                    collect_occurrences_in_cell(method, name, out);
+                    cell_scope(method, out);
                 }
             }
             Item::TypeAlias(alias) => {
                 if alias.name == name {
-                    push_span_occurrence(&alias.span, name, &alias.name, out);
+                    out.push(declaration_at(&alias.span, name));
                 }
             }
             Item::Process(process) => {
                 if process.name == name {
-                    push_span_occurrence(&process.span, name, &process.name, out);
+                    out.push(declaration_at(&process.span, name));
                 }
                 for cell in &process.cells {
-                    if cell.name == name {
-                        push_span_occurrence(&cell.span, name, &cell.name, out);
-                    }
-                    collect_occurrences_in_cell(cell, name, out);
+                    cell_scope(cell, out);
                 }
             }
             Item::Effect(effect) => {
                 if effect.name == name {
-                    push_span_occurrence(&effect.span, name, &effect.name, out);
+                    out.push(declaration_at(&effect.span, name));
                 }
             }
             Item::Handler(handler) => {
                 if handler.name == name {
-                    push_span_occurrence(&handler.span, name, &handler.name, out);
+                    out.push(declaration_at(&handler.span, name));
                 }
             }
             Item::Trait(trait_def) => {
                 if trait_def.name == name {
-                    push_span_occurrence(&trait_def.span, name, &trait_def.name, out);
+                    out.push(declaration_at(&trait_def.span, name));
                 }
             }
             Item::Impl(impl_def) => {
                 if impl_def.trait_name == name {
-                    push_span_occurrence(&impl_def.span, name, &impl_def.trait_name, out);
+                    out.push(span_to_occurrence(&impl_def.span, name));
                 }
                 for cell in &impl_def.cells {
-                    collect_occurrences_in_cell(cell, name, out);
+                    cell_scope(cell, out);
                 }
             }
             _ => {}
@@ -217,7 +274,7 @@ fn collect_occurrences_in_program(prog: &Program, name: &str, out: &mut Vec<Symb
 fn collect_occurrences_in_cell(cell: &CellDef, name: &str, out: &mut Vec<SymbolOccurrence>) {
     for param in &cell.params {
         if param.name == name {
-            push_span_occurrence(&param.span, name, &param.name, out);
+            out.push(declaration_at(&param.span, name));
         }
     }
     for stmt in &cell.body {
@@ -229,15 +286,13 @@ fn collect_occurrences_in_stmt(stmt: &Stmt, name: &str, out: &mut Vec<SymbolOccu
     match stmt {
         Stmt::Let(let_stmt) => {
             if let_stmt.name == name {
-                push_span_occurrence(&let_stmt.span, name, &let_stmt.name, out);
+                out.push(declaration_at(&let_stmt.span, name));
             }
             collect_occurrences_in_expr(&let_stmt.value, name, out);
         }
         Stmt::Assign(assign) => {
             if assign.target.as_variable() == Some(name) {
-                if let Some(var_name) = assign.target.as_variable() {
-                    push_span_occurrence(&assign.span, name, var_name, out);
-                }
+                out.push(span_to_occurrence(&assign.span, name));
             }
             collect_occurrences_in_expr(&assign.value, name, out);
         }
@@ -265,7 +320,7 @@ fn collect_occurrences_in_stmt(stmt: &Stmt, name: &str, out: &mut Vec<SymbolOccu
         }
         Stmt::For(for_stmt) => {
             if for_stmt.var == name {
-                push_span_occurrence(&for_stmt.span, name, &for_stmt.var, out);
+                out.push(declaration_at(&for_stmt.span, name));
             }
             collect_occurrences_in_expr(&for_stmt.iter, name, out);
             for s in &for_stmt.body {
@@ -286,9 +341,7 @@ fn collect_occurrences_in_stmt(stmt: &Stmt, name: &str, out: &mut Vec<SymbolOccu
         }
         Stmt::CompoundAssign(ca) => {
             if ca.target.as_variable() == Some(name) {
-                if let Some(var_name) = ca.target.as_variable() {
-                    push_span_occurrence(&ca.span, name, var_name, out);
-                }
+                out.push(span_to_occurrence(&ca.span, name));
             }
             collect_occurrences_in_expr(&ca.value, name, out);
         }
@@ -320,7 +373,7 @@ fn collect_occurrences_in_match_arm(arm: &MatchArm, name: &str, out: &mut Vec<Sy
 fn collect_occurrences_in_pattern(pat: &Pattern, name: &str, out: &mut Vec<SymbolOccurrence>) {
     match pat {
         Pattern::Ident(id, span) if id == name => {
-            out.push(span_to_occurrence(span, name));
+            out.push(declaration_at(span, name));
         }
         Pattern::Variant(vname, sub_pat, span) => {
             if vname == name {
@@ -428,7 +481,7 @@ fn collect_occurrences_in_expr(expr: &Expr, name: &str, out: &mut Vec<SymbolOccu
         Expr::Lambda { params, body, .. } => {
             for p in params {
                 if p.name == name {
-                    push_span_occurrence(&p.span, name, &p.name, out);
+                    out.push(declaration_at(&p.span, name));
                 }
             }
             match body {
@@ -509,68 +562,51 @@ fn collect_occurrences_in_expr(expr: &Expr, name: &str, out: &mut Vec<SymbolOccu
     }
 }
 
-/// Convert AST span to a SymbolOccurrence. The span's `col` tells us where the
-/// construct starts. For declaration items the name appears at a known offset
-/// inside the line, so we use text-search on the correct line as a fallback.
+/// Convert the span of a use of `name` to a SymbolOccurrence.
 fn span_to_occurrence(span: &Span, name: &str) -> SymbolOccurrence {
-    let line = if span.line > 0 {
-        (span.line - 1) as u32
-    } else {
-        0
-    };
-    let col = if span.col > 0 {
-        (span.col - 1) as u32
-    } else {
-        0
-    };
+    let line = span.line.saturating_sub(1) as u32;
+    let col = span.col.saturating_sub(1) as u32;
 
     SymbolOccurrence {
         line,
         start_char: col,
         end_char: col + name.len() as u32,
+        declaration: false,
     }
 }
 
-/// For declarations whose span covers the keyword (e.g. `cell foo`), the name
-/// is somewhere after the keyword. We approximate the name position using the
-/// item span offset.
-fn push_span_occurrence(
-    span: &Span,
-    _name: &str,
-    actual_name: &str,
-    out: &mut Vec<SymbolOccurrence>,
-) {
-    let _ = actual_name;
-    let occ = span_to_occurrence(span, _name);
-    out.push(occ);
+/// Convert the span of a construct declaring `name` to a SymbolOccurrence.
+/// The span starts at the construct (e.g. the `cell` keyword); the name is
+/// located from there by [`locate_name`].
+pub(crate) fn declaration_at(span: &Span, name: &str) -> SymbolOccurrence {
+    SymbolOccurrence {
+        declaration: true,
+        ..span_to_occurrence(span, name)
+    }
 }
 
-/// Text-based fallback: find all whole-word occurrences of `name`.
-fn find_text_occurrences(text: &str, name: &str) -> Vec<SymbolOccurrence> {
-    let mut occurrences = Vec::new();
-    for (line_idx, line) in text.lines().enumerate() {
-        let mut search_start = 0;
-        while let Some(pos) = line[search_start..].find(name) {
-            let abs_pos = search_start + pos;
-            let before_ok = abs_pos == 0
-                || !line.as_bytes()[abs_pos - 1].is_ascii_alphanumeric()
-                    && line.as_bytes()[abs_pos - 1] != b'_';
-            let after_pos = abs_pos + name.len();
-            let after_ok = after_pos >= line.len()
-                || !line.as_bytes()[after_pos].is_ascii_alphanumeric()
-                    && line.as_bytes()[after_pos] != b'_';
-
-            if before_ok && after_ok {
-                occurrences.push(SymbolOccurrence {
-                    line: line_idx as u32,
-                    start_char: abs_pos as u32,
-                    end_char: (abs_pos + name.len()) as u32,
-                });
-            }
-            search_start = abs_pos + name.len();
-        }
+/// Move a declaration occurrence onto the first `name` identifier at or after
+/// the start of its construct on the same line. Uses are already exact.
+pub(crate) fn locate_name(
+    lines: &[&str],
+    occ: SymbolOccurrence,
+    name: &str,
+) -> Option<SymbolOccurrence> {
+    if !occ.declaration {
+        return Some(occ);
     }
-    occurrences
+    let line = lines.get(occ.line as usize)?;
+    let from = occ.start_char as usize;
+    let start = line
+        .match_indices(name)
+        .map(|(start, _)| start)
+        .filter(|&start| start >= from)
+        .find(|&start| word_boundary(line, start) == Some((start, start + name.len())))?;
+    Some(SymbolOccurrence {
+        start_char: start as u32,
+        end_char: (start + name.len()) as u32,
+        ..occ
+    })
 }
 
 fn symbol_exists_in_program(prog: &Program, name: &str) -> bool {
@@ -668,6 +704,11 @@ fn expr_contains_name(expr: &Expr, name: &str) -> bool {
     }
 }
 
+/// The identifier under the cursor, if it is one (and not a keyword).
+pub(crate) fn identifier_at(text: &str, position: Position) -> Option<String> {
+    extract_word_at_position(text, position).filter(|word| is_identifier(word) && !is_keyword(word))
+}
+
 fn extract_word_at_position(text: &str, position: Position) -> Option<String> {
     let lines: Vec<&str> = text.lines().collect();
     let line = lines.get(position.line as usize)?;
@@ -701,6 +742,57 @@ fn word_boundary(line: &str, char_pos: usize) -> Option<(usize, usize)> {
     }
 }
 
+/// A word can be renamed if it is an identifier, not a keyword, and not a
+/// builtin function unless the program declares its own symbol of that name.
+fn is_renameable(word: &str, program: Option<&Program>) -> bool {
+    if !is_identifier(word) || is_keyword(word) {
+        return false;
+    }
+    if lumen_compiler::diagnostics::is_builtin_function(word) {
+        return program.is_some_and(|prog| declares_name(prog, word));
+    }
+    true
+}
+
+/// Whether the program declares `name` as an item, parameter, or binding
+/// (as opposed to only using it).
+fn declares_name(prog: &Program, name: &str) -> bool {
+    prog.items.iter().any(|item| match item {
+        Item::Cell(cell) => {
+            cell.name == name
+                || cell.params.iter().any(|p| p.name == name)
+                || stmts_declare_name(&cell.body, name)
+        }
+        Item::Record(record) => record.name == name,
+        Item::Enum(enum_def) => enum_def.name == name,
+        Item::TypeAlias(alias) => alias.name == name,
+        _ => false,
+    })
+}
+
+fn stmts_declare_name(stmts: &[Stmt], name: &str) -> bool {
+    stmts.iter().any(|stmt| match stmt {
+        Stmt::Let(let_stmt) => let_stmt.name == name,
+        Stmt::For(for_stmt) => for_stmt.var == name || stmts_declare_name(&for_stmt.body, name),
+        Stmt::If(if_stmt) => {
+            stmts_declare_name(&if_stmt.then_body, name)
+                || if_stmt
+                    .else_body
+                    .as_ref()
+                    .is_some_and(|body| stmts_declare_name(body, name))
+        }
+        Stmt::While(while_stmt) => stmts_declare_name(&while_stmt.body, name),
+        Stmt::Loop(loop_stmt) => stmts_declare_name(&loop_stmt.body, name),
+        _ => false,
+    })
+}
+
+fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
 fn is_keyword(word: &str) -> bool {
     matches!(
         word,
@@ -901,19 +993,87 @@ mod tests {
         assert!(result.is_none(), "Empty new name should be rejected");
     }
 
+    fn edit_ranges(edit: WorkspaceEdit, uri: &Uri) -> Vec<(u32, u32, u32)> {
+        let mut ranges: Vec<(u32, u32, u32)> = edit.changes.as_ref().unwrap()[uri]
+            .iter()
+            .map(|e| {
+                (
+                    e.range.start.line,
+                    e.range.start.character,
+                    e.range.end.character,
+                )
+            })
+            .collect();
+        ranges.sort();
+        ranges
+    }
+
     #[test]
-    fn test_find_text_occurrences_whole_word() {
-        let text = "let foo = 1\nlet foobar = foo + 2\nprint(foo)";
-        let occs = find_text_occurrences(text, "foo");
-        // Should find: line 0 col 4, line 1 col 15, line 2 col 6
-        assert_eq!(occs.len(), 3, "Should find 3 whole-word occurrences of foo");
-        // Should NOT match "foobar"
-        for occ in &occs {
-            assert_eq!(
-                (occ.end_char - occ.start_char) as usize,
-                3,
-                "Each occurrence should be exactly 3 chars"
-            );
+    fn test_rename_edits_cover_exactly_the_identifier() {
+        let source = "cell greet(name: String) -> String\n  return name\nend\n\ncell main() -> Int\n  let msg = greet(\"world\")\n  return 0\nend";
+        let program = parse_program(source);
+        let uri = make_uri();
+
+        let edit = rename_symbol(
+            &uri,
+            source,
+            Position {
+                line: 5,
+                character: 13,
+            },
+            "hello",
+            program.as_ref(),
+        )
+        .expect("greet should be renameable from its call site");
+        // The definition (not the `cell` keyword) and the call.
+        assert_eq!(edit_ranges(edit, &uri), vec![(0, 5, 10), (5, 12, 17)]);
+    }
+
+    #[test]
+    fn test_rename_builtin_rejected() {
+        let source = "cell main() -> Int\n  print(\"hi\")\n  return 0\nend";
+        let program = parse_program(source);
+        let at_print = Position {
+            line: 1,
+            character: 3,
+        };
+
+        assert!(prepare_rename(source, at_print, program.as_ref()).is_none());
+        let result = rename_symbol(&make_uri(), source, at_print, "show", program.as_ref());
+        assert!(result.is_none(), "Builtins should not be renameable");
+    }
+
+    #[test]
+    fn test_rename_to_invalid_name_rejected() {
+        let source = "cell main() -> Int\n  let count = 1\n  return count\nend";
+        let program = parse_program(source);
+        let at_count = Position {
+            line: 1,
+            character: 7,
+        };
+        for bad in ["1count", "my-count", "end", "two words"] {
+            let result = rename_symbol(&make_uri(), source, at_count, bad, program.as_ref());
+            assert!(result.is_none(), "'{}' should be rejected", bad);
         }
     }
+
+    #[test]
+    fn test_prepare_rename_rejects_non_identifiers() {
+        let source = "cell main() -> Int\n  return 42\nend";
+        let program = parse_program(source);
+
+        // On the number literal
+        let on_number = Position {
+            line: 1,
+            character: 10,
+        };
+        assert!(prepare_rename(source, on_number, program.as_ref()).is_none());
+
+        // On leading whitespace
+        let on_space = Position {
+            line: 1,
+            character: 0,
+        };
+        assert!(prepare_rename(source, on_space, program.as_ref()).is_none());
+    }
 }
@@ -2,7 +2,7 @@
 
 use lsp_types::{SemanticToken, SemanticTokens, SemanticTokensResult};
 use lumen_compiler::compiler::lexer::Lexer;
-use lumen_compiler::compiler::tokens::TokenKind;
+use lumen_compiler::compiler::tokens::{Token, TokenKind};
 use lumen_compiler::markdown::extract::extract_blocks;
 
 /// Token type indices (must match the legend in main.rs)
@@ -16,7 +16,6 @@ const TOKEN_TYPE_PARAMETER: u32 = 4;
 const TOKEN_TYPE_OPERATOR: u32 = 5;
 const TOKEN_TYPE_STRING: u32 = 6;
 const TOKEN_TYPE_NUMBER: u32 = 7;
-#[allow(dead_code)]
 const TOKEN_TYPE_COMMENT: u32 = 8;
 #[allow(dead_code)]
 const TOKEN_TYPE_ENUM_MEMBER: u32 = 9;
@@ -26,43 +25,63 @@ const TOKEN_TYPE_STRUCT: u32 = 10;
 const TOKEN_TYPE_ENUM: u32 = 11;
 const TOKEN_TYPE_DECORATOR: u32 = 12;
 
+/// A token before delta encoding: zero-based line, UTF-16 start column and
+/// UTF-16 length, all on a single line.
+struct AbsoluteToken {
+    line: u32,
+    start: u32,
+    length: u32,
+    token_type: u32,
+}
+
 pub fn build_semantic_tokens(text: &str, is_markdown: bool) -> Option<SemanticTokensResult> {
-    let (code, first_line, first_offset) = if is_markdown {
-        let extracted = extract_blocks(text);
-        let mut full_code = String::new();
-        let mut first_block_line = 1;
-        let mut first_block_offset = 0;
-
-        for (i, block) in extracted.code_blocks.iter().enumerate() {
-            if i == 0 {
-                first_block_line = block.code_start_line;
-                first_block_offset = block.code_offset;
-            }
-            if !full_code.is_empty() {
-                full_code.push('\n');
+    // Token spans are byte offsets into LF-normalized source
+    let normalized;
+    let text = if text.contains("\r\n") {
+        normalized = text.replace("\r\n", "\n");
+        normalized.as_str()
+    } else {
+        text
+    };
+    let line_starts = line_starts(text);
+    let mut absolute = Vec::new();
+    if is_markdown {
+        // Lex each fenced block on its own so every token keeps the line
+        // numbers of the block it came from.
+        for block in extract_blocks(text).code_blocks {
+            let mut lexer = Lexer::new(&block.code, block.code_start_line, block.code_offset);
+            if let Ok(tokens) = lexer.tokenize() {
+                collect_tokens(text, &line_starts, &tokens, &mut absolute);
             }
-            full_code.push_str(&block.code);
         }
-
-        if full_code.is_empty() {
-            return Some(SemanticTokensResult::Tokens(SemanticTokens {
-                result_id: None,
-                data: vec![],
-            }));
-        }
-
-        (full_code, first_block_line, first_block_offset)
     } else {
-        (text.to_string(), 1, 0)
-    };
+        let mut lexer = Lexer::new(text, 1, 0);
+        let tokens = lexer.tokenize().ok()?;
+        collect_tokens(text, &line_starts, &tokens, &mut absolute);
+    }
 
-    let mut lexer = Lexer::new(&code, first_line, first_offset);
-    let tokens = lexer.tokenize().ok()?;
+    Some(SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: None,
+        data: encode_tokens(absolute),
+    }))
+}
 
-    let mut semantic_tokens = Vec::new();
-    let mut prev_line = 0u32;
-    let mut prev_char = 0u32;
+/// Byte offset at which each line of `text` starts.
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
 
+/// Classify lexer tokens and record their absolute positions. Tokens that
+/// span several lines (docstrings, multi-line strings) are split into one
+/// entry per line, since clients need not support multi-line tokens.
+fn collect_tokens(
+    text: &str,
+    line_starts: &[usize],
+    tokens: &[Token],
+    out: &mut Vec<AbsoluteToken>,
+) {
     for token in tokens {
         let token_type = match &token.kind {
             // Keywords
@@ -157,79 +176,159 @@ pub fn build_semantic_tokens(text: &str, is_markdown: bool) -> Option<SemanticTo
             // Decorator (@)
             TokenKind::At => TOKEN_TYPE_DECORATOR,
 
-            // Markdown blocks map to comment tokens, with multi-line handling
-            TokenKind::MarkdownBlock(content) => {
-                let base_line = if token.span.line > 0 {
-                    (token.span.line - 1) as u32
-                } else {
-                    0
-                };
-                let base_char = token.span.start as u32;
-                let lines: Vec<&str> = content.split('\n').collect();
-
-                for (i, md_line) in lines.iter().enumerate() {
-                    let line = base_line + i as u32;
-                    let char_start = if i == 0 { base_char } else { 0 };
-                    let length = if md_line.is_empty() {
-                        1
-                    } else {
-                        md_line.len() as u32
-                    };
-
-                    let delta_line = line.saturating_sub(prev_line);
-                    let delta_char = if delta_line == 0 && char_start >= prev_char {
-                        char_start - prev_char
-                    } else {
-                        char_start
-                    };
-
-                    semantic_tokens.push(SemanticToken {
-                        delta_line,
-                        delta_start: delta_char,
-                        length,
-                        token_type: TOKEN_TYPE_COMMENT,
-                        token_modifiers_bitset: 0,
-                    });
-
-                    prev_line = line;
-                    prev_char = char_start;
-                }
-                continue;
-            }
+            // Markdown blocks map to comment tokens
+            TokenKind::MarkdownBlock(_) => TOKEN_TYPE_COMMENT,
 
             // Skip other tokens (punctuation, newlines, etc.)
             _ => continue,
         };
 
-        let line = if token.span.line > 0 {
-            (token.span.line - 1) as u32
-        } else {
-            0
-        };
-        let char = token.span.start as u32;
-        let length = (token.span.end - token.span.start).max(1) as u32;
-
-        let delta_line = line.saturating_sub(prev_line);
-        let delta_char = if delta_line == 0 && char >= prev_char {
-            char - prev_char
-        } else {
-            char
+        let Some(lexeme) = text.get(token.span.start..token.span.end) else {
+            continue;
         };
+        let first_line = token.span.line.saturating_sub(1) as u32;
+        let first_start = line_starts
+            .get(first_line as usize)
+            .map(|&start| {
+                text[start..]
+                    .chars()
+                    .take_while(|&c| c != '\n')
+                    .take(token.span.col.saturating_sub(1))
+                    .map(char::len_utf16)
+                    .sum::<usize>() as u32
+            })
+            .unwrap_or(0);
+        for (i, segment) in lexeme.split('\n').enumerate() {
+            let length = segment.encode_utf16().count() as u32;
+            if length > 0 {
+                out.push(AbsoluteToken {
+                    line: first_line + i as u32,
+                    start: if i == 0 { first_start } else { 0 },
+                    length,
+                    token_type,
+                });
+            }
+        }
+    }
+}
+
+/// Delta-encode tokens as the LSP wire format requires: each token's line is
+/// relative to the previous token's line, and its start is relative to the
+/// previous token's start when both are on the same line.
+fn encode_tokens(mut tokens: Vec<AbsoluteToken>) -> Vec<SemanticToken> {
+    tokens.sort_by_key(|t| (t.line, t.start));
+    let mut prev_line = 0;
+    let mut prev_start = 0;
+    tokens
+        .into_iter()
+        .map(|t| {
+            let delta_line = t.line - prev_line;
+            let delta_start = if delta_line == 0 {
+                t.start - prev_start
+            } else {
+                t.start
+            };
+            prev_line = t.line;
+            prev_start = t.start;
+            SemanticToken {
+                delta_line,
+                delta_start,
+                length: t.length,
+                token_type: t.token_type,
+                token_modifiers_bitset: 0,
+            }
+        })
+        .collect()
+}
 
-        semantic_tokens.push(SemanticToken {
-            delta_line,
-            delta_start: delta_char,
-            length,
-            token_type,
-            token_modifiers_bitset: 0,
-        });
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        prev_line = line;
-        prev_char = char;
+    fn data(text: &str, is_markdown: bool) -> Vec<SemanticToken> {
+        match build_semantic_tokens(text, is_markdown) {
+            Some(SemanticTokensResult::Tokens(tokens)) => tokens.data,
+            other => panic!("expected tokens, got {:?}", other),
+        }
     }
 
-    Some(SemanticTokensResult::Tokens(SemanticTokens {
-        result_id: None,
-        data: semantic_tokens,
-    }))
+    /// Decode back to (line, start, length, type) for readable assertions.
+    fn decode(data: &[SemanticToken]) -> Vec<(u32, u32, u32, u32)> {
+        let (mut line, mut start) = (0, 0);
+        data.iter()
+            .map(|t| {
+                if t.delta_line > 0 {
+                    start = 0;
+                }
+                line += t.delta_line;
+                start += t.delta_start;
+                (line, start, t.length, t.token_type)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keyword_length_is_its_lexeme_length() {
+        let tokens = data("cell main", false);
+        assert_eq!(tokens[0].length, 4);
+        assert_eq!(tokens[0].token_type, TOKEN_TYPE_KEYWORD);
+        assert_eq!(
+            decode(&tokens),
+            vec![
+                (0, 0, 4, TOKEN_TYPE_KEYWORD),
+                (0, 5, 4, TOKEN_TYPE_VARIABLE)
+            ]
+        );
+    }
+
+    #[test]
+    fn same_line_tokens_are_relative_to_previous_start() {
+        let tokens = data("cell main() -> Int\n  return 42\nend\n", false);
+        let delta: Vec<(u32, u32, u32)> = tokens
+            .iter()
+            .map(|t| (t.delta_line, t.delta_start, t.length))
+            .collect();
+        assert_eq!(
+            delta,
+            vec![
+                (0, 0, 4),  // cell
+                (0, 5, 4),  // main
+                (0, 10, 3), // Int
+                (1, 2, 6),  // return
+                (0, 7, 2),  // 42
+                (1, 0, 3),  // end
+            ]
+        );
+    }
+
+    #[test]
+    fn string_length_covers_quotes() {
+        let tokens = decode(&data("let s = \"héllo\"", false));
+        assert_eq!(tokens.last(), Some(&(0, 8, 7, TOKEN_TYPE_STRING)));
+    }
+
+    #[test]
+    fn later_line_starts_count_utf16_units() {
+        // `😀` is one char but two UTF-16 units, so `x` starts at column 15.
+        let tokens = decode(&data("let a = 1\nlet s = \"😀\" + x", false));
+        assert_eq!(tokens.last(), Some(&(1, 15, 1, TOKEN_TYPE_VARIABLE)));
+    }
+
+    #[test]
+    fn crlf_source_has_same_tokens_as_lf() {
+        let lf = "cell main() -> Int\n  return 42\nend\n";
+        assert_eq!(data(&lf.replace('\n', "\r\n"), false), data(lf, false));
+    }
+
+    #[test]
+    fn markdown_blocks_keep_their_own_lines() {
+        let text = "# A\n\n```lumen\ncell a() -> Int\n  return 1\nend\n```\n\nText\n\n```lumen\ncell b() -> Int\n  return 2\nend\n```\n";
+        let tokens = decode(&data(text, true));
+        let cells: Vec<(u32, u32, u32)> = tokens
+            .iter()
+            .filter(|t| t.2 == 4 && t.1 == 0)
+            .map(|t| (t.0, t.1, t.2))
+            .collect();
+        assert_eq!(cells, vec![(3, 0, 4), (11, 0, 4)]);
+    }
 }
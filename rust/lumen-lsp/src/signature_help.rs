@@ -6,7 +6,7 @@ use lsp_types::{
 };
 use lumen_compiler::compiler::ast::{Item, Program};
 
-use crate::hover::type_expr_to_string;
+use crate::hover::{cell_return_str, type_expr_to_string};
 
 pub fn build_signature_help(
     params: SignatureHelpParams,
@@ -21,7 +21,7 @@ pub fn build_signature_help(
     for item in &prog.items {
         if let Item::Cell(cell) = item {
             if cell.name == call_name {
-                return Some(build_cell_signature(cell, active_param));
+                return Some(build_cell_signature(cell, prog, active_param));
             }
         }
     }
@@ -78,6 +78,7 @@ fn find_call_context(text: &str, position: Position) -> Option<(String, u32)> {
 
 fn build_cell_signature(
     cell: &lumen_compiler::compiler::ast::CellDef,
+    program: &Program,
     active_param: u32,
 ) -> SignatureHelp {
     let params_str = cell
@@ -87,11 +88,7 @@ fn build_cell_signature(
         .collect::<Vec<_>>()
         .join(", ");
 
-    let return_str = cell
-        .return_type
-        .as_ref()
-        .map(|t| format!(" -> {}", type_expr_to_string(t)))
-        .unwrap_or_default();
+    let return_str = cell_return_str(cell, program);
 
     let effects_str = if !cell.effects.is_empty() {
         format!(" / {{{}}}", cell.effects.join(", "))
@@ -0,0 +1,98 @@
+//! Index of Lumen sources on disk, for features that cross file boundaries.
+//!
+//! Open documents live in the `CompilationCache`; files that are only
+//! referenced (for example through an import) are read and parsed here, and
+//! re-parsed when their modification time changes.
+
+use lsp_types::Uri;
+use lumen_compiler::compiler::ast::Program;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+struct IndexedFile {
+    modified: Option<SystemTime>,
+    program: Option<Program>,
+}
+
+#[derive(Default)]
+pub struct WorkspaceIndex {
+    files: HashMap<PathBuf, IndexedFile>,
+}
+
+impl WorkspaceIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The parsed program of the file at `path`, or `None` if it cannot be
+    /// read or parsed.
+    pub fn program(&mut self, path: &Path) -> Option<&Program> {
+        let modified = std::fs::metadata(path).ok()?.modified().ok();
+        let stale = self
+            .files
+            .get(path)
+            .is_none_or(|file| modified.is_none() || file.modified != modified);
+        if stale {
+            let text = std::fs::read_to_string(path).ok()?;
+            let is_markdown = path.to_string_lossy().ends_with(".md");
+            let (program, _) = crate::parse_for_features(&text, is_markdown);
+            self.files
+                .insert(path.to_path_buf(), IndexedFile { modified, program });
+        }
+        self.files.get(path)?.program.as_ref()
+    }
+}
+
+/// The filesystem path of a `file://` URI.
+pub fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme().map(|s| s.as_str()) != Some("file") {
+        return None;
+    }
+    let encoded = uri.path().as_str().as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        if encoded[i] == b'%' {
+            let hex = std::str::from_utf8(encoded.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(encoded[i]);
+            i += 1;
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// A `file://` URI for an absolute filesystem path.
+pub fn path_to_uri(path: &Path) -> Option<Uri> {
+    let mut uri = String::from("file://");
+    for byte in path.to_str()?.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_round_trip_through_uris() {
+        let path = Path::new("/tmp/my project/lib ü.lm");
+        let uri = path_to_uri(path).unwrap();
+        assert_eq!(uri.as_str(), "file:///tmp/my%20project/lib%20%C3%BC.lm");
+        assert_eq!(uri_to_path(&uri).unwrap(), path);
+    }
+
+    #[test]
+    fn non_file_uris_have_no_path() {
+        let uri: Uri = "untitled:Untitled-1".parse().unwrap();
+        assert!(uri_to_path(&uri).is_none());
+    }
+}
@@ -3,30 +3,49 @@
 //! Implements the `ToolProvider` trait to expose cryptographic operations as tools:
 //! - `crypto.sha256` — SHA-256 hash
 //! - `crypto.sha512` — SHA-512 hash
+//! - `crypto.sha3_256` — SHA3-256 hash
+//! - `crypto.sha3_512` — SHA3-512 hash
+//! - `crypto.blake3` — BLAKE3 hash (32-byte output)
 //! - `crypto.md5` — MD5 hash
 //! - `crypto.base64_encode` — Base64 encoding
 //! - `crypto.base64_decode` — Base64 decoding
 //! - `crypto.uuid` — Generate UUID v4
 //! - `crypto.random_int` — Random integer in range
+//! - `crypto.random_bytes` — Random bytes from the OS CSPRNG (hex or base64)
 //! - `crypto.hmac_sha256` — HMAC-SHA256
+//! - `crypto.hmac_sha256_verify` — Constant-time HMAC-SHA256 verification
+//! - `crypto.argon2_hash` — Argon2id password hash (PHC string)
+//! - `crypto.argon2_verify` — Verify a password against an Argon2 hash
+//! - `crypto.aes_gcm_encrypt` — AES-256-GCM authenticated encryption
+//! - `crypto.aes_gcm_decrypt` — AES-256-GCM authenticated decryption
 //! - `crypto.ed25519_keygen` — Generate Ed25519 keypair
 //! - `crypto.ed25519_sign` — Sign with Ed25519
 //! - `crypto.ed25519_verify` — Verify Ed25519 signature
 //!
-//! All hash operations return hexadecimal strings.
+//! All hash operations return hexadecimal strings, except the password
+//! hashing tools which produce and consume PHC-format strings.
 
 pub mod ed25519;
 pub use ed25519::Ed25519Provider;
 
+use aes_gcm::aead::AeadInPlace;
+use aes_gcm::{Aes256Gcm, Nonce, Tag};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
 use hmac::{Hmac, Mac};
 use lumen_runtime::tools::{ToolError, ToolProvider, ToolSchema};
 use md5::Md5;
-use rand::Rng;
+use rand::rngs::OsRng;
+use rand::{Rng, RngCore};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256, Sha512};
+use sha3::{Sha3_256, Sha3_512};
 use uuid::Uuid;
 
+/// Upper bound on the number of bytes `crypto.random_bytes` will generate.
+const MAX_RANDOM_BYTES: usize = 4096;
+
 // ---------------------------------------------------------------------------
 // CryptoTool enum
 // ---------------------------------------------------------------------------
@@ -35,12 +54,21 @@ use uuid::Uuid;
 enum CryptoTool {
     Sha256,
     Sha512,
+    Sha3_256,
+    Sha3_512,
+    Blake3,
     Md5,
     Base64Encode,
     Base64Decode,
     Uuid,
     RandomInt,
+    RandomBytes,
     HmacSha256,
+    HmacSha256Verify,
+    Argon2Hash,
+    Argon2Verify,
+    AesGcmEncrypt,
+    AesGcmDecrypt,
 }
 
 impl CryptoTool {
@@ -48,12 +76,21 @@ impl CryptoTool {
         match self {
             CryptoTool::Sha256 => "crypto.sha256",
             CryptoTool::Sha512 => "crypto.sha512",
+            CryptoTool::Sha3_256 => "crypto.sha3_256",
+            CryptoTool::Sha3_512 => "crypto.sha3_512",
+            CryptoTool::Blake3 => "crypto.blake3",
             CryptoTool::Md5 => "crypto.md5",
             CryptoTool::Base64Encode => "crypto.base64_encode",
             CryptoTool::Base64Decode => "crypto.base64_decode",
             CryptoTool::Uuid => "crypto.uuid",
             CryptoTool::RandomInt => "crypto.random_int",
+            CryptoTool::RandomBytes => "crypto.random_bytes",
             CryptoTool::HmacSha256 => "crypto.hmac_sha256",
+            CryptoTool::HmacSha256Verify => "crypto.hmac_sha256_verify",
+            CryptoTool::Argon2Hash => "crypto.argon2_hash",
+            CryptoTool::Argon2Verify => "crypto.argon2_verify",
+            CryptoTool::AesGcmEncrypt => "crypto.aes_gcm_encrypt",
+            CryptoTool::AesGcmDecrypt => "crypto.aes_gcm_decrypt",
         }
     }
 
@@ -61,12 +98,25 @@ impl CryptoTool {
         match self {
             CryptoTool::Sha256 => "Compute SHA-256 hash (returns hex string)",
             CryptoTool::Sha512 => "Compute SHA-512 hash (returns hex string)",
+            CryptoTool::Sha3_256 => "Compute SHA3-256 hash (returns hex string)",
+            CryptoTool::Sha3_512 => "Compute SHA3-512 hash (returns hex string)",
+            CryptoTool::Blake3 => "Compute BLAKE3 hash (returns hex string)",
             CryptoTool::Md5 => "Compute MD5 hash (returns hex string)",
             CryptoTool::Base64Encode => "Encode string to base64",
             CryptoTool::Base64Decode => "Decode base64 string",
             CryptoTool::Uuid => "Generate a random UUID v4",
             CryptoTool::RandomInt => "Generate a random integer in the specified range (inclusive)",
+            CryptoTool::RandomBytes => {
+                "Generate cryptographically secure random bytes (hex or base64)"
+            }
             CryptoTool::HmacSha256 => "Compute HMAC-SHA256 (returns hex string)",
+            CryptoTool::HmacSha256Verify => "Verify a hex HMAC-SHA256 tag in constant time",
+            CryptoTool::Argon2Hash => "Hash a password with Argon2id (returns PHC string)",
+            CryptoTool::Argon2Verify => "Verify a password against an Argon2 PHC hash",
+            CryptoTool::AesGcmEncrypt => {
+                "Encrypt with AES-256-GCM (returns base64 nonce, ciphertext and tag)"
+            }
+            CryptoTool::AesGcmDecrypt => "Decrypt and authenticate AES-256-GCM ciphertext",
         }
     }
 }
@@ -85,7 +135,12 @@ impl CryptoProvider {
     /// Create a new crypto provider for the given tool.
     fn new(tool: CryptoTool) -> Self {
         let (input_schema, output_schema) = match tool {
-            CryptoTool::Sha256 | CryptoTool::Sha512 | CryptoTool::Md5 => (
+            CryptoTool::Sha256
+            | CryptoTool::Sha512
+            | CryptoTool::Sha3_256
+            | CryptoTool::Sha3_512
+            | CryptoTool::Blake3
+            | CryptoTool::Md5 => (
                 json!({
                     "type": "object",
                     "required": ["input"],
@@ -163,6 +218,27 @@ impl CryptoProvider {
                     "description": "Random integer in range [min, max]"
                 }),
             ),
+            CryptoTool::RandomBytes => (
+                json!({
+                    "type": "object",
+                    "required": ["length"],
+                    "properties": {
+                        "length": {
+                            "type": "number",
+                            "description": "Number of bytes to generate (1-4096)"
+                        },
+                        "encoding": {
+                            "type": "string",
+                            "enum": ["hex", "base64"],
+                            "description": "Output encoding (default: hex)"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "string",
+                    "description": "Random bytes in the requested encoding"
+                }),
+            ),
             CryptoTool::HmacSha256 => (
                 json!({
                     "type": "object",
@@ -183,6 +259,138 @@ impl CryptoProvider {
                     "description": "Hex-encoded HMAC"
                 }),
             ),
+            CryptoTool::HmacSha256Verify => (
+                json!({
+                    "type": "object",
+                    "required": ["message", "key", "expected"],
+                    "properties": {
+                        "message": {
+                            "type": "string",
+                            "description": "Message that was authenticated"
+                        },
+                        "key": {
+                            "type": "string",
+                            "description": "Secret key"
+                        },
+                        "expected": {
+                            "type": "string",
+                            "description": "Hex-encoded HMAC to check"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "boolean",
+                    "description": "True if the HMAC matches"
+                }),
+            ),
+            CryptoTool::Argon2Hash => (
+                json!({
+                    "type": "object",
+                    "required": ["password"],
+                    "properties": {
+                        "password": {
+                            "type": "string",
+                            "description": "Password to hash"
+                        },
+                        "memory_kib": {
+                            "type": "number",
+                            "description": "Memory cost in KiB (default: 19456)"
+                        },
+                        "iterations": {
+                            "type": "number",
+                            "description": "Number of passes (default: 2)"
+                        },
+                        "parallelism": {
+                            "type": "number",
+                            "description": "Degree of parallelism (default: 1)"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "string",
+                    "description": "PHC-format hash ($argon2id$...)"
+                }),
+            ),
+            CryptoTool::Argon2Verify => (
+                json!({
+                    "type": "object",
+                    "required": ["password", "hash"],
+                    "properties": {
+                        "password": {
+                            "type": "string",
+                            "description": "Password to check"
+                        },
+                        "hash": {
+                            "type": "string",
+                            "description": "PHC-format hash produced by crypto.argon2_hash"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "boolean",
+                    "description": "Whether the password matches the hash"
+                }),
+            ),
+            CryptoTool::AesGcmEncrypt => (
+                json!({
+                    "type": "object",
+                    "required": ["plaintext", "key"],
+                    "properties": {
+                        "plaintext": {
+                            "type": "string",
+                            "description": "Text to encrypt"
+                        },
+                        "key": {
+                            "type": "string",
+                            "description": "32-byte key, hex or base64 encoded"
+                        },
+                        "aad": {
+                            "type": "string",
+                            "description": "Additional authenticated data (optional)"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "nonce": { "type": "string", "description": "Base64 96-bit nonce" },
+                        "ciphertext": { "type": "string", "description": "Base64 ciphertext" },
+                        "tag": { "type": "string", "description": "Base64 authentication tag" }
+                    }
+                }),
+            ),
+            CryptoTool::AesGcmDecrypt => (
+                json!({
+                    "type": "object",
+                    "required": ["nonce", "ciphertext", "tag", "key"],
+                    "properties": {
+                        "nonce": {
+                            "type": "string",
+                            "description": "Base64 nonce from crypto.aes_gcm_encrypt"
+                        },
+                        "ciphertext": {
+                            "type": "string",
+                            "description": "Base64 ciphertext from crypto.aes_gcm_encrypt"
+                        },
+                        "tag": {
+                            "type": "string",
+                            "description": "Base64 tag from crypto.aes_gcm_encrypt"
+                        },
+                        "key": {
+                            "type": "string",
+                            "description": "32-byte key, hex or base64 encoded"
+                        },
+                        "aad": {
+                            "type": "string",
+                            "description": "Additional authenticated data used at encryption (optional)"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "string",
+                    "description": "Decrypted plaintext"
+                }),
+            ),
         };
 
         let schema = ToolSchema {
@@ -206,6 +414,21 @@ impl CryptoProvider {
         Self::new(CryptoTool::Sha512)
     }
 
+    /// Create a SHA3-256 provider.
+    pub fn sha3_256() -> Self {
+        Self::new(CryptoTool::Sha3_256)
+    }
+
+    /// Create a SHA3-512 provider.
+    pub fn sha3_512() -> Self {
+        Self::new(CryptoTool::Sha3_512)
+    }
+
+    /// Create a BLAKE3 provider.
+    pub fn blake3() -> Self {
+        Self::new(CryptoTool::Blake3)
+    }
+
     /// Create an MD5 provider.
     pub fn md5() -> Self {
         Self::new(CryptoTool::Md5)
@@ -231,11 +454,41 @@ impl CryptoProvider {
         Self::new(CryptoTool::RandomInt)
     }
 
+    /// Create a random bytes provider.
+    pub fn random_bytes() -> Self {
+        Self::new(CryptoTool::RandomBytes)
+    }
+
     /// Create an HMAC-SHA256 provider.
     pub fn hmac_sha256() -> Self {
         Self::new(CryptoTool::HmacSha256)
     }
 
+    /// Create a constant-time HMAC-SHA256 verification provider.
+    pub fn hmac_sha256_verify() -> Self {
+        Self::new(CryptoTool::HmacSha256Verify)
+    }
+
+    /// Create an Argon2id password hashing provider.
+    pub fn argon2_hash() -> Self {
+        Self::new(CryptoTool::Argon2Hash)
+    }
+
+    /// Create an Argon2 password verification provider.
+    pub fn argon2_verify() -> Self {
+        Self::new(CryptoTool::Argon2Verify)
+    }
+
+    /// Create an AES-256-GCM encryption provider.
+    pub fn aes_gcm_encrypt() -> Self {
+        Self::new(CryptoTool::AesGcmEncrypt)
+    }
+
+    /// Create an AES-256-GCM decryption provider.
+    pub fn aes_gcm_decrypt() -> Self {
+        Self::new(CryptoTool::AesGcmDecrypt)
+    }
+
     /// Execute the crypto operation.
     fn execute(&self, input: Value) -> Result<Value, ToolError> {
         match self.tool {
@@ -265,6 +518,43 @@ impl CryptoProvider {
                 let result = hasher.finalize();
                 Ok(json!(hex::encode(result)))
             }
+            CryptoTool::Sha3_256 => {
+                #[derive(Deserialize)]
+                struct HashInput {
+                    input: String,
+                }
+                let input: HashInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let mut hasher = Sha3_256::new();
+                hasher.update(input.input.as_bytes());
+                let result = hasher.finalize();
+                Ok(json!(hex::encode(result)))
+            }
+            CryptoTool::Sha3_512 => {
+                #[derive(Deserialize)]
+                struct HashInput {
+                    input: String,
+                }
+                let input: HashInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let mut hasher = Sha3_512::new();
+                hasher.update(input.input.as_bytes());
+                let result = hasher.finalize();
+                Ok(json!(hex::encode(result)))
+            }
+            CryptoTool::Blake3 => {
+                #[derive(Deserialize)]
+                struct HashInput {
+                    input: String,
+                }
+                let input: HashInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let result = blake3::hash(input.input.as_bytes());
+                Ok(json!(result.to_hex().to_string()))
+            }
             CryptoTool::Md5 => {
                 #[derive(Deserialize)]
                 struct HashInput {
@@ -332,6 +622,37 @@ impl CryptoProvider {
                 let value = rng.gen_range(input.min..=input.max);
                 Ok(json!(value))
             }
+            CryptoTool::RandomBytes => {
+                #[derive(Deserialize)]
+                struct RandomBytesInput {
+                    length: i64,
+                    encoding: Option<String>,
+                }
+                let input: RandomBytesInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                if !(1..=MAX_RANDOM_BYTES as i64).contains(&input.length) {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "length must be between 1 and {}, got {}",
+                        MAX_RANDOM_BYTES, input.length
+                    )));
+                }
+                let mut bytes = vec![0u8; input.length as usize];
+                OsRng.fill_bytes(&mut bytes);
+                match input.encoding.as_deref().unwrap_or("hex") {
+                    "hex" => Ok(json!(hex::encode(&bytes))),
+                    "base64" => {
+                        use base64::Engine;
+                        Ok(json!(
+                            base64::engine::general_purpose::STANDARD.encode(&bytes)
+                        ))
+                    }
+                    other => Err(ToolError::InvocationFailed(format!(
+                        "Unsupported encoding '{}': expected 'hex' or 'base64'",
+                        other
+                    ))),
+                }
+            }
             CryptoTool::HmacSha256 => {
                 #[derive(Deserialize)]
                 struct HmacInput {
@@ -348,10 +669,180 @@ impl CryptoProvider {
                 let result = mac.finalize();
                 Ok(json!(hex::encode(result.into_bytes())))
             }
+            CryptoTool::HmacSha256Verify => {
+                #[derive(Deserialize)]
+                struct HmacVerifyInput {
+                    message: String,
+                    key: String,
+                    expected: String,
+                }
+                let input: HmacVerifyInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let expected = hex::decode(&input.expected).map_err(|e| {
+                    ToolError::InvalidArgs(format!("expected is not valid hex: {}", e))
+                })?;
+                type HmacSha256 = Hmac<Sha256>;
+                let mut mac = HmacSha256::new_from_slice(input.key.as_bytes())
+                    .map_err(|e| ToolError::InvocationFailed(format!("Invalid HMAC key: {}", e)))?;
+                mac.update(input.message.as_bytes());
+                Ok(json!(mac.verify_slice(&expected).is_ok()))
+            }
+            CryptoTool::Argon2Hash => {
+                #[derive(Deserialize)]
+                struct Argon2HashInput {
+                    password: String,
+                    memory_kib: Option<u32>,
+                    iterations: Option<u32>,
+                    parallelism: Option<u32>,
+                }
+                let input: Argon2HashInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let params = Params::new(
+                    input.memory_kib.unwrap_or(Params::DEFAULT_M_COST),
+                    input.iterations.unwrap_or(Params::DEFAULT_T_COST),
+                    input.parallelism.unwrap_or(Params::DEFAULT_P_COST),
+                    None,
+                )
+                .map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid Argon2 parameters: {}", e))
+                })?;
+                let mut salt = [0u8; 16];
+                rand::thread_rng().fill(&mut salt);
+                let salt = SaltString::encode_b64(&salt).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Failed to encode salt: {}", e))
+                })?;
+                let hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password(input.password.as_bytes(), &salt)
+                    .map_err(|e| {
+                        ToolError::InvocationFailed(format!("Password hashing failed: {}", e))
+                    })?;
+                Ok(json!(hash.to_string()))
+            }
+            CryptoTool::Argon2Verify => {
+                #[derive(Deserialize)]
+                struct Argon2VerifyInput {
+                    password: String,
+                    hash: String,
+                }
+                let input: Argon2VerifyInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let parsed = PasswordHash::new(&input.hash).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid password hash: {}", e))
+                })?;
+                // Cost parameters are read from the hash itself.
+                let matches = Argon2::default()
+                    .verify_password(input.password.as_bytes(), &parsed)
+                    .is_ok();
+                Ok(json!(matches))
+            }
+            CryptoTool::AesGcmEncrypt => {
+                #[derive(Deserialize)]
+                struct EncryptInput {
+                    plaintext: String,
+                    key: String,
+                    aad: Option<String>,
+                }
+                let input: EncryptInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let cipher = aes_gcm_cipher(&input.key)?;
+                let mut nonce = [0u8; 12];
+                rand::thread_rng().fill(&mut nonce);
+                let mut buffer = input.plaintext.into_bytes();
+                let aad = input.aad.unwrap_or_default();
+                let tag = cipher
+                    .encrypt_in_place_detached(
+                        Nonce::from_slice(&nonce),
+                        aad.as_bytes(),
+                        &mut buffer,
+                    )
+                    .map_err(|_| ToolError::InvocationFailed("Encryption failed".into()))?;
+                use base64::Engine;
+                let b64 = base64::engine::general_purpose::STANDARD;
+                Ok(json!({
+                    "nonce": b64.encode(nonce),
+                    "ciphertext": b64.encode(&buffer),
+                    "tag": b64.encode(tag),
+                }))
+            }
+            CryptoTool::AesGcmDecrypt => {
+                #[derive(Deserialize)]
+                struct DecryptInput {
+                    nonce: String,
+                    ciphertext: String,
+                    tag: String,
+                    key: String,
+                    aad: Option<String>,
+                }
+                let input: DecryptInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let cipher = aes_gcm_cipher(&input.key)?;
+                let nonce = decode_base64_field("nonce", &input.nonce)?;
+                if nonce.len() != 12 {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "nonce must be 12 bytes, got {}",
+                        nonce.len()
+                    )));
+                }
+                let tag = decode_base64_field("tag", &input.tag)?;
+                if tag.len() != 16 {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "tag must be 16 bytes, got {}",
+                        tag.len()
+                    )));
+                }
+                let mut buffer = decode_base64_field("ciphertext", &input.ciphertext)?;
+                let aad = input.aad.unwrap_or_default();
+                cipher
+                    .decrypt_in_place_detached(
+                        Nonce::from_slice(&nonce),
+                        aad.as_bytes(),
+                        &mut buffer,
+                        Tag::from_slice(&tag),
+                    )
+                    .map_err(|_| {
+                        ToolError::InvocationFailed(
+                            "Authentication failed: ciphertext, tag, key or aad mismatch".into(),
+                        )
+                    })?;
+                let plaintext = String::from_utf8(buffer).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Decrypted data is not valid UTF-8: {}", e))
+                })?;
+                Ok(json!(plaintext))
+            }
         }
     }
 }
 
+/// Build an AES-256-GCM cipher from a hex (64 chars) or base64 encoded key.
+fn aes_gcm_cipher(key: &str) -> Result<Aes256Gcm, ToolError> {
+    let bytes = if key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex::decode(key)
+            .map_err(|e| ToolError::InvocationFailed(format!("Invalid hex key: {}", e)))?
+    } else {
+        decode_base64_field("key", key)?
+    };
+    if bytes.len() != 32 {
+        return Err(ToolError::InvocationFailed(format!(
+            "AES-256 key must be 32 bytes, got {}",
+            bytes.len()
+        )));
+    }
+    <Aes256Gcm as aes_gcm::KeyInit>::new_from_slice(&bytes)
+        .map_err(|e| ToolError::InvocationFailed(format!("Invalid key: {}", e)))
+}
+
+fn decode_base64_field(field: &str, value: &str) -> Result<Vec<u8>, ToolError> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(value.as_bytes())
+        .map_err(|e| ToolError::InvocationFailed(format!("Invalid base64 {}: {}", field, e)))
+}
+
 impl ToolProvider for CryptoProvider {
     fn name(&self) -> &str {
         &self.schema.name
@@ -393,12 +884,24 @@ mod tests {
         let providers = vec![
             (CryptoProvider::sha256(), "crypto.sha256"),
             (CryptoProvider::sha512(), "crypto.sha512"),
+            (CryptoProvider::sha3_256(), "crypto.sha3_256"),
+            (CryptoProvider::sha3_512(), "crypto.sha3_512"),
+            (CryptoProvider::blake3(), "crypto.blake3"),
             (CryptoProvider::md5(), "crypto.md5"),
             (CryptoProvider::base64_encode(), "crypto.base64_encode"),
             (CryptoProvider::base64_decode(), "crypto.base64_decode"),
             (CryptoProvider::uuid(), "crypto.uuid"),
             (CryptoProvider::random_int(), "crypto.random_int"),
+            (CryptoProvider::random_bytes(), "crypto.random_bytes"),
             (CryptoProvider::hmac_sha256(), "crypto.hmac_sha256"),
+            (
+                CryptoProvider::hmac_sha256_verify(),
+                "crypto.hmac_sha256_verify",
+            ),
+            (CryptoProvider::argon2_hash(), "crypto.argon2_hash"),
+            (CryptoProvider::argon2_verify(), "crypto.argon2_verify"),
+            (CryptoProvider::aes_gcm_encrypt(), "crypto.aes_gcm_encrypt"),
+            (CryptoProvider::aes_gcm_decrypt(), "crypto.aes_gcm_decrypt"),
         ];
 
         for (provider, expected_name) in providers {
@@ -432,6 +935,55 @@ mod tests {
             .starts_with("9b71d224bd62f3785d96d46ad3ea3d73"));
     }
 
+    #[test]
+    fn sha3_256_known_answers() {
+        let provider = CryptoProvider::sha3_256();
+        // FIPS 202 test vectors
+        let empty = provider.call(json!({"input": ""})).unwrap();
+        assert_eq!(
+            empty.as_str().unwrap(),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        let abc = provider.call(json!({"input": "abc"})).unwrap();
+        assert_eq!(
+            abc.as_str().unwrap(),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+    }
+
+    #[test]
+    fn sha3_512_known_answers() {
+        let provider = CryptoProvider::sha3_512();
+        let empty = provider.call(json!({"input": ""})).unwrap();
+        assert_eq!(
+            empty.as_str().unwrap(),
+            "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6\
+             15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26"
+        );
+        let abc = provider.call(json!({"input": "abc"})).unwrap();
+        assert_eq!(
+            abc.as_str().unwrap(),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+             10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+    }
+
+    #[test]
+    fn blake3_known_answers() {
+        let provider = CryptoProvider::blake3();
+        // Reference vectors from the BLAKE3 specification
+        let empty = provider.call(json!({"input": ""})).unwrap();
+        assert_eq!(
+            empty.as_str().unwrap(),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        let abc = provider.call(json!({"input": "abc"})).unwrap();
+        assert_eq!(
+            abc.as_str().unwrap(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
     #[test]
     fn md5_hash() {
         let provider = CryptoProvider::md5();
@@ -490,6 +1042,46 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn random_bytes_hex_length() {
+        let provider = CryptoProvider::random_bytes();
+        let result = provider.call(json!({"length": 16})).unwrap();
+        let encoded = result.as_str().unwrap();
+        assert_eq!(encoded.len(), 32);
+        assert_eq!(hex::decode(encoded).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn random_bytes_base64_length() {
+        use base64::Engine;
+        let provider = CryptoProvider::random_bytes();
+        let result = provider
+            .call(json!({"length": 24, "encoding": "base64"}))
+            .unwrap();
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(result.as_str().unwrap())
+            .unwrap();
+        assert_eq!(decoded.len(), 24);
+    }
+
+    #[test]
+    fn random_bytes_successive_calls_differ() {
+        let provider = CryptoProvider::random_bytes();
+        let a = provider.call(json!({"length": 32})).unwrap();
+        let b = provider.call(json!({"length": 32})).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn random_bytes_invalid_length() {
+        let provider = CryptoProvider::random_bytes();
+        assert!(provider.call(json!({"length": 0})).is_err());
+        assert!(provider.call(json!({"length": 4097})).is_err());
+        assert!(provider
+            .call(json!({"length": 8, "encoding": "base32"}))
+            .is_err());
+    }
+
     #[test]
     fn hmac_sha256() {
         let provider = CryptoProvider::hmac_sha256();
@@ -504,6 +1096,36 @@ mod tests {
         assert_eq!(result, result2);
     }
 
+    #[test]
+    fn hmac_sha256_verify() {
+        let tag = CryptoProvider::hmac_sha256()
+            .call(json!({"message": "hello", "key": "secret"}))
+            .unwrap();
+        let tag = tag.as_str().unwrap();
+        let provider = CryptoProvider::hmac_sha256_verify();
+
+        let ok = provider
+            .call(json!({"message": "hello", "key": "secret", "expected": tag}))
+            .unwrap();
+        assert_eq!(ok, json!(true));
+
+        let mut flipped = hex::decode(tag).unwrap();
+        flipped[0] ^= 0x01;
+        let bad = provider
+            .call(json!({
+                "message": "hello",
+                "key": "secret",
+                "expected": hex::encode(flipped)
+            }))
+            .unwrap();
+        assert_eq!(bad, json!(false));
+
+        let err = provider
+            .call(json!({"message": "hello", "key": "secret", "expected": "zz"}))
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+    }
+
     #[test]
     fn hmac_different_key_different_output() {
         let provider = CryptoProvider::hmac_sha256();
@@ -515,4 +1137,138 @@ mod tests {
             .unwrap();
         assert_ne!(result1, result2);
     }
+
+    #[test]
+    fn argon2_hash_and_verify() {
+        let hasher = CryptoProvider::argon2_hash();
+        let verifier = CryptoProvider::argon2_verify();
+        let hash = hasher
+            .call(json!({"password": "hunter2", "memory_kib": 1024, "iterations": 1}))
+            .unwrap();
+        let hash = hash.as_str().unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+
+        let ok = verifier
+            .call(json!({"password": "hunter2", "hash": hash}))
+            .unwrap();
+        assert_eq!(ok, json!(true));
+        let wrong = verifier
+            .call(json!({"password": "hunter3", "hash": hash}))
+            .unwrap();
+        assert_eq!(wrong, json!(false));
+    }
+
+    #[test]
+    fn argon2_hash_uses_default_params_and_random_salt() {
+        let provider = CryptoProvider::argon2_hash();
+        let first = provider.call(json!({"password": "secret"})).unwrap();
+        let second = provider.call(json!({"password": "secret"})).unwrap();
+        assert!(first
+            .as_str()
+            .unwrap()
+            .starts_with("$argon2id$v=19$m=19456,t=2,p=1$"));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn argon2_invalid_input() {
+        let hasher = CryptoProvider::argon2_hash();
+        assert!(hasher
+            .call(json!({"password": "x", "iterations": 0}))
+            .is_err());
+        let verifier = CryptoProvider::argon2_verify();
+        assert!(verifier
+            .call(json!({"password": "x", "hash": "not-a-hash"}))
+            .is_err());
+    }
+
+    const AES_KEY_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    fn aes_encrypt(plaintext: &str, aad: Option<&str>) -> Value {
+        let mut input = json!({"plaintext": plaintext, "key": AES_KEY_HEX});
+        if let Some(aad) = aad {
+            input["aad"] = json!(aad);
+        }
+        CryptoProvider::aes_gcm_encrypt().call(input).unwrap()
+    }
+
+    fn aes_decrypt(sealed: &Value, key: &str, aad: Option<&str>) -> Result<Value, ToolError> {
+        let mut input = sealed.clone();
+        input["key"] = json!(key);
+        if let Some(aad) = aad {
+            input["aad"] = json!(aad);
+        }
+        CryptoProvider::aes_gcm_decrypt().call(input)
+    }
+
+    #[test]
+    fn aes_gcm_round_trip() {
+        let sealed = aes_encrypt("top secret", None);
+        let b64 = base64::engine::general_purpose::STANDARD;
+        use base64::Engine;
+        assert_eq!(
+            b64.decode(sealed["nonce"].as_str().unwrap()).unwrap().len(),
+            12
+        );
+        assert_eq!(
+            b64.decode(sealed["tag"].as_str().unwrap()).unwrap().len(),
+            16
+        );
+        assert_ne!(sealed["ciphertext"], json!("top secret"));
+
+        let plain = aes_decrypt(&sealed, AES_KEY_HEX, None).unwrap();
+        assert_eq!(plain, json!("top secret"));
+
+        // The same key in base64 form is accepted too.
+        let key_b64 = b64.encode(hex::decode(AES_KEY_HEX).unwrap());
+        assert_eq!(
+            aes_decrypt(&sealed, &key_b64, None).unwrap(),
+            json!("top secret")
+        );
+    }
+
+    #[test]
+    fn aes_gcm_round_trip_with_aad() {
+        let sealed = aes_encrypt("payload", Some("header"));
+        assert_eq!(
+            aes_decrypt(&sealed, AES_KEY_HEX, Some("header")).unwrap(),
+            json!("payload")
+        );
+        assert!(aes_decrypt(&sealed, AES_KEY_HEX, Some("other")).is_err());
+        assert!(aes_decrypt(&sealed, AES_KEY_HEX, None).is_err());
+    }
+
+    #[test]
+    fn aes_gcm_nonces_are_random() {
+        let a = aes_encrypt("same", None);
+        let b = aes_encrypt("same", None);
+        assert_ne!(a["nonce"], b["nonce"]);
+        assert_ne!(a["ciphertext"], b["ciphertext"]);
+    }
+
+    #[test]
+    fn aes_gcm_tampered_ciphertext_fails() {
+        use base64::Engine;
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let mut sealed = aes_encrypt("do not touch", None);
+        let mut ciphertext = b64.decode(sealed["ciphertext"].as_str().unwrap()).unwrap();
+        ciphertext[0] ^= 0x01;
+        sealed["ciphertext"] = json!(b64.encode(ciphertext));
+        match aes_decrypt(&sealed, AES_KEY_HEX, None) {
+            Err(ToolError::InvocationFailed(msg)) => assert!(msg.contains("Authentication failed")),
+            other => panic!("expected authentication failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn aes_gcm_rejects_wrong_key_length() {
+        let result =
+            CryptoProvider::aes_gcm_encrypt().call(json!({"plaintext": "x", "key": "00112233"}));
+        match result {
+            Err(ToolError::InvocationFailed(msg)) => assert!(msg.contains("32 bytes")),
+            other => panic!("expected key length error, got {:?}", other),
+        }
+        let sealed = aes_encrypt("x", None);
+        assert!(aes_decrypt(&sealed, &AES_KEY_HEX[..62], None).is_err());
+    }
 }
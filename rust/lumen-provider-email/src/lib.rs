@@ -0,0 +1,721 @@
+//! Email (SMTP) provider for Lumen tool dispatch.
+//!
+//! Implements the `ToolProvider` trait to expose `email.send`, which builds a
+//! MIME message (plain-text or HTML body, optional base64 attachments) and
+//! delivers it over SMTP. Connections use implicit TLS, STARTTLS, or plaintext
+//! for trusted local relays, with optional authentication. Addresses are
+//! validated before any connection is made, and SMTP failures are normalized
+//! into structured `ToolError`s. The tool declares the `net` effect.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{Message, SmtpTransport, Transport};
+use lumen_runtime::tools::{ToolError, ToolProvider, ToolSchema};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsMode {
+    /// Plaintext only; for trusted local relays.
+    None,
+    /// Plaintext connection upgraded with a mandatory `STARTTLS`.
+    StartTls,
+    /// TLS from the first byte (SMTPS).
+    Tls,
+}
+
+impl TlsMode {
+    fn parse(value: &str) -> Result<Self, ToolError> {
+        match value.to_ascii_lowercase().as_str() {
+            "none" | "plain" => Ok(TlsMode::None),
+            "starttls" => Ok(TlsMode::StartTls),
+            "tls" | "smtps" => Ok(TlsMode::Tls),
+            other => Err(ToolError::InvalidArgs(format!(
+                "unknown tls mode '{}' (expected none, starttls or tls)",
+                other
+            ))),
+        }
+    }
+
+    fn default_port(&self) -> u16 {
+        match self {
+            TlsMode::None => 25,
+            TlsMode::StartTls => 587,
+            TlsMode::Tls => 465,
+        }
+    }
+}
+
+/// Connection settings for an SMTP server.
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Port; defaults to the standard port for the TLS mode.
+    pub port: Option<u16>,
+    pub tls: TlsMode,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Timeout for each SMTP command, in milliseconds.
+    pub timeout_ms: u64,
+}
+
+impl SmtpConfig {
+    /// Create a STARTTLS configuration without credentials.
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port: None,
+            tls: TlsMode::StartTls,
+            username: None,
+            password: None,
+            timeout_ms: 30_000,
+        }
+    }
+
+    /// Build a configuration from the environment.
+    ///
+    /// Reads `SMTP_HOST` and optionally `SMTP_PORT`, `SMTP_TLS`
+    /// (`none`/`starttls`/`tls`), `SMTP_USERNAME` and `SMTP_PASSWORD`.
+    pub fn from_env() -> Result<Self, ToolError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let host = var("SMTP_HOST")
+            .ok_or_else(|| ToolError::InvalidArgs("SMTP_HOST is not set".to_string()))?;
+        let mut config = Self::new(host);
+        if let Some(port) = var("SMTP_PORT") {
+            config.port = Some(port.parse().map_err(|_| {
+                ToolError::InvalidArgs(format!("SMTP_PORT is not a valid port: {}", port))
+            })?);
+        }
+        if let Some(tls) = var("SMTP_TLS") {
+            config.tls = TlsMode::parse(&tls)?;
+        }
+        config.username = var("SMTP_USERNAME");
+        config.password = var("SMTP_PASSWORD");
+        Ok(config)
+    }
+
+    /// Set the port.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Set the TLS mode.
+    pub fn with_tls(mut self, tls: TlsMode) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Authenticate with a username and password.
+    pub fn with_credentials(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.username = Some(username.into());
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Apply the per-call `smtp` object on top of these settings.
+    fn merged(&self, overrides: &SmtpOverrides) -> Result<Self, ToolError> {
+        let mut config = self.clone();
+        if let Some(host) = &overrides.host {
+            config.host = host.clone();
+        }
+        if let Some(port) = overrides.port {
+            config.port = Some(port);
+        }
+        if let Some(tls) = &overrides.tls {
+            config.tls = TlsMode::parse(tls)?;
+        }
+        if overrides.username.is_some() {
+            config.username = overrides.username.clone();
+        }
+        if overrides.password.is_some() {
+            config.password = overrides.password.clone();
+        }
+        if let Some(timeout_ms) = overrides.timeout_ms {
+            if timeout_ms == 0 {
+                return Err(ToolError::InvalidArgs(
+                    "smtp.timeout_ms must be positive".to_string(),
+                ));
+            }
+            config.timeout_ms = timeout_ms;
+        }
+        Ok(config)
+    }
+
+    fn transport(&self) -> Result<SmtpTransport, ToolError> {
+        if self.host.is_empty() {
+            return Err(ToolError::InvalidArgs("smtp.host cannot be empty".into()));
+        }
+        let tls_parameters = || {
+            TlsParameters::new(self.host.clone())
+                .map_err(|e| ToolError::InvalidArgs(format!("invalid TLS settings: {}", e)))
+        };
+        let tls = match self.tls {
+            TlsMode::None => Tls::None,
+            TlsMode::StartTls => Tls::Required(tls_parameters()?),
+            TlsMode::Tls => Tls::Wrapper(tls_parameters()?),
+        };
+
+        let mut builder = SmtpTransport::builder_dangerous(self.host.as_str())
+            .port(self.port.unwrap_or(self.tls.default_port()))
+            .tls(tls)
+            .timeout(Some(Duration::from_millis(self.timeout_ms)));
+        match (&self.username, &self.password) {
+            (Some(user), Some(pass)) => {
+                builder = builder.credentials(Credentials::new(user.clone(), pass.clone()));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(ToolError::InvalidArgs(
+                    "smtp.username and smtp.password must be given together".into(),
+                ))
+            }
+        }
+        Ok(builder.build())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Request schema
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Recipients {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Recipients {
+    fn addresses(&self) -> &[String] {
+        match self {
+            Recipients::One(addr) => std::slice::from_ref(addr),
+            Recipients::Many(addrs) => addrs,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AttachmentSpec {
+    filename: String,
+    #[serde(default)]
+    content_type: Option<String>,
+    content_base64: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SmtpOverrides {
+    #[serde(default)]
+    host: Option<String>,
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    tls: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendRequest {
+    to: Recipients,
+    #[serde(default)]
+    cc: Option<Recipients>,
+    #[serde(default)]
+    bcc: Option<Recipients>,
+    from: String,
+    #[serde(default)]
+    reply_to: Option<String>,
+    subject: String,
+    body: String,
+    #[serde(default)]
+    content_type: Option<String>,
+    #[serde(default)]
+    attachments: Vec<AttachmentSpec>,
+    #[serde(default)]
+    smtp: Option<SmtpOverrides>,
+}
+
+fn parse_mailbox(field: &str, addr: &str) -> Result<Mailbox, ToolError> {
+    addr.parse::<Mailbox>().map_err(|e| {
+        ToolError::InvalidArgs(format!("invalid '{}' address '{}': {}", field, addr, e))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// EmailProvider implementation
+// ---------------------------------------------------------------------------
+
+/// Email provider implementing the `ToolProvider` trait.
+pub struct EmailProvider {
+    schema: ToolSchema,
+    /// Connection settings used when a call omits (parts of) `smtp`.
+    defaults: Option<SmtpConfig>,
+}
+
+impl EmailProvider {
+    fn new(defaults: Option<SmtpConfig>) -> Self {
+        let schema = ToolSchema {
+            name: "email.send".to_string(),
+            description: "Send an email over SMTP".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "required": ["to", "from", "subject", "body"],
+                "properties": {
+                    "to": {
+                        "description": "Recipient address or list of addresses",
+                        "oneOf": [
+                            {"type": "string"},
+                            {"type": "array", "items": {"type": "string"}}
+                        ]
+                    },
+                    "cc": {"type": "array", "items": {"type": "string"}},
+                    "bcc": {"type": "array", "items": {"type": "string"}},
+                    "from": {"type": "string", "description": "Sender, e.g. \"Ada <ada@example.com>\""},
+                    "reply_to": {"type": "string"},
+                    "subject": {"type": "string"},
+                    "body": {"type": "string"},
+                    "content_type": {
+                        "type": "string",
+                        "enum": ["text/plain", "text/html"],
+                        "description": "Body format (default text/plain)"
+                    },
+                    "attachments": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["filename", "content_base64"],
+                            "properties": {
+                                "filename": {"type": "string"},
+                                "content_type": {"type": "string", "description": "Default application/octet-stream"},
+                                "content_base64": {"type": "string"}
+                            }
+                        }
+                    },
+                    "smtp": {
+                        "type": "object",
+                        "description": "SMTP connection settings; override the provider defaults",
+                        "properties": {
+                            "host": {"type": "string"},
+                            "port": {"type": "integer"},
+                            "tls": {"type": "string", "enum": ["none", "starttls", "tls"]},
+                            "username": {"type": "string"},
+                            "password": {"type": "string"},
+                            "timeout_ms": {"type": "integer", "minimum": 1}
+                        }
+                    }
+                }
+            }),
+            output_schema: json!({
+                "type": "object",
+                "required": ["status", "message", "message_id", "accepted"],
+                "properties": {
+                    "status": {"type": "integer", "description": "Final SMTP reply code"},
+                    "message": {"type": "string", "description": "Final SMTP reply text"},
+                    "message_id": {"type": "string"},
+                    "accepted": {"type": "array", "items": {"type": "string"}}
+                }
+            }),
+            effects: vec!["net".to_string()],
+        };
+
+        Self { schema, defaults }
+    }
+
+    /// Create an `email.send` provider; every call must supply `smtp.host`.
+    pub fn send() -> Self {
+        Self::new(None)
+    }
+
+    /// Create an `email.send` provider with default connection settings.
+    pub fn with_config(config: SmtpConfig) -> Self {
+        Self::new(Some(config))
+    }
+
+    fn build_message(request: &SendRequest) -> Result<(Message, Vec<String>), ToolError> {
+        let to = request.to.addresses();
+        if to.is_empty() {
+            return Err(ToolError::InvalidArgs(
+                "'to' needs at least one address".into(),
+            ));
+        }
+
+        let mut builder = Message::builder()
+            .from(parse_mailbox("from", &request.from)?)
+            .subject(request.subject.clone())
+            .message_id(None);
+        if let Some(reply_to) = &request.reply_to {
+            builder = builder.reply_to(parse_mailbox("reply_to", reply_to)?);
+        }
+
+        let mut accepted = Vec::new();
+        for addr in to {
+            let mailbox = parse_mailbox("to", addr)?;
+            accepted.push(mailbox.email.to_string());
+            builder = builder.to(mailbox);
+        }
+        if let Some(cc) = &request.cc {
+            for addr in cc.addresses() {
+                let mailbox = parse_mailbox("cc", addr)?;
+                accepted.push(mailbox.email.to_string());
+                builder = builder.cc(mailbox);
+            }
+        }
+        if let Some(bcc) = &request.bcc {
+            for addr in bcc.addresses() {
+                let mailbox = parse_mailbox("bcc", addr)?;
+                accepted.push(mailbox.email.to_string());
+                builder = builder.bcc(mailbox);
+            }
+        }
+
+        let body_type = match request.content_type.as_deref() {
+            None | Some("text/plain") => ContentType::TEXT_PLAIN,
+            Some("text/html") => ContentType::TEXT_HTML,
+            Some(other) => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "unsupported content_type '{}' (expected text/plain or text/html)",
+                    other
+                )))
+            }
+        };
+        let body = SinglePart::builder()
+            .header(body_type)
+            .body(request.body.clone());
+
+        let message = if request.attachments.is_empty() {
+            builder.singlepart(body)
+        } else {
+            let mut parts = MultiPart::mixed().singlepart(body);
+            for attachment in &request.attachments {
+                let bytes = BASE64.decode(&attachment.content_base64).map_err(|e| {
+                    ToolError::InvalidArgs(format!(
+                        "attachment '{}' is not valid base64: {}",
+                        attachment.filename, e
+                    ))
+                })?;
+                let content_type = attachment
+                    .content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream");
+                let content_type = ContentType::parse(content_type).map_err(|e| {
+                    ToolError::InvalidArgs(format!(
+                        "attachment '{}' has invalid content_type: {}",
+                        attachment.filename, e
+                    ))
+                })?;
+                parts = parts.singlepart(
+                    Attachment::new(attachment.filename.clone()).body(bytes, content_type),
+                );
+            }
+            builder.multipart(parts)
+        }
+        .map_err(|e| ToolError::InvalidArgs(format!("could not build message: {}", e)))?;
+
+        Ok((message, accepted))
+    }
+
+    fn exec_send(&self, input: Value) -> Result<Value, ToolError> {
+        let mut request: SendRequest = serde_json::from_value(input)
+            .map_err(|e| ToolError::InvalidArgs(format!("invalid email.send request: {}", e)))?;
+
+        // Validate everything before touching the network
+        let (message, accepted) = Self::build_message(&request)?;
+        let overrides = request.smtp.take().unwrap_or_default();
+        let config = match &self.defaults {
+            Some(defaults) => defaults.merged(&overrides)?,
+            None => {
+                let host = overrides.host.clone().ok_or_else(|| {
+                    ToolError::InvalidArgs("missing 'smtp.host' (no default SMTP server)".into())
+                })?;
+                SmtpConfig::new(host).merged(&overrides)?
+            }
+        };
+        let transport = config.transport()?;
+
+        let response = transport
+            .send(&message)
+            .map_err(|e| normalize_error(&e, config.timeout_ms))?;
+        let message_id = message
+            .headers()
+            .get_raw("Message-ID")
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(json!({
+            "status": u16::from(response.code()),
+            "message": response.message().collect::<Vec<_>>().join("\n"),
+            "message_id": message_id,
+            "accepted": accepted,
+        }))
+    }
+}
+
+impl ToolProvider for EmailProvider {
+    fn name(&self) -> &str {
+        &self.schema.name
+    }
+
+    fn version(&self) -> &str {
+        "0.1.0"
+    }
+
+    fn schema(&self) -> &ToolSchema {
+        &self.schema
+    }
+
+    fn call(&self, input: Value) -> Result<Value, ToolError> {
+        self.exec_send(input)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Error normalization
+// ---------------------------------------------------------------------------
+
+/// Normalize an SMTP failure into a structured `ToolError`.
+///
+/// The server's reply code decides when there is one: authentication codes
+/// become `AuthError`, rejected mailboxes `InvalidArgs`, and transient 4xx
+/// replies `ProviderUnavailable`. Connection and TLS failures are also
+/// reported as `ProviderUnavailable`.
+fn normalize_error(err: &lettre::transport::smtp::Error, timeout_ms: u64) -> ToolError {
+    if err.is_timeout() {
+        return ToolError::Timeout {
+            elapsed_ms: timeout_ms,
+            limit_ms: timeout_ms,
+        };
+    }
+    let detail = err.to_string();
+    match err.status().map(u16::from) {
+        Some(530 | 534 | 535 | 538) => ToolError::AuthError { message: detail },
+        Some(501 | 550 | 551 | 553) => {
+            ToolError::InvalidArgs(format!("SMTP server rejected the request: {}", detail))
+        }
+        Some(400..=499) => ToolError::ProviderUnavailable {
+            provider: "email".to_string(),
+            reason: detail,
+        },
+        Some(code) => ToolError::ExecutionFailed(format!("SMTP error {}: {}", code, detail)),
+        None if err.is_client() => ToolError::ExecutionFailed(format!("SMTP error: {}", detail)),
+        None => ToolError::ProviderUnavailable {
+            provider: "email".to_string(),
+            reason: detail,
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Serve one SMTP session on a loopback port, recording every line the
+    /// client sends. `accept_auth` decides the reply to `AUTH`.
+    fn mock_smtp_server(accept_auth: bool) -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let transcript = Arc::new(Mutex::new(Vec::new()));
+        let log = transcript.clone();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            writer.write_all(b"220 mock.test ESMTP\r\n").unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    break;
+                }
+                log.lock().unwrap().push(line.trim_end().to_string());
+                if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        writer.write_all(b"250 2.0.0 queued as 42\r\n").unwrap();
+                    }
+                    continue;
+                }
+                let verb = line.get(..4).unwrap_or("").to_ascii_uppercase();
+                let reply: &[u8] = match verb.as_str() {
+                    "EHLO" => b"250-mock.test\r\n250-AUTH PLAIN LOGIN\r\n250 8BITMIME\r\n",
+                    "AUTH" if accept_auth => b"235 2.7.0 Authentication successful\r\n",
+                    "AUTH" => b"535 5.7.8 Authentication credentials invalid\r\n",
+                    "MAIL" | "RCPT" | "RSET" | "NOOP" => b"250 2.1.0 OK\r\n",
+                    "DATA" => {
+                        in_data = true;
+                        b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                    }
+                    "QUIT" => {
+                        writer.write_all(b"221 2.0.0 Bye\r\n").unwrap();
+                        break;
+                    }
+                    _ => b"502 5.5.2 Command not recognized\r\n",
+                };
+                writer.write_all(reply).unwrap();
+            }
+        });
+        (port, transcript)
+    }
+
+    fn local_smtp(port: u16) -> Value {
+        json!({ "host": "127.0.0.1", "port": port, "tls": "none" })
+    }
+
+    #[test]
+    fn schema_declares_net_effect() {
+        let provider = EmailProvider::send();
+        assert_eq!(provider.name(), "email.send");
+        assert_eq!(provider.schema().effects, vec!["net".to_string()]);
+        let required = provider.schema().input_schema["required"]
+            .as_array()
+            .unwrap();
+        assert_eq!(required.len(), 4);
+    }
+
+    #[test]
+    fn successful_send_delivers_message() {
+        let (port, transcript) = mock_smtp_server(true);
+        let mut smtp = local_smtp(port);
+        smtp["username"] = json!("ada");
+        smtp["password"] = json!("secret");
+
+        let result = EmailProvider::send()
+            .call(json!({
+                "to": ["Grace <grace@example.com>"],
+                "cc": ["ops@example.com"],
+                "from": "Ada <ada@example.com>",
+                "subject": "Build finished",
+                "body": "<p>All green</p>",
+                "content_type": "text/html",
+                "attachments": [{
+                    "filename": "report.txt",
+                    "content_type": "text/plain",
+                    "content_base64": BASE64.encode("42 passed")
+                }],
+                "smtp": smtp
+            }))
+            .unwrap();
+
+        assert_eq!(result["status"], 250);
+        assert!(result["message"].as_str().unwrap().contains("queued"));
+        assert!(!result["message_id"].as_str().unwrap().is_empty());
+        assert_eq!(
+            result["accepted"],
+            json!(["grace@example.com", "ops@example.com"])
+        );
+
+        let lines = transcript.lock().unwrap().clone();
+        assert!(lines.iter().any(|l| l.starts_with("AUTH PLAIN")));
+        assert!(lines.iter().any(|l| l == "MAIL FROM:<ada@example.com>"));
+        assert!(lines.iter().any(|l| l == "RCPT TO:<grace@example.com>"));
+        assert!(lines.iter().any(|l| l == "RCPT TO:<ops@example.com>"));
+        assert!(lines.iter().any(|l| l == "Subject: Build finished"));
+        assert!(lines.iter().any(|l| l.contains("report.txt")));
+    }
+
+    #[test]
+    fn invalid_address_is_rejected_before_connecting() {
+        let result = EmailProvider::send().call(json!({
+            "to": "not-an-address",
+            "from": "ada@example.com",
+            "subject": "hi",
+            "body": "hello",
+            "smtp": {"host": "127.0.0.1", "port": 9, "tls": "none"}
+        }));
+        match result.unwrap_err() {
+            ToolError::InvalidArgs(msg) => assert!(msg.contains("not-an-address")),
+            other => panic!("Expected InvalidArgs, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn auth_failure_is_an_auth_error() {
+        let (port, _) = mock_smtp_server(false);
+        let mut smtp = local_smtp(port);
+        smtp["username"] = json!("ada");
+        smtp["password"] = json!("wrong");
+
+        let result = EmailProvider::send().call(json!({
+            "to": "grace@example.com",
+            "from": "ada@example.com",
+            "subject": "hi",
+            "body": "hello",
+            "smtp": smtp
+        }));
+        match result.unwrap_err() {
+            ToolError::AuthError { message } => {
+                assert!(message.contains("535") || message.contains("credentials"))
+            }
+            other => panic!("Expected AuthError, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn invalid_attachment_base64_is_rejected() {
+        let result = EmailProvider::send().call(json!({
+            "to": "grace@example.com",
+            "from": "ada@example.com",
+            "subject": "hi",
+            "body": "hello",
+            "attachments": [{"filename": "a.bin", "content_base64": "***"}],
+            "smtp": {"host": "127.0.0.1"}
+        }));
+        assert!(matches!(result, Err(ToolError::InvalidArgs(_))));
+    }
+
+    #[test]
+    fn missing_host_without_defaults_is_rejected() {
+        let result = EmailProvider::send().call(json!({
+            "to": "grace@example.com",
+            "from": "ada@example.com",
+            "subject": "hi",
+            "body": "hello"
+        }));
+        match result.unwrap_err() {
+            ToolError::InvalidArgs(msg) => assert!(msg.contains("smtp.host")),
+            other => panic!("Expected InvalidArgs, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn call_overrides_merge_onto_defaults() {
+        let defaults = SmtpConfig::new("smtp.example.com").with_credentials("ada", "secret");
+        let merged = defaults
+            .merged(&SmtpOverrides {
+                port: Some(2525),
+                tls: Some("tls".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(merged.host, "smtp.example.com");
+        assert_eq!(merged.port, Some(2525));
+        assert_eq!(merged.tls, TlsMode::Tls);
+        assert_eq!(merged.username.as_deref(), Some("ada"));
+        assert!(TlsMode::parse("bogus").is_err());
+        assert_eq!(TlsMode::StartTls.default_port(), 587);
+    }
+}
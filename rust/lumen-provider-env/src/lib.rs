@@ -3,6 +3,8 @@
 //! Implements the `ToolProvider` trait to expose environment operations as tools:
 //! - `env.get` — get environment variable
 //! - `env.set` — set environment variable
+//! - `env.unset` — remove environment variable
+//! - `env.expand` — substitute `$VAR` / `${VAR}` references in a string
 //! - `env.list` — list all environment variables
 //! - `env.has` — check if environment variable exists
 //! - `env.cwd` — get current working directory
@@ -26,6 +28,8 @@ use std::env;
 enum EnvTool {
     Get,
     Set,
+    Unset,
+    Expand,
     List,
     Has,
     Cwd,
@@ -39,6 +43,8 @@ impl EnvTool {
         match self {
             EnvTool::Get => "env.get",
             EnvTool::Set => "env.set",
+            EnvTool::Unset => "env.unset",
+            EnvTool::Expand => "env.expand",
             EnvTool::List => "env.list",
             EnvTool::Has => "env.has",
             EnvTool::Cwd => "env.cwd",
@@ -54,6 +60,8 @@ impl EnvTool {
                 "Get the value of an environment variable (returns empty string if not set)"
             }
             EnvTool::Set => "Set an environment variable for the current process",
+            EnvTool::Unset => "Remove an environment variable from the current process",
+            EnvTool::Expand => "Expand $VAR and ${VAR} references in a template string",
             EnvTool::List => "List all environment variables as key-value pairs",
             EnvTool::Has => "Check if an environment variable exists",
             EnvTool::Cwd => "Get the current working directory",
@@ -114,6 +122,43 @@ impl EnvProvider {
                     "description": "Always true (operation succeeded)"
                 }),
             ),
+            EnvTool::Unset => (
+                json!({
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Name of the environment variable"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "boolean",
+                    "description": "Always true (operation succeeded)"
+                }),
+            ),
+            EnvTool::Expand => (
+                json!({
+                    "type": "object",
+                    "required": ["template"],
+                    "properties": {
+                        "template": {
+                            "type": "string",
+                            "description": "String containing $VAR or ${VAR} references ($$ for a literal $)"
+                        },
+                        "on_missing": {
+                            "type": "string",
+                            "enum": ["empty", "keep"],
+                            "description": "Replace unset variables with an empty string (default) or keep the reference as written"
+                        }
+                    }
+                }),
+                json!({
+                    "type": "string",
+                    "description": "The template with variable references substituted"
+                }),
+            ),
             EnvTool::List => (
                 json!({
                     "type": "object",
@@ -205,6 +250,16 @@ impl EnvProvider {
         Self::new(EnvTool::Set)
     }
 
+    /// Create an UNSET provider.
+    pub fn unset() -> Self {
+        Self::new(EnvTool::Unset)
+    }
+
+    /// Create an EXPAND provider.
+    pub fn expand() -> Self {
+        Self::new(EnvTool::Expand)
+    }
+
     /// Create a LIST provider.
     pub fn list() -> Self {
         Self::new(EnvTool::List)
@@ -261,6 +316,45 @@ impl EnvProvider {
                 env::set_var(&input.name, &input.value);
                 Ok(json!(true))
             }
+            EnvTool::Unset => {
+                #[derive(Deserialize)]
+                struct UnsetInput {
+                    name: String,
+                }
+                let input: UnsetInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                if input.name.is_empty() || input.name.contains(['=', '\0']) {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "Invalid variable name {:?}: must be non-empty and contain no '=' or NUL",
+                        input.name
+                    )));
+                }
+                env::remove_var(&input.name);
+                Ok(json!(true))
+            }
+            EnvTool::Expand => {
+                #[derive(Deserialize)]
+                struct ExpandInput {
+                    template: String,
+                    #[serde(default)]
+                    on_missing: Option<String>,
+                }
+                let input: ExpandInput = serde_json::from_value(input).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid input format: {}", e))
+                })?;
+                let keep_missing = match input.on_missing.as_deref() {
+                    None | Some("empty") => false,
+                    Some("keep") => true,
+                    Some(other) => {
+                        return Err(ToolError::InvocationFailed(format!(
+                            "Invalid on_missing value '{}': expected \"empty\" or \"keep\"",
+                            other
+                        )))
+                    }
+                };
+                Ok(json!(expand_template(&input.template, keep_missing)))
+            }
             EnvTool::List => {
                 let vars: HashMap<String, String> = env::vars().collect();
                 Ok(serde_json::to_value(vars).unwrap())
@@ -309,6 +403,50 @@ impl EnvProvider {
     }
 }
 
+/// Substitute `$NAME` and `${NAME}` references with environment variables.
+///
+/// `$$` produces a literal `$`, and a `$` not followed by a variable name is
+/// left as is. Unset variables expand to an empty string, or are kept as
+/// written when `keep_missing` is set.
+fn expand_template(template: &str, keep_missing: bool) -> String {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (name, reference_len) = if let Some(escaped) = after.strip_prefix('$') {
+            out.push('$');
+            rest = escaped;
+            continue;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 3),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !is_name_char(c))
+                .unwrap_or(after.len());
+            (&after[..end], end + 1)
+        };
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        let reference = &rest[pos..pos + reference_len];
+        match env::var(name) {
+            Ok(value) => out.push_str(&value),
+            Err(_) if keep_missing => out.push_str(reference),
+            Err(_) => {}
+        }
+        rest = &rest[pos + reference_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
 impl ToolProvider for EnvProvider {
     fn name(&self) -> &str {
         &self.schema.name
@@ -350,6 +488,8 @@ mod tests {
         let providers = vec![
             (EnvProvider::get(), "env.get"),
             (EnvProvider::set(), "env.set"),
+            (EnvProvider::unset(), "env.unset"),
+            (EnvProvider::expand(), "env.expand"),
             (EnvProvider::list(), "env.list"),
             (EnvProvider::has(), "env.has"),
             (EnvProvider::cwd(), "env.cwd"),
@@ -395,6 +535,58 @@ mod tests {
         env::remove_var("TEST_VAR_SET");
     }
 
+    #[test]
+    fn env_unset() {
+        env::set_var("TEST_VAR_UNSET", "value");
+        let provider = EnvProvider::unset();
+        let result = provider.call(json!({"name": "TEST_VAR_UNSET"})).unwrap();
+        assert_eq!(result, json!(true));
+        assert!(env::var("TEST_VAR_UNSET").is_err());
+    }
+
+    #[test]
+    fn env_unset_rejects_invalid_names() {
+        let provider = EnvProvider::unset();
+        for name in ["", "A=B", "A\0B"] {
+            let err = provider.call(json!({ "name": name })).unwrap_err();
+            assert!(
+                matches!(err, ToolError::InvocationFailed(ref msg) if msg.contains("Invalid variable name")),
+                "{:?}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn env_expand_braced_and_bare_references() {
+        env::set_var("TEST_EXPAND_ROOT", "/opt/tool");
+        env::set_var("TEST_EXPAND_NAME", "lumen");
+        let provider = EnvProvider::expand();
+        let result = provider
+            .call(json!({"template": "${TEST_EXPAND_ROOT}/bin/$TEST_EXPAND_NAME-cli costs $$5"}))
+            .unwrap();
+        assert_eq!(result, json!("/opt/tool/bin/lumen-cli costs $5"));
+        env::remove_var("TEST_EXPAND_ROOT");
+        env::remove_var("TEST_EXPAND_NAME");
+    }
+
+    #[test]
+    fn env_expand_missing_variables() {
+        env::remove_var("TEST_EXPAND_MISSING");
+        let provider = EnvProvider::expand();
+        let template = "[${TEST_EXPAND_MISSING}|$TEST_EXPAND_MISSING|$ |${unclosed]";
+        let result = provider.call(json!({"template": template})).unwrap();
+        assert_eq!(result, json!("[||$ |${unclosed]"));
+
+        let result = provider
+            .call(json!({"template": template, "on_missing": "keep"}))
+            .unwrap();
+        assert_eq!(result, json!(template));
+
+        let err = provider.call(json!({"template": template, "on_missing": "fail"}));
+        assert!(err.is_err());
+    }
+
     #[test]
     fn env_has_existing() {
         env::set_var("TEST_VAR_HAS", "value");
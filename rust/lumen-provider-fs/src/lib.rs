@@ -3,14 +3,24 @@
 //! Implements the `ToolProvider` trait to expose filesystem operations as tools:
 //! - `fs.read` — Read file to string
 //! - `fs.write` — Write string to file
+//! - `fs.append` — Append string to file
+//! - `fs.read_bytes` — Read file as base64-encoded bytes
+//! - `fs.write_bytes` — Write base64-encoded bytes to file
 //! - `fs.exists` — Check if path exists
+//! - `fs.stat` — Get file metadata
 //! - `fs.list` — List directory entries
+//! - `fs.glob` — Find paths matching a glob pattern
 //! - `fs.mkdir` — Create directory (recursive)
-//! - `fs.remove` — Remove file or empty directory
+//! - `fs.remove` — Remove file or directory (recursively if requested)
+//! - `fs.copy` — Copy a file
+//! - `fs.move` — Move or rename a file or directory
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use lumen_runtime::tools::{ToolError, ToolProvider, ToolSchema};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::io::Write;
 use std::path::Path;
 
 // ---------------------------------------------------------------------------
@@ -21,10 +31,17 @@ use std::path::Path;
 enum FsOp {
     Read,
     Write,
+    Append,
+    ReadBytes,
+    WriteBytes,
     Exists,
+    Stat,
     List,
+    Glob,
     Mkdir,
     Remove,
+    Copy,
+    Move,
 }
 
 impl FsOp {
@@ -32,10 +49,17 @@ impl FsOp {
         match self {
             FsOp::Read => "fs.read",
             FsOp::Write => "fs.write",
+            FsOp::Append => "fs.append",
+            FsOp::ReadBytes => "fs.read_bytes",
+            FsOp::WriteBytes => "fs.write_bytes",
             FsOp::Exists => "fs.exists",
+            FsOp::Stat => "fs.stat",
             FsOp::List => "fs.list",
+            FsOp::Glob => "fs.glob",
             FsOp::Mkdir => "fs.mkdir",
             FsOp::Remove => "fs.remove",
+            FsOp::Copy => "fs.copy",
+            FsOp::Move => "fs.move",
         }
     }
 
@@ -43,10 +67,17 @@ impl FsOp {
         match self {
             FsOp::Read => "Read file contents as a string",
             FsOp::Write => "Write string content to a file",
+            FsOp::Append => "Append string content to a file, creating it if missing",
+            FsOp::ReadBytes => "Read file contents as base64-encoded bytes",
+            FsOp::WriteBytes => "Write base64-encoded bytes to a file",
             FsOp::Exists => "Check if a path exists",
+            FsOp::Stat => "Get file type, size, and modification time",
             FsOp::List => "List directory entries",
+            FsOp::Glob => "Find paths matching a glob pattern",
             FsOp::Mkdir => "Create directory recursively",
             FsOp::Remove => "Remove file or empty directory",
+            FsOp::Copy => "Copy a file",
+            FsOp::Move => "Move or rename a file or directory",
         }
     }
 }
@@ -71,6 +102,28 @@ struct PathRequest {
     path: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemoveRequest {
+    path: String,
+    #[serde(default)]
+    recursive: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GlobRequest {
+    pattern: String,
+    #[serde(default)]
+    include_dirs: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TransferRequest {
+    from: String,
+    to: String,
+    #[serde(default)]
+    overwrite: bool,
+}
+
 // ---------------------------------------------------------------------------
 // FsProvider implementation
 // ---------------------------------------------------------------------------
@@ -127,6 +180,71 @@ impl FsProvider {
                 }),
                 effects: vec!["fs".to_string()],
             },
+            FsOp::Append => ToolSchema {
+                name: op.tool_name().to_string(),
+                description: op.description().to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "required": ["path", "content"],
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Path to the file to append to"
+                        },
+                        "content": {
+                            "type": "string",
+                            "description": "Content to append to the file"
+                        }
+                    }
+                }),
+                output_schema: json!({
+                    "type": "boolean",
+                    "description": "True if append succeeded"
+                }),
+                effects: vec!["fs".to_string()],
+            },
+            FsOp::ReadBytes => ToolSchema {
+                name: op.tool_name().to_string(),
+                description: op.description().to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "required": ["path"],
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Path to the file to read"
+                        }
+                    }
+                }),
+                output_schema: json!({
+                    "type": "string",
+                    "description": "File content, base64-encoded"
+                }),
+                effects: vec!["fs".to_string()],
+            },
+            FsOp::WriteBytes => ToolSchema {
+                name: op.tool_name().to_string(),
+                description: op.description().to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "required": ["path", "content"],
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Path to the file to write"
+                        },
+                        "content": {
+                            "type": "string",
+                            "description": "Bytes to write, base64-encoded"
+                        }
+                    }
+                }),
+                output_schema: json!({
+                    "type": "boolean",
+                    "description": "True if write succeeded"
+                }),
+                effects: vec!["fs".to_string()],
+            },
             FsOp::Exists => ToolSchema {
                 name: op.tool_name().to_string(),
                 description: op.description().to_string(),
@@ -146,6 +264,34 @@ impl FsProvider {
                 }),
                 effects: vec!["fs".to_string()],
             },
+            FsOp::Stat => ToolSchema {
+                name: op.tool_name().to_string(),
+                description: op.description().to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "required": ["path"],
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Path to inspect (symlinks are not followed)"
+                        }
+                    }
+                }),
+                output_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "exists": { "type": "boolean" },
+                        "is_file": { "type": "boolean" },
+                        "is_dir": { "type": "boolean" },
+                        "is_symlink": { "type": "boolean" },
+                        "size": { "type": "integer" },
+                        "modified_unix_ms": { "type": ["integer", "null"] },
+                        "readonly": { "type": "boolean" }
+                    },
+                    "description": "File metadata, or {exists: false} if the path is missing"
+                }),
+                effects: vec!["fs".to_string()],
+            },
             FsOp::List => ToolSchema {
                 name: op.tool_name().to_string(),
                 description: op.description().to_string(),
@@ -166,6 +312,30 @@ impl FsProvider {
                 }),
                 effects: vec!["fs".to_string()],
             },
+            FsOp::Glob => ToolSchema {
+                name: op.tool_name().to_string(),
+                description: op.description().to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "required": ["pattern"],
+                    "properties": {
+                        "pattern": {
+                            "type": "string",
+                            "description": "Glob pattern, e.g. src/**/*.lm"
+                        },
+                        "include_dirs": {
+                            "type": "boolean",
+                            "description": "Also return matching directories (default false)"
+                        }
+                    }
+                }),
+                output_schema: json!({
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Sorted list of matching paths"
+                }),
+                effects: vec!["fs".to_string()],
+            },
             FsOp::Mkdir => ToolSchema {
                 name: op.tool_name().to_string(),
                 description: op.description().to_string(),
@@ -195,6 +365,10 @@ impl FsProvider {
                         "path": {
                             "type": "string",
                             "description": "File or empty directory to remove"
+                        },
+                        "recursive": {
+                            "type": "boolean",
+                            "description": "Remove a directory and all its contents (default false)"
                         }
                     }
                 }),
@@ -204,6 +378,56 @@ impl FsProvider {
                 }),
                 effects: vec!["fs".to_string()],
             },
+            FsOp::Copy => ToolSchema {
+                name: op.tool_name().to_string(),
+                description: op.description().to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "required": ["from", "to"],
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "File to copy"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination path"
+                        },
+                        "overwrite": {
+                            "type": "boolean",
+                            "description": "Replace the destination if it exists (default false)"
+                        }
+                    }
+                }),
+                output_schema: json!({
+                    "type": "boolean",
+                    "description": "True if the file was copied"
+                }),
+                effects: vec!["fs".to_string()],
+            },
+            FsOp::Move => ToolSchema {
+                name: op.tool_name().to_string(),
+                description: op.description().to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "required": ["from", "to"],
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "File or directory to move"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination path"
+                        }
+                    }
+                }),
+                output_schema: json!({
+                    "type": "boolean",
+                    "description": "True if the path was moved"
+                }),
+                effects: vec!["fs".to_string()],
+            },
         };
 
         Self { op, schema }
@@ -218,14 +442,34 @@ impl FsProvider {
         Self::new(FsOp::Write)
     }
 
+    pub fn append() -> Self {
+        Self::new(FsOp::Append)
+    }
+
+    pub fn read_bytes() -> Self {
+        Self::new(FsOp::ReadBytes)
+    }
+
+    pub fn write_bytes() -> Self {
+        Self::new(FsOp::WriteBytes)
+    }
+
     pub fn exists() -> Self {
         Self::new(FsOp::Exists)
     }
 
+    pub fn stat() -> Self {
+        Self::new(FsOp::Stat)
+    }
+
     pub fn list() -> Self {
         Self::new(FsOp::List)
     }
 
+    pub fn glob() -> Self {
+        Self::new(FsOp::Glob)
+    }
+
     pub fn mkdir() -> Self {
         Self::new(FsOp::Mkdir)
     }
@@ -234,6 +478,14 @@ impl FsProvider {
         Self::new(FsOp::Remove)
     }
 
+    pub fn copy() -> Self {
+        Self::new(FsOp::Copy)
+    }
+
+    pub fn move_() -> Self {
+        Self::new(FsOp::Move)
+    }
+
     /// Execute the filesystem operation.
     fn execute(&self, input: Value) -> Result<Value, ToolError> {
         match self.op {
@@ -255,6 +507,41 @@ impl FsProvider {
 
                 Ok(json!(true))
             }
+            FsOp::Append => {
+                let req: WriteRequest = serde_json::from_value(input)
+                    .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
+
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&req.path)
+                    .map_err(|e| ToolError::InvocationFailed(format!("append failed: {}", e)))?;
+                file.write_all(req.content.as_bytes())
+                    .map_err(|e| ToolError::InvocationFailed(format!("append failed: {}", e)))?;
+
+                Ok(json!(true))
+            }
+            FsOp::ReadBytes => {
+                let req: ReadRequest = serde_json::from_value(input)
+                    .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
+
+                let bytes = std::fs::read(&req.path)
+                    .map_err(|e| ToolError::InvocationFailed(format!("read failed: {}", e)))?;
+
+                Ok(json!(BASE64.encode(bytes)))
+            }
+            FsOp::WriteBytes => {
+                let req: WriteRequest = serde_json::from_value(input)
+                    .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
+
+                let bytes = BASE64.decode(&req.content).map_err(|e| {
+                    ToolError::InvocationFailed(format!("invalid base64 content: {}", e))
+                })?;
+                std::fs::write(&req.path, bytes)
+                    .map_err(|e| ToolError::InvocationFailed(format!("write failed: {}", e)))?;
+
+                Ok(json!(true))
+            }
             FsOp::Exists => {
                 let req: PathRequest = serde_json::from_value(input)
                     .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
@@ -262,6 +549,35 @@ impl FsProvider {
                 let exists = Path::new(&req.path).exists();
                 Ok(json!(exists))
             }
+            FsOp::Stat => {
+                let req: PathRequest = serde_json::from_value(input)
+                    .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
+
+                let meta = match std::fs::symlink_metadata(&req.path) {
+                    Ok(meta) => meta,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        return Ok(json!({ "exists": false }));
+                    }
+                    Err(e) => {
+                        return Err(ToolError::InvocationFailed(format!("stat failed: {}", e)));
+                    }
+                };
+                let modified_unix_ms = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_millis() as u64);
+
+                Ok(json!({
+                    "exists": true,
+                    "is_file": meta.is_file(),
+                    "is_dir": meta.is_dir(),
+                    "is_symlink": meta.file_type().is_symlink(),
+                    "size": meta.len(),
+                    "modified_unix_ms": modified_unix_ms,
+                    "readonly": meta.permissions().readonly(),
+                }))
+            }
             FsOp::List => {
                 let req: PathRequest = serde_json::from_value(input)
                     .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
@@ -276,6 +592,27 @@ impl FsProvider {
 
                 Ok(json!(names))
             }
+            FsOp::Glob => {
+                let req: GlobRequest = serde_json::from_value(input)
+                    .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
+
+                let paths = glob::glob(&req.pattern).map_err(|e| {
+                    ToolError::InvocationFailed(format!("invalid glob pattern: {}", e))
+                })?;
+
+                let mut matches = Vec::new();
+                for entry in paths {
+                    let path = entry
+                        .map_err(|e| ToolError::InvocationFailed(format!("glob failed: {}", e)))?;
+                    if !req.include_dirs && path.is_dir() {
+                        continue;
+                    }
+                    matches.push(path.to_string_lossy().into_owned());
+                }
+                matches.sort();
+
+                Ok(json!(matches))
+            }
             FsOp::Mkdir => {
                 let req: PathRequest = serde_json::from_value(input)
                     .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
@@ -286,11 +623,15 @@ impl FsProvider {
                 Ok(json!(true))
             }
             FsOp::Remove => {
-                let req: PathRequest = serde_json::from_value(input)
+                let req: RemoveRequest = serde_json::from_value(input)
                     .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
 
                 let path = Path::new(&req.path);
-                if path.is_dir() {
+                if req.recursive && path.is_dir() {
+                    std::fs::remove_dir_all(&req.path).map_err(|e| {
+                        ToolError::InvocationFailed(format!("remove dir failed: {}", e))
+                    })?;
+                } else if path.is_dir() {
                     std::fs::remove_dir(&req.path).map_err(|e| {
                         ToolError::InvocationFailed(format!("remove dir failed: {}", e))
                     })?;
@@ -300,6 +641,59 @@ impl FsProvider {
                     })?;
                 }
 
+                Ok(json!(true))
+            }
+            FsOp::Copy => {
+                let req: TransferRequest = serde_json::from_value(input)
+                    .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
+
+                if !Path::new(&req.from).exists() {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "copy failed: source '{}' does not exist",
+                        req.from
+                    )));
+                }
+                if !req.overwrite && Path::new(&req.to).exists() {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "copy failed: destination '{}' exists (set overwrite to replace it)",
+                        req.to
+                    )));
+                }
+
+                std::fs::copy(&req.from, &req.to)
+                    .map_err(|e| ToolError::InvocationFailed(format!("copy failed: {}", e)))?;
+
+                Ok(json!(true))
+            }
+            FsOp::Move => {
+                let req: TransferRequest = serde_json::from_value(input)
+                    .map_err(|e| ToolError::InvocationFailed(format!("invalid input: {}", e)))?;
+
+                let from = Path::new(&req.from);
+                if !from.exists() {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "move failed: source '{}' does not exist",
+                        req.from
+                    )));
+                }
+
+                match std::fs::rename(&req.from, &req.to) {
+                    Ok(()) => {}
+                    // `rename` cannot cross filesystems; copy the file and
+                    // remove the original instead.
+                    Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices && from.is_file() => {
+                        std::fs::copy(&req.from, &req.to).map_err(|e| {
+                            ToolError::InvocationFailed(format!("move failed: {}", e))
+                        })?;
+                        std::fs::remove_file(&req.from).map_err(|e| {
+                            ToolError::InvocationFailed(format!("move failed: {}", e))
+                        })?;
+                    }
+                    Err(e) => {
+                        return Err(ToolError::InvocationFailed(format!("move failed: {}", e)));
+                    }
+                }
+
                 Ok(json!(true))
             }
         }
@@ -364,6 +758,73 @@ mod tests {
         fs::remove_dir_all(&tmp).unwrap();
     }
 
+    #[test]
+    fn test_append_concatenates_and_creates() {
+        let tmp = temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        let file_path = tmp.join("log.txt");
+        assert!(!file_path.exists());
+
+        let provider = FsProvider::append();
+        for line in ["first\n", "second\n"] {
+            let result = provider
+                .call(json!({
+                    "path": file_path.to_str().unwrap(),
+                    "content": line
+                }))
+                .unwrap();
+            assert_eq!(result, json!(true));
+        }
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "first\nsecond\n");
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_write_and_read_bytes() {
+        let tmp = temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        let file_path = tmp.join("blob.bin");
+        let bytes: Vec<u8> = vec![0x00, 0xFF, 0xFE, 0x80, b'\n', 0xC3, 0x28, 0x7F];
+
+        let write_result = FsProvider::write_bytes()
+            .call(json!({
+                "path": file_path.to_str().unwrap(),
+                "content": BASE64.encode(&bytes)
+            }))
+            .unwrap();
+        assert_eq!(write_result, json!(true));
+        assert_eq!(fs::read(&file_path).unwrap(), bytes);
+
+        let read_result = FsProvider::read_bytes()
+            .call(json!({ "path": file_path.to_str().unwrap() }))
+            .unwrap();
+        let decoded = BASE64.decode(read_result.as_str().unwrap()).unwrap();
+        assert_eq!(decoded, bytes);
+
+        // The text read still rejects non-UTF-8 content.
+        let text_result = FsProvider::read().call(json!({ "path": file_path.to_str().unwrap() }));
+        assert!(text_result.is_err());
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_write_bytes_rejects_invalid_base64() {
+        let tmp = temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        let file_path = tmp.join("bad.bin");
+
+        let result = FsProvider::write_bytes().call(json!({
+            "path": file_path.to_str().unwrap(),
+            "content": "not base64!"
+        }));
+        assert!(result.is_err());
+        assert!(!file_path.exists());
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
     #[test]
     fn test_exists() {
         let tmp = temp_dir();
@@ -393,6 +854,42 @@ mod tests {
         fs::remove_dir_all(&tmp).unwrap();
     }
 
+    #[test]
+    fn test_stat_file_and_dir() {
+        let tmp = temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        let file_path = tmp.join("stat.txt");
+        fs::write(&file_path, "twelve bytes").unwrap();
+
+        let provider = FsProvider::stat();
+        let file = provider
+            .call(json!({ "path": file_path.to_str().unwrap() }))
+            .unwrap();
+        assert_eq!(file["exists"], json!(true));
+        assert_eq!(file["is_file"], json!(true));
+        assert_eq!(file["is_dir"], json!(false));
+        assert_eq!(file["is_symlink"], json!(false));
+        assert_eq!(file["size"], json!(12));
+        assert_eq!(file["readonly"], json!(false));
+        assert!(file["modified_unix_ms"].as_u64().unwrap() > 0);
+
+        let dir = provider
+            .call(json!({ "path": tmp.to_str().unwrap() }))
+            .unwrap();
+        assert_eq!(dir["is_dir"], json!(true));
+        assert_eq!(dir["is_file"], json!(false));
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_stat_missing_path() {
+        let result = FsProvider::stat()
+            .call(json!({ "path": temp_dir().join("missing.txt").to_str().unwrap() }))
+            .unwrap();
+        assert_eq!(result, json!({ "exists": false }));
+    }
+
     #[test]
     fn test_list() {
         let tmp = temp_dir();
@@ -476,6 +973,199 @@ mod tests {
         fs::remove_dir_all(&tmp).unwrap();
     }
 
+    #[test]
+    fn test_remove_non_empty_directory_requires_recursive() {
+        let tmp = temp_dir();
+        let dir_path = tmp.join("full_dir");
+        fs::create_dir_all(dir_path.join("nested")).unwrap();
+        fs::write(dir_path.join("nested/file.txt"), "x").unwrap();
+
+        let provider = FsProvider::remove();
+        let result = provider.call(json!({
+            "path": dir_path.to_str().unwrap()
+        }));
+        assert!(result.is_err());
+        assert!(dir_path.join("nested/file.txt").exists());
+
+        let result = provider
+            .call(json!({
+                "path": dir_path.to_str().unwrap(),
+                "recursive": true
+            }))
+            .unwrap();
+        assert_eq!(result, json!(true));
+        assert!(!dir_path.exists());
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_copy_same_dir() {
+        let tmp = temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        let src = tmp.join("a.txt");
+        let dst = tmp.join("b.txt");
+        fs::write(&src, "payload").unwrap();
+
+        let result = FsProvider::copy()
+            .call(json!({
+                "from": src.to_str().unwrap(),
+                "to": dst.to_str().unwrap()
+            }))
+            .unwrap();
+        assert_eq!(result, json!(true));
+        assert_eq!(fs::read_to_string(&src).unwrap(), "payload");
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "payload");
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_copy_refuses_overwrite_without_flag() {
+        let tmp = temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        let src = tmp.join("new.txt");
+        let dst = tmp.join("old.txt");
+        fs::write(&src, "new").unwrap();
+        fs::write(&dst, "old").unwrap();
+
+        let provider = FsProvider::copy();
+        let err = provider
+            .call(json!({
+                "from": src.to_str().unwrap(),
+                "to": dst.to_str().unwrap()
+            }))
+            .unwrap_err();
+        assert!(err.to_string().contains("exists"));
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "old");
+
+        provider
+            .call(json!({
+                "from": src.to_str().unwrap(),
+                "to": dst.to_str().unwrap(),
+                "overwrite": true
+            }))
+            .unwrap();
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "new");
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_copy_and_move_missing_source() {
+        let tmp = temp_dir();
+        fs::create_dir_all(&tmp).unwrap();
+        let input = json!({
+            "from": tmp.join("missing.txt").to_str().unwrap(),
+            "to": tmp.join("out.txt").to_str().unwrap()
+        });
+
+        assert!(FsProvider::copy().call(input.clone()).is_err());
+        assert!(FsProvider::move_().call(input).is_err());
+        assert!(!tmp.join("out.txt").exists());
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    #[test]
+    fn test_move_deletes_source() {
+        let tmp = temp_dir();
+        fs::create_dir_all(tmp.join("dest")).unwrap();
+        let src = tmp.join("from.txt");
+        let dst = tmp.join("dest/to.txt");
+        fs::write(&src, "moving").unwrap();
+
+        let provider = FsProvider::move_();
+        assert_eq!(provider.name(), "fs.move");
+        let result = provider
+            .call(json!({
+                "from": src.to_str().unwrap(),
+                "to": dst.to_str().unwrap()
+            }))
+            .unwrap();
+        assert_eq!(result, json!(true));
+        assert!(!src.exists());
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "moving");
+
+        fs::remove_dir_all(&tmp).unwrap();
+    }
+
+    fn glob_tree() -> std::path::PathBuf {
+        let tmp = temp_dir();
+        fs::create_dir_all(tmp.join("sub/deeper")).unwrap();
+        for file in [
+            "b.txt",
+            "a.txt",
+            "notes.md",
+            "sub/c.txt",
+            "sub/deeper/d.txt",
+        ] {
+            fs::write(tmp.join(file), "x").unwrap();
+        }
+        tmp
+    }
+
+    fn glob_paths(input: Value) -> Vec<String> {
+        let result = FsProvider::glob().call(input).unwrap();
+        serde_json::from_value(result).unwrap()
+    }
+
+    #[test]
+    fn test_glob_single_level() {
+        let tmp = glob_tree();
+        let paths = glob_paths(json!({
+            "pattern": tmp.join("*.txt").to_str().unwrap()
+        }));
+        let expected: Vec<String> = ["a.txt", "b.txt"]
+            .iter()
+            .map(|f| tmp.join(f).to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(paths, expected);
+
+        fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_glob_recursive() {
+        let tmp = glob_tree();
+        let paths = glob_paths(json!({
+            "pattern": tmp.join("**/*.txt").to_str().unwrap()
+        }));
+        let expected: Vec<String> = ["a.txt", "b.txt", "sub/c.txt", "sub/deeper/d.txt"]
+            .iter()
+            .map(|f| tmp.join(f).to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(paths, expected);
+
+        fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_glob_include_dirs() {
+        let tmp = glob_tree();
+        let pattern = tmp.join("**").join("*").to_str().unwrap().to_string();
+        let files = glob_paths(json!({ "pattern": pattern }));
+        assert!(!files.iter().any(|p| p.ends_with("sub")));
+
+        let all = glob_paths(json!({ "pattern": pattern, "include_dirs": true }));
+        assert!(all.contains(&tmp.join("sub").to_str().unwrap().to_string()));
+        assert!(all.contains(&tmp.join("sub/deeper").to_str().unwrap().to_string()));
+        assert_eq!(all.len(), files.len() + 2);
+
+        fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_glob_invalid_pattern() {
+        let result = FsProvider::glob().call(json!({ "pattern": "src/***/x" }));
+        match result {
+            Err(ToolError::InvocationFailed(msg)) => {
+                assert!(msg.contains("invalid glob pattern"), "got: {}", msg)
+            }
+            other => panic!("expected InvocationFailed, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_read_nonexistent_file() {
         let provider = FsProvider::read();
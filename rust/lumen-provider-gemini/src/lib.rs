@@ -1,11 +1,16 @@
+use lumen_runtime::retry::RetryExecutor;
 use lumen_runtime::tools::Capability;
 use lumen_runtime::tools::*;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
 use serde_json::{json, Value};
+use std::io::BufRead;
+use std::time::SystemTime;
 
 /// Gemini tool type — each gets its own provider instance.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum GeminiTool {
     Generate,
+    GenerateStream,
     Chat,
     Embed,
 }
@@ -14,6 +19,7 @@ impl GeminiTool {
     fn tool_name(&self) -> &'static str {
         match self {
             GeminiTool::Generate => "gemini.generate",
+            GeminiTool::GenerateStream => "gemini.generate_stream",
             GeminiTool::Chat => "gemini.chat",
             GeminiTool::Embed => "gemini.embed",
         }
@@ -22,6 +28,7 @@ impl GeminiTool {
     fn description(&self) -> &'static str {
         match self {
             GeminiTool::Generate => "Generate text using Gemini",
+            GeminiTool::GenerateStream => "Generate text using Gemini, streaming partial output",
             GeminiTool::Chat => "Multi-turn chat with Gemini",
             GeminiTool::Embed => "Generate text embeddings",
         }
@@ -39,7 +46,7 @@ pub struct GeminiProvider {
 impl GeminiProvider {
     fn new(tool: GeminiTool, api_key: String) -> Self {
         let schema = match tool {
-            GeminiTool::Generate => ToolSchema {
+            GeminiTool::Generate | GeminiTool::GenerateStream => ToolSchema {
                 name: tool.tool_name().to_string(),
                 description: tool.description().to_string(),
                 input_schema: json!({
@@ -72,6 +79,7 @@ impl GeminiProvider {
                             }
                         },
                         "system": { "type": "string" },
+                        "max_tokens": { "type": "integer", "description": "Max output tokens" },
                         "temperature": { "type": "number" }
                     },
                     "required": ["messages"]
@@ -108,6 +116,11 @@ impl GeminiProvider {
         Self::new(GeminiTool::Generate, api_key)
     }
 
+    /// Create a new provider for gemini.generate_stream
+    pub fn generate_stream(api_key: String) -> Self {
+        Self::new(GeminiTool::GenerateStream, api_key)
+    }
+
     /// Create a new provider for gemini.chat
     pub fn chat(api_key: String) -> Self {
         Self::new(GeminiTool::Chat, api_key)
@@ -125,7 +138,7 @@ impl GeminiProvider {
     }
 
     /// Normalize Gemini API errors into structured ToolError variants.
-    fn normalize_error(status: u16, body: &Value) -> ToolError {
+    fn normalize_error(status: u16, headers: &HeaderMap, body: &Value) -> ToolError {
         let message = body
             .get("error")
             .and_then(|e| e.get("message"))
@@ -135,7 +148,10 @@ impl GeminiProvider {
 
         match status {
             429 => ToolError::RateLimit {
-                retry_after_ms: None, // TODO: Extract from Retry-After header
+                retry_after_ms: headers
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| parse_retry_after(v, SystemTime::now())),
                 message,
             },
             401 | 403 => ToolError::AuthError { message },
@@ -158,39 +174,76 @@ impl GeminiProvider {
         }
     }
 
-    fn execute_generate(&self, input: Value) -> Result<Value, ToolError> {
+    /// Build the `generateContent` request body for a single prompt.
+    fn generate_body(input: &Value) -> Result<Value, ToolError> {
         let prompt = input
             .get("prompt")
             .or_else(|| input.get("arg0"))
             .and_then(|p| p.as_str())
             .ok_or_else(|| ToolError::InvalidArgs("missing 'prompt' field".to_string()))?;
-        let system = input.get("system").and_then(|s| s.as_str());
-        let temperature = input.get("temperature").and_then(|t| t.as_f64());
+        let mut body = json!({
+            "contents": [{ "role": "user", "parts": [{"text": prompt}] }]
+        });
+        Self::apply_system_instruction(&mut body, input);
+        Self::apply_generation_config(&mut body, input);
+        Ok(body)
+    }
 
-        let url = format!(
-            "{}/models/{}:generateContent?key={}",
-            self.base_url, self.model, self.api_key
-        );
+    /// Build the `generateContent` request body for a multi-turn chat.
+    fn chat_body(input: &Value) -> Result<Value, ToolError> {
+        let messages = input
+            .get("messages")
+            .or_else(|| input.get("arg0"))
+            .and_then(|m| m.as_array())
+            .ok_or_else(|| ToolError::InvalidArgs("missing 'messages' array".to_string()))?;
 
-        let mut contents = vec![];
-        if let Some(sys) = system {
-            // Gemini uses systemInstruction field
-            contents.push(json!({
-                "role": "user",
-                "parts": [{"text": format!("System: {}\n\n{}", sys, prompt)}]
-            }));
-        } else {
-            contents.push(json!({
-                "role": "user",
-                "parts": [{"text": prompt}]
-            }));
-        }
+        let contents: Vec<Value> = messages
+            .iter()
+            .map(|m| {
+                let role = m.get("role").and_then(|r| r.as_str()).unwrap_or("user");
+                let content = m.get("content").and_then(|c| c.as_str()).unwrap_or("");
+                json!({
+                    "role": role,
+                    "parts": [{"text": content}]
+                })
+            })
+            .collect();
 
         let mut body = json!({ "contents": contents });
+        Self::apply_system_instruction(&mut body, input);
+        Self::apply_generation_config(&mut body, input);
+        Ok(body)
+    }
 
-        if let Some(temp) = temperature {
-            body["generationConfig"] = json!({ "temperature": temp });
+    /// Send the `system` input as Gemini's top-level `systemInstruction`
+    /// rather than mixing it into the user turn.
+    fn apply_system_instruction(body: &mut Value, input: &Value) {
+        if let Some(system) = input.get("system").and_then(|s| s.as_str()) {
+            body["systemInstruction"] = json!({ "parts": [{"text": system}] });
         }
+    }
+
+    /// Map `temperature` and `max_tokens` onto `generationConfig`, omitting
+    /// the object entirely when neither is supplied.
+    fn apply_generation_config(body: &mut Value, input: &Value) {
+        let mut config = serde_json::Map::new();
+        if let Some(temp) = input.get("temperature").and_then(|t| t.as_f64()) {
+            config.insert("temperature".to_string(), json!(temp));
+        }
+        if let Some(max_tokens) = input.get("max_tokens").and_then(|m| m.as_u64()) {
+            config.insert("maxOutputTokens".to_string(), json!(max_tokens));
+        }
+        if !config.is_empty() {
+            body["generationConfig"] = Value::Object(config);
+        }
+    }
+
+    fn execute_generate(&self, input: Value) -> Result<Value, ToolError> {
+        let body = Self::generate_body(&input)?;
+        let url = format!(
+            "{}/models/{}:generateContent?key={}",
+            self.base_url, self.model, self.api_key
+        );
 
         let client = reqwest::blocking::Client::new();
         let response = client
@@ -200,12 +253,17 @@ impl GeminiProvider {
             .map_err(|e| ToolError::ExecutionFailed(format!("HTTP error: {}", e)))?;
 
         let status = response.status();
+        let headers = response.headers().clone();
         let response_body: Value = response
             .json()
             .map_err(|e| ToolError::ExecutionFailed(format!("JSON parse error: {}", e)))?;
 
         if !status.is_success() {
-            return Err(Self::normalize_error(status.as_u16(), &response_body));
+            return Err(Self::normalize_error(
+                status.as_u16(),
+                &headers,
+                &response_body,
+            ));
         }
 
         // Extract text from Gemini response
@@ -223,32 +281,47 @@ impl GeminiProvider {
         Ok(json!(text))
     }
 
-    fn execute_chat(&self, input: Value) -> Result<Value, ToolError> {
-        let messages = input
-            .get("messages")
-            .or_else(|| input.get("arg0"))
-            .and_then(|m| m.as_array())
-            .ok_or_else(|| ToolError::InvalidArgs("missing 'messages' array".to_string()))?;
+    /// Generate text via `:streamGenerateContent`, calling `on_text` with each
+    /// partial chunk as it arrives. Returns the full concatenated text.
+    pub fn stream_generate(
+        &self,
+        input: &Value,
+        mut on_text: impl FnMut(&str),
+    ) -> Result<String, ToolError> {
+        let body = Self::generate_body(input)?;
+        let url = format!(
+            "{}/models/{}:streamGenerateContent?alt=sse&key={}",
+            self.base_url, self.model, self.api_key
+        );
 
-        let contents: Vec<Value> = messages
-            .iter()
-            .map(|m| {
-                let role = m.get("role").and_then(|r| r.as_str()).unwrap_or("user");
-                let content = m.get("content").and_then(|c| c.as_str()).unwrap_or("");
-                json!({
-                    "role": role,
-                    "parts": [{"text": content}]
-                })
-            })
-            .collect();
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .post(&url)
+            .json(&body)
+            .send()
+            .map_err(|e| ToolError::ExecutionFailed(format!("HTTP error: {}", e)))?;
 
+        let status = response.status();
+        let headers = response.headers().clone();
+        if !status.is_success() {
+            let response_body: Value = response.json().unwrap_or(Value::Null);
+            return Err(Self::normalize_error(
+                status.as_u16(),
+                &headers,
+                &response_body,
+            ));
+        }
+
+        read_sse_stream(std::io::BufReader::new(response), &headers, &mut on_text)
+    }
+
+    fn execute_chat(&self, input: Value) -> Result<Value, ToolError> {
+        let body = Self::chat_body(&input)?;
         let url = format!(
             "{}/models/{}:generateContent?key={}",
             self.base_url, self.model, self.api_key
         );
 
-        let body = json!({ "contents": contents });
-
         let client = reqwest::blocking::Client::new();
         let response = client
             .post(&url)
@@ -257,12 +330,17 @@ impl GeminiProvider {
             .map_err(|e| ToolError::ExecutionFailed(format!("HTTP error: {}", e)))?;
 
         let status = response.status();
+        let headers = response.headers().clone();
         let response_body: Value = response
             .json()
             .map_err(|e| ToolError::ExecutionFailed(format!("JSON parse error: {}", e)))?;
 
         if !status.is_success() {
-            return Err(Self::normalize_error(status.as_u16(), &response_body));
+            return Err(Self::normalize_error(
+                status.as_u16(),
+                &headers,
+                &response_body,
+            ));
         }
 
         let text = response_body
@@ -306,12 +384,17 @@ impl GeminiProvider {
             .map_err(|e| ToolError::ExecutionFailed(format!("HTTP error: {}", e)))?;
 
         let status = response.status();
+        let headers = response.headers().clone();
         let response_body: Value = response
             .json()
             .map_err(|e| ToolError::ExecutionFailed(format!("JSON parse error: {}", e)))?;
 
         if !status.is_success() {
-            return Err(Self::normalize_error(status.as_u16(), &response_body));
+            return Err(Self::normalize_error(
+                status.as_u16(),
+                &headers,
+                &response_body,
+            ));
         }
 
         let embedding = response_body
@@ -324,6 +407,102 @@ impl GeminiProvider {
     }
 }
 
+/// Read a `text/event-stream` body of `streamGenerateContent` responses,
+/// passing the text of each event to `on_text` and returning it concatenated.
+///
+/// Events are separated by blank lines; their `data:` lines carry one JSON
+/// response each. An empty payload (or `[DONE]`) ends the stream, and an
+/// event carrying an `error` object is mapped through `normalize_error`.
+fn read_sse_stream<R: BufRead>(
+    reader: R,
+    headers: &HeaderMap,
+    on_text: &mut dyn FnMut(&str),
+) -> Result<String, ToolError> {
+    let mut full = String::new();
+    // `None` until the current event has a `data:` line.
+    let mut data: Option<String> = None;
+    let mut lines = reader.lines();
+    loop {
+        let line = lines
+            .next()
+            .transpose()
+            .map_err(|e| ToolError::ExecutionFailed(format!("stream read error: {}", e)))?;
+        match line.as_deref() {
+            Some(l) if !l.is_empty() => {
+                if let Some(payload) = l.strip_prefix("data:") {
+                    let payload = payload.strip_prefix(' ').unwrap_or(payload);
+                    match data.as_mut() {
+                        Some(buf) => {
+                            buf.push('\n');
+                            buf.push_str(payload);
+                        }
+                        None => data = Some(payload.to_string()),
+                    }
+                }
+                // Comments, `event:` and `id:` lines carry nothing we need.
+            }
+            _ => {
+                // Blank line (end of event) or end of stream: dispatch.
+                if let Some(event) = data.take() {
+                    match parse_stream_event(&event, headers)? {
+                        Some(text) => {
+                            on_text(&text);
+                            full.push_str(&text);
+                        }
+                        None => break,
+                    }
+                }
+                if line.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(full)
+}
+
+/// Decode one SSE `data` payload. Returns `Ok(None)` for the terminal event.
+fn parse_stream_event(data: &str, headers: &HeaderMap) -> Result<Option<String>, ToolError> {
+    let data = data.trim();
+    if data.is_empty() || data == "[DONE]" {
+        return Ok(None);
+    }
+    let chunk: Value = serde_json::from_str(data)
+        .map_err(|e| ToolError::ExecutionFailed(format!("JSON parse error: {}", e)))?;
+    if let Some(error) = chunk.get("error") {
+        let status = error.get("code").and_then(|c| c.as_u64()).unwrap_or(500) as u16;
+        return Err(GeminiProvider::normalize_error(status, headers, &chunk));
+    }
+    let text = chunk
+        .get("candidates")
+        .and_then(|c| c.get(0))
+        .and_then(|c| c.get("content"))
+        .and_then(|c| c.get("parts"))
+        .and_then(|p| p.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+    Ok(Some(text))
+}
+
+/// Parse a `Retry-After` header value into milliseconds.
+///
+/// Accepts both delay-seconds (`"30"`) and an HTTP-date
+/// (`"Wed, 21 Oct 2015 07:28:00 GMT"`), the latter measured from `now`.
+/// Dates in the past yield zero.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<u64> {
+    if let Some(ms) = RetryExecutor::parse_retry_after(value) {
+        return Some(ms);
+    }
+    let date = httpdate::parse_http_date(value.trim()).ok()?;
+    let delay = date.duration_since(now).unwrap_or_default();
+    Some(delay.as_millis() as u64)
+}
+
 impl ToolProvider for GeminiProvider {
     fn name(&self) -> &str {
         self.tool.tool_name()
@@ -340,6 +519,7 @@ impl ToolProvider for GeminiProvider {
     fn call(&self, input: Value) -> Result<Value, ToolError> {
         match self.tool {
             GeminiTool::Generate => self.execute_generate(input),
+            GeminiTool::GenerateStream => self.stream_generate(&input, |_| {}).map(Value::from),
             GeminiTool::Chat => self.execute_chat(input),
             GeminiTool::Embed => self.execute_embed(input),
         }
@@ -349,6 +529,7 @@ impl ToolProvider for GeminiProvider {
         use Capability::*;
         match self.tool {
             GeminiTool::Generate => vec![TextGeneration, StructuredOutput, Vision],
+            GeminiTool::GenerateStream => vec![TextGeneration, Streaming, Vision],
             GeminiTool::Chat => vec![Chat, TextGeneration, StructuredOutput, Vision],
             GeminiTool::Embed => vec![Embedding],
         }
@@ -448,12 +629,199 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generate_body_uses_system_instruction() {
+        let body = GeminiProvider::generate_body(&json!({
+            "prompt": "Introduce yourself",
+            "system": "You are a pirate."
+        }))
+        .unwrap();
+        assert_eq!(
+            body["systemInstruction"]["parts"][0]["text"],
+            "You are a pirate."
+        );
+        let user_text = body["contents"][0]["parts"][0]["text"].as_str().unwrap();
+        assert_eq!(user_text, "Introduce yourself");
+        assert!(!user_text.contains("System:"));
+    }
+
+    #[test]
+    fn test_chat_body_uses_system_instruction() {
+        let body = GeminiProvider::chat_body(&json!({
+            "messages": [{"role": "user", "content": "What is 2+2?"}],
+            "system": "Answer tersely."
+        }))
+        .unwrap();
+        assert_eq!(
+            body["systemInstruction"]["parts"][0]["text"],
+            "Answer tersely."
+        );
+        assert_eq!(body["contents"].as_array().unwrap().len(), 1);
+        assert_eq!(body["contents"][0]["parts"][0]["text"], "What is 2+2?");
+    }
+
+    #[test]
+    fn test_chat_body_maps_generation_config() {
+        let body = GeminiProvider::chat_body(&json!({
+            "messages": [{"role": "user", "content": "hi"}],
+            "temperature": 0.4,
+            "max_tokens": 256
+        }))
+        .unwrap();
+        assert_eq!(body["generationConfig"]["temperature"], 0.4);
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 256);
+
+        let body = GeminiProvider::chat_body(&json!({
+            "messages": [{"role": "user", "content": "hi"}]
+        }))
+        .unwrap();
+        assert!(body.get("generationConfig").is_none());
+    }
+
+    #[test]
+    fn test_generate_body_maps_max_tokens_alone() {
+        let body =
+            GeminiProvider::generate_body(&json!({"prompt": "hi", "max_tokens": 64})).unwrap();
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 64);
+        assert!(body["generationConfig"].get("temperature").is_none());
+    }
+
+    #[test]
+    fn test_generate_body_without_system() {
+        let body = GeminiProvider::generate_body(&json!({"prompt": "hi"})).unwrap();
+        assert!(body.get("systemInstruction").is_none());
+    }
+
     #[test]
     fn test_with_model() {
         let provider = GeminiProvider::generate("test_key".to_string()).with_model("gemini-pro");
         assert_eq!(provider.model, "gemini-pro");
     }
 
+    fn retry_after_headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, value.parse().unwrap());
+        headers
+    }
+
+    fn rate_limit_body() -> Value {
+        json!({"error": {"message": "Resource has been exhausted"}})
+    }
+
+    #[test]
+    fn test_normalize_error_retry_after_seconds() {
+        let err =
+            GeminiProvider::normalize_error(429, &retry_after_headers("30"), &rate_limit_body());
+        match err {
+            ToolError::RateLimit {
+                retry_after_ms,
+                message,
+            } => {
+                assert_eq!(retry_after_ms, Some(30_000));
+                assert_eq!(message, "Resource has been exhausted");
+            }
+            other => panic!("expected RateLimit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_normalize_error_retry_after_http_date() {
+        // HTTP-dates have one-second resolution, so aim for a whole second.
+        let target = SystemTime::now() + std::time::Duration::from_secs(120);
+        let header = httpdate::fmt_http_date(target);
+        let err =
+            GeminiProvider::normalize_error(429, &retry_after_headers(&header), &rate_limit_body());
+        match err {
+            ToolError::RateLimit { retry_after_ms, .. } => {
+                let ms = retry_after_ms.expect("date should be parsed");
+                assert!((118_000..=120_000).contains(&ms), "got {}ms", ms);
+            }
+            other => panic!("expected RateLimit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after_rfc1123_date() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:27:30 GMT").unwrap();
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(30_000)
+        );
+        // A date that has already passed means "retry now".
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(0)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    const CANNED_SSE: &str = "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"Hello\"}], \"role\": \"model\"}}]}\r\n\r\n\
+data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \", \"}, {\"text\": \"world\"}]}}]}\r\n\r\n\
+: keep-alive\r\n\r\n\
+data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"!\"}]}, \"finishReason\": \"STOP\"}]}\r\n\r\n";
+
+    #[test]
+    fn test_sse_stream_concatenates_chunks() {
+        let mut chunks = vec![];
+        let text = read_sse_stream(CANNED_SSE.as_bytes(), &HeaderMap::new(), &mut |t| {
+            chunks.push(t.to_string())
+        })
+        .unwrap();
+        assert_eq!(text, "Hello, world!");
+        assert_eq!(chunks, vec!["Hello", ", world", "!"]);
+    }
+
+    #[test]
+    fn test_sse_stream_stops_at_terminal_empty_event() {
+        let body = "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"done\"}]}}]}\n\ndata:\n\ndata: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"ignored\"}]}}]}\n\n";
+        let text = read_sse_stream(body.as_bytes(), &HeaderMap::new(), &mut |_| {}).unwrap();
+        assert_eq!(text, "done");
+    }
+
+    #[test]
+    fn test_sse_stream_maps_error_events() {
+        let body = "data: {\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"partial\"}]}}]}\n\ndata: {\"error\": {\"code\": 429, \"message\": \"quota exceeded\", \"status\": \"RESOURCE_EXHAUSTED\"}}\n\n";
+        let mut seen = String::new();
+        let err = read_sse_stream(body.as_bytes(), &retry_after_headers("5"), &mut |t| {
+            seen.push_str(t)
+        })
+        .unwrap_err();
+        assert_eq!(seen, "partial");
+        match err {
+            ToolError::RateLimit {
+                retry_after_ms,
+                message,
+            } => {
+                assert_eq!(retry_after_ms, Some(5_000));
+                assert_eq!(message, "quota exceeded");
+            }
+            other => panic!("expected RateLimit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_generate_stream_metadata() {
+        let provider = GeminiProvider::generate_stream("test_key".to_string());
+        assert_eq!(provider.name(), "gemini.generate_stream");
+        assert!(provider.capabilities().contains(&Capability::Streaming));
+        assert!(matches!(
+            provider.call(json!({})),
+            Err(ToolError::InvalidArgs(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_error_without_retry_after() {
+        let err = GeminiProvider::normalize_error(429, &HeaderMap::new(), &rate_limit_body());
+        assert!(matches!(
+            err,
+            ToolError::RateLimit {
+                retry_after_ms: None,
+                ..
+            }
+        ));
+    }
+
     #[test]
     #[ignore] // Run with: cargo test -p lumen-provider-gemini -- --ignored
     fn test_real_gemini_generate() {
@@ -5,15 +5,30 @@
 //! - `http.post` — POST request with body
 //! - `http.put` — PUT request with body
 //! - `http.delete` — DELETE request
+//! - `http.patch` — PATCH request with body
+//! - `http.head` — HEAD request (empty `body`, full `status` and `headers`)
+//! - `http.options` — OPTIONS request
+//! - `http.download` — GET request streamed to a local `path`, returning
+//!   `status`, `bytes_written` and `path` (declares `http` and `fs` effects)
 //!
-//! Each tool accepts a JSON object with `url`, optional `headers`, and optional `body`,
-//! and returns a JSON object with `status`, `body`, and `headers`.
+//! Each tool accepts a JSON object with `url`, optional `headers`, optional `body`
+//! (or `body_base64` for binary uploads), and optional `timeout_ms` (overriding the
+//! 30-second client default), and returns a JSON object with `status`, `body`, and
+//! `headers`. Responses whose `Content-Type` is not textual leave `body` empty and
+//! carry the raw bytes base64-encoded in `body_base64` instead.
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+use lumen_runtime::retry::{RetryCondition, RetryExecutor, RetryPolicy, RetryState};
 use lumen_runtime::tools::{ToolError, ToolProvider, ToolSchema};
-use reqwest::blocking::Client;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::redirect;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 // ---------------------------------------------------------------------------
@@ -27,13 +42,79 @@ struct HttpRequest {
     headers: HashMap<String, String>,
     #[serde(default)]
     body: Option<String>,
+    #[serde(default)]
+    body_base64: Option<String>,
+    #[serde(default)]
+    timeout_ms: Option<i64>,
+    #[serde(default)]
+    follow_redirects: Option<bool>,
+    #[serde(default)]
+    max_redirects: Option<i64>,
+    #[serde(default)]
+    retries: Option<i64>,
+    #[serde(default)]
+    retry_on: Option<Vec<u16>>,
+    #[serde(default)]
+    retry_non_idempotent: Option<bool>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    parse_json: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct HttpResponse {
     status: u16,
     body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
     headers: HashMap<String, String>,
+    #[serde(default)]
+    final_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    json: Option<Value>,
+}
+
+/// Whether a `Content-Type` denotes JSON (`application/json` or `*+json`).
+fn is_json_content_type(content_type: Option<&str>) -> bool {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    mime == "application/json" || mime.ends_with("+json")
+}
+
+/// Whether a response with this `Content-Type` should be decoded as UTF-8.
+///
+/// A missing content type is treated as text, matching the provider's
+/// behaviour before binary bodies were supported.
+fn is_textual_content_type(content_type: Option<&str>) -> bool {
+    let Some(ct) = content_type else {
+        return true;
+    };
+    let mime = ct
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    mime.is_empty()
+        || mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime.as_str(),
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/ecmascript"
+                | "application/x-www-form-urlencoded"
+                | "application/yaml"
+                | "application/x-yaml"
+                | "application/toml"
+                | "application/graphql"
+        )
 }
 
 // ---------------------------------------------------------------------------
@@ -46,6 +127,10 @@ enum Method {
     Post,
     Put,
     Delete,
+    Patch,
+    Head,
+    Options,
+    Download,
 }
 
 impl Method {
@@ -55,6 +140,10 @@ impl Method {
             Method::Post => "http.post",
             Method::Put => "http.put",
             Method::Delete => "http.delete",
+            Method::Patch => "http.patch",
+            Method::Head => "http.head",
+            Method::Options => "http.options",
+            Method::Download => "http.download",
         }
     }
 
@@ -64,8 +153,18 @@ impl Method {
             Method::Post => "Perform an HTTP POST request with optional body",
             Method::Put => "Perform an HTTP PUT request with optional body",
             Method::Delete => "Perform an HTTP DELETE request",
+            Method::Patch => "Perform an HTTP PATCH request with optional body",
+            Method::Head => "Perform an HTTP HEAD request (status and headers only)",
+            Method::Options => "Perform an HTTP OPTIONS request",
+            Method::Download => "Download a URL to a local file, streaming the body to disk",
         }
     }
+
+    /// Whether repeating the request is safe after a connection failure,
+    /// where the server may or may not have processed it.
+    fn is_idempotent(&self) -> bool {
+        !matches!(self, Method::Post | Method::Patch)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -79,11 +178,24 @@ pub struct HttpProvider {
     client: Client,
 }
 
+/// Client-wide timeout applied unless a request overrides it with `timeout_ms`.
+///
+/// It bounds connecting, waiting for the response head and each wait for body
+/// data, not the whole transfer, so a slow but steady download can run past it.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Distinguishes the temp files of concurrent downloads within one process.
+static DOWNLOAD_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Status codes retried when a request sets `retries` but not `retry_on`.
+const DEFAULT_RETRY_ON: [u16; 3] = [502, 503, 504];
+
 impl HttpProvider {
     /// Create a new HTTP provider for the given method.
     fn new(method: Method) -> Self {
         let client = Client::builder()
-            .timeout(Duration::from_secs(30))
+            .connect_timeout(DEFAULT_TIMEOUT)
+            .timeout(DEFAULT_TIMEOUT)
             .build()
             .expect("Failed to build HTTP client");
 
@@ -105,7 +217,43 @@ impl HttpProvider {
                     },
                     "body": {
                         "type": "string",
-                        "description": "Optional request body (for POST/PUT)"
+                        "description": "Optional request body (for POST/PUT/PATCH)"
+                    },
+                    "body_base64": {
+                        "type": "string",
+                        "description": "Optional base64-encoded binary request body; mutually exclusive with body"
+                    },
+                    "timeout_ms": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Optional per-request timeout in milliseconds (default 30000)"
+                    },
+                    "follow_redirects": {
+                        "type": "boolean",
+                        "description": "Follow 3xx redirects (default true); when false the 3xx response is returned as-is"
+                    },
+                    "max_redirects": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Maximum number of redirects to follow (default 10)"
+                    },
+                    "retries": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Retry attempts after the first, with exponential backoff and jitter (default 0)"
+                    },
+                    "retry_on": {
+                        "type": "array",
+                        "items": {"type": "integer"},
+                        "description": "Status codes that trigger a retry (default [502, 503, 504]); Retry-After is honored on 429 and 503"
+                    },
+                    "parse_json": {
+                        "type": "boolean",
+                        "description": "Parse JSON responses into `json` (null when the body is not valid JSON)"
+                    },
+                    "retry_non_idempotent": {
+                        "type": "boolean",
+                        "description": "Also retry POST/PATCH after connection errors (default false)"
                     }
                 }
             }),
@@ -119,17 +267,33 @@ impl HttpProvider {
                     },
                     "body": {
                         "type": "string",
-                        "description": "Response body as text"
+                        "description": "Response body as text (empty for binary responses)"
+                    },
+                    "body_base64": {
+                        "type": "string",
+                        "description": "Base64-encoded response body, present when Content-Type is not textual"
                     },
                     "headers": {
                         "type": "object",
                         "description": "Response headers as key-value pairs",
                         "additionalProperties": {"type": "string"}
+                    },
+                    "final_url": {
+                        "type": "string",
+                        "description": "URL of the response after any followed redirects"
+                    },
+                    "json": {
+                        "description": "Parsed JSON body, present when parse_json is set; null if the response is not valid JSON"
                     }
                 }
             }),
             effects: vec!["http".to_string()],
         };
+        let schema = if method == Method::Download {
+            Self::download_schema(schema)
+        } else {
+            schema
+        };
 
         Self {
             method,
@@ -158,8 +322,290 @@ impl HttpProvider {
         Self::new(Method::Delete)
     }
 
+    /// Create a PATCH provider.
+    pub fn patch() -> Self {
+        Self::new(Method::Patch)
+    }
+
+    /// Create a HEAD provider.
+    pub fn head() -> Self {
+        Self::new(Method::Head)
+    }
+
+    /// Create an OPTIONS provider.
+    pub fn options() -> Self {
+        Self::new(Method::Options)
+    }
+
+    /// Create a download provider (`http.download`).
+    pub fn download() -> Self {
+        Self::new(Method::Download)
+    }
+
+    /// Adapt the shared schema for `http.download`: a GET whose body goes to
+    /// `path` instead of the result, touching the filesystem as well.
+    fn download_schema(mut schema: ToolSchema) -> ToolSchema {
+        if let Some(props) = schema.input_schema["properties"].as_object_mut() {
+            props.remove("body");
+            props.remove("body_base64");
+            props.remove("retry_non_idempotent");
+            props.remove("parse_json");
+            props["timeout_ms"]["description"] = json!(
+                "Optional timeout in milliseconds for connecting and for each wait for data (default 30000); the whole transfer may take longer"
+            );
+            props.insert(
+                "path".to_string(),
+                json!({
+                    "type": "string",
+                    "description": "Local file to write the response body to; not created on non-2xx"
+                }),
+            );
+        }
+        schema.input_schema["required"] = json!(["url", "path"]);
+        schema.output_schema = json!({
+            "type": "object",
+            "required": ["status", "bytes_written", "path"],
+            "properties": {
+                "status": {
+                    "type": "number",
+                    "description": "HTTP status code"
+                },
+                "bytes_written": {
+                    "type": "number",
+                    "description": "Bytes written to the file (0 when the status is not 2xx)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Path the body was written to"
+                }
+            }
+        });
+        schema.effects = vec!["http".to_string(), "fs".to_string()];
+        schema
+    }
+
+    /// Redirect policy requested by the call, or `None` to use the shared
+    /// client's default (follow up to 10 redirects).
+    fn redirect_policy(request: &HttpRequest) -> Result<Option<redirect::Policy>, ToolError> {
+        if request.follow_redirects == Some(false) {
+            return Ok(Some(redirect::Policy::none()));
+        }
+        match request.max_redirects {
+            None => Ok(None),
+            Some(n) if n < 0 => Err(ToolError::InvocationFailed(format!(
+                "max_redirects must be non-negative, got {}",
+                n
+            ))),
+            Some(n) => Ok(Some(redirect::Policy::limited(n as usize))),
+        }
+    }
+
+    /// Send `req`, retrying per the request's `retries`/`retry_on` settings.
+    ///
+    /// Listed status codes are retried for every method; connection errors
+    /// only for idempotent methods unless `retry_non_idempotent` is set.
+    fn send_with_retries(
+        &self,
+        req: RequestBuilder,
+        request: &HttpRequest,
+    ) -> Result<Response, ToolError> {
+        let retries = match request.retries {
+            None => 0,
+            Some(n) if n < 0 => {
+                return Err(ToolError::InvocationFailed(format!(
+                    "retries must be non-negative, got {}",
+                    n
+                )))
+            }
+            Some(n) => n as u32,
+        };
+        if retries == 0 {
+            return req
+                .send()
+                .map_err(|e| ToolError::InvocationFailed(format!("HTTP request failed: {}", e)));
+        }
+
+        let mut retry_on: Vec<RetryCondition> = request
+            .retry_on
+            .as_deref()
+            .unwrap_or(&DEFAULT_RETRY_ON)
+            .iter()
+            .map(|&code| RetryCondition::StatusCode(code))
+            .collect();
+        if self.method.is_idempotent() || request.retry_non_idempotent == Some(true) {
+            retry_on.push(RetryCondition::ConnectionError);
+        }
+        let executor = RetryExecutor::new(RetryPolicy {
+            max_retries: retries,
+            retry_on,
+            ..RetryPolicy::default()
+        });
+        let mut state = RetryState::new();
+
+        loop {
+            let attempt = req.try_clone().ok_or_else(|| {
+                ToolError::InvocationFailed("request body cannot be replayed for retries".into())
+            })?;
+            let error = match attempt.send() {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    if !executor.should_retry(&state, &RetryCondition::StatusCode(status)) {
+                        return Ok(response);
+                    }
+                    state.retry_after_ms = if matches!(status, 429 | 503) {
+                        response
+                            .headers()
+                            .get(reqwest::header::RETRY_AFTER)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(RetryExecutor::parse_retry_after)
+                    } else {
+                        None
+                    };
+                    format!("HTTP {}", status)
+                }
+                Err(e) => {
+                    let transient = e.is_connect() || e.is_timeout() || e.is_request();
+                    if !transient
+                        || !executor.should_retry(&state, &RetryCondition::ConnectionError)
+                    {
+                        return Err(ToolError::InvocationFailed(format!(
+                            "HTTP request failed: {}",
+                            e
+                        )));
+                    }
+                    state.retry_after_ms = None;
+                    e.to_string()
+                }
+            };
+
+            let delay_ms = executor.next_delay(&state).unwrap_or(0);
+            std::thread::sleep(Duration::from_millis(delay_ms));
+            let now_ms = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            state.record_attempt(delay_ms, &error, now_ms);
+        }
+    }
+
     /// Execute the HTTP request with the given method.
     fn execute(&self, request: HttpRequest) -> Result<HttpResponse, ToolError> {
+        let response = self.send(&request)?;
+
+        // Extract status and where the request ended up
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+
+        // Extract headers
+        let mut headers = HashMap::new();
+        for (key, value) in response.headers() {
+            if let Ok(value_str) = value.to_str() {
+                headers.insert(key.to_string(), value_str.to_string());
+            }
+        }
+
+        // Extract body (HEAD responses carry none, even if Content-Length is set)
+        let textual = is_textual_content_type(headers.get("content-type").map(String::as_str));
+        let (body, body_base64) = if self.method == Method::Head {
+            (String::new(), None)
+        } else if textual {
+            let text = response.text().map_err(|e| {
+                ToolError::InvocationFailed(format!("Failed to read response body: {}", e))
+            })?;
+            (text, None)
+        } else {
+            let bytes = response.bytes().map_err(|e| {
+                ToolError::InvocationFailed(format!("Failed to read response body: {}", e))
+            })?;
+            (String::new(), Some(BASE64.encode(&bytes)))
+        };
+
+        // Parse JSON bodies on request; failures yield null, not an error
+        let json = if request.parse_json == Some(true) {
+            let content_type = headers.get("content-type").map(String::as_str);
+            Some(if is_json_content_type(content_type) {
+                serde_json::from_str(&body).unwrap_or(Value::Null)
+            } else {
+                Value::Null
+            })
+        } else {
+            None
+        };
+
+        Ok(HttpResponse {
+            status,
+            body,
+            body_base64,
+            headers,
+            final_url,
+            json,
+        })
+    }
+
+    /// Stream a GET response body to `request.path`.
+    ///
+    /// The file is only created for 2xx responses, and a partially written
+    /// file is removed if the transfer fails.
+    fn exec_download(&self, request: HttpRequest) -> Result<Value, ToolError> {
+        let path = request
+            .path
+            .clone()
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| {
+                ToolError::InvocationFailed("'path' is required for http.download".into())
+            })?;
+
+        let mut response = self.send(&request)?;
+        let status = response.status().as_u16();
+        if !response.status().is_success() {
+            return Ok(json!({ "status": status, "bytes_written": 0, "path": path }));
+        }
+
+        // Stream into a sibling temp file and rename it into place only once
+        // the body is complete, so a failed download leaves `path` untouched.
+        let dest = std::path::Path::new(&path);
+        let temp = dest.with_file_name(format!(
+            ".{}.{}.{}.part",
+            dest.file_name().unwrap_or_default().to_string_lossy(),
+            std::process::id(),
+            DOWNLOAD_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = std::fs::File::create(&temp).map_err(|e| {
+            ToolError::InvocationFailed(format!("Failed to create '{}': {}", path, e))
+        })?;
+        let mut writer = std::io::BufWriter::new(file);
+        let written = response
+            .copy_to(&mut writer)
+            .map_err(|e| ToolError::InvocationFailed(format!("Download failed: {}", e)))
+            .and_then(|n| {
+                writer.flush().map_err(|e| {
+                    ToolError::InvocationFailed(format!("Failed to write '{}': {}", path, e))
+                })?;
+                Ok(n)
+            });
+        drop(writer);
+        let written = written.and_then(|n| {
+            std::fs::rename(&temp, dest).map_err(|e| {
+                ToolError::InvocationFailed(format!("Failed to write '{}': {}", path, e))
+            })?;
+            Ok(n)
+        });
+        match written {
+            Ok(bytes_written) => Ok(json!({
+                "status": status,
+                "bytes_written": bytes_written,
+                "path": path,
+            })),
+            Err(e) => {
+                let _ = std::fs::remove_file(&temp);
+                Err(e)
+            }
+        }
+    }
+
+    /// Build and send the request described by `request`, applying the
+    /// redirect, timeout, header, body and retry settings.
+    fn send(&self, request: &HttpRequest) -> Result<Response, ToolError> {
         // Validate URL
         if request.url.is_empty() {
             return Err(ToolError::InvocationFailed("URL cannot be empty".into()));
@@ -170,52 +616,77 @@ impl HttpProvider {
             ToolError::InvocationFailed(format!("Invalid URL '{}': {}", request.url, e))
         })?;
 
+        let timeout = match request.timeout_ms {
+            Some(timeout_ms) if timeout_ms <= 0 => {
+                return Err(ToolError::InvocationFailed(format!(
+                    "timeout_ms must be positive, got {}",
+                    timeout_ms
+                )));
+            }
+            Some(timeout_ms) => Some(Duration::from_millis(timeout_ms as u64)),
+            None => None,
+        };
+        // A per-request timeout is a deadline for the whole transfer, while a
+        // client-wide one bounds each wait; downloads want the latter.
+        let client_timeout = timeout.filter(|_| self.method == Method::Download);
+
+        // Redirect policy is client-wide in reqwest, so a non-default policy
+        // needs a dedicated client for this call
+        let policy = Self::redirect_policy(request)?;
+        let custom_client = if policy.is_some() || client_timeout.is_some() {
+            let timeout = client_timeout.unwrap_or(DEFAULT_TIMEOUT);
+            let mut builder = Client::builder().connect_timeout(timeout).timeout(timeout);
+            if let Some(policy) = policy {
+                builder = builder.redirect(policy);
+            }
+            Some(builder.build().map_err(|e| {
+                ToolError::InvocationFailed(format!("Failed to build HTTP client: {}", e))
+            })?)
+        } else {
+            None
+        };
+        let client = custom_client.as_ref().unwrap_or(&self.client);
+
         // Build request
         let mut req = match self.method {
-            Method::Get => self.client.get(url),
-            Method::Post => self.client.post(url),
-            Method::Put => self.client.put(url),
-            Method::Delete => self.client.delete(url),
+            Method::Get | Method::Download => client.get(url),
+            Method::Post => client.post(url),
+            Method::Put => client.put(url),
+            Method::Delete => client.delete(url),
+            Method::Patch => client.patch(url),
+            Method::Head => client.head(url),
+            Method::Options => client.request(reqwest::Method::OPTIONS, url),
         };
 
+        // Override the client-wide timeout for this call
+        if let Some(timeout) = timeout.filter(|_| client_timeout.is_none()) {
+            req = req.timeout(timeout);
+        }
+
         // Add headers
         for (key, value) in &request.headers {
             req = req.header(key, value);
         }
 
-        // Add body for POST/PUT
-        if matches!(self.method, Method::Post | Method::Put) {
+        // Add body for POST/PUT/PATCH
+        if request.body.is_some() && request.body_base64.is_some() {
+            return Err(ToolError::InvocationFailed(
+                "'body' and 'body_base64' are mutually exclusive".into(),
+            ));
+        }
+        if matches!(self.method, Method::Post | Method::Put | Method::Patch) {
             if let Some(body) = &request.body {
                 req = req.body(body.clone());
+            } else if let Some(encoded) = &request.body_base64 {
+                let bytes = BASE64.decode(encoded).map_err(|e| {
+                    ToolError::InvocationFailed(format!("Invalid body_base64: {}", e))
+                })?;
+                req = req.body(bytes);
             }
         }
 
         // Execute request
-        let response = req
-            .send()
-            .map_err(|e| ToolError::InvocationFailed(format!("HTTP request failed: {}", e)))?;
-
-        // Extract status
-        let status = response.status().as_u16();
-
-        // Extract headers
-        let mut headers = HashMap::new();
-        for (key, value) in response.headers() {
-            if let Ok(value_str) = value.to_str() {
-                headers.insert(key.to_string(), value_str.to_string());
-            }
-        }
-
-        // Extract body
-        let body = response.text().map_err(|e| {
-            ToolError::InvocationFailed(format!("Failed to read response body: {}", e))
-        })?;
-
-        Ok(HttpResponse {
-            status,
-            body,
-            headers,
-        })
+        self.send_with_retries(req, request)
     }
 }
 
@@ -238,6 +709,9 @@ impl ToolProvider for HttpProvider {
             .map_err(|e| ToolError::InvocationFailed(format!("Invalid request format: {}", e)))?;
 
         // Execute request
+        if self.method == Method::Download {
+            return self.exec_download(request);
+        }
         let response = self.execute(request)?;
 
         // Serialize response
@@ -254,6 +728,7 @@ impl ToolProvider for HttpProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lumen_runtime::mock_http::{MockHttpServer, Reply};
     use serde_json::json;
 
     #[test]
@@ -272,6 +747,9 @@ mod tests {
             (HttpProvider::post(), "http.post", "POST"),
             (HttpProvider::put(), "http.put", "PUT"),
             (HttpProvider::delete(), "http.delete", "DELETE"),
+            (HttpProvider::patch(), "http.patch", "PATCH"),
+            (HttpProvider::head(), "http.head", "HEAD"),
+            (HttpProvider::options(), "http.options", "OPTIONS"),
         ];
 
         for (provider, expected_name, method) in providers {
@@ -307,6 +785,60 @@ mod tests {
         assert!(output_schema["properties"]["headers"].is_object());
     }
 
+    #[test]
+    fn head_schema_keeps_standard_output_shape() {
+        let head = HttpProvider::head();
+        let get = HttpProvider::get();
+        assert_eq!(head.schema().output_schema, get.schema().output_schema);
+        assert_eq!(
+            head.schema().output_schema["required"],
+            json!(["status", "body", "headers"])
+        );
+        assert_eq!(head.schema().effects, vec!["http"]);
+    }
+
+    #[test]
+    fn schema_documents_timeout_ms() {
+        let provider = HttpProvider::get();
+        let timeout = &provider.schema().input_schema["properties"]["timeout_ms"];
+        assert_eq!(timeout["type"], "integer");
+        assert_eq!(timeout["minimum"], 1);
+    }
+
+    #[test]
+    fn tiny_timeout_to_unreachable_host_fails_fast() {
+        let provider = HttpProvider::get();
+        // 192.0.2.0/24 is TEST-NET-1 (RFC 5737) and never routable.
+        let started = std::time::Instant::now();
+        let result = provider.call(json!({
+            "url": "http://192.0.2.1:81/",
+            "timeout_ms": 50
+        }));
+        assert!(result.is_err());
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "timeout_ms was not applied: took {:?}",
+            started.elapsed()
+        );
+    }
+
+    #[test]
+    fn non_positive_timeout_is_rejected() {
+        let provider = HttpProvider::get();
+        for timeout_ms in [0, -5] {
+            let result = provider.call(json!({
+                "url": "http://192.0.2.1/",
+                "timeout_ms": timeout_ms
+            }));
+            match result.unwrap_err() {
+                ToolError::InvocationFailed(msg) => {
+                    assert!(msg.contains("timeout_ms must be positive"), "{}", msg);
+                }
+                other => panic!("Expected InvocationFailed, got: {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn invalid_url_returns_error() {
         let provider = HttpProvider::get();
@@ -384,6 +916,7 @@ mod tests {
             "Bearer token"
         );
         assert_eq!(request.body.as_ref().unwrap(), "test body");
+        assert!(request.timeout_ms.is_none());
     }
 
     #[test]
@@ -403,16 +936,418 @@ mod tests {
         let response = HttpResponse {
             status: 200,
             body: "OK".to_string(),
+            body_base64: None,
             headers: {
                 let mut map = HashMap::new();
                 map.insert("content-type".to_string(), "text/plain".to_string());
                 map
             },
+            final_url: "https://example.com/".to_string(),
+            json: None,
         };
 
         let json = serde_json::to_value(response).unwrap();
         assert_eq!(json["status"], 200);
         assert_eq!(json["body"], "OK");
         assert_eq!(json["headers"]["content-type"], "text/plain");
+        assert!(json.get("body_base64").is_none());
+        assert_eq!(json["final_url"], "https://example.com/");
+        assert!(json.get("json").is_none());
+    }
+
+    /// A `200 OK` reply carrying `body` as `content_type`.
+    fn ok_reply(content_type: &str, body: impl Into<Vec<u8>>) -> Reply {
+        Reply::new("200 OK", &[("Content-Type", content_type)], body)
+    }
+
+    #[test]
+    fn binary_upload_round_trips_through_base64() {
+        let bytes: Vec<u8> = vec![0x00, 0x9f, 0x92, 0x96, 0xff, 0xfe, 0x0a];
+        let encoded = BASE64.encode(&bytes);
+        let url = MockHttpServer::start(vec![Reply::Echo("application/octet-stream".into())])
+            .url("/upload");
+
+        let result = HttpProvider::post()
+            .call(json!({ "url": url, "body_base64": encoded }))
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["body"], "");
+        assert_eq!(result["body_base64"], encoded);
+    }
+
+    #[test]
+    fn image_response_is_returned_as_base64() {
+        let png_header: Vec<u8> = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let url = MockHttpServer::start(vec![ok_reply("image/png", png_header.clone())]).url("/");
+
+        let result = HttpProvider::get().call(json!({ "url": url })).unwrap();
+        assert_eq!(result["body"], "");
+        let decoded = BASE64
+            .decode(result["body_base64"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(decoded, png_header);
+    }
+
+    #[test]
+    fn textual_response_has_no_base64_body() {
+        let url = MockHttpServer::start(vec![ok_reply("application/json; charset=utf-8", b"{}")])
+            .url("/");
+        let result = HttpProvider::get().call(json!({ "url": url })).unwrap();
+        assert_eq!(result["body"], "{}");
+        assert!(result.get("body_base64").is_none());
+    }
+
+    #[test]
+    fn body_and_body_base64_are_mutually_exclusive() {
+        let result = HttpProvider::post().call(json!({
+            "url": "http://127.0.0.1:9/",
+            "body": "text",
+            "body_base64": "AAEC"
+        }));
+        match result.unwrap_err() {
+            ToolError::InvocationFailed(msg) => assert!(msg.contains("mutually exclusive")),
+            other => panic!("Expected InvocationFailed, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn content_type_classification() {
+        assert!(is_textual_content_type(None));
+        assert!(is_textual_content_type(Some("text/html; charset=utf-8")));
+        assert!(is_textual_content_type(Some("application/problem+json")));
+        assert!(!is_textual_content_type(Some("image/png")));
+        assert!(!is_textual_content_type(Some("application/octet-stream")));
+        assert!(!is_textual_content_type(Some("application/pdf")));
+    }
+
+    /// Script a 302 to `/final` followed by the `/final` page.
+    fn redirect_server() -> MockHttpServer {
+        MockHttpServer::start(vec![
+            Reply::new("302 Found", &[("Location", "/final")], ""),
+            ok_reply("text/plain", "landed"),
+        ])
+    }
+
+    #[test]
+    fn schema_documents_redirect_controls() {
+        let provider = HttpProvider::get();
+        let props = &provider.schema().input_schema["properties"];
+        assert_eq!(props["follow_redirects"]["type"], "boolean");
+        assert_eq!(props["max_redirects"]["type"], "integer");
+        assert!(provider.schema().output_schema["properties"]
+            .get("final_url")
+            .is_some());
+    }
+
+    #[test]
+    fn disabled_redirects_surface_the_3xx() {
+        let server = redirect_server();
+        let result = HttpProvider::get()
+            .call(json!({ "url": server.url("/start"), "follow_redirects": false }))
+            .unwrap();
+        assert_eq!(result["status"], 302);
+        assert_eq!(result["headers"]["location"], "/final");
+        assert_eq!(result["final_url"], server.url("/start"));
+    }
+
+    #[test]
+    fn followed_redirect_reports_final_url() {
+        let server = redirect_server();
+        let result = HttpProvider::get()
+            .call(json!({ "url": server.url("/start") }))
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["body"], "landed");
+        assert_eq!(result["final_url"], server.url("/final"));
+    }
+
+    #[test]
+    fn exceeding_max_redirects_fails() {
+        let server = redirect_server();
+        let result = HttpProvider::get().call(json!({
+            "url": server.url("/start"),
+            "max_redirects": 0
+        }));
+        assert!(matches!(result, Err(ToolError::InvocationFailed(_))));
+    }
+
+    #[test]
+    fn negative_max_redirects_is_rejected() {
+        let result = HttpProvider::get().call(json!({
+            "url": "http://127.0.0.1:9/",
+            "max_redirects": -1
+        }));
+        match result.unwrap_err() {
+            ToolError::InvocationFailed(msg) => assert!(msg.contains("max_redirects")),
+            other => panic!("Expected InvocationFailed, got: {:?}", other),
+        }
+    }
+
+    fn unavailable() -> Reply {
+        Reply::new("503 Service Unavailable", &[("Retry-After", "0")], "")
+    }
+
+    fn bad_gateway() -> Reply {
+        Reply::new("502 Bad Gateway", &[], "")
+    }
+
+    fn ok() -> Reply {
+        ok_reply("text/plain", "ok")
+    }
+
+    #[test]
+    fn schema_documents_retry_fields() {
+        let provider = HttpProvider::get();
+        let props = &provider.schema().input_schema["properties"];
+        assert_eq!(props["retries"]["type"], "integer");
+        assert_eq!(props["retry_on"]["type"], "array");
+        assert_eq!(props["retry_non_idempotent"]["type"], "boolean");
+    }
+
+    #[test]
+    fn retries_transient_failures_until_success() {
+        let server = MockHttpServer::start(vec![bad_gateway(), unavailable(), ok()]);
+        let url = server.url("/flaky");
+        let result = HttpProvider::get()
+            .call(json!({ "url": url, "retries": 3 }))
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["body"], "ok");
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn exhausted_retries_return_last_response() {
+        let server = MockHttpServer::start(vec![bad_gateway(), bad_gateway()]);
+        let url = server.url("/flaky");
+        let result = HttpProvider::get()
+            .call(json!({ "url": url, "retries": 1 }))
+            .unwrap();
+        assert_eq!(result["status"], 502);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn status_outside_retry_on_is_not_retried() {
+        let server = MockHttpServer::start(vec![bad_gateway(), ok()]);
+        let url = server.url("/flaky");
+        let result = HttpProvider::get()
+            .call(json!({ "url": url, "retries": 2, "retry_on": [500] }))
+            .unwrap();
+        assert_eq!(result["status"], 502);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn connection_errors_retry_only_idempotent_methods() {
+        let server = MockHttpServer::start(vec![Reply::Drop, ok()]);
+        let url = server.url("/flaky");
+        let result = HttpProvider::get()
+            .call(json!({ "url": url, "retries": 1 }))
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(server.requests().len(), 2);
+
+        let server = MockHttpServer::start(vec![Reply::Drop, ok()]);
+        let url = server.url("/flaky");
+        let result = HttpProvider::post().call(json!({ "url": url, "body": "x", "retries": 1 }));
+        assert!(result.is_err());
+        assert_eq!(server.requests().len(), 1);
+
+        let server = MockHttpServer::start(vec![Reply::Drop, ok()]);
+        let url = server.url("/flaky");
+        let result = HttpProvider::post()
+            .call(json!({
+                "url": url,
+                "body": "x",
+                "retries": 1,
+                "retry_non_idempotent": true
+            }))
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn negative_retries_is_rejected() {
+        let result = HttpProvider::get().call(json!({
+            "url": "http://127.0.0.1:9/",
+            "retries": -2
+        }));
+        match result.unwrap_err() {
+            ToolError::InvocationFailed(msg) => assert!(msg.contains("retries")),
+            other => panic!("Expected InvocationFailed, got: {:?}", other),
+        }
+    }
+
+    fn temp_download_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("lumen-http-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn download_schema_declares_http_and_fs() {
+        let provider = HttpProvider::download();
+        assert_eq!(provider.name(), "http.download");
+        assert_eq!(
+            provider.schema().effects,
+            vec!["http".to_string(), "fs".to_string()]
+        );
+        assert_eq!(
+            provider.schema().input_schema["required"],
+            json!(["url", "path"])
+        );
+        assert!(provider.schema().input_schema["properties"]
+            .get("body")
+            .is_none());
+    }
+
+    #[test]
+    fn download_streams_body_to_file() {
+        let body = b"line one\nline two\n\x00\xff".to_vec();
+        let url = MockHttpServer::start(vec![ok_reply("application/octet-stream", body.clone())])
+            .url("/");
+        let path = temp_download_path("ok.bin");
+
+        let result = HttpProvider::download()
+            .call(json!({ "url": url, "path": path.to_str().unwrap() }))
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["bytes_written"], body.len());
+        assert_eq!(result["path"], path.to_str().unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), body);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn timeout_bounds_idle_time_not_the_whole_download() {
+        // Each pause is well under the timeout, but together they exceed it.
+        let head = b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\nConnection: close\r\n\r\n".to_vec();
+        let chunks = vec![
+            head,
+            b"ab".to_vec(),
+            b"cd".to_vec(),
+            b"ef".to_vec(),
+            b"gh".to_vec(),
+        ];
+        let gap = Duration::from_millis(150);
+        let url = MockHttpServer::start(vec![Reply::Paced { chunks, gap }]).url("/slow.bin");
+        let path = temp_download_path("slow.bin");
+
+        let result = HttpProvider::download()
+            .call(json!({ "url": url, "path": path.to_str().unwrap(), "timeout_ms": 400 }))
+            .unwrap();
+        assert_eq!(result["bytes_written"], 8);
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcdefgh");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn download_times_out_when_the_body_stalls() {
+        let head = b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\nab".to_vec();
+        let chunks = vec![head, b"cd".to_vec()];
+        let gap = Duration::from_millis(800);
+        let url = MockHttpServer::start(vec![Reply::Paced { chunks, gap }]).url("/stall.bin");
+        let path = temp_download_path("stall.bin");
+
+        let result = HttpProvider::download()
+            .call(json!({ "url": url, "path": path.to_str().unwrap(), "timeout_ms": 200 }));
+        assert!(matches!(result, Err(ToolError::InvocationFailed(_))));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn download_does_not_create_file_on_error_status() {
+        let url = MockHttpServer::start(vec![Reply::new("404 Not Found", &[], "not found")])
+            .url("/missing");
+        let path = temp_download_path("missing.bin");
+
+        let result = HttpProvider::download()
+            .call(json!({ "url": url, "path": path.to_str().unwrap() }))
+            .unwrap();
+        assert_eq!(result["status"], 404);
+        assert_eq!(result["bytes_written"], 0);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn interrupted_download_keeps_existing_file() {
+        // Promises 100 bytes but closes after 9.
+        let truncated =
+            b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\npartial.."
+                .to_vec();
+        let url = MockHttpServer::start(vec![Reply::Raw(truncated)]).url("/big.bin");
+        let path = temp_download_path("existing.bin");
+        std::fs::write(&path, b"previous contents").unwrap();
+
+        let result =
+            HttpProvider::download().call(json!({ "url": url, "path": path.to_str().unwrap() }));
+        assert!(matches!(result, Err(ToolError::InvocationFailed(_))));
+        assert_eq!(std::fs::read(&path).unwrap(), b"previous contents");
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let leftovers = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let file = entry.file_name().to_string_lossy().into_owned();
+                file.starts_with(&format!(".{}", name)) && file.ends_with(".part")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn download_requires_path() {
+        let result = HttpProvider::download().call(json!({ "url": "http://127.0.0.1:9/" }));
+        match result.unwrap_err() {
+            ToolError::InvocationFailed(msg) => assert!(msg.contains("path")),
+            other => panic!("Expected InvocationFailed, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_json_populates_json_field() {
+        let url = MockHttpServer::start(vec![ok_reply(
+            "application/json; charset=utf-8",
+            br#"{"id": 7, "tags": ["a", "b"]}"#,
+        )])
+        .url("/");
+        let result = HttpProvider::get()
+            .call(json!({ "url": url, "parse_json": true }))
+            .unwrap();
+        assert_eq!(result["json"], json!({"id": 7, "tags": ["a", "b"]}));
+        assert_eq!(result["body"], r#"{"id": 7, "tags": ["a", "b"]}"#);
+    }
+
+    #[test]
+    fn parse_json_on_non_json_body_is_null() {
+        let url =
+            MockHttpServer::start(vec![ok_reply("text/plain", b"{\"looks\": \"like json\"}")])
+                .url("/");
+        let result = HttpProvider::get()
+            .call(json!({ "url": url, "parse_json": true }))
+            .unwrap();
+        assert_eq!(result["json"], Value::Null);
+        assert_eq!(result["body"], "{\"looks\": \"like json\"}");
+    }
+
+    #[test]
+    fn malformed_json_is_null_not_an_error() {
+        let url =
+            MockHttpServer::start(vec![ok_reply("application/problem+json", b"{\"broken\": ")])
+                .url("/");
+        let result = HttpProvider::get()
+            .call(json!({ "url": url, "parse_json": true }))
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["json"], Value::Null);
+        assert_eq!(result["body"], "{\"broken\": ");
+    }
+
+    #[test]
+    fn json_field_absent_without_parse_json() {
+        let url = MockHttpServer::start(vec![ok_reply("application/json", b"[1, 2]")]).url("/");
+        let result = HttpProvider::get().call(json!({ "url": url })).unwrap();
+        assert!(result.get("json").is_none());
     }
 }
@@ -1,8 +1,8 @@
 //! JSON manipulation provider for Lumen.
 //!
 //! Provides tools for parsing, stringifying, and manipulating JSON data
-//! with dot-path navigation, JSONPath-style lookup, deep merge, flatten,
-//! and diff capabilities.
+//! with JSONPath-style lookup and updates (get/set/delete), deep merge,
+//! flatten/unflatten, diff, and RFC 6902 JSON Patch capabilities.
 
 use lumen_runtime::tools::{ToolError, ToolProvider, ToolSchema};
 use serde_json::{json, Value};
@@ -23,7 +23,7 @@ impl JsonProvider {
                     "properties": {
                         "operation": {
                             "type": "string",
-                            "enum": ["parse", "stringify", "get", "set", "merge", "flatten", "diff"]
+                            "enum": ["parse", "stringify", "get", "set", "delete", "merge", "flatten", "unflatten", "diff", "patch"]
                         }
                     },
                     "required": ["operation"]
@@ -72,21 +72,7 @@ impl JsonProvider {
                         ))
                     })?;
 
-                    let resolved_idx = if *idx < 0 {
-                        let len = arr.len() as i64;
-                        let pos = len + idx;
-                        if pos < 0 {
-                            return Err(ToolError::InvocationFailed(format!(
-                                "Negative index {} out of bounds for array of length {} in path: {}",
-                                idx,
-                                arr.len(),
-                                path
-                            )));
-                        }
-                        pos as usize
-                    } else {
-                        *idx as usize
-                    };
+                    let resolved_idx = resolve_index(*idx, arr.len(), path)?;
 
                     current = arr.get(resolved_idx).ok_or_else(|| {
                         ToolError::InvocationFailed(format!(
@@ -103,17 +89,15 @@ impl JsonProvider {
         Ok(current.clone())
     }
 
-    /// Set a value in a JSON object using dot-path notation.
-    /// Creates intermediate objects as needed.
+    /// Set a value in a JSON document using JSONPath-style notation.
+    ///
+    /// Creates intermediate objects and arrays as needed; writing past the
+    /// end of an array pads it with `null`. Negative indices address existing
+    /// elements from the end and error when out of range.
     fn set(&self, json: &Value, path: &str, value: Value) -> Result<Value, ToolError> {
-        let parts: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
-
-        if parts.is_empty() {
-            return Err(ToolError::InvocationFailed("Empty path".to_string()));
-        }
-
+        let segments = parse_path_segments(path)?;
         let mut result = json.clone();
-        self.set_recursive(&mut result, &parts, value)?;
+        self.set_recursive(&mut result, &segments, value, path)?;
         Ok(result)
     }
 
@@ -121,41 +105,104 @@ impl JsonProvider {
     fn set_recursive(
         &self,
         current: &mut Value,
-        path: &[&str],
+        segments: &[PathSegment],
         value: Value,
+        path: &str,
     ) -> Result<(), ToolError> {
-        if path.is_empty() {
+        let Some((segment, rest)) = segments.split_first() else {
             return Ok(());
+        };
+
+        if !segment.matches_container(current) {
+            if rest.is_empty() {
+                return Err(ToolError::InvocationFailed(match segment {
+                    PathSegment::Key(_) => "Cannot set property on non-object".to_string(),
+                    PathSegment::Index(_) => "Cannot set index on non-array".to_string(),
+                }));
+            }
+            // Intermediate values of the wrong shape are replaced.
+            *current = segment.empty_container();
         }
 
-        if path.len() == 1 {
-            // Base case: set the value
-            if let Value::Object(ref mut map) = current {
-                map.insert(path[0].to_string(), value);
+        let slot = match (segment, current) {
+            (PathSegment::Key(key), Value::Object(map)) => {
+                map.entry(key.clone()).or_insert(Value::Null)
+            }
+            (PathSegment::Index(idx), Value::Array(arr)) => {
+                let pos = if *idx >= 0 {
+                    *idx as usize
+                } else {
+                    resolve_index(*idx, arr.len(), path)?
+                };
+                if pos >= arr.len() {
+                    arr.resize(pos + 1, Value::Null);
+                }
+                &mut arr[pos]
+            }
+            _ => unreachable!("container shape checked above"),
+        };
+
+        match rest.first() {
+            None => {
+                *slot = value;
                 Ok(())
-            } else {
-                Err(ToolError::InvocationFailed(
-                    "Cannot set property on non-object".to_string(),
-                ))
             }
-        } else {
-            // Recursive case: navigate deeper
-            if !current.is_object() {
-                *current = Value::Object(serde_json::Map::new());
+            Some(next) => {
+                if !next.matches_container(slot) {
+                    *slot = next.empty_container();
+                }
+                self.set_recursive(slot, rest, value, path)
             }
+        }
+    }
 
-            if let Value::Object(ref mut map) = current {
-                let key = path[0].to_string();
-                let next = map
-                    .entry(key.clone())
-                    .or_insert(Value::Object(serde_json::Map::new()));
-                self.set_recursive(next, &path[1..], value)
-            } else {
-                Err(ToolError::InvocationFailed(
-                    "Cannot navigate non-object".to_string(),
-                ))
+    /// Remove the object key or array element at a JSONPath-style path.
+    fn delete(&self, json: &Value, path: &str) -> Result<Value, ToolError> {
+        let segments = parse_path_segments(path)?;
+        let (last, parents) = segments
+            .split_last()
+            .ok_or_else(|| ToolError::InvocationFailed("Empty path".to_string()))?;
+
+        let mut result = json.clone();
+        let mut current = &mut result;
+        for segment in parents {
+            current = match (segment, current) {
+                (PathSegment::Key(key), Value::Object(map)) => map.get_mut(key.as_str()),
+                (PathSegment::Index(idx), Value::Array(arr)) => {
+                    let pos = resolve_index(*idx, arr.len(), path)?;
+                    arr.get_mut(pos)
+                }
+                _ => None,
             }
+            .ok_or_else(|| ToolError::InvocationFailed(format!("Path not found: {}", path)))?;
         }
+
+        match (last, current) {
+            (PathSegment::Key(key), Value::Object(map)) => {
+                map.remove(key.as_str()).ok_or_else(|| {
+                    ToolError::InvocationFailed(format!("Path not found: {}", path))
+                })?;
+            }
+            (PathSegment::Index(idx), Value::Array(arr)) => {
+                let pos = resolve_index(*idx, arr.len(), path)?;
+                if pos >= arr.len() {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "Index {} out of bounds for array of length {} in path: {}",
+                        idx,
+                        arr.len(),
+                        path
+                    )));
+                }
+                arr.remove(pos);
+            }
+            _ => {
+                return Err(ToolError::InvocationFailed(format!(
+                    "Path not found: {}",
+                    path
+                )))
+            }
+        }
+        Ok(result)
     }
 
     /// Deep merge two JSON values. For objects, recursively merges keys.
@@ -188,12 +235,56 @@ impl JsonProvider {
     /// ```json
     /// {"a": {"b": 1, "c": [2, 3]}} → {"a.b": 1, "a.c[0]": 2, "a.c[1]": 3}
     /// ```
-    fn flatten(&self, value: &Value) -> Result<Value, ToolError> {
+    ///
+    /// Empty nested objects and arrays are kept as leaf values so that
+    /// [`unflatten`](Self::unflatten) can restore them.
+    ///
+    /// The key separator and array index style come from `config`; with
+    /// `delimiter: "__"` and [`ArrayStyle::Dot`] the example above becomes
+    /// `{"a__b": 1, "a__c__0": 2, "a__c__1": 3}`.
+    fn flatten(&self, value: &Value, config: &FlattenConfig) -> Result<Value, ToolError> {
         let mut result = serde_json::Map::new();
-        flatten_recursive(value, String::new(), &mut result);
+        flatten_recursive(value, String::new(), config, &mut result);
         Ok(Value::Object(result))
     }
 
+    /// Rebuild a nested JSON value from dot/bracket keys produced by `flatten`.
+    ///
+    /// Example:
+    /// ```json
+    /// {"a.b": 1, "a.c[0]": 2} → {"a": {"b": 1, "c": [2]}}
+    /// ```
+    ///
+    /// Sparse array indices are padded with `null`. Keys that disagree about
+    /// the shape at a path (e.g. both `a` and `a.b`) are rejected.
+    fn unflatten(&self, value: &Value) -> Result<Value, ToolError> {
+        let map = value.as_object().ok_or_else(|| {
+            ToolError::InvocationFailed("unflatten expects an object of flat keys".to_string())
+        })?;
+
+        // A lone empty key is how `flatten` represents a scalar root.
+        if let Some(root) = map.get("") {
+            if map.len() > 1 {
+                return Err(ToolError::InvocationFailed(
+                    "Conflicting keys: root value '' cannot be combined with other keys"
+                        .to_string(),
+                ));
+            }
+            return Ok(root.clone());
+        }
+
+        let mut root = UnflattenNode::Empty;
+        for (key, leaf) in map {
+            let segments = parse_path_segments(key)?;
+            unflatten_insert(&mut root, &segments, leaf.clone(), key)?;
+        }
+
+        Ok(match root {
+            UnflattenNode::Empty => Value::Object(serde_json::Map::new()),
+            node => node.into_value(),
+        })
+    }
+
     /// Compute the diff between two JSON values.
     ///
     /// Returns an object with:
@@ -220,6 +311,25 @@ impl JsonProvider {
             "changes": Value::Object(changes),
         }))
     }
+
+    /// Apply an RFC 6902 JSON Patch document.
+    ///
+    /// Supports the `add`, `remove`, `replace`, `move`, `copy`, and `test`
+    /// operations, addressed with JSON Pointer paths (`/a/b/0`). Operations
+    /// are applied to a copy of the input, so a failure part-way through the
+    /// sequence leaves the original document untouched.
+    fn patch(&self, json: &Value, ops: &[Value]) -> Result<Value, ToolError> {
+        let mut result = json.clone();
+        for (i, op) in ops.iter().enumerate() {
+            apply_patch_op(&mut result, op).map_err(|e| match e {
+                ToolError::InvocationFailed(msg) => {
+                    ToolError::InvocationFailed(format!("Patch operation {} failed: {}", i, msg))
+                }
+                other => other,
+            })?;
+        }
+        Ok(result)
+    }
 }
 
 impl Default for JsonProvider {
@@ -285,6 +395,15 @@ impl ToolProvider for JsonProvider {
                     .clone();
                 self.set(json_val, path, value)
             }
+            "delete" => {
+                let json_val = input.get("json").ok_or_else(|| {
+                    ToolError::InvocationFailed("Missing 'json' field".to_string())
+                })?;
+                let path = input.get("path").and_then(|v| v.as_str()).ok_or_else(|| {
+                    ToolError::InvocationFailed("Missing 'path' string".to_string())
+                })?;
+                self.delete(json_val, path)
+            }
             "merge" => {
                 let a = input
                     .get("a")
@@ -298,7 +417,14 @@ impl ToolProvider for JsonProvider {
                 let value = input.get("value").ok_or_else(|| {
                     ToolError::InvocationFailed("Missing 'value' field".to_string())
                 })?;
-                self.flatten(value)
+                let config = FlattenConfig::from_input(&input)?;
+                self.flatten(value, &config)
+            }
+            "unflatten" => {
+                let value = input.get("value").ok_or_else(|| {
+                    ToolError::InvocationFailed("Missing 'value' field".to_string())
+                })?;
+                self.unflatten(value)
             }
             "diff" => {
                 let a = input
@@ -309,6 +435,18 @@ impl ToolProvider for JsonProvider {
                     .ok_or_else(|| ToolError::InvocationFailed("Missing 'b' field".to_string()))?;
                 self.diff(a, b)
             }
+            "patch" => {
+                let json_val = input.get("json").ok_or_else(|| {
+                    ToolError::InvocationFailed("Missing 'json' field".to_string())
+                })?;
+                let ops = input
+                    .get("patch")
+                    .and_then(|v| v.as_array())
+                    .ok_or_else(|| {
+                        ToolError::InvocationFailed("Missing 'patch' array".to_string())
+                    })?;
+                self.patch(json_val, ops)
+            }
             _ => Err(ToolError::InvocationFailed(format!(
                 "Unknown operation: {}",
                 operation
@@ -330,6 +468,42 @@ enum PathSegment {
     Index(i64),
 }
 
+impl PathSegment {
+    /// Whether `value` is the kind of container this segment indexes into.
+    fn matches_container(&self, value: &Value) -> bool {
+        match self {
+            PathSegment::Key(_) => value.is_object(),
+            PathSegment::Index(_) => value.is_array(),
+        }
+    }
+
+    /// An empty container this segment can index into.
+    fn empty_container(&self) -> Value {
+        match self {
+            PathSegment::Key(_) => Value::Object(serde_json::Map::new()),
+            PathSegment::Index(_) => Value::Array(Vec::new()),
+        }
+    }
+}
+
+/// Resolve a possibly negative index against an array of length `len`.
+///
+/// Negative indices count from the end and must land on an existing element;
+/// non-negative indices are returned as-is for the caller to bounds-check.
+fn resolve_index(idx: i64, len: usize, path: &str) -> Result<usize, ToolError> {
+    if idx >= 0 {
+        return Ok(idx as usize);
+    }
+    let pos = len as i64 + idx;
+    if pos < 0 {
+        return Err(ToolError::InvocationFailed(format!(
+            "Negative index {} out of bounds for array of length {} in path: {}",
+            idx, len, path
+        )));
+    }
+    Ok(pos as usize)
+}
+
 /// Parse a JSONPath-style path string into segments.
 ///
 /// Supports:
@@ -414,27 +588,309 @@ fn parse_path_segments(path: &str) -> Result<Vec<PathSegment>, ToolError> {
     Ok(segments)
 }
 
+/// Parse an RFC 6901 JSON Pointer into unescaped reference tokens.
+///
+/// - `""` → [] (the whole document)
+/// - `"/a/b/0"` → ["a", "b", "0"]
+/// - `"/a~1b/c~0d"` → ["a/b", "c~d"]
+fn parse_json_pointer(pointer: &str) -> Result<Vec<String>, ToolError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rest = pointer.strip_prefix('/').ok_or_else(|| {
+        ToolError::InvocationFailed(format!(
+            "Invalid JSON pointer '{}': must be empty or start with '/'",
+            pointer
+        ))
+    })?;
+
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+// =============================================================================
+// JSON Patch Helpers (RFC 6902)
+// =============================================================================
+
+/// Apply a single JSON Patch operation to `doc` in place.
+fn apply_patch_op(doc: &mut Value, op: &Value) -> Result<(), ToolError> {
+    let kind = op
+        .get("op")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::InvocationFailed("Missing 'op' string".to_string()))?;
+    let path = patch_str_field(op, "path")?;
+
+    match kind {
+        "add" => pointer_add(doc, path, patch_value_field(op)?),
+        "remove" => pointer_remove(doc, path).map(|_| ()),
+        "replace" => {
+            let value = patch_value_field(op)?;
+            let target = pointer_get_mut(doc, path)?;
+            *target = value;
+            Ok(())
+        }
+        "move" => {
+            let from = patch_str_field(op, "from")?;
+            if path != from && path.starts_with(&format!("{}/", from)) {
+                return Err(ToolError::InvocationFailed(format!(
+                    "Cannot move '{}' into its own child '{}'",
+                    from, path
+                )));
+            }
+            let value = pointer_remove(doc, from)?;
+            pointer_add(doc, path, value)
+        }
+        "copy" => {
+            let from = patch_str_field(op, "from")?;
+            let value = pointer_get_mut(doc, from)?.clone();
+            pointer_add(doc, path, value)
+        }
+        "test" => {
+            let expected = patch_value_field(op)?;
+            let actual = pointer_get_mut(doc, path)?;
+            if *actual != expected {
+                return Err(ToolError::InvocationFailed(format!(
+                    "Test failed at '{}': expected {}, found {}",
+                    path, expected, actual
+                )));
+            }
+            Ok(())
+        }
+        _ => Err(ToolError::InvocationFailed(format!(
+            "Unknown patch op: {}",
+            kind
+        ))),
+    }
+}
+
+/// Read a required string field from a patch operation.
+fn patch_str_field<'a>(op: &'a Value, field: &str) -> Result<&'a str, ToolError> {
+    op.get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::InvocationFailed(format!("Missing '{}' string", field)))
+}
+
+/// Read the required `value` field from a patch operation.
+fn patch_value_field(op: &Value) -> Result<Value, ToolError> {
+    op.get("value")
+        .cloned()
+        .ok_or_else(|| ToolError::InvocationFailed("Missing 'value' field".to_string()))
+}
+
+/// Parse an array index token. `-` is only meaningful for `add` and is
+/// handled by the caller.
+fn parse_pointer_index(token: &str, len: usize, pointer: &str) -> Result<usize, ToolError> {
+    let valid = !token.is_empty()
+        && token.chars().all(|c| c.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    let idx = if valid {
+        token.parse::<usize>().ok()
+    } else {
+        None
+    };
+    match idx {
+        Some(i) if i < len => Ok(i),
+        Some(i) => Err(ToolError::InvocationFailed(format!(
+            "Index {} out of bounds for array of length {} at pointer: {}",
+            i, len, pointer
+        ))),
+        None => Err(ToolError::InvocationFailed(format!(
+            "Invalid array index '{}' at pointer: {}",
+            token, pointer
+        ))),
+    }
+}
+
+/// Resolve a JSON pointer to a mutable reference into `doc`.
+fn pointer_get_mut<'a>(doc: &'a mut Value, pointer: &str) -> Result<&'a mut Value, ToolError> {
+    let tokens = parse_json_pointer(pointer)?;
+    let mut current = doc;
+    for token in &tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(token.as_str()).ok_or_else(|| {
+                ToolError::InvocationFailed(format!("Path not found: {}", pointer))
+            })?,
+            Value::Array(arr) => {
+                let idx = parse_pointer_index(token, arr.len(), pointer)?;
+                &mut arr[idx]
+            }
+            _ => {
+                return Err(ToolError::InvocationFailed(format!(
+                    "Path not found: {}",
+                    pointer
+                )))
+            }
+        };
+    }
+    Ok(current)
+}
+
+/// Split a pointer into its parent pointer and final token.
+fn split_pointer(pointer: &str) -> Result<(String, String), ToolError> {
+    let mut tokens = parse_json_pointer(pointer)?;
+    let last = tokens.pop().ok_or_else(|| {
+        ToolError::InvocationFailed("Cannot target the document root".to_string())
+    })?;
+    let parent: String = tokens
+        .iter()
+        .map(|t| format!("/{}", t.replace('~', "~0").replace('/', "~1")))
+        .collect();
+    Ok((parent, last))
+}
+
+/// Insert `value` at `pointer`, following RFC 6902 `add` semantics.
+fn pointer_add(doc: &mut Value, pointer: &str, value: Value) -> Result<(), ToolError> {
+    if pointer.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+
+    let (parent, last) = split_pointer(pointer)?;
+    match pointer_get_mut(doc, &parent)? {
+        Value::Object(map) => {
+            map.insert(last, value);
+            Ok(())
+        }
+        Value::Array(arr) => {
+            if last == "-" {
+                arr.push(value);
+            } else {
+                // Inserting at `len` is allowed (append).
+                let idx = parse_pointer_index(&last, arr.len() + 1, pointer)?;
+                arr.insert(idx, value);
+            }
+            Ok(())
+        }
+        _ => Err(ToolError::InvocationFailed(format!(
+            "Cannot add to non-container at pointer: {}",
+            pointer
+        ))),
+    }
+}
+
+/// Remove and return the value at `pointer`.
+fn pointer_remove(doc: &mut Value, pointer: &str) -> Result<Value, ToolError> {
+    let (parent, last) = split_pointer(pointer)?;
+    match pointer_get_mut(doc, &parent)? {
+        Value::Object(map) => map
+            .remove(last.as_str())
+            .ok_or_else(|| ToolError::InvocationFailed(format!("Path not found: {}", pointer))),
+        Value::Array(arr) => {
+            let idx = parse_pointer_index(&last, arr.len(), pointer)?;
+            Ok(arr.remove(idx))
+        }
+        _ => Err(ToolError::InvocationFailed(format!(
+            "Path not found: {}",
+            pointer
+        ))),
+    }
+}
+
 // =============================================================================
 // Flatten Helpers
 // =============================================================================
 
-/// Recursively flatten a JSON value into dot-notation keys.
-fn flatten_recursive(value: &Value, prefix: String, result: &mut serde_json::Map<String, Value>) {
+/// How array indices are rendered in flattened keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ArrayStyle {
+    /// `a[0]` — the historical default.
+    #[default]
+    Bracket,
+    /// `a.0` — the index is a regular segment joined with the delimiter.
+    Dot,
+}
+
+/// Key formatting options for `flatten`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FlattenConfig {
+    delimiter: String,
+    array_style: ArrayStyle,
+}
+
+impl Default for FlattenConfig {
+    fn default() -> Self {
+        Self {
+            delimiter: ".".to_string(),
+            array_style: ArrayStyle::Bracket,
+        }
+    }
+}
+
+impl FlattenConfig {
+    /// Read the optional `delimiter` and `array_style` fields of a tool call.
+    fn from_input(input: &Value) -> Result<Self, ToolError> {
+        let mut config = Self::default();
+        if let Some(delimiter) = input.get("delimiter") {
+            let delimiter = delimiter.as_str().ok_or_else(|| {
+                ToolError::InvocationFailed("'delimiter' must be a string".to_string())
+            })?;
+            if delimiter.is_empty() {
+                return Err(ToolError::InvocationFailed(
+                    "'delimiter' must not be empty".to_string(),
+                ));
+            }
+            config.delimiter = delimiter.to_string();
+        }
+        if let Some(style) = input.get("array_style") {
+            config.array_style = match style.as_str() {
+                Some("bracket") => ArrayStyle::Bracket,
+                Some("dot") => ArrayStyle::Dot,
+                _ => {
+                    return Err(ToolError::InvocationFailed(format!(
+                        "Invalid 'array_style': {} (expected \"bracket\" or \"dot\")",
+                        style
+                    )))
+                }
+            };
+        }
+        Ok(config)
+    }
+
+    /// Append an object key to `prefix`.
+    fn key(&self, prefix: &str, key: &str) -> String {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}{}{}", prefix, self.delimiter, key)
+        }
+    }
+
+    /// Append an array index to `prefix`.
+    fn index(&self, prefix: &str, i: usize) -> String {
+        match self.array_style {
+            ArrayStyle::Bracket => format!("{}[{}]", prefix, i),
+            ArrayStyle::Dot => self.key(prefix, &i.to_string()),
+        }
+    }
+}
+
+/// Recursively flatten a JSON value into delimited keys.
+fn flatten_recursive(
+    value: &Value,
+    prefix: String,
+    config: &FlattenConfig,
+    result: &mut serde_json::Map<String, Value>,
+) {
     match value {
+        // Nested empty containers have no leaves; keep them as values so the
+        // shape survives a round trip.
+        Value::Object(map) if map.is_empty() && !prefix.is_empty() => {
+            result.insert(prefix, value.clone());
+        }
+        Value::Array(arr) if arr.is_empty() && !prefix.is_empty() => {
+            result.insert(prefix, value.clone());
+        }
         Value::Object(map) => {
             for (key, val) in map {
-                let new_prefix = if prefix.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{}.{}", prefix, key)
-                };
-                flatten_recursive(val, new_prefix, result);
+                flatten_recursive(val, config.key(&prefix, key), config, result);
             }
         }
         Value::Array(arr) => {
             for (i, val) in arr.iter().enumerate() {
-                let new_prefix = format!("{}[{}]", prefix, i);
-                flatten_recursive(val, new_prefix, result);
+                flatten_recursive(val, config.index(&prefix, i), config, result);
             }
         }
         _ => {
@@ -444,6 +900,111 @@ fn flatten_recursive(value: &Value, prefix: String, result: &mut serde_json::Map
     }
 }
 
+// =============================================================================
+// Unflatten Helpers
+// =============================================================================
+
+/// Intermediate tree used while rebuilding a nested value from flat keys.
+///
+/// Arrays stay sparse until the end so indices can arrive in any order.
+enum UnflattenNode {
+    Empty,
+    Leaf(Value),
+    Object(Vec<(String, UnflattenNode)>),
+    Array(std::collections::BTreeMap<usize, UnflattenNode>),
+}
+
+impl UnflattenNode {
+    fn kind(&self) -> &'static str {
+        match self {
+            UnflattenNode::Empty => "empty slot",
+            UnflattenNode::Leaf(_) => "value",
+            UnflattenNode::Object(_) => "object",
+            UnflattenNode::Array(_) => "array",
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            UnflattenNode::Empty => Value::Null,
+            UnflattenNode::Leaf(v) => v,
+            UnflattenNode::Object(children) => Value::Object(
+                children
+                    .into_iter()
+                    .map(|(k, child)| (k, child.into_value()))
+                    .collect(),
+            ),
+            UnflattenNode::Array(items) => {
+                let len = items.keys().next_back().map(|i| i + 1).unwrap_or(0);
+                let mut arr = vec![Value::Null; len];
+                for (i, child) in items {
+                    arr[i] = child.into_value();
+                }
+                Value::Array(arr)
+            }
+        }
+    }
+}
+
+/// Insert `leaf` at `segments` below `node`, creating containers as needed.
+fn unflatten_insert(
+    node: &mut UnflattenNode,
+    segments: &[PathSegment],
+    leaf: Value,
+    key: &str,
+) -> Result<(), ToolError> {
+    let conflict = |existing: &UnflattenNode| {
+        ToolError::InvocationFailed(format!(
+            "Conflicting keys: '{}' expects a different shape than the existing {} at that path",
+            key,
+            existing.kind()
+        ))
+    };
+
+    let Some((segment, rest)) = segments.split_first() else {
+        if !matches!(node, UnflattenNode::Empty) {
+            return Err(conflict(node));
+        }
+        *node = UnflattenNode::Leaf(leaf);
+        return Ok(());
+    };
+
+    match segment {
+        PathSegment::Key(name) => {
+            if matches!(node, UnflattenNode::Empty) {
+                *node = UnflattenNode::Object(Vec::new());
+            }
+            let UnflattenNode::Object(children) = node else {
+                return Err(conflict(node));
+            };
+            let pos = match children.iter().position(|(k, _)| k == name) {
+                Some(pos) => pos,
+                None => {
+                    children.push((name.clone(), UnflattenNode::Empty));
+                    children.len() - 1
+                }
+            };
+            unflatten_insert(&mut children[pos].1, rest, leaf, key)
+        }
+        PathSegment::Index(idx) => {
+            let idx = usize::try_from(*idx).map_err(|_| {
+                ToolError::InvocationFailed(format!(
+                    "Negative index {} is not allowed in unflatten key: {}",
+                    idx, key
+                ))
+            })?;
+            if matches!(node, UnflattenNode::Empty) {
+                *node = UnflattenNode::Array(Default::default());
+            }
+            let UnflattenNode::Array(items) = node else {
+                return Err(conflict(node));
+            };
+            let child = items.entry(idx).or_insert(UnflattenNode::Empty);
+            unflatten_insert(child, rest, leaf, key)
+        }
+    }
+}
+
 // =============================================================================
 // Diff Helpers
 // =============================================================================
@@ -636,6 +1197,110 @@ mod tests {
         assert_eq!(result["a"]["b"]["c"].as_i64().unwrap(), 123);
     }
 
+    #[test]
+    fn test_set_array_index_in_empty_doc() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "set",
+                "json": {},
+                "path": "items[2]",
+                "value": "c"
+            }))
+            .unwrap();
+
+        assert_eq!(result, json!({"items": [null, null, "c"]}));
+    }
+
+    #[test]
+    fn test_set_index_then_key_creates_objects() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "set",
+                "json": {"users": [{"name": "Alice", "age": 30}]},
+                "path": "$.users[0].name",
+                "value": "Bob"
+            }))
+            .unwrap();
+        assert_eq!(result, json!({"users": [{"name": "Bob", "age": 30}]}));
+
+        let result = provider
+            .call(json!({
+                "operation": "set",
+                "json": {},
+                "path": "users[1].name",
+                "value": "Carol"
+            }))
+            .unwrap();
+        assert_eq!(result, json!({"users": [null, {"name": "Carol"}]}));
+    }
+
+    #[test]
+    fn test_set_negative_index() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "set",
+                "json": {"items": [1, 2, 3]},
+                "path": "items[-1]",
+                "value": 30
+            }))
+            .unwrap();
+        assert_eq!(result, json!({"items": [1, 2, 30]}));
+
+        let err = provider
+            .call(json!({
+                "operation": "set",
+                "json": {"items": [1]},
+                "path": "items[-2]",
+                "value": 0
+            }))
+            .unwrap_err();
+        assert!(err.to_string().contains("out of bounds"));
+    }
+
+    #[test]
+    fn test_delete_array_element() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "delete",
+                "json": {"users": [{"name": "Alice"}, {"name": "Bob"}]},
+                "path": "users[0]"
+            }))
+            .unwrap();
+
+        assert_eq!(result, json!({"users": [{"name": "Bob"}]}));
+    }
+
+    #[test]
+    fn test_delete_nested_key() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "delete",
+                "json": {"users": [{"name": "Alice", "age": 30}]},
+                "path": "users[-1].age"
+            }))
+            .unwrap();
+
+        assert_eq!(result, json!({"users": [{"name": "Alice"}]}));
+    }
+
+    #[test]
+    fn test_delete_missing_path_errors() {
+        let provider = JsonProvider::new();
+        for path in ["missing", "a.missing", "a.list[5]", "a.list[-3]", "a.b.c"] {
+            let result = provider.call(json!({
+                "operation": "delete",
+                "json": {"a": {"b": 1, "list": [1, 2]}},
+                "path": path
+            }));
+            assert!(result.is_err(), "expected error deleting {}", path);
+        }
+    }
+
     #[test]
     fn test_merge_objects() {
         let provider = JsonProvider::new();
@@ -923,6 +1588,174 @@ mod tests {
         assert_eq!(result.get("").unwrap().as_i64().unwrap(), 42);
     }
 
+    #[test]
+    fn test_flatten_keeps_nested_empty_containers() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "flatten",
+                "value": {"a": {}, "b": [], "c": {"d": 1}}
+            }))
+            .unwrap();
+
+        assert_eq!(result, json!({"a": {}, "b": [], "c.d": 1}));
+    }
+
+    #[test]
+    fn test_flatten_custom_delimiter() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "flatten",
+                "value": {"a": {"b": 1, "c": [2, 3]}},
+                "delimiter": "__"
+            }))
+            .unwrap();
+
+        assert_eq!(result, json!({"a__b": 1, "a__c[0]": 2, "a__c[1]": 3}));
+    }
+
+    #[test]
+    fn test_flatten_dot_array_style() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "flatten",
+                "value": {"a": [{"b": 1}, {"b": 2}], "top": [true]},
+                "array_style": "dot"
+            }))
+            .unwrap();
+
+        assert_eq!(result, json!({"a.0.b": 1, "a.1.b": 2, "top.0": true}));
+    }
+
+    #[test]
+    fn test_flatten_dot_array_style_with_delimiter() {
+        let provider = JsonProvider::new();
+        let result = provider
+            .call(json!({
+                "operation": "flatten",
+                "value": [{"x": [1]}],
+                "delimiter": "__",
+                "array_style": "dot"
+            }))
+            .unwrap();
+
+        assert_eq!(result, json!({"0__x__0": 1}));
+    }
+
+    #[test]
+    fn test_flatten_explicit_defaults_match_implicit() {
+        let provider = JsonProvider::new();
+        let value = json!({"user": {"scores": [100, {"k": null}]}, "e": []});
+        let implicit = provider
+            .call(json!({"operation": "flatten", "value": value}))
+            .unwrap();
+        let explicit = provider
+            .call(json!({
+                "operation": "flatten",
+                "value": value,
+                "delimiter": ".",
+                "array_style": "bracket"
+            }))
+            .unwrap();
+
+        assert_eq!(implicit, explicit);
+        assert_eq!(
+            implicit,
+            json!({"user.scores[0]": 100, "user.scores[1].k": null, "e": []})
+        );
+    }
+
+    #[test]
+    fn test_flatten_rejects_invalid_config() {
+        let provider = JsonProvider::new();
+        let bad_style = provider.call(json!({
+            "operation": "flatten",
+            "value": {"a": [1]},
+            "array_style": "paren"
+        }));
+        assert!(bad_style.is_err());
+
+        let empty_delimiter = provider.call(json!({
+            "operation": "flatten",
+            "value": {"a": 1},
+            "delimiter": ""
+        }));
+        assert!(empty_delimiter.is_err());
+    }
+
+    // =========================================================================
+    // Unflatten tests
+    // =========================================================================
+
+    fn unflatten(value: Value) -> Result<Value, ToolError> {
+        JsonProvider::new().call(json!({"operation": "unflatten", "value": value}))
+    }
+
+    #[test]
+    fn test_unflatten_objects_and_arrays() {
+        let result = unflatten(json!({"a.b": 1, "a.c[0]": 2, "a.c[1].d": "x"})).unwrap();
+        assert_eq!(result, json!({"a": {"b": 1, "c": [2, {"d": "x"}]}}));
+    }
+
+    #[test]
+    fn test_unflatten_sparse_indices_fill_null() {
+        let result = unflatten(json!({"items[3]": "d", "items[1]": "b"})).unwrap();
+        assert_eq!(result, json!({"items": [null, "b", null, "d"]}));
+    }
+
+    #[test]
+    fn test_unflatten_root_array_and_scalar() {
+        assert_eq!(
+            unflatten(json!({"[0]": 1, "[1].a": 2})).unwrap(),
+            json!([1, {"a": 2}])
+        );
+        assert_eq!(unflatten(json!({"": 42})).unwrap(), json!(42));
+        assert_eq!(unflatten(json!({})).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn test_unflatten_conflicting_keys() {
+        let err = unflatten(json!({"a": 1, "a.b": 2})).unwrap_err();
+        assert!(err.to_string().contains("Conflicting keys"));
+
+        let err = unflatten(json!({"a[0]": 1, "a.b": 2})).unwrap_err();
+        assert!(err.to_string().contains("Conflicting keys"));
+
+        let err = unflatten(json!({"": 1, "a": 2})).unwrap_err();
+        assert!(err.to_string().contains("Conflicting keys"));
+    }
+
+    #[test]
+    fn test_unflatten_rejects_negative_index() {
+        let err = unflatten(json!({"a[-1]": 1})).unwrap_err();
+        assert!(err.to_string().contains("Negative index"));
+    }
+
+    #[test]
+    fn test_flatten_unflatten_round_trip() {
+        let provider = JsonProvider::new();
+        let fixtures = vec![
+            json!({"a": 1, "b": "two", "c": null, "d": true}),
+            json!({"a": {"b": {"c": {"d": 1.5}}}}),
+            json!({"users": [{"name": "Alice", "tags": ["x", "y"]}, {"name": "Bob", "tags": []}]}),
+            json!({"matrix": [[1, 2], [3, [4, 5]]], "empty": {}}),
+            json!([{"a": 1}, [2, 3], "s", null]),
+            json!({"nulls": [null, null, {"k": null}]}),
+            json!("scalar"),
+            json!({}),
+        ];
+
+        for fixture in fixtures {
+            let flat = provider
+                .flatten(&fixture, &FlattenConfig::default())
+                .unwrap();
+            let restored = provider.unflatten(&flat).unwrap();
+            assert_eq!(restored, fixture, "round trip failed via {}", flat);
+        }
+    }
+
     // =========================================================================
     // Diff tests (T132)
     // =========================================================================
@@ -1214,6 +2047,182 @@ mod tests {
         assert!(parse_path_segments("$.").is_err());
     }
 
+    #[test]
+    fn test_parse_json_pointer() {
+        assert!(parse_json_pointer("").unwrap().is_empty());
+        assert_eq!(parse_json_pointer("/a/b/0").unwrap(), vec!["a", "b", "0"]);
+        assert_eq!(
+            parse_json_pointer("/a~1b/c~0d").unwrap(),
+            vec!["a/b", "c~d"]
+        );
+        assert_eq!(parse_json_pointer("/").unwrap(), vec![""]);
+        assert!(parse_json_pointer("a/b").is_err());
+    }
+
+    // =========================================================================
+    // JSON Patch tests (RFC 6902)
+    // =========================================================================
+
+    fn apply_patch(doc: Value, patch: Value) -> Result<Value, ToolError> {
+        JsonProvider::new().call(json!({
+            "operation": "patch",
+            "json": doc,
+            "patch": patch
+        }))
+    }
+
+    #[test]
+    fn test_patch_add() {
+        let result = apply_patch(
+            json!({"a": {"b": 1}, "list": [1, 3]}),
+            json!([
+                {"op": "add", "path": "/a/c", "value": 2},
+                {"op": "add", "path": "/list/1", "value": 2},
+                {"op": "add", "path": "/list/-", "value": 4}
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(result, json!({"a": {"b": 1, "c": 2}, "list": [1, 2, 3, 4]}));
+    }
+
+    #[test]
+    fn test_patch_remove() {
+        let result = apply_patch(
+            json!({"a": 1, "b": 2, "list": [1, 2, 3]}),
+            json!([
+                {"op": "remove", "path": "/a"},
+                {"op": "remove", "path": "/list/0"}
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(result, json!({"b": 2, "list": [2, 3]}));
+    }
+
+    #[test]
+    fn test_patch_remove_out_of_range() {
+        let err = apply_patch(
+            json!({"list": [1, 2]}),
+            json!([{"op": "remove", "path": "/list/5"}]),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, ToolError::InvocationFailed(_)));
+        assert!(err.to_string().contains("/list/5"));
+    }
+
+    #[test]
+    fn test_patch_replace() {
+        let result = apply_patch(
+            json!({"a": {"b": "old"}, "list": [1, 2]}),
+            json!([
+                {"op": "replace", "path": "/a/b", "value": "new"},
+                {"op": "replace", "path": "/list/1", "value": 20}
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(result, json!({"a": {"b": "new"}, "list": [1, 20]}));
+
+        let err = apply_patch(
+            json!({"a": 1}),
+            json!([{"op": "replace", "path": "/missing", "value": 2}]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("/missing"));
+    }
+
+    #[test]
+    fn test_patch_move() {
+        let result = apply_patch(
+            json!({"a": {"b": 1}, "c": {}}),
+            json!([{"op": "move", "from": "/a/b", "path": "/c/d"}]),
+        )
+        .unwrap();
+
+        assert_eq!(result, json!({"a": {}, "c": {"d": 1}}));
+
+        let err = apply_patch(
+            json!({"a": {"b": 1}}),
+            json!([{"op": "move", "from": "/a", "path": "/a/b/c"}]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("own child"));
+    }
+
+    #[test]
+    fn test_patch_copy() {
+        let result = apply_patch(
+            json!({"a": {"b": [1, 2]}}),
+            json!([{"op": "copy", "from": "/a/b", "path": "/c"}]),
+        )
+        .unwrap();
+
+        assert_eq!(result, json!({"a": {"b": [1, 2]}, "c": [1, 2]}));
+    }
+
+    #[test]
+    fn test_patch_test_op() {
+        let doc = json!({"a": {"b": "x"}});
+        let result = apply_patch(
+            doc.clone(),
+            json!([{"op": "test", "path": "/a/b", "value": "x"}]),
+        )
+        .unwrap();
+        assert_eq!(result, doc);
+
+        let err =
+            apply_patch(doc, json!([{"op": "test", "path": "/a/b", "value": "y"}])).unwrap_err();
+        assert!(matches!(err, ToolError::InvocationFailed(_)));
+        assert!(err.to_string().contains("/a/b"));
+    }
+
+    #[test]
+    fn test_patch_is_atomic() {
+        let provider = JsonProvider::new();
+        let original = json!({"a": 1, "list": [1, 2]});
+        let ops = json!([
+            {"op": "add", "path": "/b", "value": 2},
+            {"op": "remove", "path": "/list/0"},
+            {"op": "test", "path": "/a", "value": 99},
+            {"op": "add", "path": "/c", "value": 3}
+        ]);
+
+        let err = provider
+            .patch(&original, ops.as_array().unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("Patch operation 2 failed"));
+        assert_eq!(original, json!({"a": 1, "list": [1, 2]}));
+    }
+
+    #[test]
+    fn test_patch_escaped_pointer_and_root() {
+        let result = apply_patch(
+            json!({"a/b": 1, "m~n": 2}),
+            json!([
+                {"op": "replace", "path": "/a~1b", "value": 10},
+                {"op": "remove", "path": "/m~0n"}
+            ]),
+        )
+        .unwrap();
+        assert_eq!(result, json!({"a/b": 10}));
+
+        let result = apply_patch(
+            json!({"a": 1}),
+            json!([{"op": "add", "path": "", "value": [1]}]),
+        )
+        .unwrap();
+        assert_eq!(result, json!([1]));
+    }
+
+    #[test]
+    fn test_patch_missing_patch_array() {
+        let provider = JsonProvider::new();
+        let result = provider.call(json!({"operation": "patch", "json": {}}));
+        assert!(result.unwrap_err().to_string().contains("Missing 'patch'"));
+    }
+
     #[test]
     fn test_unknown_operation() {
         let provider = JsonProvider::new();
@@ -2,15 +2,20 @@
 //!
 //! This provider allows Lumen programs to call any MCP-compatible tool server.
 //! It implements the ToolProvider trait by discovering tools from an MCP server
-//! and forwarding tool calls via JSON-RPC.
+//! and forwarding tool calls via JSON-RPC, over either a subprocess's stdio
+//! ([`StdioTransport`]) or the streamable-HTTP transport ([`HttpTransport`]).
+//! Resources and prompts a server exposes are discovered the same way and
+//! wrapped as [`McpResourceProvider`] and [`McpPromptProvider`].
 
 use lumen_runtime::tools::Capability;
 use lumen_runtime::tools::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::Mutex;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
 // ---------------------------------------------------------------------------
 // MCP Transport abstraction
@@ -25,6 +30,67 @@ pub trait McpTransport: Send + Sync {
         method: &str,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, String>;
+
+    /// Capabilities the server declared in its `initialize` response, once
+    /// the handshake has completed. Transports without a handshake return
+    /// `None`.
+    fn server_capabilities(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Initialize handshake
+// ---------------------------------------------------------------------------
+
+/// MCP protocol revision announced in `initialize`.
+pub const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Lazily-run `initialize` handshake shared by the real transports.
+///
+/// MCP servers reject calls made before the client has sent `initialize`
+/// and the `notifications/initialized` notification, so transports run
+/// [`Handshake::ensure`] before every request. The first call performs the
+/// handshake and stores the server's capabilities; later calls return
+/// immediately.
+#[derive(Default)]
+struct Handshake {
+    capabilities: Mutex<Option<serde_json::Value>>,
+}
+
+impl Handshake {
+    fn ensure(
+        &self,
+        request: impl FnOnce(&str, serde_json::Value) -> Result<serde_json::Value, String>,
+        notify: impl FnOnce(&str, serde_json::Value) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut guard = self.capabilities.lock().map_err(|e| e.to_string())?;
+        if guard.is_some() {
+            return Ok(());
+        }
+        let params = serde_json::json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {
+                "name": "lumen",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        });
+        let result =
+            request("initialize", params).map_err(|e| format!("MCP initialize failed: {}", e))?;
+        notify("notifications/initialized", serde_json::json!({}))?;
+        *guard = Some(
+            result
+                .get("capabilities")
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({})),
+        );
+        Ok(())
+    }
+
+    fn capabilities(&self) -> Option<serde_json::Value> {
+        self.capabilities.lock().ok()?.clone()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -40,34 +106,78 @@ pub struct McpToolSchema {
     pub input_schema: serde_json::Value,
 }
 
+/// A resource as returned by the resources/list endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpResourceSchema {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "mimeType")]
+    pub mime_type: Option<String>,
+}
+
+/// A prompt template as returned by the prompts/list endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptSchema {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+/// A named argument accepted by an MCP prompt template.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
 // ---------------------------------------------------------------------------
 // Stdio Transport
 // ---------------------------------------------------------------------------
 
 /// Stdio-based MCP transport that spawns a subprocess.
 ///
-/// Manages the child process lifecycle, sends JSON-RPC 2.0 requests via stdin,
-/// and reads responses from stdout. The process is spawned lazily on first request.
+/// Manages the child process lifecycle and sends JSON-RPC 2.0 requests via
+/// stdin. A reader thread dispatches each line from stdout to the request
+/// with the matching `id`, so responses may arrive out of order, servers may
+/// interleave notifications and log lines, and several threads may have
+/// requests in flight at once. The process is spawned lazily on first request
+/// and shut down when the transport is dropped.
 pub struct StdioTransport {
     command: String,
     args: Vec<String>,
     child: Mutex<Option<ChildProcess>>,
+    handshake: Handshake,
 }
 
 struct ChildProcess {
-    #[allow(dead_code)]
     child: Child,
     stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
+    pending: Arc<Mutex<PendingResponses>>,
     next_id: u64,
 }
 
+/// Requests awaiting a response from the stdio server, keyed by id.
+#[derive(Default)]
+struct PendingResponses {
+    waiters: HashMap<u64, mpsc::Sender<serde_json::Value>>,
+    /// Set once the server's stdout has closed; no response can arrive.
+    closed: bool,
+}
+
 impl StdioTransport {
     pub fn new(command: &str, args: &[&str]) -> Self {
         Self {
             command: command.to_string(),
             args: args.iter().map(|s| s.to_string()).collect(),
             child: Mutex::new(None),
+            handshake: Handshake::default(),
         }
     }
 
@@ -96,15 +206,195 @@ impl StdioTransport {
             let stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
             let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
 
+            let pending = Arc::new(Mutex::new(PendingResponses::default()));
+            let dispatch_pending = Arc::clone(&pending);
+            std::thread::Builder::new()
+                .name("lumen-mcp-stdio".to_string())
+                .spawn(move || dispatch_responses(BufReader::new(stdout), &dispatch_pending))
+                .map_err(|e| format!("Failed to start MCP reader thread: {}", e))?;
+
             *guard = Some(ChildProcess {
                 child,
                 stdin,
-                stdout: BufReader::new(stdout),
+                pending,
                 next_id: 1,
             });
         }
         Ok(())
     }
+
+    /// Write one newline-delimited JSON-RPC message to the server.
+    fn write_message(child: &mut ChildProcess, message: &serde_json::Value) -> Result<(), String> {
+        let message_str = serde_json::to_string(message).map_err(|e| e.to_string())?;
+        writeln!(child.stdin, "{}", message_str)
+            .map_err(|e| format!("Failed to write to MCP server: {}", e))?;
+        child
+            .stdin
+            .flush()
+            .map_err(|e| format!("Failed to flush: {}", e))
+    }
+
+    /// Send a notification, which has no id and gets no response.
+    fn notify(&self, method: &str, params: serde_json::Value) -> Result<(), String> {
+        self.ensure_started()?;
+        let mut guard = self.child.lock().map_err(|e| e.to_string())?;
+        let child = guard.as_mut().ok_or("MCP server not started")?;
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        Self::write_message(child, &notification)
+    }
+
+    /// Send a request without running the handshake first.
+    ///
+    /// The child lock is held only while registering and writing the
+    /// request; waiting for the response happens on a per-id channel.
+    fn request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        self.ensure_started()?;
+        let (id, receiver) = {
+            let mut guard = self.child.lock().map_err(|e| e.to_string())?;
+            let child = guard.as_mut().ok_or("MCP server not started")?;
+
+            let id = child.next_id;
+            child.next_id += 1;
+
+            let (sender, receiver) = mpsc::channel();
+            {
+                let mut pending = child.pending.lock().map_err(|e| e.to_string())?;
+                if pending.closed {
+                    return Err("MCP server closed its output".to_string());
+                }
+                pending.waiters.insert(id, sender);
+            }
+
+            // Build JSON-RPC 2.0 request
+            let request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            });
+
+            // Send request (newline-delimited)
+            if let Err(e) = Self::write_message(child, &request) {
+                if let Ok(mut pending) = child.pending.lock() {
+                    pending.waiters.remove(&id);
+                }
+                return Err(e);
+            }
+            (id, receiver)
+        };
+
+        let response = receiver.recv().map_err(|_| {
+            format!(
+                "MCP server closed its output before responding to request {}",
+                id
+            )
+        })?;
+        jsonrpc_result(response)
+    }
+}
+
+/// How long `Drop` waits for the server to answer `shutdown`, and then to
+/// exit after stdin closes, before killing it.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
+impl ChildProcess {
+    /// Stop the server: send `shutdown` and `exit` if the session was
+    /// initialized, close stdin, and reap the process, killing it if it does
+    /// not exit within [`SHUTDOWN_TIMEOUT`]. Every step is best-effort.
+    fn shutdown(self, initialized: bool) {
+        let ChildProcess {
+            mut child,
+            mut stdin,
+            pending,
+            next_id,
+        } = self;
+
+        // Already gone (crashed or exited on its own): just reap it.
+        if !matches!(child.try_wait(), Ok(None)) {
+            return;
+        }
+
+        if initialized {
+            let (sender, receiver) = mpsc::channel();
+            let registered = pending
+                .lock()
+                .map(|mut pending| {
+                    if !pending.closed {
+                        pending.waiters.insert(next_id, sender);
+                    }
+                    !pending.closed
+                })
+                .unwrap_or(false);
+            let shutdown = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": next_id,
+                "method": "shutdown",
+            });
+            if registered && writeln!(stdin, "{}", shutdown).is_ok() && stdin.flush().is_ok() {
+                let _ = receiver.recv_timeout(SHUTDOWN_TIMEOUT);
+                let exit = serde_json::json!({"jsonrpc": "2.0", "method": "exit"});
+                let _ = writeln!(stdin, "{}", exit).and_then(|_| stdin.flush());
+            }
+        }
+        drop(stdin);
+
+        let deadline = std::time::Instant::now() + SHUTDOWN_TIMEOUT;
+        while std::time::Instant::now() < deadline {
+            match child.try_wait() {
+                Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+                _ => return,
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Read newline-delimited JSON-RPC messages from a stdio server and hand each
+/// response to the waiter registered for its id.
+///
+/// Notifications (messages without an `id`), server-initiated requests
+/// (messages with a `method`), responses nobody is waiting for, and lines
+/// that are not JSON (servers sometimes log to stdout) are skipped.
+/// When the stream ends, every outstanding waiter is dropped so blocked
+/// requests fail instead of hanging.
+fn dispatch_responses(mut reader: impl BufRead, pending: &Mutex<PendingResponses>) {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        if message.get("method").is_some() {
+            continue;
+        }
+        let Some(id) = message.get("id").and_then(|id| id.as_u64()) else {
+            continue;
+        };
+        let waiter = match pending.lock() {
+            Ok(mut pending) => pending.waiters.remove(&id),
+            Err(_) => break,
+        };
+        if let Some(waiter) = waiter {
+            let _ = waiter.send(message);
+        }
+    }
+    if let Ok(mut pending) = pending.lock() {
+        pending.closed = true;
+        pending.waiters.clear();
+    }
 }
 
 impl McpTransport for StdioTransport {
@@ -113,59 +403,231 @@ impl McpTransport for StdioTransport {
         method: &str,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, String> {
-        self.ensure_started()?;
-        let mut guard = self.child.lock().map_err(|e| e.to_string())?;
-        let child = guard.as_mut().ok_or("MCP server not started")?;
+        self.handshake
+            .ensure(|m, p| self.request(m, p), |m, p| self.notify(m, p))?;
+        self.request(method, params)
+    }
+
+    fn server_capabilities(&self) -> Option<serde_json::Value> {
+        self.handshake.capabilities()
+    }
+}
+
+/// Extract the `result` of a JSON-RPC 2.0 response, mapping `error` to `Err`.
+fn jsonrpc_result(response: serde_json::Value) -> Result<serde_json::Value, String> {
+    // Check for JSON-RPC error
+    if let Some(error) = response.get("error") {
+        return Err(format!("MCP error: {}", error));
+    }
+
+    // Return result
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| "No result in MCP response".to_string())
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        let initialized = self.handshake.capabilities().is_some();
+        if let Ok(mut guard) = self.child.lock() {
+            if let Some(process) = guard.take() {
+                process.shutdown(initialized);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HTTP Transport
+// ---------------------------------------------------------------------------
+
+/// Timeout for a single MCP request over HTTP.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Header carrying the session id assigned by a streamable-HTTP server.
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// Streamable-HTTP MCP transport.
+///
+/// Each JSON-RPC request is POSTed to the server URL. The server answers
+/// either with a JSON body or with a `text/event-stream`, in which case
+/// server-initiated messages may precede the response; the stream is read
+/// until the message carrying the request's id arrives. A session id
+/// returned by the server is echoed on subsequent requests. Connections are
+/// pooled by a shared `reqwest` client.
+pub struct HttpTransport {
+    url: String,
+    headers: HashMap<String, String>,
+    client: reqwest::blocking::Client,
+    next_id: AtomicU64,
+    session_id: Mutex<Option<String>>,
+    handshake: Handshake,
+}
+
+impl HttpTransport {
+    /// Create a transport for the server at `url`. `headers` (for example
+    /// `Authorization`) are sent with every request.
+    pub fn new(url: &str, headers: HashMap<String, String>) -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .expect("Failed to build HTTP client");
+        Self {
+            url: url.to_string(),
+            headers,
+            client,
+            next_id: AtomicU64::new(1),
+            session_id: Mutex::new(None),
+            handshake: Handshake::default(),
+        }
+    }
 
-        let id = child.next_id;
-        child.next_id += 1;
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    /// POST one JSON-RPC message, tracking the server's session id and
+    /// mapping non-2xx statuses to errors.
+    fn post(&self, message: &serde_json::Value) -> Result<reqwest::blocking::Response, String> {
+        let mut builder = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(message);
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let session_id = self.session_id.lock().map_err(|e| e.to_string())?.clone();
+        if let Some(session_id) = session_id {
+            builder = builder.header(SESSION_HEADER, session_id);
+        }
+
+        let response = builder
+            .send()
+            .map_err(|e| format!("Failed to reach MCP server at {}: {}", self.url, e))?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.lock().map_err(|e| e.to_string())? = Some(session_id.to_string());
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(format!(
+                "MCP server returned HTTP {}: {}",
+                status.as_u16(),
+                body.trim()
+            ));
+        }
+        Ok(response)
+    }
 
-        // Build JSON-RPC 2.0 request
+    /// Send a notification; the server acknowledges it without a body.
+    fn notify(&self, method: &str, params: serde_json::Value) -> Result<(), String> {
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        self.post(&notification).map(|_| ())
+    }
+
+    /// Send a request without running the handshake first.
+    fn request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
         let request = serde_json::json!({
             "jsonrpc": "2.0",
             "id": id,
             "method": method,
             "params": params,
         });
+        let response = self.post(&request)?;
+
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+
+        let message = if is_event_stream {
+            read_event_stream(BufReader::new(response), id)?
+        } else {
+            let body: serde_json::Value = response
+                .json()
+                .map_err(|e| format!("Invalid JSON from MCP server: {}", e))?;
+            find_response(body, id)
+                .ok_or_else(|| format!("No response for request {} from MCP server", id))?
+        };
 
-        // Send request (newline-delimited)
-        let request_str = serde_json::to_string(&request).map_err(|e| e.to_string())?;
-        writeln!(child.stdin, "{}", request_str)
-            .map_err(|e| format!("Failed to write to MCP server: {}", e))?;
-        child
-            .stdin
-            .flush()
-            .map_err(|e| format!("Failed to flush: {}", e))?;
+        jsonrpc_result(message)
+    }
+}
 
-        // Read response
-        let mut response_line = String::new();
-        child
-            .stdout
-            .read_line(&mut response_line)
-            .map_err(|e| format!("Failed to read from MCP server: {}", e))?;
+impl McpTransport for HttpTransport {
+    fn send_request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        self.handshake
+            .ensure(|m, p| self.request(m, p), |m, p| self.notify(m, p))?;
+        self.request(method, params)
+    }
 
-        let response: serde_json::Value = serde_json::from_str(&response_line)
-            .map_err(|e| format!("Invalid JSON from MCP server: {}", e))?;
+    fn server_capabilities(&self) -> Option<serde_json::Value> {
+        self.handshake.capabilities()
+    }
+}
 
-        // Check for JSON-RPC error
-        if let Some(error) = response.get("error") {
-            return Err(format!("MCP error: {}", error));
+/// Read server-sent events until the JSON-RPC response to request `id`.
+/// Server-initiated requests and notifications on the stream are skipped.
+fn read_event_stream(reader: impl BufRead, id: u64) -> Result<serde_json::Value, String> {
+    let mut data = String::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Failed to read from MCP server: {}", e))?;
+        if let Some(chunk) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(chunk.strip_prefix(' ').unwrap_or(chunk));
+        } else if line.is_empty() && !data.is_empty() {
+            let message: serde_json::Value = serde_json::from_str(&data)
+                .map_err(|e| format!("Invalid JSON from MCP server: {}", e))?;
+            data.clear();
+            if let Some(response) = find_response(message, id) {
+                return Ok(response);
+            }
         }
-
-        // Return result
-        response
-            .get("result")
-            .cloned()
-            .ok_or_else(|| "No result in MCP response".to_string())
     }
+    Err(format!(
+        "MCP event stream ended without a response to request {}",
+        id
+    ))
 }
 
-impl Drop for StdioTransport {
-    fn drop(&mut self) {
-        if let Ok(mut guard) = self.child.lock() {
-            // ChildProcess dropped, stdin closed, process terminates
-            *guard = None;
+/// Pick the response to request `id` out of a message or batch of messages.
+fn find_response(message: serde_json::Value, id: u64) -> Option<serde_json::Value> {
+    match message {
+        serde_json::Value::Array(batch) => batch.into_iter().find_map(|m| find_response(m, id)),
+        m if m.get("id").and_then(|v| v.as_u64()) == Some(id)
+            && (m.get("result").is_some() || m.get("error").is_some()) =>
+        {
+            Some(m)
         }
+        _ => None,
     }
 }
 
@@ -325,6 +787,242 @@ pub fn discover_tools(
     Ok(providers)
 }
 
+// ---------------------------------------------------------------------------
+// MCP Resource and Prompt Providers
+// ---------------------------------------------------------------------------
+
+/// A resource exposed by an MCP server.
+///
+/// Calling the provider reads the resource via `resources/read` and returns
+/// its `contents` array. Input is ignored.
+pub struct McpResourceProvider {
+    server_name: String,
+    resource: McpResourceSchema,
+    schema: ToolSchema,
+    transport: std::sync::Arc<dyn McpTransport>,
+}
+
+impl McpResourceProvider {
+    pub fn new(
+        server_name: &str,
+        resource: McpResourceSchema,
+        transport: std::sync::Arc<dyn McpTransport>,
+    ) -> Self {
+        let schema = ToolSchema {
+            name: format!("{}.{}", server_name, resource.name),
+            description: resource.description.clone().unwrap_or_default(),
+            input_schema: serde_json::json!({"type": "object"}),
+            output_schema: serde_json::Value::Null,
+            effects: vec!["mcp".to_string(), "mcp.resource".to_string()],
+        };
+        Self {
+            server_name: server_name.to_string(),
+            resource,
+            schema,
+            transport,
+        }
+    }
+
+    /// Get the qualified resource name (server_name.resource_name).
+    pub fn qualified_name(&self) -> String {
+        self.schema.name.clone()
+    }
+
+    /// The resource's URI on its server.
+    pub fn uri(&self) -> &str {
+        &self.resource.uri
+    }
+
+    /// The resource's MIME type, if the server declared one.
+    pub fn mime_type(&self) -> Option<&str> {
+        self.resource.mime_type.as_deref()
+    }
+}
+
+impl ToolProvider for McpResourceProvider {
+    fn name(&self) -> &str {
+        &self.server_name
+    }
+
+    fn version(&self) -> &str {
+        "0.1.0"
+    }
+
+    fn schema(&self) -> &ToolSchema {
+        &self.schema
+    }
+
+    fn call(&self, _input: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        read_resource(self.transport.as_ref(), &self.resource.uri)
+            .map_err(ToolError::InvocationFailed)
+    }
+}
+
+/// A prompt template exposed by an MCP server.
+///
+/// Calling the provider renders the prompt via `prompts/get`, passing the
+/// input object as the prompt's arguments, and returns the server's result
+/// (a `messages` array and optional `description`).
+pub struct McpPromptProvider {
+    server_name: String,
+    prompt: McpPromptSchema,
+    schema: ToolSchema,
+    transport: std::sync::Arc<dyn McpTransport>,
+}
+
+impl McpPromptProvider {
+    pub fn new(
+        server_name: &str,
+        prompt: McpPromptSchema,
+        transport: std::sync::Arc<dyn McpTransport>,
+    ) -> Self {
+        let properties: serde_json::Map<String, serde_json::Value> = prompt
+            .arguments
+            .iter()
+            .map(|arg| {
+                let mut property = serde_json::json!({"type": "string"});
+                if let Some(description) = &arg.description {
+                    property["description"] = serde_json::json!(description);
+                }
+                (arg.name.clone(), property)
+            })
+            .collect();
+        let required: Vec<&str> = prompt
+            .arguments
+            .iter()
+            .filter(|arg| arg.required)
+            .map(|arg| arg.name.as_str())
+            .collect();
+        let schema = ToolSchema {
+            name: format!("{}.{}", server_name, prompt.name),
+            description: prompt.description.clone().unwrap_or_default(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            }),
+            output_schema: serde_json::Value::Null,
+            effects: vec!["mcp".to_string(), "mcp.prompt".to_string()],
+        };
+        Self {
+            server_name: server_name.to_string(),
+            prompt,
+            schema,
+            transport,
+        }
+    }
+
+    /// Get the qualified prompt name (server_name.prompt_name).
+    pub fn qualified_name(&self) -> String {
+        self.schema.name.clone()
+    }
+
+    /// Arguments the prompt template accepts.
+    pub fn arguments(&self) -> &[McpPromptArgument] {
+        &self.prompt.arguments
+    }
+}
+
+impl ToolProvider for McpPromptProvider {
+    fn name(&self) -> &str {
+        &self.server_name
+    }
+
+    fn version(&self) -> &str {
+        "0.1.0"
+    }
+
+    fn schema(&self) -> &ToolSchema {
+        &self.schema
+    }
+
+    fn call(&self, input: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let arguments = match input {
+            serde_json::Value::Null => serde_json::json!({}),
+            other => other,
+        };
+        let params = serde_json::json!({
+            "name": self.prompt.name,
+            "arguments": arguments,
+        });
+        self.transport
+            .send_request("prompts/get", params)
+            .map_err(ToolError::InvocationFailed)
+    }
+}
+
+/// Discover all resources from an MCP server and create providers for each.
+pub fn discover_resources(
+    server_name: &str,
+    transport: std::sync::Arc<dyn McpTransport>,
+) -> Result<Vec<McpResourceProvider>, String> {
+    let response = transport.send_request("resources/list", serde_json::json!({}))?;
+
+    let resources = response
+        .get("resources")
+        .and_then(|r| r.as_array())
+        .ok_or_else(|| "resources/list response missing 'resources' array".to_string())?;
+
+    let mut providers = Vec::new();
+    for resource_value in resources {
+        match serde_json::from_value::<McpResourceSchema>(resource_value.clone()) {
+            Ok(resource) => {
+                providers.push(McpResourceProvider::new(
+                    server_name,
+                    resource,
+                    transport.clone(),
+                ));
+            }
+            Err(e) => {
+                eprintln!("Warning: failed to parse resource: {}", e);
+            }
+        }
+    }
+
+    Ok(providers)
+}
+
+/// Discover all prompts from an MCP server and create providers for each.
+pub fn discover_prompts(
+    server_name: &str,
+    transport: std::sync::Arc<dyn McpTransport>,
+) -> Result<Vec<McpPromptProvider>, String> {
+    let response = transport.send_request("prompts/list", serde_json::json!({}))?;
+
+    let prompts = response
+        .get("prompts")
+        .and_then(|p| p.as_array())
+        .ok_or_else(|| "prompts/list response missing 'prompts' array".to_string())?;
+
+    let mut providers = Vec::new();
+    for prompt_value in prompts {
+        match serde_json::from_value::<McpPromptSchema>(prompt_value.clone()) {
+            Ok(prompt) => {
+                providers.push(McpPromptProvider::new(
+                    server_name,
+                    prompt,
+                    transport.clone(),
+                ));
+            }
+            Err(e) => {
+                eprintln!("Warning: failed to parse prompt: {}", e);
+            }
+        }
+    }
+
+    Ok(providers)
+}
+
+/// Read a resource by URI, returning the `contents` array from
+/// `resources/read`.
+pub fn read_resource(transport: &dyn McpTransport, uri: &str) -> Result<serde_json::Value, String> {
+    let response = transport.send_request("resources/read", serde_json::json!({ "uri": uri }))?;
+    response
+        .get("contents")
+        .cloned()
+        .ok_or_else(|| "resources/read response missing 'contents' array".to_string())
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -332,6 +1030,7 @@ pub fn discover_tools(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lumen_runtime::mock_http::{CapturedRequest, MockHttpServer, Reply};
     use serde_json::json;
 
     fn mock_transport_with_tools(tools: Vec<serde_json::Value>) -> MockTransport {
@@ -471,6 +1170,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn discover_resources_from_mock_transport() {
+        let mut transport = MockTransport::new();
+        transport.set_response(
+            "resources/list",
+            json!({
+                "resources": [
+                    {
+                        "uri": "file:///docs/readme.md",
+                        "name": "readme",
+                        "description": "Project readme",
+                        "mimeType": "text/markdown"
+                    },
+                    {"uri": "db://users", "name": "users"},
+                    {"name": "missing-uri"}
+                ]
+            }),
+        );
+
+        let providers = discover_resources("docs", std::sync::Arc::new(transport)).unwrap();
+
+        assert_eq!(providers.len(), 2);
+        assert_eq!(providers[0].qualified_name(), "docs.readme");
+        assert_eq!(providers[0].uri(), "file:///docs/readme.md");
+        assert_eq!(providers[0].mime_type(), Some("text/markdown"));
+        assert_eq!(providers[0].schema().description, "Project readme");
+        assert_eq!(providers[1].uri(), "db://users");
+        assert_eq!(providers[1].mime_type(), None);
+        assert_eq!(providers[1].effects(), vec!["mcp", "mcp.resource"]);
+    }
+
+    #[test]
+    fn discover_prompts_from_mock_transport() {
+        let mut transport = MockTransport::new();
+        transport.set_response(
+            "prompts/list",
+            json!({
+                "prompts": [{
+                    "name": "summarize",
+                    "description": "Summarize a document",
+                    "arguments": [
+                        {"name": "text", "description": "Text to summarize", "required": true},
+                        {"name": "style"}
+                    ]
+                }]
+            }),
+        );
+        transport.set_response(
+            "prompts/get",
+            json!({"messages": [{"role": "user", "content": {"type": "text", "text": "hi"}}]}),
+        );
+
+        let providers = discover_prompts("writer", std::sync::Arc::new(transport)).unwrap();
+
+        assert_eq!(providers.len(), 1);
+        let prompt = &providers[0];
+        assert_eq!(prompt.qualified_name(), "writer.summarize");
+        assert_eq!(prompt.arguments().len(), 2);
+        let schema = prompt.schema();
+        assert_eq!(schema.input_schema["required"], json!(["text"]));
+        assert_eq!(
+            schema.input_schema["properties"]["text"]["description"],
+            "Text to summarize"
+        );
+        assert_eq!(schema.effects, vec!["mcp", "mcp.prompt"]);
+        let rendered = prompt.call(json!({"text": "long document"})).unwrap();
+        assert_eq!(rendered["messages"][0]["role"], "user");
+    }
+
+    #[test]
+    fn discover_resources_rejects_malformed_listing() {
+        let mut transport = MockTransport::new();
+        transport.set_response("resources/list", json!({}));
+        let err = discover_resources("srv", std::sync::Arc::new(transport))
+            .err()
+            .unwrap();
+        assert!(err.contains("missing 'resources' array"));
+    }
+
+    /// Answers `resources/read` with contents echoing the requested URI.
+    struct ResourceTransport;
+
+    impl McpTransport for ResourceTransport {
+        fn send_request(
+            &self,
+            method: &str,
+            params: serde_json::Value,
+        ) -> Result<serde_json::Value, String> {
+            assert_eq!(method, "resources/read");
+            let uri = params["uri"].as_str().unwrap();
+            Ok(json!({
+                "contents": [{"uri": uri, "mimeType": "text/plain", "text": format!("body of {}", uri)}]
+            }))
+        }
+    }
+
+    #[test]
+    fn read_resource_by_uri() {
+        let contents = read_resource(&ResourceTransport, "file:///notes.txt").unwrap();
+        assert_eq!(
+            contents,
+            json!([{"uri": "file:///notes.txt", "mimeType": "text/plain", "text": "body of file:///notes.txt"}])
+        );
+
+        let resource = McpResourceSchema {
+            uri: "db://users".to_string(),
+            name: "users".to_string(),
+            description: None,
+            mime_type: None,
+        };
+        let provider =
+            McpResourceProvider::new("srv", resource, std::sync::Arc::new(ResourceTransport));
+        assert_eq!(
+            provider.call(json!({})).unwrap()[0]["text"],
+            "body of db://users"
+        );
+    }
+
     #[test]
     fn stdio_transport_stores_configuration() {
         let transport = StdioTransport::new("node", &["server.js", "--port", "3000"]);
@@ -527,4 +1344,355 @@ mod tests {
         assert!(response.get("error").is_some());
         assert_eq!(response["error"]["code"], -32601);
     }
+
+    /// A reply from the mock MCP endpoint, which always assigns `session-1`.
+    fn mcp_reply(status: &str, content_type: &str, body: impl Into<Vec<u8>>) -> Reply {
+        Reply::new(
+            status,
+            &[
+                ("Content-Type", content_type),
+                ("Mcp-Session-Id", "session-1"),
+            ],
+            body,
+        )
+    }
+
+    /// Replies completing the `initialize` handshake (request id 1).
+    fn handshake_replies() -> Vec<Reply> {
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {"tools": {"listChanged": true}},
+                "serverInfo": {"name": "mock", "version": "1.0"}
+            }
+        });
+        vec![
+            mcp_reply("200 OK", "application/json", initialize.to_string()),
+            mcp_reply("202 Accepted", "application/json", ""),
+        ]
+    }
+
+    /// JSON-RPC body of a captured HTTP request.
+    fn request_payload(request: &CapturedRequest) -> serde_json::Value {
+        serde_json::from_slice(&request.body).unwrap()
+    }
+
+    #[test]
+    fn http_transport_discovers_tools() {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {
+                "tools": [
+                    {"name": "search", "description": "Search", "input_schema": {"type": "object"}}
+                ]
+            }
+        })
+        .to_string();
+        let mut replies = handshake_replies();
+        replies.push(mcp_reply("200 OK", "application/json", body));
+        let server = MockHttpServer::start(replies);
+        let url = server.url("/mcp");
+        let headers = HashMap::from([("Authorization".to_string(), "Bearer t0k".to_string())]);
+        let transport = std::sync::Arc::new(HttpTransport::new(&url, headers));
+
+        let providers = discover_tools("remote", transport).unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].qualified_name(), "remote.search");
+
+        let requests = server.requests();
+        let request = &requests[2];
+        assert!(request.head.starts_with("POST /mcp "), "{}", request.head);
+        let lower = request.head.to_lowercase();
+        assert!(
+            lower.contains("authorization: bearer t0k"),
+            "{}",
+            request.head
+        );
+        assert!(lower.contains("accept: application/json, text/event-stream"));
+        assert!(
+            lower.contains("mcp-session-id: session-1"),
+            "{}",
+            request.head
+        );
+        let payload = request_payload(request);
+        assert_eq!(payload["method"], "tools/list");
+        assert_eq!(payload["jsonrpc"], "2.0");
+    }
+
+    #[test]
+    fn http_transport_reads_response_from_event_stream() {
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/progress"});
+        let response = json!({"jsonrpc": "2.0", "id": 2, "result": {"content": "done"}});
+        let body = format!(
+            "event: message\ndata: {}\n\ndata: {}\n\n",
+            notification, response
+        );
+        let mut replies = handshake_replies();
+        replies.push(mcp_reply("200 OK", "text/event-stream", body));
+        let url = MockHttpServer::start(replies).url("/mcp");
+        let transport = HttpTransport::new(&url, HashMap::new());
+
+        let result = transport.send_request("tools/call", json!({})).unwrap();
+        assert_eq!(result, json!({"content": "done"}));
+    }
+
+    #[test]
+    fn http_transport_maps_http_and_rpc_errors() {
+        let url = MockHttpServer::start(vec![mcp_reply(
+            "503 Service Unavailable",
+            "text/plain",
+            "overloaded",
+        )])
+        .url("/mcp");
+        let err = HttpTransport::new(&url, HashMap::new())
+            .send_request("tools/list", json!({}))
+            .unwrap_err();
+        assert!(err.contains("HTTP 503"), "{}", err);
+        assert!(err.contains("overloaded"), "{}", err);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "error": {"code": -32601, "message": "Method not found"}
+        })
+        .to_string();
+        let mut replies = handshake_replies();
+        replies.push(mcp_reply("200 OK", "application/json", body));
+        let url = MockHttpServer::start(replies).url("/mcp");
+        let err = HttpTransport::new(&url, HashMap::new())
+            .send_request("bogus", json!({}))
+            .unwrap_err();
+        assert!(err.contains("MCP error"), "{}", err);
+
+        let err = HttpTransport::new("http://127.0.0.1:1/mcp", HashMap::new())
+            .send_request("tools/list", json!({}))
+            .unwrap_err();
+        assert!(err.contains("Failed to reach MCP server"), "{}", err);
+    }
+
+    #[test]
+    fn http_transport_initializes_once_before_requests() {
+        let mut replies = handshake_replies();
+        for id in 2..=3 {
+            let reply = json!({"jsonrpc": "2.0", "id": id, "result": {}});
+            replies.push(mcp_reply("200 OK", "application/json", reply.to_string()));
+        }
+        let server = MockHttpServer::start(replies);
+        let url = server.url("/mcp");
+        let transport = HttpTransport::new(&url, HashMap::new());
+        assert!(transport.server_capabilities().is_none());
+
+        transport.send_request("tools/call", json!({})).unwrap();
+        transport.send_request("tools/call", json!({})).unwrap();
+
+        let methods: Vec<serde_json::Value> = server
+            .requests()
+            .iter()
+            .map(|r| request_payload(r)["method"].clone())
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                json!("initialize"),
+                json!("notifications/initialized"),
+                json!("tools/call"),
+                json!("tools/call")
+            ]
+        );
+        assert_eq!(
+            transport.server_capabilities(),
+            Some(json!({"tools": {"listChanged": true}}))
+        );
+        assert_eq!(
+            transport.session_id.lock().unwrap().as_deref(),
+            Some("session-1")
+        );
+    }
+
+    /// Spawn `sh` running a fake stdio MCP server that logs each incoming
+    /// message to `log` and answers requests in order.
+    #[cfg(unix)]
+    fn stdio_mock_server(log: &std::path::Path) -> StdioTransport {
+        let script = format!(
+            r#"n=0
+while IFS= read -r line; do
+  printf '%s\n' "$line" >> '{log}'
+  case "$line" in
+    *'"id"'*)
+      n=$((n + 1))
+      case "$line" in
+        *'"method":"initialize"'*)
+          echo '{{"jsonrpc":"2.0","id":'$n',"result":{{"protocolVersion":"{version}","capabilities":{{"tools":{{}}}}}}}}' ;;
+        *)
+          echo '{{"jsonrpc":"2.0","id":'$n',"result":{{"content":"ok"}}}}' ;;
+      esac ;;
+  esac
+done"#,
+            log = log.display(),
+            version = PROTOCOL_VERSION
+        );
+        StdioTransport::new("sh", &["-c", &script])
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn stdio_transport_drop_reaps_unresponsive_server() {
+        // Ignores stdin entirely and would run for a minute if left alone.
+        let transport = StdioTransport::new("sleep", &["60"]);
+        transport.ensure_started().unwrap();
+        let pid = transport
+            .child
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|process| process.child.id())
+            .unwrap();
+        let proc_entry = std::path::PathBuf::from(format!("/proc/{}", pid));
+        assert!(proc_entry.exists());
+
+        drop(transport);
+
+        // Neither running nor left behind as a zombie.
+        assert!(!proc_entry.exists());
+    }
+
+    #[test]
+    fn stdio_dispatch_skips_notifications_and_routes_by_id() {
+        let stream = concat!(
+            "{\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\",\"params\":{}}\n",
+            "server starting up\n",
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"ping\"}\n",
+            "{\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{\"n\":3}}\n",
+            "{\"jsonrpc\":\"2.0\",\"id\":9,\"result\":{\"n\":9}}\n",
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"n\":2}}\n",
+        );
+        let pending = Mutex::new(PendingResponses::default());
+        let (tx2, rx2) = mpsc::channel();
+        let (tx3, rx3) = mpsc::channel();
+        let (tx4, rx4) = mpsc::channel();
+        {
+            let mut pending = pending.lock().unwrap();
+            pending.waiters.insert(2, tx2);
+            pending.waiters.insert(3, tx3);
+            pending.waiters.insert(4, tx4);
+        }
+
+        dispatch_responses(std::io::Cursor::new(stream), &pending);
+
+        assert_eq!(
+            jsonrpc_result(rx2.recv().unwrap()).unwrap(),
+            json!({"n": 2})
+        );
+        assert_eq!(
+            jsonrpc_result(rx3.recv().unwrap()).unwrap(),
+            json!({"n": 3})
+        );
+        // The stream ended without a response for id 4.
+        assert!(rx4.recv().is_err());
+        let pending = pending.lock().unwrap();
+        assert!(pending.closed);
+        assert!(pending.waiters.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stdio_transport_correlates_concurrent_requests() {
+        // Echoes each request's `tag` param back under its id, preceded by a
+        // notification and a stray log line.
+        let script = format!(
+            r#"while IFS= read -r line; do
+  case "$line" in
+    *'"id"'*)
+      id=$(printf '%s' "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+      echo '{{"jsonrpc":"2.0","method":"notifications/message","params":{{}}}}'
+      echo 'not json'
+      case "$line" in
+        *'"method":"initialize"'*)
+          echo '{{"jsonrpc":"2.0","id":'$id',"result":{{"protocolVersion":"{version}","capabilities":{{}}}}}}' ;;
+        *'"method":"shutdown"'*)
+          echo '{{"jsonrpc":"2.0","id":'$id',"result":null}}' ;;
+        *)
+          tag=$(printf '%s' "$line" | sed 's/.*"tag":"\([^"]*\)".*/\1/')
+          echo '{{"jsonrpc":"2.0","id":'$id',"result":{{"tag":"'$tag'"}}}}' ;;
+      esac ;;
+  esac
+done"#,
+            version = PROTOCOL_VERSION
+        );
+        let transport = std::sync::Arc::new(StdioTransport::new("sh", &["-c", &script]));
+
+        let handles: Vec<_> = (0..4)
+            .map(|thread| {
+                let transport = transport.clone();
+                std::thread::spawn(move || {
+                    for call in 0..5 {
+                        let tag = format!("t{}-{}", thread, call);
+                        let result = transport
+                            .send_request("ping", json!({ "tag": tag }))
+                            .unwrap();
+                        assert_eq!(result, json!({ "tag": tag }));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stdio_transport_initializes_once_before_first_call() {
+        let log =
+            std::env::temp_dir().join(format!("lumen-mcp-handshake-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&log);
+        let transport = stdio_mock_server(&log);
+
+        let schema = McpToolSchema {
+            name: "echo".to_string(),
+            description: None,
+            input_schema: json!({}),
+        };
+        let transport: std::sync::Arc<dyn McpTransport> = std::sync::Arc::new(transport);
+        let provider = McpToolProvider::new("srv", schema, transport.clone());
+        assert_eq!(
+            provider.call(json!({"x": 1})).unwrap(),
+            json!({"content": "ok"})
+        );
+        assert_eq!(
+            provider.call(json!({"x": 2})).unwrap(),
+            json!({"content": "ok"})
+        );
+        drop(provider);
+        assert_eq!(transport.server_capabilities(), Some(json!({"tools": {}})));
+        drop(transport);
+
+        let logged = std::fs::read_to_string(&log).unwrap();
+        let _ = std::fs::remove_file(&log);
+        let methods: Vec<String> = logged
+            .lines()
+            .map(|l| {
+                let msg: serde_json::Value = serde_json::from_str(l).unwrap();
+                msg["method"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                "initialize",
+                "notifications/initialized",
+                "tools/call",
+                "tools/call",
+                "shutdown",
+                "exit"
+            ]
+        );
+        let first: serde_json::Value =
+            serde_json::from_str(logged.lines().next().unwrap()).unwrap();
+        assert_eq!(first["params"]["protocolVersion"], PROTOCOL_VERSION);
+        assert_eq!(first["params"]["clientInfo"]["name"], "lumen");
+    }
 }
@@ -13,7 +13,21 @@ def main():
             method = req.get("method", "")
             req_id = req.get("id", 1)
 
-            if method == "tools/list":
+            if "id" not in req:
+                # Notifications (e.g. notifications/initialized) get no reply
+                continue
+
+            if method == "initialize":
+                response = {
+                    "jsonrpc": "2.0",
+                    "id": req_id,
+                    "result": {
+                        "protocolVersion": req.get("params", {}).get("protocolVersion", ""),
+                        "capabilities": {"tools": {}},
+                        "serverInfo": {"name": "lumen-test-server", "version": "0.1.0"}
+                    }
+                }
+            elif method == "tools/list":
                 # Return a list of available tools
                 response = {
                     "jsonrpc": "2.0",
@@ -1,11 +1,17 @@
 //! Content-addressed cache for tool invocation results.
+//!
+//! [`CacheStore`] entries can expire and the store can be capped: a
+//! [`CachePolicy`] given at construction sets a default time-to-live and a
+//! maximum entry count. Expired entries miss on lookup and are removed then;
+//! inserting past the cap evicts the least-recently-used entry.
 
 use crate::trace::hasher::canonical_hash;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry {
@@ -17,37 +23,118 @@ pub struct CacheEntry {
     pub outputs: serde_json::Value,
 }
 
+/// Expiry and size limits for a [`CacheStore`]. The default is unbounded
+/// with no expiry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Lifetime of entries stored with [`CacheStore::put`].
+    pub ttl: Option<Duration>,
+    /// Most entries kept at once; the least recently used goes first.
+    pub max_entries: Option<usize>,
+}
+
+impl CachePolicy {
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+}
+
+struct Slot {
+    entry: CacheEntry,
+    expires_at: Option<Instant>,
+    /// Key into `CacheStore::recency`.
+    last_used: u64,
+}
+
+impl Slot {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| now >= at)
+    }
+}
+
 pub struct CacheStore {
     cache_dir: PathBuf,
-    memory: HashMap<String, CacheEntry>,
+    memory: HashMap<String, Slot>,
+    policy: CachePolicy,
+    /// Keys ordered by last use, oldest first.
+    recency: BTreeMap<u64, String>,
+    clock: u64,
 }
 
 impl CacheStore {
     pub fn new(base_dir: &Path) -> Self {
+        Self::with_policy(base_dir, CachePolicy::default())
+    }
+
+    pub fn with_policy(base_dir: &Path, policy: CachePolicy) -> Self {
         let cache_dir = base_dir.join("cache");
         fs::create_dir_all(&cache_dir).ok();
         Self {
             cache_dir,
             memory: HashMap::new(),
+            policy,
+            recency: BTreeMap::new(),
+            clock: 0,
         }
     }
 
-    pub fn get(&self, key: &str) -> Option<&CacheEntry> {
-        self.memory.get(key)
+    pub fn policy(&self) -> CachePolicy {
+        self.policy
+    }
+
+    /// Look up `key`, marking it as recently used. An expired entry is
+    /// removed and reported as a miss.
+    pub fn get(&mut self, key: &str) -> Option<&CacheEntry> {
+        let expired = self.memory.get(key)?.is_expired(Instant::now());
+        if expired {
+            self.remove(key);
+            return None;
+        }
+        let tick = self.tick();
+        let slot = self.memory.get_mut(key)?;
+        let old = std::mem::replace(&mut slot.last_used, tick);
+        if let Some(key) = self.recency.remove(&old) {
+            self.recency.insert(tick, key);
+        }
+        Some(&slot.entry)
     }
 
+    /// Store `entry` with the policy's default time-to-live.
     pub fn put(&mut self, entry: CacheEntry) {
-        let path = self
-            .cache_dir
-            .join(format!("{}.json", &entry.key[7..71.min(entry.key.len())]));
+        self.put_with_ttl(entry, self.policy.ttl);
+    }
+
+    /// Store `entry`, expiring after `ttl` (or never, for `None`).
+    pub fn put_with_ttl(&mut self, entry: CacheEntry, ttl: Option<Duration>) {
+        let path = self.entry_path(&entry.key);
         if let Ok(json) = serde_json::to_string_pretty(&entry) {
             fs::write(&path, json).ok();
         }
-        self.memory.insert(entry.key.clone(), entry);
+        let key = entry.key.clone();
+        if let Some(old) = self.memory.remove(&key) {
+            self.recency.remove(&old.last_used);
+        }
+        let tick = self.tick();
+        self.recency.insert(tick, key.clone());
+        self.memory.insert(
+            key,
+            Slot {
+                entry,
+                expires_at: ttl.map(|ttl| Instant::now() + ttl),
+                last_used: tick,
+            },
+        );
+        self.enforce_capacity();
     }
 
     pub fn lookup(
-        &self,
+        &mut self,
         tool_id: &str,
         version: &str,
         policy_hash: &str,
@@ -57,6 +144,64 @@ impl CacheStore {
         let key = crate::trace::hasher::cache_key(tool_id, version, policy_hash, &args_hash);
         self.get(&key)
     }
+
+    /// Remove every expired entry, returning how many were removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .memory
+            .iter()
+            .filter(|(_, slot)| slot.is_expired(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            self.remove(key);
+        }
+        expired.len()
+    }
+
+    /// Number of entries held, including expired ones not yet purged.
+    pub fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty()
+    }
+
+    // -- internal ---------------------------------------------------------
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn enforce_capacity(&mut self) {
+        let Some(max) = self.policy.max_entries else {
+            return;
+        };
+        if self.memory.len() > max {
+            self.purge_expired();
+        }
+        while self.memory.len() > max {
+            let Some((_, key)) = self.recency.pop_first() else {
+                break;
+            };
+            self.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(slot) = self.memory.remove(key) {
+            self.recency.remove(&slot.last_used);
+            fs::remove_file(self.entry_path(key)).ok();
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.cache_dir
+            .join(format!("{}.json", &key[7..71.min(key.len())]))
+    }
 }
 
 // ===========================================================================
@@ -400,7 +545,78 @@ mod tests {
     #[test]
     fn cache_store_put_and_get() {
         let dir = std::env::temp_dir().join("lumen_cache_tests_store");
-        let store = CacheStore::new(&dir);
+        let mut store = CacheStore::new(&dir);
         assert!(store.get("nonexistent").is_none());
     }
+
+    fn entry(n: u32) -> CacheEntry {
+        CacheEntry {
+            key: format!("sha256:{:064x}", n),
+            tool_id: "tool".to_string(),
+            version: "1".to_string(),
+            policy_hash: String::new(),
+            inputs_hash: String::new(),
+            outputs: serde_json::json!(n),
+        }
+    }
+
+    fn temp_store_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("lumen_cache_tests_{}_{}", name, std::process::id()))
+    }
+
+    // =====================================================================
+    // 14. CacheStore entry past its TTL is a miss
+    // =====================================================================
+    #[test]
+    fn cache_store_expired_entry_misses() {
+        let dir = temp_store_dir("ttl");
+        let mut store = CacheStore::with_policy(
+            &dir,
+            CachePolicy::default().with_ttl(Duration::from_millis(30)),
+        );
+        store.put(entry(1));
+        store.put_with_ttl(entry(2), None);
+        assert!(store.get(&entry(1).key).is_some());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(store.get(&entry(1).key).is_none());
+        assert!(store.get(&entry(2).key).is_some());
+        assert_eq!(store.len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    // =====================================================================
+    // 15. CacheStore evicts the least-recently-used entry at capacity
+    // =====================================================================
+    #[test]
+    fn cache_store_evicts_least_recently_used() {
+        let dir = temp_store_dir("lru");
+        let mut store = CacheStore::with_policy(&dir, CachePolicy::default().with_max_entries(2));
+        store.put(entry(1));
+        store.put(entry(2));
+        // Touch 1 so 2 becomes the least recently used.
+        assert!(store.get(&entry(1).key).is_some());
+        store.put(entry(3));
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&entry(2).key).is_none());
+        assert!(store.get(&entry(1).key).is_some());
+        assert!(store.get(&entry(3).key).is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    // =====================================================================
+    // 16. CacheStore purge_expired sweeps all expired entries
+    // =====================================================================
+    #[test]
+    fn cache_store_purge_expired() {
+        let dir = temp_store_dir("purge");
+        let mut store = CacheStore::new(&dir);
+        store.put_with_ttl(entry(1), Some(Duration::ZERO));
+        store.put_with_ttl(entry(2), Some(Duration::ZERO));
+        store.put(entry(3));
+        assert_eq!(store.purge_expired(), 2);
+        assert_eq!(store.len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -4,9 +4,23 @@
 //! [`crossbeam_channel`]. The API is intentionally thin — a [`Sender`] /
 //! [`Receiver`] pair is created by [`bounded()`] or [`unbounded()`], and the
 //! channel can be closed by dropping all senders or calling [`Sender::close`].
+//!
+//! A bounded channel applies backpressure: once `capacity` messages are
+//! buffered, [`Sender::send`] blocks until a receiver takes one, and
+//! [`Sender::try_send`] returns [`TrySendError::Full`] instead of waiting.
+//! Closing the channel wakes blocked senders, which then fail with
+//! [`SendError`].
+//!
+//! [`fan_out()`] and [`fan_in()`] build concurrent topologies on top of these
+//! channels: one source split across several workers, or several sources
+//! merged into one. Both forward through bounded buffers, so a slow consumer
+//! eventually blocks the producers feeding it.
 
 use crossbeam_channel::{self as cb};
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 // ---------------------------------------------------------------------------
 // Errors
@@ -24,6 +38,35 @@ impl<T> fmt::Display for SendError<T> {
 
 impl<T: fmt::Debug> std::error::Error for SendError<T> {}
 
+/// Error returned by [`Sender::try_send`]; both variants hand the value back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is at capacity.
+    Full(T),
+    /// The channel is closed.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Recover the value that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) | TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "send failed: channel is full"),
+            TrySendError::Closed(_) => write!(f, "send failed: channel is closed"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for TrySendError<T> {}
+
 /// Error returned by [`Receiver::try_recv`] when the channel is empty or
 /// closed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -58,6 +101,44 @@ impl fmt::Display for RecvError {
 
 impl std::error::Error for RecvError {}
 
+// ---------------------------------------------------------------------------
+// Close signal
+// ---------------------------------------------------------------------------
+
+/// Explicit-close state shared by every handle of one channel.
+///
+/// crossbeam channels only close when one side is fully dropped, so an
+/// explicit close is layered on top: dropping `trigger` disconnects `done`,
+/// which wakes any handle blocked in a select that includes it.
+struct CloseSignal {
+    closed: AtomicBool,
+    trigger: Mutex<Option<cb::Sender<()>>>,
+    done: cb::Receiver<()>,
+}
+
+impl CloseSignal {
+    fn new() -> Arc<Self> {
+        let (trigger, done) = cb::bounded(0);
+        Arc::new(Self {
+            closed: AtomicBool::new(false),
+            trigger: Mutex::new(Some(trigger)),
+            done,
+        })
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.trigger
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Sender
 // ---------------------------------------------------------------------------
@@ -69,12 +150,14 @@ impl std::error::Error for RecvError {}
 /// (or [`close`](Sender::close) is called on any of them).
 pub struct Sender<T> {
     inner: cb::Sender<T>,
+    signal: Arc<CloseSignal>,
 }
 
 impl<T> Clone for Sender<T> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            signal: Arc::clone(&self.signal),
         }
     }
 }
@@ -89,9 +172,47 @@ impl<T> Sender<T> {
     /// Send a value into the channel.
     ///
     /// For bounded channels this blocks if the channel is full.
-    /// Returns [`SendError`] if all receivers have been dropped.
+    /// Returns [`SendError`] if the channel is closed or all receivers have
+    /// been dropped, including when it is closed while this call is blocked.
     pub fn send(&self, value: T) -> Result<(), SendError<T>> {
-        self.inner.send(value).map_err(|e| SendError(e.0))
+        if self.signal.is_closed() {
+            return Err(SendError(value));
+        }
+        // Only a send that would block needs to wait on the close signal too.
+        let value = match self.inner.try_send(value) {
+            Ok(()) => return Ok(()),
+            Err(cb::TrySendError::Disconnected(value)) => return Err(SendError(value)),
+            Err(cb::TrySendError::Full(value)) => value,
+        };
+        let mut select = cb::Select::new();
+        let send = select.send(&self.inner);
+        select.recv(&self.signal.done);
+        let op = select.select();
+        if op.index() == send {
+            op.send(&self.inner, value).map_err(|e| SendError(e.0))
+        } else {
+            let _ = op.recv(&self.signal.done);
+            Err(SendError(value))
+        }
+    }
+
+    /// Send a value without blocking.
+    ///
+    /// Returns [`TrySendError::Full`] if a bounded channel is at capacity and
+    /// [`TrySendError::Closed`] if the channel is closed.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        if self.signal.is_closed() {
+            return Err(TrySendError::Closed(value));
+        }
+        self.inner.try_send(value).map_err(|e| match e {
+            cb::TrySendError::Full(value) => TrySendError::Full(value),
+            cb::TrySendError::Disconnected(value) => TrySendError::Closed(value),
+        })
+    }
+
+    /// The channel's capacity, or `None` if it is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.inner.capacity()
     }
 
     /// Returns the number of messages currently buffered.
@@ -106,20 +227,19 @@ impl<T> Sender<T> {
 
     /// Close the channel.
     ///
-    /// Subsequent sends on any clone of this sender will fail. Receivers can
+    /// Subsequent sends on any clone of this sender will fail, and senders
+    /// blocked on a full channel wake up with [`SendError`]. Receivers can
     /// still drain messages that were already buffered.
     ///
     /// Note: dropping all senders also implicitly closes the channel.
     /// This method is provided for explicit control.
     pub fn close(&self) {
-        // crossbeam channels don't expose an explicit close. We signal
-        // closure by dropping a clone — but since `&self` is a borrow we
-        // cannot drop our inner. Instead we document that dropping all
-        // Sender handles is the canonical close path.
-        //
-        // For an *explicit* close-from-borrow we'd need additional shared
-        // state. For now we keep the API surface and note that the
-        // idiomatic close is `drop(sender)`.
+        self.signal.close();
+    }
+
+    /// Whether [`close`](Sender::close) has been called on any handle.
+    pub fn is_closed(&self) -> bool {
+        self.signal.is_closed()
     }
 }
 
@@ -133,12 +253,14 @@ impl<T> Sender<T> {
 /// delivered to exactly one receiver (MPMC semantics from crossbeam).
 pub struct Receiver<T> {
     pub(crate) inner: cb::Receiver<T>,
+    signal: Arc<CloseSignal>,
 }
 
 impl<T> Clone for Receiver<T> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            signal: Arc::clone(&self.signal),
         }
     }
 }
@@ -152,17 +274,46 @@ impl<T> fmt::Debug for Receiver<T> {
 impl<T> Receiver<T> {
     /// Block until a message is available or the channel is closed.
     pub fn recv(&self) -> Result<T, RecvError> {
-        self.inner.recv().map_err(|_| RecvError)
+        // Only a recv that would block needs to wait on the close signal too.
+        match self.inner.try_recv() {
+            Ok(value) => return Ok(value),
+            Err(cb::TryRecvError::Disconnected) => return Err(RecvError),
+            Err(cb::TryRecvError::Empty) => {}
+        }
+        let mut select = cb::Select::new();
+        let recv = select.recv(&self.inner);
+        select.recv(&self.signal.done);
+        let op = select.select();
+        if op.index() == recv {
+            op.recv(&self.inner).map_err(|_| RecvError)
+        } else {
+            // Closed explicitly: hand out whatever is still buffered.
+            let _ = op.recv(&self.signal.done);
+            self.inner.try_recv().map_err(|_| RecvError)
+        }
     }
 
     /// Attempt to receive a message without blocking.
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
         self.inner.try_recv().map_err(|e| match e {
-            cb::TryRecvError::Empty => TryRecvError::Empty,
-            cb::TryRecvError::Disconnected => TryRecvError::Disconnected,
+            cb::TryRecvError::Empty if !self.signal.is_closed() => TryRecvError::Empty,
+            _ => TryRecvError::Disconnected,
         })
     }
 
+    /// Register this receiver with `select`, returning the operation indices
+    /// for its messages and for its close signal.
+    pub(crate) fn register<'s>(&'s self, select: &mut cb::Select<'s>) -> [usize; 2] {
+        [select.recv(&self.inner), select.recv(&self.signal.done)]
+    }
+
+    fn from_inner(inner: cb::Receiver<T>) -> Self {
+        Self {
+            inner,
+            signal: CloseSignal::new(),
+        }
+    }
+
     /// Returns the number of messages currently buffered.
     pub fn len(&self) -> usize {
         self.inner.len()
@@ -180,18 +331,104 @@ impl<T> Receiver<T> {
 
 /// Create a bounded channel with the given capacity.
 ///
-/// The sender will block when the buffer is full.
+/// The sender will block when the buffer is full, until a receiver makes room
+/// or the channel is closed.
 pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
-    let (tx, rx) = cb::bounded(capacity);
-    (Sender { inner: tx }, Receiver { inner: rx })
+    pair(cb::bounded(capacity))
 }
 
 /// Create an unbounded channel.
 ///
 /// The sender never blocks (memory is the only limit).
 pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
-    let (tx, rx) = cb::unbounded();
-    (Sender { inner: tx }, Receiver { inner: rx })
+    pair(cb::unbounded())
+}
+
+fn pair<T>((tx, rx): (cb::Sender<T>, cb::Receiver<T>)) -> (Sender<T>, Receiver<T>) {
+    let signal = CloseSignal::new();
+    (
+        Sender {
+            inner: tx,
+            signal: Arc::clone(&signal),
+        },
+        Receiver { inner: rx, signal },
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Fan-out / fan-in
+// ---------------------------------------------------------------------------
+
+/// Buffer size of the channels created by [`fan_out()`] and [`fan_in()`].
+///
+/// Kept at one item so that a busy worker stops being offered new items and
+/// a stalled consumer backs up into its producers almost immediately.
+const FAN_BUFFER: usize = 1;
+
+/// Split the items of `source` across `n` worker channels.
+///
+/// Each item is delivered to exactly one worker, whichever has room first, so
+/// faster workers receive more items. When every worker is busy the
+/// forwarding thread blocks, which in turn blocks senders on `source` once its
+/// buffer fills. Worker channels close after `source` is closed and drained.
+/// Workers that drop their receiver are skipped; if all of them do, the
+/// remaining items in `source` are left unread.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn fan_out<T: Send + 'static>(source: Receiver<T>, n: usize) -> Vec<Receiver<T>> {
+    assert!(n > 0, "fan_out requires at least one worker");
+    let (mut senders, receivers): (Vec<_>, Vec<_>) =
+        (0..n).map(|_| cb::bounded::<T>(FAN_BUFFER)).unzip();
+
+    thread::spawn(move || {
+        while let Ok(mut item) = source.recv() {
+            loop {
+                if senders.is_empty() {
+                    return;
+                }
+                let mut select = cb::Select::new();
+                for tx in &senders {
+                    select.send(tx);
+                }
+                let op = select.select();
+                let idx = op.index();
+                match op.send(&senders[idx], item) {
+                    Ok(()) => break,
+                    Err(cb::SendError(rejected)) => {
+                        // That worker hung up; offer the item to the others.
+                        senders.swap_remove(idx);
+                        item = rejected;
+                    }
+                }
+            }
+        }
+    });
+
+    receivers.into_iter().map(Receiver::from_inner).collect()
+}
+
+/// Merge the items of several channels into one.
+///
+/// Items from each source keep their relative order; items from different
+/// sources are interleaved as they arrive. A forwarding thread per source
+/// blocks while the merged channel is full, so an unconsumed output applies
+/// backpressure to every source. The merged channel closes once all sources
+/// are closed and drained.
+pub fn fan_in<T: Send + 'static>(sources: Vec<Receiver<T>>) -> Receiver<T> {
+    let (tx, rx) = cb::bounded(FAN_BUFFER);
+    for source in sources {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            while let Ok(item) = source.recv() {
+                if tx.send(item).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    Receiver::from_inner(rx)
 }
 
 // ---------------------------------------------------------------------------
@@ -201,7 +438,6 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::thread;
 
     // -- unbounded --------------------------------------------------------
 
@@ -270,6 +506,70 @@ mod tests {
         handle.join().unwrap();
     }
 
+    #[test]
+    fn bounded_producer_blocks_at_capacity_and_resumes_after_recv() {
+        let (tx, rx) = bounded::<i32>(2);
+        assert_eq!(tx.capacity(), Some(2));
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+
+        let producer = thread::spawn(move || tx.send(3));
+        thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!producer.is_finished(), "send should block while full");
+
+        assert_eq!(rx.recv().unwrap(), 1);
+        producer.join().unwrap().unwrap();
+        assert_eq!(rx.recv().unwrap(), 2);
+        assert_eq!(rx.recv().unwrap(), 3);
+    }
+
+    #[test]
+    fn close_wakes_blocked_sender_with_error() {
+        let (tx, rx) = bounded::<i32>(1);
+        tx.send(1).unwrap();
+
+        let blocked = tx.clone();
+        let producer = thread::spawn(move || blocked.send(2));
+        thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!producer.is_finished());
+
+        tx.close();
+        assert_eq!(producer.join().unwrap(), Err(SendError(2)));
+        assert!(tx.is_closed());
+        assert_eq!(tx.send(3), Err(SendError(3)));
+        assert_eq!(tx.try_send(4), Err(TrySendError::Closed(4)));
+
+        // Already-buffered messages still drain, then the channel reads as closed.
+        assert_eq!(rx.recv().unwrap(), 1);
+        assert_eq!(rx.recv(), Err(RecvError));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn zero_capacity_channel_hands_off_between_threads() {
+        let (tx, rx) = bounded::<i32>(0);
+        let producer = thread::spawn(move || {
+            for i in 0..3 {
+                tx.send(i).unwrap();
+            }
+        });
+        assert_eq!(rx.recv().unwrap(), 0);
+        assert_eq!(rx.recv().unwrap(), 1);
+        assert_eq!(rx.recv().unwrap(), 2);
+        producer.join().unwrap();
+        assert_eq!(rx.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn close_wakes_blocked_receiver() {
+        let (tx, rx) = unbounded::<i32>();
+        let consumer = thread::spawn(move || rx.recv());
+        thread::sleep(std::time::Duration::from_millis(20));
+        tx.close();
+        assert_eq!(consumer.join().unwrap(), Err(RecvError));
+    }
+
     // -- multi-producer multi-consumer ------------------------------------
 
     #[test]
@@ -302,6 +602,114 @@ mod tests {
         assert_eq!(received.len(), (num_producers * msgs_per_producer) as usize);
     }
 
+    // -- fan-out / fan-in -------------------------------------------------
+
+    #[test]
+    fn fan_out_distributes_without_loss_or_duplication() {
+        let (tx, rx) = bounded::<u32>(4);
+        let workers = fan_out(rx, 4);
+        assert_eq!(workers.len(), 4);
+
+        let producer = thread::spawn(move || {
+            for i in 0..1000 {
+                tx.send(i).unwrap();
+            }
+        });
+
+        let handles: Vec<_> = workers
+            .into_iter()
+            .map(|worker| {
+                thread::spawn(move || {
+                    let mut seen = vec![];
+                    while let Ok(v) = worker.recv() {
+                        seen.push(v);
+                    }
+                    seen
+                })
+            })
+            .collect();
+
+        producer.join().unwrap();
+        let per_worker: Vec<Vec<u32>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(
+            per_worker.iter().all(|items| !items.is_empty()),
+            "every worker should receive items"
+        );
+
+        let mut all: Vec<u32> = per_worker.into_iter().flatten().collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn fan_out_skips_dropped_workers() {
+        let (tx, rx) = unbounded::<u32>();
+        let mut workers = fan_out(rx, 2);
+        drop(workers.pop());
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+        drop(tx);
+
+        let remaining = workers.pop().unwrap();
+        let mut got = vec![];
+        while let Ok(v) = remaining.recv() {
+            got.push(v);
+        }
+        assert_eq!(got, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn fan_in_merges_every_item() {
+        let mut sources = vec![];
+        let mut producers = vec![];
+        for p in 0..3u32 {
+            let (tx, rx) = bounded::<u32>(2);
+            sources.push(rx);
+            producers.push(thread::spawn(move || {
+                for i in 0..200 {
+                    tx.send(p * 1000 + i).unwrap();
+                }
+            }));
+        }
+
+        let merged = fan_in(sources);
+        let mut received = vec![];
+        while let Ok(v) = merged.recv() {
+            received.push(v);
+        }
+        for p in producers {
+            p.join().unwrap();
+        }
+
+        // Per-source order is preserved.
+        for p in 0..3u32 {
+            let from_p: Vec<u32> = received.iter().copied().filter(|v| v / 1000 == p).collect();
+            assert_eq!(from_p, (0..200).map(|i| p * 1000 + i).collect::<Vec<_>>());
+        }
+        assert_eq!(received.len(), 600);
+    }
+
+    #[test]
+    fn fan_in_applies_backpressure() {
+        let (tx, rx) = bounded::<u32>(1);
+        let merged = fan_in(vec![rx]);
+        let producer = thread::spawn(move || {
+            for i in 0..100 {
+                tx.send(i).unwrap();
+            }
+        });
+
+        // Nobody reads `merged` yet, so the producer cannot finish: at most
+        // one item sits in each buffer plus one held by the forwarder.
+        thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!producer.is_finished());
+
+        let drained: Vec<u32> = std::iter::from_fn(|| merged.recv().ok()).collect();
+        producer.join().unwrap();
+        assert_eq!(drained, (0..100).collect::<Vec<_>>());
+    }
+
     // -- len / is_empty ---------------------------------------------------
 
     #[test]
@@ -331,6 +739,8 @@ mod tests {
             .to_string()
             .contains("disconnected"));
         assert!(RecvError.to_string().contains("closed"));
+        assert!(TrySendError::Full(1).to_string().contains("full"));
+        assert!(TrySendError::Closed(1).to_string().contains("closed"));
     }
 
     // -- try_recv disconnected after drain ---------------------------------
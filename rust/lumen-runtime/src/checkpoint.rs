@@ -5,9 +5,14 @@
 //! that writes snapshots atomically via write-to-tmp + rename.
 //!
 //! [`CheckpointEngine`] wraps a store and provides higher-level operations such
-//! as `latest()` and `prune()`.
+//! as `latest()` and `prune()`.  Checkpoints are written as sealed envelopes
+//! (see [`Snapshot::seal`]) so corruption is detected on restore.
+//!
+//! [`CheckpointOptions`] selects the compression codec. The envelope records
+//! the codec, so an engine restores checkpoints written with any codec, and
+//! bare checkpoints from before envelopes existed, whatever its own setting.
 
-use crate::snapshot::{Snapshot, SnapshotError, SnapshotId};
+use crate::snapshot::{is_sealed, Snapshot, SnapshotCompression, SnapshotError, SnapshotId};
 use std::fs;
 use std::path::PathBuf;
 
@@ -117,51 +122,87 @@ impl CheckpointStore for FileCheckpointStore {
 // Engine
 // ---------------------------------------------------------------------------
 
+/// How a [`CheckpointEngine`] writes checkpoints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CheckpointOptions {
+    /// Codec applied to snapshot payloads before saving.
+    pub compression: SnapshotCompression,
+}
+
+impl CheckpointOptions {
+    /// zstd-compressed checkpoints, for large states.
+    pub fn compressed() -> Self {
+        CheckpointOptions {
+            compression: SnapshotCompression::zstd(),
+        }
+    }
+}
+
+/// Leading bytes of a gzip stream, which marks a legacy compressed checkpoint.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 /// High-level checkpoint engine that combines serialization with storage.
 pub struct CheckpointEngine {
     store: Box<dyn CheckpointStore>,
-    /// When true, snapshots are gzip-compressed before saving and
-    /// decompressed on load.
-    compressed: bool,
+    options: CheckpointOptions,
 }
 
 impl CheckpointEngine {
     pub fn new(store: Box<dyn CheckpointStore>) -> Self {
-        CheckpointEngine {
-            store,
-            compressed: false,
-        }
+        Self::with_options(store, CheckpointOptions::default())
     }
 
-    /// Create a checkpoint engine that compresses snapshots on save and
+    /// Create a checkpoint engine with the given options.
+    pub fn with_options(store: Box<dyn CheckpointStore>, options: CheckpointOptions) -> Self {
+        CheckpointEngine { store, options }
+    }
+
+    /// Create a checkpoint engine that gzip-compresses snapshots on save and
     /// decompresses on load.
     pub fn new_compressed(store: Box<dyn CheckpointStore>) -> Self {
-        CheckpointEngine {
-            store,
-            compressed: true,
-        }
+        Self::with_compression(store, SnapshotCompression::gzip())
+    }
+
+    /// Create a checkpoint engine with an explicit compression codec.
+    pub fn with_compression(
+        store: Box<dyn CheckpointStore>,
+        compression: SnapshotCompression,
+    ) -> Self {
+        Self::with_options(store, CheckpointOptions { compression })
+    }
+
+    /// The options this engine writes with.
+    pub fn options(&self) -> CheckpointOptions {
+        self.options
     }
 
     /// Whether this engine uses compression.
     pub fn is_compressed(&self) -> bool {
-        self.compressed
+        self.options.compression != SnapshotCompression::None
+    }
+
+    /// The codec this engine applies on save.
+    pub fn compression(&self) -> SnapshotCompression {
+        self.options.compression
     }
 
     /// Serialize a snapshot and persist it.  Returns the snapshot's ID.
     pub fn checkpoint(&self, snapshot: &Snapshot) -> Result<SnapshotId, CheckpointError> {
-        let bytes = if self.compressed {
-            snapshot.serialize_compressed()?
-        } else {
-            snapshot.serialize()?
-        };
+        let bytes = snapshot.seal(self.options.compression)?;
         self.store.save(snapshot.id, &bytes)?;
         Ok(snapshot.id)
     }
 
-    /// Load and deserialize a snapshot by ID.
+    /// Load and deserialize a snapshot by ID, verifying its checksum.
+    ///
+    /// Checkpoints written before sealed envelopes were introduced are still
+    /// readable, as plain or gzip-compressed bincode, without an integrity
+    /// check.
     pub fn restore(&self, id: SnapshotId) -> Result<Snapshot, CheckpointError> {
         let bytes = self.store.load(id)?;
-        if self.compressed {
+        if is_sealed(&bytes) {
+            Ok(Snapshot::unseal(&bytes)?)
+        } else if bytes.starts_with(&GZIP_MAGIC) {
             Ok(Snapshot::deserialize_compressed(&bytes)?)
         } else {
             Ok(Snapshot::deserialize(&bytes)?)
@@ -452,4 +493,97 @@ mod tests {
         let _ = fs::remove_dir_all(&dir_raw);
         let _ = fs::remove_dir_all(&dir_comp);
     }
+
+    #[test]
+    fn engine_rejects_corrupted_checkpoint() {
+        let dir = temp_dir("engine-corrupted");
+        let store = FileCheckpointStore::new(&dir).unwrap();
+        let engine = CheckpointEngine::new_compressed(Box::new(store));
+
+        let snap = sample_snapshot_for_checkpoint();
+        let id = engine.checkpoint(&snap).unwrap();
+
+        let path = dir.join(format!("{}.snap", id.0));
+        let mut bytes = fs::read(&path).unwrap();
+        let mid = bytes.len() / 2 + 20;
+        bytes[mid] ^= 0x55;
+        fs::write(&path, &bytes).unwrap();
+
+        match engine.restore(id) {
+            Err(CheckpointError::Snapshot(SnapshotError::Corrupted(_))) => {}
+            other => panic!("expected corruption error, got {:?}", other.map(|s| s.id)),
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn engine_with_explicit_compression_level() {
+        let dir = temp_dir("engine-gzip-level");
+        let store = FileCheckpointStore::new(&dir).unwrap();
+        let engine = CheckpointEngine::with_compression(
+            Box::new(store),
+            SnapshotCompression::Gzip { level: 9 },
+        );
+        assert!(engine.is_compressed());
+        assert_eq!(engine.compression(), SnapshotCompression::Gzip { level: 9 });
+
+        let snap = sample_snapshot_for_checkpoint();
+        let id = engine.checkpoint(&snap).unwrap();
+        let restored = engine.restore(id).unwrap();
+        assert_eq!(snap.frames[0].registers, restored.frames[0].registers);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn zstd_checkpoint_round_trips_identical_state() {
+        let dir = temp_dir("engine-zstd");
+        let store = FileCheckpointStore::new(&dir).unwrap();
+        let engine =
+            CheckpointEngine::with_options(Box::new(store), CheckpointOptions::compressed());
+        assert_eq!(engine.compression(), SnapshotCompression::zstd());
+
+        let snap = sample_snapshot_for_checkpoint();
+        let id = engine.checkpoint(&snap).unwrap();
+        let restored = engine.restore(id).unwrap();
+        assert_eq!(snap.serialize().unwrap(), restored.serialize().unwrap());
+
+        // An uncompressed engine reads it back too.
+        let plain = CheckpointEngine::new(Box::new(FileCheckpointStore::new(&dir).unwrap()));
+        assert_eq!(plain.restore(id).unwrap().id, snap.id);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn compressed_engine_restores_legacy_uncompressed_checkpoint() {
+        let dir = temp_dir("engine-legacy-plain");
+        let store = FileCheckpointStore::new(&dir).unwrap();
+        let snap = sample_snapshot_for_checkpoint();
+        store.save(snap.id, &snap.serialize().unwrap()).unwrap();
+
+        let engine =
+            CheckpointEngine::with_options(Box::new(store), CheckpointOptions::compressed());
+        let restored = engine.restore(snap.id).unwrap();
+        assert_eq!(snap.serialize().unwrap(), restored.serialize().unwrap());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn engine_restores_legacy_unsealed_checkpoint() {
+        let dir = temp_dir("engine-legacy");
+        let store = FileCheckpointStore::new(&dir).unwrap();
+        let snap = sample_snapshot_for_checkpoint();
+        store
+            .save(snap.id, &snap.serialize_compressed().unwrap())
+            .unwrap();
+
+        let engine = CheckpointEngine::new_compressed(Box::new(store));
+        let restored = engine.restore(snap.id).unwrap();
+        assert_eq!(snap.id, restored.id);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
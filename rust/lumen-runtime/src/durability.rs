@@ -4,11 +4,28 @@
 //! nondeterministic operation (tool calls, timestamps, random values, external
 //! input).  On replay the same log is fed back so that the replayed execution
 //! is bit-for-bit identical to the original.
+//!
+//! # Compaction
+//!
+//! A [`Checkpoint`](LogEntry::Checkpoint) entry names a snapshot that holds
+//! the full state at that point, so recovery only needs that snapshot plus
+//! the entries after it. [`compact`](DurableLog::compact) drops everything
+//! before the latest checkpoint, and a threshold set with
+//! [`set_compaction_threshold`](DurableLog::set_compaction_threshold) does so
+//! automatically once the log grows past it. The snapshot must already be
+//! persisted when its checkpoint is appended.
+//!
+//! File-backed compaction writes the compacted log to a sibling
+//! `<log>.compact` file, syncs it, and renames it over the log. A crash before
+//! the rename leaves the original log intact, and the leftover temporary file
+//! is removed the next time the log is opened; a crash after it leaves the
+//! complete compacted log.
 
 use crate::snapshot::SnapshotId;
 use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // ---------------------------------------------------------------------------
 // Log entries
@@ -59,35 +76,49 @@ pub enum DurableLogError {
 pub struct DurableLog {
     entries: Vec<LogEntry>,
     writer: Option<Box<dyn Write + Send>>,
+    /// Backing file, for logs created with [`with_file`](DurableLog::with_file).
+    path: Option<PathBuf>,
+    /// Index of the latest checkpoint in `entries`.
+    last_checkpoint: Option<usize>,
+    compaction_threshold: Option<usize>,
 }
 
 impl DurableLog {
     /// Create an in-memory-only durable log.
     pub fn new() -> Self {
-        DurableLog {
-            entries: Vec::new(),
-            writer: None,
-        }
+        Self::from_parts(Vec::new(), None, None)
     }
 
     /// Create a durable log backed by the given file path.
     /// Each entry is JSON-lines encoded and flushed on append.
     pub fn with_file(path: impl AsRef<Path>) -> Result<Self, DurableLogError> {
-        let file = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(path)?;
-        Ok(DurableLog {
-            entries: Vec::new(),
-            writer: Some(Box::new(std::io::BufWriter::new(file))),
-        })
+        let path = path.as_ref();
+        discard_interrupted_compaction(path)?;
+        let writer = open_append(path)?;
+        Ok(Self::from_parts(
+            Vec::new(),
+            Some(writer),
+            Some(path.to_path_buf()),
+        ))
     }
 
     /// Create a durable log with a custom writer (useful for testing).
     pub fn with_writer(writer: Box<dyn Write + Send>) -> Self {
+        Self::from_parts(Vec::new(), Some(writer), None)
+    }
+
+    fn from_parts(
+        entries: Vec<LogEntry>,
+        writer: Option<Box<dyn Write + Send>>,
+        path: Option<PathBuf>,
+    ) -> Self {
+        let last_checkpoint = latest_checkpoint(&entries);
         DurableLog {
-            entries: Vec::new(),
-            writer: Some(writer),
+            entries,
+            writer,
+            path,
+            last_checkpoint,
+            compaction_threshold: None,
         }
     }
 
@@ -102,10 +133,72 @@ impl DurableLog {
             writeln!(w, "{}", json)?;
             w.flush()?;
         }
+        if matches!(entry, LogEntry::Checkpoint(_)) {
+            self.last_checkpoint = Some(self.entries.len());
+        }
         self.entries.push(entry);
+        if self
+            .compaction_threshold
+            .is_some_and(|threshold| self.entries.len() > threshold)
+            && self.last_checkpoint.is_some_and(|index| index > 0)
+        {
+            self.compact()?;
+        }
         Ok(())
     }
 
+    /// Compact automatically once more than `threshold` entries are held and
+    /// a checkpoint past the start of the log lets some be dropped. `None`
+    /// turns automatic compaction off.
+    pub fn set_compaction_threshold(&mut self, threshold: Option<usize>) {
+        self.compaction_threshold = threshold;
+    }
+
+    /// Drop every entry before the latest checkpoint, returning how many were
+    /// dropped. Without a checkpoint there is nothing to fold and the log is
+    /// left as is.
+    ///
+    /// A file-backed log is compacted on disk, including entries written
+    /// before it was opened; [`entries`](DurableLog::entries) then holds the
+    /// compacted file.
+    pub fn compact(&mut self) -> Result<usize, DurableLogError> {
+        let Some(path) = self.path.clone() else {
+            let dropped = self.last_checkpoint.unwrap_or(0);
+            self.entries.drain(..dropped);
+            self.last_checkpoint = self.last_checkpoint.map(|_| 0);
+            return Ok(dropped);
+        };
+
+        let mut entries = read_entries(&path)?;
+        let dropped = latest_checkpoint(&entries).unwrap_or(0);
+        if dropped == 0 {
+            return Ok(0);
+        }
+        let tail = entries.split_off(dropped);
+
+        // Close the old handle before the file is replaced underneath it.
+        self.writer = None;
+        let swapped = write_compacted(&path, &tail);
+        self.writer = Some(open_append(&path)?);
+        swapped?;
+
+        self.entries = tail;
+        self.last_checkpoint = Some(0);
+        Ok(dropped)
+    }
+
+    /// Where recovery starts: the latest checkpoint's snapshot (if any) and
+    /// the entries to replay after it.
+    pub fn recovery_point(&self) -> (Option<SnapshotId>, &[LogEntry]) {
+        match self.last_checkpoint {
+            Some(index) => match &self.entries[index] {
+                LogEntry::Checkpoint(id) => (Some(*id), &self.entries[index + 1..]),
+                _ => (None, &self.entries),
+            },
+            None => (None, &self.entries),
+        }
+    }
+
     /// Read-only access to all recorded entries.
     pub fn entries(&self) -> &[LogEntry] {
         &self.entries
@@ -124,28 +217,81 @@ impl DurableLog {
     /// Construct a log pre-populated with entries for replay.
     /// No writer is attached — replay is read-only.
     pub fn replay_from(entries: Vec<LogEntry>) -> Self {
-        DurableLog {
-            entries,
-            writer: None,
-        }
+        Self::from_parts(entries, None, None)
     }
 
     /// Load a durable log from a JSON-lines file.
     pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, DurableLogError> {
-        let contents = std::fs::read_to_string(path)?;
-        let mut entries = Vec::new();
-        for line in contents.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
-            let entry: LogEntry = serde_json::from_str(line)
-                .map_err(|e| DurableLogError::Deserialize(e.to_string()))?;
-            entries.push(entry);
+        let path = path.as_ref();
+        discard_interrupted_compaction(path)?;
+        Ok(Self::from_parts(read_entries(path)?, None, None))
+    }
+}
+
+fn latest_checkpoint(entries: &[LogEntry]) -> Option<usize> {
+    entries
+        .iter()
+        .rposition(|entry| matches!(entry, LogEntry::Checkpoint(_)))
+}
+
+fn read_entries(path: &Path) -> Result<Vec<LogEntry>, DurableLogError> {
+    let contents = fs::read_to_string(path)?;
+    let mut entries = Vec::new();
+    for line in contents.lines() {
+        if line.trim().is_empty() {
+            continue;
         }
-        Ok(DurableLog {
-            entries,
-            writer: None,
-        })
+        let entry: LogEntry =
+            serde_json::from_str(line).map_err(|e| DurableLogError::Deserialize(e.to_string()))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn open_append(path: &Path) -> Result<Box<dyn Write + Send>, DurableLogError> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    Ok(Box::new(std::io::BufWriter::new(file)))
+}
+
+/// The temporary file a compaction of `path` writes before renaming it into
+/// place.
+fn compaction_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".compact");
+    PathBuf::from(name)
+}
+
+/// Write `entries` to the compaction file, make it durable, and rename it
+/// over `path`.
+fn write_compacted(path: &Path, entries: &[LogEntry]) -> Result<(), DurableLogError> {
+    let temp = compaction_path(path);
+    let mut out = std::io::BufWriter::new(File::create(&temp)?);
+    for entry in entries {
+        let json =
+            serde_json::to_string(entry).map_err(|e| DurableLogError::Serialize(e.to_string()))?;
+        writeln!(out, "{}", json)?;
+    }
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&temp, path)?;
+    // Persist the rename itself. Not every platform can open a directory,
+    // so this is best effort.
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            dir.sync_all().ok();
+        }
+    }
+    Ok(())
+}
+
+/// Remove a compaction file left by a crash before its rename; the log it
+/// was replacing is still complete.
+fn discard_interrupted_compaction(path: &Path) -> Result<(), DurableLogError> {
+    match fs::remove_file(compaction_path(path)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
     }
 }
 
@@ -265,6 +411,108 @@ mod tests {
         assert_eq!(log.len(), 1);
     }
 
+    fn sample_history(log: &mut DurableLog) {
+        log.append(LogEntry::Timestamp(1)).unwrap();
+        log.append(LogEntry::Random(2)).unwrap();
+        log.append(LogEntry::Checkpoint(SnapshotId(10))).unwrap();
+        log.append(LogEntry::ToolCall {
+            name: "fetch".into(),
+            args: "{}".into(),
+            result: "ok".into(),
+        })
+        .unwrap();
+        log.append(LogEntry::Checkpoint(SnapshotId(11))).unwrap();
+        log.append(LogEntry::Random(7)).unwrap();
+        log.append(LogEntry::Timestamp(8)).unwrap();
+    }
+
+    #[test]
+    fn compaction_preserves_replay_state() {
+        let path = temp_path("compact");
+        let mut log = DurableLog::with_file(&path).unwrap();
+        sample_history(&mut log);
+
+        let before = DurableLog::load_from_file(&path).unwrap();
+        let (snapshot, tail) = before.recovery_point();
+        assert_eq!(snapshot, Some(SnapshotId(11)));
+        assert_eq!(tail, &[LogEntry::Random(7), LogEntry::Timestamp(8)][..]);
+
+        assert_eq!(log.compact().unwrap(), 4);
+        let after = DurableLog::load_from_file(&path).unwrap();
+        assert_eq!(after.len(), 3);
+        assert_eq!(after.recovery_point(), before.recovery_point());
+        assert_eq!(log.recovery_point(), before.recovery_point());
+
+        // Appends after compaction land in the compacted file.
+        log.append(LogEntry::Random(9)).unwrap();
+        let reloaded = DurableLog::load_from_file(&path).unwrap();
+        assert_eq!(reloaded.entries().last(), Some(&LogEntry::Random(9)));
+        assert_eq!(reloaded.len(), 4);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn interrupted_compaction_recovers_original_log() {
+        let path = temp_path("compact-crash");
+        {
+            let mut log = DurableLog::with_file(&path).unwrap();
+            sample_history(&mut log);
+        }
+        let original = DurableLog::load_from_file(&path).unwrap();
+
+        // A crash mid-compaction leaves a partial temporary file beside the
+        // untouched log.
+        let temp = compaction_path(&path);
+        fs::write(&temp, "{\"Checkpoint\":11}\n{\"Rand").unwrap();
+
+        let recovered = DurableLog::load_from_file(&path).unwrap();
+        assert_eq!(recovered.entries(), original.entries());
+        assert!(!temp.exists());
+
+        // Reopening for append also recovers, and compaction then succeeds.
+        fs::write(&temp, "garbage").unwrap();
+        let mut log = DurableLog::with_file(&path).unwrap();
+        assert!(!temp.exists());
+        assert_eq!(log.compact().unwrap(), 4);
+        let compacted = DurableLog::load_from_file(&path).unwrap();
+        assert_eq!(compacted.recovery_point(), original.recovery_point());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn compaction_threshold_bounds_the_log() {
+        let path = temp_path("compact-auto");
+        let mut log = DurableLog::with_file(&path).unwrap();
+        log.set_compaction_threshold(Some(4));
+        for i in 0..100 {
+            log.append(LogEntry::Random(i)).unwrap();
+            if i % 3 == 2 {
+                log.append(LogEntry::Checkpoint(SnapshotId(i))).unwrap();
+            }
+        }
+        let on_disk = DurableLog::load_from_file(&path).unwrap();
+        assert!(on_disk.len() <= 5, "log grew to {}", on_disk.len());
+        assert_eq!(on_disk.recovery_point(), log.recovery_point());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn compaction_without_checkpoint_is_a_no_op() {
+        let mut log = DurableLog::new();
+        log.append(LogEntry::Timestamp(1)).unwrap();
+        log.append(LogEntry::Random(2)).unwrap();
+        assert_eq!(log.compact().unwrap(), 0);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.recovery_point(), (None, log.entries()));
+
+        log.append(LogEntry::Checkpoint(SnapshotId(3))).unwrap();
+        assert_eq!(log.compact().unwrap(), 2);
+        assert_eq!(log.entries(), &[LogEntry::Checkpoint(SnapshotId(3))][..]);
+    }
+
     #[test]
     fn default_is_empty() {
         let log = DurableLog::default();
@@ -3,7 +3,8 @@
 //! While [`crate::reduction::ReductionCounter`] provides generic cooperative
 //! preemption, this module tracks **per-effect** call counts so that
 //! individual effects (e.g. `http`, `fs`, `llm`) can be independently
-//! rate-limited at runtime.
+//! rate-limited at runtime. A total budget can additionally cap the
+//! number of calls across all effects combined.
 //!
 //! # Example
 //!
@@ -16,8 +17,9 @@
 //! for _ in 0..5 {
 //!     assert!(tracker.record_call("http").is_ok());
 //! }
-//! // Sixth call exceeds the budget.
+//! // Sixth call exceeds the budget, but other effects are unaffected.
 //! assert!(tracker.record_call("http").is_err());
+//! assert!(tracker.record_call("fs").is_ok());
 //! ```
 
 use std::collections::HashMap;
@@ -27,13 +29,18 @@ use crate::tools::ToolError;
 /// Tracks per-effect invocation counts and enforces configurable budgets.
 ///
 /// Effects without an explicit budget are **unconstrained** — calls to
-/// [`record_call`] for those effects always succeed.
+/// [`record_call`] for those effects succeed unless the total budget (see
+/// [`set_total_budget`]) is exhausted.
 #[derive(Debug, Clone)]
 pub struct EffectBudgetTracker {
     /// Maximum allowed calls per effect name.
     budgets: HashMap<String, u64>,
     /// Number of calls recorded so far per effect name.
     counts: HashMap<String, u64>,
+    /// Maximum allowed calls across all effects.
+    total_budget: Option<u64>,
+    /// Number of calls recorded so far across all effects.
+    total_count: u64,
 }
 
 impl EffectBudgetTracker {
@@ -42,6 +49,8 @@ impl EffectBudgetTracker {
         Self {
             budgets: HashMap::new(),
             counts: HashMap::new(),
+            total_budget: None,
+            total_count: 0,
         }
     }
 
@@ -61,12 +70,50 @@ impl EffectBudgetTracker {
         self.budgets.remove(effect_name).is_some()
     }
 
+    /// Cap the number of calls across all effects, budgeted or not.
+    ///
+    /// Like [`set_budget`], this does **not** reset the current total.
+    pub fn set_total_budget(&mut self, max_calls: u64) {
+        self.total_budget = Some(max_calls);
+    }
+
+    /// Remove the total budget, leaving only per-effect budgets.
+    ///
+    /// Returns `true` if a total budget was previously set.
+    pub fn remove_total_budget(&mut self) -> bool {
+        self.total_budget.take().is_some()
+    }
+
+    /// Return the total budget, or `None` if calls are only capped per effect.
+    pub fn total_budget(&self) -> Option<u64> {
+        self.total_budget
+    }
+
+    /// Return the number of calls recorded across all effects.
+    pub fn total_calls(&self) -> u64 {
+        self.total_count
+    }
+
     /// Record a call to `effect_name`.
     ///
     /// Returns `Ok(())` if the call is within budget (or no budget is set).
-    /// Returns `Err(ToolError::BudgetExhausted { .. })` if the budget is
-    /// exceeded.
+    /// Returns `Err(ToolError::BudgetExhausted { .. })` if the effect's own
+    /// budget or the total budget is exceeded; a rejected call is not
+    /// counted against either.
     pub fn record_call(&mut self, effect_name: &str) -> Result<(), ToolError> {
+        if let Some(limit) = self.total_budget {
+            if self.total_count >= limit {
+                return Err(ToolError::BudgetExhausted {
+                    effect: effect_name.to_string(),
+                    limit: limit as u32,
+                    message: format!(
+                        "total effect budget is {}, and {} call(s) have been made across all effects",
+                        limit, self.total_count
+                    ),
+                });
+            }
+        }
+
         let count = self.counts.entry(effect_name.to_string()).or_insert(0);
 
         if let Some(&limit) = self.budgets.get(effect_name) {
@@ -83,15 +130,29 @@ impl EffectBudgetTracker {
         }
 
         *count += 1;
+        self.total_count += 1;
         Ok(())
     }
 
-    /// Return the remaining budget for `effect_name`, or `None` if no budget
-    /// is configured.
+    /// Return the number of further calls `effect_name` may make — the
+    /// smaller of its own remaining budget and the total remaining budget —
+    /// or `None` if neither is configured.
     pub fn remaining(&self, effect_name: &str) -> Option<u64> {
-        let limit = self.budgets.get(effect_name)?;
-        let used = self.counts.get(effect_name).copied().unwrap_or(0);
-        Some(limit.saturating_sub(used))
+        let own = self.budgets.get(effect_name).map(|limit| {
+            let used = self.counts.get(effect_name).copied().unwrap_or(0);
+            limit.saturating_sub(used)
+        });
+        match (own, self.total_remaining()) {
+            (Some(own), Some(total)) => Some(own.min(total)),
+            (own, total) => own.or(total),
+        }
+    }
+
+    /// Return the remaining total budget, or `None` if no total budget is
+    /// configured.
+    pub fn total_remaining(&self) -> Option<u64> {
+        self.total_budget
+            .map(|limit| limit.saturating_sub(self.total_count))
     }
 
     /// Return the current call count for `effect_name`.
@@ -105,12 +166,14 @@ impl EffectBudgetTracker {
         self.budgets.get(effect_name).copied()
     }
 
-    /// Reset all counters (but keep budgets).
+    /// Reset all counters, including the total (but keep budgets).
     pub fn reset(&mut self) {
         self.counts.clear();
+        self.total_count = 0;
     }
 
-    /// Reset the counter for a single effect (budget is kept).
+    /// Reset the counter for a single effect (budget is kept). Calls it
+    /// already made still count toward the total budget.
     pub fn reset_effect(&mut self, effect_name: &str) {
         self.counts.remove(effect_name);
     }
@@ -122,10 +185,14 @@ impl EffectBudgetTracker {
         names
     }
 
-    /// Return `true` if the budget for `effect_name` is exhausted.
+    /// Return `true` if the budget for `effect_name`, or the total budget,
+    /// is exhausted.
     ///
     /// Returns `false` when no budget is configured (unconstrained).
     pub fn is_exhausted(&self, effect_name: &str) -> bool {
+        if self.total_remaining() == Some(0) {
+            return true;
+        }
         match self.budgets.get(effect_name) {
             Some(&limit) => {
                 let used = self.counts.get(effect_name).copied().unwrap_or(0);
@@ -336,4 +403,76 @@ mod tests {
         assert_eq!(tracker.remaining("http"), Some(8)); // 10 - 2
         assert!(tracker.record_call("http").is_ok());
     }
+
+    #[test]
+    fn exhausted_effect_does_not_block_others() {
+        let mut tracker = EffectBudgetTracker::new();
+        tracker.set_budget("http", 1);
+        tracker.set_budget("fs", 2);
+        tracker.set_total_budget(10);
+
+        tracker.record_call("http").unwrap();
+        assert!(tracker.record_call("http").is_err());
+        assert!(tracker.is_exhausted("http"));
+
+        assert!(!tracker.is_exhausted("fs"));
+        tracker.record_call("fs").unwrap();
+        tracker.record_call("fs").unwrap();
+        tracker.record_call("llm").unwrap();
+        // The rejected http call isn't counted.
+        assert_eq!(tracker.total_calls(), 4);
+        assert_eq!(tracker.total_remaining(), Some(6));
+    }
+
+    #[test]
+    fn total_budget_caps_calls_across_effects() {
+        let mut tracker = EffectBudgetTracker::new();
+        tracker.set_budget("http", 5);
+        tracker.set_budget("fs", 5);
+        tracker.set_total_budget(3);
+
+        tracker.record_call("http").unwrap();
+        tracker.record_call("fs").unwrap();
+        assert_eq!(tracker.remaining("http"), Some(1));
+        assert_eq!(tracker.remaining("trace"), Some(1));
+        tracker.record_call("trace").unwrap();
+
+        assert!(tracker.is_exhausted("fs"));
+        for effect in ["http", "fs", "trace"] {
+            match tracker.record_call(effect).unwrap_err() {
+                ToolError::BudgetExhausted {
+                    effect: name,
+                    limit,
+                    message,
+                } => {
+                    assert_eq!(name, effect);
+                    assert_eq!(limit, 3);
+                    assert!(message.contains("total"));
+                }
+                other => panic!("expected BudgetExhausted, got: {other}"),
+            }
+        }
+        assert_eq!(tracker.call_count("http"), 1);
+
+        tracker.reset();
+        assert_eq!(tracker.total_calls(), 0);
+        assert!(tracker.record_call("fs").is_ok());
+    }
+
+    #[test]
+    fn reset_effect_keeps_total_and_remove_total_budget() {
+        let mut tracker = EffectBudgetTracker::new();
+        tracker.set_total_budget(2);
+        tracker.record_call("http").unwrap();
+        tracker.record_call("http").unwrap();
+
+        tracker.reset_effect("http");
+        assert_eq!(tracker.call_count("http"), 0);
+        assert!(tracker.record_call("http").is_err());
+
+        assert!(tracker.remove_total_budget());
+        assert_eq!(tracker.total_budget(), None);
+        assert!(tracker.record_call("http").is_ok());
+        assert!(!tracker.remove_total_budget());
+    }
 }
@@ -26,9 +26,24 @@
 //! assert_eq!(result.unwrap(), "Alice");
 //! assert!(!executed);
 //! ```
+//!
+//! # Expiry
+//!
+//! Keys can be given a time-to-live, either per store
+//! ([`IdempotencyStore::with_ttl`]) or per call
+//! ([`check_or_execute_with_ttl`](IdempotencyStore::check_or_execute_with_ttl)).
+//! Once it passes, the key behaves as if it was never seen, so a replayed
+//! request executes again; [`sweep_expired`](IdempotencyStore::sweep_expired)
+//! frees the memory of expired keys.
+//!
+//! [`SharedIdempotencyStore`] is the thread-safe variant: concurrent calls
+//! with the same unseen key run the side effect once, and the other callers
+//! wait for and share its result.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 // ---------------------------------------------------------------------------
 // Errors
@@ -43,6 +58,39 @@ pub enum IdempotencyError {
     Deserialize(String),
 }
 
+// ---------------------------------------------------------------------------
+// Cached results
+// ---------------------------------------------------------------------------
+
+/// A serialized result and when it stops counting.
+#[derive(Debug, Clone)]
+struct CachedResult {
+    /// Serialized (bincode) result bytes.
+    data: Vec<u8>,
+    expires_at: Option<Instant>,
+}
+
+impl CachedResult {
+    fn new(data: Vec<u8>, ttl: Option<Duration>) -> Self {
+        CachedResult {
+            data,
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+        }
+    }
+
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|at| now < at)
+    }
+
+    fn decode<R: for<'de> Deserialize<'de>>(&self) -> Result<R, IdempotencyError> {
+        bincode::deserialize(&self.data).map_err(|e| IdempotencyError::Deserialize(e.to_string()))
+    }
+}
+
+fn encode<R: Serialize>(result: &R) -> Result<Vec<u8>, IdempotencyError> {
+    bincode::serialize(result).map_err(|e| IdempotencyError::Serialize(e.to_string()))
+}
+
 // ---------------------------------------------------------------------------
 // IdempotencyStore
 // ---------------------------------------------------------------------------
@@ -53,8 +101,11 @@ pub enum IdempotencyError {
 /// result immediately, avoiding re-execution.
 #[derive(Debug, Clone)]
 pub struct IdempotencyStore {
-    /// Maps idempotency keys to serialized (bincode) result bytes.
-    entries: HashMap<String, Vec<u8>>,
+    /// Maps idempotency keys to cached results.
+    entries: HashMap<String, CachedResult>,
+    /// Lifetime of keys stored without an explicit TTL; `None` keeps them
+    /// until invalidated.
+    default_ttl: Option<Duration>,
 }
 
 impl IdempotencyStore {
@@ -62,9 +113,24 @@ impl IdempotencyStore {
     pub fn new() -> Self {
         IdempotencyStore {
             entries: HashMap::new(),
+            default_ttl: None,
+        }
+    }
+
+    /// Create an empty store whose keys expire `ttl` after their result is
+    /// recorded.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        IdempotencyStore {
+            entries: HashMap::new(),
+            default_ttl: Some(ttl),
         }
     }
 
+    /// The lifetime given to keys stored without an explicit TTL.
+    pub fn default_ttl(&self) -> Option<Duration> {
+        self.default_ttl
+    }
+
     /// Check whether a result is cached for `key`. If so, deserialize and
     /// return it. Otherwise, execute `f()`, serialize and cache the result,
     /// then return it.
@@ -76,19 +142,41 @@ impl IdempotencyStore {
         F: FnOnce() -> R,
         R: Serialize + for<'de> Deserialize<'de>,
     {
-        if let Some(cached) = self.entries.get(key) {
-            let result: R = bincode::deserialize(cached)
-                .map_err(|e| IdempotencyError::Deserialize(e.to_string()))?;
-            return Ok(result);
+        self.check_or_execute_with_ttl(key, self.default_ttl, f)
+    }
+
+    /// Like [`check_or_execute`](Self::check_or_execute), but a newly
+    /// recorded result expires after `ttl` (or never, for `None`) instead of
+    /// the store's default.
+    pub fn check_or_execute_with_ttl<F, R>(
+        &mut self,
+        key: &str,
+        ttl: Option<Duration>,
+        f: F,
+    ) -> Result<R, IdempotencyError>
+    where
+        F: FnOnce() -> R,
+        R: Serialize + for<'de> Deserialize<'de>,
+    {
+        if let Some(cached) = self.live(key) {
+            return cached.decode();
         }
 
         let result = f();
-        let bytes =
-            bincode::serialize(&result).map_err(|e| IdempotencyError::Serialize(e.to_string()))?;
-        self.entries.insert(key.to_string(), bytes);
+        let bytes = encode(&result)?;
+        self.entries
+            .insert(key.to_string(), CachedResult::new(bytes, ttl));
         Ok(result)
     }
 
+    /// Remove every expired key, returning how many were removed.
+    pub fn sweep_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let before = self.entries.len();
+        self.entries.retain(|_, cached| cached.is_live(now));
+        before - self.entries.len()
+    }
+
     /// Invalidate (remove) a cached result for `key`.
     ///
     /// Returns `true` if the key was present and removed.
@@ -101,12 +189,12 @@ impl IdempotencyStore {
         self.entries.clear();
     }
 
-    /// Check whether a cached result exists for `key`.
+    /// Check whether an unexpired cached result exists for `key`.
     pub fn contains(&self, key: &str) -> bool {
-        self.entries.contains_key(key)
+        self.live(key).is_some()
     }
 
-    /// Number of cached results.
+    /// Number of cached results, including expired ones not yet swept.
     pub fn len(&self) -> usize {
         self.entries.len()
     }
@@ -121,15 +209,23 @@ impl IdempotencyStore {
         self.entries.keys().map(|s| s.as_str())
     }
 
-    /// Get the raw cached bytes for a key (for debugging / inspection).
+    /// Get the raw cached bytes for an unexpired key (for debugging /
+    /// inspection).
     pub fn get_raw(&self, key: &str) -> Option<&[u8]> {
-        self.entries.get(key).map(|v| v.as_slice())
+        self.live(key).map(|cached| cached.data.as_slice())
     }
 
     /// Insert a pre-serialized result directly (useful when hydrating from
-    /// a replay log or external source).
+    /// a replay log or external source). It expires per the store's default.
     pub fn insert_raw(&mut self, key: String, data: Vec<u8>) {
-        self.entries.insert(key, data);
+        self.entries
+            .insert(key, CachedResult::new(data, self.default_ttl));
+    }
+
+    fn live(&self, key: &str) -> Option<&CachedResult> {
+        self.entries
+            .get(key)
+            .filter(|cached| cached.is_live(Instant::now()))
     }
 }
 
@@ -139,6 +235,165 @@ impl Default for IdempotencyStore {
     }
 }
 
+// ---------------------------------------------------------------------------
+// SharedIdempotencyStore
+// ---------------------------------------------------------------------------
+
+/// Thread-safe idempotency store. Clones share the same keys.
+///
+/// While one caller is executing the side effect for a key, other callers
+/// with that key block until it finishes and then return its result. If the
+/// side effect panics, the key is released and the next waiter executes it.
+#[derive(Debug, Clone, Default)]
+pub struct SharedIdempotencyStore {
+    inner: Arc<SharedInner>,
+}
+
+#[derive(Debug, Default)]
+struct SharedInner {
+    slots: Mutex<HashMap<String, Slot>>,
+    /// Signalled whenever a running key finishes or is released.
+    settled: Condvar,
+    default_ttl: Option<Duration>,
+}
+
+#[derive(Debug)]
+enum Slot {
+    Running,
+    Done(CachedResult),
+}
+
+impl SharedIdempotencyStore {
+    /// Create an empty store whose keys never expire.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty store whose keys expire `ttl` after their result is
+    /// recorded.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        SharedIdempotencyStore {
+            inner: Arc::new(SharedInner {
+                default_ttl: Some(ttl),
+                ..SharedInner::default()
+            }),
+        }
+    }
+
+    /// See [`IdempotencyStore::check_or_execute`]. Concurrent calls for the
+    /// same key execute `f` at most once.
+    pub fn check_or_execute<F, R>(&self, key: &str, f: F) -> Result<R, IdempotencyError>
+    where
+        F: FnOnce() -> R,
+        R: Serialize + for<'de> Deserialize<'de>,
+    {
+        self.check_or_execute_with_ttl(key, self.inner.default_ttl, f)
+    }
+
+    /// See [`IdempotencyStore::check_or_execute_with_ttl`].
+    pub fn check_or_execute_with_ttl<F, R>(
+        &self,
+        key: &str,
+        ttl: Option<Duration>,
+        f: F,
+    ) -> Result<R, IdempotencyError>
+    where
+        F: FnOnce() -> R,
+        R: Serialize + for<'de> Deserialize<'de>,
+    {
+        let mut slots = self.lock();
+        loop {
+            match slots.get(key) {
+                Some(Slot::Done(cached)) if cached.is_live(Instant::now()) => {
+                    return cached.decode();
+                }
+                Some(Slot::Running) => {
+                    slots = self
+                        .inner
+                        .settled
+                        .wait(slots)
+                        .unwrap_or_else(|e| e.into_inner());
+                }
+                _ => break,
+            }
+        }
+        slots.insert(key.to_string(), Slot::Running);
+        drop(slots);
+
+        let claim = Claim { store: self, key };
+        let result = f();
+        let bytes = encode(&result)?;
+        claim.finish(CachedResult::new(bytes, ttl));
+        Ok(result)
+    }
+
+    /// Remove every expired key, returning how many were removed.
+    pub fn sweep_expired(&self) -> usize {
+        let now = Instant::now();
+        let mut slots = self.lock();
+        let before = slots.len();
+        slots.retain(|_, slot| match slot {
+            Slot::Running => true,
+            Slot::Done(cached) => cached.is_live(now),
+        });
+        before - slots.len()
+    }
+
+    /// Remove the cached result for `key`. A key that is still executing is
+    /// left alone. Returns `true` if a result was removed.
+    pub fn invalidate(&self, key: &str) -> bool {
+        let mut slots = self.lock();
+        if matches!(slots.get(key), Some(Slot::Done(_))) {
+            slots.remove(key);
+            return true;
+        }
+        false
+    }
+
+    /// Whether an unexpired result is cached for `key`.
+    pub fn contains(&self, key: &str) -> bool {
+        matches!(self.lock().get(key), Some(Slot::Done(cached)) if cached.is_live(Instant::now()))
+    }
+
+    /// Number of keys held, including running and expired ones.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether the store holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Slot>> {
+        self.inner.slots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A key claimed for execution. Dropping it without [`finish`](Claim::finish)
+/// (on panic or serialization failure) releases the key to the next caller.
+struct Claim<'a> {
+    store: &'a SharedIdempotencyStore,
+    key: &'a str,
+}
+
+impl Claim<'_> {
+    fn finish(self, cached: CachedResult) {
+        self.store
+            .lock()
+            .insert(self.key.to_string(), Slot::Done(cached));
+        self.store.inner.settled.notify_all();
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Claim<'_> {
+    fn drop(&mut self) {
+        self.store.lock().remove(self.key);
+        self.store.inner.settled.notify_all();
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -338,6 +593,93 @@ mod tests {
         assert_eq!(cached, vec!["a", "b", "c"]);
     }
 
+    #[test]
+    fn key_within_ttl_is_deduped() {
+        let mut store = IdempotencyStore::with_ttl(Duration::from_secs(60));
+        assert_eq!(store.check_or_execute("k", || 1i32).unwrap(), 1);
+        assert_eq!(store.check_or_execute("k", || 2i32).unwrap(), 1);
+    }
+
+    #[test]
+    fn key_past_ttl_re_executes() {
+        let mut store = IdempotencyStore::with_ttl(Duration::from_millis(20));
+        assert_eq!(store.check_or_execute("k", || 1i32).unwrap(), 1);
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(!store.contains("k"));
+        assert_eq!(store.check_or_execute("k", || 2i32).unwrap(), 2);
+        assert_eq!(store.check_or_execute("k", || 3i32).unwrap(), 2);
+    }
+
+    #[test]
+    fn per_call_ttl_and_sweep() {
+        let mut store = IdempotencyStore::new();
+        store
+            .check_or_execute_with_ttl("short", Some(Duration::ZERO), || 1i32)
+            .unwrap();
+        store.check_or_execute("forever", || 2i32).unwrap();
+        assert_eq!(store.len(), 2);
+        assert!(store.get_raw("short").is_none());
+
+        assert_eq!(store.sweep_expired(), 1);
+        assert_eq!(store.len(), 1);
+        assert!(store.contains("forever"));
+    }
+
+    #[test]
+    fn shared_concurrent_first_use_runs_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Barrier;
+
+        let store = SharedIdempotencyStore::with_ttl(Duration::from_secs(60));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(8));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                let runs = Arc::clone(&runs);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    store
+                        .check_or_execute("charge-card", || {
+                            runs.fetch_add(1, Ordering::SeqCst);
+                            std::thread::sleep(Duration::from_millis(30));
+                            format!("receipt-{i}")
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        let results: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|r| r == &results[0]));
+    }
+
+    #[test]
+    fn shared_key_past_ttl_re_executes_and_sweeps() {
+        let store = SharedIdempotencyStore::with_ttl(Duration::from_millis(20));
+        assert_eq!(store.check_or_execute("k", || 1i32).unwrap(), 1);
+        assert_eq!(store.check_or_execute("k", || 2i32).unwrap(), 1);
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(store.sweep_expired(), 1);
+        assert!(store.is_empty());
+        assert_eq!(store.check_or_execute("k", || 3i32).unwrap(), 3);
+    }
+
+    #[test]
+    fn shared_panic_releases_key() {
+        let store = SharedIdempotencyStore::new();
+        let panicking = store.clone();
+        let outcome = std::thread::spawn(move || {
+            panicking.check_or_execute("k", || -> i32 { panic!("side effect failed") })
+        })
+        .join();
+        assert!(outcome.is_err());
+        assert_eq!(store.check_or_execute("k", || 7i32).unwrap(), 7);
+    }
+
     #[test]
     fn replay_simulation() {
         // Simulate a replay scenario: first run records, second run replays
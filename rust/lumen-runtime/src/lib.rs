@@ -20,10 +20,15 @@ pub mod idempotency;
 pub mod injection;
 pub mod json_ops;
 pub mod linear_collections;
+pub mod log_limit;
 pub mod mailbox;
 pub mod mock_effects;
+#[cfg(feature = "test-support")]
+pub mod mock_http;
 pub mod net;
 pub mod nursery;
+#[cfg(feature = "observability")]
+pub mod observability;
 pub mod panic_boundary;
 pub mod parity_durability;
 pub mod process;
@@ -0,0 +1,497 @@
+//! Rate limiting and deduplication for runtime log output.
+//!
+//! A tight loop that logs on every iteration can bury everything else in the
+//! log. [`LogLimiter`] sits in front of a log sink and decides which lines to
+//! actually write:
+//!
+//! - **Deduplication**: an identical message repeated within the window is
+//!   written once; the repeats are reported as a single summary line when a
+//!   different message arrives, the window elapses, or [`LogLimiter::flush`]
+//!   is called.
+//! - **Rate limiting**: at most `max_per_window` lines are written per level
+//!   per window. The first line over the limit is replaced by a
+//!   "logged N times in the last ..., suppressing" notice, and the number of
+//!   dropped lines is reported when the window ends.
+//!
+//! Both are configured per [`LogLevel`] through [`LogLimitConfig`].
+//!
+//! Runtime components log through a [`RuntimeLog`], which pairs a limiter
+//! with a sink. Unless given their own, they share [`global`], which writes
+//! to stderr.
+//!
+//! # Example
+//!
+//! ```rust
+//! use lumen_runtime::log_limit::{LogLevel, LogLimiter};
+//!
+//! let mut limiter = LogLimiter::default();
+//! let mut written = Vec::new();
+//! for _ in 0..1000 {
+//!     written.extend(limiter.log(LogLevel::Error, "connection refused"));
+//! }
+//! written.extend(limiter.flush());
+//! assert_eq!(written.len(), 2);
+//! assert_eq!(written[0].message, "connection refused");
+//! assert_eq!(
+//!     written[1].message,
+//!     "last message repeated 999 more times: connection refused"
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+// ---------------------------------------------------------------------------
+// Levels and configuration
+// ---------------------------------------------------------------------------
+
+/// Severity of a log line. Limits are tracked separately for each level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogLevel::Trace => write!(f, "TRACE"),
+            LogLevel::Debug => write!(f, "DEBUG"),
+            LogLevel::Info => write!(f, "INFO"),
+            LogLevel::Warn => write!(f, "WARN"),
+            LogLevel::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+/// Limits applied to one log level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogLimitConfig {
+    /// Maximum lines written per window; further lines are suppressed.
+    pub max_per_window: u32,
+    /// Length of the rate-limit and deduplication window.
+    pub window: Duration,
+    /// Collapse identical consecutive messages within the window.
+    pub dedup: bool,
+}
+
+impl Default for LogLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_per_window: 100,
+            window: Duration::from_secs(1),
+            dedup: true,
+        }
+    }
+}
+
+impl LogLimitConfig {
+    /// A config that never suppresses anything.
+    pub fn unlimited() -> Self {
+        Self {
+            max_per_window: u32::MAX,
+            window: Duration::from_secs(1),
+            dedup: false,
+        }
+    }
+}
+
+/// A line the limiter decided should be written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub level: LogLevel,
+    pub message: String,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.level, self.message)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Limiter
+// ---------------------------------------------------------------------------
+
+/// Per-level bookkeeping.
+#[derive(Debug)]
+struct LevelState {
+    window_start: Instant,
+    /// Lines counted against the rate limit in the current window.
+    count: u32,
+    /// Lines dropped by the rate limit in the current window.
+    rate_suppressed: u32,
+    /// Last message written, when it was written, and how many identical
+    /// messages have been collapsed into it since.
+    last: Option<(String, Instant, u32)>,
+}
+
+impl LevelState {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            count: 0,
+            rate_suppressed: 0,
+            last: None,
+        }
+    }
+}
+
+/// Decides which log lines to write under failure storms.
+///
+/// The limiter does not write anything itself: [`LogLimiter::log`] returns
+/// the lines (zero or more) that should go to the sink for each message.
+#[derive(Debug, Default)]
+pub struct LogLimiter {
+    default: LogLimitConfig,
+    levels: HashMap<LogLevel, LogLimitConfig>,
+    state: HashMap<LogLevel, LevelState>,
+}
+
+impl LogLimiter {
+    /// Create a limiter applying `config` to every level.
+    pub fn new(config: LogLimitConfig) -> Self {
+        Self {
+            default: config,
+            levels: HashMap::new(),
+            state: HashMap::new(),
+        }
+    }
+
+    /// Override the limits for one level.
+    pub fn with_level(mut self, level: LogLevel, config: LogLimitConfig) -> Self {
+        self.levels.insert(level, config);
+        self
+    }
+
+    /// The limits in effect for `level`.
+    pub fn config(&self, level: LogLevel) -> LogLimitConfig {
+        self.levels.get(&level).copied().unwrap_or(self.default)
+    }
+
+    /// Submit a message, returning the lines to write now.
+    pub fn log(&mut self, level: LogLevel, message: &str) -> Vec<LogLine> {
+        self.log_at(level, message, Instant::now())
+    }
+
+    /// [`Self::log`] with an explicit clock, for deterministic callers.
+    pub fn log_at(&mut self, level: LogLevel, message: &str, now: Instant) -> Vec<LogLine> {
+        let config = self.config(level);
+        let state = self
+            .state
+            .entry(level)
+            .or_insert_with(|| LevelState::new(now));
+        let mut out = Vec::new();
+
+        if now.duration_since(state.window_start) >= config.window {
+            Self::end_window(level, config, state, now, &mut out);
+        }
+
+        if config.dedup {
+            if let Some((last, written_at, repeats)) = &mut state.last {
+                if last == message && now.duration_since(*written_at) < config.window {
+                    *repeats += 1;
+                    return out;
+                }
+            }
+        }
+        Self::flush_repeats(level, state, &mut out);
+
+        state.count += 1;
+        if state.count > config.max_per_window {
+            if state.rate_suppressed == 0 {
+                out.push(LogLine {
+                    level,
+                    message: format!(
+                        "logged {} times in the last {:?}, suppressing",
+                        config.max_per_window, config.window
+                    ),
+                });
+            }
+            state.rate_suppressed += 1;
+            return out;
+        }
+
+        out.push(LogLine {
+            level,
+            message: message.to_string(),
+        });
+        state.last = Some((message.to_string(), now, 0));
+        out
+    }
+
+    /// Report pending repeat and suppression counts for every level.
+    pub fn flush(&mut self) -> Vec<LogLine> {
+        self.flush_at(Instant::now())
+    }
+
+    /// [`Self::flush`] with an explicit clock.
+    pub fn flush_at(&mut self, now: Instant) -> Vec<LogLine> {
+        let mut levels: Vec<LogLevel> = self.state.keys().copied().collect();
+        levels.sort();
+        let mut out = Vec::new();
+        for level in levels {
+            let config = self.config(level);
+            if let Some(state) = self.state.get_mut(&level) {
+                Self::flush_repeats(level, state, &mut out);
+                Self::end_window(level, config, state, now, &mut out);
+            }
+        }
+        out
+    }
+
+    /// Emit the summary for collapsed repeats of the last message, if any.
+    fn flush_repeats(level: LogLevel, state: &mut LevelState, out: &mut Vec<LogLine>) {
+        if let Some((last, _, repeats)) = state.last.take() {
+            if repeats > 0 {
+                out.push(LogLine {
+                    level,
+                    message: format!("last message repeated {} more times: {}", repeats, last),
+                });
+            }
+        }
+    }
+
+    /// Close the current rate-limit window, reporting dropped lines.
+    fn end_window(
+        level: LogLevel,
+        config: LogLimitConfig,
+        state: &mut LevelState,
+        now: Instant,
+        out: &mut Vec<LogLine>,
+    ) {
+        if state.rate_suppressed > 0 {
+            out.push(LogLine {
+                level,
+                message: format!(
+                    "suppressed {} messages in the last {:?}",
+                    state.rate_suppressed, config.window
+                ),
+            });
+        }
+        state.window_start = now;
+        state.count = 0;
+        state.rate_suppressed = 0;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Runtime log
+// ---------------------------------------------------------------------------
+
+/// Destination for the lines a [`RuntimeLog`] lets through.
+pub type LogSink = Box<dyn FnMut(&LogLine) + Send>;
+
+/// A [`RuntimeLog`] shared between components.
+pub type SharedLog = Arc<Mutex<RuntimeLog>>;
+
+/// The runtime's diagnostic log: a [`LogLimiter`] in front of a sink.
+/// Pending repeat and suppression counts are written when it is dropped.
+pub struct RuntimeLog {
+    limiter: LogLimiter,
+    sink: LogSink,
+}
+
+impl RuntimeLog {
+    /// Write the lines `limiter` lets through to `sink`.
+    pub fn new(limiter: LogLimiter, sink: LogSink) -> Self {
+        Self { limiter, sink }
+    }
+
+    /// A log writing `[lumen-runtime] [LEVEL] message` lines to stderr
+    /// under the default limits.
+    pub fn stderr() -> Self {
+        Self::new(
+            LogLimiter::default(),
+            Box::new(|line| eprintln!("[lumen-runtime] {}", line)),
+        )
+    }
+
+    /// Wrap the log for sharing between components.
+    pub fn shared(self) -> SharedLog {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Submit a message; the limiter decides what reaches the sink.
+    pub fn log(&mut self, level: LogLevel, message: &str) {
+        for line in self.limiter.log(level, message) {
+            (self.sink)(&line);
+        }
+    }
+
+    /// Write pending repeat and suppression counts.
+    pub fn flush(&mut self) {
+        for line in self.limiter.flush() {
+            (self.sink)(&line);
+        }
+    }
+}
+
+impl Drop for RuntimeLog {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl fmt::Debug for RuntimeLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeLog")
+            .field("limiter", &self.limiter)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The process-wide runtime log, writing to stderr.
+pub fn global() -> SharedLog {
+    static GLOBAL: OnceLock<SharedLog> = OnceLock::new();
+    GLOBAL.get_or_init(|| RuntimeLog::stderr().shared()).clone()
+}
+
+/// Log `message` through `log`. A log poisoned by a panicking writer keeps
+/// working.
+pub fn log_to(log: &SharedLog, level: LogLevel, message: &str) {
+    log.lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .log(level, message);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(lines: &[LogLine]) -> Vec<&str> {
+        lines.iter().map(|l| l.message.as_str()).collect()
+    }
+
+    #[test]
+    fn repeated_message_is_written_once_with_count() {
+        let mut limiter = LogLimiter::default();
+        let start = Instant::now();
+        let mut out = Vec::new();
+        for i in 0..50 {
+            out.extend(limiter.log_at(
+                LogLevel::Error,
+                "disk full",
+                start + Duration::from_millis(i),
+            ));
+        }
+        out.extend(limiter.flush_at(start + Duration::from_millis(60)));
+        assert_eq!(
+            messages(&out),
+            vec![
+                "disk full",
+                "last message repeated 49 more times: disk full"
+            ]
+        );
+    }
+
+    #[test]
+    fn distinct_messages_are_not_suppressed() {
+        let mut limiter = LogLimiter::default();
+        let start = Instant::now();
+        let mut out = Vec::new();
+        for msg in ["a", "b", "a", "c"] {
+            out.extend(limiter.log_at(LogLevel::Info, msg, start));
+        }
+        out.extend(limiter.flush_at(start));
+        assert_eq!(messages(&out), vec!["a", "b", "a", "c"]);
+    }
+
+    #[test]
+    fn repeats_are_summarized_before_the_next_message() {
+        let mut limiter = LogLimiter::default();
+        let start = Instant::now();
+        let mut out = Vec::new();
+        for msg in ["retry", "retry", "retry", "gave up"] {
+            out.extend(limiter.log_at(LogLevel::Warn, msg, start));
+        }
+        assert_eq!(
+            messages(&out),
+            vec![
+                "retry",
+                "last message repeated 2 more times: retry",
+                "gave up"
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_message_is_written_again_after_the_window() {
+        let mut limiter = LogLimiter::default();
+        let start = Instant::now();
+        let mut out = limiter.log_at(LogLevel::Error, "boom", start);
+        out.extend(limiter.log_at(LogLevel::Error, "boom", start));
+        out.extend(limiter.log_at(LogLevel::Error, "boom", start + Duration::from_secs(2)));
+        assert_eq!(
+            messages(&out),
+            vec!["boom", "last message repeated 1 more times: boom", "boom"]
+        );
+    }
+
+    #[test]
+    fn rate_limit_suppresses_and_reports_count() {
+        let config = LogLimitConfig {
+            max_per_window: 3,
+            window: Duration::from_secs(1),
+            dedup: false,
+        };
+        let mut limiter = LogLimiter::new(config);
+        let start = Instant::now();
+        let mut out = Vec::new();
+        for i in 0..10 {
+            out.extend(limiter.log_at(LogLevel::Error, &format!("event {}", i), start));
+        }
+        out.extend(limiter.log_at(
+            LogLevel::Error,
+            "after window",
+            start + Duration::from_secs(1),
+        ));
+        assert_eq!(
+            messages(&out),
+            vec![
+                "event 0",
+                "event 1",
+                "event 2",
+                "logged 3 times in the last 1s, suppressing",
+                "suppressed 7 messages in the last 1s",
+                "after window",
+            ]
+        );
+    }
+
+    #[test]
+    fn limits_are_configured_per_level() {
+        let mut limiter = LogLimiter::new(LogLimitConfig {
+            max_per_window: 1,
+            window: Duration::from_secs(1),
+            dedup: true,
+        })
+        .with_level(LogLevel::Debug, LogLimitConfig::unlimited());
+        let start = Instant::now();
+
+        let debug: Vec<LogLine> = (0..5)
+            .flat_map(|_| limiter.log_at(LogLevel::Debug, "tick", start))
+            .collect();
+        assert_eq!(debug.len(), 5);
+
+        let mut errors = limiter.log_at(LogLevel::Error, "x", start);
+        errors.extend(limiter.log_at(LogLevel::Error, "y", start));
+        assert_eq!(
+            messages(&errors),
+            vec!["x", "logged 1 times in the last 1s, suppressing"]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "[ERROR] logged 1 times in the last 1s, suppressing"
+        );
+    }
+}
@@ -9,6 +9,12 @@
 //! mailbox for the first message matching a predicate, leaving non-matching
 //! messages in order via an internal save queue.
 //!
+//! Messages sent with [`MailboxSender::send_priority`] above
+//! [`DEFAULT_PRIORITY`] travel on a separate, unbounded priority lane and are
+//! received before any default-priority message, highest priority first and
+//! FIFO within a level. Control messages (shutdown, reconfigure) use it to
+//! overtake a backlog of data.
+//!
 //! # Example
 //!
 //! ```rust
@@ -21,9 +27,17 @@
 //! ```
 
 use crossbeam_channel::{self as cb};
-use std::collections::VecDeque;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Message priority; higher values are received first.
+pub type Priority = u8;
+
+/// Priority of messages sent with [`MailboxSender::send`].
+pub const DEFAULT_PRIORITY: Priority = 0;
 
 // ---------------------------------------------------------------------------
 // Errors
@@ -67,12 +81,14 @@ impl std::error::Error for MailboxRecvError {}
 /// are dropped.
 pub struct MailboxSender<T> {
     inner: cb::Sender<T>,
+    priority: cb::Sender<(Priority, T)>,
 }
 
 impl<T> Clone for MailboxSender<T> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            priority: self.priority.clone(),
         }
     }
 }
@@ -80,25 +96,38 @@ impl<T> Clone for MailboxSender<T> {
 impl<T> fmt::Debug for MailboxSender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MailboxSender")
-            .field("pending", &self.inner.len())
+            .field("pending", &self.len())
             .finish()
     }
 }
 
 impl<T> MailboxSender<T> {
-    /// Non-blocking send. Returns `Err` if the mailbox receiver has been dropped.
+    /// Send at [`DEFAULT_PRIORITY`]. Blocks while a bounded mailbox is full;
+    /// returns `Err` if the mailbox receiver has been dropped.
     pub fn send(&self, msg: T) -> Result<(), MailboxSendError<T>> {
         self.inner.send(msg).map_err(|e| MailboxSendError(e.0))
     }
 
+    /// Send with an explicit priority. Messages above [`DEFAULT_PRIORITY`]
+    /// go on the priority lane, which is never bounded, so they are neither
+    /// held up by back-pressure nor queued behind default-priority messages.
+    pub fn send_priority(&self, msg: T, priority: Priority) -> Result<(), MailboxSendError<T>> {
+        if priority == DEFAULT_PRIORITY {
+            return self.send(msg);
+        }
+        self.priority
+            .send((priority, msg))
+            .map_err(|e| MailboxSendError((e.0).1))
+    }
+
     /// Number of messages currently buffered in the mailbox.
     pub fn len(&self) -> usize {
-        self.inner.len()
+        self.inner.len() + self.priority.len()
     }
 
     /// Whether the mailbox buffer is currently empty.
     pub fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+        self.inner.is_empty() && self.priority.is_empty()
     }
 }
 
@@ -118,61 +147,77 @@ impl<T> MailboxSender<T> {
 /// by a `Mailbox`.
 pub struct Mailbox<T> {
     inner: cb::Receiver<T>,
+    /// Lane for messages sent above [`DEFAULT_PRIORITY`].
+    priority: cb::Receiver<(Priority, T)>,
+    /// Messages taken off the priority lane, highest priority first and in
+    /// arrival order within a level. Always received before anything else.
+    urgent: RefCell<BTreeMap<Reverse<Priority>, VecDeque<T>>>,
     /// Erlang-style save queue: messages that were inspected during selective
     /// receive but did not match the predicate are stored here and drained
     /// first on subsequent receive calls.
-    save_queue: std::cell::RefCell<VecDeque<T>>,
+    save_queue: RefCell<VecDeque<T>>,
 }
 
 impl<T> fmt::Debug for Mailbox<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Mailbox")
-            .field(
-                "pending",
-                &(self.save_queue.borrow().len() + self.inner.len()),
-            )
+            .field("pending", &self.len())
             .finish()
     }
 }
 
+/// What a blocking wait on both lanes produced.
+enum Arrival<T> {
+    Message,
+    Normal(T),
+    Closed,
+    TimedOut,
+}
+
 impl<T> Mailbox<T> {
     /// Create an unbounded mailbox, returning `(sender, mailbox)`.
     ///
     /// The sender never blocks; memory is the only limit on buffering.
     pub fn unbounded() -> (MailboxSender<T>, Self) {
-        let (tx, rx) = cb::unbounded();
-        (
-            MailboxSender { inner: tx },
-            Self {
-                inner: rx,
-                save_queue: std::cell::RefCell::new(VecDeque::new()),
-            },
-        )
+        Self::with_channel(cb::unbounded())
     }
 
     /// Create a bounded mailbox with the given capacity.
     ///
-    /// Senders block when the buffer is full (back-pressure).
+    /// Senders block when the buffer is full (back-pressure). The capacity
+    /// applies to default-priority messages only.
     pub fn bounded(capacity: usize) -> (MailboxSender<T>, Self) {
-        let (tx, rx) = cb::bounded(capacity);
+        Self::with_channel(cb::bounded(capacity))
+    }
+
+    fn with_channel((tx, rx): (cb::Sender<T>, cb::Receiver<T>)) -> (MailboxSender<T>, Self) {
+        let (priority_tx, priority_rx) = cb::unbounded();
         (
-            MailboxSender { inner: tx },
+            MailboxSender {
+                inner: tx,
+                priority: priority_tx,
+            },
             Self {
                 inner: rx,
-                save_queue: std::cell::RefCell::new(VecDeque::new()),
+                priority: priority_rx,
+                urgent: RefCell::new(BTreeMap::new()),
+                save_queue: RefCell::new(VecDeque::new()),
             },
         )
     }
 
     /// Non-blocking receive. Returns `None` if no message is available.
     ///
-    /// Messages in the save queue (from prior selective receives) are
-    /// drained first, in order.
+    /// Priority messages come first, then messages in the save queue (from
+    /// prior selective receives), in order.
     ///
     /// This returns `None` both when the channel is empty-but-open and when
     /// it is closed-and-drained. Use `is_disconnected` to distinguish.
     pub fn recv(&self) -> Option<T> {
-        // First check the save queue
+        if let Some(msg) = self.pop_urgent() {
+            return Some(msg);
+        }
+        // Then check the save queue
         {
             let mut sq = self.save_queue.borrow_mut();
             if let Some(msg) = sq.pop_front() {
@@ -184,41 +229,45 @@ impl<T> Mailbox<T> {
 
     /// Blocking receive. Waits until a message arrives.
     ///
-    /// Messages in the save queue are drained first.
+    /// Priority messages and the save queue are drained first.
     ///
     /// Returns `Err(MailboxRecvError)` only when all senders have been dropped
     /// and the queue is empty.
     pub fn recv_blocking(&self) -> Result<T, MailboxRecvError> {
-        {
-            let mut sq = self.save_queue.borrow_mut();
-            if let Some(msg) = sq.pop_front() {
-                return Ok(msg);
-            }
-        }
-        self.inner.recv().map_err(|_| MailboxRecvError)
+        self.recv_until(None).ok_or(MailboxRecvError)
     }
 
     /// Receive with a timeout. Returns `None` if no message arrives within
     /// `duration`, or if all senders have been dropped.
     ///
-    /// Messages in the save queue are drained first.
+    /// Priority messages and the save queue are drained first.
     pub fn recv_timeout(&self, duration: Duration) -> Option<T> {
-        {
-            let mut sq = self.save_queue.borrow_mut();
-            if let Some(msg) = sq.pop_front() {
+        self.recv_until(Some(Instant::now() + duration))
+    }
+
+    fn recv_until(&self, deadline: Option<Instant>) -> Option<T> {
+        loop {
+            if let Some(msg) = self.recv() {
                 return Some(msg);
             }
+            match self.wait(deadline) {
+                Arrival::Message => {}
+                // Nothing else was queued when this arrived, so it is next.
+                Arrival::Normal(msg) => return Some(msg),
+                Arrival::Closed => return self.recv(),
+                Arrival::TimedOut => return None,
+            }
         }
-        self.inner.recv_timeout(duration).ok()
     }
 
     /// Selective receive (Erlang-style). Scans the mailbox for the first
     /// message matching `predicate`, leaving non-matching messages in order.
     ///
-    /// The scan order is: save queue first (front to back), then the
-    /// underlying channel. Messages inspected but not matching are placed
-    /// back in the save queue so that a subsequent `recv()` or
-    /// `recv_selective()` sees them in the original order.
+    /// The scan order is: priority messages (highest first), then the save
+    /// queue (front to back), then the underlying channel. Messages inspected
+    /// but not matching are placed back in the save queue so that a
+    /// subsequent `recv()` or `recv_selective()` sees them in the original
+    /// order.
     ///
     /// Returns `None` if no matching message is currently available (i.e. the
     /// save queue and channel have been fully scanned without a match).
@@ -226,16 +275,20 @@ impl<T> Mailbox<T> {
     where
         F: Fn(&T) -> bool,
     {
+        if let Some(msg) = self.take_urgent_matching(&predicate) {
+            return Some(msg);
+        }
+
         let mut sq = self.save_queue.borrow_mut();
 
-        // Phase 1: scan the save queue by index — remove the first match
+        // Scan the save queue by index — remove the first match
         for i in 0..sq.len() {
             if predicate(&sq[i]) {
                 return sq.remove(i);
             }
         }
 
-        // Phase 2: drain the channel, testing each message
+        // Drain the channel, testing each message
         while let Ok(msg) = self.inner.try_recv() {
             if predicate(&msg) {
                 return Some(msg);
@@ -254,56 +307,44 @@ impl<T> Mailbox<T> {
     where
         F: Fn(&T) -> bool,
     {
-        use std::time::Instant;
-
         let deadline = Instant::now() + timeout;
 
         // First, do a non-blocking selective scan of everything already buffered
-        {
-            let mut sq = self.save_queue.borrow_mut();
-
-            // Phase 1: scan save queue by index
-            for i in 0..sq.len() {
-                if predicate(&sq[i]) {
-                    return sq.remove(i);
-                }
-            }
-
-            // Phase 2: drain channel (non-blocking)
-            while let Ok(msg) = self.inner.try_recv() {
-                if predicate(&msg) {
-                    return Some(msg);
-                }
-                sq.push_back(msg);
-            }
+        if let Some(msg) = self.recv_selective(&predicate) {
+            return Some(msg);
         }
 
-        // Phase 3: wait for new messages until deadline
+        // Then wait for new messages until deadline
         loop {
-            let remaining = deadline.saturating_duration_since(Instant::now());
-            if remaining.is_zero() {
-                return None;
-            }
-            match self.inner.recv_timeout(remaining) {
-                Ok(msg) => {
+            match self.wait(Some(deadline)) {
+                Arrival::Message => {
+                    if let Some(msg) = self.take_urgent_matching(&predicate) {
+                        return Some(msg);
+                    }
+                }
+                Arrival::Normal(msg) => {
                     if predicate(&msg) {
                         return Some(msg);
                     }
                     self.save_queue.borrow_mut().push_back(msg);
                 }
-                Err(_) => return None,
+                Arrival::Closed => return self.recv_selective(&predicate),
+                Arrival::TimedOut => return None,
             }
         }
     }
 
-    /// Number of messages currently buffered (save queue + channel).
+    /// Number of messages currently buffered (priority lane, save queue and
+    /// channel).
     pub fn len(&self) -> usize {
-        self.save_queue.borrow().len() + self.inner.len()
+        let urgent: usize = self.urgent.borrow().values().map(VecDeque::len).sum();
+        urgent + self.priority.len() + self.save_queue.borrow().len() + self.inner.len()
     }
 
-    /// Whether the mailbox is currently empty (save queue + channel).
+    /// Whether the mailbox is currently empty (priority lane, save queue and
+    /// channel).
     pub fn is_empty(&self) -> bool {
-        self.save_queue.borrow().is_empty() && self.inner.is_empty()
+        self.len() == 0
     }
 
     /// Returns `true` if all senders have been dropped.
@@ -311,30 +352,32 @@ impl<T> Mailbox<T> {
     /// Note: even if disconnected, there may still be buffered messages
     /// retrievable via `recv` / `recv_blocking` (including in the save queue).
     pub fn is_disconnected(&self) -> bool {
-        // If there are messages in the save queue, we're not fully disconnected
-        // in a meaningful sense — there's still data to read.
-        if !self.save_queue.borrow().is_empty() {
+        // If there are messages buffered on our side, we're not fully
+        // disconnected in a meaningful sense — there's still data to read.
+        if !self.save_queue.borrow().is_empty() || !self.urgent.borrow().is_empty() {
             return false;
         }
         // crossbeam doesn't expose a direct "is_disconnected" method.
         // The simplest approach: attempt a zero-duration recv.
         match self.inner.recv_timeout(Duration::ZERO) {
-            Err(cb::RecvTimeoutError::Disconnected) => self.inner.is_empty(),
+            Err(cb::RecvTimeoutError::Disconnected) => {
+                self.inner.is_empty() && self.priority.is_empty()
+            }
             _ => false,
         }
     }
 
     /// Drain all currently-buffered messages into a `Vec`.
     ///
-    /// Save-queue messages come first (in order), then channel messages.
+    /// Priority messages come first (highest first), then save-queue messages
+    /// (in order), then channel messages.
     pub fn drain(&self) -> Vec<T> {
-        let mut msgs = Vec::new();
-        {
-            let mut sq = self.save_queue.borrow_mut();
-            while let Some(msg) = sq.pop_front() {
-                msgs.push(msg);
-            }
-        }
+        self.fill_urgent();
+        let mut msgs: Vec<T> = std::mem::take(&mut *self.urgent.borrow_mut())
+            .into_values()
+            .flatten()
+            .collect();
+        msgs.extend(self.save_queue.borrow_mut().drain(..));
         while let Ok(msg) = self.inner.try_recv() {
             msgs.push(msg);
         }
@@ -349,9 +392,86 @@ impl<T> Mailbox<T> {
 
     /// Provide access to the underlying crossbeam `Receiver` for use in
     /// `crossbeam_channel::select!` or integration with the actor system.
+    ///
+    /// Only default-priority messages arrive on this receiver.
     pub fn as_receiver(&self) -> &cb::Receiver<T> {
         &self.inner
     }
+
+    // -- priority lane ----------------------------------------------------
+
+    /// Move everything waiting on the priority lane into `urgent`.
+    fn fill_urgent(&self) {
+        let mut urgent = self.urgent.borrow_mut();
+        while let Ok((priority, msg)) = self.priority.try_recv() {
+            urgent.entry(Reverse(priority)).or_default().push_back(msg);
+        }
+    }
+
+    fn pop_urgent(&self) -> Option<T> {
+        self.take_urgent_matching(|_| true)
+    }
+
+    /// Remove the first priority message matching `predicate`, scanning from
+    /// the highest priority down.
+    fn take_urgent_matching<F>(&self, predicate: F) -> Option<T>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.fill_urgent();
+        let mut urgent = self.urgent.borrow_mut();
+        let (&level, index) = urgent
+            .iter()
+            .find_map(|(level, queue)| Some((level, queue.iter().position(&predicate)?)))?;
+        let queue = urgent.get_mut(&level)?;
+        let msg = queue.remove(index);
+        if queue.is_empty() {
+            urgent.remove(&level);
+        }
+        msg
+    }
+
+    /// Block until a message arrives on either lane. Priority messages are
+    /// moved into `urgent`; a default-priority message is handed back.
+    fn wait(&self, deadline: Option<Instant>) -> Arrival<T> {
+        let mut select = cb::Select::new();
+        let priority_index = select.recv(&self.priority);
+        select.recv(&self.inner);
+        let operation = match deadline {
+            Some(deadline) => match select.select_deadline(deadline) {
+                Ok(operation) => operation,
+                Err(_) => return Arrival::TimedOut,
+            },
+            None => select.select(),
+        };
+        if operation.index() == priority_index {
+            match operation.recv(&self.priority) {
+                Ok((priority, msg)) => {
+                    self.urgent
+                        .borrow_mut()
+                        .entry(Reverse(priority))
+                        .or_default()
+                        .push_back(msg);
+                    Arrival::Message
+                }
+                Err(_) => Arrival::Closed,
+            }
+        } else {
+            match operation.recv(&self.inner) {
+                Ok(msg) => {
+                    // A priority message sent meanwhile still goes first.
+                    self.fill_urgent();
+                    if self.urgent.borrow().is_empty() {
+                        Arrival::Normal(msg)
+                    } else {
+                        self.save_queue.borrow_mut().push_back(msg);
+                        Arrival::Message
+                    }
+                }
+                Err(_) => Arrival::Closed,
+            }
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -952,4 +1072,73 @@ mod tests {
         let result = mb.recv_timeout(Duration::from_millis(10));
         assert_eq!(result, Some(5));
     }
+
+    // =====================================================================
+    // 34. Priority message overtakes a flood of normal ones
+    // =====================================================================
+    #[test]
+    fn priority_message_overtakes_flood() {
+        let (tx, mb) = Mailbox::<i32>::unbounded();
+        for i in 0..1000 {
+            tx.send(i).unwrap();
+        }
+        tx.send_priority(-1, 10).unwrap();
+
+        assert_eq!(mb.len(), 1001);
+        assert_eq!(mb.recv(), Some(-1));
+        assert_eq!(mb.recv(), Some(0));
+        assert_eq!(mb.recv(), Some(1));
+    }
+
+    // =====================================================================
+    // 35. Higher priority first, FIFO within a level
+    // =====================================================================
+    #[test]
+    fn priority_levels_order_and_fifo_within_level() {
+        let (tx, mb) = Mailbox::<&str>::unbounded();
+        tx.send("normal").unwrap();
+        tx.send_priority("low-a", 1).unwrap();
+        tx.send_priority("high-a", 5).unwrap();
+        tx.send_priority("low-b", 1).unwrap();
+        tx.send_priority("high-b", 5).unwrap();
+        tx.send_priority("default", DEFAULT_PRIORITY).unwrap();
+
+        assert_eq!(
+            mb.drain(),
+            vec!["high-a", "high-b", "low-a", "low-b", "normal", "default"]
+        );
+    }
+
+    // =====================================================================
+    // 36. Blocking receive wakes for a priority message
+    // =====================================================================
+    #[test]
+    fn recv_blocking_wakes_for_priority_message() {
+        let (tx, mb) = Mailbox::<i32>::bounded(1);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx.send_priority(7, 3).unwrap();
+        });
+        assert_eq!(mb.recv_blocking().unwrap(), 7);
+        handle.join().unwrap();
+        assert_eq!(mb.recv_blocking(), Err(MailboxRecvError));
+    }
+
+    // =====================================================================
+    // 37. Selective receive leaves non-matching priority messages queued
+    // =====================================================================
+    #[test]
+    fn selective_receive_skips_priority_messages() {
+        let (tx, mb) = Mailbox::<i32>::unbounded();
+        tx.send(1).unwrap();
+        tx.send_priority(100, 9).unwrap();
+
+        assert_eq!(mb.recv_selective(|m| *m == 1), Some(1));
+        assert_eq!(
+            mb.recv_selective_timeout(|m| *m == 2, Duration::from_millis(5)),
+            None
+        );
+        assert_eq!(mb.recv(), Some(100));
+        assert!(mb.is_empty());
+    }
 }
@@ -0,0 +1,140 @@
+//! Scripted loopback HTTP server for provider tests.
+//!
+//! [`MockHttpServer::start`] binds an ephemeral `127.0.0.1` port and answers
+//! one connection per [`Reply`], in order, recording each request it reads.
+//! Every reply closes its connection, so each request (including retries and
+//! redirect hops) is a fresh accept. Enabled by the `test-support` feature.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// What the server does with one connection.
+#[derive(Debug, Clone)]
+pub enum Reply {
+    /// Write these bytes verbatim, then close.
+    Raw(Vec<u8>),
+    /// Write each chunk verbatim, pausing `gap` before every chunk after
+    /// the first, then close.
+    Paced { chunks: Vec<Vec<u8>>, gap: Duration },
+    /// Respond `200 OK` with this content type, echoing the request body.
+    Echo(String),
+    /// Close the connection without responding.
+    Drop,
+}
+
+impl Reply {
+    /// A complete response. `status` is the status line tail (`"200 OK"`);
+    /// `Content-Length` and `Connection: close` are added.
+    pub fn new(status: &str, headers: &[(&str, &str)], body: impl Into<Vec<u8>>) -> Self {
+        Reply::Raw(response(status, headers, body.into()))
+    }
+}
+
+/// A request as the server read it.
+#[derive(Debug, Clone)]
+pub struct CapturedRequest {
+    /// Request line and headers, CRLF-terminated, without the blank line.
+    pub head: String,
+    pub body: Vec<u8>,
+}
+
+/// A loopback server working through a script of [`Reply`]s.
+pub struct MockHttpServer {
+    addr: SocketAddr,
+    requests: Receiver<CapturedRequest>,
+}
+
+impl MockHttpServer {
+    /// Serve one connection per reply on a background thread.
+    pub fn start(replies: Vec<Reply>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind loopback port");
+        let addr = listener.local_addr().expect("loopback address");
+        let (tx, requests) = mpsc::channel();
+        std::thread::spawn(move || {
+            for reply in replies {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let Some(request) = read_request(&stream) else {
+                    continue;
+                };
+                let echoed = request.body.clone();
+                // The request is recorded before replying, so it is visible
+                // as soon as the client sees the response.
+                let _ = tx.send(request);
+                let bytes = match reply {
+                    Reply::Raw(bytes) => bytes,
+                    Reply::Echo(content_type) => {
+                        response("200 OK", &[("Content-Type", &content_type)], echoed)
+                    }
+                    Reply::Paced { chunks, gap } => {
+                        for (i, chunk) in chunks.iter().enumerate() {
+                            if i > 0 {
+                                std::thread::sleep(gap);
+                            }
+                            if stream
+                                .write_all(chunk)
+                                .and_then(|_| stream.flush())
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                        continue;
+                    }
+                    Reply::Drop => continue,
+                };
+                let _ = stream.write_all(&bytes);
+            }
+        });
+        MockHttpServer { addr, requests }
+    }
+
+    /// Absolute URL for `path` on this server, e.g. `url("/upload")`.
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// Requests read since the last call, oldest first.
+    pub fn requests(&self) -> Vec<CapturedRequest> {
+        self.requests.try_iter().collect()
+    }
+}
+
+fn response(status: &str, headers: &[(&str, &str)], body: Vec<u8>) -> Vec<u8> {
+    let mut head = format!("HTTP/1.1 {}\r\n", status);
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+    let mut bytes = head.into_bytes();
+    bytes.extend(body);
+    bytes
+}
+
+fn read_request(stream: &std::net::TcpStream) -> Option<CapturedRequest> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut head = String::new();
+    let mut content_length = 0usize;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        if line == "\r\n" || line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+        head.push_str(&line);
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(CapturedRequest { head, body })
+}
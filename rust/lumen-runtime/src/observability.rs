@@ -0,0 +1,471 @@
+//! Live runtime observability endpoint.
+//!
+//! Enabled with the `observability` feature. An [`ObservabilityServer`]
+//! serves a JSON [`RuntimeSnapshot`] over plain HTTP so production
+//! deployments can inspect a running runtime without attaching a debugger:
+//!
+//! - `GET /state` (or `/`) — full snapshot: processes, scheduler queue
+//!   depths, GC stats, and metrics.
+//! - `GET /health` — liveness. `{"status":"ok"}`, or the [`HealthReport`]
+//!   of a source registered with [`ObservabilityServer::with_health`]
+//!   (typically [`Supervisor::health`](crate::supervisor::Supervisor::health)),
+//!   answered with `503` when the aggregate status is `unhealthy`.
+//! - `GET /ready` — readiness. `{"ready":true}`, or the report's readiness,
+//!   answered with `503` when not ready.
+//!
+//! The runtime does not own the VM heap, so GC statistics and metrics are
+//! pulled from host-provided sources registered with
+//! [`ObservabilityServer::with_gc_stats`] and
+//! [`ObservabilityServer::with_metrics`]. Without a source, GC stats report
+//! zeroes and metrics are empty.
+
+use crate::scheduler::Scheduler;
+use crate::supervisor::{AggregateHealth, HealthReport};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How long the accept loop sleeps between polls of the shutdown flag.
+const ACCEPT_POLL: Duration = Duration::from_millis(10);
+
+// ---------------------------------------------------------------------------
+// Snapshot types
+// ---------------------------------------------------------------------------
+
+/// State of a single process at snapshot time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub id: u64,
+    pub name: Option<String>,
+    /// Lifecycle state (`Ready`, `Running`, ...), or `Unknown` if the
+    /// process's status lock was poisoned.
+    pub status: String,
+    pub priority: u8,
+    pub mailbox_len: usize,
+    pub uptime_ms: u128,
+}
+
+/// Scheduler queue depths and throughput counters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchedulerStats {
+    pub workers: usize,
+    pub global_queue_depth: usize,
+    pub worker_queue_depths: Vec<usize>,
+    pub completed_tasks: usize,
+    pub shutdown: bool,
+}
+
+/// Garbage collector statistics reported by the host VM.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GcStats {
+    pub collections: u64,
+    pub heap_bytes: u64,
+    pub bytes_allocated: u64,
+    pub bytes_freed: u64,
+    pub last_pause_us: u64,
+}
+
+/// Everything served by the `/state` endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeSnapshot {
+    pub processes: Vec<ProcessInfo>,
+    pub scheduler: SchedulerStats,
+    pub gc: GcStats,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+type GcStatsSource = Box<dyn Fn() -> GcStats + Send + Sync>;
+type MetricsSource = Box<dyn Fn() -> BTreeMap<String, f64> + Send + Sync>;
+type HealthSource = Box<dyn Fn() -> HealthReport + Send + Sync>;
+
+// ---------------------------------------------------------------------------
+// ObservabilityServer
+// ---------------------------------------------------------------------------
+
+/// Collects [`RuntimeSnapshot`]s from a scheduler and serves them over HTTP.
+pub struct ObservabilityServer {
+    scheduler: Arc<Scheduler>,
+    gc_stats: Option<GcStatsSource>,
+    metrics: Option<MetricsSource>,
+    health: Option<HealthSource>,
+}
+
+impl ObservabilityServer {
+    /// Create a server observing `scheduler`.
+    pub fn new(scheduler: Arc<Scheduler>) -> Self {
+        Self {
+            scheduler,
+            gc_stats: None,
+            metrics: None,
+            health: None,
+        }
+    }
+
+    /// Register the source polled for GC statistics on every snapshot.
+    pub fn with_gc_stats<F>(mut self, source: F) -> Self
+    where
+        F: Fn() -> GcStats + Send + Sync + 'static,
+    {
+        self.gc_stats = Some(Box::new(source));
+        self
+    }
+
+    /// Register the source polled for named metrics on every snapshot.
+    pub fn with_metrics<F>(mut self, source: F) -> Self
+    where
+        F: Fn() -> BTreeMap<String, f64> + Send + Sync + 'static,
+    {
+        self.metrics = Some(Box::new(source));
+        self
+    }
+
+    /// Register the source polled by `/health` and `/ready`, e.g. a shared
+    /// supervisor's [`health`](crate::supervisor::Supervisor::health).
+    pub fn with_health<F>(mut self, source: F) -> Self
+    where
+        F: Fn() -> HealthReport + Send + Sync + 'static,
+    {
+        self.health = Some(Box::new(source));
+        self
+    }
+
+    /// Current health report, or `None` without a registered source.
+    pub fn health(&self) -> Option<HealthReport> {
+        self.health.as_ref().map(|f| f())
+    }
+
+    /// Capture the current runtime state.
+    pub fn snapshot(&self) -> RuntimeSnapshot {
+        let processes = self
+            .scheduler
+            .processes()
+            .iter()
+            .map(|pcb| ProcessInfo {
+                id: pcb.id().as_u64(),
+                name: pcb.name().map(str::to_string),
+                status: pcb
+                    .status()
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|_| "Unknown".to_string()),
+                priority: pcb.priority(),
+                mailbox_len: pcb.mailbox_len().unwrap_or(0),
+                uptime_ms: pcb.created_at().elapsed().as_millis(),
+            })
+            .collect();
+
+        let scheduler = SchedulerStats {
+            workers: self.scheduler.worker_count(),
+            global_queue_depth: self.scheduler.global_queue_depth(),
+            worker_queue_depths: self.scheduler.worker_queue_depths(),
+            completed_tasks: self.scheduler.completed_count(),
+            shutdown: self.scheduler.is_shutdown(),
+        };
+
+        RuntimeSnapshot {
+            processes,
+            scheduler,
+            gc: self.gc_stats.as_ref().map(|f| f()).unwrap_or_default(),
+            metrics: self.metrics.as_ref().map(|f| f()).unwrap_or_default(),
+        }
+    }
+
+    /// Bind `addr` and serve requests on a background thread until the
+    /// returned handle is shut down or dropped.
+    pub fn serve<A: ToSocketAddrs>(self, addr: A) -> io::Result<ObservabilityHandle> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+
+        let join = thread::Builder::new()
+            .name("lumen-observability".to_string())
+            .spawn(move || {
+                while !stop_flag.load(Ordering::Acquire) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            // A misbehaving client must not take the endpoint down.
+                            let _ = self.handle_connection(stream);
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(ACCEPT_POLL);
+                        }
+                        Err(_) => thread::sleep(ACCEPT_POLL),
+                    }
+                }
+            })?;
+
+        Ok(ObservabilityHandle {
+            local_addr,
+            stop,
+            join: Some(join),
+        })
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Drain headers; the endpoint takes no request body.
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && header != "\r\n" && header != "\n" {
+            header.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or("");
+        let path = parts.next().unwrap_or("");
+        let path = path.split('?').next().unwrap_or(path);
+
+        let (status, body) = match (method, path) {
+            ("GET", "/" | "/state") => (
+                "200 OK",
+                serde_json::to_string(&self.snapshot()).map_err(io::Error::other)?,
+            ),
+            ("GET", "/health") => match self.health() {
+                Some(report) => (
+                    if report.status == AggregateHealth::Unhealthy {
+                        "503 Service Unavailable"
+                    } else {
+                        "200 OK"
+                    },
+                    serde_json::to_string(&report).map_err(io::Error::other)?,
+                ),
+                None => ("200 OK", r#"{"status":"ok"}"#.to_string()),
+            },
+            ("GET", "/ready") => {
+                let ready = self.health().is_none_or(|report| report.ready);
+                (
+                    if ready {
+                        "200 OK"
+                    } else {
+                        "503 Service Unavailable"
+                    },
+                    serde_json::json!({ "ready": ready }).to_string(),
+                )
+            }
+            ("GET", _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+            _ => (
+                "405 Method Not Allowed",
+                r#"{"error":"method not allowed"}"#.to_string(),
+            ),
+        };
+        write_response(stream, status, &body)
+    }
+}
+
+fn write_response(mut stream: TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// ---------------------------------------------------------------------------
+// ObservabilityHandle
+// ---------------------------------------------------------------------------
+
+/// Handle to a running observability endpoint. Dropping it stops the server.
+pub struct ObservabilityHandle {
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    join: Option<thread::JoinHandle<()>>,
+}
+
+impl ObservabilityHandle {
+    /// The address the endpoint is bound to (useful when binding port 0).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections and wait for the server thread to exit.
+    pub fn shutdown(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(join) = self.join.take() {
+            let _ = join.join();
+        }
+    }
+}
+
+impl Drop for ObservabilityHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::sync::mpsc;
+
+    fn get(addr: SocketAddr, path: &str) -> (String, serde_json::Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).unwrap();
+        let (head, body) = raw.split_once("\r\n\r\n").expect("malformed response");
+        let status = head.lines().next().unwrap().to_string();
+        (
+            status,
+            serde_json::from_str(body).expect("body is not JSON"),
+        )
+    }
+
+    #[test]
+    fn state_endpoint_lists_spawned_process() {
+        let scheduler = Arc::new(Scheduler::new(1));
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let pid = scheduler.spawn_process(3, Some("worker-a".into()), move || {
+            let _ = release_rx.recv_timeout(Duration::from_secs(5));
+        });
+
+        let handle = ObservabilityServer::new(Arc::clone(&scheduler))
+            .serve("127.0.0.1:0")
+            .unwrap();
+        let (status, json) = get(handle.local_addr(), "/state");
+        assert!(status.contains("200"), "status: {}", status);
+
+        let procs = json["processes"].as_array().unwrap();
+        let entry = procs
+            .iter()
+            .find(|p| p["id"] == pid.as_u64())
+            .expect("spawned process missing from snapshot");
+        assert_eq!(entry["name"], "worker-a");
+        assert_eq!(entry["priority"], 3);
+        assert!(["Ready", "Running"].contains(&entry["status"].as_str().unwrap()));
+        assert_eq!(json["scheduler"]["workers"], 1);
+        assert_eq!(
+            json["scheduler"]["worker_queue_depths"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        release_tx.send(()).unwrap();
+        handle.shutdown();
+    }
+
+    #[test]
+    fn state_endpoint_includes_gc_stats_and_metrics() {
+        let scheduler = Arc::new(Scheduler::new(1));
+        let handle = ObservabilityServer::new(scheduler)
+            .with_gc_stats(|| GcStats {
+                collections: 7,
+                heap_bytes: 4096,
+                bytes_allocated: 10_000,
+                bytes_freed: 5_904,
+                last_pause_us: 120,
+            })
+            .with_metrics(|| BTreeMap::from([("requests_total".to_string(), 42.0)]))
+            .serve("127.0.0.1:0")
+            .unwrap();
+
+        let (_, json) = get(handle.local_addr(), "/state");
+        let gc: GcStats = serde_json::from_value(json["gc"].clone()).unwrap();
+        assert_eq!(gc.collections, 7);
+        assert_eq!(gc.heap_bytes, 4096);
+        assert_eq!(json["metrics"]["requests_total"], 42.0);
+    }
+
+    #[test]
+    fn snapshot_defaults_gc_stats_without_source() {
+        let scheduler = Arc::new(Scheduler::new(1));
+        let snapshot = ObservabilityServer::new(scheduler).snapshot();
+        assert_eq!(snapshot.gc, GcStats::default());
+        assert!(snapshot.metrics.is_empty());
+        assert!(snapshot.processes.is_empty());
+    }
+
+    #[test]
+    fn unknown_path_is_404_and_health_is_ok() {
+        let scheduler = Arc::new(Scheduler::new(1));
+        let handle = ObservabilityServer::new(scheduler)
+            .serve("127.0.0.1:0")
+            .unwrap();
+        let (status, json) = get(handle.local_addr(), "/nope");
+        assert!(status.contains("404"));
+        assert_eq!(json["error"], "not found");
+        let (status, json) = get(handle.local_addr(), "/health");
+        assert!(status.contains("200"));
+        assert_eq!(json["status"], "ok");
+    }
+
+    #[test]
+    fn health_endpoints_serve_supervisor_report() {
+        use crate::supervisor::{
+            ChildSpec, HealthStatus, RestartPolicy, RestartStrategy, Supervisor,
+        };
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Mutex;
+
+        let ready = Arc::new(AtomicBool::new(false));
+        let alive = Arc::new(AtomicBool::new(true));
+        let probe = |flag: Arc<AtomicBool>| {
+            move || {
+                if flag.load(Ordering::Relaxed) {
+                    HealthStatus::Healthy
+                } else {
+                    HealthStatus::Unhealthy("probe failed".into())
+                }
+            }
+        };
+        let mut sup = Supervisor::new(RestartStrategy::OneForOne);
+        sup.add_child(
+            ChildSpec::new("api", RestartPolicy::Permanent, || || {})
+                .with_liveness(probe(Arc::clone(&alive)))
+                .with_readiness(probe(Arc::clone(&ready)))
+                .on_unhealthy(crate::supervisor::HealthAction::Report),
+        );
+        let _ = sup.start_all();
+        let sup = Arc::new(Mutex::new(sup));
+
+        let source = Arc::clone(&sup);
+        let handle = ObservabilityServer::new(Arc::new(Scheduler::new(1)))
+            .with_health(move || source.lock().unwrap().health())
+            .serve("127.0.0.1:0")
+            .unwrap();
+        let addr = handle.local_addr();
+
+        sup.lock().unwrap().check_health().unwrap();
+        let (status, json) = get(addr, "/health");
+        assert!(status.contains("200"), "status: {}", status);
+        assert_eq!(json["status"], "degraded");
+        let (status, json) = get(addr, "/ready");
+        assert!(status.contains("503"), "status: {}", status);
+        assert_eq!(json["ready"], false);
+
+        ready.store(true, Ordering::Relaxed);
+        sup.lock().unwrap().check_health().unwrap();
+        let (status, json) = get(addr, "/ready");
+        assert!(status.contains("200"));
+        assert_eq!(json["ready"], true);
+        assert_eq!(get(addr, "/health").1["status"], "ok");
+
+        alive.store(false, Ordering::Relaxed);
+        sup.lock().unwrap().check_health().unwrap();
+        let (status, json) = get(addr, "/health");
+        assert!(status.contains("503"), "status: {}", status);
+        assert_eq!(json["status"], "unhealthy");
+        assert_eq!(json["children"][0]["name"], "api");
+        assert_eq!(json["children"][0]["live"], false);
+        assert_eq!(json["children"][0]["message"], "probe failed");
+    }
+}
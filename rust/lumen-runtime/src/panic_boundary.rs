@@ -33,6 +33,7 @@
 //! assert_eq!(result.unwrap(), 42);
 //! ```
 
+use crate::log_limit::{self, LogLevel};
 use std::any::Any;
 use std::fmt;
 
@@ -128,8 +129,8 @@ pub fn catch_panic<T>(f: impl FnOnce() -> T + std::panic::UnwindSafe) -> Result<
 /// Execute `f` under the given [`PanicPolicy`].
 ///
 /// - [`PanicPolicy::CatchAndReturn`]: equivalent to [`catch_panic`].
-/// - [`PanicPolicy::LogAndContinue`]: catches the panic, prints the error
-///   to stderr, and returns `Err`.
+/// - [`PanicPolicy::LogAndContinue`]: catches the panic, logs the error to
+///   the global runtime log, and returns `Err`.
 /// - [`PanicPolicy::Abort`]: does **not** catch the panic; if `f` panics
 ///   the panic propagates normally.
 pub fn with_panic_boundary<T>(
@@ -141,7 +142,11 @@ pub fn with_panic_boundary<T>(
         PanicPolicy::LogAndContinue => match catch_panic(f) {
             Ok(v) => Ok(v),
             Err(e) => {
-                eprintln!("[lumen-runtime] caught panic: {}", e.message());
+                log_limit::log_to(
+                    &log_limit::global(),
+                    LogLevel::Error,
+                    &format!("caught panic: {}", e.message()),
+                );
                 Err(e)
             }
         },
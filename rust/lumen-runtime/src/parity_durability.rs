@@ -164,7 +164,7 @@ impl DurabilityParityChecklist {
                 description: "Compress checkpoint data with gzip to reduce storage footprint.".into(),
                 status: DurabParityStatus::Implemented,
                 comparable_to: "Durable Objects compressed state".into(),
-                lumen_approach: "CheckpointEngine::new_compressed with flate2 gzip compression/decompression.".into(),
+                lumen_approach: "CheckpointOptions selects gzip or zstd compression; sealed envelopes carry a BLAKE3 checksum verified on restore.".into(),
             },
             DurabilityParityItem {
                 id: "DUR-004".into(),
@@ -229,9 +229,9 @@ impl DurabilityParityChecklist {
                 category: DurabilityCategory::EventSourcing,
                 feature: "Event log compaction".into(),
                 description: "Compact the event log by collapsing old events into a snapshot plus recent events.".into(),
-                status: DurabParityStatus::Designed,
+                status: DurabParityStatus::Implemented,
                 comparable_to: "Kafka log compaction, event store snapshots".into(),
-                lumen_approach: "Combine checkpoint (snapshot) with DurableLog tail; not yet implemented.".into(),
+                lumen_approach: "DurableLog::compact folds entries before the latest checkpoint away, swapping the file atomically; optional size threshold.".into(),
             },
 
             // ---- Snapshotting ----
@@ -418,7 +418,7 @@ impl DurabilityParityChecklist {
                 description: "Selectively invalidate cached results to allow re-execution of specific operations.".into(),
                 status: DurabParityStatus::Implemented,
                 comparable_to: "Cache invalidation in idempotency layers".into(),
-                lumen_approach: "IdempotencyStore::invalidate removes a single key; clear() wipes all.".into(),
+                lumen_approach: "IdempotencyStore::invalidate removes a single key; clear() wipes all; keys can carry a TTL and sweep_expired() drops expired ones.".into(),
             },
 
             // ---- Durable timers ----
@@ -97,10 +97,10 @@ impl fmt::Debug for Task {
 struct WorkerHandle {
     /// A stealer handle that other workers can use.
     ///
-    /// Retained here so the scheduler can expose per-worker diagnostics in
-    /// the future (e.g. queue depth). The actual work-stealing uses the
-    /// `Arc<Vec<Stealer>>` shared across all threads.
-    _stealer: Stealer<Task>,
+    /// Retained here so the scheduler can expose per-worker diagnostics
+    /// (see [`Scheduler::worker_queue_depths`]). The actual work-stealing
+    /// uses the `Arc<Vec<Stealer>>` shared across all threads.
+    stealer: Stealer<Task>,
     /// The join handle for the OS thread.
     join_handle: Option<thread::JoinHandle<()>>,
 }
@@ -173,7 +173,7 @@ impl Scheduler {
                 .expect("failed to spawn worker thread");
 
             handles.push(WorkerHandle {
-                _stealer: stealers[idx].clone(),
+                stealer: stealers[idx].clone(),
                 join_handle: Some(jh),
             });
         }
@@ -258,6 +258,27 @@ impl Scheduler {
             .unwrap_or(0)
     }
 
+    /// Return a snapshot of every registered process control block, ordered
+    /// by [`ProcessId`].
+    pub fn processes(&self) -> Vec<Arc<ProcessControlBlock>> {
+        let mut procs: Vec<_> = lock_inner(&self.process_registry)
+            .map(|guard| guard.values().cloned().collect())
+            .unwrap_or_default();
+        procs.sort_by_key(|pcb| pcb.id());
+        procs
+    }
+
+    /// Return the number of tasks waiting in the global injection queue.
+    pub fn global_queue_depth(&self) -> usize {
+        self.global_queue.len()
+    }
+
+    /// Return the number of tasks waiting in each worker's local deque,
+    /// indexed by worker.
+    pub fn worker_queue_depths(&self) -> Vec<usize> {
+        self.workers.iter().map(|w| w.stealer.len()).collect()
+    }
+
     /// Block until at least `expected` tasks have completed, or `timeout`
     /// elapses.
     ///
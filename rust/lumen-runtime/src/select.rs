@@ -8,7 +8,9 @@
 //!
 //! When multiple channels are ready at the same time, the winner is chosen
 //! **randomly** (not always the first-registered channel). This is guaranteed
-//! by the underlying [`crossbeam_channel::Select`] primitive.
+//! by the underlying [`crossbeam_channel::Select`] primitive. A closed
+//! channel is dropped from the selection rather than reported as ready, so it
+//! cannot crowd out channels that still deliver messages.
 //!
 //! # Example
 //!
@@ -33,7 +35,7 @@
 
 use crate::channel::Receiver;
 use crossbeam_channel::{self as cb};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Type alias for the boxed handler closures stored inside [`Selector`].
 type HandlerFn<'a> = Box<dyn FnOnce() -> Option<SelectResult> + 'a>;
@@ -51,6 +53,9 @@ pub enum SelectResult {
     Timeout,
     /// The default (non-blocking) case executed.
     Default,
+    /// [`Selector::try_select`] found no channel ready and had no default
+    /// case to run.
+    WouldBlock,
     /// Every registered channel is closed (disconnected).
     Closed,
 }
@@ -64,6 +69,9 @@ pub enum SelectResult {
 /// Construct with [`Selector::new`], register channels with [`recv`](Selector::recv),
 /// optionally set a [`timeout`](Selector::timeout) or [`default_case`](Selector::default_case),
 /// then call [`select`](Selector::select) to block until one fires.
+/// [`select_timeout`](Selector::select_timeout) and
+/// [`try_select`](Selector::try_select) bound or skip the wait without a
+/// default case.
 pub struct Selector<'a> {
     /// Crossbeam receivers (raw) kept alive for the duration of the select so
     /// we can register them with `cb::Select` which borrows them.
@@ -82,18 +90,19 @@ pub struct Selector<'a> {
 /// Internal helper trait to erase `T` from `Receiver<T>` so we can store
 /// heterogeneous receivers in the same vec.
 trait AsRawReceiver {
-    /// Register `self` with a `crossbeam_channel::Select` and return the op index.
-    fn register<'s, 'sel>(&'s self, sel: &mut cb::Select<'sel>) -> usize
+    /// Register `self` with a `crossbeam_channel::Select` and return the op
+    /// indices that mean this arm may be ready (a message, or a close).
+    fn register<'s, 'sel>(&'s self, sel: &mut cb::Select<'sel>) -> [usize; 2]
     where
         's: 'sel;
 }
 
 impl<T> AsRawReceiver for Receiver<T> {
-    fn register<'s, 'sel>(&'s self, sel: &mut cb::Select<'sel>) -> usize
+    fn register<'s, 'sel>(&'s self, sel: &mut cb::Select<'sel>) -> [usize; 2]
     where
         's: 'sel,
     {
-        sel.recv(&self.inner)
+        Receiver::register(self, sel)
     }
 }
 
@@ -124,7 +133,7 @@ impl<'a> Selector<'a> {
 
         // Capture a try_recv + handler closure that borrows `rx`.
         let try_handler: HandlerFn<'a> = Box::new(move || {
-            match rx.inner.try_recv() {
+            match rx.try_recv() {
                 Ok(val) => Some(handler(val)),
                 // Channel disconnected or was drained between `ready` and
                 // `try_recv` — treat as "this arm failed".
@@ -169,10 +178,37 @@ impl<'a> Selector<'a> {
     /// Panics if no channels have been registered and no default handler is
     /// set.
     pub fn select(self) -> SelectResult {
+        let wait = match self.timeout {
+            _ if self.default_handler.is_some() => Wait::Never,
+            Some(duration) => Wait::Until(Instant::now() + duration),
+            None => Wait::Forever,
+        };
+        self.run(wait)
+    }
+
+    /// Wait at most `duration` for a channel to become ready, returning
+    /// [`SelectResult::Timeout`] if none does. Overrides any earlier
+    /// [`timeout`](Self::timeout); a default case still makes this
+    /// non-blocking.
+    pub fn select_timeout(mut self, duration: Duration) -> SelectResult {
+        self.timeout = Some(duration);
+        self.select()
+    }
+
+    /// Take a message from a channel that is ready right now, without
+    /// blocking.
+    ///
+    /// If nothing is ready, the default case runs when one is set; otherwise
+    /// the result is [`SelectResult::WouldBlock`]. Any timeout is ignored.
+    pub fn try_select(self) -> SelectResult {
+        self.run(Wait::Never)
+    }
+
+    fn run(self, wait: Wait) -> SelectResult {
         let Selector {
             receivers,
             handlers,
-            timeout,
+            timeout: _,
             default_handler,
         } = self;
 
@@ -181,60 +217,29 @@ impl<'a> Selector<'a> {
             if let Some(dh) = default_handler {
                 return dh();
             }
+            if matches!(wait, Wait::Never) {
+                return SelectResult::WouldBlock;
+            }
             panic!("Selector::select called with no channels and no default handler");
         }
 
-        // Build a crossbeam Select and register every receiver.
-        let mut sel = cb::Select::new();
-        let mut indices: Vec<usize> = Vec::with_capacity(receivers.len());
-        for rx in &receivers {
-            let idx = rx.register(&mut sel);
-            indices.push(idx);
-        }
-
-        // Convert our parallel vecs into an indexable structure.
         // `handlers` is consumed element-by-element so we put it into an
         // `Option` vec.
         let mut handlers: Vec<Option<HandlerFn<'a>>> = handlers.into_iter().map(Some).collect();
 
         // ---- Attempt loop --------------------------------------------------
-        // `ready()` / `try_ready()` / `ready_timeout()` tell us which index
+        // `ready()` / `try_ready()` / `ready_deadline()` tell us which index
         // is ready but do NOT consume the message. We then call `try_recv()`
-        // via the handler closure. If `try_recv` fails (race), we remove
-        // that arm and retry.
+        // via the handler closure. If `try_recv` fails (the channel closed, or
+        // a race drained it), we remove that arm and retry with the rest.
+        // A closed channel is always "ready", so dropping it here is what
+        // keeps it from starving the open ones.
         //
         // Once all arms are removed we know every channel is closed.
-
-        // If a default handler is set, do exactly one non-blocking check.
-        if let Some(dh) = default_handler {
-            match sel.try_ready() {
-                Ok(ready_idx) => {
-                    // Find which of our arms this corresponds to.
-                    if let Some(pos) = indices.iter().position(|&i| i == ready_idx) {
-                        if let Some(h) = handlers[pos].take() {
-                            if let Some(result) = h() {
-                                return result;
-                            }
-                            // Race: channel drained between ready & try_recv.
-                            // Fall through to default.
-                        }
-                    }
-                    return dh();
-                }
-                Err(_) => return dh(),
-            }
-        }
-
-        // No default handler — blocking path.
-        loop {
-            // Check if all handlers have been consumed (all channels closed).
-            if handlers.iter().all(|h| h.is_none()) {
-                return SelectResult::Closed;
-            }
-
+        let outcome = loop {
             // Rebuild the Select with only live arms.
             let mut sel = cb::Select::new();
-            let mut live: Vec<(usize, usize)> = Vec::new(); // (handler_pos, cb_idx)
+            let mut live: Vec<(usize, [usize; 2])> = Vec::new(); // (handler_pos, cb_idxs)
             for (pos, rx) in receivers.iter().enumerate() {
                 if handlers[pos].is_some() {
                     let idx = rx.register(&mut sel);
@@ -243,37 +248,48 @@ impl<'a> Selector<'a> {
             }
 
             if live.is_empty() {
-                return SelectResult::Closed;
+                break SelectResult::Closed;
             }
 
-            let ready_result = if let Some(dur) = timeout {
-                sel.ready_timeout(dur)
-            } else {
-                Ok(sel.ready())
+            // When several arms are ready, crossbeam picks one at random.
+            let ready_result = match wait {
+                Wait::Never => sel.try_ready().map_err(|_| SelectResult::WouldBlock),
+                Wait::Until(deadline) => sel
+                    .ready_deadline(deadline)
+                    .map_err(|_| SelectResult::Timeout),
+                Wait::Forever => Ok(sel.ready()),
             };
 
             match ready_result {
-                Err(_) => return SelectResult::Timeout,
+                Err(result) => break result,
                 Ok(ready_idx) => {
                     // Map crossbeam index back to our handler position.
-                    if let Some(&(pos, _)) = live.iter().find(|(_, ci)| *ci == ready_idx) {
+                    if let Some(&(pos, _)) = live.iter().find(|(_, ci)| ci.contains(&ready_idx)) {
                         if let Some(h) = handlers[pos].take() {
                             if let Some(result) = h() {
                                 return result;
                             }
-                            // Handler returned None → channel closed mid-race.
-                            // Loop and retry with remaining arms.
-                            continue;
                         }
                     }
-                    // Unknown index — should not happen. Retry.
-                    continue;
                 }
             }
+        };
+
+        match (outcome, default_handler) {
+            (SelectResult::WouldBlock | SelectResult::Closed, Some(dh)) => dh(),
+            (outcome, _) => outcome,
         }
     }
 }
 
+/// How long [`Selector::run`] may block.
+#[derive(Debug, Clone, Copy)]
+enum Wait {
+    Never,
+    Until(Instant),
+    Forever,
+}
+
 impl<'a> Default for Selector<'a> {
     fn default() -> Self {
         Self::new()
@@ -622,4 +638,123 @@ mod tests {
             .select();
         assert_eq!(r3, SelectResult::Closed);
     }
+
+    // -- select_timeout / try_select --------------------------------------
+
+    #[test]
+    fn select_timeout_fires_when_all_channels_empty() {
+        let (_tx1, rx1) = channel::unbounded::<i32>();
+        let (_tx2, rx2) = channel::unbounded::<String>();
+
+        let start = Instant::now();
+        let result = Selector::new()
+            .recv(&rx1, |v| SelectResult::Matched(format!("int:{v}")))
+            .recv(&rx2, |v| SelectResult::Matched(format!("str:{v}")))
+            .select_timeout(Duration::from_millis(50));
+
+        assert_eq!(result, SelectResult::Timeout);
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn select_timeout_picks_channel_that_becomes_ready() {
+        let (_tx1, rx1) = channel::unbounded::<i32>();
+        let (tx2, rx2) = channel::unbounded::<i32>();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx2.send(3).unwrap();
+        });
+
+        let start = Instant::now();
+        let result = Selector::new()
+            .recv(&rx1, |v| SelectResult::Matched(format!("a:{v}")))
+            .recv(&rx2, |v| SelectResult::Matched(format!("b:{v}")))
+            .select_timeout(Duration::from_secs(10));
+
+        assert_eq!(result, SelectResult::Matched("b:3".into()));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn try_select_would_block_when_nothing_ready() {
+        let (_tx, rx) = channel::unbounded::<i32>();
+
+        let result = Selector::new()
+            .recv(&rx, |v| SelectResult::Matched(format!("{v}")))
+            .timeout(Duration::from_secs(10))
+            .try_select();
+
+        assert_eq!(result, SelectResult::WouldBlock);
+    }
+
+    #[test]
+    fn try_select_takes_ready_message() {
+        let (tx, rx) = channel::unbounded::<i32>();
+        tx.send(8).unwrap();
+
+        let result = Selector::new()
+            .recv(&rx, |v| SelectResult::Matched(format!("{v}")))
+            .try_select();
+
+        assert_eq!(result, SelectResult::Matched("8".into()));
+    }
+
+    #[test]
+    fn try_select_skips_closed_channel() {
+        // A closed channel is always "ready"; it must not hide the message
+        // waiting on the open one.
+        for _ in 0..50 {
+            let (tx1, rx1) = channel::unbounded::<i32>();
+            let (tx2, rx2) = channel::unbounded::<i32>();
+            drop(tx1);
+            tx2.send(1).unwrap();
+
+            let result = Selector::new()
+                .recv(&rx1, |v| SelectResult::Matched(format!("a:{v}")))
+                .recv(&rx2, |v| SelectResult::Matched(format!("b:{v}")))
+                .try_select();
+
+            assert_eq!(result, SelectResult::Matched("b:1".into()));
+        }
+    }
+
+    #[test]
+    fn select_wakes_when_channel_is_closed() {
+        let (tx, rx) = channel::unbounded::<i32>();
+
+        let closer = tx.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            closer.close();
+        });
+
+        let result = Selector::new()
+            .recv(&rx, |v| SelectResult::Matched(format!("{v}")))
+            .select_timeout(Duration::from_secs(10));
+
+        assert_eq!(result, SelectResult::Closed);
+    }
+
+    #[test]
+    fn try_select_hot_channel_does_not_starve_others() {
+        let (hot_tx, hot_rx) = channel::unbounded::<&str>();
+        let (cold_tx, cold_rx) = channel::unbounded::<&str>();
+        cold_tx.send("cold").unwrap();
+
+        let mut picked_cold = false;
+        for _ in 0..200 {
+            hot_tx.send("hot").unwrap();
+            let result = Selector::new()
+                .recv(&hot_rx, |v| SelectResult::Matched(v.to_string()))
+                .recv(&cold_rx, |v| SelectResult::Matched(v.to_string()))
+                .try_select();
+            if result == SelectResult::Matched("cold".into()) {
+                picked_cold = true;
+                break;
+            }
+        }
+
+        assert!(picked_cold, "cold channel never selected");
+    }
 }
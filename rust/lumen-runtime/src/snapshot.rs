@@ -6,6 +6,16 @@
 //!
 //! Values are stored as [`SerializedValue`], a fully-owned mirror of the VM's
 //! `Value` enum with no `Arc` or other shared-ownership wrappers.
+//!
+//! For persistence, [`Snapshot::seal`] wraps the serialized bytes in a small
+//! envelope recording the [`SnapshotCompression`] codec (none, gzip or zstd)
+//! and a BLAKE3 checksum
+//! of the stored payload.  [`Snapshot::unseal`] verifies the checksum before
+//! decompressing, so a corrupted snapshot is rejected with
+//! [`SnapshotError::Corrupted`] instead of being decoded.
+//!
+//! [`diff`] compares two snapshots and reports which registers and heap
+//! objects were created, changed, or freed between them.
 
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -134,6 +144,8 @@ pub enum SnapshotError {
     UnsupportedValue(String),
     #[error("version mismatch: snapshot v{found}, runtime v{expected}")]
     VersionMismatch { expected: u32, found: u32 },
+    #[error("snapshot corrupted: {0}")]
+    Corrupted(String),
 }
 
 // ---------------------------------------------------------------------------
@@ -221,6 +233,132 @@ impl Snapshot {
         let raw = decompress(bytes).map_err(|e| SnapshotError::Deserialize(e.to_string()))?;
         Self::deserialize(&raw)
     }
+
+    /// Serialize this snapshot into a checksummed envelope, compressing the
+    /// payload with the given codec.
+    pub fn seal(&self, compression: SnapshotCompression) -> Result<Vec<u8>, SnapshotError> {
+        let raw = self.serialize()?;
+        let payload = match compression {
+            SnapshotCompression::None => raw,
+            SnapshotCompression::Gzip { level } => compress_with_level(&raw, level)
+                .map_err(|e| SnapshotError::Serialize(e.to_string()))?,
+            SnapshotCompression::Zstd { level } => zstd::encode_all(raw.as_slice(), level)
+                .map_err(|e| SnapshotError::Serialize(e.to_string()))?,
+        };
+
+        let mut out = Vec::with_capacity(ENVELOPE_HEADER_LEN + payload.len());
+        out.extend_from_slice(&ENVELOPE_MAGIC);
+        out.push(ENVELOPE_VERSION);
+        out.push(compression.tag());
+        out.extend_from_slice(&crate::crypto::blake3_hash(&payload));
+        out.extend_from_slice(&payload);
+        Ok(out)
+    }
+
+    /// Verify and decode a snapshot envelope produced by [`Snapshot::seal`].
+    ///
+    /// The checksum is checked before the payload is decompressed or
+    /// deserialized.
+    pub fn unseal(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if !is_sealed(bytes) {
+            return Err(SnapshotError::Corrupted(
+                "missing snapshot envelope header".into(),
+            ));
+        }
+        if bytes.len() < ENVELOPE_HEADER_LEN {
+            return Err(SnapshotError::Corrupted(format!(
+                "truncated envelope ({} bytes, header needs {})",
+                bytes.len(),
+                ENVELOPE_HEADER_LEN
+            )));
+        }
+
+        let version = bytes[4];
+        if version != ENVELOPE_VERSION {
+            return Err(SnapshotError::Corrupted(format!(
+                "unknown envelope version {}",
+                version
+            )));
+        }
+        let codec_tag = bytes[5];
+        let expected: [u8; 32] = bytes[6..ENVELOPE_HEADER_LEN]
+            .try_into()
+            .expect("checksum slice is 32 bytes");
+        let payload = &bytes[ENVELOPE_HEADER_LEN..];
+
+        let actual = crate::crypto::blake3_hash(payload);
+        if actual != expected {
+            return Err(SnapshotError::Corrupted(format!(
+                "checksum mismatch (expected {}, found {})",
+                crate::crypto::hex_encode(&expected),
+                crate::crypto::hex_encode(&actual)
+            )));
+        }
+
+        match codec_tag {
+            0 => Self::deserialize(payload),
+            1 => Self::deserialize_compressed(payload),
+            2 => {
+                let raw = zstd::decode_all(payload)
+                    .map_err(|e| SnapshotError::Deserialize(e.to_string()))?;
+                Self::deserialize(&raw)
+            }
+            other => Err(SnapshotError::Corrupted(format!(
+                "unknown compression tag {}",
+                other
+            ))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sealed envelope
+// ---------------------------------------------------------------------------
+
+/// Compression codec applied to a sealed snapshot payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapshotCompression {
+    /// Store the bincode payload as-is.
+    #[default]
+    None,
+    /// Gzip the payload at the given level (0 = fastest, 9 = smallest).
+    Gzip { level: u32 },
+    /// Zstandard at the given level (1 = fastest, 22 = smallest; 0 picks
+    /// zstd's default).
+    Zstd { level: i32 },
+}
+
+impl SnapshotCompression {
+    /// Gzip at the default level (6).
+    pub fn gzip() -> Self {
+        SnapshotCompression::Gzip { level: 6 }
+    }
+
+    /// Zstandard at level 3, which compresses about as well as gzip's default
+    /// at several times the speed.
+    pub fn zstd() -> Self {
+        SnapshotCompression::Zstd { level: 3 }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            SnapshotCompression::None => 0,
+            SnapshotCompression::Gzip { .. } => 1,
+            SnapshotCompression::Zstd { .. } => 2,
+        }
+    }
+}
+
+/// Magic bytes identifying a sealed snapshot envelope.
+const ENVELOPE_MAGIC: [u8; 4] = *b"LSNP";
+/// Envelope layout version.
+const ENVELOPE_VERSION: u8 = 1;
+/// magic (4) + version (1) + codec (1) + BLAKE3 checksum (32).
+const ENVELOPE_HEADER_LEN: usize = 4 + 1 + 1 + 32;
+
+/// Whether `bytes` start with the sealed-envelope magic.
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ENVELOPE_MAGIC)
 }
 
 // ---------------------------------------------------------------------------
@@ -229,7 +367,13 @@ impl Snapshot {
 
 /// Compress data using gzip (flate2, compression level 6).
 pub fn compress(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
-    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    compress_with_level(data, flate2::Compression::default().level())
+}
+
+/// Compress data using gzip at an explicit level (clamped to 0–9).
+pub fn compress_with_level(data: &[u8], level: u32) -> Result<Vec<u8>, std::io::Error> {
+    let mut encoder =
+        flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level.min(9)));
     encoder.write_all(data)?;
     encoder.finish()
 }
@@ -242,6 +386,282 @@ pub fn decompress(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
     Ok(out)
 }
 
+// ---------------------------------------------------------------------------
+// Snapshot diff
+// ---------------------------------------------------------------------------
+
+/// How an entry differs between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Present only in the later snapshot.
+    Created,
+    /// Present in both snapshots with different contents.
+    Changed,
+    /// Present only in the earlier snapshot.
+    Freed,
+}
+
+impl ChangeKind {
+    fn from_presence(before: bool, after: bool) -> Self {
+        match (before, after) {
+            (false, _) => ChangeKind::Created,
+            (true, false) => ChangeKind::Freed,
+            (true, true) => ChangeKind::Changed,
+        }
+    }
+
+    fn marker(self) -> char {
+        match self {
+            ChangeKind::Created => '+',
+            ChangeKind::Changed => '~',
+            ChangeKind::Freed => '-',
+        }
+    }
+}
+
+/// A register (variable slot) that differs between two snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableChange {
+    /// Stack depth of the frame (0 is the outermost frame).
+    pub frame: usize,
+    /// Cell the frame belongs to.
+    pub cell_index: usize,
+    /// Register index within the frame.
+    pub register: usize,
+    /// Value in the earlier snapshot, if the register existed.
+    pub before: Option<SerializedValue>,
+    /// Value in the later snapshot, if the register exists.
+    pub after: Option<SerializedValue>,
+}
+
+impl VariableChange {
+    pub fn kind(&self) -> ChangeKind {
+        ChangeKind::from_presence(self.before.is_some(), self.after.is_some())
+    }
+}
+
+/// A heap object that differs between two snapshots, matched by object id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeapChange {
+    pub id: u64,
+    pub before: Option<HeapObject>,
+    pub after: Option<HeapObject>,
+}
+
+impl HeapChange {
+    pub fn kind(&self) -> ChangeKind {
+        ChangeKind::from_presence(self.before.is_some(), self.after.is_some())
+    }
+}
+
+/// Structured difference between two snapshots, produced by [`diff`].
+///
+/// The [`Display`](std::fmt::Display) impl renders one line per change,
+/// prefixed with `+` (created), `~` (changed) or `-` (freed).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    pub before: SnapshotId,
+    pub after: SnapshotId,
+    /// Instruction pointers before and after, if execution moved.
+    pub ip: Option<(InstructionPointer, InstructionPointer)>,
+    pub variables: Vec<VariableChange>,
+    pub heap: Vec<HeapChange>,
+}
+
+impl SnapshotDiff {
+    /// Whether the two snapshots hold identical state.
+    pub fn is_empty(&self) -> bool {
+        self.ip.is_none() && self.variables.is_empty() && self.heap.is_empty()
+    }
+}
+
+/// Compare two snapshots of the same process.
+///
+/// Frames are matched by stack depth; a frame whose cell differs at the same
+/// depth is treated as a different frame, so its registers are reported as
+/// freed and created rather than changed.  Heap objects are matched by id.
+pub fn diff(before: &Snapshot, after: &Snapshot) -> SnapshotDiff {
+    let mut variables = Vec::new();
+    let depth = before.frames.len().max(after.frames.len());
+    for frame in 0..depth {
+        let old = before.frames.get(frame);
+        let new = after.frames.get(frame);
+        match (old, new) {
+            (Some(old), Some(new)) if old.cell_index == new.cell_index => {
+                diff_registers(
+                    frame,
+                    old.cell_index,
+                    &old.registers,
+                    &new.registers,
+                    &mut variables,
+                );
+            }
+            _ => {
+                if let Some(old) = old {
+                    diff_registers(frame, old.cell_index, &old.registers, &[], &mut variables);
+                }
+                if let Some(new) = new {
+                    diff_registers(frame, new.cell_index, &[], &new.registers, &mut variables);
+                }
+            }
+        }
+    }
+
+    let old_heap: BTreeMap<u64, &HeapObject> =
+        before.heap.objects.iter().map(|o| (o.id, o)).collect();
+    let new_heap: BTreeMap<u64, &HeapObject> =
+        after.heap.objects.iter().map(|o| (o.id, o)).collect();
+    let mut ids: Vec<u64> = old_heap.keys().chain(new_heap.keys()).copied().collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let heap = ids
+        .into_iter()
+        .filter_map(|id| {
+            let old = old_heap.get(&id).copied();
+            let new = new_heap.get(&id).copied();
+            if old == new {
+                return None;
+            }
+            Some(HeapChange {
+                id,
+                before: old.cloned(),
+                after: new.cloned(),
+            })
+        })
+        .collect();
+
+    SnapshotDiff {
+        before: before.id,
+        after: after.id,
+        ip: (before.ip != after.ip).then_some((before.ip, after.ip)),
+        variables,
+        heap,
+    }
+}
+
+fn diff_registers(
+    frame: usize,
+    cell_index: usize,
+    old: &[SerializedValue],
+    new: &[SerializedValue],
+    out: &mut Vec<VariableChange>,
+) {
+    for register in 0..old.len().max(new.len()) {
+        let before = old.get(register);
+        let after = new.get(register);
+        if before != after {
+            out.push(VariableChange {
+                frame,
+                cell_index,
+                register,
+                before: before.cloned(),
+                after: after.cloned(),
+            });
+        }
+    }
+}
+
+impl std::fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "snapshot {} -> {}", self.before, self.after)?;
+        if self.is_empty() {
+            return write!(f, ": no changes");
+        }
+        if let Some((old, new)) = self.ip {
+            write!(
+                f,
+                "\n  ip: cell {} pc {} -> cell {} pc {}",
+                old.cell_index, old.pc, new.cell_index, new.pc
+            )?;
+        }
+        for change in &self.variables {
+            write!(
+                f,
+                "\n  {} frame {} (cell {}) r{}: ",
+                change.kind().marker(),
+                change.frame,
+                change.cell_index,
+                change.register
+            )?;
+            match (&change.before, &change.after) {
+                (Some(old), Some(new)) => {
+                    write!(f, "{} -> {}", render_value(old), render_value(new))?
+                }
+                (Some(value), None) | (None, Some(value)) => write!(f, "{}", render_value(value))?,
+                (None, None) => {}
+            }
+        }
+        for change in &self.heap {
+            let old = change.before.as_ref();
+            let new = change.after.as_ref();
+            let type_tag = new.or(old).map(|o| o.type_tag.as_str()).unwrap_or("");
+            write!(
+                f,
+                "\n  {} heap #{} {}: ",
+                change.kind().marker(),
+                change.id,
+                type_tag
+            )?;
+            match (old, new) {
+                (Some(old), Some(new)) => write!(
+                    f,
+                    "{} -> {}",
+                    render_heap_object(old),
+                    render_heap_object(new)
+                )?,
+                (Some(object), None) | (None, Some(object)) => {
+                    write!(f, "{}", render_heap_object(object))?
+                }
+                (None, None) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Render a heap object's payload, falling back to its size when the bytes
+/// are not a bincode-encoded [`SerializedValue`].
+fn render_heap_object(object: &HeapObject) -> String {
+    match bincode::deserialize::<SerializedValue>(&object.data) {
+        Ok(value) => render_value(&value),
+        Err(_) => format!("<{} bytes>", object.data.len()),
+    }
+}
+
+/// Compact, Lumen-like rendering of a serialized value.
+fn render_value(value: &SerializedValue) -> String {
+    fn join<'a>(items: impl Iterator<Item = &'a SerializedValue>) -> String {
+        items.map(render_value).collect::<Vec<_>>().join(", ")
+    }
+    fn join_fields(fields: &BTreeMap<String, SerializedValue>) -> String {
+        fields
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, render_value(v)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    match value {
+        SerializedValue::Null => "null".to_string(),
+        SerializedValue::Bool(b) => b.to_string(),
+        SerializedValue::Int(n) => n.to_string(),
+        SerializedValue::Float(x) => format!("{:?}", x),
+        SerializedValue::String(s) => format!("{:?}", s),
+        SerializedValue::Bytes(b) => format!("<{} bytes>", b.len()),
+        SerializedValue::List(items) => format!("[{}]", join(items.iter())),
+        SerializedValue::Tuple(items) => format!("({})", join(items.iter())),
+        SerializedValue::Set(items) => format!("set[{}]", join(items.iter())),
+        SerializedValue::Map(map) => format!("{{{}}}", join_fields(map)),
+        SerializedValue::Record { type_name, fields } => {
+            format!("{}({})", type_name, join_fields(fields))
+        }
+        SerializedValue::Union { tag, payload } => match payload.as_ref() {
+            SerializedValue::Null => tag.clone(),
+            other => format!("{}({})", tag, render_value(other)),
+        },
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Snapshot pruner
 // ---------------------------------------------------------------------------
@@ -668,4 +1088,232 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    // -- Sealed envelope ---------------------------------------------------
+
+    fn compressible_snapshot() -> Snapshot {
+        let frame = StackFrame {
+            cell_index: 0,
+            pc: 0,
+            registers: (0..512)
+                .map(|_| SerializedValue::String("the same string over and over".into()))
+                .collect(),
+            return_address: None,
+        };
+        Snapshot::new(
+            vec![frame],
+            HeapSnapshot { objects: vec![] },
+            InstructionPointer {
+                cell_index: 0,
+                pc: 0,
+            },
+            sample_metadata(),
+        )
+    }
+
+    #[test]
+    fn sealed_round_trip_uncompressed() {
+        let snap = sample_snapshot();
+        let bytes = snap.seal(SnapshotCompression::None).unwrap();
+        assert!(is_sealed(&bytes));
+        let restored = Snapshot::unseal(&bytes).unwrap();
+        assert_eq!(snap.id, restored.id);
+        assert_eq!(snap.frames[0].registers, restored.frames[0].registers);
+        assert_eq!(snap.heap, restored.heap);
+    }
+
+    #[test]
+    fn sealed_round_trip_gzip() {
+        let snap = compressible_snapshot();
+        for level in [0, 1, 6, 9] {
+            let bytes = snap.seal(SnapshotCompression::Gzip { level }).unwrap();
+            let restored = Snapshot::unseal(&bytes).unwrap();
+            assert_eq!(snap.id, restored.id);
+            assert_eq!(snap.frames[0].registers, restored.frames[0].registers);
+        }
+    }
+
+    #[test]
+    fn sealed_round_trip_zstd() {
+        let snap = compressible_snapshot();
+        for level in [0, 1, 3, 19] {
+            let bytes = snap.seal(SnapshotCompression::Zstd { level }).unwrap();
+            let restored = Snapshot::unseal(&bytes).unwrap();
+            assert_eq!(snap.serialize().unwrap(), restored.serialize().unwrap());
+        }
+        let plain = snap.seal(SnapshotCompression::None).unwrap();
+        let zst = snap.seal(SnapshotCompression::zstd()).unwrap();
+        assert!(
+            zst.len() * 4 < plain.len(),
+            "zstd ({}) should be much smaller than plain ({})",
+            zst.len(),
+            plain.len()
+        );
+    }
+
+    #[test]
+    fn sealed_gzip_shrinks_compressible_state() {
+        let snap = compressible_snapshot();
+        let plain = snap.seal(SnapshotCompression::None).unwrap();
+        let gz = snap.seal(SnapshotCompression::gzip()).unwrap();
+        assert!(
+            gz.len() * 4 < plain.len(),
+            "gzip ({}) should be much smaller than plain ({})",
+            gz.len(),
+            plain.len()
+        );
+    }
+
+    #[test]
+    fn sealed_rejects_corrupted_payload() {
+        let snap = compressible_snapshot();
+        for compression in [
+            SnapshotCompression::None,
+            SnapshotCompression::gzip(),
+            SnapshotCompression::zstd(),
+        ] {
+            let mut bytes = snap.seal(compression).unwrap();
+            let last = bytes.len() - 1;
+            bytes[last] ^= 0xFF;
+            match Snapshot::unseal(&bytes) {
+                Err(SnapshotError::Corrupted(msg)) => assert!(msg.contains("checksum")),
+                other => panic!("expected Corrupted, got {:?}", other.map(|s| s.id)),
+            }
+        }
+    }
+
+    #[test]
+    fn sealed_rejects_truncated_and_unsealed_input() {
+        let snap = sample_snapshot();
+        let bytes = snap.seal(SnapshotCompression::None).unwrap();
+        assert!(matches!(
+            Snapshot::unseal(&bytes[..10]),
+            Err(SnapshotError::Corrupted(_))
+        ));
+        let raw = snap.serialize().unwrap();
+        assert!(!is_sealed(&raw));
+        assert!(matches!(
+            Snapshot::unseal(&raw),
+            Err(SnapshotError::Corrupted(_))
+        ));
+    }
+
+    // =====================================================================
+    // Diff tests
+    // =====================================================================
+
+    fn frame(cell_index: usize, registers: Vec<SerializedValue>) -> StackFrame {
+        StackFrame {
+            cell_index,
+            pc: 0,
+            registers,
+            return_address: None,
+        }
+    }
+
+    fn heap_object(id: u64, value: &SerializedValue) -> HeapObject {
+        HeapObject {
+            id,
+            data: bincode::serialize(value).unwrap(),
+            type_tag: "List".into(),
+        }
+    }
+
+    #[test]
+    fn diff_reports_exactly_the_mutated_variable() {
+        let ip = InstructionPointer {
+            cell_index: 0,
+            pc: 3,
+        };
+        let registers = vec![
+            SerializedValue::Int(1),
+            SerializedValue::String("name".into()),
+            SerializedValue::Bool(true),
+        ];
+        let before = Snapshot::new(
+            vec![frame(0, registers.clone())],
+            HeapSnapshot { objects: vec![] },
+            ip,
+            sample_metadata(),
+        );
+        let mut mutated = registers;
+        mutated[1] = SerializedValue::String("renamed".into());
+        let after = Snapshot::new(
+            vec![frame(0, mutated)],
+            HeapSnapshot { objects: vec![] },
+            ip,
+            sample_metadata(),
+        );
+
+        let d = diff(&before, &after);
+        assert!(d.ip.is_none());
+        assert!(d.heap.is_empty());
+        assert_eq!(
+            d.variables,
+            vec![VariableChange {
+                frame: 0,
+                cell_index: 0,
+                register: 1,
+                before: Some(SerializedValue::String("name".into())),
+                after: Some(SerializedValue::String("renamed".into())),
+            }]
+        );
+        assert_eq!(d.variables[0].kind(), ChangeKind::Changed);
+        assert!(d
+            .to_string()
+            .contains("~ frame 0 (cell 0) r1: \"name\" -> \"renamed\""));
+    }
+
+    #[test]
+    fn diff_reports_created_and_freed_entries() {
+        let kept = heap_object(1, &SerializedValue::List(vec![SerializedValue::Int(1)]));
+        let freed = heap_object(2, &SerializedValue::Null);
+        let created = heap_object(3, &SerializedValue::List(vec![]));
+        let before = Snapshot::new(
+            vec![frame(0, vec![SerializedValue::Int(1)])],
+            HeapSnapshot {
+                objects: vec![kept.clone(), freed],
+            },
+            InstructionPointer {
+                cell_index: 0,
+                pc: 1,
+            },
+            sample_metadata(),
+        );
+        let after = Snapshot::new(
+            vec![
+                frame(0, vec![SerializedValue::Int(1)]),
+                frame(2, vec![SerializedValue::Float(0.5)]),
+            ],
+            HeapSnapshot {
+                objects: vec![kept, created],
+            },
+            InstructionPointer {
+                cell_index: 2,
+                pc: 0,
+            },
+            sample_metadata(),
+        );
+
+        let d = diff(&before, &after);
+        assert_eq!(d.variables.len(), 1);
+        assert_eq!(d.variables[0].kind(), ChangeKind::Created);
+        assert_eq!(d.variables[0].frame, 1);
+        let heap: Vec<(u64, ChangeKind)> = d.heap.iter().map(|c| (c.id, c.kind())).collect();
+        assert_eq!(heap, vec![(2, ChangeKind::Freed), (3, ChangeKind::Created)]);
+
+        let rendered = d.to_string();
+        assert!(rendered.contains("ip: cell 0 pc 1 -> cell 2 pc 0"));
+        assert!(rendered.contains("+ frame 1 (cell 2) r0: 0.5"));
+        assert!(rendered.contains("- heap #2 List: null"));
+        assert!(rendered.contains("+ heap #3 List: []"));
+    }
+
+    #[test]
+    fn diff_of_identical_snapshots_is_empty() {
+        let snap = sample_snapshot();
+        let d = diff(&snap, &snap);
+        assert!(d.is_empty());
+        assert!(d.to_string().ends_with("no changes"));
+    }
 }
@@ -13,8 +13,28 @@
 //! | RestForOne  | Restart the failed child and all children added after it. |
 //!
 //! Restart frequency is throttled: if more than `max_restarts` occur within
-//! `max_seconds`, the supervisor itself reports an error (the caller can
-//! decide whether to escalate).
+//! `max_seconds`, the supervisor applies its [`EscalationPolicy`]:
+//!
+//! | Policy         | When restart intensity is exhausted …                     |
+//! |----------------|-----------------------------------------------------------|
+//! | Escalate       | Stop every child, terminate, and report the failure upward. |
+//! | LogAndContinue | Log the failure, leave the failed child stopped, keep running. |
+//!
+//! A [`SupervisorSpec`] bundles the strategy, intensity limits and escalation
+//! policy into one serializable value, so supervision can be configured from
+//! a file rather than in code.
+//!
+//! Children may register liveness and readiness probes. The host polls them
+//! with [`Supervisor::check_health`]; a child failing its liveness probe is
+//! handled according to its [`HealthAction`] (by default it is restarted as
+//! if it had crashed). [`Supervisor::health`] aggregates the latest probe
+//! results into a [`HealthReport`] suitable for serving from the
+//! observability endpoint.
+//!
+//! In a supervision tree, a nested supervisor is an ordinary child of its
+//! parent: when it escalates, the caller reports its exit to the parent with
+//! `ExitReason::from(&error)`, and the parent applies its own strategy —
+//! typically restarting the nested supervisor, which resets it.
 //!
 //! # Current status
 //!
@@ -22,6 +42,8 @@
 //! integration with the scheduler (spawning OS-thread or green-thread work)
 //! will be wired up in a subsequent phase once the VM task model is finalised.
 
+use crate::log_limit::{self, LogLevel, SharedLog};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::time::Instant;
 
@@ -33,7 +55,8 @@ pub type RestartActions = Vec<(ChildId, Box<dyn FnOnce() + Send + 'static>)>;
 // ---------------------------------------------------------------------------
 
 /// Determines which children are restarted when one child fails.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RestartStrategy {
     /// Restart only the failed child.
     OneForOne,
@@ -58,6 +81,66 @@ pub enum RestartPolicy {
     Temporary,
 }
 
+// ---------------------------------------------------------------------------
+// Escalation policy
+// ---------------------------------------------------------------------------
+
+/// What a supervisor does once its restart intensity is exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalationPolicy {
+    /// Stop all children, terminate the supervisor, and return
+    /// [`SupervisorError::MaxRestartsExceeded`] so the parent can react.
+    #[default]
+    Escalate,
+    /// Log the failure and keep supervising the remaining children; the
+    /// child that exhausted the intensity stays stopped.
+    LogAndContinue,
+}
+
+// ---------------------------------------------------------------------------
+// Supervisor spec
+// ---------------------------------------------------------------------------
+
+/// Declarative supervisor configuration.
+///
+/// Omitted fields take their defaults, so `{"strategy": "rest_for_one"}` is
+/// a complete spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupervisorSpec {
+    /// Which siblings are restarted when a child fails.
+    pub strategy: RestartStrategy,
+    /// Maximum restarts allowed within `max_seconds`.
+    pub max_restarts: u32,
+    /// Length of the restart intensity window, in seconds.
+    pub max_seconds: u32,
+    /// What happens once the intensity is exceeded.
+    pub escalation: EscalationPolicy,
+}
+
+impl SupervisorSpec {
+    /// A spec with the given strategy and default limits.
+    pub fn new(strategy: RestartStrategy) -> Self {
+        Self {
+            strategy,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SupervisorSpec {
+    /// `OneForOne`, at most 3 restarts in 5 seconds, then escalate.
+    fn default() -> Self {
+        Self {
+            strategy: RestartStrategy::OneForOne,
+            max_restarts: 3,
+            max_seconds: 5,
+            escalation: EscalationPolicy::Escalate,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Exit reason
 // ---------------------------------------------------------------------------
@@ -90,6 +173,78 @@ impl fmt::Display for ExitReason {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Health probes
+// ---------------------------------------------------------------------------
+
+/// Result of a liveness or readiness probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy,
+    /// The probe failed; the message explains why.
+    Unhealthy(String),
+}
+
+impl HealthStatus {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+}
+
+/// What the supervisor does when a child fails its liveness probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HealthAction {
+    /// Treat the child as crashed and apply its restart policy.
+    #[default]
+    Restart,
+    /// Only report the failure through [`Supervisor::health`].
+    Report,
+}
+
+/// A liveness or readiness callback polled by the supervisor.
+type HealthProbe = Box<dyn Fn() -> HealthStatus + Send + 'static>;
+
+/// Latest probe results for one child; `None` until first polled.
+#[derive(Debug, Clone, Default)]
+struct ProbeResults {
+    live: Option<HealthStatus>,
+    ready: Option<HealthStatus>,
+}
+
+/// Aggregate health of a supervised group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregateHealth {
+    /// Every child that should be running is live and ready.
+    Ok,
+    /// Every such child is live, but some are not ready.
+    Degraded,
+    /// A child is failing its liveness probe or is not running, or the
+    /// supervisor has terminated.
+    Unhealthy,
+}
+
+/// Health of a single child as reported by [`Supervisor::health`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChildHealth {
+    pub name: String,
+    /// `Running` or `Stopped`.
+    pub state: String,
+    pub live: bool,
+    pub ready: bool,
+    /// Message from the most recent failing probe, if any.
+    pub message: Option<String>,
+}
+
+/// Health of a supervised group, served by the observability endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: AggregateHealth,
+    pub live: bool,
+    pub ready: bool,
+    pub children: Vec<ChildHealth>,
+}
+
 // ---------------------------------------------------------------------------
 // ChildSpec
 // ---------------------------------------------------------------------------
@@ -111,6 +266,12 @@ pub struct ChildSpec {
     start_fn: Box<dyn Fn() -> Box<dyn FnOnce() + Send + 'static> + Send + 'static>,
     /// Per-child restart policy.
     pub restart_policy: RestartPolicy,
+    /// Probe reporting whether the child is alive.
+    liveness: Option<HealthProbe>,
+    /// Probe reporting whether the child can accept work.
+    readiness: Option<HealthProbe>,
+    /// Action taken when the liveness probe fails.
+    pub on_unhealthy: HealthAction,
 }
 
 impl ChildSpec {
@@ -124,9 +285,37 @@ impl ChildSpec {
             name: name.into(),
             start_fn: Box::new(move || Box::new(start_fn())),
             restart_policy,
+            liveness: None,
+            readiness: None,
+            on_unhealthy: HealthAction::default(),
         }
     }
 
+    /// Register a liveness probe, polled by [`Supervisor::check_health`].
+    pub fn with_liveness<F>(mut self, probe: F) -> Self
+    where
+        F: Fn() -> HealthStatus + Send + 'static,
+    {
+        self.liveness = Some(Box::new(probe));
+        self
+    }
+
+    /// Register a readiness probe. A child with a readiness probe is not
+    /// ready until the probe has passed.
+    pub fn with_readiness<F>(mut self, probe: F) -> Self
+    where
+        F: Fn() -> HealthStatus + Send + 'static,
+    {
+        self.readiness = Some(Box::new(probe));
+        self
+    }
+
+    /// Set the action taken when the liveness probe fails.
+    pub fn on_unhealthy(mut self, action: HealthAction) -> Self {
+        self.on_unhealthy = action;
+        self
+    }
+
     /// Invoke the start factory to create a fresh work closure.
     fn make_work(&self) -> Box<dyn FnOnce() + Send + 'static> {
         (self.start_fn)()
@@ -138,6 +327,9 @@ impl fmt::Debug for ChildSpec {
         f.debug_struct("ChildSpec")
             .field("name", &self.name)
             .field("restart_policy", &self.restart_policy)
+            .field("liveness", &self.liveness.is_some())
+            .field("readiness", &self.readiness.is_some())
+            .field("on_unhealthy", &self.on_unhealthy)
             .finish()
     }
 }
@@ -168,6 +360,8 @@ pub struct Supervisor {
     children: Vec<ChildSpec>,
     /// Per-child runtime state (parallel to `children`).
     states: Vec<ChildState>,
+    /// Latest probe results (parallel to `children`).
+    probes: Vec<ProbeResults>,
     /// Maximum restarts allowed within the time window.
     max_restarts: u32,
     /// Time window (in seconds) for restart counting.
@@ -176,6 +370,16 @@ pub struct Supervisor {
     restart_timestamps: Vec<Instant>,
     /// Count of `start_all` calls and restarts performed.
     start_count: usize,
+    /// Behaviour when the restart throttle is exceeded.
+    escalation: EscalationPolicy,
+    /// Set once the supervisor has escalated; cleared by `start_all`.
+    terminated: bool,
+    /// Number of throttle breaches absorbed under `LogAndContinue`.
+    suppressed_escalations: usize,
+    /// Number of children restarted because of a failed liveness probe.
+    health_restarts: usize,
+    /// Where `LogAndContinue` reports the failures it absorbs.
+    log: SharedLog,
 }
 
 /// Errors that can occur during supervisor operations.
@@ -185,6 +389,8 @@ pub enum SupervisorError {
     MaxRestartsExceeded { restarts: u32, window_seconds: u32 },
     /// The specified child ID is out of bounds.
     InvalidChildId(ChildId),
+    /// The supervisor already escalated and must be restarted by its parent.
+    Terminated,
 }
 
 impl fmt::Display for SupervisorError {
@@ -203,25 +409,54 @@ impl fmt::Display for SupervisorError {
             SupervisorError::InvalidChildId(id) => {
                 write!(f, "invalid child id: {}", id)
             }
+            SupervisorError::Terminated => write!(f, "supervisor has terminated"),
         }
     }
 }
 
 impl std::error::Error for SupervisorError {}
 
+impl From<&SupervisorError> for ExitReason {
+    /// The exit reason a parent records for a nested supervisor that failed.
+    fn from(err: &SupervisorError) -> Self {
+        ExitReason::Error(err.to_string())
+    }
+}
+
 impl Supervisor {
     /// Create a new supervisor with the given strategy.
     ///
     /// Defaults: `max_restarts = 3`, `max_seconds = 5`.
     pub fn new(strategy: RestartStrategy) -> Self {
+        Self::from_spec(SupervisorSpec::new(strategy))
+    }
+
+    /// Create a supervisor configured by `spec`.
+    pub fn from_spec(spec: SupervisorSpec) -> Self {
         Self {
-            strategy,
+            strategy: spec.strategy,
             children: Vec::new(),
             states: Vec::new(),
-            max_restarts: 3,
-            max_seconds: 5,
+            probes: Vec::new(),
+            max_restarts: spec.max_restarts,
+            max_seconds: spec.max_seconds,
             restart_timestamps: Vec::new(),
             start_count: 0,
+            escalation: spec.escalation,
+            terminated: false,
+            suppressed_escalations: 0,
+            health_restarts: 0,
+            log: log_limit::global(),
+        }
+    }
+
+    /// The supervisor's configuration.
+    pub fn spec(&self) -> SupervisorSpec {
+        SupervisorSpec {
+            strategy: self.strategy,
+            max_restarts: self.max_restarts,
+            max_seconds: self.max_seconds,
+            escalation: self.escalation,
         }
     }
 
@@ -237,11 +472,24 @@ impl Supervisor {
         self
     }
 
+    /// Set the policy applied when the restart throttle is exceeded.
+    pub fn escalation(mut self, policy: EscalationPolicy) -> Self {
+        self.escalation = policy;
+        self
+    }
+
+    /// Report absorbed failures to `log` instead of the global runtime log.
+    pub fn log_to(mut self, log: SharedLog) -> Self {
+        self.log = log;
+        self
+    }
+
     /// Add a child specification. Returns the child's index (ID).
     pub fn add_child(&mut self, spec: ChildSpec) -> ChildId {
         let id = self.children.len();
         self.children.push(spec);
         self.states.push(ChildState::Stopped);
+        self.probes.push(ProbeResults::default());
         id
     }
 
@@ -265,6 +513,26 @@ impl Supervisor {
         self.start_count
     }
 
+    /// Return the escalation policy.
+    pub fn escalation_policy(&self) -> EscalationPolicy {
+        self.escalation
+    }
+
+    /// Whether the supervisor has escalated and stopped all its children.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Number of throttle breaches logged instead of escalated.
+    pub fn suppressed_escalations(&self) -> usize {
+        self.suppressed_escalations
+    }
+
+    /// Number of failed liveness probes that led to a restart.
+    pub fn health_restarts(&self) -> usize {
+        self.health_restarts
+    }
+
     // -- lifecycle --------------------------------------------------------
 
     /// Start all children in order.
@@ -272,11 +540,17 @@ impl Supervisor {
     /// Returns a `Vec` of the work closures produced by each child's start
     /// factory. The caller is responsible for actually executing them (e.g.
     /// by submitting to the scheduler).
+    ///
+    /// Starting a terminated supervisor resets it: the restart window is
+    /// cleared so the restarted subtree gets its full intensity back.
     pub fn start_all(&mut self) -> Vec<Box<dyn FnOnce() + Send + 'static>> {
+        self.terminated = false;
+        self.restart_timestamps.clear();
         let mut work = Vec::with_capacity(self.children.len());
         for (i, spec) in self.children.iter().enumerate() {
             work.push(spec.make_work());
             self.states[i] = ChildState::Running;
+            self.probes[i] = ProbeResults::default();
             self.start_count += 1;
         }
         work
@@ -285,8 +559,9 @@ impl Supervisor {
     /// Handle the exit of a child process.
     ///
     /// Returns `Ok(restarts)` with a list of `(ChildId, work_closure)` pairs
-    /// for children that should be restarted, or `Err` if the restart
-    /// frequency threshold has been exceeded.
+    /// for children that should be restarted. If the restart frequency
+    /// threshold has been exceeded, the [`EscalationPolicy`] decides between
+    /// terminating with `Err` and returning `Ok` with no restarts.
     pub fn handle_exit(
         &mut self,
         child_id: ChildId,
@@ -295,6 +570,9 @@ impl Supervisor {
         if child_id >= self.children.len() {
             return Err(SupervisorError::InvalidChildId(child_id));
         }
+        if self.terminated {
+            return Err(SupervisorError::Terminated);
+        }
 
         // Mark the child as stopped.
         self.states[child_id] = ChildState::Stopped;
@@ -311,7 +589,9 @@ impl Supervisor {
         }
 
         // Check restart frequency throttle.
-        self.record_restart()?;
+        if let Err(err) = self.record_restart() {
+            return self.escalate(child_id, err);
+        }
 
         // Determine which children to restart based on strategy.
         let restart_ids: Vec<ChildId> = match self.strategy {
@@ -330,6 +610,7 @@ impl Supervisor {
         for &id in &restart_ids {
             let work = self.children[id].make_work();
             self.states[id] = ChildState::Running;
+            self.probes[id] = ProbeResults::default();
             self.start_count += 1;
             restarts.push((id, work));
         }
@@ -337,6 +618,125 @@ impl Supervisor {
         Ok(restarts)
     }
 
+    // -- health -----------------------------------------------------------
+
+    /// Poll the liveness and readiness probes of every running child.
+    ///
+    /// A child failing its liveness probe with [`HealthAction::Restart`] is
+    /// handled as an abnormal exit, so its restart policy, the strategy and
+    /// the restart throttle all apply. Returns the resulting restarts; as
+    /// with [`handle_exit`](Self::handle_exit), the caller is responsible for
+    /// stopping the unhealthy instance and running the new work closures.
+    pub fn check_health(&mut self) -> Result<RestartActions, SupervisorError> {
+        if self.terminated {
+            return Err(SupervisorError::Terminated);
+        }
+        let mut restarts: RestartActions = Vec::new();
+        for id in 0..self.children.len() {
+            // Children restarted earlier in this pass start with fresh probes.
+            if self.states[id] != ChildState::Running || restarts.iter().any(|(r, _)| *r == id) {
+                continue;
+            }
+            let spec = &self.children[id];
+            let live = spec.liveness.as_ref().map(|probe| probe());
+            let ready = spec.readiness.as_ref().map(|probe| probe());
+            let action = spec.on_unhealthy;
+            self.probes[id] = ProbeResults {
+                live: live.clone(),
+                ready,
+            };
+
+            if let (Some(HealthStatus::Unhealthy(msg)), HealthAction::Restart) = (live, action) {
+                self.health_restarts += 1;
+                let reason = ExitReason::Error(format!("liveness check failed: {}", msg));
+                restarts.extend(self.handle_exit(id, reason)?);
+            }
+        }
+        Ok(restarts)
+    }
+
+    /// Aggregate the latest probe results into a [`HealthReport`].
+    ///
+    /// Children that have stopped count against the aggregate only if they
+    /// are expected to run (their policy is [`RestartPolicy::Permanent`]).
+    pub fn health(&self) -> HealthReport {
+        let mut live = !self.terminated;
+        let mut ready = !self.terminated;
+        let mut children = Vec::with_capacity(self.children.len());
+
+        for (id, spec) in self.children.iter().enumerate() {
+            let probes = &self.probes[id];
+            let running = self.states[id] == ChildState::Running;
+            let child_live = running && !matches!(probes.live, Some(HealthStatus::Unhealthy(_)));
+            let child_ready = child_live
+                && match (&spec.readiness, &probes.ready) {
+                    (None, _) => true,
+                    (Some(_), Some(status)) => status.is_healthy(),
+                    (Some(_), None) => false,
+                };
+            let message =
+                [&probes.live, &probes.ready]
+                    .into_iter()
+                    .find_map(|status| match status {
+                        Some(HealthStatus::Unhealthy(msg)) => Some(msg.clone()),
+                        _ => None,
+                    });
+
+            if running || spec.restart_policy == RestartPolicy::Permanent {
+                live &= child_live;
+                ready &= child_ready;
+            }
+            children.push(ChildHealth {
+                name: spec.name.clone(),
+                state: format!("{:?}", self.states[id]),
+                live: child_live,
+                ready: child_ready,
+                message,
+            });
+        }
+
+        let status = match (live, ready) {
+            (true, true) => AggregateHealth::Ok,
+            (true, false) => AggregateHealth::Degraded,
+            (false, _) => AggregateHealth::Unhealthy,
+        };
+        HealthReport {
+            status,
+            live,
+            ready,
+            children,
+        }
+    }
+
+    /// Apply the escalation policy after the throttle is exceeded.
+    fn escalate(
+        &mut self,
+        child_id: ChildId,
+        err: SupervisorError,
+    ) -> Result<RestartActions, SupervisorError> {
+        match self.escalation {
+            EscalationPolicy::Escalate => {
+                for state in &mut self.states {
+                    *state = ChildState::Stopped;
+                }
+                self.terminated = true;
+                Err(err)
+            }
+            EscalationPolicy::LogAndContinue => {
+                log_limit::log_to(
+                    &self.log,
+                    LogLevel::Error,
+                    &format!(
+                        "supervisor giving up on child '{}': {}",
+                        self.children[child_id].name, err
+                    ),
+                );
+                self.suppressed_escalations += 1;
+                Ok(Vec::new())
+            }
+        }
+    }
+
     // -- restart throttle -------------------------------------------------
 
     /// Record a restart event and check whether the throttle is exceeded.
@@ -370,6 +770,8 @@ impl fmt::Debug for Supervisor {
             .field("max_restarts", &self.max_restarts)
             .field("max_seconds", &self.max_seconds)
             .field("start_count", &self.start_count)
+            .field("escalation", &self.escalation)
+            .field("terminated", &self.terminated)
             .finish()
     }
 }
@@ -381,7 +783,7 @@ impl fmt::Debug for Supervisor {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     use std::sync::Arc;
 
     /// Helper: create a ChildSpec whose start_fn increments a counter.
@@ -614,6 +1016,64 @@ mod tests {
         }
     }
 
+    // -- Spec -------------------------------------------------------------
+
+    #[test]
+    fn spec_deserializes_with_defaults() {
+        let spec: SupervisorSpec =
+            serde_json::from_str(r#"{"strategy": "rest_for_one", "max_restarts": 5}"#).unwrap();
+        assert_eq!(
+            spec,
+            SupervisorSpec {
+                strategy: RestartStrategy::RestForOne,
+                max_restarts: 5,
+                max_seconds: 5,
+                escalation: EscalationPolicy::Escalate,
+            }
+        );
+        assert_eq!(Supervisor::from_spec(spec).spec(), spec);
+    }
+
+    #[test]
+    fn spec_limits_escalate_upward() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut sup = Supervisor::from_spec(SupervisorSpec {
+            strategy: RestartStrategy::OneForAll,
+            max_restarts: 1,
+            max_seconds: 60,
+            escalation: EscalationPolicy::Escalate,
+        });
+        for name in ["a", "b"] {
+            sup.add_child(counting_child(
+                name,
+                RestartPolicy::Permanent,
+                Arc::clone(&counter),
+            ));
+        }
+        let _ = sup.start_all();
+
+        let restarted = sup.handle_exit(1, ExitReason::Error("1".into())).unwrap();
+        assert_eq!(
+            restarted.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+
+        let err = sup
+            .handle_exit(1, ExitReason::Error("2".into()))
+            .err()
+            .expect("second crash exceeds the intensity");
+        assert_eq!(
+            err,
+            SupervisorError::MaxRestartsExceeded {
+                restarts: 1,
+                window_seconds: 60,
+            }
+        );
+        assert!(sup.is_terminated());
+        assert_eq!(sup.child_state(0), Some(ChildState::Stopped));
+        assert_eq!(ExitReason::from(&err), ExitReason::Error(err.to_string()));
+    }
+
     // -- Edge cases -------------------------------------------------------
 
     #[test]
@@ -669,4 +1129,341 @@ mod tests {
         assert!(dbg.contains("my-worker"));
         assert!(dbg.contains("Transient"));
     }
+
+    // -- Escalation -------------------------------------------------------
+
+    #[test]
+    fn escalate_stops_children_and_terminates() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut sup = Supervisor::new(RestartStrategy::OneForOne).max_restarts(1);
+        sup.add_child(counting_child(
+            "a",
+            RestartPolicy::Permanent,
+            counter.clone(),
+        ));
+        sup.add_child(counting_child("b", RestartPolicy::Permanent, counter));
+        let _ = sup.start_all();
+
+        assert!(sup.handle_exit(0, ExitReason::Error("1".into())).is_ok());
+        let err = sup
+            .handle_exit(0, ExitReason::Error("2".into()))
+            .err()
+            .unwrap();
+        assert!(matches!(err, SupervisorError::MaxRestartsExceeded { .. }));
+        assert!(sup.is_terminated());
+        assert_eq!(sup.child_state(1), Some(ChildState::Stopped));
+        assert!(matches!(
+            sup.handle_exit(1, ExitReason::Normal),
+            Err(SupervisorError::Terminated)
+        ));
+
+        // Restarting the supervisor resets it.
+        let _ = sup.start_all();
+        assert!(!sup.is_terminated());
+        assert!(sup.handle_exit(0, ExitReason::Error("3".into())).is_ok());
+    }
+
+    #[test]
+    fn log_and_continue_keeps_supervising() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut sup = Supervisor::new(RestartStrategy::OneForOne)
+            .max_restarts(1)
+            .escalation(EscalationPolicy::LogAndContinue);
+        sup.add_child(counting_child(
+            "a",
+            RestartPolicy::Permanent,
+            counter.clone(),
+        ));
+        sup.add_child(counting_child("b", RestartPolicy::Permanent, counter));
+        let _ = sup.start_all();
+
+        assert_eq!(
+            sup.handle_exit(0, ExitReason::Error("1".into()))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(sup
+            .handle_exit(0, ExitReason::Error("2".into()))
+            .unwrap()
+            .is_empty());
+        assert!(!sup.is_terminated());
+        assert_eq!(sup.suppressed_escalations(), 1);
+        assert_eq!(sup.child_state(0), Some(ChildState::Stopped));
+        assert_eq!(sup.child_state(1), Some(ChildState::Running));
+    }
+
+    #[test]
+    fn log_and_continue_failure_storm_is_rate_limited() {
+        use crate::log_limit::{LogLimiter, RuntimeLog};
+        use std::sync::Mutex;
+
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let sink = written.clone();
+        let log = RuntimeLog::new(
+            LogLimiter::default(),
+            Box::new(move |line| sink.lock().unwrap().push(line.message.clone())),
+        )
+        .shared();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut sup = Supervisor::new(RestartStrategy::OneForOne)
+            .max_restarts(0)
+            .escalation(EscalationPolicy::LogAndContinue)
+            .log_to(log.clone());
+        sup.add_child(counting_child(
+            "a",
+            RestartPolicy::Permanent,
+            counter.clone(),
+        ));
+        sup.add_child(counting_child("b", RestartPolicy::Permanent, counter));
+        let _ = sup.start_all();
+
+        for _ in 0..50 {
+            sup.handle_exit(0, ExitReason::Error("crash".into()))
+                .unwrap();
+        }
+        sup.handle_exit(1, ExitReason::Error("crash".into()))
+            .unwrap();
+        log.lock().unwrap().flush();
+
+        let giving_up = |child: &str| {
+            format!(
+                "supervisor giving up on child '{}': max restarts exceeded: 0 restarts in 5 seconds",
+                child
+            )
+        };
+        assert_eq!(
+            *written.lock().unwrap(),
+            vec![
+                giving_up("a"),
+                format!("last message repeated 49 more times: {}", giving_up("a")),
+                giving_up("b"),
+            ]
+        );
+        assert_eq!(sup.suppressed_escalations(), 51);
+    }
+
+    #[test]
+    fn two_level_tree_escalates_to_root() {
+        use std::sync::Mutex;
+
+        // Child supervisor owning a grandchild that always crashes.
+        let grandchild_starts = Arc::new(AtomicUsize::new(0));
+        let mut child_sup = Supervisor::new(RestartStrategy::OneForOne).max_restarts(2);
+        child_sup.add_child(counting_child(
+            "grandchild",
+            RestartPolicy::Permanent,
+            grandchild_starts.clone(),
+        ));
+        let child_sup = Arc::new(Mutex::new(child_sup));
+
+        // Root supervisor: OneForAll over the child supervisor and a sibling,
+        // so an escalation from the subtree restarts both.
+        let sibling_starts = Arc::new(AtomicUsize::new(0));
+        let mut root = Supervisor::new(RestartStrategy::OneForAll).max_restarts(1);
+        let nested = child_sup.clone();
+        let child_id = root.add_child(ChildSpec::new(
+            "child_sup",
+            RestartPolicy::Permanent,
+            move || {
+                let nested = nested.clone();
+                move || {
+                    for work in nested.lock().unwrap().start_all() {
+                        work();
+                    }
+                }
+            },
+        ));
+        root.add_child(counting_child(
+            "sibling",
+            RestartPolicy::Permanent,
+            sibling_starts.clone(),
+        ));
+        for work in root.start_all() {
+            work();
+        }
+        assert_eq!(grandchild_starts.load(Ordering::Relaxed), 1);
+
+        // The grandchild crashes until the child supervisor gives up.
+        let crash_subtree = || loop {
+            let mut sup = child_sup.lock().unwrap();
+            match sup.handle_exit(0, ExitReason::Error("crash".into())) {
+                Ok(restarts) => {
+                    drop(sup);
+                    for (_, work) in restarts {
+                        work();
+                    }
+                }
+                Err(err) => return err,
+            }
+        };
+        let err = crash_subtree();
+        assert!(matches!(err, SupervisorError::MaxRestartsExceeded { .. }));
+        assert!(child_sup.lock().unwrap().is_terminated());
+        assert_eq!(grandchild_starts.load(Ordering::Relaxed), 3);
+
+        // Root applies OneForAll: the subtree and its sibling restart.
+        let restarts = root.handle_exit(child_id, ExitReason::from(&err)).unwrap();
+        let ids: Vec<ChildId> = restarts.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![0, 1]);
+        for (_, work) in restarts {
+            work();
+        }
+        assert!(!child_sup.lock().unwrap().is_terminated());
+        assert_eq!(grandchild_starts.load(Ordering::Relaxed), 4);
+        assert_eq!(sibling_starts.load(Ordering::Relaxed), 2);
+
+        // A second escalation exhausts the root's own intensity.
+        let err = crash_subtree();
+        assert!(matches!(
+            root.handle_exit(child_id, ExitReason::from(&err)),
+            Err(SupervisorError::MaxRestartsExceeded { restarts: 1, .. })
+        ));
+        assert!(root.is_terminated());
+    }
+
+    // -- health probes ----------------------------------------------------
+
+    /// Helper: a probe whose result is toggled through `healthy`.
+    fn flag_probe(healthy: Arc<AtomicBool>, msg: &'static str) -> impl Fn() -> HealthStatus {
+        move || {
+            if healthy.load(Ordering::Relaxed) {
+                HealthStatus::Healthy
+            } else {
+                HealthStatus::Unhealthy(msg.to_string())
+            }
+        }
+    }
+
+    #[test]
+    fn failing_liveness_probe_restarts_child() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let alive = Arc::new(AtomicBool::new(true));
+        let mut sup = Supervisor::new(RestartStrategy::OneForOne);
+        sup.add_child(counting_child(
+            "steady",
+            RestartPolicy::Permanent,
+            Arc::clone(&counter),
+        ));
+        sup.add_child(
+            counting_child("flaky", RestartPolicy::Permanent, Arc::clone(&counter))
+                .with_liveness(flag_probe(Arc::clone(&alive), "deadlocked")),
+        );
+        let _ = sup.start_all();
+
+        assert!(sup.check_health().unwrap().is_empty());
+
+        alive.store(false, Ordering::Relaxed);
+        let restarts = sup.check_health().unwrap();
+        let ids: Vec<ChildId> = restarts.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![1]);
+        for (_, work) in restarts {
+            work();
+        }
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+        assert_eq!(sup.health_restarts(), 1);
+        assert_eq!(sup.child_state(1), Some(ChildState::Running));
+        // The restarted child has not been probed yet, so it counts as live.
+        assert_eq!(sup.health().status, AggregateHealth::Ok);
+    }
+
+    #[test]
+    fn report_action_leaves_unhealthy_child_running() {
+        let alive = Arc::new(AtomicBool::new(false));
+        let mut sup = Supervisor::new(RestartStrategy::OneForAll);
+        sup.add_child(
+            ChildSpec::new("svc", RestartPolicy::Permanent, || || {})
+                .with_liveness(flag_probe(Arc::clone(&alive), "stuck"))
+                .on_unhealthy(HealthAction::Report),
+        );
+        let _ = sup.start_all();
+
+        assert!(sup.check_health().unwrap().is_empty());
+        assert_eq!(sup.health_restarts(), 0);
+        assert_eq!(sup.child_state(0), Some(ChildState::Running));
+
+        let report = sup.health();
+        assert_eq!(report.status, AggregateHealth::Unhealthy);
+        assert!(!report.children[0].live);
+        assert_eq!(report.children[0].message.as_deref(), Some("stuck"));
+    }
+
+    #[test]
+    fn unhealthy_child_counts_toward_restart_intensity() {
+        let mut sup = Supervisor::new(RestartStrategy::OneForOne).max_restarts(1);
+        sup.add_child(
+            ChildSpec::new("svc", RestartPolicy::Permanent, || || {})
+                .with_liveness(|| HealthStatus::Unhealthy("down".into())),
+        );
+        let _ = sup.start_all();
+
+        assert_eq!(sup.check_health().unwrap().len(), 1);
+        assert!(matches!(
+            sup.check_health(),
+            Err(SupervisorError::MaxRestartsExceeded { .. })
+        ));
+        assert!(sup.is_terminated());
+        assert_eq!(sup.health().status, AggregateHealth::Unhealthy);
+        assert!(matches!(
+            sup.check_health(),
+            Err(SupervisorError::Terminated)
+        ));
+    }
+
+    #[test]
+    fn aggregate_health_reflects_child_probes() {
+        let db_ready = Arc::new(AtomicBool::new(false));
+        let api_alive = Arc::new(AtomicBool::new(true));
+        let mut sup = Supervisor::new(RestartStrategy::OneForOne);
+        sup.add_child(
+            ChildSpec::new("db", RestartPolicy::Permanent, || || {})
+                .with_readiness(flag_probe(Arc::clone(&db_ready), "warming cache")),
+        );
+        sup.add_child(
+            ChildSpec::new("api", RestartPolicy::Permanent, || || {})
+                .with_liveness(flag_probe(Arc::clone(&api_alive), "no heartbeat"))
+                .on_unhealthy(HealthAction::Report),
+        );
+        sup.add_child(ChildSpec::new("job", RestartPolicy::Temporary, || || {}));
+
+        // Nothing is running yet.
+        assert_eq!(sup.health().status, AggregateHealth::Unhealthy);
+
+        let _ = sup.start_all();
+        // A temporary child that finished does not affect the aggregate.
+        sup.handle_exit(2, ExitReason::Normal).unwrap();
+        // `db` has a readiness probe that has not passed yet.
+        let report = sup.health();
+        assert_eq!(report.status, AggregateHealth::Degraded);
+        assert!(report.live && !report.ready);
+
+        sup.check_health().unwrap();
+        let report = sup.health();
+        assert_eq!(report.status, AggregateHealth::Degraded);
+        assert_eq!(report.children[0].message.as_deref(), Some("warming cache"));
+
+        db_ready.store(true, Ordering::Relaxed);
+        sup.check_health().unwrap();
+        let report = sup.health();
+        assert_eq!(report.status, AggregateHealth::Ok);
+        assert_eq!(
+            report
+                .children
+                .iter()
+                .map(|c| (c.name.as_str(), c.state.as_str(), c.live, c.ready))
+                .collect::<Vec<_>>(),
+            vec![
+                ("db", "Running", true, true),
+                ("api", "Running", true, true),
+                ("job", "Stopped", false, false),
+            ]
+        );
+
+        api_alive.store(false, Ordering::Relaxed);
+        sup.check_health().unwrap();
+        let report = sup.health();
+        assert_eq!(report.status, AggregateHealth::Unhealthy);
+        assert!(!report.live);
+        assert_eq!(report.children[1].message.as_deref(), Some("no heartbeat"));
+    }
 }
@@ -0,0 +1,206 @@
+//! Export trace events to the Chrome Trace Event Format.
+//!
+//! The output loads in `chrome://tracing` and Perfetto. Cell and call
+//! entry/exit become `B`/`E` duration pairs, tool calls, schema checks and
+//! errors become instant (`i`) events, and the run boundaries become global
+//! instants. `vm_step` events are dropped: at one per instruction they drown
+//! out everything else.
+//!
+//! Trace events carry no OS identity, so the Lumen process id recorded in
+//! `details.process_id` is used as both `pid` and `tid`, giving each process
+//! its own track. Events without one land on track 1.
+
+use crate::trace::events::{TraceEvent, TraceEventKind};
+use serde_json::{json, Map, Value};
+
+/// Track used for events that don't name a process.
+const DEFAULT_TRACK: u64 = 1;
+
+/// Convert `events` into a Chrome trace document (`{"traceEvents": [...]}`).
+///
+/// Timestamps are microseconds since the first event.
+pub fn to_chrome_trace(events: &[TraceEvent]) -> Value {
+    let origin = events.first().map(|e| e.timestamp);
+    let trace_events: Vec<Value> = events
+        .iter()
+        .filter_map(|event| {
+            let ts = origin
+                .and_then(|o| (event.timestamp - o).num_microseconds())
+                .unwrap_or(0);
+            chrome_event(event, ts)
+        })
+        .collect();
+
+    json!({
+        "traceEvents": trace_events,
+        "displayTimeUnit": "ms",
+    })
+}
+
+fn chrome_event(event: &TraceEvent, ts: i64) -> Option<Value> {
+    let cell = event.cell.as_deref().unwrap_or("<unknown>");
+    let (name, ph, cat) = match event.kind {
+        TraceEventKind::CellStart => (cell.to_string(), "B", "cell"),
+        TraceEventKind::CellEnd => (cell.to_string(), "E", "cell"),
+        TraceEventKind::CallEnter => (cell.to_string(), "B", "call"),
+        TraceEventKind::CallExit => (cell.to_string(), "E", "call"),
+        TraceEventKind::ToolCall => (
+            event.tool_id.clone().unwrap_or_else(|| "tool".to_string()),
+            "i",
+            "effect",
+        ),
+        TraceEventKind::SchemaValidate => ("schema_validate".to_string(), "i", "schema"),
+        TraceEventKind::Error => ("error".to_string(), "i", "error"),
+        TraceEventKind::RunStart => ("run_start".to_string(), "i", "run"),
+        TraceEventKind::RunEnd => ("run_end".to_string(), "i", "run"),
+        TraceEventKind::VmStep => return None,
+    };
+
+    let track = event
+        .details
+        .as_ref()
+        .and_then(|d| d.get("process_id"))
+        .and_then(Value::as_u64)
+        .unwrap_or(DEFAULT_TRACK);
+
+    let mut out = Map::new();
+    out.insert("name".into(), json!(name));
+    out.insert("cat".into(), json!(cat));
+    out.insert("ph".into(), json!(ph));
+    out.insert("ts".into(), json!(ts));
+    out.insert("pid".into(), json!(track));
+    out.insert("tid".into(), json!(track));
+    if ph == "i" {
+        // Run boundaries span every track; other instants belong to one thread.
+        let scope = if cat == "run" { "g" } else { "t" };
+        out.insert("s".into(), json!(scope));
+    }
+
+    let args = event_args(event);
+    if !args.is_empty() {
+        out.insert("args".into(), Value::Object(args));
+    }
+    Some(Value::Object(out))
+}
+
+fn event_args(event: &TraceEvent) -> Map<String, Value> {
+    let mut args = Map::new();
+    args.insert("seq".into(), json!(event.seq));
+    if let Some(cell) = &event.cell {
+        args.insert("cell".into(), json!(cell));
+    }
+    if let Some(version) = &event.tool_version {
+        args.insert("tool_version".into(), json!(version));
+    }
+    if let Some(latency) = event.latency_ms {
+        args.insert("latency_ms".into(), json!(latency));
+    }
+    if let Some(cached) = event.cached {
+        args.insert("cached".into(), json!(cached));
+    }
+    if let Some(message) = &event.message {
+        args.insert("message".into(), json!(message));
+    }
+    if let Some(Value::Object(details)) = &event.details {
+        for (k, v) in details {
+            args.entry(k.clone()).or_insert_with(|| v.clone());
+        }
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn event(seq: u64, kind: TraceEventKind, cell: Option<&str>, offset_us: i64) -> TraceEvent {
+        TraceEvent {
+            seq,
+            kind,
+            prev_hash: String::new(),
+            hash: String::new(),
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+                + Duration::microseconds(offset_us),
+            doc_hash: "sha256:doc".to_string(),
+            cell: cell.map(str::to_string),
+            tool_id: None,
+            tool_version: None,
+            inputs_hash: None,
+            outputs_hash: None,
+            policy_hash: None,
+            latency_ms: None,
+            cached: None,
+            details: None,
+            message: None,
+        }
+    }
+
+    fn sample_run() -> Vec<TraceEvent> {
+        let mut tool = event(4, TraceEventKind::ToolCall, Some("fetch"), 300);
+        tool.tool_id = Some("http.get".to_string());
+        tool.latency_ms = Some(12);
+        tool.details = Some(json!({ "success": true, "process_id": 7 }));
+        vec![
+            event(1, TraceEventKind::RunStart, None, 0),
+            event(2, TraceEventKind::CellStart, Some("main"), 100),
+            event(3, TraceEventKind::VmStep, Some("main"), 150),
+            tool,
+            event(5, TraceEventKind::CellEnd, Some("main"), 900),
+            event(6, TraceEventKind::RunEnd, None, 1000),
+        ]
+    }
+
+    #[test]
+    fn exported_events_have_required_fields() {
+        let doc = to_chrome_trace(&sample_run());
+        let events = doc["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 5, "vm_step should be dropped");
+        for e in events {
+            for field in ["name", "ph", "ts", "pid", "tid"] {
+                assert!(e.get(field).is_some(), "missing '{}' in {}", field, e);
+            }
+        }
+        assert_eq!(events[0]["ts"], 0);
+        assert_eq!(events[1]["ts"], 100);
+    }
+
+    #[test]
+    fn cell_durations_are_begin_end_pairs() {
+        let doc = to_chrome_trace(&sample_run());
+        let main: Vec<&Value> = doc["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["name"] == "main")
+            .collect();
+        assert_eq!(main.len(), 2);
+        assert_eq!(main[0]["ph"], "B");
+        assert_eq!(main[1]["ph"], "E");
+        assert_eq!(main[0]["tid"], main[1]["tid"]);
+        assert!(main[0]["ts"].as_i64() < main[1]["ts"].as_i64());
+    }
+
+    #[test]
+    fn tool_calls_are_instants_on_their_process_track() {
+        let doc = to_chrome_trace(&sample_run());
+        let tool = doc["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["name"] == "http.get")
+            .unwrap()
+            .clone();
+        assert_eq!(tool["ph"], "i");
+        assert_eq!(tool["s"], "t");
+        assert_eq!(tool["pid"], 7);
+        assert_eq!(tool["tid"], 7);
+        assert_eq!(tool["args"]["latency_ms"], 12);
+    }
+
+    #[test]
+    fn empty_trace_exports_empty_event_list() {
+        let doc = to_chrome_trace(&[]);
+        assert_eq!(doc["traceEvents"], json!([]));
+    }
+}
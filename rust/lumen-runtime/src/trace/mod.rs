@@ -1,3 +1,5 @@
+pub mod chrome;
 pub mod events;
 pub mod hasher;
+pub mod sampling;
 pub mod store;
@@ -0,0 +1,219 @@
+//! Record-time sampling for trace events.
+//!
+//! A [`TraceSampler`] decides, before an event is built, whether the
+//! [`TraceStore`](crate::trace::store::TraceStore) records it, so skipped
+//! events cost a counter bump and a hash. Run boundaries and errors are
+//! always recorded. The decision depends only on the seed and the position of
+//! the event among sampled candidates, so the same run with the same seed
+//! keeps the same events. A call frame is decided once, when it is entered,
+//! so its opening and closing events are kept or dropped together.
+
+use crate::trace::events::TraceEventKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Which trace events to record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraceSampling {
+    /// Keep one in every `one_in` candidate events; `0` and `1` keep all.
+    pub one_in: u64,
+    /// When set, tool calls to effects outside this list are not recorded.
+    pub effects: Option<BTreeSet<String>>,
+    /// Seed for the sampling decision.
+    pub seed: u64,
+}
+
+impl Default for TraceSampling {
+    fn default() -> Self {
+        Self {
+            one_in: 1,
+            effects: None,
+            seed: 0,
+        }
+    }
+}
+
+impl TraceSampling {
+    /// Record every event.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Record one in every `n` events.
+    pub fn one_in(n: u64) -> Self {
+        Self {
+            one_in: n,
+            ..Self::default()
+        }
+    }
+
+    /// Only record tool calls to the listed effects.
+    pub fn with_effects<I, S>(mut self, effects: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.effects = Some(effects.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Seed the sampling decision.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Whether every event is recorded.
+    pub fn is_all(&self) -> bool {
+        self.one_in <= 1 && self.effects.is_none()
+    }
+}
+
+/// Applies a [`TraceSampling`] to a stream of events.
+#[derive(Debug, Clone, Default)]
+pub struct TraceSampler {
+    config: TraceSampling,
+    candidates: u64,
+    /// Decisions for the frames entered but not yet exited, innermost last.
+    frames: Vec<bool>,
+}
+
+impl TraceSampler {
+    pub fn new(config: TraceSampling) -> Self {
+        Self {
+            config,
+            candidates: 0,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &TraceSampling {
+        &self.config
+    }
+
+    /// Restart the decision sequence, so each run samples the same way.
+    pub fn reset(&mut self) {
+        self.candidates = 0;
+        self.frames.clear();
+    }
+
+    /// Whether to record the opening event (`CellStart`, `CallEnter`) of a
+    /// frame. The decision also covers the frame's closing event.
+    pub fn enter(&mut self, kind: &TraceEventKind) -> bool {
+        let keep = self.keep(kind, None);
+        self.frames.push(keep);
+        keep
+    }
+
+    /// Whether to record the closing event of the innermost frame, as decided
+    /// by [`enter`](Self::enter). Unmatched exits get a decision of their own.
+    pub fn exit(&mut self, kind: &TraceEventKind) -> bool {
+        match self.frames.pop() {
+            Some(keep) => keep,
+            None => self.keep(kind, None),
+        }
+    }
+
+    /// Whether to record an event of `kind`. `effect` is the tool id for tool
+    /// calls.
+    pub fn keep(&mut self, kind: &TraceEventKind, effect: Option<&str>) -> bool {
+        if matches!(
+            kind,
+            TraceEventKind::RunStart | TraceEventKind::RunEnd | TraceEventKind::Error
+        ) {
+            return true;
+        }
+        if let (Some(effects), Some(effect)) = (&self.config.effects, effect) {
+            if !effects.contains(effect) {
+                return false;
+            }
+        }
+        if self.config.one_in <= 1 {
+            return true;
+        }
+        let n = self.candidates;
+        self.candidates += 1;
+        mix(self.config.seed ^ n.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .is_multiple_of(self.config.one_in)
+    }
+}
+
+/// splitmix64 finalizer.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kept(sampler: &mut TraceSampler, n: usize) -> Vec<bool> {
+        (0..n)
+            .map(|_| sampler.keep(&TraceEventKind::VmStep, None))
+            .collect()
+    }
+
+    #[test]
+    fn one_in_ten_keeps_about_a_tenth() {
+        let mut sampler = TraceSampler::new(TraceSampling::one_in(10).with_seed(7));
+        let count = kept(&mut sampler, 10_000)
+            .into_iter()
+            .filter(|k| *k)
+            .count();
+        assert!((800..=1200).contains(&count), "kept {count} of 10000");
+    }
+
+    #[test]
+    fn decisions_are_reproducible_for_a_seed() {
+        let config = TraceSampling::one_in(4).with_seed(42);
+        let first = kept(&mut TraceSampler::new(config.clone()), 500);
+
+        let mut sampler = TraceSampler::new(config);
+        assert_eq!(kept(&mut sampler, 500), first);
+        sampler.reset();
+        assert_eq!(kept(&mut sampler, 500), first);
+
+        let other = kept(
+            &mut TraceSampler::new(TraceSampling::one_in(4).with_seed(43)),
+            500,
+        );
+        assert_ne!(other, first);
+    }
+
+    #[test]
+    fn errors_and_run_boundaries_are_always_kept() {
+        let mut sampler = TraceSampler::new(TraceSampling::one_in(u64::MAX).with_effects(["none"]));
+        for _ in 0..100 {
+            assert!(sampler.keep(&TraceEventKind::Error, None));
+            assert!(sampler.keep(&TraceEventKind::RunStart, None));
+            assert!(sampler.keep(&TraceEventKind::RunEnd, None));
+        }
+    }
+
+    #[test]
+    fn frames_keep_or_drop_both_boundaries() {
+        let mut sampler = TraceSampler::new(TraceSampling::one_in(3).with_seed(11));
+        let mut kept_frames = 0;
+        for _ in 0..300 {
+            let cell = sampler.enter(&TraceEventKind::CellStart);
+            let call = sampler.enter(&TraceEventKind::CallEnter);
+            // Steps inside the frame draw from the same sequence.
+            sampler.keep(&TraceEventKind::VmStep, None);
+            assert_eq!(sampler.exit(&TraceEventKind::CallExit), call);
+            assert_eq!(sampler.exit(&TraceEventKind::CellEnd), cell);
+            kept_frames += usize::from(cell) + usize::from(call);
+        }
+        assert!(kept_frames > 0 && kept_frames < 600, "kept {kept_frames}");
+    }
+
+    #[test]
+    fn effect_allowlist_filters_tool_calls() {
+        let mut sampler = TraceSampler::new(TraceSampling::all().with_effects(["http.get"]));
+        assert!(sampler.keep(&TraceEventKind::ToolCall, Some("http.get")));
+        assert!(!sampler.keep(&TraceEventKind::ToolCall, Some("fs.read")));
+        assert!(sampler.keep(&TraceEventKind::CellStart, None));
+    }
+}
@@ -1,7 +1,12 @@
 //! JSONL trace file writer with hash-chaining.
+//!
+//! A [`TraceSampling`] set with [`TraceStore::set_sampling`] thins out what is
+//! recorded. Skipped events never get a sequence number, so a sampled trace
+//! is still a complete hash chain.
 
 use crate::trace::events::{TraceEvent, TraceEventKind};
 use crate::trace::hasher::{canonical_json, sha256_hash};
+use crate::trace::sampling::{TraceSampler, TraceSampling};
 use chrono::Utc;
 use serde_json::json;
 use std::fs::{self, File, OpenOptions};
@@ -15,6 +20,7 @@ pub struct TraceStore {
     seq: u64,
     prev_hash: String,
     doc_hash: String,
+    sampler: TraceSampler,
 }
 
 const TRACE_GENESIS_HASH: &str = "sha256:genesis";
@@ -30,15 +36,27 @@ impl TraceStore {
             seq: 0,
             prev_hash: TRACE_GENESIS_HASH.to_string(),
             doc_hash: String::new(),
+            sampler: TraceSampler::default(),
         }
     }
 
+    /// Record only the events `sampling` selects. Takes effect immediately;
+    /// the decision sequence restarts with each run.
+    pub fn set_sampling(&mut self, sampling: TraceSampling) {
+        self.sampler = TraceSampler::new(sampling);
+    }
+
+    pub fn sampling(&self) -> &TraceSampling {
+        self.sampler.config()
+    }
+
     pub fn start_run(&mut self, doc_hash: &str) -> String {
         let run_id = uuid::Uuid::new_v4().to_string();
         self.current_run_id = run_id.clone();
         self.doc_hash = doc_hash.to_string();
         self.seq = 0;
         self.prev_hash = TRACE_GENESIS_HASH.to_string();
+        self.sampler.reset();
 
         let path = self.trace_dir.join(format!("{}.jsonl", &run_id));
         self.current_file = OpenOptions::new()
@@ -58,18 +76,27 @@ impl TraceStore {
     }
 
     pub fn cell_start(&mut self, cell_name: &str) {
-        self.emit_event(TraceEventKind::CellStart, Some(cell_name.to_string()), None);
+        if self.sampler.enter(&TraceEventKind::CellStart) {
+            self.emit_event(TraceEventKind::CellStart, Some(cell_name), None);
+        }
     }
 
     pub fn cell_end(&mut self, cell_name: &str) {
-        self.emit_event(TraceEventKind::CellEnd, Some(cell_name.to_string()), None);
+        if self.sampler.exit(&TraceEventKind::CellEnd) {
+            self.emit_event(TraceEventKind::CellEnd, Some(cell_name), None);
+        }
     }
 
     pub fn call_enter(&mut self, cell_name: &str) {
-        self.emit_event(TraceEventKind::CallEnter, Some(cell_name.to_string()), None);
+        if self.sampler.enter(&TraceEventKind::CallEnter) {
+            self.emit_event(TraceEventKind::CallEnter, Some(cell_name), None);
+        }
     }
 
     pub fn call_exit(&mut self, cell_name: &str, result_type: &str) {
+        if !self.sampler.exit(&TraceEventKind::CallExit) {
+            return;
+        }
         let mut event = self.make_event(TraceEventKind::CallExit);
         event.cell = Some(cell_name.to_string());
         event.details = Some(json!({ "result_type": result_type }));
@@ -77,6 +104,9 @@ impl TraceStore {
     }
 
     pub fn vm_step(&mut self, cell: &str, ip: usize, opcode: &str) {
+        if !self.sampler.keep(&TraceEventKind::VmStep, None) {
+            return;
+        }
         let mut event = self.make_event(TraceEventKind::VmStep);
         event.cell = Some(cell.to_string());
         event.details = Some(json!({ "ip": ip, "opcode": opcode }));
@@ -94,6 +124,10 @@ impl TraceStore {
         success: bool,
         message: Option<&str>,
     ) {
+        // Failed calls are errors, which sampling never drops.
+        if success && !self.sampler.keep(&TraceEventKind::ToolCall, Some(tool_id)) {
+            return;
+        }
         let mut event = self.make_event(TraceEventKind::ToolCall);
         event.cell = Some(cell.to_string());
         event.tool_id = Some(tool_id.to_string());
@@ -106,6 +140,9 @@ impl TraceStore {
     }
 
     pub fn schema_validate(&mut self, cell: &str, schema: &str, valid: bool) {
+        if !self.sampler.keep(&TraceEventKind::SchemaValidate, None) {
+            return;
+        }
         let mut event = self.make_event(TraceEventKind::SchemaValidate);
         event.cell = Some(cell.to_string());
         event.details = Some(json!({ "schema": schema, "valid": valid }));
@@ -123,9 +160,10 @@ impl TraceStore {
         &self.current_run_id
     }
 
-    fn emit_event(&mut self, kind: TraceEventKind, cell: Option<String>, message: Option<String>) {
+    /// Write an event the caller has already decided to record.
+    fn emit_event(&mut self, kind: TraceEventKind, cell: Option<&str>, message: Option<String>) {
         let mut event = self.make_event(kind);
-        event.cell = cell;
+        event.cell = cell.map(ToString::to_string);
         event.message = message;
         self.write_event(&mut event);
     }
@@ -340,6 +378,86 @@ mod tests {
         fs::remove_dir_all(&base_dir).expect("test temp dir should be removed");
     }
 
+    #[test]
+    fn sampled_trace_keeps_errors_and_still_verifies() {
+        let base_dir = std::env::temp_dir().join(format!(
+            "lumen-trace-store-sampling-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&base_dir).expect("test temp dir should be created");
+
+        let mut store = TraceStore::new(&base_dir);
+        store.set_sampling(TraceSampling::one_in(10).with_seed(3));
+        let run_id = store.start_run("doc-123");
+        for ip in 0..1000 {
+            store.vm_step("main", ip, "Add");
+            if ip % 100 == 0 {
+                store.error(Some("main"), "boom");
+                store.tool_call("main", "http.get", "1.0.0", 1, false, false, None);
+            }
+        }
+        store.end_run();
+
+        let path = base_dir.join("trace").join(format!("{}.jsonl", run_id));
+        let content = fs::read_to_string(&path).expect("trace file should be readable");
+        let events: Vec<TraceEvent> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("trace event should deserialize"))
+            .collect();
+
+        let count = |kind: TraceEventKind| events.iter().filter(|e| e.kind == kind).count();
+        assert_eq!(count(TraceEventKind::Error), 10);
+        assert_eq!(count(TraceEventKind::ToolCall), 10);
+        assert_eq!(count(TraceEventKind::RunStart), 1);
+        assert_eq!(count(TraceEventKind::RunEnd), 1);
+        let steps = count(TraceEventKind::VmStep);
+        assert!((50..=150).contains(&steps), "kept {steps} of 1000 steps");
+        verify_event_chain(&events).expect("sampled trace should pass chain verification");
+
+        fs::remove_dir_all(&base_dir).expect("test temp dir should be removed");
+    }
+
+    #[test]
+    fn sampled_trace_keeps_frame_boundaries_paired() {
+        let base_dir = std::env::temp_dir().join(format!(
+            "lumen-trace-store-frame-sampling-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&base_dir).expect("test temp dir should be created");
+
+        let mut store = TraceStore::new(&base_dir);
+        store.set_sampling(TraceSampling::one_in(3).with_seed(5));
+        let run_id = store.start_run("doc-123");
+        for ip in 0..200 {
+            store.cell_start("main");
+            store.call_enter("helper");
+            store.vm_step("helper", ip, "Add");
+            store.call_exit("helper", "Int");
+            store.cell_end("main");
+        }
+        store.end_run();
+
+        let path = base_dir.join("trace").join(format!("{}.jsonl", run_id));
+        let content = fs::read_to_string(&path).expect("trace file should be readable");
+        let events: Vec<TraceEvent> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("trace event should deserialize"))
+            .collect();
+
+        let count = |kind: TraceEventKind| events.iter().filter(|e| e.kind == kind).count();
+        assert!(count(TraceEventKind::CellStart) < 200);
+        assert_eq!(
+            count(TraceEventKind::CellStart),
+            count(TraceEventKind::CellEnd)
+        );
+        assert_eq!(
+            count(TraceEventKind::CallEnter),
+            count(TraceEventKind::CallExit)
+        );
+
+        fs::remove_dir_all(&base_dir).expect("test temp dir should be removed");
+    }
+
     #[test]
     fn compute_event_hash_is_stable_across_timestamp_but_sensitive_to_payload() {
         let mut event = TraceEvent {
@@ -24,17 +24,62 @@
 //! All cells are eligible for JIT compilation attempt. If a cell contains
 //! unsupported opcodes, compilation fails gracefully and the cell falls back
 //! to the interpreter.
+//!
+//! ## Tiers
+//!
+//! When `tier_up_threshold` is above `hot_threshold`, a hot cell is first
+//! compiled without optimisation (cheap to produce) and recompiled at the
+//! configured optimisation level once it has been called `tier_up_threshold`
+//! times. Otherwise hot cells go straight to optimised code.
 
 #[cfg(feature = "jit")]
-use lumen_codegen::jit::{CodegenSettings, JitEngine, JitStats, OptLevel};
+use lumen_codegen::jit::{CodegenSettings, JitEngine, JitError, JitStats, OptLevel};
 use lumen_compiler::compiler::lir::LirModule;
 use std::collections::HashSet;
 
+/// User-facing tuning for when cells are promoted from the interpreter to
+/// native code. See [`VM::with_jit_config`](crate::vm::VM::with_jit_config).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JitConfig {
+    /// Number of calls before a cell is considered "hot" and compiled.
+    pub hot_threshold: u64,
+    /// Number of calls before a hot cell is recompiled with optimisations.
+    /// At or below `hot_threshold`, hot cells are optimised immediately.
+    pub tier_up_threshold: u64,
+    /// Keep every cell in the interpreter, e.g. for deterministic benchmarks.
+    pub disable: bool,
+}
+
+impl Default for JitConfig {
+    fn default() -> Self {
+        let tier = JitTierConfig::default();
+        Self {
+            hot_threshold: tier.hot_threshold,
+            tier_up_threshold: tier.tier_up_threshold,
+            disable: !tier.enabled,
+        }
+    }
+}
+
+impl From<JitConfig> for JitTierConfig {
+    fn from(config: JitConfig) -> Self {
+        Self {
+            hot_threshold: config.hot_threshold,
+            tier_up_threshold: config.tier_up_threshold,
+            enabled: !config.disable,
+            ..Default::default()
+        }
+    }
+}
+
 /// Configuration for the tiered JIT.
 #[derive(Debug, Clone)]
 pub struct JitTierConfig {
     /// Number of calls before a cell is considered "hot" and compiled.
     pub hot_threshold: u64,
+    /// Number of calls before a hot cell is recompiled at `opt_level`.
+    /// At or below `hot_threshold` there is a single, optimised tier.
+    pub tier_up_threshold: u64,
     /// Optimisation level for JIT compilation.
     pub opt_level: JitOptLevel,
     /// Whether JIT is enabled at all.
@@ -54,6 +99,7 @@ impl Default for JitTierConfig {
     fn default() -> Self {
         Self {
             hot_threshold: 10,
+            tier_up_threshold: 10,
             opt_level: JitOptLevel::Speed,
             enabled: true,
         }
@@ -82,6 +128,9 @@ pub struct JitTier {
     eligibility: Vec<CellEligibility>,
     /// Set of cell indices that have been compiled.
     compiled: HashSet<usize>,
+    /// Whether the module has been compiled at the configured optimisation
+    /// level. Once set, later recompilations stay at that level.
+    optimized: bool,
     /// Configuration.
     config: JitTierConfig,
     /// The actual Cranelift JIT engine (only present when feature = "jit").
@@ -91,6 +140,11 @@ pub struct JitTier {
     pub stats: JitTierStats,
 }
 
+/// Native execution ran out of call depth: calls between compiled cells
+/// nested deeper than the budget passed to [`JitTier::execute`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallDepthExceeded;
+
 /// Public statistics about tiered JIT activity.
 #[derive(Debug, Clone, Default)]
 pub struct JitTierStats {
@@ -102,6 +156,8 @@ pub struct JitTierStats {
     pub compile_failures: u64,
     /// Total number of calls tracked.
     pub total_calls_tracked: u64,
+    /// Number of recompilations from the baseline tier to optimised code.
+    pub tier_ups: u64,
 }
 
 impl JitTier {
@@ -111,6 +167,7 @@ impl JitTier {
             call_counts: Vec::new(),
             eligibility: Vec::new(),
             compiled: HashSet::new(),
+            optimized: false,
             config,
             #[cfg(feature = "jit")]
             engine: None,
@@ -129,9 +186,12 @@ impl JitTier {
     /// Initialise internal vectors to match the number of cells in the module.
     /// Must be called after `VM::load()`.
     pub fn init_for_module(&mut self, num_cells: usize) {
+        self.call_counts.clear();
         self.call_counts.resize(num_cells, 0);
+        self.eligibility.clear();
         self.eligibility.resize(num_cells, CellEligibility::Unknown);
         self.compiled.clear();
+        self.optimized = false;
         self.stats = JitTierStats::default();
     }
 
@@ -147,6 +207,12 @@ impl JitTier {
         self.compiled.contains(&cell_idx)
     }
 
+    /// Whether compiled code is at the configured optimisation level rather
+    /// than the baseline tier.
+    pub fn is_optimized(&self) -> bool {
+        self.optimized
+    }
+
     /// Check and cache JIT eligibility for a cell.
     /// All cells are eligible — if compilation fails for unsupported opcodes,
     /// the cell gracefully falls back to the interpreter.
@@ -166,8 +232,13 @@ impl JitTier {
         }
     }
 
-    /// Record a call to `cell_idx`. Returns `true` if the cell *just* crossed
-    /// the hot threshold and should be compiled NOW.
+    /// Whether hot cells are first compiled unoptimised and tiered up later.
+    fn has_baseline_tier(&self) -> bool {
+        self.config.tier_up_threshold > self.config.hot_threshold
+    }
+
+    /// Record a call to `cell_idx`. Returns `true` if the cell is past the
+    /// threshold for its tier and should be compiled (or tiered up) NOW.
     #[inline]
     pub fn record_call(&mut self, cell_idx: usize) -> bool {
         if !self.config.enabled {
@@ -176,13 +247,42 @@ impl JitTier {
         if cell_idx >= self.call_counts.len() {
             return false;
         }
-        // Already compiled — no need to track further.
-        if self.compiled.contains(&cell_idx) {
+        // Already compiled — only baseline code still needs its calls counted.
+        if self.compiled.contains(&cell_idx) && (self.optimized || !self.has_baseline_tier()) {
             return false;
         }
         self.call_counts[cell_idx] += 1;
         self.stats.total_calls_tracked += 1;
-        self.call_counts[cell_idx] == self.config.hot_threshold + 1
+        let threshold = if self.compiled.contains(&cell_idx) {
+            self.config.tier_up_threshold
+        } else {
+            self.config.hot_threshold
+        };
+        self.call_counts[cell_idx] > threshold
+    }
+
+    /// Track a call to `cell_idx` and compile or tier it up when it crosses a
+    /// threshold. Returns `true` if the call should run as native code.
+    pub fn prepare_call(&mut self, cell_idx: usize, module: &LirModule) -> bool {
+        if !self.record_call(cell_idx) {
+            return self.is_compiled(cell_idx);
+        }
+        if self.is_compiled(cell_idx) {
+            self.tier_up(cell_idx, module);
+            return true;
+        }
+        self.check_eligibility(cell_idx, module)
+            && self.try_compile(cell_idx, module)
+            && self.is_compiled(cell_idx)
+    }
+
+    /// Recompile the module at the configured optimisation level. On failure
+    /// the baseline code stays in place.
+    fn tier_up(&mut self, cell_idx: usize, module: &LirModule) {
+        self.optimized = true;
+        if self.compile_engine(cell_idx, module, self.config.opt_level) {
+            self.stats.tier_ups += 1;
+        }
     }
 
     /// Attempt to compile a hot cell. Returns `true` on success.
@@ -192,7 +292,26 @@ impl JitTier {
     /// cause compilation to fail gracefully, falling back to the interpreter.
     ///
     /// On no-jit builds, this is a no-op that returns `false`.
-    pub fn try_compile(&mut self, _cell_idx: usize, module: &LirModule) -> bool {
+    /// Once the module has tiered up, recompiling for a newly hot cell stays
+    /// at the configured optimisation level.
+    pub fn try_compile(&mut self, cell_idx: usize, module: &LirModule) -> bool {
+        if !self.has_baseline_tier() {
+            self.optimized = true;
+        }
+        let opt_level = if self.optimized {
+            self.config.opt_level
+        } else {
+            JitOptLevel::None
+        };
+        self.compile_engine(cell_idx, module, opt_level)
+    }
+
+    fn compile_engine(
+        &mut self,
+        _cell_idx: usize,
+        module: &LirModule,
+        opt_level: JitOptLevel,
+    ) -> bool {
         #[cfg(feature = "jit")]
         {
             if module.cells.is_empty() {
@@ -200,7 +319,7 @@ impl JitTier {
                 return false;
             }
 
-            let opt = match self.config.opt_level {
+            let opt = match opt_level {
                 JitOptLevel::None => OptLevel::None,
                 JitOptLevel::Speed => OptLevel::Speed,
                 JitOptLevel::SpeedAndSize => OptLevel::SpeedAndSize,
@@ -212,7 +331,7 @@ impl JitTier {
 
             // Create a new engine each time (Cranelift JITModule doesn't support
             // incremental addition of functions after finalize_definitions).
-            let mut engine = JitEngine::new(settings, 0);
+            let mut engine = JitEngine::new(settings, self.config.hot_threshold);
             match engine.compile_module(module) {
                 Ok(()) => {
                     // Only mark cells that were actually compiled by the engine.
@@ -220,8 +339,13 @@ impl JitTier {
                     // compile_module and won't be in the engine's cache.
                     for (idx, cell) in module.cells.iter().enumerate() {
                         if engine.is_compiled(&cell.name) {
-                            self.compiled.insert(idx);
-                            self.stats.cells_compiled += 1;
+                            if self.compiled.insert(idx) {
+                                self.stats.cells_compiled += 1;
+                            }
+                        } else if idx == _cell_idx {
+                            // The engine skipped the hot cell (unsupported
+                            // code); recompiling won't change that.
+                            self.eligibility[idx] = CellEligibility::NotEligible;
                         }
                     }
                     self.engine = Some(engine);
@@ -241,34 +365,42 @@ impl JitTier {
 
         #[cfg(not(feature = "jit"))]
         {
-            let _ = module;
+            let _ = (module, opt_level);
             false
         }
     }
 
-    /// Execute a JIT-compiled cell with the given i64 arguments.
-    /// Returns `Some(result)` on success, `None` if not compiled or execution fails.
+    /// Execute a JIT-compiled cell with the given i64 arguments, letting
+    /// calls between compiled cells nest at most `depth_budget` deep.
+    /// Returns `Ok(Some(result))` on success, `Ok(None)` if not compiled or
+    /// execution fails, and `Err` if the depth budget ran out.
     #[inline]
-    pub fn execute(&mut self, cell_name: &str, args: &[i64]) -> Option<i64> {
+    pub fn execute(
+        &mut self,
+        cell_name: &str,
+        args: &[i64],
+        depth_budget: usize,
+    ) -> Result<Option<i64>, CallDepthExceeded> {
         #[cfg(feature = "jit")]
         {
             if let Some(ref mut engine) = self.engine {
-                match engine.execute_jit(cell_name, args) {
+                match engine.execute_jit_within_depth(cell_name, args, depth_budget) {
                     Ok(result) => {
                         self.stats.jit_executions += 1;
-                        Some(result)
+                        Ok(Some(result))
                     }
-                    Err(_) => None,
+                    Err(JitError::CallDepthExceeded) => Err(CallDepthExceeded),
+                    Err(_) => Ok(None),
                 }
             } else {
-                None
+                Ok(None)
             }
         }
 
         #[cfg(not(feature = "jit"))]
         {
-            let _ = (cell_name, args);
-            None
+            let _ = (cell_name, args, depth_budget);
+            Ok(None)
         }
     }
 
@@ -5,10 +5,12 @@ pub mod arena;
 pub mod gc;
 pub mod immix;
 pub mod jit_tier;
+pub mod memo;
 pub mod parity_concurrency;
 pub mod strings;
 pub mod tagged;
 pub mod tlab;
 pub mod types;
 pub mod values;
+pub mod verify;
 pub mod vm;
@@ -0,0 +1,502 @@
+//! Memoization of `@pure` cell results.
+//!
+//! The compiler marks cells whose purity it verified as `memoizable` in the
+//! LIR. When memoization is enabled, the VM consults a [`MemoCache`] before
+//! entering such a cell: results are keyed by the callee and a structural hash
+//! of its arguments, and stored when the callee returns. The cache is a bounded
+//! LRU so long-running programs cannot grow it without limit.
+//!
+//! Arguments whose identity matters more than their structure (futures and
+//! trace references) make a call ineligible for memoization.
+//!
+//! Each miss records the VM's reduction (instruction) count at call entry, so
+//! the cost of the call is known when it returns. Results of calls cheaper
+//! than [`MemoConfig::min_reductions`] are not cached: re-running them costs
+//! less than hashing and comparing their arguments.
+
+use crate::strings::StringTable;
+use crate::values::{values_equal, StringRef, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Default maximum number of memoized results.
+pub const DEFAULT_MEMO_CAPACITY: usize = 1024;
+
+/// Configuration for `@pure` cell memoization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoConfig {
+    /// Whether results of memoizable cells are cached.
+    pub enabled: bool,
+    /// Maximum number of cached results. Least recently used entries are
+    /// evicted once the cache is full. A capacity of 0 disables caching.
+    pub capacity: usize,
+    /// Minimum number of reductions a call must take for its result to be
+    /// cached. Cheaper calls are re-executed each time. 0 caches every call.
+    pub min_reductions: u64,
+}
+
+impl Default for MemoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: DEFAULT_MEMO_CAPACITY,
+            min_reductions: 0,
+        }
+    }
+}
+
+/// Counters describing memo cache activity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Calls answered from the cache.
+    pub hits: u64,
+    /// Calls that executed the cell body.
+    pub misses: u64,
+    /// Misses whose result was not cached because the call took fewer than
+    /// `min_reductions` reductions.
+    pub below_threshold: u64,
+    /// Reductions avoided by cache hits, based on the cost of the call that
+    /// produced each entry.
+    pub reductions_saved: u64,
+    /// Entries dropped to stay within capacity.
+    pub evictions: u64,
+    /// Entries currently cached.
+    pub entries: usize,
+}
+
+/// A cache miss awaiting the callee's return value.
+#[derive(Debug, Clone)]
+pub(crate) struct MemoPending {
+    key: u64,
+    cell_idx: usize,
+    args: Vec<Value>,
+    start_reductions: u64,
+    /// Entry of a caller that tail-called this call; it returns the same
+    /// value from the same frame.
+    outer: Option<Box<MemoPending>>,
+}
+
+impl MemoPending {
+    /// Reductions executed since the call was entered.
+    pub(crate) fn cost(&self, reductions_now: u64) -> u64 {
+        reductions_now.saturating_sub(self.start_reductions)
+    }
+
+    /// Chain the entry of the tail-calling caller behind this one.
+    pub(crate) fn with_outer(mut self: Box<Self>, outer: Option<Box<MemoPending>>) -> Box<Self> {
+        self.outer = outer;
+        self
+    }
+}
+
+/// Outcome of consulting the cache for a call.
+pub(crate) enum MemoProbe {
+    /// The result was cached; the call can be skipped.
+    Hit(Value),
+    /// Not cached; record the result on return.
+    Miss(Box<MemoPending>),
+    /// Memoization is disabled or the arguments are not hashable.
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+struct MemoEntry {
+    cell_idx: usize,
+    args: Vec<Value>,
+    result: Value,
+    cost: u64,
+    last_used: u64,
+}
+
+/// Bounded LRU cache of `@pure` cell results.
+#[derive(Debug, Clone, Default)]
+pub struct MemoCache {
+    config: MemoConfig,
+    entries: HashMap<u64, MemoEntry>,
+    tick: u64,
+    stats: MemoStats,
+}
+
+impl MemoCache {
+    pub fn new(config: MemoConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> MemoConfig {
+        self.config
+    }
+
+    /// Enable or disable memoization. Disabling also drops cached results.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.config.enabled = enabled;
+        if !enabled {
+            self.entries.clear();
+        }
+    }
+
+    /// Change the capacity, evicting least recently used entries if needed.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.config.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.evict_lru();
+        }
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached results. Counters are kept.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn is_active(&self) -> bool {
+        self.config.enabled && self.config.capacity > 0
+    }
+
+    /// Change the minimum call cost (in reductions) worth caching.
+    pub fn set_min_reductions(&mut self, min_reductions: u64) {
+        self.config.min_reductions = min_reductions;
+    }
+
+    /// Look up the result of calling `cell_idx` with `args`. `reductions` is
+    /// the VM's reduction count at call entry.
+    pub(crate) fn probe(
+        &mut self,
+        cell_idx: usize,
+        args: &[Value],
+        strings: &StringTable,
+        reductions: u64,
+    ) -> MemoProbe {
+        if !self.is_active() {
+            return MemoProbe::Skip;
+        }
+        let Some(key) = memo_key(cell_idx, args, strings) else {
+            return MemoProbe::Skip;
+        };
+
+        self.tick += 1;
+        if let Some(entry) = self.entries.get_mut(&key) {
+            let same_args = entry.cell_idx == cell_idx
+                && entry.args.len() == args.len()
+                && entry
+                    .args
+                    .iter()
+                    .zip(args)
+                    .all(|(a, b)| values_equal(a, b, strings));
+            if same_args {
+                entry.last_used = self.tick;
+                self.stats.hits += 1;
+                self.stats.reductions_saved += entry.cost;
+                return MemoProbe::Hit(entry.result.clone());
+            }
+        }
+
+        self.stats.misses += 1;
+        MemoProbe::Miss(Box::new(MemoPending {
+            key,
+            cell_idx,
+            args: args.to_vec(),
+            start_reductions: reductions,
+            outer: None,
+        }))
+    }
+
+    /// Store the result of a call that previously missed and took `cost`
+    /// reductions. Calls below the configured threshold are not cached.
+    pub(crate) fn insert(&mut self, pending: MemoPending, result: Value, cost: u64) {
+        if !self.is_active() {
+            return;
+        }
+        if cost < self.config.min_reductions {
+            self.stats.below_threshold += 1;
+            return;
+        }
+        if !self.entries.contains_key(&pending.key) && self.entries.len() >= self.config.capacity {
+            self.evict_lru();
+        }
+        self.tick += 1;
+        self.entries.insert(
+            pending.key,
+            MemoEntry {
+                cell_idx: pending.cell_idx,
+                args: pending.args,
+                result,
+                cost,
+                last_used: self.tick,
+            },
+        );
+    }
+
+    /// Record `result` for a returning frame's pending entry and for every
+    /// tail-calling caller chained behind it.
+    pub(crate) fn complete(
+        &mut self,
+        mut pending: Option<Box<MemoPending>>,
+        result: &Value,
+        reductions_now: u64,
+    ) {
+        while let Some(mut entry) = pending {
+            pending = entry.outer.take();
+            let cost = entry.cost(reductions_now);
+            self.insert(*entry, result.clone(), cost);
+        }
+    }
+
+    fn evict_lru(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, e)| e.last_used)
+            .map(|(k, _)| *k);
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+            self.stats.evictions += 1;
+        }
+    }
+}
+
+/// Structural hash of a call, or `None` if an argument cannot be memoized.
+fn memo_key(cell_idx: usize, args: &[Value], strings: &StringTable) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    cell_idx.hash(&mut hasher);
+    args.len().hash(&mut hasher);
+    for arg in args {
+        if !hash_value(arg, strings, &mut hasher) {
+            return None;
+        }
+    }
+    Some(hasher.finish())
+}
+
+/// Feed `value` into `hasher`. Interned and owned strings with the same
+/// contents hash identically. Returns `false` for values compared by identity.
+fn hash_value(value: &Value, strings: &StringTable, hasher: &mut DefaultHasher) -> bool {
+    match value {
+        Value::Null => 0u8.hash(hasher),
+        Value::Bool(b) => {
+            1u8.hash(hasher);
+            b.hash(hasher);
+        }
+        Value::Int(n) => {
+            2u8.hash(hasher);
+            n.hash(hasher);
+        }
+        Value::BigInt(n) => {
+            3u8.hash(hasher);
+            n.hash(hasher);
+        }
+        Value::Float(f) => {
+            4u8.hash(hasher);
+            f.to_bits().hash(hasher);
+        }
+        Value::String(s) => {
+            5u8.hash(hasher);
+            match s {
+                StringRef::Owned(s) => s.as_str().hash(hasher),
+                StringRef::Interned(id) => strings.resolve(*id).unwrap_or("").hash(hasher),
+            }
+        }
+        Value::Bytes(bytes) => {
+            6u8.hash(hasher);
+            bytes.hash(hasher);
+        }
+        Value::List(items) | Value::Tuple(items) => {
+            let tag = if matches!(value, Value::List(_)) {
+                7u8
+            } else {
+                8u8
+            };
+            tag.hash(hasher);
+            items.len().hash(hasher);
+            return items.iter().all(|v| hash_value(v, strings, hasher));
+        }
+        Value::Set(items) => {
+            9u8.hash(hasher);
+            items.len().hash(hasher);
+            return items.iter().all(|v| hash_value(v, strings, hasher));
+        }
+        Value::Map(map) => {
+            10u8.hash(hasher);
+            map.len().hash(hasher);
+            return map.iter().all(|(k, v)| {
+                k.hash(hasher);
+                hash_value(v, strings, hasher)
+            });
+        }
+        Value::Record(r) => {
+            11u8.hash(hasher);
+            r.type_name.hash(hasher);
+            r.fields.len().hash(hasher);
+            return r.fields.iter().all(|(k, v)| {
+                k.hash(hasher);
+                hash_value(v, strings, hasher)
+            });
+        }
+        Value::Union(u) => {
+            12u8.hash(hasher);
+            strings.resolve(u.tag).unwrap_or("").hash(hasher);
+            return hash_value(&u.payload, strings, hasher);
+        }
+        Value::Closure(c) => {
+            13u8.hash(hasher);
+            c.cell_idx.hash(hasher);
+            c.captures.len().hash(hasher);
+            return c.captures.iter().all(|v| hash_value(v, strings, hasher));
+        }
+        Value::TraceRef(_) | Value::Future(_) => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owned(s: &str) -> Value {
+        Value::String(StringRef::Owned(s.to_string()))
+    }
+
+    fn miss(cache: &mut MemoCache, cell_idx: usize, args: &[Value], result: Value) {
+        miss_costing(cache, cell_idx, args, result, 0);
+    }
+
+    fn miss_costing(
+        cache: &mut MemoCache,
+        cell_idx: usize,
+        args: &[Value],
+        result: Value,
+        cost: u64,
+    ) {
+        let strings = StringTable::new();
+        match cache.probe(cell_idx, args, &strings, 0) {
+            MemoProbe::Miss(pending) => cache.insert(*pending, result, cost),
+            _ => panic!("expected a miss"),
+        }
+    }
+
+    #[test]
+    fn hit_after_insert() {
+        let strings = StringTable::new();
+        let mut cache = MemoCache::new(MemoConfig::default());
+        miss(&mut cache, 0, &[Value::Int(3)], Value::Int(9));
+
+        match cache.probe(0, &[Value::Int(3)], &strings, 0) {
+            MemoProbe::Hit(v) => assert_eq!(v, Value::Int(9)),
+            _ => panic!("expected a hit"),
+        }
+        // Same args, different cell: not shared.
+        assert!(matches!(
+            cache.probe(1, &[Value::Int(3)], &strings, 0),
+            MemoProbe::Miss(_)
+        ));
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 1));
+    }
+
+    #[test]
+    fn interned_and_owned_strings_share_entries() {
+        let mut strings = StringTable::new();
+        let id = strings.intern("key");
+        let mut cache = MemoCache::new(MemoConfig::default());
+        match cache.probe(0, &[owned("key")], &strings, 0) {
+            MemoProbe::Miss(pending) => cache.insert(*pending, Value::Int(1), 0),
+            _ => panic!("expected a miss"),
+        }
+        assert!(matches!(
+            cache.probe(0, &[Value::String(StringRef::Interned(id))], &strings, 0),
+            MemoProbe::Hit(_)
+        ));
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let strings = StringTable::new();
+        let mut cache = MemoCache::new(MemoConfig {
+            capacity: 2,
+            ..MemoConfig::default()
+        });
+        miss(&mut cache, 0, &[Value::Int(1)], Value::Int(1));
+        miss(&mut cache, 0, &[Value::Int(2)], Value::Int(2));
+        // Touch 1 so that 2 becomes the LRU entry.
+        assert!(matches!(
+            cache.probe(0, &[Value::Int(1)], &strings, 0),
+            MemoProbe::Hit(_)
+        ));
+        miss(&mut cache, 0, &[Value::Int(3)], Value::Int(3));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().evictions, 1);
+        assert!(matches!(
+            cache.probe(0, &[Value::Int(1)], &strings, 0),
+            MemoProbe::Hit(_)
+        ));
+        assert!(matches!(
+            cache.probe(0, &[Value::Int(2)], &strings, 0),
+            MemoProbe::Miss(_)
+        ));
+    }
+
+    #[test]
+    fn disabled_cache_skips() {
+        let strings = StringTable::new();
+        let mut cache = MemoCache::new(MemoConfig::default());
+        miss(&mut cache, 0, &[Value::Int(1)], Value::Int(1));
+        cache.set_enabled(false);
+        assert!(cache.is_empty());
+        assert!(matches!(
+            cache.probe(0, &[Value::Int(1)], &strings, 0),
+            MemoProbe::Skip
+        ));
+    }
+
+    #[test]
+    fn calls_below_min_reductions_are_not_cached() {
+        let strings = StringTable::new();
+        let mut cache = MemoCache::new(MemoConfig {
+            min_reductions: 100,
+            ..MemoConfig::default()
+        });
+        miss_costing(&mut cache, 0, &[Value::Int(1)], Value::Int(1), 99);
+        miss_costing(&mut cache, 0, &[Value::Int(2)], Value::Int(2), 100);
+
+        assert!(matches!(
+            cache.probe(0, &[Value::Int(1)], &strings, 0),
+            MemoProbe::Miss(_)
+        ));
+        assert!(matches!(
+            cache.probe(0, &[Value::Int(2)], &strings, 0),
+            MemoProbe::Hit(_)
+        ));
+        let stats = cache.stats();
+        assert_eq!(stats.below_threshold, 1);
+        assert_eq!(stats.reductions_saved, 100);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[test]
+    fn pending_cost_counts_reductions_since_entry() {
+        let strings = StringTable::new();
+        let mut cache = MemoCache::new(MemoConfig::default());
+        match cache.probe(0, &[Value::Int(1)], &strings, 40) {
+            MemoProbe::Miss(pending) => assert_eq!(pending.cost(55), 15),
+            _ => panic!("expected a miss"),
+        }
+    }
+}
@@ -0,0 +1,564 @@
+//! Bytecode verification for LIR modules.
+//!
+//! The interpreter indexes registers, constants and jump targets straight from
+//! instruction operands, so it relies on its input being well formed. Modules
+//! produced by the compiler are; modules read from disk or built by hand may
+//! not be. [`verify`] checks a module up front and reports every problem it
+//! finds, so a corrupt module is rejected before it runs instead of failing
+//! (or misbehaving) partway through.
+//!
+//! Checks performed for each cell:
+//!
+//! - register operands, including call argument windows and loop state, fall
+//!   inside the cell's register file;
+//! - jump, skip and handler targets land inside the cell (one past the last
+//!   instruction is allowed and means an implicit return);
+//! - constant, string, cell and tool indices are in range, and `Perform`
+//!   operands are string constants;
+//! - parameters use valid registers and only the last one is variadic.
+
+use lumen_compiler::compiler::lir::{Constant, Instruction, LirCell, LirModule, OpCode};
+use thiserror::Error;
+
+/// A problem found by [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VerifyError {
+    #[error(
+        "cell '{cell}' instruction {pc}: register r{register} out of range ({registers} registers)"
+    )]
+    RegisterOutOfRange {
+        cell: String,
+        pc: usize,
+        register: usize,
+        registers: u16,
+    },
+    #[error("cell '{cell}' instruction {pc}: target {target} outside 0..={len}")]
+    JumpOutOfRange {
+        cell: String,
+        pc: usize,
+        target: i64,
+        len: usize,
+    },
+    #[error("cell '{cell}' instruction {pc}: constant {index} out of range ({len} constants)")]
+    ConstantOutOfRange {
+        cell: String,
+        pc: usize,
+        index: usize,
+        len: usize,
+    },
+    #[error("cell '{cell}' instruction {pc}: constant {index} must be a {expected}")]
+    ConstantType {
+        cell: String,
+        pc: usize,
+        index: usize,
+        expected: &'static str,
+    },
+    #[error("cell '{cell}' instruction {pc}: string {index} out of range ({len} strings)")]
+    StringOutOfRange {
+        cell: String,
+        pc: usize,
+        index: usize,
+        len: usize,
+    },
+    #[error("cell '{cell}' instruction {pc}: cell {index} out of range ({len} cells)")]
+    CellOutOfRange {
+        cell: String,
+        pc: usize,
+        index: usize,
+        len: usize,
+    },
+    #[error("cell '{cell}' instruction {pc}: tool {index} out of range ({len} tools)")]
+    ToolOutOfRange {
+        cell: String,
+        pc: usize,
+        index: usize,
+        len: usize,
+    },
+    #[error("cell '{cell}': parameter '{param}' uses r{register} but the cell has {registers} registers")]
+    ParamOutOfRange {
+        cell: String,
+        param: String,
+        register: u8,
+        registers: u16,
+    },
+    #[error("cell '{cell}': variadic parameter '{param}' must be the last parameter")]
+    VariadicNotLast { cell: String, param: String },
+}
+
+/// Verify every cell of `module`, returning all problems found.
+pub fn verify(module: &LirModule) -> Result<(), Vec<VerifyError>> {
+    let mut errors = Vec::new();
+    for cell in &module.cells {
+        verify_cell(module, cell, &mut errors);
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn verify_cell(module: &LirModule, cell: &LirCell, errors: &mut Vec<VerifyError>) {
+    for (i, param) in cell.params.iter().enumerate() {
+        if param.register as u16 >= cell.registers {
+            errors.push(VerifyError::ParamOutOfRange {
+                cell: cell.name.clone(),
+                param: param.name.clone(),
+                register: param.register,
+                registers: cell.registers,
+            });
+        }
+        if param.variadic && i + 1 != cell.params.len() {
+            errors.push(VerifyError::VariadicNotLast {
+                cell: cell.name.clone(),
+                param: param.name.clone(),
+            });
+        }
+    }
+
+    let len = cell.instructions.len();
+    for (pc, instr) in cell.instructions.iter().enumerate() {
+        if let Err(register) = check_registers(*instr, cell.registers) {
+            errors.push(VerifyError::RegisterOutOfRange {
+                cell: cell.name.clone(),
+                pc,
+                register,
+                registers: cell.registers,
+            });
+        }
+
+        if let Some(target) = control_target(*instr, pc) {
+            if target < 0 || target > len as i64 {
+                errors.push(VerifyError::JumpOutOfRange {
+                    cell: cell.name.clone(),
+                    pc,
+                    target,
+                    len,
+                });
+            }
+        }
+
+        let index_error = |index: usize, table_len: usize, kind: IndexKind| {
+            (index >= table_len).then(|| kind.error(cell.name.clone(), pc, index, table_len))
+        };
+        let bx = instr.bx() as usize;
+        let found = match instr.op {
+            OpCode::LoadK => index_error(bx, cell.constants.len(), IndexKind::Constant),
+            OpCode::Closure | OpCode::Spawn => index_error(bx, module.cells.len(), IndexKind::Cell),
+            OpCode::ToolCall => index_error(bx, module.tools.len(), IndexKind::Tool),
+            OpCode::NewRecord | OpCode::IsVariant => {
+                index_error(bx, module.strings.len(), IndexKind::String)
+            }
+            OpCode::GetField => {
+                index_error(instr.c as usize, module.strings.len(), IndexKind::String)
+            }
+            OpCode::SetField => {
+                index_error(instr.b as usize, module.strings.len(), IndexKind::String)
+            }
+            _ => None,
+        };
+        errors.extend(found);
+
+        if instr.op == OpCode::Perform {
+            for index in [instr.b as usize, instr.c as usize] {
+                match cell.constants.get(index) {
+                    Some(Constant::String(_)) => {}
+                    Some(_) => errors.push(VerifyError::ConstantType {
+                        cell: cell.name.clone(),
+                        pc,
+                        index,
+                        expected: "string",
+                    }),
+                    None => errors.push(VerifyError::ConstantOutOfRange {
+                        cell: cell.name.clone(),
+                        pc,
+                        index,
+                        len: cell.constants.len(),
+                    }),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum IndexKind {
+    Constant,
+    String,
+    Cell,
+    Tool,
+}
+
+impl IndexKind {
+    fn error(self, cell: String, pc: usize, index: usize, len: usize) -> VerifyError {
+        match self {
+            IndexKind::Constant => VerifyError::ConstantOutOfRange {
+                cell,
+                pc,
+                index,
+                len,
+            },
+            IndexKind::String => VerifyError::StringOutOfRange {
+                cell,
+                pc,
+                index,
+                len,
+            },
+            IndexKind::Cell => VerifyError::CellOutOfRange {
+                cell,
+                pc,
+                index,
+                len,
+            },
+            IndexKind::Tool => VerifyError::ToolOutOfRange {
+                cell,
+                pc,
+                index,
+                len,
+            },
+        }
+    }
+}
+
+/// Where control may go from `instr` at `pc`, besides the next instruction.
+fn control_target(instr: Instruction, pc: usize) -> Option<i64> {
+    let next = pc as i64 + 1;
+    match instr.op {
+        OpCode::Jmp | OpCode::Break | OpCode::Continue => Some(next + instr.sax_val() as i64),
+        OpCode::Loop => Some(next + instr.sbx() as i64),
+        OpCode::ForPrep => Some(next + instr.bx() as i64),
+        OpCode::ForLoop => Some(next - instr.bx() as i64),
+        OpCode::HandlePush => Some(pc as i64 + instr.bx() as i64),
+        OpCode::LoadBool if instr.c != 0 => Some(next + 1),
+        OpCode::Eq | OpCode::Lt | OpCode::Le | OpCode::Test | OpCode::IsVariant => Some(next + 1),
+        _ => None,
+    }
+}
+
+/// Check that every register `instr` reads or writes is below `registers`,
+/// returning the first one that isn't.
+pub(crate) fn check_registers(instr: Instruction, registers: u16) -> Result<(), usize> {
+    let a = instr.a as usize;
+    let b = instr.b as usize;
+    let c = instr.c as usize;
+    let one = |reg: usize| {
+        if reg < registers as usize {
+            Ok(())
+        } else {
+            Err(reg)
+        }
+    };
+    let span = |start: usize, len: usize| {
+        if len == 0 {
+            Ok(())
+        } else {
+            one(start.saturating_add(len - 1))
+        }
+    };
+
+    match instr.op {
+        OpCode::Nop | OpCode::Jmp | OpCode::Break | OpCode::Continue => Ok(()),
+
+        OpCode::LoadK
+        | OpCode::LoadBool
+        | OpCode::LoadInt
+        | OpCode::NewRecord
+        | OpCode::Test
+        | OpCode::Return
+        | OpCode::Halt
+        | OpCode::Loop
+        | OpCode::Closure
+        | OpCode::Schema
+        | OpCode::Emit
+        | OpCode::TraceRef
+        | OpCode::Spawn
+        | OpCode::IsVariant => one(a),
+
+        OpCode::LoadNil => span(a, b + 1),
+
+        OpCode::Move
+        | OpCode::MoveOwn
+        | OpCode::Neg
+        | OpCode::BitNot
+        | OpCode::Not
+        | OpCode::Append
+        | OpCode::Unbox => {
+            one(a)?;
+            one(b)
+        }
+
+        OpCode::NewList | OpCode::NewTuple | OpCode::NewSet => {
+            one(a)?;
+            span(a + 1, b)
+        }
+
+        OpCode::NewMap => {
+            one(a)?;
+            span(a + 1, b.saturating_mul(2))
+        }
+
+        OpCode::GetField
+        | OpCode::GetIndex
+        | OpCode::GetTuple
+        | OpCode::Add
+        | OpCode::Sub
+        | OpCode::Mul
+        | OpCode::Div
+        | OpCode::FloorDiv
+        | OpCode::Mod
+        | OpCode::Pow
+        | OpCode::Concat
+        | OpCode::BitOr
+        | OpCode::BitAnd
+        | OpCode::BitXor
+        | OpCode::Shl
+        | OpCode::Shr
+        | OpCode::Eq
+        | OpCode::Lt
+        | OpCode::Le
+        | OpCode::And
+        | OpCode::Or
+        | OpCode::In
+        | OpCode::Is
+        | OpCode::NullCo
+        | OpCode::SetIndex
+        | OpCode::NewUnion
+        | OpCode::Await => {
+            one(a)?;
+            one(b)?;
+            one(c)
+        }
+
+        OpCode::SetField | OpCode::SetUpval => {
+            one(a)?;
+            one(c)
+        }
+
+        OpCode::ForPrep => span(a, 3),
+
+        OpCode::ForLoop => span(a, 4),
+
+        OpCode::ForIn => {
+            one(a)?;
+            one(a + 1)?;
+            one(b)?;
+            one(c)
+        }
+
+        OpCode::Call | OpCode::TailCall => {
+            one(a)?;
+            span(a + 1, b)
+        }
+
+        OpCode::Intrinsic => {
+            one(a)?;
+            one(c)
+        }
+
+        OpCode::GetUpval => one(a),
+
+        OpCode::ToolCall => one(a),
+
+        OpCode::Perform => one(a),
+        OpCode::HandlePush | OpCode::HandlePop => Ok(()),
+        OpCode::Resume => one(a),
+        OpCode::Extension => {
+            one(a)?;
+            one(c)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lumen_compiler::compiler::lir::LirParam;
+
+    fn module_with(cell: LirCell) -> LirModule {
+        let mut module = LirModule::new("test".into());
+        module.cells.push(cell);
+        module
+    }
+
+    fn cell(registers: u16, instructions: Vec<Instruction>) -> LirCell {
+        LirCell {
+            name: "main".into(),
+            params: Vec::new(),
+            returns: None,
+            registers,
+            constants: vec![Constant::Int(1)],
+            instructions,
+            effect_handler_metas: Vec::new(),
+            memoizable: false,
+        }
+    }
+
+    #[test]
+    fn compiled_programs_verify() {
+        let md = r#"# ok
+
+```lumen
+effect Log
+  cell write(msg: String) -> Null
+end
+
+cell total(items: list[Int]) -> Int
+  let sum = 0
+  for x in items
+    if x > 1
+      sum = sum + x
+    end
+  end
+  return sum
+end
+
+cell main() -> Int
+  let add = fn(a: Int, b: Int) -> Int => a + b
+  let n = add(1, 2) + total([1, 2, 3])
+  let r = handle
+    perform Log.write("hi")
+    n
+  with
+    Log.write(msg) =>
+      resume(null)
+  end
+  match n
+    6 -> return r
+    _ -> return 0
+  end
+end
+```
+"#;
+        let module = lumen_compiler::compile(md).expect("program should compile");
+        assert_eq!(verify(&module), Ok(()));
+    }
+
+    #[test]
+    fn reports_out_of_range_register() {
+        let module = module_with(cell(
+            2,
+            vec![
+                Instruction::abc(OpCode::Add, 0, 1, 5),
+                Instruction::abc(OpCode::Return, 0, 1, 0),
+            ],
+        ));
+        assert_eq!(
+            verify(&module),
+            Err(vec![VerifyError::RegisterOutOfRange {
+                cell: "main".into(),
+                pc: 0,
+                register: 5,
+                registers: 2,
+            }])
+        );
+    }
+
+    #[test]
+    fn reports_bad_jump_target() {
+        let module = module_with(cell(
+            1,
+            vec![
+                Instruction::sax(OpCode::Jmp, 10),
+                Instruction::abc(OpCode::Return, 0, 1, 0),
+            ],
+        ));
+        assert_eq!(
+            verify(&module),
+            Err(vec![VerifyError::JumpOutOfRange {
+                cell: "main".into(),
+                pc: 0,
+                target: 11,
+                len: 2,
+            }])
+        );
+    }
+
+    #[test]
+    fn jump_to_end_is_an_implicit_return() {
+        let module = module_with(cell(
+            1,
+            vec![
+                Instruction::sax(OpCode::Jmp, 1),
+                Instruction::abc(OpCode::Return, 0, 1, 0),
+            ],
+        ));
+        assert_eq!(verify(&module), Ok(()));
+    }
+
+    #[test]
+    fn reports_constant_and_call_window_problems() {
+        let module = module_with(cell(
+            4,
+            vec![
+                Instruction::abx(OpCode::LoadK, 0, 3),
+                Instruction::abc(OpCode::Call, 0, 4, 1),
+                Instruction::abc(OpCode::Perform, 0, 0, 0),
+            ],
+        ));
+        let errors = verify(&module).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                VerifyError::ConstantOutOfRange {
+                    cell: "main".into(),
+                    pc: 0,
+                    index: 3,
+                    len: 1,
+                },
+                VerifyError::RegisterOutOfRange {
+                    cell: "main".into(),
+                    pc: 1,
+                    register: 4,
+                    registers: 4,
+                },
+                VerifyError::ConstantType {
+                    cell: "main".into(),
+                    pc: 2,
+                    index: 0,
+                    expected: "string",
+                },
+                VerifyError::ConstantType {
+                    cell: "main".into(),
+                    pc: 2,
+                    index: 0,
+                    expected: "string",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_parameter_problems() {
+        let mut bad = cell(1, vec![Instruction::abc(OpCode::Return, 0, 1, 0)]);
+        bad.params = vec![
+            LirParam {
+                name: "rest".into(),
+                ty: "list[Int]".into(),
+                register: 0,
+                variadic: true,
+            },
+            LirParam {
+                name: "x".into(),
+                ty: "Int".into(),
+                register: 1,
+                variadic: false,
+            },
+        ];
+        let errors = verify(&module_with(bad)).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                VerifyError::VariadicNotLast {
+                    cell: "main".into(),
+                    param: "rest".into(),
+                },
+                VerifyError::ParamOutOfRange {
+                    cell: "main".into(),
+                    param: "x".into(),
+                    register: 1,
+                    registers: 1,
+                },
+            ]
+        );
+    }
+}
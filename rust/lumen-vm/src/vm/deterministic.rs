@@ -0,0 +1,167 @@
+//! Deterministic execution mode for reproducible runs.
+//!
+//! With deterministic mode on, every source of nondeterminism the VM controls
+//! is pinned down:
+//!
+//! - `random`, `random_int` and `uuid` draw from one RNG seeded by the caller,
+//!   so the same seed produces the same values in the same order.
+//! - Futures run on the `DeferredFifo` schedule, so spawn order fixes
+//!   execution order.
+//! - Map, set and record iteration is already ordered by key.
+//!
+//! - The `crypto.uuid` and `crypto.random_int` tools are answered from the
+//!   same RNG instead of being dispatched, and tools that compute a pure
+//!   function of their arguments (hashes, encodings, verification) dispatch
+//!   as usual.
+//!
+//! Operations whose results come from outside the VM and can't be seeded
+//! (wall-clock reads and every other tool call) are rejected with a runtime
+//! error. A program that needs them can declare the effect and have a
+//! handler supply the value, which keeps it replayable.
+
+use super::*;
+
+/// Tools whose output depends only on their arguments.
+const PURE_TOOLS: &[&str] = &[
+    "crypto.sha256",
+    "crypto.sha512",
+    "crypto.sha3_256",
+    "crypto.sha3_512",
+    "crypto.blake3",
+    "crypto.md5",
+    "crypto.base64_encode",
+    "crypto.base64_decode",
+    "crypto.hmac_sha256",
+    "crypto.hmac_sha256_verify",
+    "crypto.argon2_verify",
+    "crypto.aes_gcm_decrypt",
+];
+
+/// Tools whose only nondeterminism is randomness the seeded RNG can supply.
+const SEEDED_TOOLS: &[&str] = &["crypto.uuid", "crypto.random_int"];
+
+impl VM {
+    /// Enable deterministic mode with the given RNG seed.
+    ///
+    /// This also selects [`FutureSchedule::DeferredFifo`], overriding any
+    /// `@deterministic` directive or earlier [`VM::set_future_schedule`].
+    pub fn set_deterministic(&mut self, seed: u64) {
+        self.deterministic_seed = Some(seed);
+        self.rng_state = initial_rng_state(seed);
+        self.set_future_schedule(FutureSchedule::DeferredFifo);
+    }
+
+    /// Whether deterministic mode is on.
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic_seed.is_some()
+    }
+
+    /// Restart the seeded RNG, so reloading a module replays the same values.
+    pub(crate) fn reset_deterministic_rng(&mut self) {
+        if let Some(seed) = self.deterministic_seed {
+            self.rng_state = initial_rng_state(seed);
+        }
+    }
+
+    /// Next value from the seeded RNG, or `None` outside deterministic mode.
+    pub(crate) fn next_seeded_u64(&mut self) -> Option<u64> {
+        self.deterministic_seed?;
+        let mut s = self.rng_state;
+        s ^= s << 13;
+        s ^= s >> 7;
+        s ^= s << 17;
+        self.rng_state = s;
+        Some(s)
+    }
+
+    /// A v4 UUID built from the seeded RNG, or `None` outside deterministic
+    /// mode.
+    pub(crate) fn seeded_uuid(&mut self) -> Option<uuid::Uuid> {
+        let hi = self.next_seeded_u64()?;
+        let lo = self.next_seeded_u64()?;
+        let bytes = ((hi as u128) << 64 | lo as u128).to_be_bytes();
+        Some(uuid::Builder::from_random_bytes(bytes).into_uuid())
+    }
+
+    /// A seeded integer in `min..=max`, or `None` outside deterministic mode.
+    /// The caller checks `min <= max`.
+    pub(crate) fn seeded_int_in(&mut self, min: i64, max: i64) -> Option<i64> {
+        let range = (max as i128 - min as i128 + 1) as u128;
+        let s = self.next_seeded_u64()?;
+        Some((min as i128 + (s as u128 % range) as i128) as i64)
+    }
+
+    /// Reject a call to `tool_id` if deterministic mode is on and the tool's
+    /// result can't be reproduced from its arguments and the seed.
+    pub(crate) fn check_deterministic_tool(
+        &self,
+        tool_id: &str,
+        alias: &str,
+    ) -> Result<(), VmError> {
+        if PURE_TOOLS.contains(&tool_id) || SEEDED_TOOLS.contains(&tool_id) {
+            return Ok(());
+        }
+        self.check_deterministic(&format!("tool '{}'", alias))
+    }
+
+    /// In deterministic mode, the output of a seeded tool call drawn from the
+    /// seeded RNG instead of the provider. `None` means dispatch as usual.
+    pub(crate) fn seeded_tool_output(
+        &mut self,
+        tool_id: &str,
+        args: &serde_json::Value,
+    ) -> Option<Result<serde_json::Value, String>> {
+        if !self.is_deterministic() {
+            return None;
+        }
+        match tool_id {
+            "crypto.uuid" => self
+                .seeded_uuid()
+                .map(|id| Ok(serde_json::Value::String(id.to_string()))),
+            "crypto.random_int" => {
+                let bound = |name: &str| {
+                    args.get(name)
+                        .and_then(serde_json::Value::as_i64)
+                        .ok_or_else(|| {
+                            format!("Invalid input format: '{}' must be an integer", name)
+                        })
+                };
+                let (min, max) = match (bound("min"), bound("max")) {
+                    (Ok(min), Ok(max)) => (min, max),
+                    (Err(e), _) | (_, Err(e)) => return Some(Err(e)),
+                };
+                if min > max {
+                    return Some(Err("min must be less than or equal to max".to_string()));
+                }
+                self.seeded_int_in(min, max)
+                    .map(|n| Ok(serde_json::Value::from(n)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Reject `operation` if deterministic mode is on.
+    pub(crate) fn check_deterministic(&self, operation: &str) -> Result<(), VmError> {
+        if self.is_deterministic() {
+            return Err(VmError::Runtime(format!(
+                "{} is nondeterministic and unavailable in deterministic mode",
+                operation
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Spread `seed` into a nonzero xorshift state (splitmix64 finalizer), so
+/// small seeds like 0 and 1 still produce well-mixed sequences.
+fn initial_rng_state(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    if z == 0 {
+        1
+    } else {
+        z
+    }
+}
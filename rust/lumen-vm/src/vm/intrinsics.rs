@@ -727,10 +727,11 @@ impl VM {
                 Ok(Value::String(StringRef::Owned(h)))
             }
             "uuid" | "uuid_v4" => {
-                let id = uuid::Uuid::new_v4().to_string();
-                Ok(Value::String(StringRef::Owned(id)))
+                let id = self.seeded_uuid().unwrap_or_else(uuid::Uuid::new_v4);
+                Ok(Value::String(StringRef::Owned(id.to_string())))
             }
             "timestamp" => {
+                self.check_deterministic("timestamp")?;
                 let dur = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap_or_default();
@@ -1185,6 +1186,9 @@ impl VM {
             }
             // Random
             "random" => {
+                if let Some(s) = self.next_seeded_u64() {
+                    return Ok(Value::Float((s >> 11) as f64 / ((1u64 << 53) as f64)));
+                }
                 use std::cell::Cell;
                 thread_local! {
                     static RNG_STATE: Cell<u64> = const { Cell::new(0) };
@@ -1368,6 +1372,10 @@ impl VM {
                         min_val, max_val
                     )));
                 }
+                if let Some(n) = self.seeded_int_in(min_val, max_val) {
+                    return Ok(Value::Int(n));
+                }
+                let range = (max_val - min_val + 1) as u64;
                 use std::cell::Cell;
                 thread_local! {
                     static RNG_STATE_INT: Cell<u64> = const { Cell::new(0) };
@@ -1387,7 +1395,6 @@ impl VM {
                     s ^= s >> 7;
                     s ^= s << 17;
                     state.set(s);
-                    let range = (max_val - min_val + 1) as u64;
                     let result = min_val + (s % range) as i64;
                     Ok(Value::Int(result))
                 })
@@ -2093,6 +2100,7 @@ impl VM {
                 }
             }
             "hrtime" => {
+                self.check_deterministic("hrtime")?;
                 use std::sync::OnceLock;
                 use std::time::Instant;
                 static EPOCH: OnceLock<Instant> = OnceLock::new();
@@ -2177,9 +2185,7 @@ impl VM {
         closure: &ClosureValue,
         args: &[Value],
     ) -> Result<Value, VmError> {
-        if self.frames.len() >= MAX_CALL_DEPTH {
-            return Err(VmError::StackOverflow(MAX_CALL_DEPTH));
-        }
+        self.check_call_depth()?;
         let cv = closure.clone();
         let module = self.module.as_ref().ok_or(VmError::NoModule)?;
         if cv.cell_idx >= module.cells.len() {
@@ -2235,6 +2241,7 @@ impl VM {
             ip: 0,
             return_register: new_base, // result will be written here
             future_id: None,
+            memo: None,
         });
         // Run the VM until this frame returns
         self.run_until(self.frames.len().saturating_sub(1))?;
@@ -3761,6 +3768,7 @@ impl VM {
             }
             133 => {
                 // HRTIME: high-resolution monotonic timer in nanoseconds
+                self.check_deterministic("hrtime")?;
                 use std::sync::OnceLock;
                 use std::time::Instant;
                 static EPOCH: OnceLock<Instant> = OnceLock::new();
@@ -1,6 +1,7 @@
 //! Register VM dispatch loop for executing LIR bytecode.
 
 pub mod continuations;
+mod deterministic;
 mod helpers;
 mod intrinsics;
 mod ops;
@@ -11,13 +12,15 @@ pub(crate) use processes::{
     MachineExpr, MachineGraphDef, MachineParamDef, MachineRuntime, MachineStateDef, MemoryRuntime,
 };
 
-use crate::jit_tier::{JitTier, JitTierConfig};
+use crate::jit_tier::{JitConfig, JitTier, JitTierConfig};
+use crate::memo::{MemoCache, MemoConfig, MemoPending, MemoProbe, MemoStats};
 use crate::strings::StringTable;
 use crate::types::{RuntimeField, RuntimeType, RuntimeTypeKind, RuntimeVariant, TypeTable};
 use crate::values::{
     values_equal, ClosureValue, FutureStatus, FutureValue, RecordValue, StringRef, TraceRefValue,
     UnionValue, Value,
 };
+use crate::verify::VerifyError;
 use crate::vm::ops::BinaryOp;
 use lumen_compiler::compiler::lir::*;
 
@@ -74,8 +77,8 @@ pub enum VmError {
     Runtime(String),
     #[error("halt: {0}")]
     Halt(String),
-    #[error("stack overflow: call depth exceeded {0}")]
-    StackOverflow(usize),
+    #[error("stack overflow: call depth exceeded {depth}")]
+    StackOverflow { depth: usize },
     #[error("undefined cell: {0}")]
     UndefinedCell(String),
     #[error("register out of bounds: r{0} in cell with {1} registers")]
@@ -94,11 +97,23 @@ pub enum VmError {
     InstructionLimitExceeded(u64),
     #[error("register out of bounds: {0}")]
     RegisterOutOfBounds(usize),
+    #[error("unhandled effect: {effect}.{operation}")]
+    UnhandledEffect { effect: String, operation: String },
+    #[error("effect budget exceeded for '{effect}': limit {limit} reached")]
+    BudgetExceeded { effect: String, limit: u32 },
+    #[error("internal error in cell '{cell}' at instruction {ip}: {message}")]
+    InternalError {
+        message: String,
+        cell: String,
+        ip: usize,
+    },
     #[error("{message}\nStack trace (most recent call last):{stack_trace}")]
     WithStackTrace {
         message: String,
         stack_trace: String,
         frames: Vec<StackFrame>,
+        /// The error the trace was attached to.
+        error: Box<VmError>,
     },
 }
 
@@ -125,6 +140,7 @@ impl VmError {
             message,
             stack_trace: trace,
             frames,
+            error: Box::new(self),
         }
     }
 
@@ -177,6 +193,16 @@ impl VmError {
         }
     }
 
+    /// The call depth limit that was hit, if the underlying error is a
+    /// StackOverflow (works through WithStackTrace wrapper).
+    pub fn stack_overflow_depth(&self) -> Option<usize> {
+        match self {
+            VmError::StackOverflow { depth } => Some(*depth),
+            VmError::WithStackTrace { error, .. } => error.stack_overflow_depth(),
+            _ => None,
+        }
+    }
+
     /// Check if the underlying error is a RegisterOOB (works through WithStackTrace wrapper).
     pub fn is_register_oob(&self) -> bool {
         match self {
@@ -234,8 +260,6 @@ impl VmError {
     }
 }
 
-const MAX_CALL_DEPTH: usize = 4096;
-
 /// Call frame on the VM stack.
 #[derive(Debug, Clone)]
 pub(crate) struct CallFrame {
@@ -244,6 +268,26 @@ pub(crate) struct CallFrame {
     pub(crate) ip: usize,
     pub(crate) return_register: usize,
     pub(crate) future_id: Option<u64>,
+    /// Pending memo entry for a `@pure` call that missed the cache; the
+    /// return value is recorded under it when this frame returns.
+    pub(crate) memo: Option<Box<MemoPending>>,
+}
+
+/// Instruction pointer of the dispatch loop. The loop keeps it out of the
+/// frame stack between calls; if an opcode handler panics, dropping it
+/// during unwinding hands the position to [`VM::run_until`].
+struct DispatchSite<'a> {
+    ip: usize,
+    fault_ip: &'a mut Option<usize>,
+}
+
+impl Drop for DispatchSite<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            // `ip` has already been advanced past the faulting instruction.
+            *self.fault_ip = Some(self.ip.saturating_sub(1));
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -285,6 +329,8 @@ pub(crate) struct EffectScope {
     pub effect_name: String,
     /// The operation name this handler matches (e.g. "log")
     pub operation: String,
+    /// Depth of the continuation stack when the handle block was entered.
+    pub continuation_depth: usize,
 }
 
 /// A suspended continuation for algebraic effects (one-shot).
@@ -297,6 +343,11 @@ pub(crate) struct SuspendedContinuation {
     #[allow(dead_code)]
     pub resume_frame_count: usize,
     pub result_reg: usize,
+    /// Handler stack at the `perform`, reinstated on resume.
+    pub handlers: Vec<EffectScope>,
+    /// Continuation stack depth when the handling block was entered, restored
+    /// if the handler finishes without resuming.
+    pub scope_depth: usize,
 }
 
 /// The Lumen register VM.
@@ -326,9 +377,19 @@ pub struct VM {
     pub(crate) process_configs: BTreeMap<String, BTreeMap<String, Value>>,
     pub(crate) await_fuel: u32,
     pub(crate) effect_handlers: Vec<EffectScope>,
-    pub(crate) suspended_continuation: Option<SuspendedContinuation>,
+    /// Continuations awaiting `resume`, innermost last.
+    pub(crate) suspended_continuations: Vec<SuspendedContinuation>,
     pub(crate) max_instructions: u64,
     pub(crate) instruction_count: u64,
+    /// Maximum number of live call frames before calls fail with
+    /// [`VmError::StackOverflow`].
+    pub(crate) max_call_depth: usize,
+    /// Seed for deterministic mode; `None` when the mode is off.
+    pub(crate) deterministic_seed: Option<u64>,
+    /// Whether [`VM::try_load`] verifies modules before loading them.
+    pub(crate) verify_on_load: bool,
+    /// State of the seeded RNG used in deterministic mode.
+    pub(crate) rng_state: u64,
     /// Optional fuel counter. Each instruction decrements fuel by 1.
     /// When fuel hits 0, execution stops with a "fuel exhausted" error.
     pub(crate) fuel: Option<u64>,
@@ -337,6 +398,9 @@ pub struct VM {
     /// Effect budget tracking: maps effect name → (remaining_calls, original_limit).
     /// When a budget is set and remaining reaches 0, further calls are rejected.
     pub(crate) effect_budgets: HashMap<String, (u32, u32)>,
+    /// When true, an exhausted effect budget yields an `err(BudgetExceeded)`
+    /// value to the program instead of aborting execution.
+    pub(crate) recoverable_budgets: bool,
     /// Cache mapping cell names to their index in module.cells for O(1) dispatch.
     cell_index_cache: HashMap<String, usize>,
     /// Logical top of the register file. Registers beyond this index are unused.
@@ -345,6 +409,9 @@ pub struct VM {
     /// Tiered JIT compilation engine. Tracks call counts and compiles hot cells
     /// to native code via Cranelift.
     pub jit_tier: JitTier,
+    /// Bounded LRU cache of `@pure` cell results, keyed by structural
+    /// argument hash.
+    pub(crate) memo: MemoCache,
     /// Pre-interned tag IDs for common union tags ("ok", "err").
     pub tag_ok: u32,
     pub tag_err: u32,
@@ -352,6 +419,7 @@ pub struct VM {
 
 const MAX_AWAIT_RETRIES: u32 = 10_000;
 const DEFAULT_MAX_INSTRUCTIONS: u64 = 10_000_000_000;
+const DEFAULT_MAX_CALL_DEPTH: usize = 4096;
 
 impl VM {
     pub fn new() -> Self {
@@ -381,16 +449,22 @@ impl VM {
             process_configs: BTreeMap::new(),
             await_fuel: MAX_AWAIT_RETRIES,
             effect_handlers: Vec::new(),
-            suspended_continuation: None,
+            suspended_continuations: Vec::new(),
             max_instructions: DEFAULT_MAX_INSTRUCTIONS,
             instruction_count: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            deterministic_seed: None,
+            verify_on_load: true,
+            rng_state: 0,
             fuel: None,
             trace_id: None,
             trace_seq: 0,
             effect_budgets: HashMap::new(),
+            recoverable_budgets: false,
             cell_index_cache: HashMap::new(),
             register_top: 0,
             jit_tier: JitTier::disabled(),
+            memo: MemoCache::new(MemoConfig::default()),
             tag_ok,
             tag_err,
         }
@@ -414,11 +488,29 @@ impl VM {
     pub fn enable_jit(&mut self, threshold: u64) {
         self.jit_tier = JitTier::new(JitTierConfig {
             hot_threshold: threshold,
+            tier_up_threshold: threshold,
             enabled: true,
             ..Default::default()
         });
     }
 
+    /// Create a VM whose tiered JIT follows `config`.
+    ///
+    /// [`VM::new`] leaves the JIT off; this is the entry point for callers
+    /// that want to tune promotion thresholds or explicitly keep every cell
+    /// interpreted.
+    pub fn with_jit_config(config: JitConfig) -> Self {
+        let mut vm = Self::new();
+        vm.set_jit_config(config);
+        vm
+    }
+
+    /// Replace the tiered JIT's thresholds. Must be called before
+    /// [`VM::load`].
+    pub fn set_jit_config(&mut self, config: JitConfig) {
+        self.jit_tier = JitTier::new(config.into());
+    }
+
     /// Enable tiered JIT with full configuration.
     pub fn enable_jit_with_config(&mut self, config: JitTierConfig) {
         self.jit_tier = JitTier::new(config);
@@ -429,6 +521,27 @@ impl VM {
         self.jit_tier.tier_stats()
     }
 
+    /// Enable or disable memoization of `@pure` cell results.
+    ///
+    /// Memoization is on by default; turning it off is mainly useful for
+    /// benchmarking the uncached execution path.
+    pub fn set_memoization(&mut self, enabled: bool) {
+        self.memo.set_enabled(enabled);
+    }
+
+    /// Configure `@pure` cell memoization (enabled flag, LRU capacity and
+    /// minimum call cost).
+    pub fn set_memo_config(&mut self, config: MemoConfig) {
+        self.memo.set_enabled(config.enabled);
+        self.memo.set_capacity(config.capacity);
+        self.memo.set_min_reductions(config.min_reductions);
+    }
+
+    /// Get memoization statistics.
+    pub fn memo_stats(&self) -> MemoStats {
+        self.memo.stats()
+    }
+
     /// Grow register file for a new call frame. Returns the new base index.
     /// Uses the `register_top` watermark to avoid unnecessary resize/truncate.
     #[inline(always)]
@@ -545,7 +658,31 @@ impl VM {
         }
     }
 
-    /// Load a LIR module into the VM.
+    /// Verify a LIR module and load it.
+    ///
+    /// [`verify`](crate::verify::verify) rejects out-of-range registers,
+    /// jump targets and table indices before they can reach the interpreter.
+    /// `lumen run` loads through here; use it for any module that did not
+    /// come straight from the compiler, such as LIR read from disk.
+    /// Verification can be turned off with [`VM::set_verify_on_load`], in
+    /// which case this is [`VM::load`].
+    pub fn try_load(&mut self, module: LirModule) -> Result<(), Vec<VerifyError>> {
+        if self.verify_on_load {
+            crate::verify::verify(&module)?;
+        }
+        self.load(module);
+        Ok(())
+    }
+
+    /// Choose whether [`VM::try_load`] verifies modules (default: on).
+    pub fn set_verify_on_load(&mut self, enabled: bool) {
+        self.verify_on_load = enabled;
+    }
+
+    /// Load a LIR module into the VM without verifying it.
+    ///
+    /// The module is trusted to be well formed, as compiler output is. See
+    /// [`VM::try_load`] for modules from elsewhere.
     pub fn load(&mut self, module: LirModule) {
         // Intern all strings
         for s in &module.strings {
@@ -566,9 +703,11 @@ impl VM {
         self.machine_graphs.clear();
         self.await_fuel = MAX_AWAIT_RETRIES;
         self.effect_handlers.clear();
-        self.suspended_continuation = None;
+        self.suspended_continuations.clear();
         self.instruction_count = 0;
         self.cell_index_cache.clear();
+        self.memo.clear();
+        self.reset_deterministic_rng();
         let mut machine_initials: BTreeMap<String, String> = BTreeMap::new();
         for addon in &module.addons {
             if let Some(name) = &addon.name {
@@ -741,6 +880,49 @@ impl VM {
         self.max_instructions = max_instructions;
     }
 
+    /// Set the maximum call depth (default 4096). A call that would push
+    /// frame number `depth + 1` fails with [`VmError::StackOverflow`] instead
+    /// of growing the stack, so runaway recursion is an ordinary error.
+    ///
+    /// Tail calls reuse their frame and never count against the limit.
+    /// Frames are heap-allocated, so large limits are safe for interpreted
+    /// code. Calls between JIT-compiled cells run on the native stack but
+    /// count against the same limit, so keep it within what the native
+    /// stack of the executing thread can hold.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// The current maximum call depth.
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// Fail with [`VmError::StackOverflow`] if another frame would exceed
+    /// the call depth limit.
+    #[inline]
+    pub(crate) fn check_call_depth(&self) -> Result<(), VmError> {
+        if self.frames.len() >= self.max_call_depth {
+            return Err(VmError::StackOverflow {
+                depth: self.max_call_depth,
+            });
+        }
+        Ok(())
+    }
+
+    /// How deeply calls between JIT-compiled cells may nest beneath a
+    /// compiled cell running as frame number `frame`.
+    fn jit_depth_budget(&self, frame: usize) -> usize {
+        self.max_call_depth.saturating_sub(frame)
+    }
+
+    /// The error for native recursion that ran out of call depth.
+    fn jit_stack_overflow(&self) -> VmError {
+        VmError::StackOverflow {
+            depth: self.max_call_depth,
+        }
+    }
+
     /// Set the fuel counter. Each executed instruction consumes one unit of fuel.
     /// When fuel reaches 0, execution stops with a "fuel exhausted" error.
     pub fn set_fuel(&mut self, fuel: u64) {
@@ -755,6 +937,35 @@ impl VM {
             .insert(effect.to_string(), (limit, limit));
     }
 
+    /// Make effect budget exhaustion recoverable.
+    ///
+    /// By default an exhausted budget aborts execution with
+    /// [`VmError::BudgetExceeded`]. When enabled, the rejected call instead
+    /// evaluates to `err(BudgetExceeded { effect, limit })`, which the program
+    /// can handle with `match`, `??` or `?` and continue on a fallback path.
+    pub fn set_recoverable_budgets(&mut self, enabled: bool) {
+        self.recoverable_budgets = enabled;
+    }
+
+    /// Build the `err(BudgetExceeded { effect, limit })` value handed to the
+    /// program when a budget is exhausted in recoverable mode.
+    fn budget_exceeded_value(&self, effect: &str, limit: u32) -> Value {
+        let mut fields = BTreeMap::new();
+        fields.insert(
+            "effect".to_string(),
+            Value::String(StringRef::Owned(effect.to_string())),
+        );
+        fields.insert("limit".to_string(), Value::Int(limit as i64));
+        let record = Value::new_record(RecordValue {
+            type_name: "BudgetExceeded".to_string(),
+            fields,
+        });
+        Value::Union(UnionValue {
+            tag: self.tag_err,
+            payload: Arc::new(record),
+        })
+    }
+
     /// Check (and decrement) the budget for `effect`.  Returns `Ok(())` when
     /// the call is allowed, or `Err(message)` when the budget is exhausted.
     pub fn check_effect_budget(&mut self, effect: &str) -> Result<(), String> {
@@ -824,21 +1035,6 @@ impl VM {
         }
     }
 
-    #[inline]
-    #[allow(dead_code)]
-    fn check_register_span(
-        &self,
-        start: usize,
-        len: usize,
-        cell_registers: u16,
-    ) -> Result<(), VmError> {
-        if len == 0 {
-            return Ok(());
-        }
-        let end = start.saturating_add(len - 1);
-        self.check_register(end, cell_registers)
-    }
-
     /// Copy call arguments into parameter registers, packing trailing args
     /// into a list for the variadic parameter (if any).
     fn copy_args_to_params(
@@ -894,117 +1090,8 @@ impl VM {
         instr: Instruction,
         cell_registers: u16,
     ) -> Result<(), VmError> {
-        let a = instr.a as usize;
-        let b = instr.b as usize;
-        let c = instr.c as usize;
-
-        match instr.op {
-            OpCode::Nop | OpCode::Jmp | OpCode::Break | OpCode::Continue => Ok(()),
-
-            OpCode::LoadK
-            | OpCode::LoadBool
-            | OpCode::LoadInt
-            | OpCode::NewRecord
-            | OpCode::Test
-            | OpCode::Return
-            | OpCode::Halt
-            | OpCode::Loop
-            | OpCode::Closure
-            | OpCode::Schema
-            | OpCode::Emit
-            | OpCode::TraceRef
-            | OpCode::Spawn
-            | OpCode::IsVariant => self.check_register(a, cell_registers),
-
-            OpCode::LoadNil => self.check_register_span(a, b + 1, cell_registers),
-
-            OpCode::Move
-            | OpCode::MoveOwn
-            | OpCode::Neg
-            | OpCode::BitNot
-            | OpCode::Not
-            | OpCode::Append
-            | OpCode::Unbox => {
-                self.check_register(a, cell_registers)?;
-                self.check_register(b, cell_registers)
-            }
-
-            OpCode::NewList | OpCode::NewTuple | OpCode::NewSet => {
-                self.check_register(a, cell_registers)?;
-                self.check_register_span(a + 1, b, cell_registers)
-            }
-
-            OpCode::NewMap => {
-                self.check_register(a, cell_registers)?;
-                self.check_register_span(a + 1, b.saturating_mul(2), cell_registers)
-            }
-
-            OpCode::GetField
-            | OpCode::GetIndex
-            | OpCode::GetTuple
-            | OpCode::Add
-            | OpCode::Sub
-            | OpCode::Mul
-            | OpCode::Div
-            | OpCode::FloorDiv
-            | OpCode::Mod
-            | OpCode::Pow
-            | OpCode::Concat
-            | OpCode::BitOr
-            | OpCode::BitAnd
-            | OpCode::BitXor
-            | OpCode::Shl
-            | OpCode::Shr
-            | OpCode::Eq
-            | OpCode::Lt
-            | OpCode::Le
-            | OpCode::And
-            | OpCode::Or
-            | OpCode::In
-            | OpCode::Is
-            | OpCode::NullCo
-            | OpCode::SetIndex
-            | OpCode::NewUnion
-            | OpCode::Await => {
-                self.check_register(a, cell_registers)?;
-                self.check_register(b, cell_registers)?;
-                self.check_register(c, cell_registers)
-            }
-
-            OpCode::SetField | OpCode::SetUpval => {
-                self.check_register(a, cell_registers)?;
-                self.check_register(c, cell_registers)
-            }
-
-            OpCode::ForPrep => self.check_register_span(a, 3, cell_registers),
-
-            OpCode::ForLoop => self.check_register_span(a, 4, cell_registers),
-
-            OpCode::ForIn => {
-                self.check_register(a, cell_registers)?;
-                self.check_register(a + 1, cell_registers)?;
-                self.check_register(b, cell_registers)?;
-                self.check_register(c, cell_registers)
-            }
-
-            OpCode::Call | OpCode::TailCall => {
-                self.check_register(a, cell_registers)?;
-                self.check_register_span(a + 1, b, cell_registers)
-            }
-
-            OpCode::Intrinsic => {
-                self.check_register(a, cell_registers)?;
-                self.check_register(c, cell_registers)
-            }
-
-            OpCode::GetUpval => self.check_register(a, cell_registers),
-
-            OpCode::ToolCall => self.check_register(a, cell_registers),
-
-            OpCode::Perform => self.check_register(a, cell_registers),
-            OpCode::HandlePush | OpCode::HandlePop => Ok(()),
-            OpCode::Resume => self.check_register(a, cell_registers),
-        }
+        crate::verify::check_registers(instr, cell_registers)
+            .or_else(|reg| self.check_register(reg, cell_registers))
     }
 
     fn ensure_process_instance(&mut self, value: &mut Value) {
@@ -1065,9 +1152,7 @@ impl VM {
     }
 
     fn start_future_task(&mut self, task: FutureTask) -> Result<(), VmError> {
-        if self.frames.len() >= MAX_CALL_DEPTH {
-            return Err(VmError::StackOverflow(MAX_CALL_DEPTH));
-        }
+        self.check_call_depth()?;
         let module = self.module.as_ref().ok_or(VmError::NoModule)?;
         match task.target {
             FutureTarget::Cell(cell_idx) => {
@@ -1099,6 +1184,7 @@ impl VM {
                     ip: 0,
                     return_register: 0,
                     future_id: Some(task.future_id),
+                    memo: None,
                 });
             }
             FutureTarget::Closure(cv) => {
@@ -1135,6 +1221,7 @@ impl VM {
                     ip: 0,
                     return_register: 0,
                     future_id: Some(task.future_id),
+                    memo: None,
                 });
             }
         }
@@ -1338,17 +1425,25 @@ impl VM {
         // Push initial frame
         self.instruction_count = 0;
         self.trace_seq = 0;
+        let entry_depth = self.frames.len();
+        let entry_handlers = self.effect_handlers.len();
         self.frames.push(CallFrame {
             cell_idx,
             base_register: base,
             ip: 0,
             return_register: 0,
             future_id: None,
+            memo: None,
         });
 
         // Execute
         self.run_until(0).map_err(|err| {
             let frames = self.capture_stack_trace();
+            // Unwind whatever the failed run left behind so the VM can
+            // execute again.
+            self.frames.truncate(entry_depth);
+            self.effect_handlers.truncate(entry_handlers);
+            self.shrink_registers(base.min(self.register_top));
             err.with_stack_trace(frames)
         })
     }
@@ -1378,7 +1473,42 @@ impl VM {
         }
     }
 
+    /// Run frames above `limit` until they return.
+    ///
+    /// Dispatch runs inside a panic boundary: a panic in an opcode handler
+    /// (or in host code it calls) becomes [`VmError::InternalError`] naming
+    /// the cell and instruction being executed, so only this execution fails
+    /// and the host process keeps running.
     pub fn run_until(&mut self, limit: usize) -> Result<Value, VmError> {
+        let mut at = None;
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.run_until_inner(limit, &mut at)
+        }));
+        outcome.unwrap_or_else(|payload| {
+            // Frames are kept in sync with the dispatch loop's current cell;
+            // only the instruction pointer lives in a local until unwinding.
+            let frame = self.frames.last();
+            let cell_idx = frame.map_or(0, |f| f.cell_idx);
+            let ip = at.or(frame.map(|f| f.ip)).unwrap_or(0);
+            let cell = self
+                .module
+                .as_ref()
+                .and_then(|m| m.cells.get(cell_idx))
+                .map(|c| c.name.clone())
+                .unwrap_or_else(|| format!("<unknown-cell-{}>", cell_idx));
+            Err(VmError::InternalError {
+                message: lumen_runtime::panic_boundary::PanicError::from_payload(payload)
+                    .message()
+                    .to_string(),
+                cell,
+                ip,
+            })
+        })
+    }
+
+    /// Dispatch loop behind [`Self::run_until`]. If an opcode handler
+    /// panics, `at` receives the instruction pointer it was dispatching.
+    fn run_until_inner(&mut self, limit: usize, at: &mut Option<usize>) -> Result<Value, VmError> {
         // --- Hot loop optimization ---
         // Cache module pointer to avoid Option check every instruction.
         // SAFETY: self.module is never modified during run_until execution.
@@ -1395,7 +1525,10 @@ impl VM {
         let frame = self.frames.last().unwrap();
         let mut cell_idx = frame.cell_idx;
         let mut base = frame.base_register;
-        let mut ip = frame.ip;
+        let mut site = DispatchSite {
+            ip: frame.ip,
+            fault_ip: at,
+        };
         let mut cell = &module.cells[cell_idx];
 
         // Pre-check: do we have debug or fuel active? Branch once, not per-instruction.
@@ -1408,7 +1541,7 @@ impl VM {
 
         loop {
             // Check if IP is past end of instructions (implicit return)
-            if ip >= cell.instructions.len() {
+            if site.ip >= cell.instructions.len() {
                 self.frames.pop();
                 if self.frames.len() <= limit {
                     return Ok(Value::Null);
@@ -1416,13 +1549,13 @@ impl VM {
                 let frame = self.frames.last().unwrap();
                 cell_idx = frame.cell_idx;
                 base = frame.base_register;
-                ip = frame.ip;
+                site.ip = frame.ip;
                 cell = &module.cells[cell_idx];
                 continue;
             }
 
-            let instr = cell.instructions[ip];
-            ip += 1;
+            let instr = cell.instructions[site.ip];
+            site.ip += 1;
 
             // Lightweight instruction counting — use local counter, sync periodically
             local_count += 1;
@@ -1432,7 +1565,7 @@ impl VM {
                 if self.instruction_count > self.max_instructions {
                     // Sync IP back before returning error
                     if let Some(f) = self.frames.last_mut() {
-                        f.ip = ip;
+                        f.ip = site.ip;
                     }
                     return Err(VmError::InstructionLimitExceeded(self.max_instructions));
                 }
@@ -1443,7 +1576,7 @@ impl VM {
                 if let Some(ref mut fuel) = self.fuel {
                     if *fuel == 0 {
                         if let Some(f) = self.frames.last_mut() {
-                            f.ip = ip;
+                            f.ip = site.ip;
                         }
                         return Err(VmError::Runtime("fuel exhausted".into()));
                     }
@@ -1456,7 +1589,7 @@ impl VM {
                 let cell_name = cell.name.clone();
                 self.emit_debug_event(DebugEvent::Step {
                     cell_name,
-                    ip: ip.wrapping_sub(1),
+                    ip: site.ip.wrapping_sub(1),
                     opcode: format!("{:?}", instr.op),
                 });
             }
@@ -1478,7 +1611,7 @@ impl VM {
             ) {
                 // Sync IP back to frame before dispatch (call/return needs it)
                 if let Some(f) = self.frames.last_mut() {
-                    f.ip = ip;
+                    f.ip = site.ip;
                 }
                 match instr.op {
                     OpCode::Call => {
@@ -1514,25 +1647,33 @@ impl VM {
                         };
 
                         if let Some(target_idx) = fast_cell_idx {
+                            // ─── MEMO: @pure cells answer repeats from cache ──
+                            let mut memo_pending = None;
+                            if module.cells[target_idx].memoizable {
+                                let args = &self.registers[base + a + 1..base + a + 1 + nargs];
+                                let reductions = self.instruction_count + local_count;
+                                match self.memo.probe(target_idx, args, &self.strings, reductions) {
+                                    MemoProbe::Hit(result) => {
+                                        self.registers[callee_reg] = result;
+                                        continue;
+                                    }
+                                    MemoProbe::Miss(pending) => memo_pending = Some(pending),
+                                    MemoProbe::Skip => {}
+                                }
+                            }
+                            // ─── END MEMO ────────────────────────────────────
+
                             // ─── JIT TIER: check if cell is compiled ─────────
                             // If the cell is already JIT-compiled, execute it as
                             // a native function pointer and skip the interpreter.
                             if self.jit_tier.is_enabled() {
-                                // Check if already compiled
-                                let run_jit = if self.jit_tier.is_compiled(target_idx) {
-                                    true
-                                } else if self.jit_tier.record_call(target_idx) {
-                                    // Just crossed hot threshold — try to compile
-                                    if self.jit_tier.check_eligibility(target_idx, module) {
-                                        self.jit_tier.try_compile(target_idx, module)
-                                    } else {
-                                        false
-                                    }
-                                } else {
-                                    false
-                                };
+                                // Compiles (or tiers up) the cell when it
+                                // crosses a threshold.
+                                let run_jit = self.jit_tier.prepare_call(target_idx, module);
 
                                 if run_jit {
+                                    // The callee takes a frame of its own.
+                                    self.check_call_depth()?;
                                     // Extract i64 args from registers.
                                     // Int → raw i64, Float → f64 bits as i64,
                                     // String → heap-clone as *mut String cast to i64
@@ -1577,8 +1718,11 @@ impl VM {
                                         }
                                     }
                                     if args_ok {
-                                        if let Some(result) =
-                                            self.jit_tier.execute(&callee_cell.name, &i64_args)
+                                        let budget = self.jit_depth_budget(self.frames.len() + 1);
+                                        if let Some(result) = self
+                                            .jit_tier
+                                            .execute(&callee_cell.name, &i64_args, budget)
+                                            .map_err(|_| self.jit_stack_overflow())?
                                         {
                                             // Check if the JIT function returns a string pointer.
                                             if self.jit_tier.returns_string(&callee_cell.name) {
@@ -1592,6 +1736,16 @@ impl VM {
                                             } else {
                                                 self.registers[callee_reg] = Value::Int(result);
                                             }
+                                            // Native code is not metered, so a
+                                            // JIT-compiled call cannot be judged
+                                            // cheap; always cache its result.
+                                            if let Some(pending) = memo_pending {
+                                                self.memo.insert(
+                                                    *pending,
+                                                    self.registers[callee_reg].clone(),
+                                                    u64::MAX,
+                                                );
+                                            }
                                             continue;
                                         }
                                     }
@@ -1601,9 +1755,7 @@ impl VM {
                             // ─── END JIT TIER ────────────────────────────────
 
                             // Fast path: direct cell call — no cloning, no dispatch_call overhead
-                            if self.frames.len() >= MAX_CALL_DEPTH {
-                                return Err(VmError::StackOverflow(MAX_CALL_DEPTH));
-                            }
+                            self.check_call_depth()?;
                             let callee_cell = &module.cells[target_idx];
                             let num_regs = callee_cell.registers as usize;
                             let new_base = self.grow_registers(num_regs.max(16));
@@ -1641,6 +1793,7 @@ impl VM {
                                 ip: 0,
                                 return_register: callee_reg,
                                 future_id: None,
+                                memo: memo_pending,
                             });
 
                             if has_debug {
@@ -1652,7 +1805,7 @@ impl VM {
                             // Reload frame state
                             cell_idx = target_idx;
                             base = new_base;
-                            ip = 0;
+                            site.ip = 0;
                             cell = &module.cells[cell_idx];
                             continue;
                         }
@@ -1667,7 +1820,7 @@ impl VM {
                                 let frame = self.frames.last().unwrap();
                                 cell_idx = frame.cell_idx;
                                 base = frame.base_register;
-                                ip = frame.ip;
+                                site.ip = frame.ip;
                                 cell = &module.cells[cell_idx];
                                 continue;
                             }
@@ -1677,59 +1830,61 @@ impl VM {
                         let frame = self.frames.last().unwrap();
                         cell_idx = frame.cell_idx;
                         base = frame.base_register;
-                        ip = frame.ip;
+                        site.ip = frame.ip;
                         cell = &module.cells[cell_idx];
                         continue;
                     }
                     OpCode::TailCall => {
-                        // ─── JIT TIER: TailCall fast path ────────────────
-                        // Same JIT dispatch as Call, but on success we
-                        // simulate Return (pop frame, write to caller)
-                        // instead of continuing in the current frame.
+                        // The current frame is reused for the callee, so
+                        // tail recursion runs in constant stack space. When
+                        // the callee finishes without a new frame (memo hit
+                        // or JIT), its result is returned from this frame.
                         let callee_reg = base + a;
                         let nargs = b;
-                        let mut jit_handled = false;
-
-                        if self.jit_tier.is_enabled() {
-                            // Resolve cell index from callee register
-                            let fast_cell_idx = match &self.registers[callee_reg] {
-                                Value::String(sr) => {
-                                    let name_str = match sr {
-                                        StringRef::Owned(s) => s.as_str(),
-                                        StringRef::Interned(id) => {
-                                            self.strings.resolve(*id).unwrap_or("")
-                                        }
-                                    };
-                                    if name_str == cell.name {
-                                        Some(cell_idx)
-                                    } else if let Some(&cached) =
-                                        self.cell_index_cache.get(name_str)
-                                    {
-                                        Some(cached)
-                                    } else if let Some(idx) =
-                                        module.cells.iter().position(|c| c.name == name_str)
-                                    {
-                                        self.cell_index_cache.insert(name_str.to_string(), idx);
-                                        Some(idx)
-                                    } else {
-                                        None
+                        let fast_cell_idx = match &self.registers[callee_reg] {
+                            Value::String(sr) => {
+                                let name_str = match sr {
+                                    StringRef::Owned(s) => s.as_str(),
+                                    StringRef::Interned(id) => {
+                                        self.strings.resolve(*id).unwrap_or("")
                                     }
+                                };
+                                if name_str == cell.name {
+                                    Some(cell_idx)
+                                } else if let Some(&cached) = self.cell_index_cache.get(name_str) {
+                                    Some(cached)
+                                } else if let Some(idx) =
+                                    module.cells.iter().position(|c| c.name == name_str)
+                                {
+                                    self.cell_index_cache.insert(name_str.to_string(), idx);
+                                    Some(idx)
+                                } else {
+                                    None
                                 }
-                                _ => None,
-                            };
+                            }
+                            _ => None,
+                        };
+                        let mut tail_result: Option<Value> = None;
 
+                        // ─── MEMO: @pure callee ──────────────────────────
+                        let mut memo_pending = None;
+                        if let Some(target_idx) = fast_cell_idx {
+                            if module.cells[target_idx].memoizable {
+                                let args = &self.registers[base + a + 1..base + a + 1 + nargs];
+                                let reductions = self.instruction_count + local_count;
+                                match self.memo.probe(target_idx, args, &self.strings, reductions) {
+                                    MemoProbe::Hit(result) => tail_result = Some(result),
+                                    MemoProbe::Miss(pending) => memo_pending = Some(pending),
+                                    MemoProbe::Skip => {}
+                                }
+                            }
+                        }
+                        // ─── END MEMO ────────────────────────────────────
+
+                        // ─── JIT TIER: TailCall fast path ────────────────
+                        if tail_result.is_none() && self.jit_tier.is_enabled() {
                             if let Some(target_idx) = fast_cell_idx {
-                                let run_jit = if self.jit_tier.is_compiled(target_idx) {
-                                    true
-                                } else if self.jit_tier.record_call(target_idx) {
-                                    if self.jit_tier.check_eligibility(target_idx, module) {
-                                        self.jit_tier.try_compile(target_idx, module)
-                                    } else {
-                                        false
-                                    }
-                                } else {
-                                    false
-                                };
+                                let run_jit = self.jit_tier.prepare_call(target_idx, module);
 
                                 if run_jit {
                                     let callee_cell = &module.cells[target_idx];
@@ -1769,8 +1924,12 @@ impl VM {
                                         }
                                     }
                                     if args_ok {
-                                        if let Some(result) =
-                                            self.jit_tier.execute(&callee_cell.name, &i64_args)
+                                        // The callee replaces the current frame.
+                                        let budget = self.jit_depth_budget(self.frames.len());
+                                        if let Some(result) = self
+                                            .jit_tier
+                                            .execute(&callee_cell.name, &i64_args, budget)
+                                            .map_err(|_| self.jit_stack_overflow())?
                                         {
                                             // Convert i64 result back to Value
                                             let result_value = if self
@@ -1784,51 +1943,16 @@ impl VM {
                                             } else {
                                                 Value::Int(result)
                                             };
-
-                                            // TailCall JIT success: simulate Return.
-                                            // Pop current frame and write result to caller.
-                                            let frame = self.frames.pop().ok_or_else(|| {
-                                                VmError::Runtime(
-                                                    "call stack underflow on tailcall JIT".into(),
-                                                )
-                                            })?;
-
-                                            if has_debug {
-                                                let cname =
-                                                    module.cells[frame.cell_idx].name.clone();
-                                                self.emit_debug_event(DebugEvent::CallExit {
-                                                    cell_name: cname,
-                                                    result: result_value.clone(),
-                                                });
-                                            }
-
-                                            self.shrink_registers(frame.base_register);
-
-                                            if let Some(fid) = frame.future_id {
-                                                self.future_states.insert(
-                                                    fid,
-                                                    FutureState::Completed(result_value),
+                                            // Native code is not metered; always
+                                            // cache its result.
+                                            if let Some(pending) = memo_pending.take() {
+                                                self.memo.insert(
+                                                    *pending,
+                                                    result_value.clone(),
+                                                    u64::MAX,
                                                 );
-                                                if self.frames.len() <= limit {
-                                                    return Ok(Value::Null);
-                                                }
-                                                let f = self.frames.last().unwrap();
-                                                cell_idx = f.cell_idx;
-                                                base = f.base_register;
-                                                ip = f.ip;
-                                                cell = &module.cells[cell_idx];
-                                                continue;
-                                            }
-                                            if self.frames.len() <= limit {
-                                                return Ok(result_value);
                                             }
-                                            self.registers[frame.return_register] = result_value;
-                                            let f = self.frames.last().unwrap();
-                                            cell_idx = f.cell_idx;
-                                            base = f.base_register;
-                                            ip = f.ip;
-                                            cell = &module.cells[cell_idx];
-                                            jit_handled = true;
+                                            tail_result = Some(result_value);
                                         }
                                     }
                                     // JIT execution failed — fall through to interpreter
@@ -1837,7 +1961,50 @@ impl VM {
                         }
                         // ─── END JIT TIER (TailCall) ─────────────────────
 
-                        if jit_handled {
+                        if let Some(result_value) = tail_result {
+                            // Simulate Return: pop the current frame and
+                            // hand the result to its caller.
+                            let frame = self.frames.pop().ok_or_else(|| {
+                                VmError::Runtime("call stack underflow on tailcall".into())
+                            })?;
+                            self.memo.complete(
+                                frame.memo,
+                                &result_value,
+                                self.instruction_count + local_count,
+                            );
+
+                            if has_debug {
+                                let cname = module.cells[frame.cell_idx].name.clone();
+                                self.emit_debug_event(DebugEvent::CallExit {
+                                    cell_name: cname,
+                                    result: result_value.clone(),
+                                });
+                            }
+
+                            self.shrink_registers(frame.base_register);
+
+                            if let Some(fid) = frame.future_id {
+                                self.future_states
+                                    .insert(fid, FutureState::Completed(result_value));
+                                if self.frames.len() <= limit {
+                                    return Ok(Value::Null);
+                                }
+                                let f = self.frames.last().unwrap();
+                                cell_idx = f.cell_idx;
+                                base = f.base_register;
+                                site.ip = f.ip;
+                                cell = &module.cells[cell_idx];
+                                continue;
+                            }
+                            if self.frames.len() <= limit {
+                                return Ok(result_value);
+                            }
+                            self.registers[frame.return_register] = result_value;
+                            let f = self.frames.last().unwrap();
+                            cell_idx = f.cell_idx;
+                            base = f.base_register;
+                            site.ip = f.ip;
+                            cell = &module.cells[cell_idx];
                             continue;
                         }
 
@@ -1849,18 +2016,28 @@ impl VM {
                                 let frame = self.frames.last().unwrap();
                                 cell_idx = frame.cell_idx;
                                 base = frame.base_register;
-                                ip = frame.ip;
+                                site.ip = frame.ip;
                                 cell = &module.cells[cell_idx];
                                 continue;
                             }
                             return Err(err);
                         }
-                        // Reload frame state after tailcall (frame reused)
-                        let frame = self.frames.last().unwrap();
+                        // Reload frame state after tailcall (frame reused).
+                        // The reused frame now returns for both the caller
+                        // and the callee, so it carries both memo entries.
+                        let frame = self.frames.last_mut().unwrap();
+                        if let Some(pending) = memo_pending {
+                            frame.memo = Some(pending.with_outer(frame.memo.take()));
+                        }
                         cell_idx = frame.cell_idx;
                         base = frame.base_register;
-                        ip = frame.ip;
+                        site.ip = frame.ip;
                         cell = &module.cells[cell_idx];
+                        if has_debug && fast_cell_idx.is_some() {
+                            self.emit_debug_event(DebugEvent::CallEnter {
+                                cell_name: cell.name.clone(),
+                            });
+                        }
                         continue;
                     }
                     OpCode::Intrinsic => {
@@ -1874,7 +2051,7 @@ impl VM {
                                     let frame = self.frames.last().unwrap();
                                     cell_idx = frame.cell_idx;
                                     base = frame.base_register;
-                                    ip = frame.ip;
+                                    site.ip = frame.ip;
                                     cell = &module.cells[cell_idx];
                                     continue;
                                 }
@@ -1914,7 +2091,7 @@ impl VM {
                 OpCode::LoadBool => {
                     self.registers[base + a] = Value::Bool(b != 0);
                     if c != 0 {
-                        ip += 1;
+                        site.ip += 1;
                     }
                 }
                 OpCode::LoadInt => {
@@ -2519,14 +2696,14 @@ impl VM {
                 OpCode::Test => {
                     let truthy = self.value_is_truthy(&self.registers[base + a]);
                     if truthy != (c != 0) {
-                        ip += 1;
+                        site.ip += 1;
                     }
                 }
 
                 // Control flow
                 OpCode::Jmp => {
                     let offset = instr.sax_val();
-                    ip = (ip as i32 + offset) as usize;
+                    site.ip = (site.ip as i32 + offset) as usize;
                 }
                 // Call and TailCall are handled in the pre-match above
                 OpCode::Call | OpCode::TailCall => {
@@ -2542,6 +2719,12 @@ impl VM {
                         .pop()
                         .ok_or_else(|| VmError::Runtime("call stack underflow".into()))?;
 
+                    self.memo.complete(
+                        frame.memo,
+                        &return_val,
+                        self.instruction_count + local_count,
+                    );
+
                     if has_debug {
                         let cell_name = module.cells[frame.cell_idx].name.clone();
                         self.emit_debug_event(DebugEvent::CallExit {
@@ -2565,7 +2748,7 @@ impl VM {
                         let f = self.frames.last().unwrap();
                         cell_idx = f.cell_idx;
                         base = f.base_register;
-                        ip = f.ip;
+                        site.ip = f.ip;
                         cell = &module.cells[cell_idx];
                         continue;
                     }
@@ -2577,7 +2760,7 @@ impl VM {
                     let f = self.frames.last().unwrap();
                     cell_idx = f.cell_idx;
                     base = f.base_register;
-                    ip = f.ip;
+                    site.ip = f.ip;
                     cell = &module.cells[cell_idx];
                 }
                 OpCode::Halt => {
@@ -2593,7 +2776,7 @@ impl VM {
                         let f = self.frames.last().unwrap();
                         cell_idx = f.cell_idx;
                         base = f.base_register;
-                        ip = f.ip;
+                        site.ip = f.ip;
                         cell = &module.cells[cell_idx];
                         continue;
                     }
@@ -2605,7 +2788,7 @@ impl VM {
                     if let Value::Int(ref mut n) = self.registers[base + a] {
                         *n -= 1;
                         if *n > 0 {
-                            ip = (ip as i32 + sb) as usize;
+                            site.ip = (site.ip as i32 + sb) as usize;
                         }
                     }
                 }
@@ -2619,7 +2802,7 @@ impl VM {
                         _ => 0,
                     };
                     if len == 0 {
-                        ip += bx;
+                        site.ip += bx;
                     }
                     self.registers[base + a + 1] = Value::Int(0);
                     self.registers[base + a + 2] = Value::Int(len as i64);
@@ -2640,7 +2823,7 @@ impl VM {
                         };
                         self.registers[base + a + 3] = elem;
                         self.registers[base + a + 1] = Value::Int(idx + 1);
-                        ip = (ip as i32 - bx as i32) as usize;
+                        site.ip = (site.ip as i32 - bx as i32) as usize;
                     }
                 }
                 OpCode::ForIn => {
@@ -2689,12 +2872,12 @@ impl VM {
                 OpCode::Break => {
                     // Jump to loop end (offset in Ax)
                     let offset = instr.sax_val();
-                    ip = (ip as i32 + offset) as usize;
+                    site.ip = (site.ip as i32 + offset) as usize;
                 }
                 OpCode::Continue => {
                     // Jump to loop start (offset in Ax)
                     let offset = instr.sax_val();
-                    ip = (ip as i32 + offset) as usize;
+                    site.ip = (site.ip as i32 + offset) as usize;
                 }
 
                 // Intrinsic is handled in the pre-match above
@@ -2768,6 +2951,7 @@ impl VM {
                     let tool_id = tool.tool_id.clone();
                     let tool_version = tool.version.clone();
                     let tool_alias = tool.alias.clone();
+                    self.check_deterministic_tool(&tool_id, &tool_alias)?;
                     let args_json = serde_json::Value::Object(args_map);
                     let policy = merged_policy_for_tool(module, &tool_alias);
                     if let Err(msg) = validate_tool_policy(&policy, &args_json) {
@@ -2789,19 +2973,36 @@ impl VM {
                     // ── Effect-budget enforcement (T158) ──
                     // Check budgets against both the tool alias and the tool_id
                     // prefix (e.g. "http" from "http.get") so callers can set
-                    // budgets at either granularity.
-                    for budget_key in [tool_alias.as_str(), tool_id.split('.').next().unwrap_or("")]
-                    {
-                        if let Some((remaining, limit)) = self.effect_budgets.get_mut(budget_key) {
-                            if *remaining == 0 {
-                                return Err(VmError::ToolError(format!(
-                                    "effect budget exceeded for '{}': limit {} reached",
-                                    budget_key, limit
-                                )));
+                    // budgets at either granularity. Every applicable budget
+                    // is checked before any is charged, so a rejected call
+                    // costs nothing.
+                    let prefix = tool_id.split('.').next().unwrap_or("");
+                    let budget_keys: &[&str] = if prefix == tool_alias {
+                        &[prefix]
+                    } else {
+                        &[tool_alias.as_str(), prefix]
+                    };
+                    let exceeded =
+                        budget_keys
+                            .iter()
+                            .find_map(|key| match self.effect_budgets.get(*key) {
+                                Some(&(0, limit)) => Some((key.to_string(), limit)),
+                                _ => None,
+                            });
+                    if exceeded.is_none() {
+                        for key in budget_keys {
+                            if let Some((remaining, _)) = self.effect_budgets.get_mut(*key) {
+                                *remaining -= 1;
                             }
-                            *remaining -= 1;
                         }
                     }
+                    if let Some((effect, limit)) = exceeded {
+                        if self.recoverable_budgets {
+                            self.registers[base + a] = self.budget_exceeded_value(&effect, limit);
+                            continue;
+                        }
+                        return Err(VmError::BudgetExceeded { effect, limit });
+                    }
 
                     let request = ToolRequest {
                         tool_id: tool_id.clone(),
@@ -2809,21 +3010,29 @@ impl VM {
                         args: args_json,
                         policy,
                     };
-                    if let Some(dispatcher) = self.tool_dispatcher.as_ref() {
-                        match dispatcher.dispatch(&request) {
-                            Ok(response) => {
-                                self.registers[base + a] = json_to_value(&response.outputs);
+                    let outcome = match self.seeded_tool_output(&tool_id, &request.args) {
+                        Some(seeded) => Some(seeded.map(|outputs| (outputs, 0))),
+                        None => self.tool_dispatcher.as_ref().map(|dispatcher| {
+                            dispatcher
+                                .dispatch(&request)
+                                .map(|response| (response.outputs, response.latency_ms))
+                                .map_err(|e| e.to_string())
+                        }),
+                    };
+                    if let Some(outcome) = outcome {
+                        match outcome {
+                            Ok((outputs, latency_ms)) => {
+                                self.registers[base + a] = json_to_value(&outputs);
                                 self.emit_debug_event(DebugEvent::ToolCall {
                                     cell_name: cell.name.clone(),
                                     tool_id,
                                     tool_version,
-                                    latency_ms: response.latency_ms,
+                                    latency_ms,
                                     success: true,
                                     message: None,
                                 });
                             }
-                            Err(e) => {
-                                let err_msg = e.to_string();
+                            Err(err_msg) => {
                                 self.emit_debug_event(DebugEvent::ToolCall {
                                     cell_name: cell.name.clone(),
                                     tool_id,
@@ -2901,7 +3110,7 @@ impl VM {
                     // Save the Await instruction's IP (rewound) so that when we
                     // return to this frame after running a deferred future, we
                     // re-execute the Await to check the result.
-                    let await_ip = ip - 1; // IP of the current Await instruction
+                    let await_ip = site.ip - 1; // IP of the current Await instruction
                     if let Some(f) = self.frames.last_mut() {
                         f.ip = await_ip;
                     }
@@ -2913,7 +3122,7 @@ impl VM {
                             // Advance IP past the Await instruction (already done by ip += 1)
                             // Restore the advanced IP in the frame
                             if let Some(f) = self.frames.last_mut() {
-                                f.ip = ip;
+                                f.ip = site.ip;
                             }
                         }
                         None => {
@@ -2929,7 +3138,7 @@ impl VM {
                             let f = self.frames.last().unwrap();
                             cell_idx = f.cell_idx;
                             base = f.base_register;
-                            ip = f.ip;
+                            site.ip = f.ip;
                             cell = &module.cells[cell_idx];
                             continue;
                         }
@@ -2938,7 +3147,7 @@ impl VM {
                 OpCode::Spawn => {
                     // Sync IP before spawn (which may push frames in Eager mode)
                     if let Some(f) = self.frames.last_mut() {
-                        f.ip = ip;
+                        f.ip = site.ip;
                     }
                     let bx = instr.bx() as usize;
                     self.registers[base + a] =
@@ -2947,7 +3156,7 @@ impl VM {
                     let f = self.frames.last().unwrap();
                     cell_idx = f.cell_idx;
                     base = f.base_register;
-                    ip = f.ip;
+                    site.ip = f.ip;
                     cell = &module.cells[cell_idx];
                 }
 
@@ -2976,7 +3185,7 @@ impl VM {
                         _ => false,
                     };
                     if matched {
-                        ip += 1;
+                        site.ip += 1;
                     }
                 }
                 OpCode::Unbox => {
@@ -2995,7 +3204,7 @@ impl VM {
                 OpCode::HandlePush => {
                     let meta_idx = a;
                     let offset = instr.bx() as usize;
-                    let handler_ip = ip.saturating_sub(1) + offset;
+                    let handler_ip = site.ip.saturating_sub(1) + offset;
 
                     let (eff_name, op_name) = if meta_idx < cell.effect_handler_metas.len() {
                         let meta = &cell.effect_handler_metas[meta_idx];
@@ -3012,10 +3221,22 @@ impl VM {
                         cell_idx,
                         effect_name: eff_name,
                         operation: op_name,
+                        continuation_depth: self.suspended_continuations.len(),
                     });
                 }
+                OpCode::HandlePop if instr.ax_val() == HANDLER_EXIT => {
+                    // A handler ran to the end of its clause without resuming:
+                    // its continuation (and any left above it) is dead.
+                    if let Some(depth) = self.suspended_continuations.last().map(|c| c.scope_depth)
+                    {
+                        self.suspended_continuations.truncate(depth);
+                    }
+                }
                 OpCode::HandlePop => {
-                    self.effect_handlers.pop();
+                    if let Some(scope) = self.effect_handlers.pop() {
+                        self.suspended_continuations
+                            .truncate(scope.continuation_depth);
+                    }
                 }
                 OpCode::Perform => {
                     // Use cached cell reference directly (no re-borrow needed)
@@ -3037,38 +3258,47 @@ impl VM {
                     };
 
                     // ── Effect-budget enforcement (T158) ──
-                    if let Err(msg) = self.check_effect_budget(&eff_name) {
-                        return Err(VmError::Runtime(format!(
-                            "effect budget exceeded for '{}.{}': {}",
-                            eff_name, op_name, msg
-                        )));
+                    if self.check_effect_budget(&eff_name).is_err() {
+                        let limit = self.effect_budgets[&eff_name].1;
+                        if self.recoverable_budgets {
+                            self.registers[base + a] = self.budget_exceeded_value(&eff_name, limit);
+                            continue;
+                        }
+                        return Err(VmError::BudgetExceeded {
+                            effect: eff_name,
+                            limit,
+                        });
                     }
 
                     // Search effect_handlers stack (top to bottom) for matching handler
-                    let handler_scope = self
-                        .effect_handlers
-                        .iter()
-                        .rev()
-                        .find(|scope| scope.effect_name == eff_name && scope.operation == op_name)
-                        .cloned();
-
-                    if let Some(scope) = handler_scope {
+                    let handler_pos = self.effect_handlers.iter().rposition(|scope| {
+                        scope.effect_name == eff_name && scope.operation == op_name
+                    });
+
+                    if let Some(pos) = handler_pos {
+                        let scope = self.effect_handlers[pos].clone();
                         // Sync IP back before saving continuation
                         if let Some(f) = self.frames.last_mut() {
-                            f.ip = ip;
+                            f.ip = site.ip;
                         }
                         // Save continuation: snapshot current execution state
                         let cont = SuspendedContinuation {
                             frames: self.frames.clone(),
                             registers: self.registers.clone(),
-                            resume_ip: ip,
+                            resume_ip: site.ip,
                             resume_frame_count: self.frames.len(),
                             result_reg: base + a,
+                            handlers: self.effect_handlers.clone(),
+                            scope_depth: scope.continuation_depth,
                         };
-                        self.suspended_continuation = Some(cont);
+                        self.suspended_continuations.push(cont);
 
-                        // Jump to handler code
-                        if let Some(f) = self.frames.get_mut(scope.frame_idx) {
+                        // The handler body runs in the frame that installed it, with
+                        // only the handlers outside it in scope, so a `perform` from
+                        // inside the handler reaches the next enclosing handler.
+                        self.effect_handlers.truncate(pos);
+                        self.frames.truncate(scope.frame_idx + 1);
+                        if let Some(f) = self.frames.last_mut() {
                             f.ip = scope.handler_ip;
                         }
 
@@ -3076,30 +3306,31 @@ impl VM {
                         let f = self.frames.last().unwrap();
                         cell_idx = f.cell_idx;
                         base = f.base_register;
-                        ip = f.ip;
+                        site.ip = f.ip;
                         cell = &module.cells[cell_idx];
                     } else {
-                        return Err(VmError::Runtime(format!(
-                            "unhandled effect: {}.{}",
-                            eff_name, op_name
-                        )));
+                        return Err(VmError::UnhandledEffect {
+                            effect: eff_name,
+                            operation: op_name,
+                        });
                     }
                 }
                 OpCode::Resume => {
-                    if let Some(cont) = self.suspended_continuation.take() {
+                    if let Some(cont) = self.suspended_continuations.pop() {
                         // The lowerer emits: Resume dest, val_reg, 0
                         // The resume value is in register B (val_reg), not A (dest).
                         let resume_value = self.registers[base + b].clone();
                         // Restore the suspended state
                         self.frames = cont.frames;
                         self.registers = cont.registers;
+                        self.effect_handlers = cont.handlers;
                         // Put the resume value into the result register
                         self.registers[cont.result_reg] = resume_value;
                         // Reload frame state after restoration
                         let f = self.frames.last().unwrap();
                         cell_idx = f.cell_idx;
                         base = f.base_register;
-                        ip = f.ip;
+                        site.ip = f.ip;
                         cell = &module.cells[cell_idx];
                     } else {
                         return Err(VmError::Runtime(
@@ -3107,6 +3338,13 @@ impl VM {
                         ));
                     }
                 }
+                OpCode::Extension => {
+                    // Extension opcodes only have lowerings registered with
+                    // the code generator; the interpreter can't run them.
+                    return Err(VmError::Runtime(format!(
+                        "extension opcode {b} is not supported by the interpreter"
+                    )));
+                }
             }
         }
     }
@@ -3139,9 +3377,7 @@ impl VM {
                     None
                 };
                 if let Some(idx) = idx_opt {
-                    if self.frames.len() >= MAX_CALL_DEPTH {
-                        return Err(VmError::StackOverflow(MAX_CALL_DEPTH));
-                    }
+                    self.check_call_depth()?;
                     let callee_cell = &module.cells[idx];
                     let num_regs = callee_cell.registers as usize;
                     let params: Vec<LirParam> = callee_cell.params.clone();
@@ -3155,6 +3391,7 @@ impl VM {
                         ip: 0,
                         return_register: base + a,
                         future_id: None,
+                        memo: None,
                     });
                     if self.debug_callback.is_some() {
                         self.emit_debug_event(DebugEvent::CallEnter {
@@ -3168,9 +3405,7 @@ impl VM {
                 }
             }
             Value::Closure(ref cv) => {
-                if self.frames.len() >= MAX_CALL_DEPTH {
-                    return Err(VmError::StackOverflow(MAX_CALL_DEPTH));
-                }
+                self.check_call_depth()?;
                 let cv = cv.clone();
                 let module = self.module.as_ref().ok_or(VmError::NoModule)?;
                 let callee_cell = &module.cells[cv.cell_idx];
@@ -3198,6 +3433,7 @@ impl VM {
                     ip: 0,
                     return_register: base + a,
                     future_id: None,
+                    memo: None,
                 });
                 if self.debug_callback.is_some() {
                     let module = self.module.as_ref().ok_or(VmError::NoModule)?;
@@ -3353,6 +3589,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3361,6 +3598,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         }
     }
 
@@ -3402,6 +3640,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3410,6 +3649,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         }
     }
 
@@ -3433,6 +3673,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3441,6 +3682,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         }
     }
 
@@ -3485,6 +3727,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3493,6 +3736,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -3544,6 +3788,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3552,6 +3797,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -3605,6 +3851,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3613,6 +3860,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -3644,6 +3892,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3652,6 +3901,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -3686,6 +3936,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3694,6 +3945,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -3724,6 +3976,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3732,6 +3985,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -3763,6 +4017,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3771,6 +4026,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -3802,6 +4058,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3810,6 +4067,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -3855,6 +4113,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3863,6 +4122,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -3902,6 +4162,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3910,6 +4171,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -3933,6 +4195,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_panic_during_dispatch_becomes_internal_error() {
+        let module = LirModule {
+            version: "1.0.0".into(),
+            doc_hash: "test".into(),
+            strings: vec![],
+            types: vec![],
+            cells: vec![
+                LirCell {
+                    name: "ok".into(),
+                    params: vec![],
+                    returns: None,
+                    registers: 4,
+                    constants: vec![Constant::Int(5)],
+                    instructions: vec![
+                        Instruction::abx(OpCode::LoadK, 0, 0),
+                        Instruction::abc(OpCode::Return, 0, 1, 0),
+                    ],
+                    effect_handler_metas: vec![],
+                    memoizable: false,
+                },
+                LirCell {
+                    name: "faulty".into(),
+                    params: vec![],
+                    returns: None,
+                    registers: 4,
+                    constants: vec![Constant::Int(5)],
+                    instructions: vec![
+                        Instruction::abx(OpCode::LoadK, 0, 0),
+                        Instruction::abc(OpCode::Add, 1, 0, 0),
+                        // Loads a constant the cell's pool doesn't have.
+                        Instruction::abx(OpCode::LoadK, 2, 7),
+                        Instruction::abc(OpCode::Return, 2, 1, 0),
+                    ],
+                    effect_handler_metas: vec![],
+                    memoizable: false,
+                },
+            ],
+            tools: vec![],
+            policies: vec![],
+            agents: vec![],
+            addons: vec![],
+            effects: vec![],
+            effect_binds: vec![],
+            handlers: vec![],
+            source_map: Vec::new(),
+        };
+
+        let mut vm = VM::new();
+        vm.load(module);
+
+        let err = vm.execute("faulty", vec![]).unwrap_err();
+        match err {
+            VmError::WithStackTrace { message, .. } => assert!(
+                message.starts_with(
+                    "internal error in cell 'faulty' at instruction 2: index out of bounds"
+                ),
+                "{}",
+                message
+            ),
+            other => panic!("expected internal error, got {:?}", other),
+        }
+
+        // The VM itself survives and can run another cell.
+        assert_eq!(vm.execute("ok", vec![]).unwrap(), Value::Int(5));
+    }
+
     #[test]
     fn test_debug_hooks_capture_call_exit() {
         use std::sync::{Arc, Mutex};
@@ -3955,6 +4284,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -3963,6 +4293,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -4004,6 +4335,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![LirTool {
                 alias: "HttpGet".into(),
@@ -4017,6 +4349,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut dispatcher = StubDispatcher::new();
@@ -4089,6 +4422,7 @@ mod tests {
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -4097,6 +4431,7 @@ mod tests {
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -4661,6 +4996,7 @@ end
                         Instruction::abc(OpCode::Return, 1, 1, 0),
                     ],
                     effect_handler_metas: vec![],
+                    memoizable: false,
                 },
                 LirCell {
                     name: "worker".into(),
@@ -4670,6 +5006,7 @@ end
                     constants: worker_consts,
                     instructions: worker_instrs,
                     effect_handler_metas: vec![],
+                    memoizable: false,
                 },
             ],
             tools: vec![],
@@ -4679,6 +5016,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         }
     }
 
@@ -4948,6 +5286,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -4956,6 +5295,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -4987,6 +5327,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -4995,6 +5336,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5026,6 +5368,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -5034,6 +5377,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5065,6 +5409,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -5073,6 +5418,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5104,6 +5450,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -5112,6 +5459,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5145,6 +5493,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -5153,6 +5502,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5185,6 +5535,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -5193,6 +5544,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5224,6 +5576,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -5232,6 +5585,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5245,19 +5599,26 @@ end
 
     #[test]
     fn test_stack_overflow_detection() {
-        // Verify MAX_CALL_DEPTH is enforced
+        // Verify the call depth limit is enforced
         let mut vm = VM::new();
         // Push frames up to the limit
-        for _ in 0..MAX_CALL_DEPTH {
+        for _ in 0..DEFAULT_MAX_CALL_DEPTH {
             vm.frames.push(CallFrame {
                 cell_idx: 0,
                 base_register: 0,
                 ip: 0,
                 return_register: 0,
                 future_id: None,
+                memo: None,
             });
         }
-        assert_eq!(vm.frames.len(), MAX_CALL_DEPTH);
+        assert_eq!(vm.frames.len(), DEFAULT_MAX_CALL_DEPTH);
+        assert!(matches!(
+            vm.check_call_depth(),
+            Err(VmError::StackOverflow {
+                depth: DEFAULT_MAX_CALL_DEPTH
+            })
+        ));
     }
 
     #[test]
@@ -5297,6 +5658,7 @@ end
                         Instruction::abc(OpCode::Return, 1, 1, 0),   // return result
                     ],
                     effect_handler_metas: vec![],
+                    memoizable: false,
                 },
                 LirCell {
                     name: "__closure_0".into(),
@@ -5309,6 +5671,7 @@ end
                         Instruction::abc(OpCode::Return, 0, 1, 0),   // return r0
                     ],
                     effect_handler_metas: vec![],
+                    memoizable: false,
                 },
             ],
             tools: vec![],
@@ -5318,6 +5681,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5352,6 +5716,7 @@ end
                         Instruction::abc(OpCode::Return, 2, 1, 0),    // return result
                     ],
                     effect_handler_metas: vec![],
+                    memoizable: false,
                 },
                 LirCell {
                     name: "__closure_1".into(),
@@ -5366,6 +5731,7 @@ end
                         Instruction::abc(OpCode::Return, 2, 1, 0),   // return 30
                     ],
                     effect_handler_metas: vec![],
+                    memoizable: false,
                 },
             ],
             tools: vec![],
@@ -5375,6 +5741,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -5454,6 +5821,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -5462,6 +5830,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -5482,6 +5851,7 @@ end
             ip: 0,
             return_register: 0,
             future_id: None,
+            memo: None,
         });
 
         let result = vm.run_until(0).unwrap();
@@ -6056,6 +6426,7 @@ end
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6064,6 +6435,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6097,6 +6469,7 @@ end
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6105,6 +6478,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6142,6 +6516,7 @@ end
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6150,6 +6525,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6186,6 +6562,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6194,6 +6571,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6223,6 +6601,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6231,6 +6610,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6260,6 +6640,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6268,6 +6649,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6297,6 +6679,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6305,6 +6688,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6334,6 +6718,7 @@ end
                     Instruction::abc(OpCode::Return, 2, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6342,6 +6727,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6388,6 +6774,7 @@ end
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6396,6 +6783,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6429,6 +6817,7 @@ end
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6437,6 +6826,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6462,6 +6852,7 @@ end
                 constants: vec![],
                 instructions: vec![Instruction::sax(OpCode::Jmp, -1)],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6470,6 +6861,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6545,6 +6937,7 @@ end
                 constants: vec![],
                 instructions: vec![Instruction::sax(OpCode::Jmp, -1)],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6553,6 +6946,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6587,6 +6981,7 @@ end
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6595,6 +6990,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6623,6 +7019,7 @@ end
                     Instruction::abc(OpCode::Return, 0, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6631,6 +7028,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6686,6 +7084,7 @@ end
                     param_count: 0,
                     handler_ip: 4,
                 }],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6694,6 +7093,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6716,12 +7116,56 @@ end
             cell_idx: 0,
             effect_name: "TestEffect".into(),
             operation: "test_op".into(),
+            continuation_depth: 0,
         });
         assert_eq!(vm.effect_handlers.len(), 1);
         vm.effect_handlers.pop();
         assert!(vm.effect_handlers.is_empty());
     }
 
+    #[test]
+    fn test_unresumed_handler_in_loop_keeps_continuations_bounded() {
+        let source = r#"
+effect Ask
+  cell value() -> Int
+end
+
+cell main() -> Int / {Ask}
+  let mut count = 0
+  let mut i = 0
+  while i < 200
+    let r = handle
+      perform Ask.value()
+    with
+      Ask.value() =>
+        0
+    end
+    let s = handle
+      perform Ask.value()
+    with
+      Ask.value() =>
+        resume(1)
+    end
+    count = count + s
+    i = i + 1
+  end
+  return count
+end
+"#;
+        let md = format!("# test\n\n```lumen\n{}\n```\n", source.trim());
+        let module = compile_lumen(&md).expect("source should compile");
+        let mut vm = VM::new();
+        vm.load(module);
+        let result = vm.execute("main", vec![]).expect("main should execute");
+        assert_eq!(result, Value::Int(200));
+        assert!(
+            vm.suspended_continuations.is_empty(),
+            "{} continuations left behind",
+            vm.suspended_continuations.len()
+        );
+        assert!(vm.effect_handlers.is_empty());
+    }
+
     #[test]
     fn test_perform_matches_correct_handler() {
         // Test that Perform finds the handler that matches effect_name + operation.
@@ -6773,6 +7217,7 @@ end
                     param_count: 1,
                     handler_ip: 5,
                 }],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6781,6 +7226,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6829,6 +7275,7 @@ end
                     param_count: 1,
                     handler_ip: 4,
                 }],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6837,6 +7284,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -6915,6 +7363,7 @@ end
                         handler_ip: 10,
                     },
                 ],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -6923,6 +7372,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut vm = VM::new();
@@ -7063,6 +7513,7 @@ end
                     Instruction::abc(OpCode::Return, 1, 1, 0), // return r1
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![],
             policies: vec![],
@@ -7071,6 +7522,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
         let mut vm = VM::new();
         vm.load(module);
@@ -7470,6 +7922,7 @@ end
                     Instruction::abc(OpCode::Return, 1, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![LirTool {
                 alias: "HttpGet".into(),
@@ -7483,6 +7936,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut dispatcher = StubDispatcher::new();
@@ -7530,6 +7984,7 @@ end
                     Instruction::abc(OpCode::Return, 1, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![LirTool {
                 alias: "MyHttp".into(),
@@ -7543,6 +7998,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut dispatcher = StubDispatcher::new();
@@ -7552,6 +8008,7 @@ end
         vm.tool_dispatcher = Some(Box::new(dispatcher));
         // Budget by tool_id prefix "http" — only 1 call allowed
         vm.set_effect_budget("http", 1);
+        vm.set_effect_budget("MyHttp", 5);
         vm.load(module);
 
         let err = vm
@@ -7559,10 +8016,12 @@ end
             .expect_err("second call should exceed http budget");
         let msg = err.to_string();
         assert!(
-            msg.contains("budget exceeded"),
+            msg.contains("budget exceeded") && msg.contains("'http'"),
             "expected budget error, got: {}",
             msg
         );
+        // Only the successful first call is charged to the alias budget.
+        assert_eq!(vm.effect_budgets["MyHttp"], (4, 5));
     }
 
     #[test]
@@ -7585,6 +8044,7 @@ end
                     Instruction::abc(OpCode::Return, 1, 1, 0),
                 ],
                 effect_handler_metas: vec![],
+                memoizable: false,
             }],
             tools: vec![LirTool {
                 alias: "HttpGet".into(),
@@ -7598,6 +8058,7 @@ end
             effects: vec![],
             effect_binds: vec![],
             handlers: vec![],
+            source_map: Vec::new(),
         };
 
         let mut dispatcher = StubDispatcher::new();
@@ -7613,4 +8074,249 @@ end
             .expect("2 calls within budget of 2 should succeed");
         assert_eq!(result, Value::String(StringRef::Owned("ok".into())));
     }
+
+    const BUDGETED_FETCH: &str = r#"
+use tool http.get as HttpGet
+bind effect http to HttpGet
+grant HttpGet
+
+cell fetch(url: String) -> String / {http}
+  let resp = HttpGet(url: url)
+  match resp
+    err(e) -> return "fallback:" + e.effect
+    _ -> return resp.body
+  end
+end
+
+cell main() -> String / {http}
+  let first = fetch("https://a.example.com")
+  let second = fetch("https://b.example.com")
+  return first + "," + second
+end
+"#;
+
+    fn run_budgeted_fetch(recoverable: bool) -> Result<Value, VmError> {
+        let md = format!("# test\n\n```lumen\n{}\n```\n", BUDGETED_FETCH.trim());
+        let module = compile_lumen(&md).expect("source should compile");
+        let mut dispatcher = StubDispatcher::new();
+        dispatcher.set_response("http.get", serde_json::json!({"body": "live"}));
+
+        let mut vm = VM::new();
+        vm.tool_dispatcher = Some(Box::new(dispatcher));
+        vm.set_effect_budget("http", 1);
+        vm.set_recoverable_budgets(recoverable);
+        vm.load(module);
+        vm.execute("main", vec![])
+    }
+
+    #[test]
+    fn test_recoverable_budget_exhaustion_takes_fallback_path() {
+        let result = run_budgeted_fetch(true).expect("budget error should be caught");
+        assert_eq!(
+            result,
+            Value::String(StringRef::Owned("live,fallback:http".into()))
+        );
+    }
+
+    #[test]
+    fn test_uncaught_budget_exhaustion_terminates_with_error() {
+        let err = run_budgeted_fetch(false).expect_err("exhausted budget should abort");
+        match err {
+            VmError::WithStackTrace {
+                message, frames, ..
+            } => {
+                assert_eq!(
+                    message,
+                    "effect budget exceeded for 'http': limit 1 reached"
+                );
+                assert_eq!(frames.last().map(|f| f.cell_name.as_str()), Some("fetch"));
+            }
+            other => panic!("expected budget error with stack trace, got: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_recoverable_budget_exhaustion_on_perform() {
+        let md = format!(
+            "# test\n\n```lumen\n{}\n```\n",
+            r#"
+effect Counter
+  cell next() -> Int
+end
+
+cell main() -> Int / {Counter}
+  let result = handle
+    let a = perform Counter.next()
+    let b = perform Counter.next()
+    match b
+      err(e) -> return a + e.limit
+      _ -> return a + b
+    end
+  with
+    Counter.next() =>
+      resume(10)
+  end
+  return result
+end
+"#
+            .trim()
+        );
+        let module = compile_lumen(&md).expect("source should compile");
+        let mut vm = VM::new();
+        vm.set_effect_budget("Counter", 1);
+        vm.set_recoverable_budgets(true);
+        vm.load(module);
+        let result = vm
+            .execute("main", vec![])
+            .expect("budget error should be caught");
+        assert_eq!(result, Value::Int(11));
+    }
+
+    // ── @pure memoization ──
+
+    /// Run `main` and count how many times the body of `cell_name` was entered.
+    fn run_counting_calls(source: &str, cell_name: &str, vm: &mut VM) -> (Value, usize) {
+        use std::sync::{Arc, Mutex};
+        let md = format!("# test\n\n```lumen\n{}\n```\n", source.trim());
+        let module = compile_lumen(&md).expect("source should compile");
+        let calls = Arc::new(Mutex::new(0usize));
+        let calls_clone = Arc::clone(&calls);
+        let target = cell_name.to_string();
+        vm.debug_callback = Some(Box::new(move |event| {
+            if matches!(event, DebugEvent::CallEnter { cell_name } if *cell_name == target) {
+                *calls_clone.lock().unwrap() += 1;
+            }
+        }));
+        vm.load(module);
+        let result = vm.execute("main", vec![]).expect("main should execute");
+        let count = *calls.lock().unwrap();
+        (result, count)
+    }
+
+    const PURE_FIB: &str = r#"
+@pure
+cell fib(n: Int) -> Int
+  if n < 2
+    return n
+  end
+  return fib(n - 1) + fib(n - 2)
+end
+
+cell main() -> Int
+  fib(20)
+end
+"#;
+
+    #[test]
+    fn test_pure_cell_computed_once_per_argument() {
+        let mut vm = VM::new();
+        let (result, calls) = run_counting_calls(PURE_FIB, "fib", &mut vm);
+        assert_eq!(result, Value::Int(6765));
+        // One body execution per distinct argument 0..=20.
+        assert_eq!(calls, 21);
+        let stats = vm.memo_stats();
+        assert_eq!(stats.misses, 21);
+        assert!(stats.hits > 0);
+    }
+
+    #[test]
+    fn test_memoization_can_be_disabled() {
+        let mut vm = VM::new();
+        vm.set_memoization(false);
+        let (result, calls) = run_counting_calls(PURE_FIB, "fib", &mut vm);
+        assert_eq!(result, Value::Int(6765));
+        // Naive recursion: fib(20) makes 21891 calls.
+        assert_eq!(calls, 21891);
+        assert_eq!(vm.memo_stats(), MemoStats::default());
+    }
+
+    #[test]
+    fn test_impure_cell_is_not_memoized() {
+        let source = r#"
+cell square(x: Int) -> Int
+  x * x
+end
+
+cell main() -> Int
+  square(3) + square(3)
+end
+"#;
+        let mut vm = VM::new();
+        let (result, calls) = run_counting_calls(source, "square", &mut vm);
+        assert_eq!(result, Value::Int(18));
+        assert_eq!(calls, 2);
+        assert_eq!(vm.memo_stats().misses, 0);
+    }
+
+    #[test]
+    fn test_memo_cache_is_bounded() {
+        let source = r#"
+@pure
+cell square(x: Int) -> Int
+  x * x
+end
+
+cell main() -> Int
+  let mut total = 0
+  for i in 0..100
+    total = total + square(i)
+  end
+  for i in 0..100
+    total = total + square(i)
+  end
+  total
+end
+"#;
+        let mut vm = VM::new();
+        vm.set_memo_config(MemoConfig {
+            capacity: 8,
+            ..MemoConfig::default()
+        });
+        let (result, calls) = run_counting_calls(source, "square", &mut vm);
+        assert_eq!(result, Value::Int(2 * 328350));
+        let stats = vm.memo_stats();
+        assert!(stats.entries <= 8, "cache grew to {}", stats.entries);
+        assert_eq!(stats.evictions, stats.misses - stats.entries as u64);
+        // With only 8 slots, the second sweep over 100 values misses again.
+        assert_eq!(calls, 200);
+    }
+
+    #[test]
+    fn test_memo_skips_calls_below_reduction_threshold() {
+        let source = r#"
+@pure
+cell double(x: Int) -> Int
+  x + x
+end
+
+@pure
+cell sum_to(n: Int) -> Int
+  let mut total = 0
+  for i in 0..n
+    total = total + i
+  end
+  total
+end
+
+cell main() -> Int
+  double(4) + double(4) + sum_to(200) + sum_to(200)
+end
+"#;
+        let mut vm = VM::new();
+        vm.set_memo_config(MemoConfig {
+            min_reductions: 50,
+            ..MemoConfig::default()
+        });
+        let (result, double_calls) = run_counting_calls(source, "double", &mut vm);
+        assert_eq!(result, Value::Int(16 + 2 * 19900));
+        // The cheap cell runs every time; the expensive one is answered from
+        // the cache on the second call.
+        assert_eq!(double_calls, 2);
+        let stats = vm.memo_stats();
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.below_threshold, 2);
+        assert_eq!(stats.entries, 1);
+        assert!(stats.reductions_saved >= 200, "{:?}", stats);
+    }
 }
@@ -224,6 +224,7 @@ impl VM {
             ip: 0,
             return_register: 0,
             future_id: None,
+            memo: None,
         });
 
         let result = self.run_until(0);
@@ -0,0 +1,89 @@
+//! Runaway recursion fails with a structured `StackOverflow` error once the
+//! configurable call depth limit is hit, and leaves the VM usable.
+
+use lumen_compiler::compile;
+use lumen_vm::values::Value;
+use lumen_vm::vm::VM;
+
+const SOURCE: &str = r#"
+cell forever(n: Int) -> Int
+  return 1 + forever(n + 1)
+end
+
+cell depth(n: Int) -> Int
+  if n == 0
+    return 0
+  end
+  return 1 + depth(n - 1)
+end
+
+cell main() -> Int
+  return forever(0)
+end
+"#;
+
+fn load(vm: &mut VM) {
+    let md = format!("# call-depth\n\n```lumen\n{}\n```\n", SOURCE.trim());
+    vm.load(compile(&md).expect("source should compile"));
+}
+
+#[test]
+fn infinite_recursion_is_a_stack_overflow_error() {
+    let mut vm = VM::new();
+    load(&mut vm);
+    let err = vm
+        .execute("main", vec![])
+        .expect_err("recursion never ends");
+    assert_eq!(
+        err.stack_overflow_depth(),
+        Some(vm.max_call_depth()),
+        "got: {err}"
+    );
+}
+
+#[test]
+fn bounded_recursion_under_the_default_limit_succeeds() {
+    let mut vm = VM::new();
+    load(&mut vm);
+    let result = vm.execute("depth", vec![Value::Int(4000)]).unwrap();
+    assert_eq!(result, Value::Int(4000));
+}
+
+#[test]
+fn limit_is_configurable() {
+    let mut vm = VM::new();
+    vm.set_max_call_depth(100);
+    load(&mut vm);
+    let result = vm.execute("depth", vec![Value::Int(90)]).unwrap();
+    assert_eq!(result, Value::Int(90));
+
+    let err = vm
+        .execute("depth", vec![Value::Int(200)])
+        .expect_err("200 frames exceed the limit");
+    assert_eq!(err.stack_overflow_depth(), Some(100), "got: {err}");
+}
+
+#[test]
+fn vm_is_reusable_after_an_overflow() {
+    let mut vm = VM::new();
+    load(&mut vm);
+    assert!(vm.execute("main", vec![]).is_err());
+    let result = vm.execute("depth", vec![Value::Int(10)]).unwrap();
+    assert_eq!(result, Value::Int(10));
+}
+
+#[test]
+fn jit_compiled_recursion_is_a_stack_overflow_error() {
+    let mut vm = VM::new();
+    vm.enable_jit(0);
+    vm.set_max_call_depth(1000);
+    load(&mut vm);
+    let result = vm.execute("depth", vec![Value::Int(500)]).unwrap();
+    assert_eq!(result, Value::Int(500));
+
+    let err = vm
+        .execute("forever", vec![Value::Int(0)])
+        .expect_err("recursion never ends");
+    assert_eq!(err.stack_overflow_depth(), Some(1000), "got: {err}");
+    assert!(vm.jit_stats().cells_compiled > 0);
+}
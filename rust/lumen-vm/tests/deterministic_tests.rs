@@ -0,0 +1,165 @@
+//! Deterministic mode: the same program and seed produce the same output and
+//! the same execution trace, run after run.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use lumen_compiler::compile;
+use lumen_vm::values::Value;
+use lumen_vm::vm::{FutureSchedule, VM};
+
+const PROGRAM: &str = r#"
+cell roll() -> Int
+  return random_int(1, 1000)
+end
+
+cell main() -> String
+  let id = uuid()
+  let f = random()
+  let scores = {"b": roll(), "a": roll(), "c": roll()}
+  for entry in scores
+    print(entry)
+  end
+  print(f)
+  return id
+end
+"#;
+
+struct Run {
+    result: Value,
+    output: Vec<String>,
+    trace: Vec<String>,
+}
+
+fn run(source: &str, seed: u64) -> Run {
+    let md = format!("# deterministic\n\n```lumen\n{}\n```\n", source.trim());
+    let module = compile(&md).expect("source should compile");
+    let trace = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&trace);
+    let mut vm = VM::new();
+    vm.set_deterministic(seed);
+    vm.debug_callback = Some(Box::new(move |event| {
+        sink.borrow_mut().push(format!("{:?}", event));
+    }));
+    vm.load(module);
+    let result = vm.execute("main", vec![]).expect("program should run");
+    let trace = trace.borrow().clone();
+    Run {
+        result,
+        output: vm.output.clone(),
+        trace,
+    }
+}
+
+#[test]
+fn same_seed_reproduces_output_and_trace() {
+    let first = run(PROGRAM, 42);
+    let second = run(PROGRAM, 42);
+    assert_eq!(first.result, second.result);
+    assert_eq!(first.output.len(), 4);
+    assert_eq!(first.output, second.output);
+    assert!(!first.trace.is_empty());
+    assert_eq!(first.trace, second.trace);
+}
+
+#[test]
+fn different_seeds_give_different_values() {
+    assert_ne!(run(PROGRAM, 1).result, run(PROGRAM, 2).result);
+}
+
+#[test]
+fn seeded_uuid_is_a_valid_v4_uuid() {
+    let id = uuid::Uuid::parse_str(&run(PROGRAM, 7).result.as_string()).unwrap();
+    assert_eq!(id.get_version_num(), 4);
+}
+
+#[test]
+fn wall_clock_reads_are_rejected() {
+    let md = "# clock\n\n```lumen\ncell main() -> Float\n  return timestamp()\nend\n```\n";
+    let mut vm = VM::new();
+    vm.set_deterministic(0);
+    vm.load(compile(md).unwrap());
+    let err = vm.execute("main", vec![]).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unavailable in deterministic mode"),
+        "got: {err}"
+    );
+}
+
+#[test]
+fn deterministic_mode_schedules_futures_in_spawn_order() {
+    let mut vm = VM::new();
+    vm.set_deterministic(0);
+    assert!(vm.is_deterministic());
+    assert_eq!(vm.future_schedule(), FutureSchedule::DeferredFifo);
+}
+
+const TOOLS: &str = r#"
+use tool crypto.uuid as NewId
+use tool crypto.random_int as Roll
+use tool crypto.sha256 as Digest
+use tool http.get as HttpGet
+bind effect crypto to NewId
+bind effect http to HttpGet
+grant NewId
+grant Roll
+grant Digest
+grant HttpGet
+"#;
+
+fn run_tools(body: &str, seed: u64) -> Result<Value, lumen_vm::vm::VmError> {
+    let md = format!("# tools\n\n```lumen\n{}\n{}\n```\n", TOOLS, body.trim());
+    let mut dispatcher = lumen_runtime::tools::StubDispatcher::new();
+    dispatcher.set_response("crypto.sha256", serde_json::json!("sha256:stub"));
+    dispatcher.set_response("http.get", serde_json::json!({"body": "ok"}));
+    let mut vm = VM::new();
+    vm.set_deterministic(seed);
+    vm.tool_dispatcher = Some(Box::new(dispatcher));
+    vm.load(compile(&md).expect("source should compile"));
+    vm.execute("main", vec![])
+}
+
+#[test]
+fn seeded_tools_draw_from_the_seeded_rng() {
+    let body = r#"
+cell main() -> list[Any] / {crypto, external}
+  return [NewId(), Roll(min: 1, max: 6), Roll(min: 1, max: 1000000)]
+end
+"#;
+    let first = run_tools(body, 42).expect("seeded tools run");
+    assert_eq!(first, run_tools(body, 42).unwrap());
+    assert_ne!(first, run_tools(body, 43).unwrap());
+    let Value::List(items) = first else {
+        panic!("expected a list, got {:?}", first);
+    };
+    let id = uuid::Uuid::parse_str(&items[0].as_string()).unwrap();
+    assert_eq!(id.get_version_num(), 4);
+    assert!(matches!(items[1], Value::Int(1..=6)), "{:?}", items[1]);
+}
+
+#[test]
+fn pure_tools_still_dispatch() {
+    let body = r#"
+cell main() -> String / {external}
+  return Digest(input: "abc")
+end
+"#;
+    assert_eq!(run_tools(body, 0).unwrap().as_string(), "sha256:stub");
+}
+
+#[test]
+fn nondeterministic_tools_are_rejected() {
+    let body = r#"
+cell main() -> String / {http}
+  let resp = HttpGet(url: "https://api.example.com")
+  return resp.body
+end
+"#;
+    let err = run_tools(body, 0).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("tool 'HttpGet' is nondeterministic"),
+        "got: {err}"
+    );
+}
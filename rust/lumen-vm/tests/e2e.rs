@@ -2491,6 +2491,119 @@ end
     }
 }
 
+#[test]
+fn e2e_effect_perform_in_called_cell() {
+    // The handler is in dynamic scope: a cell called from the handled body performs
+    let result = run_main(
+        r#"
+effect Ask
+  cell ask(prompt: String) -> Int
+end
+
+cell helper() -> Int / {Ask}
+  let x = perform Ask.ask("a")
+  return x + 1
+end
+
+cell main() -> Int / {Ask}
+  let result = handle
+    helper()
+  with
+    Ask.ask(prompt) =>
+      resume(41)
+  end
+  return result
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(42));
+}
+
+#[test]
+fn e2e_effect_nested_handler_shadows_outer() {
+    // The inner handler wins inside its body; the outer one applies after it
+    let result = run_main(
+        r#"
+effect Ask
+  cell ask(prompt: String) -> Int
+end
+
+cell main() -> Int / {Ask}
+  let result = handle
+    let inner = handle
+      perform Ask.ask("a")
+    with
+      Ask.ask(prompt) =>
+        resume(1)
+    end
+    inner + perform Ask.ask("b")
+  with
+    Ask.ask(prompt) =>
+      resume(100)
+  end
+  return result
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(101));
+}
+
+#[test]
+fn e2e_effect_handler_performs_to_outer_handler() {
+    // A perform inside a handler body goes to the next enclosing handler
+    let result = run_main(
+        r#"
+effect Ask
+  cell ask(prompt: String) -> Int
+end
+
+cell main() -> Int / {Ask}
+  let result = handle
+    handle
+      perform Ask.ask("a")
+    with
+      Ask.ask(prompt) =>
+        resume(perform Ask.ask("outer") + 1)
+    end
+  with
+    Ask.ask(prompt) =>
+      resume(10)
+  end
+  return result
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(11));
+}
+
+#[test]
+fn e2e_effect_unhandled_operation_errors() {
+    let md = format!(
+        "# e2e-test\n\n```lumen\n{}\n```\n",
+        r#"
+effect Ask
+  cell ask(prompt: String) -> Int
+end
+
+cell main() -> Int / {Ask}
+  return perform Ask.ask("a")
+end
+"#
+        .trim()
+    );
+    let module = compile(&md).expect("source should compile");
+    let mut vm = VM::new();
+    vm.load(module);
+    let err = vm
+        .execute("main", vec![])
+        .expect_err("perform without a handler should fail");
+    assert!(
+        err.to_string().contains("unhandled effect: Ask.ask"),
+        "unexpected error: {}",
+        err
+    );
+}
+
 // ─── For-loop continue (T199) ───
 
 /// Basic continue in a for-loop: skip element 2, collect the rest.
@@ -2812,3 +2925,135 @@ end
     );
     assert_eq!(result, Value::Int(60));
 }
+
+#[test]
+fn e2e_method_call_desugars_to_cell_call() {
+    let result = run_main(
+        r#"
+cell add(a: Int, b: Int) -> Int
+  return a + b
+end
+
+cell main() -> Int
+  let xs = [1, 2, 3]
+  let n = xs.len()
+  return n.add(6)
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(9));
+}
+
+#[test]
+fn e2e_match_guard_falls_through_when_false() {
+    let result = run_main(
+        r#"
+enum Reading
+  Temp(Int)
+  Missing
+end
+
+cell describe(r: Reading) -> String
+  match r
+    Temp(t) when t > 30 -> return "hot"
+    Temp(t) where t < 0 -> return "freezing"
+    Temp(_) -> return "mild"
+    Missing -> return "none"
+  end
+end
+
+cell main() -> String
+  return describe(Temp(35)) ++ "," ++ describe(Temp(-5)) ++ "," ++ describe(Temp(20)) ++ "," ++ describe(Missing)
+end
+"#,
+    );
+    match result {
+        Value::String(StringRef::Owned(s)) => assert_eq!(s, "hot,freezing,mild,none"),
+        other => panic!("expected string, got {:?}", other),
+    }
+}
+
+// ─── Destructuring bindings ───
+
+#[test]
+fn e2e_let_tuple_destructuring() {
+    let result = run_main(
+        r#"
+cell main() -> Int
+  let pair = (3, 4)
+  let (a, b) = pair
+  let ((c, d), e) = ((5, 6), 7)
+  return a * 10000 + b * 1000 + c * 100 + d * 10 + e
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(34567));
+}
+
+#[test]
+fn e2e_let_record_destructuring() {
+    let result = run_main(
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+cell main() -> Int
+  let p = Point(x: 3, y: 4)
+  let Point(x, y) = p
+  let Point(x: px, y: _) = p
+  return x * 100 + y * 10 + px
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(343));
+}
+
+#[test]
+fn e2e_nested_destructuring_in_match_arm() {
+    let result = run_main(
+        r#"
+record Point
+  x: Int
+  y: Int
+end
+
+enum Shape
+  Dot(Point)
+  Segment((Point, Point))
+end
+
+cell describe(s: Shape) -> Int
+  match s
+    Dot(Point(x, y)) -> return x + y
+    Segment((Point(x: x1, y: y1), Point(x2, y2))) -> return x1 * 1000 + y1 * 100 + x2 * 10 + y2
+  end
+end
+
+cell main() -> Int
+  return describe(Segment((Point(x: 1, y: 2), Point(x: 3, y: 4)))) + describe(Dot(Point(x: 10, y: 20))) * 10000
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(301234));
+}
+
+// ─── Inferred return types ───
+
+#[test]
+fn e2e_unannotated_cell_returns_its_tail_expression() {
+    let result = run_main(
+        r#"
+cell double(x: Int)
+  x + x
+end
+
+cell main() -> Int
+  let y: Int = double(4)
+  return y
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(8));
+}
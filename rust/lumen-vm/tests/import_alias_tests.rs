@@ -0,0 +1,161 @@
+//! Calls through import aliases reach the imported cell at runtime.
+
+use lumen_compiler::compile_with_imports;
+use lumen_vm::values::Value;
+use lumen_vm::vm::VM;
+
+const UTIL: &str = r#"
+```lumen
+cell double(x: Int) -> Int
+  return x * 2
+end
+
+cell add(a: Int, b: Int) -> Int
+  return a + b
+end
+```
+"#;
+
+/// `LIB2` and `LIB3` both define `helper`, with different results.
+const LIB2: &str = r#"
+```lumen
+cell helper(x: Int) -> Int
+  return x + 1
+end
+
+cell api(x: Int) -> Int
+  return helper(x)
+end
+```
+"#;
+
+const LIB3: &str = r#"
+```lumen
+cell helper(x: Int) -> Int
+  return x + 1000
+end
+
+cell api2(x: Int) -> Int
+  return helper(x)
+end
+```
+"#;
+
+fn run_main(source: &str) -> Value {
+    let md = format!("# imports\n\n```lumen\n{}\n```\n", source.trim());
+    let module = compile_with_imports(&md, &|module| match module {
+        "util" => Some(UTIL.to_string()),
+        "lib2" => Some(LIB2.to_string()),
+        "lib3" => Some(LIB3.to_string()),
+        _ => None,
+    })
+    .expect("source should compile");
+    let mut vm = VM::new();
+    vm.load(module);
+    vm.execute("main", vec![]).expect("main should execute")
+}
+
+#[test]
+fn module_alias_call_runs_the_imported_cell() {
+    let result = run_main(
+        r#"
+import util as u
+
+cell main() -> Int
+  return u.double(21)
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(42));
+}
+
+#[test]
+fn module_alias_call_in_expression_position() {
+    let result = run_main(
+        r#"
+import util as u: add
+
+cell main() -> Int
+  let n = u.add(1, 2)
+  return n + u.add(n, 10)
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(16));
+}
+
+#[test]
+fn renamed_import_call_runs_the_imported_cell() {
+    let result = run_main(
+        r#"
+import util: double as twice
+
+cell main() -> Int
+  return twice(4)
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(8));
+}
+
+#[test]
+fn module_alias_cell_as_a_value() {
+    let result = run_main(
+        r#"
+import util as u
+
+cell apply(f: fn(Int) -> Int, x: Int) -> Int
+  return f(x)
+end
+
+cell main() -> Int
+  return apply(u.double, 5)
+end
+"#,
+    );
+    assert_eq!(result, Value::Int(10));
+}
+
+#[test]
+fn aliased_modules_keep_their_own_same_named_cells() {
+    let result = run_main(
+        r#"
+import lib2 as a
+import lib3 as b
+
+cell main() -> list[Int]
+  return [a.api(1), b.api2(1), a.helper(1), b.helper(1)]
+end
+"#,
+    );
+    assert_eq!(
+        result,
+        Value::new_list(vec![
+            Value::Int(2),
+            Value::Int(1001),
+            Value::Int(2),
+            Value::Int(1001)
+        ])
+    );
+}
+
+#[test]
+fn importer_cell_does_not_replace_a_module_cell_of_the_same_name() {
+    let result = run_main(
+        r#"
+import lib2 as l
+
+cell helper(x: Int) -> Int
+  return 100
+end
+
+cell main() -> list[Int]
+  return [l.api(1), helper(1)]
+end
+"#,
+    );
+    assert_eq!(
+        result,
+        Value::new_list(vec![Value::Int(2), Value::Int(100)])
+    );
+}
@@ -0,0 +1,46 @@
+//! A module's private cells stay bound to the module at runtime.
+
+use lumen_compiler::compile_with_imports;
+use lumen_vm::values::Value;
+use lumen_vm::vm::VM;
+
+const LIB: &str = r#"
+```lumen
+cell helper(x: Int) -> Int
+  return x + 1
+end
+
+pub cell api(x: Int) -> Int
+  return helper(x)
+end
+```
+"#;
+
+#[test]
+fn importer_cell_does_not_replace_a_private_cell_of_the_same_name() {
+    let source = r#"
+```lumen
+import lib: *
+
+cell helper(x: Int) -> Int
+  return 100
+end
+
+cell main() -> list[Int]
+  return [api(1), helper(1)]
+end
+```
+"#;
+    let module = compile_with_imports(source, &|module| match module {
+        "lib" => Some(LIB.to_string()),
+        _ => None,
+    })
+    .expect("a private helper doesn't clash with the importer's");
+    let mut vm = VM::new();
+    vm.load(module);
+    let result = vm.execute("main", vec![]).expect("main should execute");
+    assert_eq!(
+        result,
+        Value::new_list(vec![Value::Int(2), Value::Int(100)])
+    );
+}
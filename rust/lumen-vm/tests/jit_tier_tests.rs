@@ -0,0 +1,144 @@
+//! Tiered JIT thresholds configured through `JitConfig`.
+
+use lumen_compiler::compile;
+use lumen_vm::jit_tier::{JitConfig, JitOptLevel, JitTier, JitTierConfig, JitTierStats};
+use lumen_vm::values::Value;
+use lumen_vm::vm::VM;
+
+const HOT_LOOP: &str = r#"
+cell inc(n: Int) -> Int
+  return n + 1
+end
+
+cell count_to(limit: Int) -> Int
+  let mut i = 0
+  while i < limit
+    i = inc(i)
+  end
+  return i
+end
+"#;
+
+/// Run `count_to(calls)` under `config`, returning the JIT statistics.
+fn run_hot_loop(config: JitConfig, calls: i64) -> JitTierStats {
+    let md = format!("# jit-tier\n\n```lumen\n{}\n```\n", HOT_LOOP.trim());
+    let module = compile(&md).expect("source should compile");
+    let mut vm = VM::with_jit_config(config);
+    vm.load(module);
+    let result = vm
+        .execute("count_to", vec![Value::Int(calls)])
+        .expect("count_to should execute");
+    assert_eq!(result, Value::Int(calls));
+    vm.jit_stats()
+}
+
+#[test]
+fn disabled_jit_never_compiles_a_hot_loop() {
+    let stats = run_hot_loop(
+        JitConfig {
+            hot_threshold: 0,
+            tier_up_threshold: 0,
+            disable: true,
+        },
+        500,
+    );
+    assert_eq!(stats.cells_compiled, 0);
+    assert_eq!(stats.total_calls_tracked, 0);
+    assert_eq!(stats.jit_executions, 0);
+}
+
+#[test]
+fn lower_hot_threshold_promotes_sooner() {
+    let eager = JitConfig {
+        hot_threshold: 2,
+        tier_up_threshold: 2,
+        disable: false,
+    };
+    let lazy = JitConfig {
+        hot_threshold: 100,
+        tier_up_threshold: 100,
+        disable: false,
+    };
+    assert!(run_hot_loop(eager, 5).cells_compiled > 0);
+    assert_eq!(run_hot_loop(lazy, 5).cells_compiled, 0);
+    assert!(run_hot_loop(lazy, 150).cells_compiled > 0);
+}
+
+#[test]
+fn hot_cells_tier_up_after_the_second_threshold() {
+    let config = JitConfig {
+        hot_threshold: 2,
+        tier_up_threshold: 20,
+        disable: false,
+    };
+    assert_eq!(run_hot_loop(config, 10).tier_ups, 0);
+    let stats = run_hot_loop(config, 50);
+    assert!(stats.cells_compiled > 0);
+    assert_eq!(stats.tier_ups, 1);
+}
+
+#[test]
+fn default_config_keeps_a_single_tier() {
+    let config = JitConfig::default();
+    assert!(!config.disable);
+    assert_eq!(config.hot_threshold, config.tier_up_threshold);
+    assert_eq!(run_hot_loop(config, 50).tier_ups, 0);
+}
+
+#[test]
+fn cells_hot_after_tier_up_keep_optimised_code() {
+    // `is_even`/`is_odd` tail-call each other, so the JIT leaves them to the
+    // interpreter and their getting hot recompiles the module after `inc`
+    // has already tiered up.
+    let md = r#"# jit-tier
+
+```lumen
+cell inc(n: Int) -> Int
+  return n + 1
+end
+
+cell is_even(n: Int) -> Bool
+  if n == 0
+    return true
+  end
+  return is_odd(n - 1)
+end
+
+cell is_odd(n: Int) -> Bool
+  if n == 0
+    return false
+  end
+  return is_even(n - 1)
+end
+```
+"#;
+    let module = compile(md).expect("source should compile");
+    let index = |name: &str| module.cells.iter().position(|c| c.name == name).unwrap();
+    let (inc, is_even) = (index("inc"), index("is_even"));
+
+    let mut tier = JitTier::new(JitTierConfig {
+        hot_threshold: 2,
+        tier_up_threshold: 5,
+        opt_level: JitOptLevel::Speed,
+        enabled: true,
+    });
+    tier.init_for_module(module.cells.len());
+    for _ in 0..10 {
+        tier.prepare_call(inc, &module);
+    }
+    assert!(tier.is_compiled(inc));
+    assert!(tier.is_optimized());
+    assert_eq!(tier.tier_stats().tier_ups, 1);
+    assert!(!tier.is_compiled(is_even));
+
+    for _ in 0..10 {
+        assert!(!tier.prepare_call(is_even, &module));
+    }
+    assert!(
+        tier.is_optimized(),
+        "a newly hot cell de-optimised the module"
+    );
+    assert!(tier.is_compiled(inc));
+    assert_eq!(tier.tier_stats().tier_ups, 1);
+    assert!(!tier.is_compiled(is_even));
+}
@@ -0,0 +1,114 @@
+//! Tail calls reuse the caller's frame, so tail recursion runs in constant
+//! stack space — including implicit returns and mutual recursion.
+
+use lumen_compiler::compile;
+use lumen_vm::jit_tier::JitConfig;
+use lumen_vm::values::Value;
+use lumen_vm::vm::{VmError, VM};
+
+fn run_main(vm: &mut VM, source: &str) -> Result<Value, VmError> {
+    let md = format!("# tail-calls\n\n```lumen\n{}\n```\n", source.trim());
+    let module = compile(&md).expect("source should compile");
+    vm.load(module);
+    vm.execute("main", vec![])
+}
+
+const COUNTER: &str = r#"
+cell count(n: Int, acc: Int) -> Int
+  if n == 0
+    acc
+  else
+    count(n - 1, acc + 1)
+  end
+end
+
+cell main() -> Int
+  count(2000000, 0)
+end
+"#;
+
+const MUTUAL: &str = r#"
+cell is_even(n: Int) -> Bool
+  if n == 0
+    return true
+  end
+  return is_odd(n - 1)
+end
+
+cell is_odd(n: Int) -> Bool
+  if n == 0
+    return false
+  end
+  return is_even(n - 1)
+end
+
+cell main() -> Bool
+  return is_even(1000001)
+end
+"#;
+
+#[test]
+fn tail_recursive_counter_runs_millions_of_iterations() {
+    let result = run_main(&mut VM::new(), COUNTER).expect("counter should not overflow");
+    assert_eq!(result, Value::Int(2_000_000));
+}
+
+#[test]
+fn mutual_tail_recursion_reuses_the_frame() {
+    let result = run_main(&mut VM::new(), MUTUAL).expect("mutual recursion should not overflow");
+    assert_eq!(result, Value::Bool(false));
+}
+
+#[test]
+fn returning_a_bound_call_result_is_a_tail_call() {
+    let result = run_main(
+        &mut VM::new(),
+        r#"
+cell count(n: Int) -> Int
+  if n == 0
+    return 0
+  end
+  let rest = count(n - 1)
+  return rest
+end
+
+cell main() -> Int
+  return count(100000)
+end
+"#,
+    )
+    .expect("bound tail call should not overflow");
+    assert_eq!(result, Value::Int(0));
+}
+
+#[test]
+fn non_tail_call_still_grows_the_stack() {
+    let err = run_main(
+        &mut VM::new(),
+        r#"
+cell depth(n: Int) -> Int
+  if n == 0
+    return 0
+  end
+  return 1 + depth(n - 1)
+end
+
+cell main() -> Int
+  return depth(100000)
+end
+"#,
+    )
+    .expect_err("non-tail recursion should overflow");
+    assert!(err.to_string().contains("stack overflow"), "got: {err}");
+}
+
+#[test]
+fn jit_leaves_mutual_tail_recursion_to_the_interpreter() {
+    let mut vm = VM::with_jit_config(JitConfig {
+        hot_threshold: 1,
+        tier_up_threshold: 1,
+        disable: false,
+    });
+    let result = run_main(&mut vm, MUTUAL).expect("mutual recursion should not overflow");
+    assert_eq!(result, Value::Bool(false));
+}
@@ -0,0 +1,89 @@
+//! `VM::try_load` rejects malformed modules before they run.
+
+use lumen_compiler::compiler::lir::{Instruction, LirCell, LirModule, OpCode};
+use lumen_vm::values::Value;
+use lumen_vm::verify::VerifyError;
+use lumen_vm::vm::VM;
+
+fn malformed() -> LirModule {
+    let mut module = LirModule::new("malformed".into());
+    module.cells.push(LirCell {
+        name: "main".into(),
+        params: Vec::new(),
+        returns: Some("Int".into()),
+        registers: 2,
+        constants: Vec::new(),
+        instructions: vec![
+            Instruction::abc(OpCode::LoadInt, 0, 0, 7),
+            Instruction::sax(OpCode::Jmp, -5),
+            Instruction::abc(OpCode::Move, 1, 9, 0),
+            Instruction::abc(OpCode::Return, 0, 1, 0),
+        ],
+        effect_handler_metas: Vec::new(),
+        memoizable: false,
+    });
+    module
+}
+
+#[test]
+fn try_load_reports_every_problem() {
+    let mut vm = VM::new();
+    let errors = vm.try_load(malformed()).unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            VerifyError::JumpOutOfRange {
+                cell: "main".into(),
+                pc: 1,
+                target: -3,
+                len: 4,
+            },
+            VerifyError::RegisterOutOfRange {
+                cell: "main".into(),
+                pc: 2,
+                register: 9,
+                registers: 2,
+            },
+        ]
+    );
+    assert!(matches!(
+        vm.execute("main", vec![]),
+        Err(lumen_vm::vm::VmError::NoModule)
+    ));
+}
+
+#[test]
+fn compiled_modules_load() {
+    let md = "# ok\n\n```lumen\ncell main() -> Int\n  return 40 + 2\nend\n```\n";
+    let mut vm = VM::new();
+    vm.try_load(lumen_compiler::compile(md).unwrap()).unwrap();
+    assert_eq!(vm.execute("main", vec![]).unwrap(), Value::Int(42));
+}
+
+#[test]
+fn verification_can_be_turned_off() {
+    let mut vm = VM::new();
+    vm.set_verify_on_load(false);
+    assert!(vm.try_load(malformed()).is_ok());
+}
+
+#[test]
+fn compiled_examples_pass_verification() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut verified = 0;
+    for entry in std::fs::read_dir(&dir).expect("examples dir") {
+        let path = entry.unwrap().path();
+        if !path.to_string_lossy().ends_with(".lm.md") {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        let Ok(module) = lumen_compiler::compile(&source) else {
+            continue;
+        };
+        if let Err(errors) = lumen_vm::verify::verify(&module) {
+            panic!("{}: {:?}", path.display(), errors);
+        }
+        verified += 1;
+    }
+    assert!(verified >= 10, "only {} examples verified", verified);
+}